@@ -1,6 +1,7 @@
 use man::prelude::*;
 use std::path::Path;
 
+#[allow(clippy::too_many_lines)] // one declarative builder chain
 fn main() {
     let page = Manual::new("batdoc")
         .about("cat(1) for doc, docx, xls, xlsx, pptx, and pdf — renders to markdown with bat")
@@ -17,6 +18,145 @@ fn main() {
                 .long("--markdown")
                 .help("Output as markdown (default when terminal detected)."),
         )
+        .option(Opt::new("FORMAT").long("--to").help(
+            "Output format: markdown, plain, org, csv, or xml. Org-mode output uses \
+                     * headings, | tables, and [[url][text]] links. XML output \
+                     serializes the parsed headings, paragraphs, list items, \
+                     tables, slides, and pages, with .docx text colors and \
+                     highlights as <span> elements. -p and -m are shorthands \
+                     for --to plain and --to markdown.",
+        ))
+        .option(Opt::new("FMT").long("--format").help(
+            "Parse every input as FMT (doc, docx, xls, xlsx, pptx, or pdf) \
+                     instead of detecting the format from its signature. For \
+                     files whose header is missing or damaged.",
+        ))
+        .option(Opt::new("EXT").long("--assume-ext").help(
+            "When an input's signature is not recognized, it is parsed according \
+                     to its file extension. EXT (such as docx or .xls) supplies the \
+                     extension for stdin and for files without a recognized one.",
+        ))
+        .flag(Flag::new().long("--csv").help(
+            "Write .xls/.xlsx cell values as CSV (same as --to csv). A \
+                     single-sheet workbook is written to stdout; use --csv-dir \
+                     or --sheet for workbooks with several sheets.",
+        ))
+        .flag(Flag::new().long("--summary").help(
+            "Print a triage view of each document instead of its text: the \
+                     metadata, an outline of the headings, an inventory of tables \
+                     and figure captions, and the first words of each section, \
+                     slide, or page, as markdown.",
+        ))
+        .flag(Flag::new().long("--metadata").help(
+            "Print only the document properties, one \"Label: value\" line \
+                     each: title, author, company, dates, application, page and \
+                     word counts, and custom properties. Read from the OOXML \
+                     docProps parts, the OLE2 summary information streams, or a \
+                     PDF's Info dictionary and XMP metadata, then an .xlsx \
+                     workbook's defined names and data validation lists.",
+        ))
+        .flag(Flag::new().long("--stats").help(
+            "Print a table of counts instead of the text: words, characters, \
+                     paragraphs, tables, and slides, sheets, or pages, one row per \
+                     document and a total row for several. Counted from the parsed \
+                     document; always written to stdout.",
+        ))
+        .option(Opt::new("PATTERN").long("--grep").help(
+            "Print the lines of text and table rows containing PATTERN, a \
+                     literal, case-sensitive string, as FILE:LOCATION: LINE, where \
+                     the location is the page, slide, or sheet and row. Matches are \
+                     highlighted on a terminal unless NO_COLOR is set.",
+        ))
+        .flag(Flag::new().long("--list").help(
+            "Print the structure of each document instead of its text: the \
+                     sheets of a workbook with their numbers of rows and columns \
+                     (and an .xlsx workbook's defined names and data validation \
+                     lists), the slides of a presentation with their titles, the page \
+                     count and bookmarks of a PDF, or the heading outline of a \
+                     .doc or .docx.",
+        ))
+        .flag(Flag::new().long("--chunk").help(
+            "Split each document into chunks for search and retrieval \
+                     pipelines, written as one JSON object per line with the file, \
+                     the chunk's number, its location (page, slide, or sheet and \
+                     rows), the headings it falls under, and its text as markdown. \
+                     A chunk never spans a heading, slide, page, or sheet; long \
+                     tables are split between rows with the header row repeated.",
+        ))
+        .option(Opt::new("N").long("--chunk-size").help(
+            "Maximum number of characters in a chunk with --chunk (default \
+                     2000). Implies --chunk.",
+        ))
+        .option(Opt::new("N").long("--summary-words").help(
+            "Number of words shown from each section with --summary (default \
+                     50); 0 leaves the section excerpts out. Implies --summary.",
+        ))
+        .flag(Flag::new().long("--preview").help(
+            "Fast, short output for the preview pane of a file manager (lf, \
+                     ranger) or fuzzy finder (fzf): stop reading each document \
+                     once --preview-size KB of text has been extracted, cut the \
+                     output to that size at a line break, leave out images, and \
+                     never page.",
+        ))
+        .option(Opt::new("KB").long("--preview-size").help(
+            "Kilobytes of output kept by --preview (default 64). Implies \
+                     --preview.",
+        ))
+        .option(Opt::new("N").long("--head").help(
+            "Show only the first N lines of each document's plain, markdown, \
+                     or Org output. Reading stops once the document has given N \
+                     lines, so the rest of a long PDF or presentation is not \
+                     extracted.",
+        ))
+        .option(Opt::new("N").long("--tail").help(
+            "Show only the last N lines of each document's plain, markdown, or \
+                     Org output. Cannot be combined with --head.",
+        ))
+        .option(Opt::new("FILE").short("-o").long("--output").help(
+            "Write output to FILE instead of stdout. Unless -p, -m, or --to is \
+                     given, the format follows the extension: .md for markdown, \
+                     .org for Org, .csv for CSV, .xml for XML, and plain text \
+                     otherwise. \
+                     {stem}, {name}, and {ext} are replaced by each input's file \
+                     stem, name, and extension (batdoc -o '{stem}.md' *.docx); \
+                     without them, all inputs are written to the one file.",
+        ))
+        .flag(Flag::new().long("--copy").help(
+            "Put the output on the clipboard instead of stdout, as markdown \
+                     unless -p or --to says otherwise. The text is sent to the \
+                     terminal as an OSC 52 escape sequence (wrapped for tmux), so \
+                     the terminal must support OSC 52; it works over SSH. Cannot \
+                     be combined with --output.",
+        ))
+        .option(Opt::new("DIR").long("--csv-dir").help(
+            "Write each non-empty worksheet to its own CSV file in DIR, \
+                     named FILE_SHEET.csv. Implies --csv.",
+        ))
+        .option(Opt::new("SHEET").long("--sheet").help(
+            "Extract only one worksheet of an .xls/.xlsx workbook, in every \
+                     output format: the sheet named SHEET (exactly, else ignoring \
+                     case), or else the SHEET-th visible sheet, counting from 1.",
+        ))
+        .flag(Flag::new().long("--pick").help(
+            "Before showing a workbook or presentation on a terminal, list its \
+                     sheets or slides and show only the chosen ones. Typing \
+                     filters the list, the arrow keys move, Tab marks an item, \
+                     Enter shows the marked items (or the one under the cursor), \
+                     and Esc shows everything. Cannot be combined with --jobs.",
+        ))
+        .option(Opt::new("PASSWORD").long("--password").help(
+            "Decrypt password-protected documents with PASSWORD: RC4-encrypted \
+                     .doc and .xls files, and .docx, .xlsx, and .pptx files with \
+                     standard or agile AES encryption. Defaults to \
+                     $BATDOC_PASSWORD. Workbooks encrypted with Excel's default \
+                     password open without one.",
+        ))
+        .option(Opt::new("ENC").long("--encoding").help(
+            "Decode the 8-bit text and document properties of .doc and .xls \
+                     files with codepage ENC instead of the declared or guessed \
+                     one: a number (1251), a Windows name (cp1251, windows-1251), \
+                     or an encoding label (koi8-r, shift_jis, latin1).",
+        ))
         .flag(Flag::new().short("-i").long("--images").help(
             "Embed images as inline base64 data URIs in markdown output. \
                      Extracts embedded images from .docx, .pptx, and .xlsx files. \
@@ -25,12 +165,216 @@ fn main() {
                      Ignored in plain text mode and for formats without image \
                      support (.doc, .xls, .pdf).",
         ))
+        .flag(Flag::new().long("--no-reflow").help(
+            "Keep hard line breaks in .doc and PDF markdown output. By default, \
+                     lines that end without terminal punctuation are joined with \
+                     the following line when they look like a hard wrap.",
+        ))
+        .flag(Flag::new().long("--table").help(
+            "Draw spreadsheet and .docx tables in plain text output as aligned, \
+                     box-drawn grids instead of tab-separated rows. Column widths \
+                     are measured in terminal columns.",
+        ))
+        .flag(Flag::new().long("--escape-html").help(
+            "In markdown output, escape document text that a markdown renderer \
+                     would treat as raw HTML: < before a tag name, /, !, or ? \
+                     becomes &lt;, and & before an entity reference becomes &amp;. \
+                     Code spans and fenced code blocks are left alone.",
+        ))
+        .option(Opt::new("LIST").long("--pages").help(
+            "Extract only the listed PDF pages: a comma-separated list of page \
+                     numbers and ranges such as 2-5,9 or 10- (to the last page). \
+                     Page headings keep the document's numbering. Other formats \
+                     are not affected.",
+        ))
+        .flag(Flag::new().long("--no-print-area").help(
+            "Show the whole of each .xls sheet. By default, a sheet with a \
+                     print area (the built-in Print_Area name) is cropped to it.",
+        ))
+        .flag(Flag::new().long("--hidden").help(
+            "Include hidden sheets, and the rows and columns an .xlsx sheet \
+                     hides. By default they are left out.",
+        ))
+        .option(Opt::new("MODE").long("--cell-newline").help(
+            "What a line break inside a spreadsheet cell becomes in plain \
+                     (tab-separated) output: escape writes a literal \\\\n (the \
+                     default), space writes a space. Either way each output line \
+                     is one spreadsheet row.",
+        ))
+        .option(Opt::new("N").long("--max-rows").help(
+            "Show at most N rows of each .xls or .xlsx sheet, counted from \
+                     its first cell with a value, and note how many more there \
+                     are after it, as \"… (120 more rows)\". CSV export, \
+                     --stats, and --grep cover whole sheets.",
+        ))
+        .option(Opt::new("N").long("--max-cols").help(
+            "Show at most N columns of each .xls or .xlsx sheet, like \
+                     --max-rows.",
+        ))
+        .option(Opt::new("WHERE").long("--comments").help(
+            "Where to show the comments of a .docx: none leaves them out (the \
+                     default); inline shows each as > [Author: text] after the \
+                     paragraph it is attached to; end marks its place with [c1], \
+                     [c2], ... and lists the comments, with the text they refer \
+                     to, in a Comments section at the end. The notes and \
+                     threaded comments of a .xlsx, and the notes of a .xls, mark \
+                     their cell with [c1], [c2], ... and are listed after each \
+                     sheet (inline) or after the last (end).",
+        ))
+        .option(Opt::new("MODE").long("--revisions").help(
+            "How to show the tracked changes of a .docx: final shows the \
+                     text with every change accepted (the default), original \
+                     with every change rejected, and marked shows both, with \
+                     deleted text as [-text-] and inserted text as {+text+}.",
+        ))
+        .option(Opt::new("MODE").long("--marks").help(
+            "How to mark struck-through, underlined, and highlighted .docx \
+                     text in markdown: extended writes ~~text~~, <u>text</u>, and \
+                     ==text== (the default); emphasis keeps to plain markdown, \
+                     with underlined text as *text* and highlighted text as \
+                     **text**; none leaves them unmarked.",
+        ))
+        .flag(Flag::new().long("--control-labels").help(
+            "Put the title of each content control in a .docx form, or its \
+                     tag if it has none, before the control's text, as Title: \
+                     text. The placeholder text of empty controls is left out \
+                     either way.",
+        ))
+        .flag(Flag::new().long("--embedded").help(
+            "Extract the documents embedded in a .docx (worksheets, PDFs, \
+                     attached files) that batdoc can read, and put their content \
+                     after the placeholder naming each object. Without it only \
+                     the placeholder is shown.",
+        ))
+        .option(Opt::new("SEP").long("--page-breaks").help(
+            "Write the line SEP where a .docx has a page break or a section \
+                     break, such as --- for a markdown rule, so the output keeps \
+                     the document's pagination. Page breaks are not marked by \
+                     default.",
+        ))
+        .option(Opt::new("WHEN").long("--paging").help(
+            "When to use a pager for terminal output: auto (only when the \
+                     output does not fit on one screen, the default), always, \
+                     or never. Applies to plain output only with --pager-plain.",
+        ))
+        .flag(Flag::new().long("--pager-plain").help(
+            "Send plain text output through the pager when stdout is a \
+                     terminal, as markdown output is. Follows --paging.",
+        ))
+        .option(Opt::new("CMD").long("--pager").help(
+            "Pager command line for terminal output, such as 'less -RS'. \
+                     Overrides \\fBBAT_PAGER\\fR and \\fBPAGER\\fR.",
+        ))
+        .flag(
+            Flag::new()
+                .long("--no-pager")
+                .help("Never use a pager; the same as --paging never."),
+        )
+        .option(Opt::new("THEME").long("--theme").help(
+            "Syntax highlighting theme for terminal output, as in bat(1). \
+                     Defaults to \\fB$BAT_THEME\\fR, or ansi when it is not set. An \
+                     unknown theme is an error.",
+        ))
+        .option(Opt::new("LIST").long("--style").help(
+            "Decorations around terminal output, as in bat(1): a \
+                     comma-separated list of header, grid, numbers, rule, and snip, \
+                     or one of the presets default (header and grid), full, or plain.",
+        ))
+        .flag(Flag::new().short("-n").long("--line-numbers").help(
+            "Show line numbers in terminal output, in addition to the \
+                     decorations chosen with --style.",
+        ))
+        .option(Opt::new("MODE").long("--wrap").help(
+            "How long lines are shown on a terminal: auto (the default) \
+                     leaves them to the terminal, character wraps them at the \
+                     terminal width inside the grid, and never keeps them on one \
+                     line for the pager to scroll sideways.",
+        ))
+        .flag(Flag::new().long("--no-filename").help(
+            "Do not precede the text of each file with a \\fB==> file <==\\fR \
+                     line. By default the line is written when several files are \
+                     extracted to the same place, and for each archive member.",
+        ))
+        .option(Opt::new("FMT").long("--errors").help(
+            "Report errors on stderr as text (the default) or json. In json \
+                     mode each failed file produces one object with file, format, \
+                     kind, and message keys; kind is a stable code: encrypted, \
+                     wrong_password, corrupt, unsupported, empty, not_found, permission_denied, \
+                     too_large, io, usage, or render. Warnings about files that \
+                     were still extracted, such as salvaged text, use kind \
+                     warning.",
+        ))
+        .flag(Flag::new().long("--fail-fast").help(
+            "Stop at the first file that fails instead of going on with the \
+                     rest; nothing after it is written.",
+        ))
+        .flag(Flag::new().short("-v").long("--verbose").help(
+            "Report on stderr each document's format, how it was \
+                     determined, its size, and the time taken to extract it. Given \
+                     twice (-vv), also report how the parser read it, such as the \
+                     codepage of 8-bit text and the hidden sheets it skipped. \
+                     These are info messages (kind info with --errors json).",
+        ))
+        .flag(Flag::new().short("-q").long("--quiet").help(
+            "Do not report warnings or show the progress bar for large \
+                     workbooks and PDFs; errors are still reported.",
+        ))
+        .flag(
+            Flag::new()
+                .long("--no-config")
+                .help("Ignore the configuration file and use only command-line options."),
+        )
+        .flag(Flag::new().long("--deterministic").help(
+            "Make the output depend only on the inputs and the command line, \
+                     byte for byte on every run and platform: the configuration \
+                     file is ignored and stdout is treated as a pipe even on a \
+                     terminal (no colors, decorations, or pager).",
+        ))
+        .flag(Flag::new().long("--allow-remote").help(
+            "Download http:// and https:// URL arguments with curl(1) and extract \
+                     them. Downloads count against the 256 MiB input limit, and \
+                     redirects may only lead to other HTTP(S) URLs. Without this \
+                     flag such arguments are rejected.",
+        ))
+        .option(Opt::new("FILE").long("--files-from").help(
+            "Read further input paths from FILE, or from stdin if FILE is -, \
+                     one per line, after those given as arguments. Blank lines are \
+                     skipped. Useful when there are too many files for one command \
+                     line.",
+        ))
+        .flag(Flag::new().short("-0").long("--null").help(
+            "With \\fB--files-from\\fR, paths are separated by NUL bytes instead \
+                     of newlines, as written by find -print0.",
+        ))
+        .option(Opt::new("N").short("-j").long("--jobs").help(
+            "Extract up to N input files at once on worker threads; 0 uses one \
+                     per CPU. The default is 1. Output, errors, and warnings are \
+                     still written in the order the files were given. A very \
+                     large .docx or .xlsx that is written out as it is read, \
+                     rather than held in memory, is extracted while it is \
+                     written, one at a time in that order.",
+        ))
+        .flag(Flag::new().long("--word-diff").help(
+            "With \\fBbatdoc diff\\fR, print each changed passage once with the \
+                     removed words as [-old-] and the added ones as {+new+}, or in \
+                     red and green on a terminal, instead of whole changed lines.",
+        ))
+        .option(Opt::new("ADDR").long("--listen").help(
+            "With \\fBbatdoc serve\\fR, the address and port to listen on \
+                     (default 127.0.0.1:8080).",
+        ))
         .flag(
             Flag::new()
                 .short("-h")
                 .long("--help")
                 .help("Show help information."),
         )
+        .flag(
+            Flag::new()
+                .short("-V")
+                .long("--version")
+                .help("Show version information."),
+        )
         .arg(Arg::new("[FILE...]"))
         .custom(
             Section::new("description")
@@ -45,6 +389,30 @@ fn main() {
                      (OOXML), .xls (BIFF8 Excel 97+), .xlsx (OOXML), .pptx \
                      (OOXML), and .pdf.",
                 )
+                .paragraph(
+                    "Plain .zip archives (ones that are not Office documents) and \
+                     .tar or gzipped .tar archives are opened, and each supported \
+                     document inside is extracted in order under a \
+                     \\fB==> archive/member <==\\fR header. Other members are skipped.",
+                )
+                .paragraph(
+                    "\\fBbatdoc diff\\fR \\fIOLD\\fR \\fINEW\\fR compares the text of \
+                     two documents, of the same format or not, and prints a unified \
+                     diff of their lines: headings, list items, paragraphs, table \
+                     rows, and page, slide, and sheet markers. The exit status is 0 \
+                     when the texts are the same, 1 when they differ, and 2 on error.",
+                )
+                .paragraph(
+                    "\\fBbatdoc serve\\fR answers HTTP requests instead of reading \
+                     files. A document posted to / as the request body, or as the \
+                     file of a multipart/form-data upload, is returned as markdown, \
+                     or in the format named by ?to= (plain, org, csv, xml, or json \
+                     for the markdown in a JSON object). Documents that cannot be \
+                     converted get a 4xx or 5xx status and a JSON error object like \
+                     those of --errors json. -j sets how many requests are handled \
+                     at once and -v logs each one. There is no TLS or \
+                     authentication; it listens on localhost by default.",
+                )
                 .paragraph(
                     "When stdout is a terminal, output is pretty-printed as \
                      syntax-highlighted markdown via bat(1) with paging. When \
@@ -52,8 +420,10 @@ fn main() {
                 )
                 .paragraph(
                     "Multiple files can be specified and will be processed in \
-                     order. Use \\fB-\\fR to read from stdin explicitly. Maximum \
-                     input size is 256 MiB.",
+                     order. Use \\fB-\\fR to read from stdin explicitly, and \
+                     \\fB--\\fR to end the options: every argument after it is a \
+                     file name, even one that starts with a dash. \\fBfile://\\fR \
+                     URLs are read as local paths. Maximum input size is 256 MiB.",
                 ),
         )
         .example(
@@ -71,11 +441,68 @@ fn main() {
                 .text("Convert a presentation to markdown with embedded images")
                 .command("batdoc --images slides.pptx > slides.md"),
         )
+        .example(
+            Example::new()
+                .text("Preview documents while picking one with fzf")
+                .command("fzf --preview 'batdoc --preview {}'"),
+        )
+        .example(
+            Example::new()
+                .text("Triage a long document: outline, tables, section openings")
+                .command("batdoc --summary handbook.docx"),
+        )
+        .example(
+            Example::new()
+                .text("See which sheets a workbook has before extracting one")
+                .command("batdoc --list budget.xlsx"),
+        )
+        .example(
+            Example::new()
+                .text("Chunk a set of documents for a search index, as JSON lines")
+                .command("batdoc --chunk --chunk-size 1000 *.docx *.pdf > chunks.jsonl"),
+        )
+        .example(
+            Example::new()
+                .text("Count the words in every document in a directory")
+                .command("batdoc --stats *.docx *.pdf"),
+        )
+        .example(
+            Example::new()
+                .text("Find every mention of an invoice number")
+                .command("batdoc --grep INV-2024-117 *.docx *.pdf *.xlsx"),
+        )
+        .example(
+            Example::new()
+                .text("Convert every .docx found under a directory")
+                .command(
+                    "find docs -name '*.docx' -print0 | batdoc -0 --files-from - -o '{stem}.md'",
+                ),
+        )
+        .example(
+            Example::new()
+                .text("Convert a large batch eight files at a time")
+                .command("batdoc -j8 -o '{stem}.txt' archive/*.doc"),
+        )
+        .example(
+            Example::new()
+                .text("Review the wording changes between two versions of a contract")
+                .command("batdoc diff --word-diff contract-v1.doc contract-v2.docx"),
+        )
+        .example(
+            Example::new()
+                .text("Serve conversions over HTTP, four at a time")
+                .command("batdoc serve --listen 127.0.0.1:9000 -j 4"),
+        )
         .example(
             Example::new()
                 .text("Read from stdin")
                 .command("curl -sL https://example.com/file.docx | batdoc"),
         )
+        .example(
+            Example::new()
+                .text("Fetch and extract a remote document")
+                .command("batdoc --allow-remote https://example.com/file.docx"),
+        )
         .custom(
             Section::new("environment")
                 .paragraph(
@@ -83,8 +510,48 @@ fn main() {
                      When set, colored output is suppressed even on a terminal.",
                 )
                 .paragraph(
-                    "The \\fBPAGER\\fR environment variable controls which pager \
-                     is used when output is displayed on a terminal.",
+                    "\\fBBAT_PAGER\\fR, or else \\fBPAGER\\fR, names the pager used \
+                     when output is displayed on a terminal and \\fB--pager\\fR is \
+                     not given; the default is less(1). A more(1) or most(1) in \
+                     \\fBPAGER\\fR is replaced by less, which can show colors.",
+                )
+                .paragraph(
+                    "\\fBBAT_THEME\\fR sets the syntax highlighting theme when \
+                     \\fB--theme\\fR is not given.",
+                )
+                .paragraph(
+                    "\\fBBATDOC_CONFIG\\fR names the configuration file, overriding \
+                     the default \\fI$XDG_CONFIG_HOME/batdoc/config.toml\\fR \
+                     (\\fI~/.config/batdoc/config.toml\\fR). Top-level keys \
+                     (images, reflow, table, escape_html, print_area, hidden, \
+                     control_labels, embedded = true or false; cell_newline = \
+                     escape or space; max_rows, max_cols = a number; comments \
+                     = none, inline, or end; revisions = final, original, or \
+                     marked; marks = extended, emphasis, or none; page_breaks \
+                     = a separator line) apply to every document; a [doc], \
+                     [docx], [xls], [xlsx], [pptx], or [pdf] section \
+                     applies to that format only. Command-line flags take \
+                     precedence.",
+                )
+                .paragraph(
+                    "\\fBBATDOC_PASSWORD\\fR is the password for encrypted \
+                     documents when \\fB--password\\fR is not given. Unlike the \
+                     flag, it does not show up in the process list.",
+                ),
+        )
+        .custom(
+            Section::new("exit status")
+                .paragraph("0 if every file was extracted.")
+                .paragraph("1 if some files failed, for different or other reasons.")
+                .paragraph("2 if the command line or the configuration file is invalid.")
+                .paragraph("3 if every failure was a file in an unsupported format.")
+                .paragraph(
+                    "4 if every failure was an encrypted file without the right \
+                     password.",
+                )
+                .paragraph(
+                    "\\fBbatdoc diff\\fR exits 0 if the documents' text is the same, \
+                     1 if it differs, and 2 on error.",
                 ),
         )
         .custom(Section::new("see also").paragraph("bat(1), catdoc(1), pdftotext(1)"))
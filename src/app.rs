@@ -0,0 +1,1480 @@
+//! The `batdoc` command-line program.
+//!
+//! Reads each input, detects its format, and writes it to stdout as plain
+//! text, markdown (pretty-printed through `bat` on a terminal), Org, or CSV.
+
+use crate::cli::{self, Args, Command, ErrorFormat, Mode, Paging, Wrap};
+use crate::config;
+use crate::error::{self, BatdocError};
+use crate::format::{detect_format, detect_format_or, Format};
+use crate::options::{Budget, Comments, Options};
+use crate::progress::{self, Progress};
+use crate::{archive, doc, docx, json, markup, org, pdf, picker, platform, pptx, remote, sheet};
+use crate::{
+    chunk, clipboard, crypto, diff, document, grep, listing, metadata, serve, stats, summary, xml,
+};
+use crate::{xls, xlsx};
+
+use bat::{Input, PrettyPrinter};
+use is_terminal::IsTerminal;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fs::OpenOptions;
+use std::io::{self, Read, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::time::Instant;
+
+/// Maximum input file size (256 MiB). Prevents accidental OOM from
+/// huge files or zip bombs.
+const MAX_INPUT_SIZE: usize = 256 * 1024 * 1024;
+
+/// Exit statuses, so that scripts can tell why a run failed.
+mod exit {
+    /// Every input was extracted.
+    pub(super) const OK: i32 = 0;
+    /// Some inputs failed, for different or other reasons.
+    pub(super) const FAILED: i32 = 1;
+    /// The command line or the config file is invalid.
+    pub(super) const USAGE: i32 = 2;
+    /// Every failed input was in an unsupported format.
+    pub(super) const UNSUPPORTED: i32 = 3;
+    /// Every failed input was encrypted, with no or the wrong password.
+    pub(super) const ENCRYPTED: i32 = 4;
+}
+
+/// Run the command line program. Exits the process on failure.
+pub fn main() {
+    let exit_code = {
+        let _console = platform::init_console();
+        run_cli()
+    };
+    if exit_code != 0 {
+        process::exit(exit_code);
+    }
+}
+
+/// Runs a subcommand such as `batdoc diff` and returns its exit code.
+type Subcommand = fn(&Extractor) -> i32;
+
+/// Process the command line and return the exit code.
+fn run_cli() -> i32 {
+    let (mut args, subcommand): (_, Option<Subcommand>) =
+        match cli::parse_args(std::env::args_os().skip(1)) {
+            Ok(Command::Run(args)) => (args, None),
+            Ok(Command::Diff(args)) => (args, Some(run_diff)),
+            Ok(Command::Serve(args)) => (args, Some(run_serve)),
+            Ok(Command::Help) => {
+                println!("{}", cli::USAGE);
+                return exit::OK;
+            }
+            Ok(Command::Version) => {
+                println!("batdoc {}", env!("CARGO_PKG_VERSION"));
+                return exit::OK;
+            }
+            Err(msg) => {
+                eprintln!("batdoc: {msg}");
+                eprintln!("Try 'batdoc --help' for more information.");
+                return exit::USAGE;
+            }
+        };
+    if let Some(list) = &args.files_from {
+        match read_file_list(list, args.null) {
+            Ok(paths) => args.files.extend(paths),
+            Err(e) => {
+                let name = platform::display_path(Path::new(list));
+                report_error(args.errors, &name, None, &e.into());
+                return exit::FAILED;
+            }
+        }
+    }
+    let files = &args.files;
+
+    let Some(config) = load_config(&args) else {
+        return exit::USAGE;
+    };
+
+    if let Some(theme) = &args.theme {
+        if !PrettyPrinter::new().themes().any(|t| t == theme) {
+            eprintln!("batdoc: unknown theme '{theme}'");
+            return exit::USAGE;
+        }
+    }
+
+    let is_tty = !args.deterministic && !args.copy && io::stdout().is_terminal();
+    let jobs = workers(args.jobs);
+    // Name each input when several share the destination, as head(1) does
+    let shared_output = args
+        .output
+        .as_deref()
+        .is_none_or(|t| !["{stem}", "{name}", "{ext}"].iter().any(|p| t.contains(p)));
+    let extractor = Extractor {
+        args: &args,
+        config,
+        password: args
+            .password
+            .clone()
+            .or_else(|| std::env::var("BATDOC_PASSWORD").ok()),
+        is_tty,
+        headers: !args.no_filename && files.len() > 1 && shared_output,
+        // Bars of documents extracted side by side would overwrite each
+        // other
+        progress: !args.quiet && (jobs == 1 || files.len() < 2) && io::stderr().is_terminal(),
+    };
+    if let Some(run) = subcommand {
+        return run(&extractor);
+    }
+    // With --copy, what would be printed is kept for the clipboard
+    let mut copied = Vec::new();
+    let mut session = Session {
+        args: &args,
+        stdout: Output {
+            writer: if args.copy {
+                Box::new(&mut copied)
+            } else {
+                Box::new(io::stdout())
+            },
+            is_tty,
+            mode: args.mode,
+            tally: stats::Tally::default(),
+        },
+        written: HashMap::new(),
+        documents: 0,
+        status: Status::default(),
+    };
+
+    // With --fail-fast, nothing is written after the first failure and
+    // no further inputs are started
+    let stop = AtomicBool::new(false);
+    let mut emit = |event| {
+        if !stop.load(Ordering::Relaxed) && !session.emit(event) && args.fail_fast {
+            stop.store(true, Ordering::Relaxed);
+        }
+    };
+    if jobs > 1 && files.len() > 1 {
+        extractor.inputs_parallel(files, jobs, &stop, &mut emit);
+    } else {
+        for path in files.iter().take_while(|_| !stop.load(Ordering::Relaxed)) {
+            extractor.input(path, &mut emit);
+        }
+    }
+
+    let mut status = session.finish();
+    if args.copy {
+        copy_output(&copied, args.quiet, &mut status);
+    }
+    status.code()
+}
+
+/// Load the config file, unless told not to. A config file that cannot
+/// be read is reported, and gives `None`.
+fn load_config(args: &Args) -> Option<config::Config> {
+    // --deterministic output must not depend on the user's environment
+    let no_config = args.no_config || args.deterministic;
+    match config::default_path() {
+        Some(path) if !no_config => match config::Config::load(&path) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                eprintln!("batdoc: config: {e}");
+                None
+            }
+        },
+        _ => Some(config::Config::default()),
+    }
+}
+
+/// Put the output of a `--copy` run on the clipboard, and say so unless
+/// `quiet`.
+fn copy_output(text: &[u8], quiet: bool, status: &mut Status) {
+    match clipboard::copy(text) {
+        Ok(()) if !quiet => eprintln!("batdoc: copied {} bytes to the clipboard", text.len()),
+        Ok(()) => {}
+        Err(e) => {
+            eprintln!("batdoc: {e}");
+            status.fail(&e.into());
+        }
+    }
+}
+
+/// How the inputs of a run have fared, for the exit status.
+#[derive(Debug, Default)]
+struct Status(Option<i32>);
+
+impl Status {
+    /// Record a failure: the status names its cause while every failure
+    /// shares one, and is [`exit::FAILED`] once they differ.
+    fn fail(&mut self, err: &BatdocError) {
+        let code = match err.code() {
+            "unsupported" => exit::UNSUPPORTED,
+            "encrypted" | "wrong_password" => exit::ENCRYPTED,
+            _ => exit::FAILED,
+        };
+        self.0 = match self.0 {
+            Some(previous) if previous != code => Some(exit::FAILED),
+            _ => Some(code),
+        };
+    }
+
+    fn code(&self) -> i32 {
+        self.0.unwrap_or(exit::OK)
+    }
+}
+
+/// Everything needed to turn an input into rendered documents. Nothing
+/// here changes while inputs are processed, so `--jobs` workers share it.
+struct Extractor<'a> {
+    args: &'a Args,
+    config: config::Config,
+    /// `--password`, or `$BATDOC_PASSWORD`
+    password: Option<String>,
+    /// Whether stdout is a terminal
+    is_tty: bool,
+    /// Precede each input's output with a `==> name <==` line
+    headers: bool,
+    /// Show a progress bar on stderr while large inputs are parsed
+    progress: bool,
+}
+
+/// What one input produced, passed to [`Session::emit`] in input order.
+enum Event {
+    /// An input or archive member that failed before it could be
+    /// extracted, such as an unreadable file or an unknown format.
+    Error(String, BatdocError),
+    /// A document extracted and rendered for its destination.
+    Document(Entry),
+}
+
+/// A document rendered for its destination but not yet written out.
+struct Entry {
+    filename: String,
+    format: Format,
+    /// Precede the output with a `==> name <==` line (archive members).
+    header: bool,
+    body: error::Result<Body>,
+    /// Problems the parser worked around, reported after the output.
+    warnings: Vec<String>,
+    /// How the document was read (`-v`), reported before the output.
+    notes: Vec<String>,
+}
+
+/// A document's output, ready to be written.
+enum Body {
+    /// Text written as is.
+    Text(String),
+    /// Markdown pretty-printed through bat.
+    Pretty(String),
+    /// Plain text sent through bat's pager (`--pager-plain`).
+    Paged(String),
+    /// Counts that become a row of the `--stats` table.
+    Stats(stats::Stats),
+    /// Text written as it is extracted, for a document too large to hold
+    /// whole (see [`stream`]).
+    Stream(Stream),
+}
+
+/// Writes a document's text to the output as it extracts it.
+type Stream = Box<dyn FnOnce(&mut dyn Write) -> error::Result<()> + Send>;
+
+impl Body {
+    /// The body with its text cut down by `--head`, `--tail`, and
+    /// `--preview`.
+    fn limited(self, args: &Args) -> Self {
+        let limit = |mut text: String| {
+            if let Some(n) = args.head {
+                text = head(text, n);
+            }
+            if let Some(n) = args.tail {
+                text = tail(text, n);
+            }
+            if let Some(bytes) = args.preview {
+                text = truncate(text, bytes);
+            }
+            text
+        };
+        match self {
+            Self::Text(text) => Self::Text(limit(text)),
+            Self::Pretty(md) => Self::Pretty(limit(md)),
+            Self::Paged(text) => Self::Paged(limit(text)),
+            Self::Stats(stats) => Self::Stats(stats),
+            Self::Stream(stream) => Self::Stream(stream),
+        }
+    }
+}
+
+/// Ask which sheets of a workbook or slides of a presentation to show
+/// (`--pick`), when it has more than one, and set `opts.picked`.
+/// Ctrl-C in the picker ends the program.
+fn pick(data: &[u8], format: Format, filename: &str, opts: &mut Options) -> error::Result<()> {
+    let (what, numbers, items): (_, Vec<usize>, Vec<String>) = match format {
+        Format::Xls | Format::Xlsx => {
+            let sheets = match format {
+                Format::Xls => xls::extract_sheets(data, opts)?,
+                _ => xlsx::extract_sheets(data, opts)?,
+            };
+            let names = sheets.into_iter().map(|s| s.name).collect::<Vec<_>>();
+            ("sheets", (1..=names.len()).collect(), names)
+        }
+        Format::Pptx => {
+            let slides = listing::slide_titles(&document::extract_blocks(data, format, opts)?);
+            let items = slides.iter().map(|(n, title)| format!("{n}. {title}"));
+            (
+                "slides",
+                slides.iter().map(|&(n, _)| n).collect(),
+                items.collect(),
+            )
+        }
+        Format::Doc | Format::Docx | Format::Pdf => return Ok(()),
+    };
+    if items.len() < 2 {
+        return Ok(());
+    }
+    match picker::pick(&format!("{filename} {what} "), &items) {
+        Ok(picked) => {
+            opts.picked = picked.map(|picked| picked.into_iter().map(|i| numbers[i]).collect());
+            Ok(())
+        }
+        Err(e) if e.kind() == io::ErrorKind::Interrupted => process::exit(130),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The first `n` lines of `text`.
+fn head(mut text: String, n: usize) -> String {
+    let end = text.split_inclusive('\n').take(n).map(str::len).sum();
+    text.truncate(end);
+    text
+}
+
+/// The last `n` lines of `text`.
+fn tail(mut text: String, n: usize) -> String {
+    let keep: usize = text.split_inclusive('\n').rev().take(n).map(str::len).sum();
+    text.drain(..text.len() - keep);
+    text
+}
+
+/// Cut `text` to at most `limit` bytes, after the last line break that
+/// fits, or mid-line if the first line is longer.
+fn truncate(mut text: String, limit: usize) -> String {
+    if text.len() > limit {
+        let mut end = limit;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let end = text[..end].rfind('\n').map_or(end, |i| i + 1);
+        text.truncate(end);
+    }
+    text
+}
+
+impl Extractor<'_> {
+    /// Read one input argument and extract it, or each document in it if
+    /// it is an archive, passing the results to `emit`.
+    fn input(&self, path: &OsStr, emit: &mut dyn FnMut(Event)) {
+        let args = self.args;
+        let (filename, result) = read_input(path, args.allow_remote);
+        let buf = match result {
+            Ok(buf) => buf,
+            Err(e) => return emit(Event::Error(filename, e)),
+        };
+
+        if buf.len() > MAX_INPUT_SIZE {
+            let err = BatdocError::too_large(Some(buf.len()), MAX_INPUT_SIZE);
+            return emit(Event::Error(filename, err));
+        }
+
+        // A forced --format applies to the input itself, never to members
+        let archive = args
+            .format
+            .is_none()
+            .then(|| archive::detect(&buf))
+            .flatten();
+        match archive {
+            Some(kind) => self.archive(&buf, kind, &filename, emit),
+            None => emit(self.document(&buf, &filename, self.headers)),
+        }
+    }
+
+    /// [`input`](Self::input) for each of `files` on `jobs` worker
+    /// threads. Each input's events are held until those of the inputs
+    /// before it have been passed to `emit`, so the output is the same as
+    /// with one job. Workers start no new input once `stop` is set.
+    ///
+    /// A [`Body::Stream`] is not run on its worker: it is only a closure
+    /// until `emit` writes it, so streamed inputs are extracted one after
+    /// another, in order, on this thread.
+    fn inputs_parallel(
+        &self,
+        files: &[OsString],
+        jobs: usize,
+        stop: &AtomicBool,
+        emit: &mut dyn FnMut(Event),
+    ) {
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+        std::thread::scope(|scope| {
+            for _ in 0..jobs.min(files.len()) {
+                let sender = sender.clone();
+                let next = &next;
+                scope.spawn(move || loop {
+                    if stop.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = files.get(index) else { break };
+                    let mut events = Vec::new();
+                    self.input(path, &mut |event| events.push(event));
+                    if sender.send((index, events)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+
+            let mut done = HashMap::new();
+            let mut written = 0;
+            for (index, events) in receiver {
+                done.insert(index, events);
+                while let Some(events) = done.remove(&written) {
+                    events.into_iter().for_each(&mut *emit);
+                    written += 1;
+                }
+            }
+        });
+    }
+
+    /// Extract one document and render it for its destination. With
+    /// `header`, a `==> name <==` line will precede output that is not
+    /// shown through bat (which has its own file header).
+    fn document(&self, data: &[u8], filename: &str, header: bool) -> Event {
+        let args = self.args;
+        let mut start = Instant::now();
+        let (data, format, mut opts) = match self.prepare(data, filename) {
+            Ok(prepared) => prepared,
+            Err(e) => return Event::Error(filename.to_string(), e),
+        };
+        let (mode, is_tty) = args
+            .output
+            .as_ref()
+            .map_or((args.mode, self.is_tty), |template| {
+                (
+                    output_mode(&expand_output(template, filename), args.mode),
+                    false,
+                )
+            });
+        if args.pick && is_tty {
+            // Time spent choosing is not extraction time, for -v
+            let asked = Instant::now();
+            if let Err(e) = pick(&data, format, filename, &mut opts) {
+                return Event::Error(filename.to_string(), e);
+            }
+            start += asked.elapsed();
+        }
+        // --preview and --head cut text output short, so the parser can
+        // stop early; other modes need the whole document
+        let limited = matches!(mode, Mode::Auto | Mode::Plain | Mode::Markdown | Mode::Org);
+        if limited {
+            opts.budget = Budget::new(args.preview, args.head);
+        } else {
+            // Stats, grep, chunks, and CSV cover whole sheets
+            (opts.max_rows, opts.max_cols) = (None, None);
+        }
+        if self.progress && data.len() >= progress::MIN_BYTES {
+            opts.progress = Progress::stderr(filename);
+        }
+        let mut body = stream(&data, format, filename, args, &opts, mode, is_tty).map_or_else(
+            || render(&data, format, filename, args, &opts, mode, is_tty),
+            |stream| Ok(Body::Stream(stream)),
+        );
+        opts.progress.finish();
+        if limited {
+            body = body.map(|body| body.limited(args));
+        }
+
+        let mut notes = Vec::new();
+        if args.verbose > 0 {
+            let how = if args.format.is_some() {
+                "forced with --format"
+            } else if detect_format(&data).is_ok() {
+                "detected"
+            } else {
+                "from the file extension"
+            };
+            let decrypted = if matches!(data, Cow::Owned(_)) {
+                ", decrypted"
+            } else {
+                ""
+            };
+            notes.push(format!(
+                "{} ({how}), {} bytes{decrypted}, {:.1} ms",
+                format.name(),
+                data.len(),
+                start.elapsed().as_secs_f64() * 1000.0
+            ));
+        }
+        if args.verbose > 1 {
+            notes.extend(opts.notes.take());
+        }
+        Event::Document(Entry {
+            filename: filename.to_string(),
+            format,
+            header,
+            body,
+            warnings: opts.warnings.take(),
+            notes,
+        })
+    }
+
+    /// Convert a document posted to `batdoc serve` into the format named
+    /// by the request's `to` parameter (markdown by default, or `json`
+    /// for the markdown in a JSON object).
+    fn respond(&self, request: &serve::Request) -> serve::Response {
+        let filename = request
+            .filename
+            .as_deref()
+            .or_else(|| request.param("name"))
+            .unwrap_or("upload");
+        let to = request.param("to").unwrap_or("markdown");
+        let mode = match to {
+            "json" => Ok(Mode::Markdown),
+            to => cli::parse_mode(to).map_err(|_| {
+                BatdocError::Usage(format!(
+                    "unknown output '{to}' (expected markdown, plain, org, csv, xml, or json)"
+                ))
+            }),
+        };
+        let mut format = None;
+        let result = mode.and_then(|mode| {
+            let (data, detected, opts) = self.prepare(&request.body, filename)?;
+            format = Some(detected);
+            let body = render(&data, detected, filename, self.args, &opts, mode, false)?;
+            Ok((mode, body, opts.warnings.take()))
+        });
+        let (mode, text, warnings) = match result {
+            Ok((mode, Body::Text(text), warnings)) => (mode, text, warnings),
+            Ok(_) => unreachable!("only terminal output is pretty-printed or paged"),
+            Err(e) => {
+                let object = error_json(filename, format, &e);
+                return serve::Response::json(serve::error_status(&e), &object);
+            }
+        };
+        if to == "json" {
+            let warnings = warnings.join("\n");
+            let object = json::object(&[
+                ("file", Some(filename)),
+                ("format", format.map(Format::name)),
+                ("markdown", Some(&text)),
+                (
+                    "warnings",
+                    Some(warnings.as_str()).filter(|w| !w.is_empty()),
+                ),
+            ]);
+            return serve::Response::json(200, &object);
+        }
+        let content_type = match mode {
+            Mode::Markdown => "text/markdown; charset=utf-8",
+            Mode::Csv => "text/csv; charset=utf-8",
+            Mode::Xml => "application/xml",
+            _ => "text/plain; charset=utf-8",
+        };
+        serve::Response::new(200, content_type, text)
+    }
+
+    /// Decrypt `data` if it is an encrypted package and a password was
+    /// given, detect its format, and build its options.
+    fn prepare<'d>(
+        &self,
+        data: &'d [u8],
+        filename: &str,
+    ) -> error::Result<(Cow<'d, [u8]>, Format, Options)> {
+        let args = self.args;
+        // An encrypted .docx, .xlsx, or .pptx is a package inside an OLE2
+        // wrapper; with a password, the package replaces its wrapper
+        let data = match self.password.as_deref() {
+            Some(password) if crypto::is_encrypted_package(data) => {
+                Cow::Owned(crypto::decrypt_package(data, password)?)
+            }
+            _ => Cow::Borrowed(data),
+        };
+        let format = match args.format {
+            Some(format) => format,
+            None => detect_format_or(&data, extension_format(filename).or(args.assume_ext))?,
+        };
+
+        let mut opts = self.config.options(format.name(), &args.opts);
+        opts.pages.clone_from(&args.pages);
+        opts.sheet.clone_from(&args.sheet);
+        opts.password.clone_from(&self.password);
+        opts.encoding = args.encoding;
+        Ok((data, format, opts))
+    }
+
+    /// Extract each supported document inside an archive, named
+    /// `archive/member`. Members that are not documents are skipped.
+    fn archive(
+        &self,
+        data: &[u8],
+        kind: archive::Kind,
+        filename: &str,
+        emit: &mut dyn FnMut(Event),
+    ) {
+        let mut found = 0;
+        let result = archive::for_each_entry(data, kind, MAX_INPUT_SIZE, |name, member| {
+            let name = format!("{filename}/{name}");
+            match member {
+                Ok(member)
+                    if detect_format_or(&member, extension_format(&name))
+                        .is_err_and(|e| e.code() == "unsupported") => {}
+                Ok(member) => {
+                    found += 1;
+                    emit(self.document(&member, &name, !self.args.no_filename));
+                }
+                Err(e) => {
+                    found += 1;
+                    emit(Event::Error(name, e));
+                }
+            }
+        });
+        let err = match result {
+            Err(e) => e,
+            Ok(()) if found == 0 => {
+                BatdocError::Unsupported("archive contains no supported documents".into())
+            }
+            Ok(()) => return,
+        };
+        emit(Event::Error(filename.to_string(), err));
+    }
+}
+
+/// `batdoc diff OLD NEW`: print the differences between the text of two
+/// documents. Returns 0 when they are the same, 1 when they differ, and 2
+/// on error, like diff(1).
+fn run_diff(extractor: &Extractor) -> i32 {
+    let args = extractor.args;
+    let mut sides = Vec::new();
+    for path in &args.files {
+        let (filename, result) = read_input(path, args.allow_remote);
+        let mut format = None;
+        let blocks = result.and_then(|buf| {
+            if buf.len() > MAX_INPUT_SIZE {
+                return Err(BatdocError::too_large(Some(buf.len()), MAX_INPUT_SIZE));
+            }
+            let (data, detected, opts) = extractor.prepare(&buf, &filename)?;
+            format = Some(detected);
+            let blocks = document::extract_blocks(&data, detected, &opts);
+            if args.verbose > 1 {
+                for note in opts.notes.take() {
+                    report_message(args.errors, &filename, detected, "info", &note);
+                }
+            }
+            if !args.quiet {
+                for warning in opts.warnings.take() {
+                    report_message(args.errors, &filename, detected, "warning", &warning);
+                }
+            }
+            blocks
+        });
+        match blocks {
+            Ok(blocks) => sides.push((filename, diff::lines(&blocks))),
+            Err(e) => {
+                report_error(args.errors, &filename, format, &e);
+                return 2;
+            }
+        }
+    }
+    let [(old_name, old), (new_name, new)] = &sides[..] else {
+        unreachable!("cli::parse_args checks for two files")
+    };
+
+    let color = extractor.is_tty && std::env::var_os("NO_COLOR").is_none();
+    let text = diff::render(old_name, old, new_name, new, args.word_diff, color);
+    let mut stdout = io::stdout();
+    if let Err(e) = stdout
+        .write_all(text.as_bytes())
+        .and_then(|()| stdout.flush())
+    {
+        eprintln!("batdoc: {e}");
+        return 2;
+    }
+    i32::from(!text.is_empty())
+}
+
+/// `batdoc serve`: convert documents posted over HTTP until killed.
+/// Returns only if the address cannot be listened on.
+fn run_serve(extractor: &Extractor) -> i32 {
+    let args = extractor.args;
+    let addr = args.listen.as_deref().unwrap_or(serve::DEFAULT_ADDR);
+    let listener = match TcpListener::bind(addr) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("batdoc: {addr}: {e}");
+            return exit::FAILED;
+        }
+    };
+    if !args.quiet {
+        let bound = listener
+            .local_addr()
+            .map_or_else(|_| addr.to_string(), |bound| bound.to_string());
+        eprintln!("batdoc: listening on http://{bound}/");
+    }
+    serve::run(
+        &listener,
+        workers(args.jobs),
+        MAX_INPUT_SIZE,
+        args.verbose > 0,
+        &|request| extractor.respond(request),
+    );
+    exit::OK
+}
+
+/// The number of inputs or requests handled at a time for `--jobs`, where
+/// 0 means one per CPU.
+fn workers(jobs: usize) -> usize {
+    match jobs {
+        0 => std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get),
+        jobs => jobs,
+    }
+}
+
+/// Where the rendered documents of one invocation are written, in order.
+struct Session<'a> {
+    args: &'a Args,
+    stdout: Output<'a>,
+    /// Inputs written so far to each --output file, for separators
+    written: HashMap<PathBuf, usize>,
+    /// Documents attempted so far on stdout, for separators
+    documents: usize,
+    /// Failures so far, for the exit status
+    status: Status,
+}
+
+impl Session<'_> {
+    /// Write the total row of `--stats`, if any, and return how the run
+    /// went.
+    fn finish(mut self) -> Status {
+        if let Some(total) = self.stdout.tally.total() {
+            let out = &mut self.stdout.writer;
+            if let Err(e) = out.write_all(total.as_bytes()).and_then(|()| out.flush()) {
+                eprintln!("batdoc: {e}");
+                self.status.fail(&e.into());
+            }
+        }
+        self.status
+    }
+
+    /// Write out one event, reporting and recording any error. Returns
+    /// whether it succeeded.
+    fn emit(&mut self, event: Event) -> bool {
+        let args = self.args;
+        let Entry {
+            filename,
+            format,
+            header,
+            body,
+            warnings,
+            notes,
+        } = match event {
+            Event::Document(entry) => entry,
+            Event::Error(filename, e) => {
+                report_error(args.errors, &filename, None, &e);
+                self.status.fail(&e);
+                return false;
+            }
+        };
+        for note in &notes {
+            report_message(args.errors, &filename, format, "info", note);
+        }
+        let result = match &args.output {
+            None => {
+                let separate = self.documents > 0;
+                self.documents += 1;
+                write_entry(&filename, header, body, args, &mut self.stdout, separate)
+            }
+            Some(template) => {
+                let path = expand_output(template, &filename);
+                let count = self.written.entry(path.clone()).or_insert(0);
+                *count += 1;
+                let append = *count > 1;
+                open_output(&path, append, args.mode).and_then(|mut out| {
+                    write_entry(&filename, header, body, args, &mut out, append)
+                })
+            }
+        };
+        if !args.quiet {
+            for warning in &warnings {
+                report_message(args.errors, &filename, format, "warning", warning);
+            }
+        }
+        if let Err(e) = result {
+            report_error(args.errors, &filename, Some(format), &e);
+            self.status.fail(&e);
+            return false;
+        }
+        true
+    }
+}
+
+/// [`write_body`], preceded by a `==> name <==` line when `header` is set
+/// and the output is text written as is: bat shows its own file header,
+/// XML and summaries carry the name already, and CSV has no room for one.
+fn write_entry(
+    filename: &str,
+    header: bool,
+    body: error::Result<Body>,
+    args: &Args,
+    out: &mut Output,
+    needs_separator: bool,
+) -> error::Result<()> {
+    let shown_by_bat = out.is_tty
+        && match out.mode {
+            Mode::Markdown | Mode::Auto | Mode::Summary => true,
+            Mode::Plain => args.pager_plain,
+            Mode::Org
+            | Mode::Csv
+            | Mode::Xml
+            | Mode::Metadata
+            | Mode::Stats
+            | Mode::Grep
+            | Mode::List
+            | Mode::Chunk => false,
+        };
+    let text = matches!(
+        out.mode,
+        Mode::Plain | Mode::Markdown | Mode::Auto | Mode::Org | Mode::Metadata | Mode::List
+    );
+    if !header || shown_by_bat || !text {
+        return write_body(body, filename, args, out, needs_separator);
+    }
+    if needs_separator && !out.is_tty {
+        out.writer.write_all(b"\n")?;
+    }
+    writeln!(out.writer, "==> {filename} <==")?;
+    write_body(body, filename, args, out, false)
+}
+
+/// The format implied by `filename`'s extension, if any.
+fn extension_format(filename: &str) -> Option<Format> {
+    Path::new(filename)
+        .extension()
+        .and_then(|ext| Format::from_extension(&ext.to_string_lossy()))
+}
+
+/// Read one input argument: `-` (stdin), an `http(s)://` URL (downloaded
+/// when `allow_remote`), a `file://` URL, or a path. Returns the name to
+/// show in messages along with the contents.
+fn read_input(path: &OsStr, allow_remote: bool) -> (String, error::Result<Vec<u8>>) {
+    if path == "-" {
+        let mut buf = Vec::new();
+        let result = io::stdin().read_to_end(&mut buf).map(|_| buf);
+        return ("stdin".to_string(), result.map_err(Into::into));
+    }
+    if let Some(url) = path.to_str().filter(|p| remote::is_remote(p)) {
+        let result = if allow_remote {
+            remote::fetch(url, MAX_INPUT_SIZE)
+        } else {
+            Err(BatdocError::Usage(
+                "remote URLs require --allow-remote".into(),
+            ))
+        };
+        return (url.to_string(), result);
+    }
+    let local = path.to_str().and_then(remote::file_url_path);
+    let path = local.as_deref().unwrap_or_else(|| Path::new(path));
+    (
+        platform::display_path(path),
+        std::fs::read(path).map_err(Into::into),
+    )
+}
+
+/// Read a `--files-from` list; `-` is stdin.
+fn read_file_list(list: &OsStr, null: bool) -> io::Result<Vec<OsString>> {
+    let data = if list == "-" {
+        let mut buf = Vec::new();
+        io::stdin().read_to_end(&mut buf)?;
+        buf
+    } else {
+        std::fs::read(list)?
+    };
+    Ok(split_file_list(&data, null))
+}
+
+/// The paths in a `--files-from` list: one per line, or per NUL byte with
+/// `null`. Blank entries are skipped, and so is the `\r` of a CRLF line
+/// ending.
+fn split_file_list(data: &[u8], null: bool) -> Vec<OsString> {
+    let separator = if null { b'\0' } else { b'\n' };
+    data.split(|&b| b == separator)
+        .map(|entry| match entry {
+            [rest @ .., b'\r'] if !null => rest,
+            _ => entry,
+        })
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| platform::path_from_bytes(entry.to_vec()))
+        .collect()
+}
+
+/// Print an error for `filename` to stderr, as text or as a JSON object
+/// with a stable error code (`--errors json`).
+fn report_error(errors: ErrorFormat, filename: &str, format: Option<Format>, err: &BatdocError) {
+    match errors {
+        ErrorFormat::Text => eprintln!("batdoc: {filename}: {err}"),
+        ErrorFormat::Json => eprintln!("{}", error_json(filename, format, err)),
+    }
+}
+
+/// An error as a JSON object, for `--errors json` and `batdoc serve`.
+fn error_json(filename: &str, format: Option<Format>, err: &BatdocError) -> String {
+    json::object(&[
+        ("file", Some(filename)),
+        ("format", format.map(Format::name)),
+        ("kind", Some(err.code())),
+        ("message", Some(&err.to_string())),
+    ])
+}
+
+/// Print a message about a document that was still extracted: a
+/// `warning`, such as text that had to be salvaged, or an `info` note on
+/// how it was read (`-v`). `kind` is also the JSON `kind`.
+fn report_message(errors: ErrorFormat, filename: &str, format: Format, kind: &str, message: &str) {
+    match errors {
+        ErrorFormat::Text => eprintln!("batdoc: {filename}: {kind}: {message}"),
+        ErrorFormat::Json => eprintln!(
+            "{}",
+            json::object(&[
+                ("file", Some(filename)),
+                ("format", Some(format.name())),
+                ("kind", Some(kind)),
+                ("message", Some(message)),
+            ])
+        ),
+    }
+}
+
+/// Where an input's rendering goes: stdout, an `--output` file, or the
+/// text kept for `--copy`.
+struct Output<'a> {
+    writer: Box<dyn Write + 'a>,
+    /// Whether the destination is a terminal; only ever true for stdout.
+    is_tty: bool,
+    mode: Mode,
+    /// Counts so far in [`Mode::Stats`].
+    tally: stats::Tally,
+}
+
+/// Expand the `--output` template for one input: `{stem}`, `{name}`, and
+/// `{ext}` are replaced by the input's file stem, file name, and extension.
+fn expand_output(template: &str, input: &str) -> PathBuf {
+    let path = Path::new(input);
+    let part = |s: Option<&std::ffi::OsStr>| {
+        s.map_or_else(String::new, |s| s.to_string_lossy().into_owned())
+    };
+    PathBuf::from(
+        template
+            .replace("{stem}", &part(path.file_stem()))
+            .replace("{name}", &part(path.file_name()))
+            .replace("{ext}", &part(path.extension())),
+    )
+}
+
+/// Open an `--output` file, truncating it for the first input written to
+/// it and appending for later ones.
+fn open_output(path: &Path, append: bool, mode: Mode) -> error::Result<Output<'static>> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", platform::display_path(path))))?;
+    Ok(Output {
+        writer: Box::new(io::BufWriter::new(file)),
+        is_tty: false,
+        mode: output_mode(path, mode),
+        tally: stats::Tally::default(),
+    })
+}
+
+/// The mode of an `--output` file: in auto mode, the output format
+/// follows the file extension.
+fn output_mode(path: &Path, mode: Mode) -> Mode {
+    if mode == Mode::Auto {
+        mode_for_path(path)
+    } else {
+        mode
+    }
+}
+
+/// Output mode implied by an output file's extension; plain text unless
+/// it names a markup format.
+fn mode_for_path(path: &Path) -> Mode {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("md" | "markdown") => Mode::Markdown,
+        Some("org") => Mode::Org,
+        Some("csv") => Mode::Csv,
+        Some("xml") => Mode::Xml,
+        _ => Mode::Plain,
+    }
+}
+
+/// Extract a document and render it in `mode`, for a terminal when
+/// `is_tty`. Nothing is written except `--csv-dir` files.
+fn render(
+    data: &[u8],
+    format: Format,
+    filename: &str,
+    args: &Args,
+    opts: &Options,
+    mode: Mode,
+    is_tty: bool,
+) -> error::Result<Body> {
+    Ok(match mode {
+        Mode::Plain => {
+            let text = extract_plain(data, format, opts)?;
+            if is_tty && args.pager_plain {
+                Body::Paged(text)
+            } else {
+                Body::Text(text)
+            }
+        }
+        Mode::Markdown => {
+            let md = render_markdown(data, format, opts)?;
+            if is_tty {
+                Body::Pretty(md)
+            } else {
+                Body::Text(md)
+            }
+        }
+        Mode::Auto => {
+            if is_tty {
+                Body::Pretty(render_markdown(data, format, opts)?)
+            } else {
+                Body::Text(extract_plain(data, format, opts)?)
+            }
+        }
+        Mode::Org => {
+            let md = extract_markdown(data, format, opts)?;
+            Body::Text(org::markdown_to_org(&md))
+        }
+        Mode::Xml => {
+            let blocks = document::extract_blocks(data, format, opts)?;
+            Body::Text(xml::render(&blocks, format, filename))
+        }
+        Mode::Summary => {
+            let blocks = document::extract_blocks(data, format, opts)?;
+            let meta = metadata::read(data, format, opts.encoding);
+            let md = summary::render(&blocks, &meta, format, filename, args.summary_words);
+            if is_tty {
+                Body::Pretty(md)
+            } else {
+                Body::Text(md)
+            }
+        }
+        Mode::Metadata => Body::Text(metadata::read(data, format, opts.encoding).to_text()),
+        Mode::List => Body::Text(listing::render(data, format, opts)?),
+        Mode::Grep => {
+            let blocks = document::extract_blocks(data, format, opts)?;
+            let pattern = args.grep.as_deref().unwrap_or_default();
+            let color = is_tty && std::env::var_os("NO_COLOR").is_none();
+            Body::Text(grep::search(&blocks, filename, pattern, color))
+        }
+        Mode::Chunk => {
+            let blocks = document::extract_blocks(data, format, opts)?;
+            Body::Text(chunk::render(&blocks, filename, args.chunk_size))
+        }
+        Mode::Stats => {
+            let blocks = document::extract_blocks(data, format, opts)?;
+            Body::Stats(stats::Stats::new(&blocks, format))
+        }
+        Mode::Csv => Body::Text(export_csv(
+            data,
+            format,
+            opts,
+            filename,
+            args.csv_dir.as_deref(),
+        )?),
+    })
+}
+
+/// A large .docx written as it is extracted rather than extracted whole
+/// first, when its plain text or markdown goes to a file or pipe uncut
+/// and unescaped; so is a large .xlsx exported as CSV, or as plain text
+/// to a file or pipe uncut by `--max-rows` and `--max-cols` and without
+/// `--table` or `--comments`. `None` for any other document or output.
+fn stream(
+    data: &[u8],
+    format: Format,
+    filename: &str,
+    args: &Args,
+    opts: &Options,
+    mode: Mode,
+    is_tty: bool,
+) -> Option<Stream> {
+    let uncut = args.head.is_none() && args.tail.is_none() && args.preview.is_none();
+    let text = !is_tty
+        && matches!(mode, Mode::Auto | Mode::Plain | Mode::Markdown)
+        && uncut
+        && !opts.escape_html;
+    let streamed = match format {
+        Format::Docx => text && docx::is_large(data),
+        Format::Xlsx if mode == Mode::Csv => xlsx::is_large(data),
+        Format::Xlsx => {
+            text && mode != Mode::Markdown
+                && !opts.table
+                && opts.comments == Comments::None
+                && opts.max_rows.is_none()
+                && opts.max_cols.is_none()
+                && xlsx::is_large(data)
+        }
+        _ => false,
+    };
+    if !streamed {
+        return None;
+    }
+    let (data, opts) = (data.to_vec(), opts.clone());
+    if format == Format::Xlsx {
+        if mode == Mode::Csv {
+            let (filename, dir) = (filename.to_string(), args.csv_dir.clone());
+            return Some(Box::new(move |out| {
+                stream_csv(&data, &opts, &filename, dir.as_deref(), out)
+            }));
+        }
+        return Some(Box::new(move |out| xlsx::stream_plain(&data, &opts, out)));
+    }
+    Some(Box::new(move |out| {
+        if mode == Mode::Markdown {
+            docx::stream_markdown(&data, &opts, out)
+        } else {
+            docx::stream_plain(&data, &opts, out)
+        }
+    }))
+}
+
+/// Write a rendered document to `out`, after a blank line separating it
+/// from the previous one when `needs_separator`.
+fn write_body(
+    body: error::Result<Body>,
+    filename: &str,
+    args: &Args,
+    out: &mut Output,
+    needs_separator: bool,
+) -> error::Result<()> {
+    // CSV output goes to files or is a single sheet, and stats, grep, and
+    // chunk results are lines naming their file; no separator needed
+    if needs_separator
+        && !out.is_tty
+        && !matches!(out.mode, Mode::Csv | Mode::Stats | Mode::Grep | Mode::Chunk)
+    {
+        out.writer.write_all(b"\n")?;
+    }
+
+    match body? {
+        Body::Text(text) => out.writer.write_all(text.as_bytes())?,
+        Body::Pretty(md) => pretty_print(&md, filename, args)?,
+        Body::Paged(text) => page_plain(&text, args)?,
+        Body::Stats(stats) => {
+            let row = out.tally.record(&stats, filename);
+            out.writer.write_all(row.as_bytes())?;
+        }
+        Body::Stream(stream) => stream(&mut out.writer)?,
+    }
+
+    out.writer.flush()?;
+    Ok(())
+}
+
+/// Export spreadsheet sheets as CSV: one file per non-empty sheet in
+/// `dir`, or the single non-empty sheet returned as text.
+fn export_csv(
+    data: &[u8],
+    format: Format,
+    opts: &Options,
+    filename: &str,
+    dir: Option<&Path>,
+) -> error::Result<String> {
+    let sheets = match format {
+        Format::Xls => xls::extract_sheets(data, opts)?,
+        Format::Xlsx => xlsx::extract_sheets(data, opts)?,
+        _ => {
+            return Err(BatdocError::Unsupported(
+                "CSV export requires a spreadsheet (.xls or .xlsx)".into(),
+            ))
+        }
+    };
+    let sheets: Vec<&sheet::Sheet> = sheets
+        .iter()
+        .filter(|s| !sheet::skip_empty_sheet(s))
+        .collect();
+
+    if let Some(dir) = dir {
+        let names: Vec<&str> = sheets.iter().map(|s| s.name.as_str()).collect();
+        for (sheet, path) in sheets.iter().zip(csv_paths(dir, filename, &names)?) {
+            std::fs::write(path, sheet::render_csv(sheet))?;
+        }
+        return Ok(String::new());
+    }
+
+    match sheets.as_slice() {
+        [] => Ok(String::new()),
+        [sheet] => Ok(sheet::render_csv(sheet)),
+        _ => Err(too_many_sheets(sheets.len())),
+    }
+}
+
+/// [`export_csv`] for a large .xlsx, written a row at a time to the files
+/// in `dir` or to `out`.
+fn stream_csv(
+    data: &[u8],
+    opts: &Options,
+    filename: &str,
+    dir: Option<&Path>,
+    out: &mut dyn Write,
+) -> error::Result<()> {
+    let mut sheets = xlsx::CsvSheets::open(data, opts)?;
+    let count = sheets.names().len();
+
+    if let Some(dir) = dir {
+        for (i, path) in csv_paths(dir, filename, &sheets.names())?
+            .into_iter()
+            .enumerate()
+        {
+            let mut file = io::BufWriter::new(std::fs::File::create(path)?);
+            sheets.write(i, &mut file)?;
+            file.flush()?;
+        }
+        return Ok(());
+    }
+
+    match count {
+        0 => Ok(()),
+        1 => sheets.write(0, out),
+        _ => Err(too_many_sheets(count)),
+    }
+}
+
+/// Create `dir` for the CSV files of the sheets named `names` and return
+/// their paths, named after `filename`.
+fn csv_paths(dir: &Path, filename: &str, names: &[&str]) -> error::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let stem = Path::new(filename)
+        .file_stem()
+        .map_or_else(|| filename.into(), |s| s.to_string_lossy());
+    Ok(sheet::csv_file_names(&stem, names)
+        .into_iter()
+        .map(|name| dir.join(name))
+        .collect())
+}
+
+/// The error for exporting a workbook of `count` sheets to one CSV.
+fn too_many_sheets(count: usize) -> BatdocError {
+    BatdocError::Usage(format!(
+        "workbook has {count} sheets; use --csv-dir DIR to write one CSV per sheet"
+    ))
+}
+
+fn extract_plain(data: &[u8], format: Format, opts: &Options) -> error::Result<String> {
+    match format {
+        Format::Doc => doc::extract_plain(data, opts),
+        Format::Xls => xls::extract_plain(data, opts),
+        Format::Docx => docx::extract_plain(data, opts),
+        Format::Xlsx => xlsx::extract_plain(data, opts),
+        Format::Pptx => pptx::extract_plain(data, opts),
+        Format::Pdf => pdf::extract_plain(data, opts),
+    }
+}
+
+/// Markdown for output as markdown, with raw HTML escaped if requested.
+fn render_markdown(data: &[u8], format: Format, opts: &Options) -> error::Result<String> {
+    let md = extract_markdown(data, format, opts)?;
+    Ok(if opts.escape_html {
+        markup::escape_raw_html(&md)
+    } else {
+        md
+    })
+}
+
+fn extract_markdown(data: &[u8], format: Format, opts: &Options) -> error::Result<String> {
+    match format {
+        Format::Doc => doc::extract_markdown(data, opts),
+        Format::Xls => xls::extract_markdown(data, opts),
+        Format::Docx => docx::extract_markdown(data, opts),
+        Format::Xlsx => xlsx::extract_markdown(data, opts),
+        Format::Pptx => pptx::extract_markdown(data, opts),
+        Format::Pdf => pdf::extract_markdown(data, opts),
+    }
+}
+
+const fn paging_mode(paging: Paging) -> bat::PagingMode {
+    match paging {
+        Paging::Auto => bat::PagingMode::QuitIfOneScreen,
+        Paging::Always => bat::PagingMode::Always,
+        Paging::Never => bat::PagingMode::Never,
+    }
+}
+
+const fn wrapping_mode(wrap: Wrap) -> bat::WrappingMode {
+    match wrap {
+        Wrap::Auto => bat::WrappingMode::NoWrapping(false),
+        Wrap::Character => bat::WrappingMode::Character,
+        Wrap::Never => bat::WrappingMode::NoWrapping(true),
+    }
+}
+
+/// A bat printer set up with the `--wrap`, `--paging`, and `--pager`
+/// options. Without `--pager`, bat takes the pager from `$BAT_PAGER` or
+/// `$PAGER`, falling back to `less`.
+fn printer(args: &Args) -> PrettyPrinter<'_> {
+    let mut printer = PrettyPrinter::new();
+    printer
+        .wrapping_mode(wrapping_mode(args.wrap))
+        .paging_mode(paging_mode(args.paging));
+    if let Some(pager) = &args.pager {
+        printer.pager(pager);
+    }
+    printer
+}
+
+/// Show markdown through bat with the `--theme` and `--style` options.
+fn pretty_print(content: &str, filename: &str, args: &Args) -> error::Result<()> {
+    let input = Input::from_bytes(content.as_bytes())
+        .name(filename)
+        .title(filename);
+
+    let theme = args
+        .theme
+        .clone()
+        .unwrap_or_else(|| std::env::var("BAT_THEME").unwrap_or_else(|_| "ansi".to_string()));
+    let style = args.style;
+
+    printer(args)
+        .input(input)
+        .language("Markdown")
+        .theme(&theme)
+        .header(style.header)
+        .line_numbers(style.numbers)
+        .grid(style.grid)
+        .rule(style.rule)
+        .snip(style.snip)
+        .colored_output(true)
+        .true_color(true)
+        .print()
+        .map_err(|e| BatdocError::Render(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Write plain text to the terminal through bat's pager, without
+/// highlighting or decorations (`--pager-plain`).
+fn page_plain(content: &str, args: &Args) -> error::Result<()> {
+    printer(args)
+        .input(Input::from_bytes(content.as_bytes()))
+        .header(false)
+        .line_numbers(false)
+        .grid(false)
+        .colored_output(false)
+        .print()
+        .map_err(|e| BatdocError::Render(e.to_string()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── expand_output ────────────────────────────────────────────
+
+    #[test]
+    fn expand_output_placeholders() {
+        assert_eq!(
+            expand_output("out/{stem}.md", "docs/report.docx"),
+            PathBuf::from("out/report.md")
+        );
+        assert_eq!(
+            expand_output("{name}.{ext}.txt", "a.b.xlsx"),
+            PathBuf::from("a.b.xlsx.xlsx.txt")
+        );
+        assert_eq!(expand_output("all.md", "x.doc"), PathBuf::from("all.md"));
+        assert_eq!(
+            expand_output("{stem}.txt", "stdin"),
+            PathBuf::from("stdin.txt")
+        );
+    }
+
+    // ── split_file_list ──────────────────────────────────────────
+
+    #[test]
+    fn file_list_lines_and_nuls() {
+        assert_eq!(
+            split_file_list(b"a.docx\r\n\nb c.pdf\n", false),
+            ["a.docx", "b c.pdf"]
+        );
+        assert_eq!(
+            split_file_list(b"line\nbreak.doc\0x.xls\0", true),
+            ["line\nbreak.doc", "x.xls"]
+        );
+        assert!(split_file_list(b"", false).is_empty());
+    }
+
+    // ── head / tail ──────────────────────────────────────────────
+
+    #[test]
+    fn head_lines() {
+        assert_eq!(head("a\nb\nc\n".into(), 2), "a\nb\n");
+        assert_eq!(head("a\nb\nc".into(), 5), "a\nb\nc");
+        assert_eq!(head("a\nb\n".into(), 0), "");
+    }
+
+    #[test]
+    fn tail_lines() {
+        assert_eq!(tail("a\nb\nc\n".into(), 2), "b\nc\n");
+        assert_eq!(tail("a\nb\nc".into(), 1), "c");
+        assert_eq!(tail("a\nb\n".into(), 5), "a\nb\n");
+        assert_eq!(tail("a\nb\n".into(), 0), "");
+    }
+
+    // ── truncate ─────────────────────────────────────────────────
+
+    #[test]
+    fn truncate_at_line_break() {
+        assert_eq!(truncate("one\ntwo\nthree\n".into(), 10), "one\ntwo\n");
+        assert_eq!(truncate("one\ntwo\n".into(), 8), "one\ntwo\n");
+        assert_eq!(truncate("long line".into(), 4), "long");
+        // never splits a character
+        assert_eq!(truncate("ééé".into(), 3), "é");
+    }
+
+    // ── Status ───────────────────────────────────────────────────
+
+    #[test]
+    fn status_names_a_shared_cause() {
+        let mut status = Status::default();
+        assert_eq!(status.code(), exit::OK);
+        status.fail(&BatdocError::Encrypted);
+        status.fail(&BatdocError::WrongPassword);
+        assert_eq!(status.code(), exit::ENCRYPTED);
+        status.fail(&BatdocError::Unsupported("nope".into()));
+        assert_eq!(status.code(), exit::FAILED);
+
+        let mut status = Status::default();
+        status.fail(&BatdocError::Unsupported("nope".into()));
+        assert_eq!(status.code(), exit::UNSUPPORTED);
+    }
+
+    // ── mode_for_path ────────────────────────────────────────────
+
+    #[test]
+    fn mode_from_extension() {
+        assert_eq!(mode_for_path(Path::new("a.md")), Mode::Markdown);
+        assert_eq!(mode_for_path(Path::new("a.ORG")), Mode::Org);
+        assert_eq!(mode_for_path(Path::new("a.csv")), Mode::Csv);
+        assert_eq!(mode_for_path(Path::new("a.xml")), Mode::Xml);
+        assert_eq!(mode_for_path(Path::new("a.txt")), Mode::Plain);
+        assert_eq!(mode_for_path(Path::new("out")), Mode::Plain);
+    }
+}
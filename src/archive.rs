@@ -0,0 +1,321 @@
+//! Archive containers: plain ZIP files and (optionally gzipped) tarballs.
+//!
+//! An archive that is not itself a document (a ZIP without OOXML parts, or
+//! a ustar/GNU/pax tarball) is opened and each member is handed back in
+//! archive order, so email attachment bundles and export archives can be
+//! extracted in one go. Members are read one at a time and each is held to
+//! the same size limit as a standalone input.
+
+use std::io::{self, Read};
+
+use crate::error::{BatdocError, Result};
+use crate::format::{detect_format, ZIP_MAGIC};
+
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+const TAR_BLOCK: usize = 512;
+
+/// Kind of archive container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+/// Detect an archive container. OOXML documents are ZIP files too, but
+/// are not archives; neither are corrupt ZIPs, which are left for the
+/// document path to report.
+pub(crate) fn detect(data: &[u8]) -> Option<Kind> {
+    if data.starts_with(&ZIP_MAGIC) {
+        return matches!(detect_format(data), Err(BatdocError::Unsupported(_)))
+            .then_some(Kind::Zip);
+    }
+    if is_tar_header(data) {
+        return Some(Kind::Tar);
+    }
+    if data.starts_with(&GZIP_MAGIC) {
+        let mut head = Vec::with_capacity(TAR_BLOCK);
+        let _ = flate2::read::MultiGzDecoder::new(data)
+            .take(TAR_BLOCK as u64)
+            .read_to_end(&mut head);
+        return is_tar_header(&head).then_some(Kind::TarGz);
+    }
+    None
+}
+
+/// Whether `block` starts with a ustar (POSIX or GNU) header.
+fn is_tar_header(block: &[u8]) -> bool {
+    block.len() >= TAR_BLOCK && &block[257..262] == b"ustar"
+}
+
+/// Call `f` with the name and contents of each regular file in the
+/// archive, in order. A member larger than `max_size` is passed as a
+/// [`TooLarge`](BatdocError::TooLarge) error instead. Errors in the
+/// archive structure itself stop the iteration.
+pub(crate) fn for_each_entry(
+    data: &[u8],
+    kind: Kind,
+    max_size: usize,
+    mut f: impl FnMut(&str, Result<Vec<u8>>),
+) -> Result<()> {
+    match kind {
+        Kind::Zip => zip_entries(data, max_size, &mut f),
+        Kind::Tar => tar_entries(data, max_size, &mut f),
+        Kind::TarGz => tar_entries(flate2::read::MultiGzDecoder::new(data), max_size, &mut f),
+    }
+}
+
+fn zip_entries(
+    data: &[u8],
+    max_size: usize,
+    f: &mut impl FnMut(&str, Result<Vec<u8>>),
+) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(io::Cursor::new(data))?;
+    for i in 0..archive.len() {
+        let name = archive.name_for_index(i).unwrap_or_default().to_string();
+        let mut entry = match archive.by_index(i) {
+            Ok(entry) => entry,
+            Err(e) => {
+                // Encrypted or unsupported compression
+                f(&name, Err(e.into()));
+                continue;
+            }
+        };
+        if entry.is_dir() {
+            continue;
+        }
+        let size = usize::try_from(entry.size()).unwrap_or(usize::MAX);
+        if size > max_size {
+            f(&name, Err(BatdocError::too_large(Some(size), max_size)));
+            continue;
+        }
+        // The declared size may lie, so cap the read as well
+        f(&name, read_capped(&mut entry, max_size));
+    }
+    Ok(())
+}
+
+fn tar_entries(
+    mut reader: impl Read,
+    max_size: usize,
+    f: &mut impl FnMut(&str, Result<Vec<u8>>),
+) -> Result<()> {
+    // Name from a preceding GNU long-name or pax header, if any
+    let mut long_name: Option<String> = None;
+    let mut header = [0u8; TAR_BLOCK];
+    loop {
+        if !read_block(&mut reader, &mut header)? || header.iter().all(|&b| b == 0) {
+            return Ok(());
+        }
+        let size = tar_size(&header[124..136])
+            .ok_or_else(|| BatdocError::Document("invalid tar entry size".into()))?;
+        let padded = size.div_ceil(TAR_BLOCK as u64) * TAR_BLOCK as u64;
+        let mut body = (&mut reader).take(padded);
+
+        match header[156] {
+            b'0' | b'\0' | b'7' => {
+                let name = long_name.take().unwrap_or_else(|| header_name(&header));
+                let name = name.strip_prefix("./").unwrap_or(&name);
+                let result = match usize::try_from(size) {
+                    Ok(n) if n <= max_size => read_capped(&mut (&mut body).take(size), max_size),
+                    _ => Err(BatdocError::too_large(usize::try_from(size).ok(), max_size)),
+                };
+                f(name, result);
+            }
+            b'L' if size <= 64 * 1024 => {
+                let name = read_capped(&mut (&mut body).take(size), max_size)?;
+                let name = String::from_utf8_lossy(&name);
+                long_name = Some(name.trim_end_matches('\0').to_string());
+            }
+            b'x' if size <= 64 * 1024 => {
+                let records = read_capped(&mut (&mut body).take(size), max_size)?;
+                long_name = pax_path(&records).or(long_name);
+            }
+            _ => {}
+        }
+        io::copy(&mut body, &mut io::sink())?;
+    }
+}
+
+/// Read one 512-byte block; `false` at a clean end of input.
+fn read_block(reader: &mut impl Read, block: &mut [u8; TAR_BLOCK]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < TAR_BLOCK {
+        match reader.read(&mut block[filled..])? {
+            0 if filled == 0 => return Ok(false),
+            0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            n => filled += n,
+        }
+    }
+    Ok(true)
+}
+
+/// Entry size: NUL/space-terminated octal, or GNU base-256 when the high
+/// bit of the first byte is set.
+fn tar_size(field: &[u8]) -> Option<u64> {
+    if field[0] & 0x80 != 0 {
+        return field[1..]
+            .iter()
+            .try_fold(u64::from(field[0] & 0x7F), |n, &b| {
+                n.checked_mul(256).map(|n| n | u64::from(b))
+            });
+    }
+    let digits = std::str::from_utf8(field).ok()?;
+    let digits = digits.trim_matches(|c| c == '\0' || c == ' ');
+    if digits.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(digits, 8).ok()
+}
+
+/// Member path from a ustar header: `prefix/name`.
+fn header_name(header: &[u8; TAR_BLOCK]) -> String {
+    let field = |range: std::ops::Range<usize>| {
+        let bytes = &header[range];
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..end]).into_owned()
+    };
+    let name = field(0..100);
+    let prefix = field(345..500);
+    if prefix.is_empty() {
+        name
+    } else {
+        format!("{prefix}/{name}")
+    }
+}
+
+/// The `path` value from pax extended header records (`LEN path=VALUE\n`).
+fn pax_path(records: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(records);
+    text.lines()
+        .filter_map(|line| line.split_once(' ')?.1.strip_prefix("path="))
+        .next_back()
+        .map(str::to_string)
+}
+
+/// Read all of `reader`, failing if it holds more than `max_size` bytes.
+fn read_capped(reader: &mut impl Read, max_size: usize) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    reader.take(max_size as u64 + 1).read_to_end(&mut data)?;
+    if data.len() > max_size {
+        return Err(BatdocError::too_large(None, max_size));
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// A tar archive of `(name, contents)` members.
+    fn tar(members: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (name, contents) in members {
+            let mut header = [0u8; TAR_BLOCK];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            let size = format!("{:011o}\0", contents.len());
+            header[124..136].copy_from_slice(size.as_bytes());
+            header[156] = b'0';
+            header[257..263].copy_from_slice(b"ustar\0");
+            out.extend_from_slice(&header);
+            out.extend_from_slice(contents);
+            out.resize(out.len().div_ceil(TAR_BLOCK) * TAR_BLOCK, 0);
+        }
+        out.resize(out.len() + 2 * TAR_BLOCK, 0);
+        out
+    }
+
+    fn collect(data: &[u8], max_size: usize) -> Vec<(String, Result<Vec<u8>>)> {
+        let kind = detect(data).expect("archive");
+        let mut entries = Vec::new();
+        for_each_entry(data, kind, max_size, |name, r| {
+            entries.push((name.to_string(), r));
+        })
+        .unwrap();
+        entries
+    }
+
+    // ── detect ───────────────────────────────────────────────────
+
+    #[test]
+    fn detects_tar_and_gzip() {
+        let data = tar(&[("a.txt", b"hi")]);
+        assert_eq!(detect(&data), Some(Kind::Tar));
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gz.write_all(&data).unwrap();
+        assert_eq!(detect(&gz.finish().unwrap()), Some(Kind::TarGz));
+
+        assert_eq!(detect(b"%PDF-1.4"), None);
+    }
+
+    #[test]
+    fn plain_zip_is_an_archive() {
+        let mut zip = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        zip.start_file("notes/a.txt", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"hello").unwrap();
+        let data = zip.finish().unwrap().into_inner();
+        assert_eq!(detect(&data), Some(Kind::Zip));
+
+        let entries = collect(&data, 1024);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "notes/a.txt");
+        assert_eq!(entries[0].1.as_deref().unwrap(), b"hello");
+    }
+
+    // ── for_each_entry ───────────────────────────────────────────
+
+    #[test]
+    fn tar_members_in_order() {
+        let data = tar(&[("./a.doc", b"one"), ("dir/b.pdf", &[7u8; 600])]);
+        let entries = collect(&data, 1024);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0, "a.doc");
+        assert_eq!(entries[0].1.as_deref().unwrap(), b"one");
+        assert_eq!(entries[1].0, "dir/b.pdf");
+        assert_eq!(entries[1].1.as_ref().unwrap().len(), 600);
+    }
+
+    #[test]
+    fn oversized_member_reported() {
+        let data = tar(&[("big.pdf", &[0u8; 100]), ("small.pdf", b"x")]);
+        let entries = collect(&data, 10);
+        assert!(matches!(entries[0].1, Err(BatdocError::TooLarge { .. })));
+        assert_eq!(entries[1].1.as_deref().unwrap(), b"x");
+    }
+
+    #[test]
+    fn gnu_long_name() {
+        let long = "d/".repeat(60) + "report.docx";
+        let mut data = tar(&[("././@LongLink", long.as_bytes()), ("short", b"x")]);
+        data[156] = b'L';
+        let entries = collect(&data, 1024);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, long);
+    }
+
+    // ── tar_size ─────────────────────────────────────────────────
+
+    #[test]
+    fn octal_and_base256_sizes() {
+        assert_eq!(tar_size(b"00000001750\0"), Some(1000));
+        assert_eq!(tar_size(b"        \0\0\0\0"), Some(0));
+        let mut field = [0u8; 12];
+        field[0] = 0x80;
+        field[10] = 0x01;
+        assert_eq!(tar_size(&field), Some(256));
+    }
+
+    // ── pax_path ─────────────────────────────────────────────────
+
+    #[test]
+    fn pax_path_record() {
+        assert_eq!(
+            pax_path(b"20 mtime=1700000000\n25 path=a/very/long.xlsx\n"),
+            Some("a/very/long.xlsx".into())
+        );
+        assert_eq!(pax_path(b"20 mtime=1700000000\n"), None);
+    }
+}
@@ -0,0 +1,167 @@
+//! Box-drawn tables for plain text output (`--table`).
+//!
+//! Tab-separated rows are compact but stop lining up as soon as cell widths
+//! vary, which makes piped output hard to read in logs. With `--table`, the
+//! renderers that produce tabular data (spreadsheets and `.docx` tables)
+//! draw them with box-drawing characters instead, sizing each column to its
+//! widest cell. Widths are measured in terminal columns, so CJK text and
+//! emoji stay aligned.
+
+use unicode_width::UnicodeWidthStr;
+
+/// Render `rows` as a box-drawn table. The first row is separated from the
+/// rest by a rule, as a header. Short rows are padded with empty cells, and
+/// cells containing newlines span several text lines.
+pub(crate) fn render<R: AsRef<[C]>, C: AsRef<str>>(rows: &[R], out: &mut String) {
+    render_aligned(rows, &[], out);
+}
+
+/// [`render`], with the text of each column flagged in `right` (such as
+/// a column of numbers) aligned to its right edge.
+pub(crate) fn render_aligned<R: AsRef<[C]>, C: AsRef<str>>(
+    rows: &[R],
+    right: &[bool],
+    out: &mut String,
+) {
+    let ncols = rows.iter().map(|r| r.as_ref().len()).max().unwrap_or(0);
+    if ncols == 0 {
+        return;
+    }
+
+    // Each cell split into its text lines, with tabs flattened to spaces
+    let cells: Vec<Vec<Vec<String>>> = rows
+        .iter()
+        .map(|row| {
+            let row = row.as_ref();
+            (0..ncols)
+                .map(|c| {
+                    let text = row.get(c).map_or("", AsRef::as_ref);
+                    text.lines().map(|l| l.replace('\t', " ")).collect()
+                })
+                .collect()
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..ncols)
+        .map(|c| {
+            cells
+                .iter()
+                .flat_map(|row| row[c].iter())
+                .map(|line| line.width())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    push_rule(&widths, ('┌', '┬', '┐'), out);
+    for (ri, row) in cells.iter().enumerate() {
+        if ri == 1 {
+            push_rule(&widths, ('├', '┼', '┤'), out);
+        }
+        let height = row.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        for line in 0..height {
+            out.push('│');
+            for (c, (cell, &width)) in row.iter().zip(&widths).enumerate() {
+                let text = cell.get(line).map_or("", String::as_str);
+                let pad = " ".repeat(width - text.width());
+                out.push(' ');
+                if right.get(c) == Some(&true) {
+                    out.push_str(&pad);
+                    out.push_str(text);
+                } else {
+                    out.push_str(text);
+                    out.push_str(&pad);
+                }
+                out.push_str(" │");
+            }
+            out.push('\n');
+        }
+    }
+    push_rule(&widths, ('└', '┴', '┘'), out);
+}
+
+/// Push a horizontal rule using the given (left, junction, right) corners.
+fn push_rule(widths: &[usize], (left, mid, right): (char, char, char), out: &mut String) {
+    out.push(left);
+    for (i, &width) in widths.iter().enumerate() {
+        if i > 0 {
+            out.push(mid);
+        }
+        out.push_str(&"─".repeat(width + 2));
+    }
+    out.push(right);
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boxed(rows: &[Vec<&str>]) -> String {
+        let mut out = String::new();
+        render(rows, &mut out);
+        out
+    }
+
+    #[test]
+    fn header_and_rows() {
+        let out = boxed(&[vec!["Name", "Age"], vec!["Alice", "30"], vec!["Bob", "7"]]);
+        assert_eq!(
+            out,
+            "┌───────┬─────┐\n\
+             │ Name  │ Age │\n\
+             ├───────┼─────┤\n\
+             │ Alice │ 30  │\n\
+             │ Bob   │ 7   │\n\
+             └───────┴─────┘\n"
+        );
+    }
+
+    #[test]
+    fn single_row_has_no_header_rule() {
+        assert_eq!(
+            boxed(&[vec!["a", "b"]]),
+            "┌───┬───┐\n│ a │ b │\n└───┴───┘\n"
+        );
+    }
+
+    #[test]
+    fn short_rows_padded() {
+        let out = boxed(&[vec!["a", "b"], vec!["c"]]);
+        assert!(out.contains("│ c │   │"));
+    }
+
+    #[test]
+    fn wide_characters_measured() {
+        let out = boxed(&[vec!["名前", "x"], vec!["ab", "y"]]);
+        assert!(out.contains("│ 名前 │ x │"));
+        assert!(out.contains("│ ab   │ y │"));
+    }
+
+    #[test]
+    fn multiline_cell() {
+        let out = boxed(&[vec!["one\ntwo", "x"]]);
+        assert_eq!(out, "┌─────┬───┐\n│ one │ x │\n│ two │   │\n└─────┴───┘\n");
+    }
+
+    #[test]
+    fn right_aligned_column() {
+        let mut out = String::new();
+        render_aligned(
+            &[
+                vec!["Item", "Cost"],
+                vec!["Tea", "4.50"],
+                vec!["Cake", "12.00"],
+            ],
+            &[false, true],
+            &mut out,
+        );
+        assert!(out.contains("│ Tea  │  4.50 │"), "{out}");
+        assert!(out.contains("│ Item │  Cost │"), "{out}");
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(boxed(&[]), "");
+    }
+}
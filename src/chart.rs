@@ -0,0 +1,241 @@
+//! Data of embedded charts (`charts/chart*.xml` parts).
+//!
+//! A chart part keeps a cache of the data it was drawn from alongside the
+//! references into the workbook that holds it: each series (`<c:ser>`) has
+//! a name (`<c:tx>`), category labels (`<c:cat>`, or `<c:xVal>` for scatter
+//! charts), and values (`<c:val>` or `<c:yVal>`), as points (`<c:pt>`)
+//! indexed by position. Read back, they make a table with a row per
+//! category and a column per series.
+
+use std::collections::BTreeMap;
+
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+
+use crate::xml_util::get_attr;
+
+/// The title and cached data of a chart.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Chart {
+    pub(crate) title: Option<String>,
+    /// Category labels by point index, shared by the series.
+    categories: BTreeMap<usize, String>,
+    series: Vec<Series>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Series {
+    name: String,
+    /// Values by point index.
+    values: BTreeMap<usize, String>,
+}
+
+/// What the text being read belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Title,
+    Name,
+    Category,
+    Value,
+}
+
+impl Chart {
+    /// Read a chart part. Returns `None` if it has no series.
+    pub(crate) fn parse(xml: &str) -> Option<Self> {
+        let mut reader = Reader::from_str(xml);
+        let mut chart = Self::default();
+        let mut title = String::new();
+        // Local names of the open elements
+        let mut path: Vec<Vec<u8>> = Vec::new();
+        let mut idx = 0usize;
+        // Whether the current series' categories are being kept, as only
+        // the first series with categories is read
+        let mut own_categories = false;
+
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e)) => {
+                    let name = e.local_name().as_ref().to_vec();
+                    match name.as_slice() {
+                        b"ser" => chart.series.push(Series::default()),
+                        b"cat" | b"xVal" => own_categories = chart.categories.is_empty(),
+                        b"pt" => {
+                            idx = get_attr(e, b"idx")
+                                .and_then(|i| i.parse().ok())
+                                .unwrap_or(0);
+                        }
+                        b"p" if target(&path) == Some(Target::Title) && !title.is_empty() => {
+                            title.push(' ');
+                        }
+                        _ => {}
+                    }
+                    path.push(name);
+                }
+                Ok(Event::Text(ref t)) => {
+                    let Ok(text) = t.unescape() else { continue };
+                    let leaf = path.last().map(Vec::as_slice);
+                    if !matches!(leaf, Some(b"v" | b"t")) {
+                        continue;
+                    }
+                    match (target(&path), chart.series.last_mut()) {
+                        (Some(Target::Title), _) => title.push_str(&text),
+                        (Some(Target::Name), Some(series)) => series.name.push_str(&text),
+                        (Some(Target::Category), Some(_)) if own_categories => {
+                            chart
+                                .categories
+                                .entry(idx)
+                                .or_insert_with(|| text.into_owned());
+                        }
+                        (Some(Target::Value), Some(series)) => {
+                            series.values.insert(idx, number(&text));
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Event::End(_)) => {
+                    path.pop();
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => {}
+            }
+        }
+
+        let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+        chart.title = (!title.is_empty()).then_some(title);
+        (!chart.series.is_empty()).then_some(chart)
+    }
+
+    /// The data as rows: a header with the series names, then a row per
+    /// category. Categories are numbered from 1 if the chart has none.
+    pub(crate) fn rows(&self) -> Vec<Vec<String>> {
+        let points = self
+            .series
+            .iter()
+            .filter_map(|s| s.values.keys().next_back())
+            .chain(self.categories.keys().next_back())
+            .max()
+            .map_or(0, |&last| last + 1);
+        let mut rows = Vec::with_capacity(points + 1);
+        let mut header = vec![String::new()];
+        for (i, series) in self.series.iter().enumerate() {
+            header.push(if series.name.is_empty() {
+                format!("Series {}", i + 1)
+            } else {
+                series.name.clone()
+            });
+        }
+        rows.push(header);
+        for idx in 0..points {
+            let mut row = vec![self
+                .categories
+                .get(&idx)
+                .cloned()
+                .unwrap_or_else(|| (idx + 1).to_string())];
+            for series in &self.series {
+                row.push(series.values.get(&idx).cloned().unwrap_or_default());
+            }
+            rows.push(row);
+        }
+        rows
+    }
+}
+
+/// What text at `path` belongs to: the chart's title (not an axis title,
+/// which is inside the plot area), or a series' name, categories, or values.
+fn target(path: &[Vec<u8>]) -> Option<Target> {
+    let has = |name: &[u8]| path.iter().any(|p| p.as_slice() == name);
+    if has(b"ser") {
+        if has(b"tx") {
+            Some(Target::Name)
+        } else if has(b"cat") || has(b"xVal") {
+            Some(Target::Category)
+        } else if has(b"val") || has(b"yVal") {
+            Some(Target::Value)
+        } else {
+            None
+        }
+    } else if has(b"title") && !has(b"plotArea") {
+        Some(Target::Title)
+    } else {
+        None
+    }
+}
+
+/// A cached value as it would be shown: numbers to 15 significant digits
+/// in their shortest form (`2` for `2.0`, `0.1` for `0.10000000000000001`),
+/// anything else as is.
+fn number(text: &str) -> String {
+    let text = text.trim();
+    match text.parse::<f64>() {
+        Ok(n) if n.is_finite() => {
+            let rounded: f64 = format!("{n:.14e}").parse().unwrap_or(n);
+            rounded.to_string()
+        }
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHART: &str = r#"<c:chartSpace xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main">
+      <c:chart>
+        <c:title><c:tx><c:rich><a:p><a:r><a:t>Sales</a:t></a:r></a:p><a:p><a:r><a:t>by quarter</a:t></a:r></a:p></c:rich></c:tx></c:title>
+        <c:plotArea>
+          <c:barChart>
+            <c:ser>
+              <c:tx><c:strRef><c:f>Sheet1!$B$1</c:f><c:strCache><c:ptCount val="1"/><c:pt idx="0"><c:v>North</c:v></c:pt></c:strCache></c:strRef></c:tx>
+              <c:cat><c:strRef><c:f>Sheet1!$A$2:$A$4</c:f><c:strCache><c:ptCount val="3"/><c:pt idx="0"><c:v>Q1</c:v></c:pt><c:pt idx="1"><c:v>Q2</c:v></c:pt><c:pt idx="2"><c:v>Q3</c:v></c:pt></c:strCache></c:strRef></c:cat>
+              <c:val><c:numRef><c:f>Sheet1!$B$2:$B$4</c:f><c:numCache><c:formatCode>General</c:formatCode><c:ptCount val="3"/><c:pt idx="0"><c:v>4.3</c:v></c:pt><c:pt idx="1"><c:v>2.5</c:v></c:pt><c:pt idx="2"><c:v>3.5000000000000004</c:v></c:pt></c:numCache></c:numRef></c:val>
+            </c:ser>
+            <c:ser>
+              <c:tx><c:v>South</c:v></c:tx>
+              <c:cat><c:strRef><c:strCache><c:pt idx="0"><c:v>ignored</c:v></c:pt></c:strCache></c:strRef></c:cat>
+              <c:val><c:numRef><c:numCache><c:pt idx="0"><c:v>2.0</c:v></c:pt><c:pt idx="2"><c:v>1E-3</c:v></c:pt></c:numCache></c:numRef></c:val>
+            </c:ser>
+          </c:barChart>
+          <c:valAx><c:title><c:tx><c:rich><a:p><a:r><a:t>Units</a:t></a:r></a:p></c:rich></c:tx></c:title></c:valAx>
+        </c:plotArea>
+      </c:chart>
+    </c:chartSpace>"#;
+
+    // ── Chart ────────────────────────────────────────────────────
+
+    #[test]
+    fn title_and_series_as_rows() {
+        let chart = Chart::parse(CHART).unwrap();
+        assert_eq!(chart.title.as_deref(), Some("Sales by quarter"));
+        assert_eq!(
+            chart.rows(),
+            [
+                vec!["", "North", "South"],
+                vec!["Q1", "4.3", "2"],
+                vec!["Q2", "2.5", ""],
+                vec!["Q3", "3.5", "0.001"],
+            ]
+        );
+    }
+
+    #[test]
+    fn untitled_chart_without_categories() {
+        let xml = r#"<c:chartSpace xmlns:c="c"><c:chart><c:plotArea><c:pieChart><c:ser><c:val><c:numLit><c:pt idx="0"><c:v>1</c:v></c:pt><c:pt idx="1"><c:v>3</c:v></c:pt></c:numLit></c:val></c:ser></c:pieChart></c:plotArea></c:chart></c:chartSpace>"#;
+        let chart = Chart::parse(xml).unwrap();
+        assert_eq!(chart.title, None);
+        assert_eq!(
+            chart.rows(),
+            [vec!["", "Series 1"], vec!["1", "1"], vec!["2", "3"]]
+        );
+        assert_eq!(Chart::parse("<c:chartSpace/>"), None);
+    }
+
+    // ── number ───────────────────────────────────────────────────
+
+    #[test]
+    fn numbers_in_shortest_form() {
+        assert_eq!(number("2.0"), "2");
+        assert_eq!(number("0.10000000000000001"), "0.1");
+        assert_eq!(number("12%"), "12%");
+        assert_eq!(number("-7"), "-7");
+    }
+}
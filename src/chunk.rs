@@ -0,0 +1,374 @@
+//! Output split into chunks for retrieval pipelines (`--chunk`).
+//!
+//! Splitting rendered markdown after the fact cuts tables in half and
+//! loses track of which page or section a piece came from. This works on
+//! the document model ([`Block`]s) instead: a chunk never spans a
+//! heading, slide, page, or sheet, blocks are packed into chunks of up to
+//! `size` characters, and a table too big for one chunk is split between
+//! rows with its header row repeated. Only a block bigger than a chunk
+//! on its own is broken up, between lines or words.
+//!
+//! Each chunk is written as one line of JSON with the file, the chunk's
+//! number, its location (page, slide, or sheet and rows), the path of
+//! headings it falls under, and its text as markdown.
+
+use std::fmt::Write as _;
+
+use crate::document::Block;
+use crate::heuristic;
+use crate::json;
+
+/// Characters per chunk unless `--chunk-size` says otherwise.
+pub(crate) const DEFAULT_SIZE: usize = 2000;
+
+/// A piece of the document, up to the chunk size.
+#[derive(Debug, PartialEq, Eq)]
+struct Chunk {
+    location: Option<String>,
+    /// The headings it falls under, outermost first.
+    headings: Vec<String>,
+    text: String,
+}
+
+/// Packs blocks into chunks, starting a new one at each boundary.
+struct Chunker {
+    size: usize,
+    chunks: Vec<Chunk>,
+    current: Option<Chunk>,
+    /// Open headings with their levels, outermost first.
+    headings: Vec<(u8, String)>,
+    /// The current page or slide.
+    marker: Option<String>,
+}
+
+impl Chunker {
+    const fn new(size: usize) -> Self {
+        Self {
+            size,
+            chunks: Vec::new(),
+            current: None,
+            headings: Vec::new(),
+            marker: None,
+        }
+    }
+
+    /// End the current chunk, so the next text starts a new one.
+    fn boundary(&mut self) {
+        self.chunks.extend(self.current.take());
+    }
+
+    /// Add `text`, broken up first if it is bigger than a chunk.
+    /// `location` overrides the current page or slide.
+    fn push(&mut self, text: &str, location: Option<&str>) {
+        if len(text) <= self.size {
+            self.add(text, location);
+        } else {
+            for piece in split(text, self.size) {
+                self.add(&piece, location);
+            }
+        }
+    }
+
+    /// Add a piece of at most the chunk size to the current chunk, or to
+    /// a new one if it does not fit.
+    fn add(&mut self, piece: &str, location: Option<&str>) {
+        if let Some(chunk) = &mut self.current {
+            if len(&chunk.text) + 2 + len(piece) <= self.size {
+                chunk.text.push_str("\n\n");
+                chunk.text.push_str(piece);
+                return;
+            }
+            self.boundary();
+        }
+        self.current = Some(Chunk {
+            location: location.map(str::to_string).or_else(|| self.marker.clone()),
+            headings: self.headings.iter().map(|(_, h)| h.clone()).collect(),
+            text: piece.to_string(),
+        });
+    }
+
+    fn heading(&mut self, level: u8, text: &str) {
+        self.boundary();
+        while self.headings.last().is_some_and(|&(l, _)| l >= level) {
+            self.headings.pop();
+        }
+        self.headings.push((level, text.to_string()));
+        let line = format!("{} {text}", "#".repeat(usize::from(level.clamp(1, 6))));
+        self.push(&line, None);
+    }
+
+    /// Add a table in groups of rows that fit a chunk, each starting with
+    /// the header row. A sheet (a named table) gets chunks of its own,
+    /// located by sheet and rows.
+    fn table(&mut self, name: Option<&str>, rows: &[Vec<String>]) {
+        if rows.is_empty() {
+            return;
+        }
+        if name.is_some() {
+            self.boundary();
+            self.headings.clear();
+            self.marker = None;
+        }
+        let ncols = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.split_whitespace().collect::<Vec<_>>().join(" "))
+                    .collect()
+            })
+            .collect();
+        let mut markdown = String::new();
+        heuristic::push_markdown_table(&cells, ncols, &mut markdown);
+        // The header row and separator, then one line per further row
+        let lines: Vec<&str> = markdown.lines().collect();
+        let head = lines[..2].join("\n");
+
+        let mut group = head.clone();
+        let mut first = 1;
+        for (i, line) in lines[2..].iter().enumerate().map(|(i, l)| (i + 1, l)) {
+            if i > first && len(&group) + 1 + len(line) > self.size {
+                self.push(&group, name.map(|n| row_range(n, first, i)).as_deref());
+                group.clone_from(&head);
+                first = i;
+            }
+            group.push('\n');
+            group.push_str(line);
+        }
+        self.push(
+            &group,
+            name.map(|n| row_range(n, first, rows.len())).as_deref(),
+        );
+        if name.is_some() {
+            self.boundary();
+        }
+    }
+}
+
+/// Split `blocks` into chunks of at most `size` characters, one JSON
+/// object per line naming `source`.
+pub(crate) fn render(blocks: &[Block], source: &str, size: usize) -> String {
+    let mut out = String::new();
+    for (i, chunk) in chunks(blocks, size).iter().enumerate() {
+        out.push_str("{\"file\":");
+        json::push_str(&mut out, source);
+        let _ = write!(out, ",\"chunk\":{},\"location\":", i + 1);
+        match &chunk.location {
+            Some(location) => json::push_str(&mut out, location),
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"headings\":[");
+        for (j, heading) in chunk.headings.iter().enumerate() {
+            if j > 0 {
+                out.push(',');
+            }
+            json::push_str(&mut out, heading);
+        }
+        out.push_str("],\"text\":");
+        json::push_str(&mut out, &chunk.text);
+        out.push_str("}\n");
+    }
+    out
+}
+
+fn chunks(blocks: &[Block], size: usize) -> Vec<Chunk> {
+    let mut chunker = Chunker::new(size);
+    for block in blocks {
+        match block {
+            Block::Heading { level, text } => chunker.heading(*level, text),
+            Block::Paragraph { text, .. } => chunker.push(text, None),
+            Block::ListItem {
+                level,
+                ordered,
+                text,
+                ..
+            } => {
+                let bullet = if *ordered { "1." } else { "-" };
+                let indent = "  ".repeat(usize::from(*level));
+                chunker.push(&format!("{indent}{bullet} {text}"), None);
+            }
+            Block::Table { name, rows } => chunker.table(name.as_deref(), rows),
+            Block::Slide { number } => {
+                chunker.boundary();
+                chunker.headings.clear();
+                chunker.marker = Some(format!("slide {number}"));
+            }
+            Block::Page { number } => {
+                chunker.boundary();
+                chunker.marker = Some(format!("page {number}"));
+            }
+        }
+    }
+    chunker.boundary();
+    chunker.chunks
+}
+
+/// `sheet, rows A–B` for the body rows `first..end` (indices into the
+/// sheet's rows, so the header is row 1), or the header alone if there
+/// are none.
+fn row_range(sheet: &str, first: usize, end: usize) -> String {
+    if end <= first {
+        format!("{sheet}, row 1")
+    } else if end == first + 1 {
+        format!("{sheet}, row {}", first + 1)
+    } else {
+        format!("{sheet}, rows {}–{end}", first + 1)
+    }
+}
+
+/// `text` in pieces of at most `size` characters, broken between lines
+/// where possible, else between words, else anywhere.
+fn split(text: &str, size: usize) -> Vec<String> {
+    let mut pieces: Vec<String> = Vec::new();
+    let mut add = |part: &str, sep: &str| match pieces.last_mut() {
+        Some(last) if len(last) + len(sep) + len(part) <= size => {
+            last.push_str(sep);
+            last.push_str(part);
+        }
+        _ => pieces.push(part.to_string()),
+    };
+    for line in text.lines() {
+        if len(line) <= size {
+            add(line, "\n");
+            continue;
+        }
+        let mut sep = "\n";
+        for word in line.split_whitespace() {
+            if len(word) <= size {
+                add(word, sep);
+            } else {
+                let chars: Vec<char> = word.chars().collect();
+                for (i, part) in chars.chunks(size).enumerate() {
+                    add(
+                        &part.iter().collect::<String>(),
+                        if i == 0 { sep } else { "" },
+                    );
+                }
+            }
+            sep = " ";
+        }
+    }
+    pieces
+}
+
+/// Length in characters, which is what the chunk size counts.
+fn len(s: &str) -> usize {
+    s.chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn para(text: &str) -> Block {
+        Block::Paragraph {
+            text: text.into(),
+            spans: Vec::new(),
+        }
+    }
+
+    fn heading(level: u8, text: &str) -> Block {
+        Block::Heading {
+            level,
+            text: text.into(),
+        }
+    }
+
+    // ── chunks ───────────────────────────────────────────────────
+
+    #[test]
+    fn chunks_end_at_headings() {
+        let blocks = vec![
+            heading(1, "Guide"),
+            para("Intro."),
+            heading(2, "Install"),
+            para("Run it."),
+            para("Then this."),
+            heading(2, "Use"),
+            para("Open a file."),
+        ];
+        let chunks = chunks(&blocks, 100);
+        let summary: Vec<(Vec<String>, &str)> = chunks
+            .iter()
+            .map(|c| (c.headings.clone(), c.text.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (vec!["Guide".to_string()], "# Guide\n\nIntro."),
+                (
+                    vec!["Guide".to_string(), "Install".to_string()],
+                    "## Install\n\nRun it.\n\nThen this."
+                ),
+                (
+                    vec!["Guide".to_string(), "Use".to_string()],
+                    "## Use\n\nOpen a file."
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn chunks_stay_within_size() {
+        let blocks = vec![
+            Block::Page { number: 2 },
+            para("one two three"),
+            para("four five"),
+            para(&"word ".repeat(10)),
+        ];
+        let chunks = chunks(&blocks, 20);
+        assert!(chunks.iter().all(|c| len(&c.text) <= 20));
+        assert_eq!(chunks[0].text, "one two three");
+        assert_eq!(chunks[1].text, "four five");
+        assert!(chunks
+            .iter()
+            .all(|c| c.location.as_deref() == Some("page 2")));
+    }
+
+    #[test]
+    fn sheet_split_between_rows_with_header() {
+        let rows: Vec<Vec<String>> = (0..5).map(|i| vec![format!("r{i}"), "x".into()]).collect();
+        let blocks = vec![Block::Table {
+            name: Some("Q1".into()),
+            rows,
+        }];
+        let chunks = chunks(&blocks, 46);
+        let located: Vec<(&str, &str)> = chunks
+            .iter()
+            .map(|c| (c.location.as_deref().unwrap_or_default(), c.text.as_str()))
+            .collect();
+        assert_eq!(
+            located,
+            [
+                (
+                    "Q1, rows 2–3",
+                    "| r0 | x |\n| --- | --- |\n| r1 | x |\n| r2 | x |"
+                ),
+                (
+                    "Q1, rows 4–5",
+                    "| r0 | x |\n| --- | --- |\n| r3 | x |\n| r4 | x |"
+                ),
+            ]
+        );
+    }
+
+    // ── split / render ───────────────────────────────────────────
+
+    #[test]
+    fn split_prefers_lines_then_words() {
+        assert_eq!(split("ab\ncd\nef", 5), ["ab\ncd", "ef"]);
+        assert_eq!(split("one two three", 8), ["one two", "three"]);
+        assert_eq!(split("abcdefg", 3), ["abc", "def", "g"]);
+    }
+
+    #[test]
+    fn one_json_object_per_chunk() {
+        let blocks = vec![Block::Slide { number: 1 }, heading(1, "Hi \"there\"")];
+        assert_eq!(
+            render(&blocks, "d.pptx", 100),
+            "{\"file\":\"d.pptx\",\"chunk\":1,\"location\":\"slide 1\",\
+             \"headings\":[\"Hi \\\"there\\\"\"],\"text\":\"# Hi \\\"there\\\"\"}\n"
+        );
+        assert_eq!(render(&[], "e.docx", 100), "");
+    }
+}
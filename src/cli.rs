@@ -0,0 +1,1844 @@
+//! Command-line argument parsing.
+//!
+//! Hand-rolled rather than pulling in an argument-parsing crate: the option
+//! set is small and the binary stays lean. Value-taking options accept both
+//! `--opt VALUE` and `--opt=VALUE`, and short flags can be combined as in
+//! `-pi`.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use crate::codepage;
+use crate::format::Format;
+use crate::options::{CellNewline, Comments, Marks, Overrides, PageRanges, Revisions};
+use crate::{chunk, summary};
+
+pub(crate) const USAGE: &str = "\
+batdoc - bat for .doc, .docx, .xls, .xlsx, .pptx, and .pdf files
+
+Usage: batdoc [OPTIONS] [FILE...]
+       cat FILE | batdoc [OPTIONS]
+       batdoc [OPTIONS] -
+       batdoc diff [OPTIONS] OLD NEW
+       batdoc serve [--listen ADDR] [OPTIONS]
+
+Options:
+  -p, --plain       Force plain text output (no colors, no decorations)
+  -m, --markdown    Output as markdown (default when terminal detected)
+      --to FORMAT   Output format: markdown, plain, org, csv, or xml
+      --format FMT  Input format: doc, docx, xls, xlsx, pptx, or pdf
+      --assume-ext EXT Extension to assume when detection fails (stdin)
+      --csv         Write spreadsheet data as CSV (same as --to csv)
+      --summary     Show metadata, outline, tables, and section openings
+      --metadata    Show only the document properties (title, author, ...)
+      --stats       Count words, characters, paragraphs, tables, and pages
+      --grep PATTERN Print the lines containing PATTERN, with their location
+      --list        List sheets, slides, bookmarks, or headings, not the text
+      --chunk       Split the text into chunks, written as JSON lines
+      --chunk-size N Characters per chunk with --chunk (default 2000)
+      --summary-words N Words shown per section in --summary (default 50)
+      --csv-dir DIR Write each worksheet to its own CSV file in DIR
+      --preview     Fast, short output for file-manager previews
+      --preview-size KB Output kept by --preview (default 64)
+      --head N      Show only the first N lines of each document
+      --tail N      Show only the last N lines of each document
+  -o, --output FILE Write to FILE instead of stdout ({stem} = input name)
+      --copy        Copy the output to the clipboard instead of printing it
+  -i, --images      Embed images as inline base64 data URIs in markdown
+      --no-reflow   Keep hard line breaks in .doc and PDF markdown output
+      --table       Draw plain-text tables with box-drawing characters
+      --escape-html Escape HTML-looking text in markdown output
+      --pages LIST  Extract only these PDF pages, e.g. 2-5,9 or 10-
+      --sheet SHEET Extract only this worksheet, by name or number
+      --pick        Choose the sheets or slides to show, on a terminal
+      --password PASSWORD Password for encrypted documents ($BATDOC_PASSWORD)
+      --encoding ENC Codepage of 8-bit .doc/.xls text, e.g. cp1251 or koi8-r
+      --no-print-area Show all of each .xls sheet, not just its print area
+      --hidden      Include hidden sheets, and hidden .xlsx rows and columns
+      --cell-newline MODE Line breaks in TSV cells: escape (\\n) or space
+      --max-rows N  Show at most N rows of each sheet, noting how many more
+      --max-cols N  Show at most N columns of each sheet, likewise
+      --comments WHERE Show .docx/.xls/.xlsx comments: inline, end, or none (default)
+      --revisions MODE Tracked .docx changes: final (default), original, or marked
+      --marks MODE  .docx strike/underline/highlight: extended (default), emphasis, or none
+      --control-labels Label .docx content controls with their names
+      --embedded    Extract documents embedded in a .docx after their placeholders
+      --page-breaks SEP Write SEP, e.g. ---, at .docx page and section breaks
+      --paging WHEN Use a pager on a terminal: auto, always, or never
+      --pager-plain Page plain text output on a terminal too
+      --pager CMD   Pager command ($BAT_PAGER, $PAGER, less)
+      --no-pager    Never use a pager (same as --paging never)
+      --theme THEME Syntax theme for terminal markdown ($BAT_THEME, ansi)
+      --style LIST  Decorations: default, full, plain, or header,grid,...
+  -n, --line-numbers Show line numbers in terminal markdown
+      --wrap MODE   Wrap long lines on a terminal: auto, character, never
+      --no-filename Do not name each file in front of its output
+      --errors FMT  Report errors on stderr as text or json
+      --fail-fast   Stop at the first file that fails
+  -v, --verbose     Report each file's format and timing (-vv: parser notes)
+  -q, --quiet       Do not report warnings or progress, only errors
+      --no-config   Ignore the config file
+      --deterministic Same bytes for the same input, whatever the environment
+      --allow-remote Fetch http:// and https:// URL arguments
+      --files-from FILE Read input paths from FILE, one per line (- = stdin)
+  -0, --null        With --files-from, paths are separated by NUL bytes
+  -j, --jobs N      Extract N files at a time (default 1, 0 = one per CPU)
+      --word-diff   With diff, show changed words instead of whole lines
+      --listen ADDR With serve, the address to listen on (127.0.0.1:8080)
+  -h, --help        Show this help
+  -V, --version     Show the version
+
+When stdout is a terminal, output is pretty-printed as syntax-highlighted
+markdown with decorations. When piped, output is plain text.
+
+-o FILE writes the output to FILE. Without -p/-m/--to, the format follows
+the extension: .md is markdown, .org is Org, .csv is CSV, .xml is XML,
+anything else plain text. {stem}, {name}, and {ext} in FILE are replaced
+by each input's file stem, name, and extension, so -o '{stem}.md'
+converts several files at once; without them all inputs go to the one
+file.
+
+--copy puts the output on the clipboard instead of stdout, as markdown
+unless -p or --to says otherwise, by sending it to the terminal as an
+OSC 52 escape sequence. It works over SSH and in tmux, but only in
+terminals that support OSC 52.
+
+On a terminal, markdown output goes through a pager when it does not fit
+on one screen (--paging auto). Plain output is written straight through
+unless --pager-plain is given; it then follows --paging as well.
+--pager CMD names the pager and its arguments; without it, $BAT_PAGER
+or else $PAGER is used, and less -R if neither is set. --no-pager
+writes straight to the terminal, like --paging never.
+
+Terminal output looks like bat's and takes its display options.
+--theme THEME picks the syntax theme (default $BAT_THEME, else ansi).
+--style LIST chooses the decorations as a comma-separated list of
+header, grid, numbers, rule, and snip, or one of default (header and
+grid), full, or plain; -n/--line-numbers adds line numbers to whichever
+style is in effect. --wrap character wraps long lines at the terminal
+width inside the grid, and --wrap never leaves them for the pager to
+scroll sideways.
+
+--to org emits Emacs Org-mode markup (* headings, | tables, [[url][text]]
+links) instead of markdown. --to xml writes the parsed document model
+(headings, paragraphs, list items, tables, slides, pages) as XML, with
+.docx text colors and highlights kept as <span> elements. -p and -m are
+shorthands for --to plain and --to markdown.
+
+--summary prints a triage view of each document instead of its text:
+the metadata, an outline of the headings, an inventory of tables and
+figure captions, and the first words of every section (or slide, or
+page). --summary-words N sets how many; 0 leaves the sections out.
+
+--preview is for the preview pane of lf, ranger, fzf, and the like: it
+stops reading each document once it has --preview-size KB of text (64
+by default), cuts the output to that size at a line break, leaves out
+images, and never starts a pager.
+
+--head N and --tail N keep only the first or last N lines of each
+document's plain, markdown, or Org output. With --head, reading stops
+once the document has given N lines, so the start of a long PDF or
+presentation shows up without the rest being extracted; --tail has to
+read the whole document.
+
+--metadata prints the document properties instead of the text: title,
+author, company, dates, application, and page and word counts, from the
+OOXML docProps parts, the OLE2 summary information streams, or a PDF's
+Info dictionary and XMP metadata. Only properties that are set are shown,
+followed by the defined names of an .xlsx workbook and the drop-down lists
+its data validation holds cells to.
+
+--stats prints a table of counts instead of the text: words, characters
+(with spaces), paragraphs, tables, and slides, sheets, or pages, one row
+per document and a total row when there are several. They are counted
+from the parsed document, not the rendered output. --stats always writes
+to stdout.
+
+--list shows what a document contains instead of its text: the sheets of
+a workbook with their numbers of rows and columns (and an .xlsx
+workbook's defined names and drop-down lists), the slides of a
+presentation with their titles, the page count and bookmarks of a PDF,
+or the outline of the headings of a .doc or .docx.
+
+--chunk splits each document into chunks for search and retrieval
+pipelines, each written as a line of JSON with the file, the chunk's
+number, its location (page, slide, or sheet and rows), the headings it
+falls under, and its text as markdown. A chunk never spans a heading,
+slide, page, or sheet, and has at most --chunk-size N characters (2000
+by default); a long table is split between rows, with its header row
+repeated in each chunk.
+
+--grep PATTERN searches instead of printing: each line of text (or
+table row) containing PATTERN, a literal, case-sensitive string, is
+printed as FILE:LOCATION: LINE, where the location is the page, the
+slide, or the sheet and row. On a terminal, matches are highlighted.
+
+batdoc diff OLD NEW compares the text of two documents and prints a
+unified diff of their lines, with headings, list items, table rows, and
+page, slide, and sheet markers each on a line of their own. Formats can
+differ: a .doc can be compared with the .docx it became. --word-diff
+shows each changed passage once, with removed words as [-old-] and added
+ones as {+new+} (in red and green on a terminal). The exit status is 0
+when the texts are the same, 1 when they differ, and 2 on error.
+
+batdoc serve answers HTTP requests instead of reading files, for
+services that convert many documents. POST a document to / as the
+request body or as a multipart/form-data file upload, and the response
+is its markdown; ?to=plain, org, csv, or xml picks another format, and
+?to=json returns a JSON object with the file, format, and markdown. A
+document that cannot be converted gets a 4xx or 5xx status and a JSON
+error like those of --errors json. The other options (--password,
+--sheet, --images, ...) apply to every request. --listen ADDR sets the
+address (default 127.0.0.1:8080), -j N handles N requests at a time, and
+-v logs each request on stderr. There is no TLS or authentication, so
+keep it on localhost or a trusted network.
+
+--format skips detection and parses every input as the given format, for
+files whose signature is missing or damaged. Without it, an input whose
+signature is not recognized is parsed according to its file extension;
+--assume-ext EXT supplies one for stdin and extensionless files.
+
+--csv streams a single-sheet .xls/.xlsx workbook to stdout as CSV. For
+workbooks with several sheets, use --csv-dir DIR to write one file per
+sheet, named FILE_SHEET.csv, or pick one with --sheet.
+
+--sheet SHEET limits spreadsheet output to one worksheet, named (--sheet
+'Q3 Data') or numbered from 1 among the visible sheets (--sheet 2). A
+sheet whose name matches exactly takes precedence over the number.
+
+--pick lists the sheets of a workbook, or the slides of a presentation,
+and shows only those you choose. Type to filter the list, use the arrow
+keys to move and Tab to mark, then Enter to show the marked items (or
+the one under the cursor), or Esc to show everything. It only applies
+when the output is a terminal.
+
+--password PASSWORD decrypts password-protected documents: .doc and .xls
+files encrypted with RC4 (Office 97-2003) or XOR-obfuscated (Office 95),
+and .docx, .xlsx, and .pptx files with standard or agile AES encryption
+(Office 2007 and later). The BATDOC_PASSWORD environment variable is used
+when --password is not given. Workbooks that Excel encrypted with its
+default password open without one.
+
+--encoding ENC decodes the 8-bit text of .doc and .xls files, and of
+their document properties, with the given codepage instead of the one
+the file declares or its language suggests. ENC is a codepage number or
+name (1251, cp1251, windows-1251) or an encoding label such as koi8-r,
+shift_jis, or latin1. Unicode text is not affected.
+
+An .xls sheet with a print area is cropped to it, since that is the part
+the author meant to show; --no-print-area shows the whole sheet.
+
+Hidden sheets, and the rows and columns an .xlsx sheet hides, are left
+out, since the author hid them from readers; --hidden includes them.
+
+--images extracts embedded images from .docx, .pptx, and .xlsx files and
+includes them as ![](data:image/...;base64,...) in the markdown output.
+Most useful when piping to a file (batdoc --images report.docx > out.md).
+Ignored in plain text mode and for formats without image support (.doc, .xls, .pdf).
+Without it, .docx pictures show as ![alt text] in markdown and as
+[Image: alt text] in plain text, with the caption below a picture, or
+else its name, standing in for a missing description.
+
+--table draws spreadsheet and .docx tables in plain output as aligned,
+box-drawn grids instead of tab-separated rows. Columns of numbers in a
+sheet are right-aligned, there and in markdown.
+
+--escape-html escapes document text that looks like HTML (<script>,
+&amp;) in markdown output, for renderers that would pass it through raw.
+
+In plain output, spreadsheet rows are tab-separated, one line per row.
+Line breaks inside a cell are written as a literal \\n by default, or as
+a space with --cell-newline space, so awk and cut see whole rows.
+
+--max-rows N and --max-cols N cut each sheet of a .xls or .xlsx to N rows
+or columns, counted from its first cell with a value, and note what was
+cut after it, as … (120 more rows). CSV export, --stats, and --grep
+always cover whole sheets.
+
+Comments in a .docx are left out unless --comments says where to put
+them: inline shows each one as > [Author: text] after the paragraph it
+is attached to; end marks the spot with [c1], [c2], ... and lists the
+comments, with the text they refer to, in a Comments section at the end.
+In a .xlsx, notes and threaded comments mark their cell with [c1], [c2],
+... and are listed after each sheet (inline) or after the last (end), as
+are the notes of a .xls.
+
+Tracked changes in a .docx are shown accepted by default. --revisions
+original shows the text as it was before them, and --revisions marked
+shows both, with deleted text as [-text-] and inserted text as {+text+}.
+
+Struck-through, underlined, and highlighted text in a .docx is marked in
+markdown as ~~text~~, <u>text</u>, and ==text==. --marks emphasis keeps to
+plain markdown, with underlined text as *text* and highlighted text as
+**text**, and --marks none leaves them unmarked.
+Monospace .docx text (Consolas, Courier New, code styles) is written as
+`code`, and a run of code paragraphs as a fenced code block.
+
+Content controls in a .docx form show the text filled in, leaving out the
+placeholder text of empty ones. --control-labels puts each control's
+title (or tag) before its text, as Title: text.
+
+Objects embedded in a .docx (a worksheet, a PDF, an attached file) show
+as a placeholder with the object's name and type, such as
+[Object: budget.xlsx (Excel.Sheet.12)]. --embedded extracts the ones
+batdoc can read and puts their content after the placeholder.
+
+Page breaks in a .docx are left unmarked unless --page-breaks gives a
+line to write in their place, such as --page-breaks --- for a markdown
+rule; section breaks, which usually start a new page, get it too.
+
+For .doc and PDF markdown, lines broken mid-sentence are joined back into
+paragraphs. Use --no-reflow to keep the original line breaks.
+
+--errors json prints one JSON object per failed file on stderr, with
+file, format, kind, and message keys. kind is a stable code such as
+encrypted, wrong_password, corrupt, unsupported, empty, not_found, or too_large, or
+warning for a file that was extracted despite a problem.
+
+The exit status is 0 when every file was extracted, 1 when some failed,
+2 for an invalid command line or config file, 3 when every failure was
+an unsupported format, and 4 when every failure was an encrypted file
+without the right password. Failures do not stop the other files unless
+--fail-fast is given; output then ends at the first one. batdoc diff
+exits 0, 1, or 2 like diff(1) instead.
+
+-v prints a line per document on stderr with its format, how the format
+was determined, its size, and how long it took to extract. -vv adds
+notes from the parser about how the document was read, such as the
+codepage chosen for 8-bit text and the hidden sheets that were skipped,
+to help explain output that looks wrong. They are info lines, or JSON
+objects of kind info with --errors json. -q/--quiet drops warnings; errors
+are always reported.
+
+While a workbook or PDF of 16 MB or more is parsed, a progress bar on
+stderr counts its sheets or pages, when stderr is a terminal. -q and
+--jobs with several files leave it out.
+
+Defaults for --images, --table, --escape-html, --cell-newline,
+--max-rows, --max-cols, --comments, --revisions, --marks,
+--control-labels, --embedded, --page-breaks, --no-print-area, --hidden,
+and reflow can be set in ~/.config/batdoc/config.toml (or
+$BATDOC_CONFIG), globally or in a per-format section such as [xlsx].
+Command-line flags take precedence.
+
+--deterministic makes the output depend only on the inputs and the
+command line, for pipelines that cache or diff it: the config file is
+ignored and stdout is treated as a pipe even on a terminal (no colors,
+no pager). The output bytes are then the same on every run and platform.
+
+Multiple files can be specified and will be processed in order. When
+their text goes to one place, each is preceded by a ==> file <== line,
+as with head(1); --no-filename leaves these out, for archive members too.
+--files-from FILE reads more of them from FILE, or from stdin with -,
+after those on the command line: one path per line, or separated by NUL
+bytes with -0/--null, as written by find -print0. Blank entries are
+skipped. This avoids the command-line length limit:
+find docs -name '*.docx' -print0 | batdoc -0 --files-from - -o '{stem}.md'
+-j/--jobs N extracts up to N of them at once on worker threads; the
+output is still written in the order the files were given. A very large
+.docx or .xlsx that is written out as it is read, rather than held in
+memory, is extracted while it is written, one at a time in that order.
+Use - to read from stdin explicitly, and -- to end the options, so that
+the arguments after it are file names even if they start with - (or are
+-- themselves). file:// URLs name local files;
+http:// and https:// URLs are downloaded with curl (up to the 256 MiB
+input limit) only when --allow-remote is given.
+
+Supports legacy .doc/.xls (OLE2), modern .docx/.xlsx/.pptx (OOXML), and .pdf.
+Format is detected by magic bytes, not file extension. Plain .zip, .tar,
+and .tar.gz archives are opened and each supported document inside is
+extracted under a ==> archive/member <== header.";
+
+/// Output mode selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mode {
+    /// Detect automatically: markdown to terminal, plain text when piped.
+    Auto,
+    /// Force plain text output.
+    Plain,
+    /// Force markdown output.
+    Markdown,
+    /// Emacs Org-mode markup, converted from the markdown rendering.
+    Org,
+    /// CSV export of spreadsheet data.
+    Csv,
+    /// XML serialization of the document model.
+    Xml,
+    /// Markdown triage view: metadata, outline, tables, section openings.
+    Summary,
+    /// Document properties only, as `Label: value` lines.
+    Metadata,
+    /// A table of word, character, and structure counts.
+    Stats,
+    /// Lines containing [`Args::grep`], with their locations.
+    Grep,
+    /// The sheets, slides, pages and bookmarks, or headings, without content.
+    List,
+    /// JSON lines of chunks of at most [`Args::chunk_size`] characters.
+    Chunk,
+}
+
+/// When to send terminal output through a pager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Paging {
+    /// Page only when the output does not fit on one screen.
+    Auto,
+    /// Always page.
+    Always,
+    /// Never page.
+    Never,
+}
+
+/// How long lines are wrapped on a terminal (`--wrap`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Wrap {
+    /// bat's default: lines are not wrapped, but may still be folded by
+    /// the terminal.
+    Auto,
+    /// Wrap at the terminal width, continuing inside the grid.
+    Character,
+    /// Never wrap; the pager scrolls long lines sideways.
+    Never,
+}
+
+/// Decorations around markdown on a terminal (`--style`), as in bat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)] // independent components
+pub(crate) struct Style {
+    /// The file name above the output.
+    pub(crate) header: bool,
+    /// Lines separating the header and line numbers from the text.
+    pub(crate) grid: bool,
+    /// Line numbers.
+    pub(crate) numbers: bool,
+    /// A horizontal rule between files.
+    pub(crate) rule: bool,
+    /// Markers between the ranges of a file.
+    pub(crate) snip: bool,
+}
+
+impl Style {
+    /// The header and grid, without line numbers.
+    pub(crate) const DEFAULT: Self = Self {
+        header: true,
+        grid: true,
+        numbers: false,
+        rule: false,
+        snip: false,
+    };
+
+    /// Every component.
+    const FULL: Self = Self {
+        header: true,
+        grid: true,
+        numbers: true,
+        rule: true,
+        snip: true,
+    };
+
+    /// No decorations at all.
+    const PLAIN: Self = Self {
+        header: false,
+        grid: false,
+        numbers: false,
+        rule: false,
+        snip: false,
+    };
+
+    /// Parse a `--style` value: a comma-separated list of components and
+    /// presets, combined.
+    fn parse(value: &str) -> Result<Self, String> {
+        let mut style = Self::PLAIN;
+        for part in value.split(',').map(str::trim) {
+            match part {
+                "default" | "auto" => style = style.union(Self::DEFAULT),
+                "full" => style = Self::FULL,
+                "plain" => {}
+                "header" | "header-filename" => style.header = true,
+                "grid" => style.grid = true,
+                "numbers" => style.numbers = true,
+                "rule" => style.rule = true,
+                "snip" => style.snip = true,
+                _ => {
+                    return Err(format!(
+                        "invalid value for --style: '{part}' (expected default, full, \
+                         plain, header, grid, numbers, rule, or snip)"
+                    ))
+                }
+            }
+        }
+        Ok(style)
+    }
+
+    const fn union(self, other: Self) -> Self {
+        Self {
+            header: self.header || other.header,
+            grid: self.grid || other.grid,
+            numbers: self.numbers || other.numbers,
+            rule: self.rule || other.rule,
+            snip: self.snip || other.snip,
+        }
+    }
+}
+
+/// How errors are reported on stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ErrorFormat {
+    /// `batdoc: FILE: message` lines.
+    Text,
+    /// One JSON object per error, with a stable error code.
+    Json,
+}
+
+/// Parsed command line.
+#[derive(Debug)]
+#[allow(clippy::struct_excessive_bools)] // independent flags
+pub(crate) struct Args {
+    pub(crate) mode: Mode,
+    /// Input format forced with `--format`, bypassing detection.
+    pub(crate) format: Option<Format>,
+    /// Format implied by `--assume-ext`, used when detection fails on an
+    /// input without a recognized extension of its own.
+    pub(crate) assume_ext: Option<Format>,
+    /// Options set on the command line; layered over the config file.
+    pub(crate) opts: Overrides,
+    /// PDF pages to extract (`--pages`).
+    pub(crate) pages: Option<PageRanges>,
+    /// Spreadsheet sheet to extract (`--sheet`), by name or index.
+    pub(crate) sheet: Option<String>,
+    /// Password for encrypted documents (`--password`).
+    pub(crate) password: Option<String>,
+    /// Codepage for 8-bit legacy text (`--encoding`).
+    pub(crate) encoding: Option<u16>,
+    /// Words shown from each section in [`Mode::Summary`].
+    pub(crate) summary_words: usize,
+    /// Bytes of output kept by `--preview`, which also ends parsing
+    /// early; `None` without it.
+    pub(crate) preview: Option<usize>,
+    /// Lines kept from the start of each document (`--head`).
+    pub(crate) head: Option<usize>,
+    /// Lines kept from the end of each document (`--tail`).
+    pub(crate) tail: Option<usize>,
+    /// Pattern searched for in [`Mode::Grep`].
+    pub(crate) grep: Option<String>,
+    /// Characters per chunk in [`Mode::Chunk`].
+    pub(crate) chunk_size: usize,
+    /// Skip the config file (`--no-config`).
+    pub(crate) no_config: bool,
+    /// Make the output a function of the inputs and command line alone
+    /// (`--deterministic`): no config file, no terminal rendering.
+    pub(crate) deterministic: bool,
+    /// With [`Mode::Csv`], write one file per sheet into this directory
+    /// instead of streaming to stdout.
+    pub(crate) csv_dir: Option<PathBuf>,
+    /// Write output to this file instead of stdout; may contain `{stem}`,
+    /// `{name}`, and `{ext}` placeholders expanded per input.
+    pub(crate) output: Option<String>,
+    /// Pager behaviour for terminal output.
+    pub(crate) paging: Paging,
+    /// Page plain text output on a terminal, not just markdown.
+    pub(crate) pager_plain: bool,
+    /// Pager command line (`--pager`); bat picks one from the
+    /// environment if `None`.
+    pub(crate) pager: Option<String>,
+    /// bat theme for terminal output (`--theme`); `$BAT_THEME` or `ansi`
+    /// if `None`.
+    pub(crate) theme: Option<String>,
+    /// Decorations around terminal output (`--style`, `--line-numbers`).
+    pub(crate) style: Style,
+    /// Line wrapping on a terminal (`--wrap`).
+    pub(crate) wrap: Wrap,
+    /// Error reporting format.
+    pub(crate) errors: ErrorFormat,
+    /// Notes reported on stderr (`-v` once or twice): 1 for each
+    /// document's format, size, and time, 2 for the parser's decisions.
+    pub(crate) verbose: u8,
+    /// Leave out warnings (`--quiet`).
+    pub(crate) quiet: bool,
+    /// Leave out the `==> name <==` lines between inputs and archive
+    /// members (`--no-filename`).
+    pub(crate) no_filename: bool,
+    /// Ask which sheets or slides to show (`--pick`).
+    pub(crate) pick: bool,
+    /// Put the output on the clipboard instead of stdout (`--copy`).
+    pub(crate) copy: bool,
+    /// Stop at the first failure (`--fail-fast`).
+    pub(crate) fail_fast: bool,
+    /// Download `http(s)://` arguments (`--allow-remote`).
+    pub(crate) allow_remote: bool,
+    /// List of further input paths (`--files-from`); `-` means stdin.
+    pub(crate) files_from: Option<OsString>,
+    /// `--files-from` entries are separated by NUL bytes rather than
+    /// newlines (`--null`).
+    pub(crate) null: bool,
+    /// Inputs extracted at once (`--jobs`); 0 means one per CPU.
+    pub(crate) jobs: usize,
+    /// Show changed words rather than lines in `batdoc diff`
+    /// (`--word-diff`).
+    pub(crate) word_diff: bool,
+    /// Address for `batdoc serve` to listen on (`--listen`).
+    pub(crate) listen: Option<String>,
+    /// Input paths in order; `-` means stdin. Never empty unless
+    /// `files_from` is set, or for `batdoc serve`.
+    pub(crate) files: Vec<OsString>,
+}
+
+/// What `main` should do after parsing.
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)] // built once per run
+pub(crate) enum Command {
+    /// Print usage and exit successfully.
+    Help,
+    /// Print the version and exit successfully.
+    Version,
+    /// Process the given inputs.
+    Run(Args),
+    /// Compare the two inputs (`batdoc diff OLD NEW`).
+    Diff(Args),
+    /// Convert documents posted over HTTP (`batdoc serve`).
+    Serve(Args),
+}
+
+/// Parse command-line arguments (without the program name).
+///
+/// Arguments are taken as `OsString`s so that file names which are not
+/// valid UTF-8 still work. Short flags may be combined (`-pi`), and
+/// value-taking options accept `--opt VALUE`, `--opt=VALUE`, `-o VALUE`,
+/// and `-oVALUE`. Returns an error message suitable for printing
+/// after `batdoc: `.
+#[allow(clippy::too_many_lines)] // one match arm per option
+pub(crate) fn parse_args<I>(args: I) -> Result<Command, String>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let mut mode = Mode::Auto;
+    let mut format = None;
+    let mut assume_ext = None;
+    let mut opts = Overrides::default();
+    let mut pages = None;
+    let mut sheet = None;
+    let mut password = None;
+    let mut encoding = None;
+    let mut summary_words = summary::DEFAULT_WORDS;
+    let mut preview = None;
+    let mut head = None;
+    let mut tail = None;
+    let mut grep = None;
+    let mut chunk_size = chunk::DEFAULT_SIZE;
+    let mut no_config = false;
+    let mut deterministic = false;
+    let mut csv_dir = None;
+    let mut output = None;
+    let mut paging = Paging::Auto;
+    let mut pager_plain = false;
+    let mut pager_cmd = None;
+    let mut theme = None;
+    let mut style = Style::DEFAULT;
+    let mut line_numbers = false;
+    let mut wrap = Wrap::Auto;
+    let mut errors = ErrorFormat::Text;
+    let mut verbose = 0u8;
+    let mut quiet = false;
+    let mut fail_fast = false;
+    let mut copy = false;
+    let mut pick = false;
+    let mut no_filename = false;
+    let mut allow_remote = false;
+    let mut files_from = None;
+    let mut null = false;
+    let mut jobs = 1;
+    let mut word_diff = false;
+    let mut listen = None;
+    let mut files: Vec<OsString> = Vec::new();
+    let mut args = args.into_iter().peekable();
+
+    // A subcommand is only recognized as the first argument; a file
+    // named diff can still be given as ./diff
+    let diff = args.next_if(|arg| arg == "diff").is_some();
+    let serve = !diff && args.next_if(|arg| arg == "serve").is_some();
+
+    while let Some(arg) = args.next() {
+        // Everything after -- is a file name, even one starting with -
+        if arg == "--" {
+            files.extend(args.by_ref());
+            break;
+        }
+
+        // Options are ASCII, so anything that isn't UTF-8 is a file name
+        let arg = match arg.into_string() {
+            Ok(arg) => arg,
+            Err(path) => {
+                files.push(path);
+                continue;
+            }
+        };
+
+        for (name, inline_value) in split_arg(arg) {
+            if inline_value.is_some() && !VALUE_OPTIONS.contains(&name.as_str()) {
+                return Err(format!("option {name} does not take a value"));
+            }
+
+            match name.as_str() {
+                "-h" | "--help" => return Ok(Command::Help),
+                "-V" | "--version" => return Ok(Command::Version),
+                "-p" | "--plain" => mode = Mode::Plain,
+                "-m" | "--markdown" => mode = Mode::Markdown,
+                "--to" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    mode = parse_mode(&value)?;
+                }
+                "--format" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    format = Some(Format::from_name(&value).ok_or_else(|| {
+                        format!(
+                            "invalid value for --format: '{value}' \
+                             (expected doc, docx, xls, xlsx, pptx, or pdf)"
+                        )
+                    })?);
+                }
+                "--assume-ext" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    let ext = value.strip_prefix('.').unwrap_or(&value);
+                    assume_ext = Some(Format::from_extension(ext).ok_or_else(|| {
+                        format!(
+                            "invalid value for --assume-ext: '{value}' (not a supported extension)"
+                        )
+                    })?);
+                }
+                "--csv" => mode = Mode::Csv,
+                "--summary" => mode = Mode::Summary,
+                "--metadata" => mode = Mode::Metadata,
+                "--stats" => mode = Mode::Stats,
+                "--list" => mode = Mode::List,
+                "--grep" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    if value.is_empty() {
+                        return Err("option --grep requires a non-empty pattern".into());
+                    }
+                    grep = Some(value);
+                    mode = Mode::Grep;
+                }
+                "--chunk" => mode = Mode::Chunk,
+                "--chunk-size" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    chunk_size = value.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                        format!(
+                            "invalid value for --chunk-size: '{value}' \
+                             (expected a number of characters)"
+                        )
+                    })?;
+                    mode = Mode::Chunk;
+                }
+                "--summary-words" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    summary_words = value.parse().map_err(|_| {
+                        format!("invalid value for --summary-words: '{value}' (expected a number)")
+                    })?;
+                    mode = Mode::Summary;
+                }
+                "--preview" => {
+                    preview.get_or_insert(PREVIEW_KB * 1024);
+                }
+                "--preview-size" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    let kb: usize = value.parse().ok().filter(|&kb| kb > 0).ok_or_else(|| {
+                        format!(
+                            "invalid value for --preview-size: '{value}' (expected a number of KB)"
+                        )
+                    })?;
+                    preview = Some(kb.saturating_mul(1024));
+                }
+                "--head" | "--tail" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    let lines = value.parse().map_err(|_| {
+                        format!("invalid value for {name}: '{value}' (expected a number)")
+                    })?;
+                    if name == "--head" {
+                        head = Some(lines);
+                    } else {
+                        tail = Some(lines);
+                    }
+                }
+                "--csv-dir" => {
+                    csv_dir = Some(PathBuf::from(take_value(&name, inline_value, &mut args)?));
+                    mode = Mode::Csv;
+                }
+                "-o" | "--output" => output = Some(take_str(&name, inline_value, &mut args)?),
+                "-i" | "--images" => opts.images = Some(true),
+                "--no-reflow" => opts.reflow = Some(false),
+                "--table" => opts.table = Some(true),
+                "--escape-html" => opts.escape_html = Some(true),
+                "--control-labels" => opts.control_labels = Some(true),
+                "--embedded" => opts.embedded = Some(true),
+                "--page-breaks" => {
+                    opts.page_breaks = Some(take_str(&name, inline_value, &mut args)?);
+                }
+                "--no-print-area" => opts.print_area = Some(false),
+                "--hidden" => opts.hidden = Some(true),
+                "--pages" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    pages = Some(PageRanges::parse(&value)?);
+                }
+                "--sheet" => sheet = Some(take_str(&name, inline_value, &mut args)?),
+                "--password" => password = Some(take_str(&name, inline_value, &mut args)?),
+                "--encoding" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    encoding = Some(codepage::parse(&value).ok_or_else(|| {
+                        format!(
+                            "invalid value for --encoding: '{value}' \
+                             (expected a codepage such as cp1251 or koi8-r)"
+                        )
+                    })?);
+                }
+                "--cell-newline" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    opts.cell_newline = Some(CellNewline::parse(&value).ok_or_else(|| {
+                        format!(
+                            "invalid value for --cell-newline: '{value}' (expected escape or space)"
+                        )
+                    })?);
+                }
+                "--max-rows" | "--max-cols" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    let limit = value.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                        format!("invalid value for {name}: '{value}' (expected a positive number)")
+                    })?;
+                    if name == "--max-rows" {
+                        opts.max_rows = Some(limit);
+                    } else {
+                        opts.max_cols = Some(limit);
+                    }
+                }
+                "--comments" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    opts.comments = Some(Comments::parse(&value).ok_or_else(|| {
+                        format!(
+                            "invalid value for --comments: '{value}' \
+                             (expected none, inline, or end)"
+                        )
+                    })?);
+                }
+                "--revisions" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    opts.revisions = Some(Revisions::parse(&value).ok_or_else(|| {
+                        format!(
+                            "invalid value for --revisions: '{value}' \
+                             (expected final, original, or marked)"
+                        )
+                    })?);
+                }
+                "--marks" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    opts.marks = Some(Marks::parse(&value).ok_or_else(|| {
+                        format!(
+                            "invalid value for --marks: '{value}' \
+                             (expected extended, emphasis, or none)"
+                        )
+                    })?);
+                }
+                "--no-config" => no_config = true,
+                "--deterministic" => deterministic = true,
+                "--paging" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    paging = parse_paging(&value)?;
+                }
+                "--pager-plain" => pager_plain = true,
+                "--pager" => pager_cmd = Some(take_str(&name, inline_value, &mut args)?),
+                "--no-pager" => paging = Paging::Never,
+                "--theme" => theme = Some(take_str(&name, inline_value, &mut args)?),
+                "--style" => style = Style::parse(&take_str(&name, inline_value, &mut args)?)?,
+                "-n" | "--line-numbers" => line_numbers = true,
+                "--wrap" => {
+                    wrap = match take_str(&name, inline_value, &mut args)?.as_str() {
+                        "auto" => Wrap::Auto,
+                        "character" => Wrap::Character,
+                        "never" => Wrap::Never,
+                        other => {
+                            return Err(format!(
+                                "invalid value for --wrap: '{other}' \
+                                 (expected auto, character, or never)"
+                            ))
+                        }
+                    };
+                }
+                "--errors" => {
+                    errors = match take_str(&name, inline_value, &mut args)?.as_str() {
+                        "text" => ErrorFormat::Text,
+                        "json" => ErrorFormat::Json,
+                        other => {
+                            return Err(format!(
+                                "invalid value for --errors: '{other}' (expected text or json)"
+                            ))
+                        }
+                    };
+                }
+                "--allow-remote" => allow_remote = true,
+                "-v" | "--verbose" => verbose = verbose.saturating_add(1),
+                "-q" | "--quiet" => quiet = true,
+                "--fail-fast" => fail_fast = true,
+                "--copy" => copy = true,
+                "--pick" => pick = true,
+                "--no-filename" => no_filename = true,
+                "--word-diff" => word_diff = true,
+                "--listen" => listen = Some(take_str(&name, inline_value, &mut args)?),
+                "--files-from" => files_from = Some(take_value(&name, inline_value, &mut args)?),
+                "-0" | "--null" => null = true,
+                "-j" | "--jobs" => {
+                    let value = take_str(&name, inline_value, &mut args)?;
+                    jobs = value.parse().map_err(|_| {
+                        format!("invalid value for {name}: '{value}' (expected a number)")
+                    })?;
+                }
+                "-" => files.push(name.into()),
+                s if s.starts_with('-') => return Err(format!("unknown option: {s}")),
+                _ => files.push(name.into()),
+            }
+        }
+    }
+
+    if mode == Mode::Stats && output.is_some() {
+        return Err("--stats writes to stdout and cannot be combined with --output".into());
+    }
+
+    if diff {
+        if files_from.is_some() {
+            return Err("diff compares two files and cannot be combined with --files-from".into());
+        }
+        if files.len() != 2 {
+            return Err("diff takes two files: batdoc diff OLD NEW".into());
+        }
+        if output.is_some() {
+            return Err("diff writes to stdout and cannot be combined with --output".into());
+        }
+        if copy {
+            return Err("diff writes to stdout and cannot be combined with --copy".into());
+        }
+    } else if word_diff {
+        return Err("--word-diff is only used with batdoc diff".into());
+    }
+
+    if serve {
+        if !files.is_empty() || files_from.is_some() {
+            return Err("serve reads documents from requests and takes no files".into());
+        }
+        if output.is_some() || csv_dir.is_some() || copy {
+            return Err("serve answers requests and cannot write to files or the clipboard".into());
+        }
+    } else if listen.is_some() {
+        return Err("--listen is only used with batdoc serve".into());
+    }
+
+    if copy {
+        if output.is_some() {
+            return Err("--copy and --output cannot be combined".into());
+        }
+        // Copied text is pasted into chats and issues, which show markdown
+        if mode == Mode::Auto {
+            mode = Mode::Markdown;
+        }
+    }
+
+    if head.is_some() && tail.is_some() {
+        return Err("--head and --tail cannot be combined".into());
+    }
+    if (head.is_some() || tail.is_some())
+        && (diff || !matches!(mode, Mode::Auto | Mode::Plain | Mode::Markdown | Mode::Org))
+    {
+        return Err("--head and --tail only apply to plain, markdown, and Org output".into());
+    }
+
+    // A preview pane has no room for a pager or embedded images
+    if preview.is_some() {
+        paging = Paging::Never;
+        opts.images = Some(false);
+    }
+
+    // -n adds numbers to whatever --style is, wherever it was given
+    style.numbers |= line_numbers;
+
+    if pick && jobs != 1 {
+        return Err(
+            "--pick asks about one file at a time and cannot be combined with --jobs".into(),
+        );
+    }
+
+    if null && files_from.is_none() {
+        return Err("--null is only used with --files-from".into());
+    }
+
+    // No files specified → read from stdin
+    if files.is_empty() && files_from.is_none() && !serve {
+        files.push("-".into());
+    }
+
+    let args = Args {
+        mode,
+        format,
+        assume_ext,
+        opts,
+        pages,
+        sheet,
+        password,
+        encoding,
+        summary_words,
+        preview,
+        head,
+        tail,
+        grep,
+        chunk_size,
+        no_config,
+        deterministic,
+        csv_dir,
+        output,
+        paging,
+        pager_plain,
+        pager: pager_cmd,
+        theme,
+        style,
+        wrap,
+        errors,
+        verbose,
+        quiet,
+        fail_fast,
+        pick,
+        copy,
+        no_filename,
+        allow_remote,
+        files_from,
+        null,
+        jobs,
+        word_diff,
+        listen,
+        files,
+    };
+    Ok(if diff {
+        Command::Diff(args)
+    } else if serve {
+        Command::Serve(args)
+    } else {
+        Command::Run(args)
+    })
+}
+
+/// Output kept by `--preview` without `--preview-size`, in KB.
+const PREVIEW_KB: usize = 64;
+
+/// Options that take a value, in every spelling.
+const VALUE_OPTIONS: &[&str] = &[
+    "--to",
+    "--listen",
+    "--format",
+    "--assume-ext",
+    "--pages",
+    "--sheet",
+    "--password",
+    "--encoding",
+    "--cell-newline",
+    "--max-rows",
+    "--max-cols",
+    "--comments",
+    "--revisions",
+    "--marks",
+    "--page-breaks",
+    "--summary-words",
+    "--preview-size",
+    "--head",
+    "--tail",
+    "--grep",
+    "--chunk-size",
+    "--csv-dir",
+    "-o",
+    "--output",
+    "--paging",
+    "--pager",
+    "--theme",
+    "--style",
+    "--wrap",
+    "--errors",
+    "-j",
+    "--jobs",
+    "--files-from",
+];
+
+/// Split one argument into `(name, inline value)` pairs: `--opt=value`
+/// becomes one pair, and a cluster of short flags such as `-pi` becomes one
+/// pair per flag. A value-taking short flag ends the cluster, with the rest
+/// as its value (`-ofile.md`). Anything else is returned unchanged.
+fn split_arg(arg: String) -> Vec<(String, Option<String>)> {
+    if arg.starts_with("--") {
+        return match arg.split_once('=') {
+            Some((n, v)) => vec![(n.to_string(), Some(v.to_string()))],
+            None => vec![(arg, None)],
+        };
+    }
+    let Some(cluster) = arg.strip_prefix('-').filter(|c| c.chars().count() > 1) else {
+        return vec![(arg, None)];
+    };
+    let mut parts = Vec::new();
+    for (i, c) in cluster.char_indices() {
+        let name = format!("-{c}");
+        if VALUE_OPTIONS.contains(&name.as_str()) {
+            let rest = &cluster[i + c.len_utf8()..];
+            let rest = rest.strip_prefix('=').unwrap_or(rest);
+            parts.push((name, (!rest.is_empty()).then(|| rest.to_string())));
+            break;
+        }
+        parts.push((name, None));
+    }
+    parts
+}
+
+/// Get the value for a value-taking option, either from `--opt=value` or
+/// from the next argument.
+fn take_value(
+    name: &str,
+    inline_value: Option<String>,
+    args: &mut impl Iterator<Item = OsString>,
+) -> Result<OsString, String> {
+    inline_value
+        .map(OsString::from)
+        .or_else(|| args.next())
+        .ok_or_else(|| format!("option {name} requires a value"))
+}
+
+/// Like [`take_value`], for options whose value must be UTF-8.
+fn take_str(
+    name: &str,
+    inline_value: Option<String>,
+    args: &mut impl Iterator<Item = OsString>,
+) -> Result<String, String> {
+    take_value(name, inline_value, args)?
+        .into_string()
+        .map_err(|v| format!("invalid value for {name}: '{}'", v.to_string_lossy()))
+}
+
+pub(crate) fn parse_mode(value: &str) -> Result<Mode, String> {
+    match value {
+        "markdown" | "md" => Ok(Mode::Markdown),
+        "plain" | "text" | "txt" => Ok(Mode::Plain),
+        "org" => Ok(Mode::Org),
+        "csv" => Ok(Mode::Csv),
+        "xml" => Ok(Mode::Xml),
+        _ => Err(format!(
+            "invalid value for --to: '{value}' (expected markdown, plain, org, csv, or xml)"
+        )),
+    }
+}
+
+fn parse_paging(value: &str) -> Result<Paging, String> {
+    match value {
+        "auto" => Ok(Paging::Auto),
+        "always" => Ok(Paging::Always),
+        "never" => Ok(Paging::Never),
+        _ => Err(format!(
+            "invalid value for --paging: '{value}' (expected auto, always, or never)"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(OsString::from))
+    }
+
+    fn run_args(args: &[&str]) -> Args {
+        match parse(args) {
+            Ok(Command::Run(a)) => a,
+            other => panic!("expected Run, got {other:?}"),
+        }
+    }
+
+    // ── parse_args ───────────────────────────────────────────────
+
+    #[test]
+    fn defaults_to_stdin() {
+        let args = run_args(&[]);
+        assert_eq!(args.mode, Mode::Auto);
+        assert_eq!(args.files, vec!["-"]);
+    }
+
+    #[test]
+    fn help_flag() {
+        assert!(matches!(parse(&["-h"]), Ok(Command::Help)));
+        assert!(matches!(parse(&["a.doc", "--help"]), Ok(Command::Help)));
+    }
+
+    #[test]
+    fn version_flag() {
+        assert!(matches!(parse(&["-V"]), Ok(Command::Version)));
+        assert!(matches!(parse(&["--version"]), Ok(Command::Version)));
+    }
+
+    #[test]
+    fn combined_short_flags() {
+        let args = run_args(&["-pi", "a.docx"]);
+        assert_eq!(args.mode, Mode::Plain);
+        assert_eq!(args.opts.images, Some(true));
+
+        assert_eq!(
+            run_args(&["-po", "out.txt"]).output.as_deref(),
+            Some("out.txt")
+        );
+        assert_eq!(
+            run_args(&["-o{stem}.md"]).output.as_deref(),
+            Some("{stem}.md")
+        );
+        assert_eq!(run_args(&["-o=x.md"]).output.as_deref(), Some("x.md"));
+
+        let err = parse(&["-px"]).unwrap_err();
+        assert_eq!(err, "unknown option: -x");
+    }
+
+    #[test]
+    fn mode_flags() {
+        assert_eq!(run_args(&["-p"]).mode, Mode::Plain);
+        assert_eq!(run_args(&["--markdown"]).mode, Mode::Markdown);
+    }
+
+    #[test]
+    fn to_with_separate_value() {
+        assert_eq!(run_args(&["--to", "org", "a.docx"]).mode, Mode::Org);
+        assert_eq!(run_args(&["--to", "xml", "a.docx"]).mode, Mode::Xml);
+    }
+
+    #[test]
+    fn to_with_inline_value() {
+        let args = run_args(&["--to=plain", "a.docx"]);
+        assert_eq!(args.mode, Mode::Plain);
+        assert_eq!(args.files, vec!["a.docx"]);
+    }
+
+    #[test]
+    fn to_missing_value() {
+        let err = parse(&["--to"]).unwrap_err();
+        assert!(err.contains("requires a value"));
+    }
+
+    #[test]
+    fn to_invalid_value() {
+        let err = parse(&["--to", "html"]).unwrap_err();
+        assert!(err.contains("'html'"));
+    }
+
+    #[test]
+    fn option_flags() {
+        let args = run_args(&["-i", "--no-reflow", "--table"]);
+        assert_eq!(args.opts.images, Some(true));
+        assert_eq!(args.opts.reflow, Some(false));
+        assert_eq!(args.opts.table, Some(true));
+        assert_eq!(args.opts.escape_html, None);
+        assert_eq!(run_args(&["--escape-html"]).opts.escape_html, Some(true));
+        assert_eq!(args.opts.print_area, None);
+        assert_eq!(run_args(&["--no-print-area"]).opts.print_area, Some(false));
+        assert_eq!(args.opts.hidden, None);
+        assert_eq!(run_args(&["--hidden"]).opts.hidden, Some(true));
+        assert_eq!(args.opts.control_labels, None);
+        assert_eq!(
+            run_args(&["--control-labels"]).opts.control_labels,
+            Some(true)
+        );
+        assert_eq!(args.opts.embedded, None);
+        assert_eq!(run_args(&["--embedded"]).opts.embedded, Some(true));
+    }
+
+    #[test]
+    fn cell_newline_flag() {
+        assert_eq!(run_args(&[]).opts.cell_newline, None);
+        assert_eq!(
+            run_args(&["--cell-newline", "space"]).opts.cell_newline,
+            Some(CellNewline::Space)
+        );
+        let err = parse(&["--cell-newline=tab"]).unwrap_err();
+        assert!(err.contains("expected escape or space"));
+    }
+
+    #[test]
+    fn max_rows_and_cols_flags() {
+        let args = run_args(&["--max-rows", "20", "--max-cols=5"]);
+        assert_eq!(args.opts.max_rows, Some(20));
+        assert_eq!(args.opts.max_cols, Some(5));
+        assert_eq!(run_args(&[]).opts.max_rows, None);
+        let err = parse(&["--max-rows", "0"]).unwrap_err();
+        assert!(err.contains("expected a positive number"));
+    }
+
+    #[test]
+    fn comments_flag() {
+        assert_eq!(run_args(&[]).opts.comments, None);
+        assert_eq!(
+            run_args(&["--comments", "inline"]).opts.comments,
+            Some(Comments::Inline)
+        );
+        let err = parse(&["--comments=margin"]).unwrap_err();
+        assert!(err.contains("expected none, inline, or end"));
+    }
+
+    #[test]
+    fn revisions_flag() {
+        assert_eq!(run_args(&[]).opts.revisions, None);
+        assert_eq!(
+            run_args(&["--revisions=marked"]).opts.revisions,
+            Some(Revisions::Marked)
+        );
+        let err = parse(&["--revisions", "all"]).unwrap_err();
+        assert!(err.contains("expected final, original, or marked"));
+    }
+
+    #[test]
+    fn marks_flag() {
+        assert_eq!(run_args(&[]).opts.marks, None);
+        assert_eq!(run_args(&["--marks", "none"]).opts.marks, Some(Marks::None));
+        let err = parse(&["--marks=html"]).unwrap_err();
+        assert!(err.contains("expected extended, emphasis, or none"));
+    }
+
+    #[test]
+    fn page_breaks_flag() {
+        assert_eq!(run_args(&[]).opts.page_breaks, None);
+        assert_eq!(
+            run_args(&["--page-breaks", "---"])
+                .opts
+                .page_breaks
+                .as_deref(),
+            Some("---")
+        );
+    }
+
+    #[test]
+    fn unset_options_defer_to_config() {
+        let args = run_args(&["a.docx"]);
+        assert_eq!(args.opts, Overrides::default());
+        assert!(!args.no_config);
+        assert!(run_args(&["--no-config"]).no_config);
+        assert!(!args.deterministic);
+        assert!(run_args(&["--deterministic"]).deterministic);
+    }
+
+    #[test]
+    fn paging_flags() {
+        let args = run_args(&[]);
+        assert_eq!(args.paging, Paging::Auto);
+        assert!(!args.pager_plain);
+
+        let args = run_args(&["--paging", "always", "--pager-plain"]);
+        assert_eq!(args.paging, Paging::Always);
+        assert!(args.pager_plain);
+        assert_eq!(run_args(&["--paging=never"]).paging, Paging::Never);
+
+        assert_eq!(run_args(&["--no-pager"]).paging, Paging::Never);
+        assert_eq!(
+            run_args(&["--pager", "less -RS"]).pager.as_deref(),
+            Some("less -RS")
+        );
+        assert_eq!(run_args(&[]).pager, None);
+
+        let err = parse(&["--paging", "sometimes"]).unwrap_err();
+        assert!(err.contains("'sometimes'"));
+    }
+
+    #[test]
+    fn allow_remote_flag() {
+        let args = run_args(&["https://example.com/a.docx"]);
+        assert!(!args.allow_remote);
+        assert_eq!(args.files, vec!["https://example.com/a.docx"]);
+        assert!(run_args(&["--allow-remote", "-"]).allow_remote);
+    }
+
+    #[test]
+    fn errors_flag() {
+        assert_eq!(run_args(&[]).errors, ErrorFormat::Text);
+        assert_eq!(run_args(&["--errors=json"]).errors, ErrorFormat::Json);
+        assert!(parse(&["--errors", "xml"]).unwrap_err().contains("'xml'"));
+    }
+
+    #[test]
+    fn output_flag() {
+        assert_eq!(run_args(&[]).output, None);
+        let args = run_args(&["-o", "{stem}.md", "a.docx"]);
+        assert_eq!(args.output.as_deref(), Some("{stem}.md"));
+        assert_eq!(args.files, vec!["a.docx"]);
+        assert_eq!(
+            run_args(&["--output=out.txt"]).output.as_deref(),
+            Some("out.txt")
+        );
+        assert!(parse(&["-o"]).is_err());
+    }
+
+    #[test]
+    fn csv_flags() {
+        assert_eq!(run_args(&["--csv"]).mode, Mode::Csv);
+        assert_eq!(run_args(&["--to", "csv"]).mode, Mode::Csv);
+
+        let args = run_args(&["--csv-dir=out", "a.xlsx"]);
+        assert_eq!(args.mode, Mode::Csv);
+        assert_eq!(args.csv_dir, Some(PathBuf::from("out")));
+    }
+
+    #[test]
+    fn format_flag() {
+        assert_eq!(run_args(&["a.bin"]).format, None);
+        assert_eq!(
+            run_args(&["--format", "xls", "a.bin"]).format,
+            Some(Format::Xls)
+        );
+        assert_eq!(run_args(&["--format=pptx"]).format, Some(Format::Pptx));
+
+        let err = parse(&["--format", "odt"]).unwrap_err();
+        assert!(err.contains("'odt'"));
+    }
+
+    #[test]
+    fn assume_ext_flag() {
+        assert_eq!(run_args(&[]).assume_ext, None);
+        assert_eq!(
+            run_args(&["--assume-ext", "docx"]).assume_ext,
+            Some(Format::Docx)
+        );
+        assert_eq!(
+            run_args(&["--assume-ext=.XLSM"]).assume_ext,
+            Some(Format::Xlsx)
+        );
+
+        let err = parse(&["--assume-ext", "txt"]).unwrap_err();
+        assert!(err.contains("'txt'"));
+    }
+
+    #[test]
+    fn summary_flags() {
+        let args = run_args(&["--summary"]);
+        assert_eq!(args.mode, Mode::Summary);
+        assert_eq!(args.summary_words, summary::DEFAULT_WORDS);
+
+        let args = run_args(&["--summary-words=10"]);
+        assert_eq!(args.mode, Mode::Summary);
+        assert_eq!(args.summary_words, 10);
+        assert!(parse(&["--summary-words", "many"])
+            .unwrap_err()
+            .contains("expected a number"));
+    }
+
+    #[test]
+    fn preview_flags() {
+        assert_eq!(run_args(&[]).preview, None);
+
+        let args = run_args(&["--preview", "-i", "--paging=always"]);
+        assert_eq!(args.preview, Some(PREVIEW_KB * 1024));
+        assert_eq!(args.paging, Paging::Never);
+        assert_eq!(args.opts.images, Some(false));
+
+        assert_eq!(run_args(&["--preview-size=8"]).preview, Some(8 * 1024));
+        assert_eq!(
+            run_args(&["--preview-size", "8", "--preview"]).preview,
+            Some(8 * 1024)
+        );
+        assert!(parse(&["--preview-size", "0"])
+            .unwrap_err()
+            .contains("expected a number of KB"));
+    }
+
+    #[test]
+    fn head_tail_flags() {
+        let args = run_args(&["--head", "20", "-p"]);
+        assert_eq!((args.head, args.tail), (Some(20), None));
+        let args = run_args(&["--tail=5"]);
+        assert_eq!((args.head, args.tail), (None, Some(5)));
+
+        for bad in [
+            &["--head", "1", "--tail", "1"][..],
+            &["--head", "1", "--stats"],
+            &["diff", "--tail", "3", "a.doc", "b.doc"],
+        ] {
+            assert!(parse(bad).is_err(), "{bad:?}");
+        }
+        assert!(parse(&["--head", "-3"])
+            .unwrap_err()
+            .contains("expected a number"));
+    }
+
+    #[test]
+    fn diff_subcommand() {
+        let args = match parse(&["diff", "--word-diff", "a.doc", "b.docx"]) {
+            Ok(Command::Diff(a)) => a,
+            other => panic!("expected Diff, got {other:?}"),
+        };
+        assert!(args.word_diff);
+        assert_eq!(args.files, vec!["a.doc", "b.docx"]);
+
+        // Only as the first argument
+        assert_eq!(run_args(&["a.doc", "diff"]).files, vec!["a.doc", "diff"]);
+        assert!(parse(&["diff", "a.doc"]).unwrap_err().contains("two files"));
+        assert!(parse(&["diff", "a", "b", "-o", "x"])
+            .unwrap_err()
+            .contains("--output"));
+        assert!(parse(&["--word-diff", "a"])
+            .unwrap_err()
+            .contains("only used with"));
+    }
+
+    #[test]
+    fn files_from_flags() {
+        let args = run_args(&["--files-from", "list.txt"]);
+        assert_eq!(args.files_from, Some("list.txt".into()));
+        assert!(args.files.is_empty());
+        assert!(!args.null);
+
+        let args = run_args(&["-0", "--files-from=-", "a.doc"]);
+        assert_eq!(args.files_from, Some("-".into()));
+        assert_eq!(args.files, vec!["a.doc"]);
+        assert!(args.null);
+
+        assert!(parse(&["--null"]).unwrap_err().contains("--files-from"));
+        assert!(parse(&["diff", "a", "b", "--files-from", "x"])
+            .unwrap_err()
+            .contains("--files-from"));
+    }
+
+    #[test]
+    fn display_flags() {
+        let args = run_args(&[]);
+        assert_eq!(args.theme, None);
+        assert_eq!(args.style, Style::DEFAULT);
+        assert_eq!(args.wrap, Wrap::Auto);
+
+        let args = run_args(&["--theme", "Nord", "--wrap=never"]);
+        assert_eq!(args.theme.as_deref(), Some("Nord"));
+        assert_eq!(args.wrap, Wrap::Never);
+        assert!(parse(&["--wrap", "word"]).unwrap_err().contains("'word'"));
+
+        let style = run_args(&["--style", "header,numbers"]).style;
+        assert!(style.header && style.numbers && !style.grid);
+        assert_eq!(run_args(&["--style=plain"]).style, Style::PLAIN);
+        assert_eq!(run_args(&["--style", "full"]).style, Style::FULL);
+        assert!(parse(&["--style", "fancy"])
+            .unwrap_err()
+            .contains("'fancy'"));
+
+        // -n combines with any style, before or after it
+        let style = run_args(&["-n", "--style", "plain"]).style;
+        assert!(style.numbers && !style.header);
+        assert!(run_args(&["-pn"]).style.numbers);
+    }
+
+    #[test]
+    fn verbosity_flags() {
+        let args = run_args(&[]);
+        assert_eq!((args.verbose, args.quiet), (0, false));
+        assert_eq!(run_args(&["-v"]).verbose, 1);
+        assert_eq!(run_args(&["-vv", "--verbose"]).verbose, 3);
+        assert!(run_args(&["-pq"]).quiet);
+        assert!(run_args(&["--quiet"]).quiet);
+    }
+
+    #[test]
+    fn no_filename_flag() {
+        assert!(!run_args(&["a.doc", "b.doc"]).no_filename);
+        assert!(run_args(&["--no-filename", "a.doc", "b.doc"]).no_filename);
+    }
+
+    #[test]
+    fn pick_flag() {
+        assert!(!run_args(&[]).pick);
+        assert!(run_args(&["--pick", "deck.pptx"]).pick);
+        assert!(parse(&["--pick", "-j4", "a.xlsx", "b.xlsx"])
+            .unwrap_err()
+            .contains("--jobs"));
+    }
+
+    #[test]
+    fn fail_fast_flag() {
+        assert!(!run_args(&[]).fail_fast);
+        assert!(run_args(&["--fail-fast", "a.doc"]).fail_fast);
+    }
+
+    #[test]
+    fn jobs_flag() {
+        assert_eq!(run_args(&[]).jobs, 1);
+        assert_eq!(run_args(&["-j4"]).jobs, 4);
+        assert_eq!(run_args(&["--jobs", "0"]).jobs, 0);
+        assert_eq!(run_args(&["-pj", "8"]).jobs, 8);
+        assert!(parse(&["--jobs=all"])
+            .unwrap_err()
+            .contains("expected a number"));
+    }
+
+    #[test]
+    fn metadata_flag() {
+        assert_eq!(run_args(&["--metadata"]).mode, Mode::Metadata);
+        assert_eq!(run_args(&["--metadata", "-p"]).mode, Mode::Plain);
+    }
+
+    #[test]
+    fn serve_subcommand() {
+        match parse(&["serve", "--listen", "0.0.0.0:9000", "-j", "4"]) {
+            Ok(Command::Serve(args)) => {
+                assert_eq!(args.listen.as_deref(), Some("0.0.0.0:9000"));
+                assert!(args.files.is_empty());
+            }
+            other => panic!("expected serve, got {other:?}"),
+        }
+        assert!(parse(&["serve", "a.doc"]).unwrap_err().contains("no files"));
+        assert!(parse(&["--listen", ":80", "a.doc"])
+            .unwrap_err()
+            .contains("serve"));
+    }
+
+    #[test]
+    fn copy_flag() {
+        let args = run_args(&["--copy", "a.docx"]);
+        assert!(args.copy);
+        assert_eq!(args.mode, Mode::Markdown);
+        assert_eq!(run_args(&["--copy", "-p", "a.docx"]).mode, Mode::Plain);
+        assert!(parse(&["--copy", "-o", "a.md"])
+            .unwrap_err()
+            .contains("--output"));
+    }
+
+    #[test]
+    fn list_flag() {
+        assert_eq!(run_args(&["--list", "a.pdf"]).mode, Mode::List);
+        assert!(parse(&["--list", "--head", "5"])
+            .unwrap_err()
+            .contains("--head"));
+    }
+
+    #[test]
+    fn chunk_flags() {
+        let args = run_args(&["--chunk", "a.docx"]);
+        assert_eq!(args.mode, Mode::Chunk);
+        assert_eq!(args.chunk_size, chunk::DEFAULT_SIZE);
+        let args = run_args(&["--chunk-size=500", "a.docx"]);
+        assert_eq!(args.mode, Mode::Chunk);
+        assert_eq!(args.chunk_size, 500);
+        assert!(parse(&["--chunk-size", "0"])
+            .unwrap_err()
+            .contains("expected a number"));
+    }
+
+    #[test]
+    fn grep_flag() {
+        let args = run_args(&["--grep", "invoice", "a.docx"]);
+        assert_eq!(args.mode, Mode::Grep);
+        assert_eq!(args.grep.as_deref(), Some("invoice"));
+        assert_eq!(args.files, ["a.docx"]);
+        assert!(parse(&["--grep="]).unwrap_err().contains("non-empty"));
+    }
+
+    #[test]
+    fn stats_flag() {
+        assert_eq!(run_args(&["--stats", "a.docx"]).mode, Mode::Stats);
+        assert!(parse(&["--stats", "-o", "out.txt"])
+            .unwrap_err()
+            .contains("--output"));
+    }
+
+    #[test]
+    fn sheet_flag() {
+        assert_eq!(run_args(&[]).sheet, None);
+        let args = run_args(&["--sheet", "Q3 Data"]);
+        assert_eq!(args.sheet.as_deref(), Some("Q3 Data"));
+        assert_eq!(run_args(&["--sheet=2"]).sheet.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn password_flag() {
+        assert_eq!(run_args(&[]).password, None);
+        let args = run_args(&["--password", "a b=c", "x.docx"]);
+        assert_eq!(args.password.as_deref(), Some("a b=c"));
+        assert_eq!(run_args(&["--password=s3"]).password.as_deref(), Some("s3"));
+    }
+
+    #[test]
+    fn encoding_flag() {
+        assert_eq!(run_args(&[]).encoding, None);
+        assert_eq!(run_args(&["--encoding", "cp1251"]).encoding, Some(1251));
+        assert_eq!(run_args(&["--encoding=koi8-r"]).encoding, Some(20866));
+        let err = parse(&["--encoding", "ebcdic"]).unwrap_err();
+        assert!(err.contains("'ebcdic'"), "{err}");
+    }
+
+    #[test]
+    fn pages_flag() {
+        assert_eq!(run_args(&[]).pages, None);
+        let pages = run_args(&["--pages", "2-5,9"]).pages.unwrap();
+        assert!(pages.contains(9) && !pages.contains(6));
+
+        let err = parse(&["--pages=5-2"]).unwrap_err();
+        assert_eq!(err, "invalid page range '5-2'");
+    }
+
+    #[test]
+    fn double_dash_ends_options() {
+        let args = run_args(&["-p", "--", "-weird-name.docx", "--", "-"]);
+        assert_eq!(args.mode, Mode::Plain);
+        assert_eq!(args.files, ["-weird-name.docx", "--", "-"]);
+
+        match parse(&["diff", "--", "-old.doc", "-new.doc"]) {
+            Ok(Command::Diff(args)) => assert_eq!(args.files, ["-old.doc", "-new.doc"]),
+            other => panic!("expected Diff, got {other:?}"),
+        }
+
+        // A lone -- still means stdin
+        assert_eq!(run_args(&["--"]).files, ["-"]);
+    }
+
+    #[test]
+    fn unknown_option() {
+        let err = parse(&["--bogus"]).unwrap_err();
+        assert_eq!(err, "unknown option: --bogus");
+    }
+
+    #[test]
+    fn flag_with_value_rejected() {
+        let err = parse(&["--table=yes"]).unwrap_err();
+        assert_eq!(err, "option --table does not take a value");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_file_name() {
+        use std::os::unix::ffi::OsStringExt;
+        let name = OsString::from_vec(b"caf\xe9.doc".to_vec());
+        match parse_args(vec![OsString::from("-p"), name.clone()]) {
+            Ok(Command::Run(args)) => {
+                assert_eq!(args.mode, Mode::Plain);
+                assert_eq!(args.files, vec![name]);
+            }
+            other => panic!("expected Run, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn files_in_order() {
+        let args = run_args(&["a.doc", "-", "b.xlsx"]);
+        assert_eq!(args.files, vec!["a.doc", "-", "b.xlsx"]);
+    }
+
+    // ── --help, man page, and parser agree ───────────────────────
+
+    /// Every option in all its spellings, with a value it accepts if it
+    /// takes one.
+    const FLAGS: &[(&[&str], Option<&str>)] = &[
+        (&["-p", "--plain"], None),
+        (&["-m", "--markdown"], None),
+        (&["--to"], Some("org")),
+        (&["--format"], Some("docx")),
+        (&["--assume-ext"], Some("docx")),
+        (&["--csv"], None),
+        (&["--summary"], None),
+        (&["--metadata"], None),
+        (&["--stats"], None),
+        (&["--grep"], Some("total")),
+        (&["--list"], None),
+        (&["--chunk"], None),
+        (&["--chunk-size"], Some("500")),
+        (&["--summary-words"], Some("20")),
+        (&["--csv-dir"], Some("out")),
+        (&["--preview"], None),
+        (&["--preview-size"], Some("8")),
+        (&["--head"], Some("5")),
+        (&["--tail"], Some("5")),
+        (&["-o", "--output"], Some("out.md")),
+        (&["--copy"], None),
+        (&["-i", "--images"], None),
+        (&["--no-reflow"], None),
+        (&["--table"], None),
+        (&["--escape-html"], None),
+        (&["--pages"], Some("2-5,9")),
+        (&["--sheet"], Some("2")),
+        (&["--pick"], None),
+        (&["--password"], Some("secret")),
+        (&["--encoding"], Some("cp1251")),
+        (&["--no-print-area"], None),
+        (&["--hidden"], None),
+        (&["--cell-newline"], Some("space")),
+        (&["--max-rows"], Some("10")),
+        (&["--max-cols"], Some("4")),
+        (&["--comments"], Some("end")),
+        (&["--revisions"], Some("marked")),
+        (&["--marks"], Some("none")),
+        (&["--control-labels"], None),
+        (&["--embedded"], None),
+        (&["--page-breaks"], Some("---")),
+        (&["--paging"], Some("never")),
+        (&["--pager-plain"], None),
+        (&["--pager"], Some("less")),
+        (&["--no-pager"], None),
+        (&["--theme"], Some("ansi")),
+        (&["--style"], Some("header,grid")),
+        (&["-n", "--line-numbers"], None),
+        (&["--wrap"], Some("never")),
+        (&["--no-filename"], None),
+        (&["--errors"], Some("json")),
+        (&["--fail-fast"], None),
+        (&["-v", "--verbose"], None),
+        (&["-q", "--quiet"], None),
+        (&["--no-config"], None),
+        (&["--deterministic"], None),
+        (&["--allow-remote"], None),
+        (&["--files-from"], Some("list.txt")),
+        (&["-0", "--null"], None),
+        (&["-j", "--jobs"], Some("2")),
+        (&["--word-diff"], None),
+        (&["--listen"], Some("127.0.0.1:8080")),
+        (&["-h", "--help"], None),
+        (&["-V", "--version"], None),
+    ];
+
+    /// The option names in the first column of the `Options:` list in
+    /// `USAGE`.
+    fn usage_flags() -> Vec<&'static str> {
+        USAGE
+            .lines()
+            .skip_while(|line| *line != "Options:")
+            .take_while(|line| line.starts_with("  ") || *line == "Options:")
+            .flat_map(|line| {
+                line.split_whitespace()
+                    .map(|word| word.trim_end_matches(','))
+                    .take_while(|word| word.starts_with('-'))
+            })
+            .collect()
+    }
+
+    /// The option names the man page is built with in `build.rs`.
+    fn man_flags() -> Vec<&'static str> {
+        include_str!("../build.rs")
+            .split(".long(\"")
+            .chain(include_str!("../build.rs").split(".short(\""))
+            .filter_map(|part| part.split_once('"').map(|(name, _)| name))
+            .filter(|name| name.starts_with('-'))
+            .collect()
+    }
+
+    #[test]
+    fn every_flag_is_documented_and_parsed() {
+        let (usage, man) = (usage_flags(), man_flags());
+        for &(names, value) in FLAGS {
+            for &name in names {
+                // Some options only apply with a subcommand or another option
+                let mut args = match name {
+                    "--word-diff" => vec!["diff", "old.docx", "new.docx"],
+                    "--listen" => vec!["serve"],
+                    "-0" | "--null" => vec!["--files-from", "list.txt"],
+                    _ => Vec::new(),
+                };
+                args.push(name);
+                args.extend(value);
+                assert!(parse(&args).is_ok(), "{args:?}: {:?}", parse(&args));
+                assert_eq!(VALUE_OPTIONS.contains(&name), value.is_some(), "{name}");
+                assert!(usage.contains(&name), "{name} is not in --help");
+                assert!(man.contains(&name), "{name} is not in the man page");
+            }
+        }
+
+        let known: Vec<&str> = FLAGS
+            .iter()
+            .flat_map(|(names, _)| names.iter().copied())
+            .collect();
+        for name in usage.iter().chain(&man).chain(VALUE_OPTIONS) {
+            assert!(known.contains(name), "{name} is missing from FLAGS");
+        }
+    }
+}
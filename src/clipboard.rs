@@ -0,0 +1,53 @@
+//! Copying output to the system clipboard (`--copy`).
+//!
+//! The text is sent to the terminal as an OSC 52 escape sequence, which
+//! the terminal emulator puts on the clipboard. This needs no clipboard
+//! library or display server and works over SSH, but only in terminals
+//! that support it (most do, some after enabling it), and some limit how
+//! much can be copied at once. Inside tmux, the sequence is wrapped so
+//! tmux passes it on to the outer terminal.
+
+use std::io::{self, Write};
+
+use base64::Engine;
+use is_terminal::IsTerminal;
+
+/// Put `text` on the clipboard through the terminal on stderr, or on
+/// stdout if only that is a terminal.
+pub(crate) fn copy(text: &[u8]) -> io::Result<()> {
+    let sequence = osc52(text, std::env::var_os("TMUX").is_some());
+    if io::stderr().is_terminal() {
+        let mut stderr = io::stderr().lock();
+        stderr.write_all(sequence.as_bytes())?;
+        stderr.flush()
+    } else if io::stdout().is_terminal() {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(sequence.as_bytes())?;
+        stdout.flush()
+    } else {
+        Err(io::Error::other("--copy needs a terminal to copy through"))
+    }
+}
+
+/// The escape sequence that sets the clipboard to `text`, wrapped in a
+/// tmux passthrough sequence when `tmux`.
+fn osc52(text: &[u8], tmux: bool) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let sequence = format!("\x1b]52;c;{encoded}\x07");
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc52_sequence() {
+        assert_eq!(osc52(b"hi", false), "\x1b]52;c;aGk=\x07");
+        assert_eq!(osc52(b"hi", true), "\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\");
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! Provides a function to decode 8-bit text using a Windows codepage ID,
 //! shared by the `.doc` and `.xls` parsers. Falls back to cp1252 (Western
-//! European) for unknown or unsupported codepages.
+//! European) for unknown or unsupported codepages. [`parse`] reads the
+//! codepage names accepted by `--encoding`.
 
 use encoding_rs::Encoding;
 
@@ -22,6 +23,39 @@ pub(crate) fn decode_byte(byte: u8, codepage: u16) -> char {
     cow.chars().next().unwrap_or('\u{FFFD}')
 }
 
+/// Decode a byte string using the given Windows codepage.
+pub(crate) fn decode_bytes(bytes: &[u8], codepage: u16) -> String {
+    let (cow, _encoding_used, _had_errors) = codepage_to_encoding(codepage).decode(bytes);
+    cow.into_owned()
+}
+
+/// Codepages [`codepage_to_encoding`] knows, in the order [`parse`] prefers
+/// them when several share an encoding.
+const KNOWN: &[u16] = &[
+    1252, 1250, 1251, 1253, 1254, 1255, 1256, 1257, 1258, 874, 932, 936, 949, 950, 866, 437, 10000,
+    20866, 21866, 28592, 28595, 28597, 28598, 65001,
+];
+
+/// The codepage named by `name`: a number (`1251`), a Windows name
+/// (`cp1251`, `windows-1251`), or any encoding label the WHATWG Encoding
+/// Standard knows (`koi8-r`, `shift_jis`, `latin1`). `None` if it names
+/// no codepage we can decode.
+pub(crate) fn parse(name: &str) -> Option<u16> {
+    let lower = name.trim().to_ascii_lowercase();
+    let number = lower
+        .strip_prefix("cp")
+        .or_else(|| lower.strip_prefix("windows-"))
+        .unwrap_or(&lower);
+    if let Some(cp) = number.parse().ok().filter(|cp| KNOWN.contains(cp)) {
+        return Some(cp);
+    }
+    let encoding = Encoding::for_label(lower.as_bytes())?;
+    KNOWN
+        .iter()
+        .copied()
+        .find(|&cp| codepage_to_encoding(cp) == encoding)
+}
+
 /// Map a Windows codepage ID to an `encoding_rs` encoding.
 ///
 /// Covers the codepages most commonly encountered in Office documents.
@@ -29,7 +63,8 @@ pub(crate) fn decode_byte(byte: u8, codepage: u16) -> char {
 /// is the most common encoding in legacy Office files.
 fn codepage_to_encoding(codepage: u16) -> &'static Encoding {
     match codepage {
-        437 => encoding_rs::IBM866, // DOS US — closest available; not perfect
+        // 437 is DOS US — IBM866 is the closest available; not perfect
+        437 | 866 => encoding_rs::IBM866,
         874 => encoding_rs::WINDOWS_874,
         932 => encoding_rs::SHIFT_JIS,
         936 => encoding_rs::GBK,
@@ -98,6 +133,11 @@ mod tests {
         assert_eq!(decode_byte(0xC0, 1251), '\u{0410}');
     }
 
+    #[test]
+    fn decode_bytes_cp1252() {
+        assert_eq!(decode_bytes(b"caf\xe9", 1252), "café");
+    }
+
     #[test]
     fn lid_russian() {
         assert_eq!(lid_to_codepage(0x0419), 1251); // Russian (Russia)
@@ -122,4 +162,29 @@ mod tests {
     fn lid_polish() {
         assert_eq!(lid_to_codepage(0x0415), 1250); // Polish
     }
+
+    // ── parse ────────────────────────────────────────────────────
+
+    #[test]
+    fn parse_numbers_and_windows_names() {
+        assert_eq!(parse("1251"), Some(1251));
+        assert_eq!(parse("CP1251"), Some(1251));
+        assert_eq!(parse("windows-1250"), Some(1250));
+        assert_eq!(parse("cp866"), Some(866));
+    }
+
+    #[test]
+    fn parse_encoding_labels() {
+        assert_eq!(parse("koi8-r"), Some(20866));
+        assert_eq!(parse("Shift_JIS"), Some(932));
+        assert_eq!(parse("latin1"), Some(1252));
+        assert_eq!(parse("utf-8"), Some(65001));
+    }
+
+    #[test]
+    fn parse_rejects_unknown() {
+        assert_eq!(parse("1234"), None);
+        assert_eq!(parse("klingon"), None);
+        assert_eq!(parse(""), None);
+    }
 }
@@ -0,0 +1,204 @@
+//! User configuration file.
+//!
+//! Defaults for the extraction options can be set in a small TOML file,
+//! `$XDG_CONFIG_HOME/batdoc/config.toml` (or `~/.config/batdoc/config.toml`),
+//! overridden by the `BATDOC_CONFIG` environment variable. Keys at the top
+//! apply to every document; a `[docx]`, `[xlsx]`, ... section applies to
+//! that format only, since sensible defaults differ by document type:
+//!
+//! ```toml
+//! reflow = false
+//!
+//! [xlsx]
+//! table = true
+//! ```
+//!
+//! Command-line flags always win over the file. Only the subset of TOML
+//! needed for this is understood: sections, `key = value` pairs, and `#`
+//! comments.
+
+use std::path::{Path, PathBuf};
+
+use crate::options::{Options, Overrides};
+
+/// Format names accepted as section headers.
+const FORMATS: &[&str] = &["doc", "docx", "xls", "xlsx", "pptx", "pdf"];
+
+/// Parsed configuration file.
+#[derive(Debug, Default)]
+pub(crate) struct Config {
+    /// Top-level keys, applied to every format.
+    global: Overrides,
+    /// Per-format sections, keyed by format name.
+    formats: Vec<(String, Overrides)>,
+}
+
+impl Config {
+    /// Parse config file text. Errors carry the 1-based line number.
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let mut config = Self::default();
+        let mut section: Option<usize> = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let fail = |msg: String| format!("line {}: {msg}", i + 1);
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = name.trim();
+                if !FORMATS.contains(&name) {
+                    return Err(fail(format!("unknown section [{name}]")));
+                }
+                section = Some(
+                    config
+                        .formats
+                        .iter()
+                        .position(|(n, _)| n == name)
+                        .unwrap_or_else(|| {
+                            config
+                                .formats
+                                .push((name.to_string(), Overrides::default()));
+                            config.formats.len() - 1
+                        }),
+                );
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| fail(format!("expected key = value, found '{line}'")))?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            let target = match section {
+                Some(idx) => &mut config.formats[idx].1,
+                None => &mut config.global,
+            };
+            target.set(key.trim(), value).map_err(fail)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Read and parse the config file at `path`. A missing file is an empty
+    /// config.
+    pub(crate) fn load(path: &Path) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("{}: {e}", path.display())),
+        }
+    }
+
+    /// Effective options for a document of `format`: the defaults, then the
+    /// top-level keys, then the format's section, then the command line.
+    pub(crate) fn options(&self, format: &str, cli: &Overrides) -> Options {
+        let mut opts = Options::default();
+        self.global.apply(&mut opts);
+        if let Some((_, section)) = self.formats.iter().find(|(n, _)| n == format) {
+            section.apply(&mut opts);
+        }
+        cli.apply(&mut opts);
+        opts
+    }
+}
+
+/// Location of the config file, if one can be determined.
+pub(crate) fn default_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("BATDOC_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(base.join("batdoc").join("config.toml"))
+}
+
+/// Drop a trailing `# comment`, ignoring `#` inside double quotes.
+fn strip_comment(line: &str) -> &str {
+    let mut in_quotes = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '#' if !in_quotes => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── parse ────────────────────────────────────────────────────
+
+    #[test]
+    fn global_and_sections() {
+        let config = Config::parse(
+            "# defaults\n\
+             reflow = false\n\
+             \n\
+             [xlsx]\n\
+             table = true  # grid\n\
+             [docx]\n\
+             images = \"true\"\n",
+        )
+        .unwrap();
+
+        let xlsx = config.options("xlsx", &Overrides::default());
+        assert!(xlsx.table);
+        assert!(!xlsx.reflow);
+        assert!(!xlsx.images);
+
+        let docx = config.options("docx", &Overrides::default());
+        assert!(docx.images);
+        assert!(!docx.table);
+
+        let pdf = config.options("pdf", &Overrides::default());
+        assert!(!pdf.reflow);
+    }
+
+    #[test]
+    fn repeated_section_merges() {
+        let config = Config::parse("[xls]\ntable = true\n[xls]\nreflow = false\n").unwrap();
+        let opts = config.options("xls", &Overrides::default());
+        assert!(opts.table);
+        assert!(!opts.reflow);
+    }
+
+    #[test]
+    fn cli_wins() {
+        let config = Config::parse("[xlsx]\ntable = true\n").unwrap();
+        let cli = Overrides {
+            table: Some(false),
+            ..Overrides::default()
+        };
+        assert!(!config.options("xlsx", &cli).table);
+    }
+
+    #[test]
+    fn errors_have_line_numbers() {
+        let err = Config::parse("table = true\n[odt]\n").unwrap_err();
+        assert_eq!(err, "line 2: unknown section [odt]");
+
+        let err = Config::parse("\nbogus = 1\n").unwrap_err();
+        assert!(err.starts_with("line 2: unknown option 'bogus'"));
+
+        let err = Config::parse("table\n").unwrap_err();
+        assert!(err.starts_with("line 1: expected key = value"));
+    }
+
+    // ── load ─────────────────────────────────────────────────────
+
+    #[test]
+    fn missing_file_is_empty() {
+        let config = Config::load(Path::new("/nonexistent/batdoc/config.toml")).unwrap();
+        assert!(config.formats.is_empty());
+    }
+}
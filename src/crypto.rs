@@ -0,0 +1,1222 @@
+//! Decryption of password-protected Office documents (MS-OFFCRYPTO).
+//!
+//! Covers the schemes Office has used by default since Office 95:
+//!
+//! - XOR obfuscation (Office 95, and still offered by later versions),
+//!   which XORs the `.doc` and `.xls` streams with bytes derived from the
+//!   password.
+//! - RC4 with MD5 keys (Office 97/2000) or SHA-1 keys (`CryptoAPI`, Office
+//!   XP/2003), which encrypt the `.doc` and `.xls` streams in place.
+//! - Standard encryption (Office 2007): AES in ECB mode with SHA-1 keys.
+//! - Agile encryption (Office 2010 on): AES in CBC mode, usually with
+//!   SHA-512 keys.
+//!
+//! The last two wrap the whole `.docx`, `.xlsx`, or `.pptx` package in an
+//! OLE2 compound file, next to an `EncryptionInfo` stream describing the
+//! keys. The integrity check (an HMAC) of agile encryption is not
+//! verified.
+//!
+//! SHA-1 and RC4 are small enough to implement here; AES, CBC chaining,
+//! SHA-2, and MD5 come from the `RustCrypto` crates.
+
+use std::io::{Cursor, Read};
+
+use aes::cipher::{
+    consts::U16, generic_array::GenericArray, BlockCipher, BlockDecrypt, BlockDecryptMut,
+    InnerIvInit, KeyInit,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use cfb::CompoundFile;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use sha2::Digest;
+
+use crate::error::{BatdocError, Result};
+use crate::format::OLE2_MAGIC;
+use crate::xml_util::get_attr;
+
+/// Password Excel encrypts with when a workbook is protected against
+/// changes but not against reading; tried when no password is given.
+pub(crate) const DEFAULT_XLS_PASSWORD: &str = "VelvetSweatshop";
+
+/// Agile block keys of the password verifier and the package key (2.3.4.13).
+const BLOCK_VERIFIER_INPUT: [u8; 8] = [0xfe, 0xa7, 0xd2, 0x76, 0x3b, 0x4b, 0x9e, 0x79];
+const BLOCK_VERIFIER_VALUE: [u8; 8] = [0xd7, 0xaa, 0x0f, 0x6d, 0x30, 0x61, 0x34, 0x4e];
+const BLOCK_KEY_VALUE: [u8; 8] = [0x14, 0x6e, 0x0b, 0xe7, 0xab, 0xac, 0xd0, 0xd6];
+
+/// Bytes of an agile-encrypted package decrypted with one IV.
+const SEGMENT_LEN: usize = 4096;
+
+/// Hash iterations of standard encryption's key derivation.
+const STANDARD_SPIN_COUNT: u32 = 50_000;
+
+/// Upper bound on an agile `spinCount`, so that a hostile file cannot
+/// keep us hashing for hours. Office writes 100,000.
+const MAX_SPIN_COUNT: u32 = 10_000_000;
+
+/// `CryptoAPI` algorithm identifiers (`ALG_ID`) in an `EncryptionHeader`.
+const ALG_RC4: u32 = 0x6801;
+const ALG_AES_128: u32 = 0x660E;
+const ALG_AES_256: u32 = 0x6610;
+
+// ── OOXML packages ────────────────────────────────────────────────
+
+/// Whether `data` is an encrypted `.docx`, `.xlsx`, or `.pptx`: an OLE2
+/// compound file holding the encrypted package.
+pub(crate) fn is_encrypted_package(data: &[u8]) -> bool {
+    data.starts_with(&OLE2_MAGIC)
+        && CompoundFile::open(Cursor::new(data))
+            .is_ok_and(|cfb| cfb.exists("/EncryptionInfo") && cfb.exists("/EncryptedPackage"))
+}
+
+/// Decrypt an encrypted OOXML file, returning the ZIP package inside.
+///
+/// Fails with [`BatdocError::WrongPassword`] if `password` does not match
+/// the file's password verifier.
+pub(crate) fn decrypt_package(data: &[u8], password: &str) -> Result<Vec<u8>> {
+    let mut cfb = CompoundFile::open(Cursor::new(data))?;
+    let info = read_stream(&mut cfb, "/EncryptionInfo")?;
+    let package = read_stream(&mut cfb, "/EncryptedPackage")?;
+    let size = package
+        .get(..8)
+        .and_then(|b| usize::try_from(u64::from_le_bytes(b.try_into().ok()?)).ok())
+        .ok_or_else(|| malformed("EncryptedPackage header"))?;
+    let body = &package[8..];
+
+    let mut plain = match version(&info)? {
+        (4, 4) => decrypt_agile(&info, body, password)?,
+        (2..=4, 2) => decrypt_standard(&info, body, password)?,
+        (major, minor) => return Err(unsupported_version(major, minor)),
+    };
+    if size > plain.len() {
+        return Err(malformed("EncryptedPackage size"));
+    }
+    plain.truncate(size);
+    Ok(plain)
+}
+
+fn read_stream(cfb: &mut CompoundFile<Cursor<&[u8]>>, path: &str) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    cfb.open_stream(path)?.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Standard encryption: the package is AES-ECB encrypted with a key
+/// derived from the password by 50,000 rounds of SHA-1 (2.3.4.7).
+fn decrypt_standard(info: &[u8], body: &[u8], password: &str) -> Result<Vec<u8>> {
+    let info = StandardInfo::parse(info)?;
+    if !(ALG_AES_128..=ALG_AES_256).contains(&info.alg_id) {
+        return Err(BatdocError::Unsupported(format!(
+            "unsupported encryption algorithm 0x{:04X}",
+            info.alg_id
+        )));
+    }
+
+    let key = standard_key(info.salt, password);
+    let key = key
+        .get(..info.key_bits / 8)
+        .ok_or_else(|| malformed("EncryptionHeader key size"))?;
+    let aes = Aes::new(key)?;
+
+    let mut verifier = info.verifier.to_vec();
+    aes.decrypt_ecb(&mut verifier);
+    let mut verifier_hash = info.verifier_hash.to_vec();
+    aes.decrypt_ecb(&mut verifier_hash);
+    if verifier_hash.get(..20) != Some(&sha1(&verifier)[..]) {
+        return Err(BatdocError::WrongPassword);
+    }
+
+    let mut plain = body[..body.len() / 16 * 16].to_vec();
+    aes.decrypt_ecb(&mut plain);
+    Ok(plain)
+}
+
+/// The 40 bytes that standard encryption keys are a prefix of.
+fn standard_key(salt: &[u8], password: &str) -> Vec<u8> {
+    let hash = spin_hash(Hash::Sha1, salt, password, STANDARD_SPIN_COUNT);
+    let hash = sha1(&[hash.as_slice(), &0u32.to_le_bytes()].concat());
+    let derive = |fill: u8| {
+        let mut buf = [fill; 64];
+        for (b, h) in buf.iter_mut().zip(hash) {
+            *b ^= h;
+        }
+        sha1(&buf)
+    };
+    [derive(0x36), derive(0x5C)].concat()
+}
+
+/// Agile encryption: a random package key, itself encrypted with a key
+/// derived from the password, encrypts the package in 4096-byte segments
+/// with AES-CBC (2.3.4.10 to 2.3.4.15).
+fn decrypt_agile(info: &[u8], body: &[u8], password: &str) -> Result<Vec<u8>> {
+    let xml = info
+        .get(8..)
+        .and_then(|xml| std::str::from_utf8(xml).ok())
+        .ok_or_else(|| malformed("agile EncryptionInfo"))?;
+    let (key_data, encrypted_key) = parse_agile(xml)?;
+    let params = &encrypted_key.params;
+
+    let hash = spin_hash(params.hash, &params.salt, password, encrypted_key.spin);
+    let decrypt = |block_key: &[u8], data: &[u8]| -> Result<Vec<u8>> {
+        let key = fit(params.hash.digest(&[&hash, block_key]), params.key_bits / 8);
+        let mut data = data[..data.len() / 16 * 16].to_vec();
+        Aes::new(&key)?.decrypt_cbc(&params.salt, &mut data);
+        Ok(data)
+    };
+    let input = decrypt(&BLOCK_VERIFIER_INPUT, &encrypted_key.verifier_input)?;
+    let value = decrypt(&BLOCK_VERIFIER_VALUE, &encrypted_key.verifier_value)?;
+    let input = input.get(..params.salt.len()).unwrap_or(&input);
+    if value.get(..params.hash_len) != Some(&params.hash.digest(&[input])[..]) {
+        return Err(BatdocError::WrongPassword);
+    }
+    let key = decrypt(&BLOCK_KEY_VALUE, &encrypted_key.key_value)?;
+    let aes = Aes::new(
+        key.get(..key_data.key_bits / 8)
+            .ok_or_else(|| malformed("agile encryptedKeyValue"))?,
+    )?;
+
+    let mut plain = Vec::with_capacity(body.len());
+    for (i, segment) in (0u32..).zip(body.chunks(SEGMENT_LEN)) {
+        let iv = key_data.hash.digest(&[&key_data.salt, &i.to_le_bytes()]);
+        let mut segment = segment[..segment.len() / 16 * 16].to_vec();
+        aes.decrypt_cbc(&iv, &mut segment);
+        plain.extend_from_slice(&segment);
+    }
+    Ok(plain)
+}
+
+/// Cipher parameters shared by the agile `keyData` and `encryptedKey`
+/// elements.
+#[derive(Debug)]
+struct AgileParams {
+    salt: Vec<u8>,
+    key_bits: usize,
+    hash_len: usize,
+    hash: Hash,
+}
+
+/// The password key encryptor of agile encryption.
+#[derive(Debug)]
+struct EncryptedKey {
+    params: AgileParams,
+    spin: u32,
+    verifier_input: Vec<u8>,
+    verifier_value: Vec<u8>,
+    key_value: Vec<u8>,
+}
+
+/// The `keyData` and password `encryptedKey` of an agile `EncryptionInfo`.
+fn parse_agile(xml: &str) -> Result<(AgileParams, EncryptedKey)> {
+    let mut reader = Reader::from_str(xml);
+    let mut key_data = None;
+    let mut encrypted_key = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e) | Event::Empty(ref e)) => match e.local_name().as_ref() {
+                b"keyData" => key_data = Some(agile_params(e)?),
+                // Certificate key encryptors have an encryptedKey too, but
+                // no spinCount
+                b"encryptedKey" if encrypted_key.is_none() => {
+                    let Some(spin) = get_attr(e, b"spinCount") else {
+                        continue;
+                    };
+                    let spin = spin
+                        .parse()
+                        .ok()
+                        .filter(|&n| n <= MAX_SPIN_COUNT)
+                        .ok_or_else(|| malformed("agile spinCount"))?;
+                    encrypted_key = Some(EncryptedKey {
+                        params: agile_params(e)?,
+                        spin,
+                        verifier_input: base64_attr(e, b"encryptedVerifierHashInput")?,
+                        verifier_value: base64_attr(e, b"encryptedVerifierHashValue")?,
+                        key_value: base64_attr(e, b"encryptedKeyValue")?,
+                    });
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(_) => return Err(malformed("agile EncryptionInfo")),
+            _ => {}
+        }
+    }
+    match (key_data, encrypted_key) {
+        (Some(key_data), Some(encrypted_key)) => Ok((key_data, encrypted_key)),
+        (_, None) => Err(BatdocError::Unsupported(
+            "document is encrypted without a password (certificate encryption)".into(),
+        )),
+        (None, _) => Err(malformed("agile keyData")),
+    }
+}
+
+fn agile_params(e: &BytesStart) -> Result<AgileParams> {
+    let attr = |name: &[u8]| {
+        get_attr(e, name).ok_or_else(|| {
+            malformed(&format!(
+                "agile {} attribute",
+                String::from_utf8_lossy(name)
+            ))
+        })
+    };
+    let number = |name: &[u8]| {
+        attr(name)?
+            .parse::<usize>()
+            .map_err(|_| malformed("agile EncryptionInfo number"))
+    };
+    let cipher = attr(b"cipherAlgorithm")?;
+    let chaining = attr(b"cipherChaining")?;
+    if cipher != "AES" || chaining != "ChainingModeCBC" {
+        return Err(BatdocError::Unsupported(format!(
+            "unsupported encryption cipher {cipher} ({chaining})"
+        )));
+    }
+    let hash_name = attr(b"hashAlgorithm")?;
+    let hash = Hash::from_name(&hash_name).ok_or_else(|| {
+        BatdocError::Unsupported(format!("unsupported encryption hash {hash_name}"))
+    })?;
+    // Key and hash sizes come from the file: check them before they size
+    // any buffer
+    let key_bits = number(b"keyBits")?;
+    if !matches!(key_bits, 128 | 192 | 256) {
+        return Err(malformed("agile keyBits"));
+    }
+    let hash_len = number(b"hashSize")?;
+    if hash_len != hash.len() {
+        return Err(malformed("agile hashSize"));
+    }
+    Ok(AgileParams {
+        salt: base64_attr(e, b"saltValue")?,
+        key_bits,
+        hash_len,
+        hash,
+    })
+}
+
+fn base64_attr(e: &BytesStart, name: &[u8]) -> Result<Vec<u8>> {
+    get_attr(e, name)
+        .and_then(|value| BASE64.decode(value).ok())
+        .ok_or_else(|| malformed("agile EncryptionInfo value"))
+}
+
+// ── RC4 streams (.doc, .xls) ─────────────────────────────────────
+
+/// The RC4 encryption of a `.doc` or `.xls` stream, which is cut into
+/// blocks of `block_len` bytes (512 in Word, 1024 in Excel), each
+/// encrypted with its own key derived from the password and the block
+/// number.
+pub(crate) struct Rc4Stream {
+    key: Rc4Key,
+    block_len: usize,
+}
+
+enum Rc4Key {
+    /// Office binary document RC4 (2.3.6): the MD5 hash the block keys
+    /// are derived from.
+    Md5([u8; 16]),
+    /// RC4 `CryptoAPI` (2.3.5): the SHA-1 hash the block keys are derived
+    /// from, and their length in bytes.
+    CryptoApi { hash: [u8; 20], len: usize },
+}
+
+impl Rc4Key {
+    fn md5(salt: &[u8], password: &str) -> Self {
+        // Passwords are limited to 15 characters
+        let password: Vec<u8> = password
+            .encode_utf16()
+            .take(15)
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let hash = md5(&[&password]);
+        let mut buf = Vec::with_capacity(16 * 21);
+        for _ in 0..16 {
+            buf.extend_from_slice(&hash[..5]);
+            buf.extend_from_slice(salt);
+        }
+        Self::Md5(md5(&[&buf]))
+    }
+
+    fn crypto_api(salt: &[u8], password: &str, len: usize) -> Self {
+        Self::CryptoApi {
+            hash: sha1(&[salt, &utf16le(password)].concat()),
+            len,
+        }
+    }
+
+    fn block(&self, block: u32) -> Rc4 {
+        match self {
+            Self::Md5(hash) => Rc4::new(&md5(&[&hash[..5], &block.to_le_bytes()])),
+            Self::CryptoApi { hash, len } => {
+                let mut key =
+                    sha1(&[hash.as_slice(), &block.to_le_bytes()].concat())[..*len].to_vec();
+                // A 40-bit key is zero-padded to 128 bits
+                if *len == 5 {
+                    key.resize(16, 0);
+                }
+                Rc4::new(&key)
+            }
+        }
+    }
+}
+
+impl Rc4Stream {
+    /// Derive the keys for `password` from `info`, the encryption header
+    /// stored at the start of a `.doc` table stream or in an `.xls`
+    /// FILEPASS record, starting with its version.
+    pub(crate) fn new(info: &[u8], password: &str, block_len: usize) -> Result<Self> {
+        let (key, verifier, verifier_hash) = match version(info)? {
+            (1, 1) => {
+                let fields = info
+                    .get(4..52)
+                    .ok_or_else(|| malformed("RC4 encryption header"))?;
+                let (salt, rest) = fields.split_at(16);
+                (Rc4Key::md5(salt, password), &rest[..16], &rest[16..])
+            }
+            (2..=4, 2) => {
+                let info = StandardInfo::parse(info)?;
+                if info.alg_id != ALG_RC4 {
+                    return Err(BatdocError::Unsupported(format!(
+                        "unsupported encryption algorithm 0x{:04X}",
+                        info.alg_id
+                    )));
+                }
+                // A key size of 0 means 40 bits
+                let len = match info.key_bits {
+                    0 => 5,
+                    bits @ 40..=160 => bits / 8,
+                    _ => return Err(malformed("EncryptionHeader key size")),
+                };
+                let key = Rc4Key::crypto_api(info.salt, password, len);
+                let verifier_hash = info
+                    .verifier_hash
+                    .get(..20)
+                    .ok_or_else(|| malformed("EncryptionVerifier"))?;
+                (key, info.verifier, verifier_hash)
+            }
+            (major, minor) => return Err(unsupported_version(major, minor)),
+        };
+
+        // The verifier and its hash are encrypted as one run of block 0
+        let mut check = [verifier, verifier_hash].concat();
+        key.block(0).apply(&mut check);
+        let (verifier, verifier_hash) = check.split_at(16);
+        let expected = match key {
+            Rc4Key::Md5(_) => md5(&[verifier]).to_vec(),
+            Rc4Key::CryptoApi { .. } => sha1(verifier).to_vec(),
+        };
+        if verifier_hash != expected {
+            return Err(BatdocError::WrongPassword);
+        }
+        Ok(Self { key, block_len })
+    }
+
+    /// Decrypt `data`, which starts `offset` bytes into the stream.
+    pub(crate) fn decrypt(&self, data: &mut [u8], offset: usize) {
+        let mut pos = offset;
+        let mut rest = data;
+        while !rest.is_empty() {
+            let skip = pos % self.block_len;
+            let (chunk, tail) = rest.split_at_mut((self.block_len - skip).min(rest.len()));
+            let mut rc4 = self
+                .key
+                .block(u32::try_from(pos / self.block_len).unwrap_or(u32::MAX));
+            rc4.apply(&mut vec![0; skip]);
+            rc4.apply(chunk);
+            pos += chunk.len();
+            rest = tail;
+        }
+    }
+}
+
+// ── XOR obfuscation (.doc, .xls) ─────────────────────────────────
+
+/// Bytes that follow the password in the XOR array (2.3.7.2).
+const XOR_PAD: [u8; 15] = [
+    0xBB, 0xFF, 0xFF, 0xBA, 0xFF, 0xFF, 0xB9, 0x80, 0x00, 0xBE, 0x0F, 0x00, 0xBF, 0x0F, 0x00,
+];
+
+/// The application whose XOR obfuscation a stream uses: Word and Excel
+/// rotate the XOR array and the data differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum XorApp {
+    Word,
+    Excel,
+}
+
+/// The XOR obfuscation of a `.doc` or `.xls` stream (2.3.7): each byte
+/// is XORed with one of 16 bytes derived from the password, picked by
+/// its position in the stream.
+pub(crate) struct XorObfuscation {
+    array: [u8; 16],
+    app: XorApp,
+}
+
+impl XorObfuscation {
+    /// Derive the XOR array for `password`, which must match `verifier`,
+    /// the password verifier stored in the FIB or FILEPASS record.
+    pub(crate) fn new(verifier: u16, password: &str, app: XorApp) -> Result<Self> {
+        let password = xor_password(password);
+        if xor_verifier(&password) != verifier {
+            return Err(BatdocError::WrongPassword);
+        }
+        let key = xor_key(&password).to_le_bytes();
+        let rotate = match app {
+            XorApp::Word => 7,
+            XorApp::Excel => 2,
+        };
+        let array = std::array::from_fn(|i| {
+            let byte = password
+                .get(i)
+                .or_else(|| XOR_PAD.get(i - password.len()))
+                .copied()
+                .unwrap_or(0);
+            (byte ^ key[i % 2]).rotate_left(rotate)
+        });
+        Ok(Self { array, app })
+    }
+
+    /// Deobfuscate `data`, whose first byte is XORed with entry `offset`
+    /// (mod 16) of the array.
+    pub(crate) fn decrypt(&self, data: &mut [u8], offset: usize) {
+        for (i, byte) in data.iter_mut().enumerate() {
+            let key = self.array[(offset + i) % 16];
+            match self.app {
+                // Zero bytes, and bytes equal to their key, are left as
+                // they are so that no zero byte is written in their place
+                XorApp::Word => {
+                    if *byte != 0 && *byte != key {
+                        *byte ^= key;
+                    }
+                }
+                XorApp::Excel => *byte = (*byte ^ key).rotate_left(3),
+            }
+        }
+    }
+
+    /// Obfuscate `data` as [`decrypt`](Self::decrypt) undoes it, for
+    /// building obfuscated test streams.
+    #[cfg(test)]
+    pub(crate) fn encrypt(&self, data: &mut [u8], offset: usize) {
+        match self.app {
+            XorApp::Word => self.decrypt(data, offset),
+            XorApp::Excel => {
+                for (i, byte) in data.iter_mut().enumerate() {
+                    *byte = byte.rotate_left(5) ^ self.array[(offset + i) % 16];
+                }
+            }
+        }
+    }
+}
+
+/// The bytes of `password` that XOR obfuscation uses: at most 15, each
+/// the low byte of a UTF-16 code unit, or its high byte if that is zero.
+fn xor_password(password: &str) -> Vec<u8> {
+    password
+        .encode_utf16()
+        .take(15)
+        .map(|unit| {
+            let [low, high] = unit.to_le_bytes();
+            if low == 0 {
+                high
+            } else {
+                low
+            }
+        })
+        .collect()
+}
+
+/// The password verifier of XOR obfuscation (2.3.7.1).
+fn xor_verifier(password: &[u8]) -> u16 {
+    let len = u8::try_from(password.len()).unwrap_or(u8::MAX);
+    let verifier = password
+        .iter()
+        .rev()
+        .chain([&len])
+        .fold(0u16, |v, &byte| {
+            ((v >> 14) & 1 | (v << 1) & 0x7FFF) ^ u16::from(byte)
+        });
+    verifier ^ 0xCE4B
+}
+
+/// The XOR key (2.3.7.2). The specification's `InitialCode` and
+/// `XorMatrix` tables are successive doublings in CRC-16-CCITT
+/// arithmetic, so they are computed here rather than copied.
+fn xor_key(password: &[u8]) -> u16 {
+    let double = |v: u16| (v << 1) ^ if v & 0x8000 == 0 { 0 } else { 0x1021 };
+    let (mut key, mut initial, mut matrix) = (0, 0xFFFF, 0x1021);
+    for &byte in password.iter().rev() {
+        // Seven bits of each character count, but eight steps are taken
+        for bit in 0..8 {
+            if bit < 7 && byte & (1 << bit) != 0 {
+                key ^= matrix;
+            }
+            matrix = double(matrix);
+            initial = double(initial);
+        }
+    }
+    key ^ initial
+}
+
+/// The key and password verifier of XOR obfuscation for `password`, as
+/// an `.xls` FILEPASS record stores them, for building obfuscated test
+/// streams.
+#[cfg(test)]
+pub(crate) fn xor_header(password: &str) -> [u8; 4] {
+    let password = xor_password(password);
+    let [k0, k1] = xor_key(&password).to_le_bytes();
+    let [v0, v1] = xor_verifier(&password).to_le_bytes();
+    [k0, k1, v0, v1]
+}
+
+// ── Shared structures ─────────────────────────────────────────────
+
+/// The `EncryptionHeader` and `EncryptionVerifier` of standard encryption
+/// and RC4 `CryptoAPI` (2.3.2, 2.3.3).
+struct StandardInfo<'a> {
+    alg_id: u32,
+    key_bits: usize,
+    salt: &'a [u8],
+    verifier: &'a [u8],
+    verifier_hash: &'a [u8],
+}
+
+impl<'a> StandardInfo<'a> {
+    /// Parse `info`, starting with the version and flags.
+    fn parse(info: &'a [u8]) -> Result<Self> {
+        let field = |data: &[u8], at: usize| {
+            data.get(at..at + 4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize) // u32 → usize: lossless on 32+ bit
+        };
+        let parse = || {
+            let header_len = field(info, 8)?;
+            let header = info.get(12..12_usize.checked_add(header_len)?)?;
+            let verifier = &info[12 + header_len..];
+            let salt_len = field(verifier, 0)?;
+            let salt = verifier.get(4..4_usize.checked_add(salt_len)?)?;
+            let rest = &verifier[4 + salt_len..];
+            Some(StandardInfo {
+                alg_id: u32::try_from(field(header, 8)?).ok()?,
+                key_bits: field(header, 16)?,
+                salt,
+                verifier: rest.get(..16)?,
+                verifier_hash: rest.get(20..)?,
+            })
+        };
+        parse().ok_or_else(|| malformed("EncryptionHeader"))
+    }
+}
+
+/// The `(major, minor)` version at the start of encryption information.
+fn version(info: &[u8]) -> Result<(u16, u16)> {
+    info.get(..4)
+        .map(|b| {
+            (
+                u16::from_le_bytes([b[0], b[1]]),
+                u16::from_le_bytes([b[2], b[3]]),
+            )
+        })
+        .ok_or_else(|| malformed("encryption version"))
+}
+
+fn unsupported_version(major: u16, minor: u16) -> BatdocError {
+    BatdocError::Unsupported(format!("unsupported encryption (version {major}.{minor})"))
+}
+
+fn malformed(what: &str) -> BatdocError {
+    BatdocError::Document(format!("malformed {what}"))
+}
+
+fn utf16le(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// `bytes` truncated, or padded with 0x36, to `len` bytes (2.3.4.11).
+fn fit(mut bytes: Vec<u8>, len: usize) -> Vec<u8> {
+    bytes.resize(len, 0x36);
+    bytes
+}
+
+/// The hash of the salt and password, rehashed `spin` times with the
+/// iteration number prepended.
+fn spin_hash(hash: Hash, salt: &[u8], password: &str, spin: u32) -> Vec<u8> {
+    let mut h = hash.digest(&[salt, &utf16le(password)]);
+    for i in 0..spin {
+        h = hash.digest(&[&i.to_le_bytes(), &h]);
+    }
+    h
+}
+
+// ── Primitives ───────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Hash {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl Hash {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "SHA1" | "SHA-1" => Some(Self::Sha1),
+            "SHA256" | "SHA-256" => Some(Self::Sha256),
+            "SHA384" | "SHA-384" => Some(Self::Sha384),
+            "SHA512" | "SHA-512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    /// The length of a digest, in bytes.
+    fn len(self) -> usize {
+        match self {
+            Self::Sha1 => 20,
+            Self::Sha256 => 32,
+            Self::Sha384 => 48,
+            Self::Sha512 => 64,
+        }
+    }
+
+    /// The hash of `parts`, concatenated.
+    fn digest(self, parts: &[&[u8]]) -> Vec<u8> {
+        fn digest<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+            let mut d = D::new();
+            for part in parts {
+                d.update(part);
+            }
+            d.finalize().to_vec()
+        }
+        match self {
+            Self::Sha1 => sha1(&parts.concat()).to_vec(),
+            Self::Sha256 => digest::<sha2::Sha256>(parts),
+            Self::Sha384 => digest::<sha2::Sha384>(parts),
+            Self::Sha512 => digest::<sha2::Sha512>(parts),
+        }
+    }
+}
+
+fn md5(parts: &[&[u8]]) -> [u8; 16] {
+    let mut d = md5::Md5::new();
+    for part in parts {
+        d.update(part);
+    }
+    d.finalize().into()
+}
+
+/// SHA-1 (FIPS 180-4), small enough to keep here rather than add the
+/// `sha1` crate as a dependency for two call sites.
+#[allow(clippy::many_single_char_names)] // the standard's names
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [
+        0x6745_2301,
+        0xEFCD_AB89,
+        0x98BA_DCFE,
+        0x1032_5476,
+        0xC3D2_E1F0,
+    ];
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&(u64::try_from(data.len()).unwrap_or(u64::MAX) * 8).to_be_bytes());
+
+    for chunk in msg.chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (w, word) in w.iter_mut().zip(chunk.chunks_exact(4)) {
+            *w = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+                _ => (b ^ c ^ d, 0xCA62_C1D6),
+            };
+            let t = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = t;
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+
+    let mut out = [0; 20];
+    for (out, h) in out.chunks_exact_mut(4).zip(h) {
+        out.copy_from_slice(&h.to_be_bytes());
+    }
+    out
+}
+
+/// The RC4 stream cipher; encrypting and decrypting are the same.
+struct Rc4 {
+    s: [u8; 256],
+    i: u8,
+    j: u8,
+}
+
+impl Rc4 {
+    fn new(key: &[u8]) -> Self {
+        #[allow(clippy::cast_possible_truncation)] // i < 256
+        let mut s: [u8; 256] = std::array::from_fn(|i| i as u8);
+        let mut j = 0u8;
+        for i in 0..256 {
+            j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
+            s.swap(i, usize::from(j));
+        }
+        Self { s, i: 0, j: 0 }
+    }
+
+    fn apply(&mut self, data: &mut [u8]) {
+        for byte in data {
+            self.i = self.i.wrapping_add(1);
+            self.j = self.j.wrapping_add(self.s[usize::from(self.i)]);
+            self.s.swap(usize::from(self.i), usize::from(self.j));
+            let k = self.s[usize::from(self.i)].wrapping_add(self.s[usize::from(self.j)]);
+            *byte ^= self.s[usize::from(k)];
+        }
+    }
+}
+
+/// AES with any of its three key sizes.
+enum Aes {
+    Aes128(aes::Aes128),
+    Aes192(aes::Aes192),
+    Aes256(aes::Aes256),
+}
+
+impl Aes {
+    fn new(key: &[u8]) -> Result<Self> {
+        match key.len() {
+            16 => Ok(Self::Aes128(aes::Aes128::new(GenericArray::from_slice(
+                key,
+            )))),
+            24 => Ok(Self::Aes192(aes::Aes192::new(GenericArray::from_slice(
+                key,
+            )))),
+            32 => Ok(Self::Aes256(aes::Aes256::new(GenericArray::from_slice(
+                key,
+            )))),
+            n => Err(BatdocError::Unsupported(format!(
+                "unsupported AES key size ({} bits)",
+                n * 8
+            ))),
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let block = GenericArray::from_mut_slice(block);
+        match self {
+            Self::Aes128(c) => c.decrypt_block(block),
+            Self::Aes192(c) => c.decrypt_block(block),
+            Self::Aes256(c) => c.decrypt_block(block),
+        }
+    }
+
+    /// Decrypt whole 16-byte blocks, each on its own.
+    fn decrypt_ecb(&self, data: &mut [u8]) {
+        for block in data.chunks_exact_mut(16) {
+            self.decrypt_block(block);
+        }
+    }
+
+    /// Decrypt whole 16-byte blocks chained from `iv`, which is fitted to
+    /// the block size as agile encryption requires.
+    fn decrypt_cbc(&self, iv: &[u8], data: &mut [u8]) {
+        fn decrypt<C>(cipher: &C, iv: &[u8], data: &mut [u8])
+        where
+            C: BlockCipher<BlockSize = U16> + BlockDecryptMut + Clone,
+        {
+            let mut cbc =
+                cbc::Decryptor::inner_iv_init(cipher.clone(), GenericArray::from_slice(iv));
+            for block in data.chunks_exact_mut(16) {
+                cbc.decrypt_block_mut(GenericArray::from_mut_slice(block));
+            }
+        }
+        let iv = fit(iv.to_vec(), 16);
+        match self {
+            Self::Aes128(c) => decrypt(c, &iv, data),
+            Self::Aes192(c) => decrypt(c, &iv, data),
+            Self::Aes256(c) => decrypt(c, &iv, data),
+        }
+    }
+}
+
+/// An Office binary document RC4 header (version 1.1) for `password`,
+/// for building encrypted test streams.
+#[cfg(test)]
+pub(crate) fn rc4_header(password: &str) -> Vec<u8> {
+    let salt: Vec<u8> = (1..=16).collect();
+    let verifier: Vec<u8> = (100..116).collect();
+    let mut check = [verifier.as_slice(), &md5(&[&verifier])].concat();
+    Rc4Key::md5(&salt, password).block(0).apply(&mut check);
+    [&[1, 0, 1, 0], salt.as_slice(), &check].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::cipher::BlockEncrypt;
+    use std::fmt::Write as _;
+    use std::io::Write;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().fold(String::new(), |mut out, b| {
+            let _ = write!(out, "{b:02x}");
+            out
+        })
+    }
+
+    fn encrypt_ecb(key: &[u8], data: &mut [u8]) {
+        let aes = aes::Aes128::new(GenericArray::from_slice(key));
+        for block in data.chunks_exact_mut(16) {
+            aes.encrypt_block(GenericArray::from_mut_slice(block));
+        }
+    }
+
+    fn encrypt_cbc(key: &[u8], iv: &[u8], data: &[u8]) -> Vec<u8> {
+        use aes::cipher::{BlockEncryptMut, KeyIvInit};
+        let mut cbc = cbc::Encryptor::<aes::Aes256>::new(
+            GenericArray::from_slice(key),
+            GenericArray::from_slice(&iv[..16]),
+        );
+        let mut out = data.to_vec();
+        out.resize(data.len().div_ceil(16) * 16, 0);
+        for block in out.chunks_exact_mut(16) {
+            cbc.encrypt_block_mut(GenericArray::from_mut_slice(block));
+        }
+        out
+    }
+
+    fn unhex(text: &str) -> Vec<u8> {
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// An OLE2 file with the given `EncryptionInfo` and package streams.
+    fn container(info: &[u8], package: &[u8], size: usize) -> Vec<u8> {
+        let mut cfb = CompoundFile::create(Cursor::new(Vec::new())).unwrap();
+        cfb.create_stream("/EncryptionInfo")
+            .unwrap()
+            .write_all(info)
+            .unwrap();
+        let mut stream = cfb.create_stream("/EncryptedPackage").unwrap();
+        stream.write_all(&(size as u64).to_le_bytes()).unwrap();
+        stream.write_all(package).unwrap();
+        drop(stream);
+        cfb.into_inner().into_inner()
+    }
+
+    fn package() -> Vec<u8> {
+        (0..5000u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    // ── primitives ───────────────────────────────────────────────
+
+    #[test]
+    fn sha1_known_answers() {
+        assert_eq!(hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            hex(&sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex(&sha1(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+        assert_eq!(
+            hex(&sha1(&[b'a'; 1_000_000])),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+        );
+        // 56 bytes: too many for the length to fit in the same block
+        assert_eq!(
+            hex(&sha1(&[b'a'; 56])),
+            "c2db330f6083854c99d4b5bfb6e8f29f201be699"
+        );
+    }
+
+    #[test]
+    fn rc4_known_answers() {
+        for (key, plain, cipher) in [
+            (&b"Key"[..], &b"Plaintext"[..], "bbf316e8d940af0ad3"),
+            (b"Wiki", b"pedia", "1021bf0420"),
+            (b"Secret", b"Attack at dawn", "45a01f645fc35b383552544b9bf5"),
+        ] {
+            let mut data = plain.to_vec();
+            Rc4::new(key).apply(&mut data);
+            assert_eq!(hex(&data), cipher);
+        }
+        // RFC 6229, 40-bit key, keystream bytes 0 and 240
+        let mut stream = [0u8; 256];
+        Rc4::new(&[1, 2, 3, 4, 5]).apply(&mut stream);
+        assert_eq!(hex(&stream[..16]), "b2396305f03dc027ccc3524a0a1118a8");
+        assert_eq!(hex(&stream[240..]), "28cb1132c96ce286421dcaadb8b69eae");
+    }
+
+    #[test]
+    fn aes_cbc_known_answer() {
+        // NIST SP 800-38A, F.2.2 (CBC-AES128.Decrypt)
+        let aes = Aes::new(&unhex("2b7e151628aed2a6abf7158809cf4f3c")).unwrap();
+        let mut data = unhex("7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2");
+        aes.decrypt_cbc(&unhex("000102030405060708090a0b0c0d0e0f"), &mut data);
+        assert_eq!(
+            hex(&data),
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51"
+        );
+    }
+
+    // ── Rc4Stream ────────────────────────────────────────────────
+
+    #[test]
+    fn rc4_stream_checks_password() {
+        let header = rc4_header("secret");
+        assert!(Rc4Stream::new(&header, "secret", 512).is_ok());
+        assert!(matches!(
+            Rc4Stream::new(&header, "Secret", 512),
+            Err(BatdocError::WrongPassword)
+        ));
+        assert!(matches!(
+            Rc4Stream::new(&[3, 0, 3, 0], "secret", 512),
+            Err(BatdocError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn rc4_stream_decrypts_at_any_offset() {
+        let rc4 = Rc4Stream::new(&rc4_header("secret"), "secret", 512).unwrap();
+        let plain = package();
+        let mut data = plain.clone();
+        rc4.decrypt(&mut data, 0);
+        assert_ne!(data, plain);
+        // Pieces straddling block boundaries decrypt on their own
+        let (head, tail) = data.split_at_mut(700);
+        rc4.decrypt(head, 0);
+        rc4.decrypt(tail, 700);
+        assert_eq!(data, plain);
+    }
+
+    #[test]
+    fn rc4_crypto_api_header() {
+        let salt = [7u8; 16];
+        let verifier = [9u8; 16];
+        let key = Rc4Key::crypto_api(&salt, "pw", 16);
+        let mut check = [verifier.as_slice(), &sha1(&verifier)].concat();
+        key.block(0).apply(&mut check);
+        let mut header = vec![0u8; 32];
+        header[8..12].copy_from_slice(&ALG_RC4.to_le_bytes());
+        header[16..20].copy_from_slice(&128u32.to_le_bytes());
+        let info = [
+            &[4, 0, 2, 0, 0x04, 0, 0, 0][..],
+            &32u32.to_le_bytes(),
+            &header,
+            &16u32.to_le_bytes(),
+            &salt,
+            &check[..16],
+            &20u32.to_le_bytes(),
+            &check[16..],
+        ]
+        .concat();
+        assert!(Rc4Stream::new(&info, "pw", 1024).is_ok());
+        assert!(matches!(
+            Rc4Stream::new(&info, "wrong", 1024),
+            Err(BatdocError::WrongPassword)
+        ));
+    }
+
+    // ── XorObfuscation ───────────────────────────────────────────
+
+    #[test]
+    fn xor_key_matches_the_spec_tables() {
+        // All-zero passwords give InitialCode (2.3.7.2) for their length
+        let initial_code = [
+            0xE1F0, 0x1D0F, 0xCC9C, 0x84C0, 0x110C, 0x0E10, 0xF1CE, 0x313E, 0x1872, 0xE139,
+            0xD40F, 0x84F9, 0x280C, 0xA96A, 0x4EC3,
+        ];
+        for (len, &code) in initial_code.iter().enumerate() {
+            assert_eq!(xor_key(&vec![0; len + 1]), code);
+        }
+        // Single bits pick out entries of XorMatrix
+        assert_eq!(xor_key(&[0x01]), 0xE1F0 ^ 0x1021);
+        assert_eq!(xor_key(&[0x40]), 0xE1F0 ^ 0x48C4);
+        let mut first = [0; 15];
+        first[0] = 0x01;
+        assert_eq!(xor_key(&first), 0x4EC3 ^ 0xAEFC);
+    }
+
+    #[test]
+    fn xor_verifier_known_answer() {
+        // The familiar Excel protection hash of "password"
+        assert_eq!(xor_verifier(b"password"), 0x83AF);
+        assert_eq!(xor_password("pässwörd\u{100}"), b"p\xe4ssw\xf6rd\x01");
+    }
+
+    #[test]
+    fn xor_checks_password() {
+        let verifier = u16::from_le_bytes([xor_header("secret")[2], xor_header("secret")[3]]);
+        assert!(XorObfuscation::new(verifier, "secret", XorApp::Word).is_ok());
+        assert!(matches!(
+            XorObfuscation::new(verifier, "Secret", XorApp::Excel),
+            Err(BatdocError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn xor_decrypts_at_any_offset() {
+        let verifier = u16::from_le_bytes([xor_header("pw")[2], xor_header("pw")[3]]);
+        let plain: Vec<u8> = (0..=255).chain([0; 20]).collect();
+
+        // Word's obfuscation is its own inverse
+        let word = XorObfuscation::new(verifier, "pw", XorApp::Word).unwrap();
+        let mut data = plain.clone();
+        word.decrypt(&mut data, 3);
+        assert_ne!(data, plain);
+        assert!(data[256..].iter().all(|&b| b == 0));
+        let (head, tail) = data.split_at_mut(100);
+        word.decrypt(head, 3);
+        word.decrypt(tail, 103);
+        assert_eq!(data, plain);
+
+        // Excel rotates each byte left by 5 before XORing it (2.3.7.3)
+        let excel = XorObfuscation::new(verifier, "pw", XorApp::Excel).unwrap();
+        let mut data = plain.clone();
+        excel.encrypt(&mut data, 9);
+        assert_eq!(data[0], excel.array[9]);
+        assert_eq!(data[1], 1u8.rotate_left(5) ^ excel.array[10]);
+        excel.decrypt(&mut data, 9);
+        assert_eq!(data, plain);
+    }
+
+    // ── decrypt_package ──────────────────────────────────────────
+
+    #[test]
+    fn standard_encryption() {
+        let salt = [3u8; 16];
+        let key = standard_key(&salt, "pw")[..16].to_vec();
+        let mut verifier = [5u8; 16].to_vec();
+        let mut verifier_hash = sha1(&verifier).to_vec();
+        verifier_hash.resize(32, 0);
+        encrypt_ecb(&key, &mut verifier);
+        encrypt_ecb(&key, &mut verifier_hash);
+        let mut header = vec![0u8; 34];
+        header[8..12].copy_from_slice(&ALG_AES_128.to_le_bytes());
+        header[16..20].copy_from_slice(&128u32.to_le_bytes());
+        let info = [
+            &[3, 0, 2, 0, 0x24, 0, 0, 0][..],
+            &34u32.to_le_bytes(),
+            &header,
+            &16u32.to_le_bytes(),
+            &salt,
+            &verifier,
+            &20u32.to_le_bytes(),
+            &verifier_hash,
+        ]
+        .concat();
+        let plain = package();
+        let mut body = plain.clone();
+        body.resize(plain.len().div_ceil(16) * 16, 0);
+        encrypt_ecb(&key, &mut body);
+
+        let file = container(&info, &body, plain.len());
+        assert!(is_encrypted_package(&file));
+        assert_eq!(decrypt_package(&file, "pw").unwrap(), plain);
+        assert!(matches!(
+            decrypt_package(&file, "nope"),
+            Err(BatdocError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn agile_encryption() {
+        let (password_salt, data_salt) = ([1u8; 16], [2u8; 16]);
+        let secret = [4u8; 32];
+        let spin = 1000;
+        let hash = spin_hash(Hash::Sha512, &password_salt, "pässword", spin);
+        let encrypt = |block_key: &[u8], data: &[u8]| {
+            let key = fit(Hash::Sha512.digest(&[&hash, block_key]), 32);
+            BASE64.encode(encrypt_cbc(&key, &password_salt, data))
+        };
+        let verifier = [6u8; 16];
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<encryption xmlns="http://schemas.microsoft.com/office/2006/encryption" xmlns:p="http://schemas.microsoft.com/office/2006/keyEncryptor/password">
+<keyData saltSize="16" blockSize="16" keyBits="256" hashSize="64" cipherAlgorithm="AES" cipherChaining="ChainingModeCBC" hashAlgorithm="SHA512" saltValue="{}"/>
+<keyEncryptors><keyEncryptor uri="http://schemas.microsoft.com/office/2006/keyEncryptor/password">
+<p:encryptedKey spinCount="{spin}" saltSize="16" blockSize="16" keyBits="256" hashSize="64" cipherAlgorithm="AES" cipherChaining="ChainingModeCBC" hashAlgorithm="SHA512" saltValue="{}" encryptedVerifierHashInput="{}" encryptedVerifierHashValue="{}" encryptedKeyValue="{}"/>
+</keyEncryptor></keyEncryptors></encryption>"#,
+            BASE64.encode(data_salt),
+            BASE64.encode(password_salt),
+            encrypt(&BLOCK_VERIFIER_INPUT, &verifier),
+            encrypt(&BLOCK_VERIFIER_VALUE, &Hash::Sha512.digest(&[&verifier])),
+            encrypt(&BLOCK_KEY_VALUE, &secret),
+        );
+        let info = [&[4, 0, 4, 0, 0x40, 0, 0, 0][..], xml.as_bytes()].concat();
+        let plain = package();
+        let body: Vec<u8> = (0u32..)
+            .zip(plain.chunks(SEGMENT_LEN))
+            .flat_map(|(i, segment)| {
+                let iv = Hash::Sha512.digest(&[&data_salt, &i.to_le_bytes()]);
+                encrypt_cbc(&secret, &iv, segment)
+            })
+            .collect();
+
+        let file = container(&info, &body, plain.len());
+        assert_eq!(decrypt_package(&file, "pässword").unwrap(), plain);
+        assert!(matches!(
+            decrypt_package(&file, "password"),
+            Err(BatdocError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn agile_sizes_are_checked() {
+        let info = |key_bits: &str, hash_size: &str| {
+            let params = format!(
+                r#"saltSize="16" blockSize="16" keyBits="{key_bits}" hashSize="{hash_size}" cipherAlgorithm="AES" cipherChaining="ChainingModeCBC" hashAlgorithm="SHA512" saltValue="AQID""#
+            );
+            let xml = format!(
+                r#"<encryption><keyData {params}/><keyEncryptors><keyEncryptor><p:encryptedKey spinCount="1" {params} encryptedVerifierHashInput="AQID" encryptedVerifierHashValue="AQID" encryptedKeyValue="AQID"/></keyEncryptor></keyEncryptors></encryption>"#
+            );
+            container(
+                &[&[4, 0, 4, 0, 0x40, 0, 0, 0][..], xml.as_bytes()].concat(),
+                &[],
+                0,
+            )
+        };
+        for (key_bits, hash_size) in [
+            ("18446744073709551615", "64"),
+            ("4096", "64"),
+            ("256", "1000000000"),
+        ] {
+            let err = decrypt_package(&info(key_bits, hash_size), "pw").unwrap_err();
+            assert!(matches!(err, BatdocError::Document(_)), "{err}");
+        }
+        assert!(matches!(
+            decrypt_package(&info("256", "64"), "pw"),
+            Err(BatdocError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn unencrypted_ole_is_not_a_package() {
+        assert!(!is_encrypted_package(b"PK\x03\x04"));
+        let mut cfb = CompoundFile::create(Cursor::new(Vec::new())).unwrap();
+        cfb.create_stream("/WordDocument").unwrap();
+        assert!(!is_encrypted_package(&cfb.into_inner().into_inner()));
+    }
+}
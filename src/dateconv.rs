@@ -3,6 +3,8 @@
 //! Excel stores dates as floating-point serial numbers: the integer part
 //! counts days since 1899-12-30 (with the Lotus 1-2-3 bug that treats
 //! 1900 as a leap year), and the fractional part is the time of day.
+//! Workbooks in the 1904 date system, from early Mac Excel, count days
+//! since 1904-01-01 instead.
 //!
 //! Whether a numeric cell is a date depends on its number format (`numFmtId`).
 //! This module provides both the format detection logic and the serial→ISO
@@ -88,15 +90,31 @@ pub(crate) fn resolve_date_styles(fmt_ids: &[u16], custom_formats: &[(u16, Strin
         .collect()
 }
 
+/// The day a workbook counts its date serial numbers from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Epoch {
+    /// Serial 1 is 1900-01-01, as in Excel for Windows.
+    #[default]
+    Y1900,
+    /// Serial 0 is 1904-01-01 (`date1904`), as in early Mac Excel.
+    Y1904,
+}
+
 /// Convert an Excel serial number to an ISO 8601 string.
 ///
 /// Returns `YYYY-MM-DD` for whole numbers, `YYYY-MM-DD HH:MM:SS` if
-/// there is a fractional (time) component.
+/// there is a fractional (time) component. A serial in the 1904 date
+/// system is 1462 days behind the same date in the 1900 system.
 ///
 /// Handles the Lotus 1-2-3 bug: serial 60 is treated as 1900-02-29
 /// (which doesn't exist), and serials ≤ 0 or absurdly large values
 /// are returned as-is.
-pub(crate) fn serial_to_iso(serial: f64) -> String {
+pub(crate) fn serial_to_iso(serial: f64, epoch: Epoch) -> String {
+    // Times of day (below 1) read the same in both systems
+    let serial = match epoch {
+        Epoch::Y1904 if serial >= 1.0 => serial + 1462.0,
+        _ => serial,
+    };
     if serial < 0.0 || serial.is_nan() || serial.is_infinite() {
         return format_fallback(serial);
     }
@@ -317,94 +335,102 @@ mod tests {
     #[test]
     fn epoch_day_one() {
         // Serial 1 = 1900-01-01
-        assert_eq!(serial_to_iso(1.0), "1900-01-01");
+        assert_eq!(serial_to_iso(1.0, Epoch::Y1900), "1900-01-01");
     }
 
     #[test]
     fn lotus_bug_day_60() {
         // Serial 60 = the fake 1900-02-29
-        assert_eq!(serial_to_iso(60.0), "1900-02-29");
+        assert_eq!(serial_to_iso(60.0, Epoch::Y1900), "1900-02-29");
     }
 
     #[test]
     fn day_after_lotus_bug() {
         // Serial 61 = 1900-03-01
-        assert_eq!(serial_to_iso(61.0), "1900-03-01");
+        assert_eq!(serial_to_iso(61.0, Epoch::Y1900), "1900-03-01");
+    }
+
+    #[test]
+    fn date1904_system() {
+        // 2024-01-01 is serial 43830 in the 1904 system
+        assert_eq!(serial_to_iso(43830.0, Epoch::Y1904), "2024-01-01");
+        assert_eq!(serial_to_iso(1.5, Epoch::Y1904), "1904-01-02 12:00:00");
+        assert_eq!(serial_to_iso(0.25, Epoch::Y1904), "06:00:00");
     }
 
     #[test]
     fn known_date_2024_01_01() {
         // 2024-01-01 = serial 45292
-        assert_eq!(serial_to_iso(45292.0), "2024-01-01");
+        assert_eq!(serial_to_iso(45292.0, Epoch::Y1900), "2024-01-01");
     }
 
     #[test]
     fn known_date_2000_01_01() {
         // 2000-01-01 = serial 36526
-        assert_eq!(serial_to_iso(36526.0), "2000-01-01");
+        assert_eq!(serial_to_iso(36526.0, Epoch::Y1900), "2000-01-01");
     }
 
     #[test]
     fn known_date_1999_12_31() {
         // 1999-12-31 = serial 36525
-        assert_eq!(serial_to_iso(36525.0), "1999-12-31");
+        assert_eq!(serial_to_iso(36525.0, Epoch::Y1900), "1999-12-31");
     }
 
     #[test]
     fn date_with_time() {
         // 2024-01-01 12:00:00 = 45292.5
-        assert_eq!(serial_to_iso(45292.5), "2024-01-01 12:00:00");
+        assert_eq!(serial_to_iso(45292.5, Epoch::Y1900), "2024-01-01 12:00:00");
     }
 
     #[test]
     fn date_with_time_6am() {
         // 2024-01-01 06:00:00 = 45292.25
-        assert_eq!(serial_to_iso(45292.25), "2024-01-01 06:00:00");
+        assert_eq!(serial_to_iso(45292.25, Epoch::Y1900), "2024-01-01 06:00:00");
     }
 
     #[test]
     fn pure_time_value() {
         // 0.5 = 12:00:00
-        assert_eq!(serial_to_iso(0.5), "12:00:00");
+        assert_eq!(serial_to_iso(0.5, Epoch::Y1900), "12:00:00");
     }
 
     #[test]
     fn pure_time_quarter_day() {
         // 0.25 = 06:00:00
-        assert_eq!(serial_to_iso(0.25), "06:00:00");
+        assert_eq!(serial_to_iso(0.25, Epoch::Y1900), "06:00:00");
     }
 
     #[test]
     fn negative_serial_fallback() {
-        assert_eq!(serial_to_iso(-1.0), "-1");
+        assert_eq!(serial_to_iso(-1.0, Epoch::Y1900), "-1");
     }
 
     #[test]
     fn zero_serial_fallback() {
-        assert_eq!(serial_to_iso(0.0), "0");
+        assert_eq!(serial_to_iso(0.0, Epoch::Y1900), "0");
     }
 
     #[test]
     fn huge_serial_fallback() {
-        assert_eq!(serial_to_iso(3_000_000.0), "3000000");
+        assert_eq!(serial_to_iso(3_000_000.0, Epoch::Y1900), "3000000");
     }
 
     #[test]
     fn known_date_feb_28_1900() {
         // Serial 59 = 1900-02-28
-        assert_eq!(serial_to_iso(59.0), "1900-02-28");
+        assert_eq!(serial_to_iso(59.0, Epoch::Y1900), "1900-02-28");
     }
 
     #[test]
     fn known_date_mar_1_1900() {
         // Serial 61 = 1900-03-01 (because 60 is the fake Feb 29)
-        assert_eq!(serial_to_iso(61.0), "1900-03-01");
+        assert_eq!(serial_to_iso(61.0, Epoch::Y1900), "1900-03-01");
     }
 
     #[test]
     fn known_date_dec_31_9999() {
         // Serial 2958465 = 9999-12-31 (max supported)
-        assert_eq!(serial_to_iso(2_958_465.0), "9999-12-31");
+        assert_eq!(serial_to_iso(2_958_465.0, Epoch::Y1900), "9999-12-31");
     }
 
     // ── frac_to_hms ───────────────────────────────────────────────
@@ -0,0 +1,451 @@
+//! Compare two documents (`batdoc diff`).
+//!
+//! Both documents are reduced to lines of the document model ([`Block`]s)
+//! rather than their rendered output, so a `.doc` can be compared with the
+//! `.docx` it was converted to, and a change of layout that leaves the
+//! text alone shows no difference. Lines are compared with Myers'
+//! algorithm and printed as a unified diff; in word mode, each changed
+//! run of lines is diffed again word by word.
+
+use std::fmt::Write as _;
+
+use crate::document::Block;
+
+/// Unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+/// git's default colors: hunk headers cyan, removals red, additions green.
+const HUNK_COLOR: &str = "\x1b[36m";
+const DELETE_COLOR: &str = "\x1b[31m";
+const INSERT_COLOR: &str = "\x1b[32m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// One step of an edit script turning the old sequence into the new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// The text of `blocks`, one line per line of text or table row.
+/// Headings keep their `#` markers and list items their bullets, so a
+/// change of structure shows up as well as a change of wording; pages,
+/// slides, and sheets get a `[page 2]` style line of their own.
+pub(crate) fn lines(blocks: &[Block]) -> Vec<String> {
+    let mut out = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Heading { level, text } => {
+                let marks = "#".repeat(usize::from(*level));
+                out.extend(text.lines().map(|line| format!("{marks} {line}")));
+            }
+            Block::Paragraph { text, .. } => {
+                out.extend(
+                    text.lines()
+                        .filter(|l| !l.trim().is_empty())
+                        .map(Into::into),
+                );
+            }
+            Block::ListItem { level, text, .. } => {
+                let indent = "  ".repeat(usize::from(*level));
+                out.extend(text.lines().map(|line| format!("{indent}- {line}")));
+            }
+            Block::Table { name, rows } => {
+                if let Some(name) = name {
+                    out.push(format!("[sheet {name}]"));
+                }
+                out.extend(rows.iter().map(|row| {
+                    row.iter()
+                        .map(|cell| cell.split_whitespace().collect::<Vec<_>>().join(" "))
+                        .collect::<Vec<_>>()
+                        .join("\t")
+                }));
+            }
+            Block::Slide { number } => out.push(format!("[slide {number}]")),
+            Block::Page { number } => out.push(format!("[page {number}]")),
+        }
+    }
+    out
+}
+
+/// A unified diff of `old` and `new` with `---`/`+++` headers naming
+/// them, or an empty string when they are the same. With `words`, changed
+/// lines are shown once with the removed words as `[-old-]` and the added
+/// ones as `{+new+}`; with `color`, changes are colored instead.
+pub(crate) fn render(
+    old_name: &str,
+    old: &[String],
+    new_name: &str,
+    new: &[String],
+    words: bool,
+    color: bool,
+) -> String {
+    let ops = diff(old, new);
+    if ops.iter().all(|&op| op == Op::Equal) {
+        return String::new();
+    }
+
+    let mut out = String::new();
+    let (bold, reset) = if color { (BOLD, RESET) } else { ("", "") };
+    let _ = writeln!(out, "{bold}--- {old_name}{reset}");
+    let _ = writeln!(out, "{bold}+++ {new_name}{reset}");
+
+    for hunk in hunks(&ops) {
+        // Positions in both sequences where the hunk starts
+        let (mut i, mut j) = ops[..hunk.start]
+            .iter()
+            .fold((0, 0), |pos, &op| advance(pos, op));
+        let (old_len, new_len) = ops[hunk.clone()]
+            .iter()
+            .fold((0, 0), |pos, &op| advance(pos, op));
+        let header = format!("@@ -{} +{} @@", range(i, old_len), range(j, new_len));
+        if color {
+            let _ = writeln!(out, "{HUNK_COLOR}{header}{RESET}");
+        } else {
+            let _ = writeln!(out, "{header}");
+        }
+
+        let hunk = &ops[hunk];
+        let mut k = 0;
+        while k < hunk.len() {
+            if hunk[k] == Op::Equal {
+                let prefix = if words { "" } else { " " };
+                let _ = writeln!(out, "{prefix}{}", old[i]);
+                i += 1;
+                j += 1;
+                k += 1;
+                continue;
+            }
+            // A run of changed lines: everything up to the next equal one
+            let run = hunk[k..].iter().take_while(|&&op| op != Op::Equal).count();
+            let deleted = hunk[k..k + run]
+                .iter()
+                .filter(|&&op| op == Op::Delete)
+                .count();
+            let inserted = run - deleted;
+            let removed = &old[i..i + deleted];
+            let added = &new[j..j + inserted];
+            if words {
+                out.push_str(&word_diff(&removed.join("\n"), &added.join("\n"), color));
+                out.push('\n');
+            } else {
+                for line in removed {
+                    push_line(&mut out, '-', line, color.then_some(DELETE_COLOR));
+                }
+                for line in added {
+                    push_line(&mut out, '+', line, color.then_some(INSERT_COLOR));
+                }
+            }
+            i += deleted;
+            j += inserted;
+            k += run;
+        }
+    }
+    out
+}
+
+/// Add one `-` or `+` line, in `color` if given.
+fn push_line(out: &mut String, sign: char, line: &str, color: Option<&str>) {
+    match color {
+        Some(color) => {
+            let _ = writeln!(out, "{color}{sign}{line}{RESET}");
+        }
+        None => {
+            let _ = writeln!(out, "{sign}{line}");
+        }
+    }
+}
+
+/// Count an op into the `(old, new)` positions it moves past.
+const fn advance((i, j): (usize, usize), op: Op) -> (usize, usize) {
+    match op {
+        Op::Equal => (i + 1, j + 1),
+        Op::Delete => (i + 1, j),
+        Op::Insert => (i, j + 1),
+    }
+}
+
+/// A hunk header range: 1-based start and length, with the length left
+/// out when it is 1 and the start naming the line before when it is 0.
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{len}", start + 1),
+    }
+}
+
+/// Ranges of `ops` to show: each change with up to [`CONTEXT`] equal ops
+/// on either side, merging changes whose context would overlap.
+fn hunks(ops: &[Op]) -> Vec<std::ops::Range<usize>> {
+    let mut hunks: Vec<std::ops::Range<usize>> = Vec::new();
+    for (k, &op) in ops.iter().enumerate() {
+        if op == Op::Equal {
+            continue;
+        }
+        let start = k.saturating_sub(CONTEXT);
+        let end = (k + 1 + CONTEXT).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => hunks.push(start..end),
+        }
+    }
+    hunks
+}
+
+/// `old` and `new` merged word by word, with the removed words marked as
+/// `[-old-]` and the added ones as `{+new+}` (or in red and green).
+fn word_diff(old: &str, new: &str, color: bool) -> String {
+    let old = tokens(old);
+    let new = tokens(new);
+    let ops = diff(&old, &new);
+    let (mut i, mut j) = (0, 0);
+    let mut out = String::new();
+    let mut k = 0;
+    while k < ops.len() {
+        if ops[k] == Op::Equal {
+            out.push_str(old[i]);
+            i += 1;
+            j += 1;
+            k += 1;
+            continue;
+        }
+        let run = ops[k..].iter().take_while(|&&op| op != Op::Equal).count();
+        let deleted = ops[k..k + run]
+            .iter()
+            .filter(|&&op| op == Op::Delete)
+            .count();
+        let inserted = run - deleted;
+        let removed = old[i..i + deleted].concat();
+        let added = new[j..j + inserted].concat();
+        if !removed.is_empty() {
+            if color {
+                let _ = write!(out, "{DELETE_COLOR}{removed}{RESET}");
+            } else {
+                let _ = write!(out, "[-{removed}-]");
+            }
+        }
+        if !added.is_empty() {
+            if color {
+                let _ = write!(out, "{INSERT_COLOR}{added}{RESET}");
+            } else {
+                let _ = write!(out, "{{+{added}+}}");
+            }
+        }
+        i += deleted;
+        j += inserted;
+        k += run;
+    }
+    out
+}
+
+/// Split text into alternating runs of whitespace and non-whitespace,
+/// so that joining the tokens gives the text back.
+fn tokens(text: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut space = None;
+    for (i, c) in text.char_indices() {
+        let is_space = c.is_whitespace();
+        if space.is_some_and(|s| s != is_space) {
+            out.push(&text[start..i]);
+            start = i;
+        }
+        space = Some(is_space);
+    }
+    if start < text.len() {
+        out.push(&text[start..]);
+    }
+    out
+}
+
+/// A shortest edit script from `a` to `b` (Myers' O(ND) algorithm), after
+/// setting aside the common prefix and suffix, which are usually most of a
+/// revised document.
+fn diff<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Op> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let mut ops = vec![Op::Equal; prefix];
+    ops.extend(myers(
+        &a[prefix..a.len() - suffix],
+        &b[prefix..b.len() - suffix],
+    ));
+    ops.extend(std::iter::repeat_n(Op::Equal, suffix));
+    ops
+}
+
+/// Myers' greedy algorithm, keeping the furthest-reaching x for each
+/// diagonal after every edit count so the path can be traced back.
+#[allow(
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::many_single_char_names,
+    clippy::suspicious_operation_groupings
+)] // lengths fit in isize; the paper's names
+fn myers<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Op> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    let mut trace = Vec::new();
+    let at = |k: isize| (k + offset) as usize;
+
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[at(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            ops.push(Op::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            ops.push(if x == prev_x { Op::Insert } else { Op::Delete });
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.reverse();
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(text: &str) -> Vec<String> {
+        text.lines().map(Into::into).collect()
+    }
+
+    // ── diff ─────────────────────────────────────────────────────
+
+    #[test]
+    fn edit_script_is_shortest() {
+        let a: Vec<char> = "ABCABBA".chars().collect();
+        let b: Vec<char> = "CBABAC".chars().collect();
+        let ops = diff(&a, &b);
+        // Myers' example: five edits, four matches
+        assert_eq!(ops.iter().filter(|&&op| op != Op::Equal).count(), 5);
+        let (i, j) = ops.iter().fold((0, 0), |pos, &op| advance(pos, op));
+        assert_eq!((i, j), (a.len(), b.len()));
+
+        assert_eq!(diff::<char>(&[], &[]), vec![]);
+        assert_eq!(diff(&['a'], &[]), vec![Op::Delete]);
+        assert_eq!(diff(&[], &['a']), vec![Op::Insert]);
+    }
+
+    // ── lines ────────────────────────────────────────────────────
+
+    #[test]
+    fn blocks_become_lines() {
+        let blocks = vec![
+            Block::Heading {
+                level: 2,
+                text: "Scope".into(),
+            },
+            Block::Paragraph {
+                text: "One\ntwo".into(),
+                spans: Vec::new(),
+            },
+            Block::ListItem {
+                level: 1,
+                ordered: false,
+                text: "item".into(),
+                spans: Vec::new(),
+            },
+            Block::Page { number: 2 },
+            Block::Table {
+                name: Some("Q3".into()),
+                rows: vec![vec!["a  b".into(), "c".into()]],
+            },
+        ];
+        assert_eq!(
+            lines(&blocks),
+            [
+                "## Scope",
+                "One",
+                "two",
+                "  - item",
+                "[page 2]",
+                "[sheet Q3]",
+                "a b\tc"
+            ]
+        );
+    }
+
+    // ── render ───────────────────────────────────────────────────
+
+    #[test]
+    fn unified_hunks_with_context() {
+        let old = strings("1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12");
+        let new = strings("1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13");
+        assert_eq!(
+            render("a.doc", &old, "b.docx", &new, false, false),
+            "--- a.doc\n+++ b.docx\n\
+             @@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n\
+             @@ -10,3 +10,4 @@\n 10\n 11\n 12\n+13\n"
+        );
+    }
+
+    #[test]
+    fn identical_documents_print_nothing() {
+        let lines = strings("same\ntext");
+        assert_eq!(render("a", &lines, "b", &lines, false, false), "");
+        assert_eq!(render("a", &[], "b", &[], true, false), "");
+    }
+
+    #[test]
+    fn word_mode_marks_changed_words() {
+        let old = strings("intro\nThe fee is 10 dollars per month.\noutro");
+        let new = strings("intro\nThe fee is 12 dollars a month.\noutro");
+        assert_eq!(
+            render("a", &old, "b", &new, true, false),
+            "--- a\n+++ b\n@@ -1,3 +1,3 @@\nintro\n\
+             The fee is [-10-]{+12+} dollars [-per-]{+a+} month.\noutro\n"
+        );
+    }
+
+    #[test]
+    fn hunk_range_for_insert_into_empty() {
+        let new = strings("only");
+        assert_eq!(
+            render("a", &[], "b", &new, false, false),
+            "--- a\n+++ b\n@@ -0,0 +1 @@\n+only\n"
+        );
+    }
+}
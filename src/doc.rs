@@ -4,18 +4,41 @@
 //! the FIB (File Information Block) header for text boundaries and flags,
 //! and extracts the text using the 256-byte block Unicode/8-bit heuristic
 //! from the original C `catdoc` project. Field codes (HYPERLINK, TOC, etc.)
-//! are suppressed.
+//! are suppressed; document-property fields are resolved against the
+//! summary information streams. Bookmarks are read from the table stream
+//! so that internal `HYPERLINK \l` links can point at markdown anchors.
+//!
+//! Documents encrypted with RC4 or XOR-obfuscated are decrypted with
+//! `opts.password`.
+//!
+//! When the FIB's text boundaries are unusable, the text is salvaged by
+//! scanning the whole stream for runs that look like text, as catdoc does
+//! for files it cannot parse, and a warning is raised.
 
 use cfb::CompoundFile;
 use std::io::{Cursor, Read};
 
 use crate::codepage;
+use crate::crypto::{Rc4Stream, XorApp, XorObfuscation};
+use crate::document;
 use crate::error::BatdocError;
+use crate::fields;
 use crate::heuristic;
+use crate::metadata::{self, Metadata};
+use crate::options::Options;
 
 // FIB flag bits
 const F_ENCRYPTED: u16 = 0x0100;
+const F_OBFUSCATED: u16 = 0x8000;
 const F_EXT_CHAR: u16 = 0x1000;
+const F_WHICH_TBL_STM: u16 = 0x0200;
+
+/// Bytes at the start of the FIB that are never encrypted.
+const FIB_CLEAR_LEN: usize = 68;
+
+// Indices into FibRgFcLcb97 (fc/lcb pairs locating table stream structures)
+const FC_STTBF_BKMK: usize = 21;
+const FC_PLCF_BKF: usize = 22;
 
 /// Extract markdown-formatted text from an OLE2 .doc file.
 ///
@@ -24,14 +47,40 @@ const F_EXT_CHAR: u16 = 0x1000;
 ///   - Numbered lines like "1. Foo" or "1.2 Bar" that are short → headings
 ///   - Short standalone lines (< 80 chars, no sentence-ending punctuation) → bold
 ///   - Tab-separated lines with consistent columns → markdown tables
-pub(crate) fn extract_markdown(data: &[u8]) -> crate::error::Result<String> {
-    let plain = extract_plain(data)?;
+///
+/// With `opts.reflow`, hard-wrapped lines are joined into paragraphs first.
+///
+/// Bookmarks that an internal link points at become `<a id="..."></a>`
+/// anchors before their paragraph.
+pub(crate) fn extract_markdown(data: &[u8], opts: &Options) -> crate::error::Result<String> {
+    let plain = extract_text(data, true, opts)?;
+    let plain = if opts.reflow {
+        heuristic::reflow(&plain)
+    } else {
+        plain
+    };
     Ok(heuristic::plain_to_markdown(&plain))
 }
 
+/// Extract paragraphs from a .doc file for the library API.
+pub(crate) fn extract_blocks(
+    data: &[u8],
+    opts: &Options,
+) -> crate::error::Result<Vec<document::Block>> {
+    let mut blocks = Vec::new();
+    document::text_blocks(&extract_plain(data, opts)?, &mut blocks);
+    Ok(blocks)
+}
+
 /// Extract plain text from an OLE2 .doc file.
 /// Returns the document text as a String with paragraph separation.
-pub(crate) fn extract_plain(data: &[u8]) -> crate::error::Result<String> {
+pub(crate) fn extract_plain(data: &[u8], opts: &Options) -> crate::error::Result<String> {
+    extract_text(data, false, opts)
+}
+
+/// Extract the document text, with bookmark anchors if `anchors` is set.
+/// Text salvaged from a damaged stream is reported to `opts.warnings`.
+fn extract_text(data: &[u8], anchors: bool, opts: &Options) -> crate::error::Result<String> {
     let cursor = Cursor::new(data);
     let mut cfb = CompoundFile::open(cursor)?;
 
@@ -45,6 +94,7 @@ pub(crate) fn extract_plain(data: &[u8]) -> crate::error::Result<String> {
     let mut stream = cfb.open_stream(stream_path)?;
     let mut buf = Vec::new();
     stream.read_to_end(&mut buf)?;
+    let meta = metadata::from_ole(&mut cfb, opts.encoding);
 
     if buf.len() < 32 {
         return Err(BatdocError::Document(
@@ -53,23 +103,47 @@ pub(crate) fn extract_plain(data: &[u8]) -> crate::error::Result<String> {
     }
 
     let flags = u16::from_le_bytes([buf[10], buf[11]]);
+    let table_path = if flags & F_WHICH_TBL_STM != 0 {
+        "/1Table"
+    } else {
+        "/0Table"
+    };
+    let mut table = Vec::new();
+    if let Ok(mut stream) = cfb.open_stream(table_path) {
+        stream.read_to_end(&mut table)?;
+    }
 
     if flags & F_ENCRYPTED != 0 {
-        return Err(BatdocError::Document("document is encrypted".into()));
+        decrypt(&mut buf, &mut table, flags, opts.password.as_deref())?;
     }
 
     // FIB `lid` (install language) at offset 6-7, used to infer codepage
-    // for 8-bit text blocks when no piece table is available.
+    // for 8-bit text blocks when no piece table is available, unless
+    // --encoding names one.
     let lid = u16::from_le_bytes([buf[6], buf[7]]);
-    let cp = codepage::lid_to_codepage(lid);
+    let cp = opts
+        .encoding
+        .unwrap_or_else(|| codepage::lid_to_codepage(lid));
+    opts.notes.push(match opts.encoding {
+        Some(_) => format!("8-bit text decoded as cp{cp} (--encoding)"),
+        None => format!("8-bit text decoded as cp{cp}, from language {lid:#06x}"),
+    });
 
     let text_start = u32::from_le_bytes([buf[24], buf[25], buf[26], buf[27]]) as usize; // u32 → usize: lossless on 32+ bit
     let text_end = u32::from_le_bytes([buf[28], buf[29], buf[30], buf[31]]) as usize;
 
     if text_start >= buf.len() || text_end > buf.len() || text_start >= text_end {
-        return Err(BatdocError::Document(
-            "invalid text boundaries in FIB".into(),
-        ));
+        let chars = salvage_text(&buf, cp);
+        if chars.is_empty() {
+            return Err(BatdocError::Document(
+                "invalid text boundaries in FIB".into(),
+            ));
+        }
+        opts.warnings.push(
+            "invalid text boundaries in FIB; text was recovered by scanning \
+             the document and may be incomplete or out of order",
+        );
+        return Ok(chars_to_text(&chars, &meta));
     }
 
     let text_data = &buf[text_start..text_end];
@@ -81,7 +155,139 @@ pub(crate) fn extract_plain(data: &[u8]) -> crate::error::Result<String> {
         extract_8bit_text(text_data, cp)
     };
 
-    Ok(chars_to_text(&chars))
+    if !anchors {
+        return Ok(chars_to_text(&chars, &meta));
+    }
+    let bookmarks = linked_bookmarks(read_bookmarks(&buf, &table), &chars);
+    Ok(render_chars(&chars, &meta, &bookmarks))
+}
+
+/// Decrypt the `WordDocument` stream `buf` and the table stream of an
+/// encrypted document in place.
+///
+/// With RC4, the encryption header takes the first `lKey` bytes of the
+/// table stream; it and the start of the FIB are stored in the clear, but
+/// count towards the block offsets. With XOR obfuscation, `lKey` holds the
+/// password verifier and the whole table stream is obfuscated.
+fn decrypt(
+    buf: &mut [u8],
+    table: &mut [u8],
+    flags: u16,
+    password: Option<&str>,
+) -> crate::error::Result<()> {
+    let password = password.ok_or(BatdocError::Encrypted)?;
+    if flags & F_OBFUSCATED != 0 {
+        let verifier = read_u16(buf, 14).unwrap_or(0);
+        let xor = XorObfuscation::new(verifier, password, XorApp::Word)?;
+        if let Some(rest) = buf.get_mut(FIB_CLEAR_LEN..) {
+            xor.decrypt(rest, FIB_CLEAR_LEN);
+        }
+        xor.decrypt(table, 0);
+        return Ok(());
+    }
+    let header_len = read_u32(buf, 14).unwrap_or(0) as usize; // u32 → usize: lossless on 32+ bit
+    let header = table
+        .get(..header_len)
+        .ok_or_else(|| BatdocError::Document("encryption header past the table stream".into()))?;
+    let rc4 = Rc4Stream::new(header, password, 512)?;
+    if let Some(rest) = buf.get_mut(FIB_CLEAR_LEN..) {
+        rc4.decrypt(rest, FIB_CLEAR_LEN);
+    }
+    rc4.decrypt(&mut table[header_len..], header_len);
+    Ok(())
+}
+
+/// Location of a `FibRgFcLcb97` structure in the table stream, if present
+/// and non-empty.
+fn fib_fc_lcb(fib: &[u8], index: usize) -> Option<(usize, usize)> {
+    // FibBase (32 bytes), then the counted FibRgW, FibRgLw, and FibRgFcLcb
+    let words = usize::from(read_u16(fib, 32)?);
+    let longs_at = 34 + words * 2;
+    let longs = usize::from(read_u16(fib, longs_at)?);
+    let cb_at = longs_at + 2 + longs * 4;
+    if index >= usize::from(read_u16(fib, cb_at)?) {
+        return None;
+    }
+    let at = cb_at + 2 + index * 8;
+    let fc = read_u32(fib, at)? as usize; // u32 → usize: lossless on 32+ bit
+    let lcb = read_u32(fib, at + 4)? as usize;
+    (lcb > 0).then_some((fc, lcb))
+}
+
+/// Bookmark starts as `(cp, name)`, sorted by character position, from
+/// the `SttbfBkmk` names and `PlcfBkf` positions. Empty if either is missing
+/// or malformed.
+fn read_bookmarks(fib: &[u8], table: &[u8]) -> Vec<(usize, String)> {
+    let slice = |index| {
+        let (fc, lcb) = fib_fc_lcb(fib, index)?;
+        table.get(fc..fc.checked_add(lcb)?)
+    };
+    let (Some(sttb), Some(plc)) = (slice(FC_STTBF_BKMK), slice(FC_PLCF_BKF)) else {
+        return Vec::new();
+    };
+    let names = read_sttb(sttb);
+    // PlcfBkf: n + 1 CPs followed by n 4-byte FBKF entries
+    let n = plc.len().saturating_sub(4) / 8;
+    let mut bookmarks: Vec<(usize, String)> = (0..n.min(names.len()))
+        .filter_map(|i| Some((read_u32(plc, i * 4)? as usize, names[i].clone())))
+        .collect();
+    bookmarks.sort_by_key(|&(cp, _)| cp);
+    bookmarks
+}
+
+/// Strings of an extended (UTF-16) STTB without extra data per entry.
+fn read_sttb(data: &[u8]) -> Vec<String> {
+    if read_u16(data, 0) != Some(0xFFFF) {
+        return Vec::new();
+    }
+    let count = read_u16(data, 2).unwrap_or(0);
+    let cb_extra = usize::from(read_u16(data, 4).unwrap_or(0));
+    let mut names = Vec::new();
+    let mut at = 6;
+    for _ in 0..count {
+        let Some(cch) = read_u16(data, at) else {
+            break;
+        };
+        let end = at + 2 + usize::from(cch) * 2;
+        let Some(bytes) = data.get(at + 2..end) else {
+            break;
+        };
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        names.push(String::from_utf16_lossy(&units));
+        at = end + cb_extra;
+    }
+    names
+}
+
+/// The bookmarks that some `HYPERLINK \l` field in the text links to.
+/// Word adds many bookmarks of its own (`_GoBack`, `_Toc…`), so anchors
+/// are only worth emitting where a link lands.
+fn linked_bookmarks(bookmarks: Vec<(usize, String)>, chars: &[u16]) -> Vec<(usize, String)> {
+    let text = String::from_utf16_lossy(chars);
+    let targets: Vec<String> = text
+        .split('\u{13}')
+        .filter_map(|field| {
+            let instr = field.split(['\u{14}', '\u{15}']).next()?;
+            fields::internal_link_target(instr)
+        })
+        .collect();
+    bookmarks
+        .into_iter()
+        .filter(|(_, name)| targets.iter().any(|t| t.eq_ignore_ascii_case(name)))
+        .collect()
+}
+
+fn read_u16(data: &[u8], off: usize) -> Option<u16> {
+    data.get(off..off + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], off: usize) -> Option<u32> {
+    data.get(off..off + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
 }
 
 /// Extract text from Word 97+ format using the 256-byte block heuristic.
@@ -115,6 +321,81 @@ fn extract_word8_text(data: &[u8], codepage: u16) -> Vec<u16> {
     result
 }
 
+/// Shortest run of text kept by [`salvage_text`]; shorter runs are too
+/// likely to be binary data that happens to decode.
+const SALVAGE_MIN_RUN: usize = 12;
+
+/// Recover text from a `WordDocument` stream whose FIB cannot be trusted,
+/// by keeping runs of plausible UTF-16LE or 8-bit text in stream order,
+/// each ending a paragraph.
+fn salvage_text(buf: &[u8], codepage: u16) -> Vec<u16> {
+    // (offset, text) of every run found by either scan
+    let mut runs: Vec<(usize, Vec<u16>)> = Vec::new();
+
+    // UTF-16LE text may start at an even or an odd offset
+    for parity in 0..2 {
+        let mut start = parity;
+        let mut run = Vec::new();
+        let pairs = buf.get(parity..).unwrap_or_default().chunks_exact(2);
+        for (i, pair) in pairs.enumerate() {
+            let unit = u16::from_le_bytes([pair[0], pair[1]]);
+            if is_salvageable_unit(unit) {
+                if run.is_empty() {
+                    start = parity + 2 * i;
+                }
+                run.push(unit);
+            } else {
+                keep_run(&mut runs, start, &mut run);
+            }
+        }
+        keep_run(&mut runs, start, &mut run);
+    }
+
+    // 8-bit text; 0xFF is left out as the usual filler of binary data
+    let mut start = 0;
+    let mut run = Vec::new();
+    for (i, &b) in buf.iter().enumerate() {
+        if matches!(b, b'\t' | b'\r' | 0x20..=0x7E | 0xA0..=0xFE) {
+            if run.is_empty() {
+                start = i;
+            }
+            let mut units = [0u16; 2];
+            run.extend_from_slice(codepage::decode_byte(b, codepage).encode_utf16(&mut units));
+        } else {
+            keep_run(&mut runs, start, &mut run);
+        }
+    }
+    keep_run(&mut runs, start, &mut run);
+
+    runs.sort_by_key(|&(offset, _)| offset);
+    let mut chars = Vec::new();
+    for (_, run) in runs {
+        chars.extend_from_slice(&run);
+        chars.push(u16::from(b'\r'));
+    }
+    chars
+}
+
+/// Move `run` into `runs` if it reads like prose — long enough, mostly
+/// letters, with at least one space — and clear it either way.
+fn keep_run(runs: &mut Vec<(usize, Vec<u16>)>, start: usize, run: &mut Vec<u16>) {
+    let letters = char::decode_utf16(run.iter().copied())
+        .filter(|c| c.as_ref().is_ok_and(|c| c.is_alphabetic()))
+        .count();
+    if run.len() >= SALVAGE_MIN_RUN && letters * 2 >= run.len() && run.contains(&0x20) {
+        runs.push((start, std::mem::take(run)));
+    } else {
+        run.clear();
+    }
+}
+
+/// Whether a UTF-16 code unit is likely document text: printable ASCII,
+/// a paragraph mark or tab, or a letter or punctuation mark from the
+/// Latin, Greek, and Cyrillic blocks.
+const fn is_salvageable_unit(u: u16) -> bool {
+    matches!(u, 0x09 | 0x0D | 0x20..=0x7E | 0xA0..=0x024F | 0x0370..=0x04FF | 0x2010..=0x2027)
+}
+
 /// Detect if a 256-byte block is UTF-16LE encoded.
 fn detect_unicode_block(block: &[u8]) -> bool {
     block.chunks_exact(2).any(|pair| {
@@ -149,6 +430,26 @@ fn flush_paragraph(paragraph: &mut String, output: &mut String, first: &mut bool
     paragraph.clear();
 }
 
+/// Emit `<a id="..."></a>` anchors as their own paragraph ahead of the
+/// paragraph they belong to, once it turns out to have text.
+fn flush_anchors(
+    anchors: &mut Vec<String>,
+    paragraph: &str,
+    output: &mut String,
+    first: &mut bool,
+) {
+    if anchors.is_empty() || paragraph.trim_end().is_empty() {
+        return;
+    }
+    let mut line = String::new();
+    for name in anchors.drain(..) {
+        line.push_str("<a id=\"");
+        line.push_str(&name);
+        line.push_str("\"></a>");
+    }
+    flush_paragraph(&mut line, output, first);
+}
+
 /// Tracks what we're collecting during a field: instruction or display text.
 #[derive(Clone)]
 enum FieldState {
@@ -156,7 +457,11 @@ enum FieldState {
     Instruction(String),
     /// Collecting display text (between 0x0014 and 0x0015), with the URL
     /// if this is a HYPERLINK field.
-    Display { url: Option<String>, text: String },
+    Display {
+        instr: String,
+        url: Option<String>,
+        text: String,
+    },
 }
 
 /// Process the u16 character stream into paragraphs, extracting hyperlinks
@@ -168,21 +473,35 @@ enum FieldState {
 /// - `0x0015` — field end
 ///
 /// For `HYPERLINK` fields, we capture the URL from the instruction and
-/// emit `[display text](url)` inline. Other field types (TOC, PAGE, etc.)
-/// are suppressed as before.
+/// emit `[display text](url)` inline. Document-property fields (`DOCPROPERTY`,
+/// `CREATEDATE`, …) are resolved against `meta`. Other field types (TOC,
+/// PAGE, etc.) show their display text.
 ///
 /// Handles UTF-16 surrogate pairs: a high surrogate (0xD800-0xDBFF) followed
 /// by a low surrogate (0xDC00-0xDFFF) is decoded into the correct supplementary
 /// plane character. Unpaired surrogates are replaced with U+FFFD.
-fn chars_to_text(chars: &[u16]) -> String {
+fn chars_to_text(chars: &[u16], meta: &Metadata) -> String {
+    render_chars(chars, meta, &[])
+}
+
+/// [`chars_to_text`], with an anchor paragraph ahead of each paragraph
+/// containing the start of one of `bookmarks` (`(cp, name)`, sorted by
+/// CP). Character positions are indices into `chars`.
+fn render_chars(chars: &[u16], meta: &Metadata, bookmarks: &[(usize, String)]) -> String {
     let mut output = String::new();
+    let mut bookmarks = bookmarks.iter().peekable();
+    let mut anchors: Vec<String> = Vec::new();
     let mut paragraph = String::new();
     let mut first = true;
     let mut field_depth: i32 = 0;
     let mut field_stack: Vec<FieldState> = Vec::new();
     let mut pending_high_surrogate: Option<u16> = None;
 
-    for &c in chars {
+    for (cp, &c) in chars.iter().enumerate() {
+        while let Some((_, name)) = bookmarks.next_if(|(at, _)| *at <= cp) {
+            anchors.push(name.clone());
+        }
+
         // Handle surrogate pair completion
         if let Some(hi) = pending_high_surrogate.take() {
             if (0xDC00..=0xDFFF).contains(&c) {
@@ -212,19 +531,18 @@ fn chars_to_text(chars: &[u16]) -> String {
             0x0014 => {
                 // Field separator — switch from instruction to display text
                 if let Some(state) = field_stack.last_mut() {
-                    let url = if let FieldState::Instruction(ref instr) = state {
-                        extract_hyperlink_url(instr)
-                    } else {
-                        None
-                    };
-                    *state = FieldState::Display {
-                        url,
-                        text: String::new(),
-                    };
+                    if let FieldState::Instruction(instr) = state {
+                        let instr = std::mem::take(instr);
+                        *state = FieldState::Display {
+                            url: fields::hyperlink_url(&instr),
+                            instr,
+                            text: String::new(),
+                        };
+                    }
                 }
             }
             0x0015 => {
-                emit_field_end(&mut field_stack, &mut paragraph);
+                emit_field_end(&mut field_stack, &mut paragraph, meta);
                 if field_depth > 0 {
                     field_depth -= 1;
                 }
@@ -251,6 +569,7 @@ fn chars_to_text(chars: &[u16]) -> String {
                 }
             }
             0x000B..=0x000D => {
+                flush_anchors(&mut anchors, &paragraph, &mut output, &mut first);
                 flush_paragraph(&mut paragraph, &mut output, &mut first);
             }
             0x0007 | 0x0009 => {
@@ -278,18 +597,20 @@ fn chars_to_text(chars: &[u16]) -> String {
         paragraph.push('\u{FFFD}');
     }
 
+    flush_anchors(&mut anchors, &paragraph, &mut output, &mut first);
     flush_paragraph(&mut paragraph, &mut output, &mut first);
     output
 }
 
 /// Process a field-end marker (0x0015): pop the field state and emit
 /// the result into the paragraph.
-fn emit_field_end(field_stack: &mut Vec<FieldState>, paragraph: &mut String) {
+fn emit_field_end(field_stack: &mut Vec<FieldState>, paragraph: &mut String, meta: &Metadata) {
     if let Some(state) = field_stack.pop() {
         match state {
             FieldState::Display {
                 url: Some(url),
                 text,
+                ..
             } => {
                 // Emit markdown-style link
                 paragraph.push('[');
@@ -298,12 +619,23 @@ fn emit_field_end(field_stack: &mut Vec<FieldState>, paragraph: &mut String) {
                 paragraph.push_str(&url);
                 paragraph.push(')');
             }
-            FieldState::Display { url: None, text } => {
-                // Non-hyperlink field with display text — emit the display text
-                paragraph.push_str(&text);
+            FieldState::Display {
+                url: None,
+                instr,
+                text,
+            } => {
+                // Non-hyperlink field — emit the property value if it is a
+                // document-property field, otherwise the display text
+                match fields::resolve(&instr, meta) {
+                    Some(value) => paragraph.push_str(&value),
+                    None => paragraph.push_str(&text),
+                }
             }
-            FieldState::Instruction(_) => {
-                // No separator seen — field is fully suppressed
+            FieldState::Instruction(instr) => {
+                // No separator seen — only property fields produce output
+                if let Some(value) = fields::resolve(&instr, meta) {
+                    paragraph.push_str(&value);
+                }
             }
         }
     }
@@ -322,56 +654,6 @@ fn push_char_to_field_or_para(ch: char, field_stack: &mut [FieldState], paragrap
     }
 }
 
-/// Extract a URL from a HYPERLINK field instruction string.
-///
-/// Field instruction format: `HYPERLINK "http://example.com" \l "bookmark"`
-/// or `HYPERLINK http://example.com`. We extract the URL, handling both
-/// quoted and unquoted forms.
-fn extract_hyperlink_url(instruction: &str) -> Option<String> {
-    let trimmed = instruction.trim();
-
-    // Must start with "HYPERLINK" (case-insensitive)
-    let rest = if let Some(r) = trimmed.strip_prefix("HYPERLINK") {
-        r
-    } else if let Some(r) = trimmed.strip_prefix("hyperlink") {
-        r
-    } else {
-        let lower = trimmed.to_lowercase();
-        if let Some(idx) = lower.find("hyperlink") {
-            &trimmed[idx + 9..]
-        } else {
-            return None;
-        }
-    };
-
-    let rest = rest.trim_start();
-    if rest.is_empty() {
-        return None;
-    }
-
-    // Extract URL: may be quoted or unquoted
-    let url = rest.strip_prefix('"').map_or_else(
-        || {
-            // Unquoted: take until whitespace or backslash (switch start)
-            let end = rest
-                .find(|c: char| c.is_whitespace() || c == '\\')
-                .unwrap_or(rest.len());
-            &rest[..end]
-        },
-        |inner| {
-            // Quoted: find closing quote
-            let end = inner.find('"').unwrap_or(inner.len());
-            &inner[..end]
-        },
-    );
-
-    if url.is_empty() {
-        None
-    } else {
-        Some(url.to_string())
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -406,6 +688,31 @@ mod tests {
         assert!(!detect_unicode_block(&[0x20]));
     }
 
+    // ── salvage_text ─────────────────────────────────────────────
+
+    fn utf16le(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn salvage_finds_utf16_and_8bit_runs_in_order() {
+        let mut buf = vec![0xEC, 0xA5, 0xC1, 0x00, 0x07, 0x01, 0xFF, 0xFF];
+        buf.extend(utf16le("First paragraph here\r"));
+        buf.extend([0x00, 0x03, 0x9C, 0x00]);
+        buf.extend(b"Second one, in 8-bit\r");
+        buf.extend([0xFF; 40]);
+        let text = chars_to_text(&salvage_text(&buf, 1252), &Metadata::default());
+        assert_eq!(text, "First paragraph here\n\nSecond one, in 8-bit\n");
+    }
+
+    #[test]
+    fn salvage_ignores_short_and_binary_runs() {
+        let mut buf = utf16le("Hi there");
+        buf.extend([0x00; 3]);
+        buf.extend(b"x1y2z3w4v5u6t7s8");
+        assert!(salvage_text(&buf, 1252).is_empty());
+    }
+
     // ── chars_to_text ────────────────────────────────────────────
 
     #[test]
@@ -413,7 +720,10 @@ mod tests {
         let chars: Vec<u16> = "Hello world".encode_utf16().collect();
         let mut chars_with_cr = chars;
         chars_with_cr.push(0x000D);
-        assert_eq!(chars_to_text(&chars_with_cr), "Hello world\n");
+        assert_eq!(
+            chars_to_text(&chars_with_cr, &Metadata::default()),
+            "Hello world\n"
+        );
     }
 
     #[test]
@@ -423,7 +733,10 @@ mod tests {
         chars.push(0x000D);
         chars.extend("Second".encode_utf16());
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "First\n\nSecond\n");
+        assert_eq!(
+            chars_to_text(&chars, &Metadata::default()),
+            "First\n\nSecond\n"
+        );
     }
 
     #[test]
@@ -438,7 +751,7 @@ mod tests {
         chars.push(0x0015); // field end
         chars.push(0x000D);
         assert_eq!(
-            chars_to_text(&chars),
+            chars_to_text(&chars, &Metadata::default()),
             "Before [visible text](http://example.com)\n"
         );
     }
@@ -452,7 +765,10 @@ mod tests {
         chars.extend("click".encode_utf16());
         chars.push(0x0015);
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "[click](http://example.com)\n");
+        assert_eq!(
+            chars_to_text(&chars, &Metadata::default()),
+            "[click](http://example.com)\n"
+        );
     }
 
     #[test]
@@ -466,7 +782,28 @@ mod tests {
         chars.extend("42".encode_utf16());
         chars.push(0x0015); // field end
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "Page 42\n");
+        assert_eq!(chars_to_text(&chars, &Metadata::default()), "Page 42\n");
+    }
+
+    #[test]
+    fn docproperty_field_resolved_from_metadata() {
+        let meta = Metadata {
+            title: Some("Final Title".into()),
+            ..Metadata::default()
+        };
+        let mut chars: Vec<u16> = Vec::new();
+        chars.push(0x0013);
+        chars.extend(r"DOCPROPERTY Title \* MERGEFORMAT".encode_utf16());
+        chars.push(0x0014);
+        chars.extend("Draft".encode_utf16());
+        chars.push(0x0015);
+        chars.push(0x0013);
+        chars.extend("AUTHOR".encode_utf16());
+        chars.push(0x0015);
+        chars.push(0x000D);
+        // Title is replaced; AUTHOR is unset, so the bare field stays hidden
+        assert_eq!(chars_to_text(&chars, &meta), "Final Title\n");
+        assert_eq!(chars_to_text(&chars, &Metadata::default()), "Draft\n");
     }
 
     #[test]
@@ -479,28 +816,71 @@ mod tests {
         chars.push(0x0015); // field end (no separator)
         chars.extend(" After".encode_utf16());
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "Before  After\n");
+        assert_eq!(
+            chars_to_text(&chars, &Metadata::default()),
+            "Before  After\n"
+        );
+    }
+
+    // ── bookmarks ────────────────────────────────────────────────
+
+    /// A field: begin, instruction, separator, display text, end.
+    fn field(instr: &str, display: &str) -> Vec<u16> {
+        let mut chars = vec![0x0013];
+        chars.extend(instr.encode_utf16());
+        chars.push(0x0014);
+        chars.extend(display.encode_utf16());
+        chars.push(0x0015);
+        chars
     }
 
     #[test]
-    fn extract_hyperlink_url_quoted() {
+    fn anchor_before_bookmarked_paragraph() {
+        let mut chars = field(r#"HYPERLINK \l "Results""#, "see results");
+        chars.push(0x000D);
+        let start = chars.len();
+        chars.extend("Results".encode_utf16());
+        chars.push(0x000D);
+
+        let bookmarks = linked_bookmarks(
+            vec![(0, "_GoBack".into()), (start, "results".into())],
+            &chars,
+        );
+        assert_eq!(bookmarks, vec![(start, "results".to_string())]);
         assert_eq!(
-            extract_hyperlink_url(r#" HYPERLINK "https://example.com" \l "top""#),
-            Some("https://example.com".to_string())
+            render_chars(&chars, &Metadata::default(), &bookmarks),
+            "[see results](#Results)\n\n<a id=\"results\"></a>\n\nResults\n"
         );
     }
 
     #[test]
-    fn extract_hyperlink_url_unquoted() {
+    fn anchor_waits_for_text() {
+        // Bookmark on an empty paragraph lands on the next one with text
+        let chars: Vec<u16> = "\rBody\r".encode_utf16().collect();
         assert_eq!(
-            extract_hyperlink_url("HYPERLINK http://example.com"),
-            Some("http://example.com".to_string())
+            render_chars(&chars, &Metadata::default(), &[(0, "top".into())]),
+            "<a id=\"top\"></a>\n\nBody\n"
         );
     }
 
     #[test]
-    fn extract_hyperlink_url_not_hyperlink() {
-        assert_eq!(extract_hyperlink_url("TOC \\o \\h"), None);
+    fn anchor_survives_markdown_heuristics() {
+        let chars: Vec<u16> = "1. Introduction\r".encode_utf16().collect();
+        let plain = render_chars(&chars, &Metadata::default(), &[(0, "intro".into())]);
+        let md = heuristic::plain_to_markdown(&heuristic::reflow(&plain));
+        assert_eq!(md, "<a id=\"intro\"></a>\n\n# 1. Introduction\n\n");
+    }
+
+    #[test]
+    fn sttb_names() {
+        let mut data = vec![0xFF, 0xFF, 2, 0, 0, 0];
+        for name in ["a", "Bc"] {
+            data.extend(u16::try_from(name.len()).unwrap().to_le_bytes());
+            data.extend(name.encode_utf16().flat_map(u16::to_le_bytes));
+        }
+        assert_eq!(read_sttb(&data), vec!["a", "Bc"]);
+        // Non-extended (8-bit) tables are not used for bookmarks
+        assert!(read_sttb(&[0x02, 0x00]).is_empty());
     }
 
     #[test]
@@ -510,7 +890,7 @@ mod tests {
         chars.push(0x0009); // tab
         chars.extend("B".encode_utf16());
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "A\tB\n");
+        assert_eq!(chars_to_text(&chars, &Metadata::default()), "A\tB\n");
     }
 
     #[test]
@@ -520,7 +900,10 @@ mod tests {
         chars.push(0x0007); // cell marker
         chars.extend("Cell2".encode_utf16());
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "Cell1\tCell2\n");
+        assert_eq!(
+            chars_to_text(&chars, &Metadata::default()),
+            "Cell1\tCell2\n"
+        );
     }
 
     #[test]
@@ -530,7 +913,7 @@ mod tests {
         chars.push(0x001E); // non-breaking hyphen
         chars.extend("known".encode_utf16());
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "well-known\n");
+        assert_eq!(chars_to_text(&chars, &Metadata::default()), "well-known\n");
     }
 
     #[test]
@@ -538,13 +921,13 @@ mod tests {
         let mut chars: Vec<u16> = Vec::new();
         chars.extend("Hello   ".encode_utf16());
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "Hello\n");
+        assert_eq!(chars_to_text(&chars, &Metadata::default()), "Hello\n");
     }
 
     #[test]
     fn empty_paragraphs_skipped() {
         let chars: Vec<u16> = vec![0x000D, 0x000D, 0x000D];
-        assert_eq!(chars_to_text(&chars), "");
+        assert_eq!(chars_to_text(&chars, &Metadata::default()), "");
     }
 
     #[test]
@@ -552,7 +935,7 @@ mod tests {
         let mut chars: Vec<u16> = vec![0xFEFF];
         chars.extend("Hello".encode_utf16());
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "Hello\n");
+        assert_eq!(chars_to_text(&chars, &Metadata::default()), "Hello\n");
     }
 
     #[test]
@@ -562,13 +945,16 @@ mod tests {
         chars.push(0x000C); // page break
         chars.extend("Page2".encode_utf16());
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "Page1\n\nPage2\n");
+        assert_eq!(
+            chars_to_text(&chars, &Metadata::default()),
+            "Page1\n\nPage2\n"
+        );
     }
 
     #[test]
     fn text_without_trailing_cr() {
         let chars: Vec<u16> = "No newline".encode_utf16().collect();
-        assert_eq!(chars_to_text(&chars), "No newline\n");
+        assert_eq!(chars_to_text(&chars, &Metadata::default()), "No newline\n");
     }
 
     // ── surrogate pair handling ─────────────────────────────────
@@ -578,21 +964,21 @@ mod tests {
         // U+1F600 (😀) = D83D DE00 in UTF-16
         let mut chars: Vec<u16> = vec![0xD83D, 0xDE00];
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "\u{1F600}\n");
+        assert_eq!(chars_to_text(&chars, &Metadata::default()), "\u{1F600}\n");
     }
 
     #[test]
     fn unpaired_high_surrogate() {
         let mut chars: Vec<u16> = vec![0xD83D]; // high surrogate alone
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "\u{FFFD}\n");
+        assert_eq!(chars_to_text(&chars, &Metadata::default()), "\u{FFFD}\n");
     }
 
     #[test]
     fn unpaired_low_surrogate() {
         let mut chars: Vec<u16> = vec![0xDE00]; // low surrogate alone
         chars.push(0x000D);
-        assert_eq!(chars_to_text(&chars), "\u{FFFD}\n");
+        assert_eq!(chars_to_text(&chars, &Metadata::default()), "\u{FFFD}\n");
     }
 
     // ── extract_8bit_text ────────────────────────────────────────
@@ -618,4 +1004,68 @@ mod tests {
         let result = extract_8bit_text(&data, 1251);
         assert_eq!(result, vec![0x0410]);
     }
+
+    // ── decrypt ──────────────────────────────────────────────────
+
+    #[test]
+    fn decrypts_rc4_streams() {
+        let header = crate::crypto::rc4_header("secret");
+        let mut buf: Vec<u8> = (0..200).collect();
+        buf[14..18].copy_from_slice(&u32::try_from(header.len()).unwrap().to_le_bytes());
+        let plain_fib = buf.clone();
+        let plain_table = [header.as_slice(), b"table stream contents"].concat();
+        let mut table = plain_table.clone();
+        let flags = F_ENCRYPTED;
+
+        // RC4 is symmetric, so decrypting the plain streams encrypts them
+        decrypt(&mut buf, &mut table, flags, Some("secret")).unwrap();
+        assert_eq!(buf[..FIB_CLEAR_LEN], plain_fib[..FIB_CLEAR_LEN]);
+        assert_ne!(buf, plain_fib);
+        assert_eq!(table[..header.len()], header[..]);
+        assert_ne!(table, plain_table);
+        decrypt(&mut buf, &mut table, flags, Some("secret")).unwrap();
+        assert_eq!((buf, table), (plain_fib, plain_table));
+    }
+
+    #[test]
+    fn encrypted_needs_the_password() {
+        let header = crate::crypto::rc4_header("secret");
+        let mut fib = vec![0u8; 100];
+        fib[14..18].copy_from_slice(&u32::try_from(header.len()).unwrap().to_le_bytes());
+        let mut table = header;
+        assert!(matches!(
+            decrypt(&mut fib, &mut table, F_ENCRYPTED, None),
+            Err(BatdocError::Encrypted)
+        ));
+        assert!(matches!(
+            decrypt(&mut fib, &mut table, F_ENCRYPTED, Some("guess")),
+            Err(BatdocError::WrongPassword)
+        ));
+    }
+
+    #[test]
+    fn decrypts_xor_obfuscation() {
+        let key = crate::crypto::xor_header("secret");
+        let mut buf: Vec<u8> = (0..200).collect();
+        buf[14..18].copy_from_slice(&[key[2], key[3], 0, 0]);
+        let plain_fib = buf.clone();
+        let plain_table = b"table stream contents".to_vec();
+        let mut table = plain_table.clone();
+        let flags = F_ENCRYPTED | F_OBFUSCATED;
+
+        // Word's XOR obfuscation is symmetric too
+        decrypt(&mut buf, &mut table, flags, Some("secret")).unwrap();
+        assert_eq!(buf[..FIB_CLEAR_LEN], plain_fib[..FIB_CLEAR_LEN]);
+        assert_ne!((&buf, &table), (&plain_fib, &plain_table));
+        assert!(matches!(
+            decrypt(&mut buf, &mut table, flags, Some("guess")),
+            Err(BatdocError::WrongPassword)
+        ));
+        assert!(matches!(
+            decrypt(&mut buf, &mut table, flags, None),
+            Err(BatdocError::Encrypted)
+        ));
+        decrypt(&mut buf, &mut table, flags, Some("secret")).unwrap();
+        assert_eq!((buf, table), (plain_fib, plain_table));
+    }
 }
@@ -0,0 +1,469 @@
+//! Format-independent document structure for library consumers.
+//!
+//! Each format parser turns its own internal representation into a flat
+//! sequence of [`Block`]s, which [`DocumentReader`] hands out one at a time.
+//! The parser runs on a thread of its own a batch of blocks ahead of the
+//! reader: a paragraph or table of a `.docx`, a sheet, a slide or a PDF
+//! page. Callers that only need the first few blocks, or that process
+//! blocks as they go, can stop early and drop the reader, and the rest of
+//! the document is never parsed.
+
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+use crate::cancel::CancelToken;
+use crate::error::{BatdocError, Result};
+use crate::format::{detect_format, Format};
+use crate::options::Options;
+use crate::sheet::Sheet;
+use crate::{doc, docx, pdf, pptx, xls, xlsx};
+
+/// A unit of document content, as plain text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Block {
+    /// A heading; `level` is 1 (top) to 9.
+    Heading { level: u8, text: String },
+    /// A paragraph of body text. Lines within it are separated by `\n`.
+    /// `spans` carry styling of parts of the text, where the format has it.
+    Paragraph { text: String, spans: Vec<Span> },
+    /// A list item; `level` is the nesting depth, 0 for the outermost list.
+    ListItem {
+        level: u8,
+        ordered: bool,
+        text: String,
+        spans: Vec<Span>,
+    },
+    /// A table of cell text. Spreadsheets produce one table per sheet,
+    /// with the sheet name in `name`.
+    Table {
+        name: Option<String>,
+        rows: Vec<Vec<String>>,
+    },
+    /// Start of a presentation slide (numbered from 1).
+    Slide { number: usize },
+    /// Start of a PDF page (numbered from 1).
+    Page { number: usize },
+}
+
+/// Styling of part of a block's text that plain text cannot show, such
+/// as red or highlighted words in a `.docx`. Only styled parts have one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Span {
+    /// Byte range of the styled text within the block's `text`.
+    pub range: std::ops::Range<usize>,
+    /// Font color as six hex digits, e.g. `FF0000`.
+    pub color: Option<String>,
+    /// Highlight color name, e.g. `yellow`.
+    pub highlight: Option<String>,
+}
+
+/// Reads a document block by block.
+///
+/// ```no_run
+/// use batdoc::{Block, DocumentReader};
+///
+/// let mut reader = DocumentReader::open("report.docx")?;
+/// while let Some(block) = reader.next_block() {
+///     if let Block::Heading { text, .. } = block {
+///         println!("{text}");
+///     }
+/// }
+/// # Ok::<(), batdoc::BatdocError>(())
+/// ```
+///
+/// `DocumentReader` is also an [`Iterator`] over [`Block`]s. A `.doc` is
+/// read whole before its first block; other formats are read a batch of
+/// blocks at a time, so an error partway through the document ends it
+/// early, and [`error`](Self::error) says why.
+#[derive(Debug)]
+pub struct DocumentReader {
+    format: Format,
+    /// What is left of the batch being handed out.
+    batch: std::vec::IntoIter<Block>,
+    /// Batches from the parsing thread, until it has finished.
+    batches: Option<mpsc::Receiver<Result<Vec<Block>>>>,
+    thread: Option<JoinHandle<()>>,
+    cancel: CancelToken,
+    error: Option<BatdocError>,
+}
+
+impl DocumentReader {
+    /// Read a document from memory, detecting its format from magic bytes.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the format is not recognized, or the document is encrypted
+    /// or malformed.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Self::from_bytes_with_cancel(data, &CancelToken::new())
+    }
+
+    /// Like [`from_bytes`](Self::from_bytes), but stops with
+    /// [`BatdocError::Cancelled`](crate::BatdocError::Cancelled) soon after
+    /// `cancel` is cancelled from another thread. The reader checks it
+    /// before each batch, and the parser as it reads spreadsheet records
+    /// and rows and PDF pages. Once the reader has been made, a
+    /// cancellation ends the document, and [`error`](Self::error) is
+    /// `Cancelled`.
+    ///
+    /// # Errors
+    ///
+    /// As for [`from_bytes`](Self::from_bytes), or `Cancelled`.
+    pub fn from_bytes_with_cancel(data: &[u8], cancel: &CancelToken) -> Result<Self> {
+        cancel.check()?;
+        let format = detect_format(data)?;
+        let data = data.to_vec(); // owned copy for the parsing thread
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let thread_cancel = cancel.clone();
+        let thread = std::thread::spawn(move || {
+            let opts = Options {
+                cancel: thread_cancel,
+                ..Options::default()
+            };
+            let result = stream_blocks(&data, format, &opts, &mut |blocks| {
+                // The reader has been dropped
+                sender.send(Ok(blocks)).map_err(|_| BatdocError::Cancelled)
+            });
+            if let Err(e) = result {
+                let _ = sender.send(Err(e));
+            }
+        });
+        let mut reader = Self {
+            format,
+            batch: Vec::new().into_iter(),
+            batches: Some(receiver),
+            thread: Some(thread),
+            cancel: cancel.clone(),
+            error: None,
+        };
+        // Errors before the first batch, such as a malformed or encrypted
+        // document, are the reader's own
+        match reader.fetch()? {
+            Some(batch) => reader.batch = batch.into_iter(),
+            None => reader.batches = None,
+        }
+        Ok(reader)
+    }
+
+    /// Read the document at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be read, or for any reason
+    /// [`from_bytes`](Self::from_bytes) fails.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let data = std::fs::read(path)?;
+        Self::from_bytes(&data)
+    }
+
+    /// The detected document format.
+    #[must_use]
+    pub const fn format(&self) -> Format {
+        self.format
+    }
+
+    /// The next block, or `None` at the end of the document.
+    pub fn next_block(&mut self) -> Option<Block> {
+        loop {
+            if let Some(block) = self.batch.next() {
+                return Some(block);
+            }
+            match self.fetch() {
+                Ok(Some(batch)) => self.batch = batch.into_iter(),
+                end => {
+                    // Dropping the receiver stops the parsing thread
+                    self.batches = None;
+                    self.error = end.err();
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// The error that ended the document early, if any, such as a
+    /// cancellation or a part of the document that could not be parsed.
+    #[must_use]
+    pub const fn error(&self) -> Option<&BatdocError> {
+        self.error.as_ref()
+    }
+
+    /// The next batch of blocks from the parsing thread, or `None` once it
+    /// has finished.
+    fn fetch(&mut self) -> Result<Option<Vec<Block>>> {
+        let Some(batches) = &self.batches else {
+            return Ok(None);
+        };
+        self.cancel.check()?;
+        if let Ok(next) = batches.recv() {
+            return next.map(Some);
+        }
+        match self.thread.take().map(JoinHandle::join) {
+            Some(Err(_)) => Err(BatdocError::Document(
+                "extraction panicked (malformed document)".into(),
+            )),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Iterator for DocumentReader {
+    type Item = Block;
+
+    fn next(&mut self) -> Option<Block> {
+        self.next_block()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.batch.len();
+        (left, self.batches.is_none().then_some(left))
+    }
+}
+
+/// Parse `data` as `format` into blocks.
+pub(crate) fn extract_blocks(data: &[u8], format: Format, opts: &Options) -> Result<Vec<Block>> {
+    match format {
+        Format::Doc => doc::extract_blocks(data, opts),
+        Format::Xls => xls::extract_sheets(data, opts).map(sheet_blocks),
+        Format::Docx => docx::extract_blocks(data, opts),
+        Format::Xlsx => xlsx::extract_sheets(data, opts).map(sheet_blocks),
+        Format::Pptx => pptx::extract_blocks(data),
+        Format::Pdf => pdf::extract_blocks(data, opts),
+    }
+}
+
+/// Parse `data` as `format`, handing its blocks to `emit` a batch at a
+/// time as [`DocumentReader`] describes. A `.doc` is one batch.
+pub(crate) fn stream_blocks(
+    data: &[u8],
+    format: Format,
+    opts: &Options,
+    emit: &mut dyn FnMut(Vec<Block>) -> Result<()>,
+) -> Result<()> {
+    match format {
+        Format::Doc => emit(doc::extract_blocks(data, opts)?),
+        Format::Xls => xls::stream_sheets(data, opts, &mut |sheet| emit_sheet(sheet, emit)),
+        Format::Docx => docx::stream_blocks(data, opts, emit),
+        Format::Xlsx => xlsx::stream_sheets(data, opts, &mut |sheet| emit_sheet(sheet, emit)),
+        Format::Pptx => pptx::stream_blocks(data, emit),
+        Format::Pdf => pdf::stream_blocks(data, opts, emit),
+    }
+}
+
+/// Hand the table of a sheet to `emit`, unless the sheet is empty.
+fn emit_sheet(sheet: Sheet, emit: &mut dyn FnMut(Vec<Block>) -> Result<()>) -> Result<()> {
+    let blocks = sheet_blocks(vec![sheet]);
+    if blocks.is_empty() {
+        return Ok(());
+    }
+    emit(blocks)
+}
+
+/// One table per non-empty sheet.
+fn sheet_blocks(sheets: Vec<Sheet>) -> Vec<Block> {
+    sheets
+        .into_iter()
+        .filter(|s| !crate::sheet::skip_empty_sheet(s))
+        .map(|s| Block::Table {
+            name: Some(s.name),
+            rows: s.rows,
+        })
+        .collect()
+}
+
+/// Split plain text into paragraphs at blank lines.
+pub(crate) fn text_blocks(text: &str, out: &mut Vec<Block>) {
+    for para in text.split("\n\n") {
+        let para = para.trim_matches('\n');
+        if !para.trim().is_empty() {
+            out.push(Block::Paragraph {
+                text: para.into(),
+                spans: Vec::new(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── text_blocks ──────────────────────────────────────────────
+
+    #[test]
+    fn text_blocks_split_on_blank_lines() {
+        let mut out = Vec::new();
+        text_blocks("one\ntwo\n\n\nthree\n", &mut out);
+        assert_eq!(
+            out,
+            vec![
+                Block::Paragraph {
+                    text: "one\ntwo".into(),
+                    spans: Vec::new(),
+                },
+                Block::Paragraph {
+                    text: "three".into(),
+                    spans: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    // ── DocumentReader ───────────────────────────────────────────
+
+    #[test]
+    fn cancelled_before_start() {
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let err = DocumentReader::from_bytes_with_cancel(b"%PDF-1.4", &cancel).unwrap_err();
+        assert_eq!(err.code(), "cancelled");
+    }
+
+    #[test]
+    fn rejects_unknown_format() {
+        let err = DocumentReader::from_bytes(b"hello").unwrap_err();
+        assert_eq!(err.code(), "unsupported");
+    }
+
+    fn zip<N: AsRef<str>>(parts: &[(N, String)]) -> Vec<u8> {
+        use std::io::Write;
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, xml) in parts {
+            zip.start_file(name.as_ref(), zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(xml.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    /// A workbook of sheets `One` to `Four`, each with its name in A1.
+    fn four_sheets() -> Vec<u8> {
+        use std::fmt::Write;
+        let ns = r#"xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships""#;
+        let (mut sheets, mut rels, mut parts) = (String::new(), String::new(), Vec::new());
+        for (i, name) in ["One", "Two", "Three", "Four"].iter().enumerate() {
+            let i = i + 1;
+            write!(
+                sheets,
+                r#"<sheet name="{name}" sheetId="{i}" r:id="rId{i}"/>"#
+            )
+            .unwrap();
+            write!(
+                rels,
+                r#"<Relationship Id="rId{i}" Target="worksheets/sheet{i}.xml"/>"#
+            )
+            .unwrap();
+            parts.push((
+                format!("xl/worksheets/sheet{i}.xml"),
+                format!(r#"<worksheet {ns}><sheetData><row r="1"><c r="A1" t="inlineStr"><is><t>{name}</t></is></c></row></sheetData></worksheet>"#),
+            ));
+        }
+        parts.push((
+            "xl/workbook.xml".into(),
+            format!("<workbook {ns}><sheets>{sheets}</sheets></workbook>"),
+        ));
+        parts.push((
+            "xl/_rels/workbook.xml.rels".into(),
+            format!("<Relationships>{rels}</Relationships>"),
+        ));
+        zip(&parts)
+    }
+
+    #[test]
+    fn reads_a_sheet_at_a_time() {
+        let data = four_sheets();
+        let mut batches = Vec::new();
+        stream_blocks(&data, Format::Xlsx, &Options::default(), &mut |blocks| {
+            batches.push(blocks.len());
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(batches, [1, 1, 1, 1]);
+
+        let reader = DocumentReader::from_bytes(&data).unwrap();
+        assert_eq!(reader.size_hint(), (1, None));
+        let blocks: Vec<Block> = reader.collect();
+        assert_eq!(
+            blocks,
+            extract_blocks(&data, Format::Xlsx, &Options::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn cancelled_partway() {
+        let cancel = CancelToken::new();
+        let mut reader = DocumentReader::from_bytes_with_cancel(&four_sheets(), &cancel).unwrap();
+        assert!(matches!(
+            reader.next_block(),
+            Some(Block::Table { name: Some(name), .. }) if name == "One"
+        ));
+        cancel.cancel();
+        // The sheets after the first are never handed out
+        assert_eq!(reader.next_block(), None);
+        assert_eq!(reader.error().unwrap().code(), "cancelled");
+        assert_eq!(reader.next_block(), None);
+    }
+
+    #[test]
+    fn docx_read_a_paragraph_at_a_time() {
+        let w = r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main""#;
+        let body = r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Title</w:t></w:r></w:p><w:p><w:r><w:t>First</w:t></w:r></w:p><w:tbl><w:tr><w:tc><w:p><w:r><w:t>cell</w:t></w:r></w:p></w:tc></w:tr></w:tbl><w:p><w:r><w:t>H</w:t></w:r><w:r><w:rPr><w:vertAlign w:val="subscript"/></w:rPr><w:t>2</w:t></w:r><w:r><w:t>O</w:t></w:r></w:p>"#;
+        let data = zip(&[(
+            "word/document.xml",
+            format!("<w:document {w}><w:body>{body}</w:body></w:document>"),
+        )]);
+        let mut batches = 0;
+        stream_blocks(&data, Format::Docx, &Options::default(), &mut |_| {
+            batches += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(batches, 4);
+        let blocks: Vec<Block> = DocumentReader::from_bytes(&data).unwrap().collect();
+        assert_eq!(
+            blocks,
+            extract_blocks(&data, Format::Docx, &Options::default()).unwrap()
+        );
+        assert_eq!(blocks.len(), 4);
+    }
+
+    #[test]
+    fn docx_content_controls_across_batches() {
+        let w = r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main""#;
+        let para = |text: &str| format!("<w:p><w:r><w:t>{text}</w:t></w:r></w:p>");
+        let control = |content: &str| {
+            format!(
+                r#"<w:sdt><w:sdtPr><w:alias w:val="Box"/></w:sdtPr><w:sdtContent>{content}</w:sdtContent></w:sdt>"#
+            )
+        };
+        // Its paragraphs are left out, and only its own
+        let placeholder = |content: &str| {
+            format!(
+                r#"<w:sdt><w:sdtPr><w:showingPlcHdr/></w:sdtPr><w:sdtContent>{content}</w:sdtContent></w:sdt>"#
+            )
+        };
+        let body = [
+            para("before"),
+            control(&[para("outer"), control(&para("inner")), para("outer again")].concat()),
+            control(&placeholder(&para("Click here to enter text."))),
+            control(&control(&control(&para("deep")))),
+            para("after"),
+        ]
+        .concat();
+        let data = zip(&[(
+            "word/document.xml",
+            format!("<w:document {w}><w:body>{body}</w:body></w:document>"),
+        )]);
+        let whole = extract_blocks(&data, Format::Docx, &Options::default()).unwrap();
+        assert_eq!(whole.len(), 6);
+        let mut reader = DocumentReader::from_bytes(&data).unwrap();
+        let mut streamed = Vec::new();
+        while let Some(block) = reader.next_block() {
+            streamed.push(block);
+        }
+        assert!(reader.error().is_none());
+        assert_eq!(streamed, whole);
+    }
+}
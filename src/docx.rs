@@ -3,18 +3,37 @@
 //! Unzips the `.docx` archive, parses `word/document.xml` with `quick-xml`
 //! into structured [`Block`] types (paragraphs with heading/list styles and
 //! runs with bold/italic/hyperlink, tables with rows and cells), then renders
-//! to either plain text or markdown.
+//! to either plain text or markdown. Reviewers' comments from
+//! `word/comments.xml` are shown inline or at the end with `--comments`.
+//! Tracked changes are accepted, rejected, or marked up with `--revisions`.
+//! The text of `SmartArt` diagrams becomes nested lists, the data of
+//! charts becomes tables, and equations become LaTeX. A very large
+//! document can instead be streamed: parsed, resolved, and written a
+//! batch of blocks at a time.
 
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
-use std::io::{Cursor, Read};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
 use zip::ZipArchive;
 
+use crate::chart::Chart;
+use crate::document;
+use crate::embedded;
+use crate::fields;
+use crate::format::detect_format;
+use crate::html;
 use crate::markup;
-use crate::xml_util::{self, get_attr, Rels};
+use crate::metadata::{self, Metadata};
+use crate::numbering::Numbering;
+use crate::omml;
+use crate::options::{Budget, Comments, Marks, Options, Revisions};
+use crate::smartart;
+use crate::symbols;
+use crate::xml_util::{self, get_attr, unescaped_attr, Rels};
 
 /// Extracted document structure for rich output.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Block {
     Paragraph {
         style: ParaStyle,
@@ -23,25 +42,236 @@ enum Block {
     Table {
         rows: Vec<Row>,
     }, // rows -> cells -> blocks
-    /// An embedded image, stored as a base64 data URI markdown tag.
+    /// An image: with `--images`, its ZIP path until `resolve_images`
+    /// makes it a reference to a base64 data URI; otherwise shown by its
+    /// alternative text alone.
     Image {
         markdown: String,
+        /// `descr` or `title` of `<wp:docPr>`, else the caption below the
+        /// image, else its name (`Picture 3`).
+        alt: String,
+        /// Whether `alt` is the author's description rather than the
+        /// image's name, which a caption replaces.
+        described: bool,
+    },
+    /// A comment shown after the paragraph it is anchored to
+    /// (`--comments inline`).
+    Comment {
+        author: String,
+        text: String,
+    },
+    /// A diagram, chart, or imported chunk, replaced with its text after
+    /// parsing.
+    Graphic(Graphic),
+}
+
+/// Content kept in a part of its own — a `SmartArt` diagram or chart in
+/// a drawing, or a chunk of another format — by the relationship ID of
+/// the part holding its data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Graphic {
+    /// `<dgm:relIds r:dm="..">`
+    Diagram(String),
+    /// `<c:chart r:id="..">`
+    Chart(String),
+    /// `<w:altChunk r:id="..">`: HTML, MHT, or plain text that Word
+    /// imports when the document is opened.
+    Chunk(String),
+    /// `<o:OLEObject r:id=".." ProgID="..">` in a `<w:object>`: an
+    /// embedded worksheet, document, or attached file.
+    Object {
+        rid: String,
+        prog_id: Option<String>,
     },
 }
 
+impl Graphic {
+    /// The graphic an empty element in a `<a:graphicData>` refers to.
+    fn from_element(e: &quick_xml::events::BytesStart) -> Option<Self> {
+        match e.local_name().as_ref() {
+            b"relIds" => get_attr(e, b"r:dm").map(Self::Diagram),
+            b"chart" => get_attr(e, b"r:id").map(Self::Chart),
+            b"altChunk" => get_attr(e, b"r:id").map(Self::Chunk),
+            b"OLEObject" => get_attr(e, b"r:id").map(|rid| Self::Object {
+                rid,
+                prog_id: get_attr(e, b"ProgID"),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Block {
+    /// Bytes of text in the block, including nested table cells.
+    fn text_len(&self) -> usize {
+        match self {
+            Self::Paragraph { runs, .. } => runs.iter().map(|r| r.text.len()).sum(),
+            Self::Table { rows } => rows.iter().flatten().flatten().map(Self::text_len).sum(),
+            Self::Image { alt, .. } => alt.len(),
+            Self::Graphic(_) => 0,
+            Self::Comment { author, text } => author.len() + text.len(),
+        }
+    }
+
+    /// The fewest lines of output the block makes: one per paragraph or
+    /// table row with text.
+    fn lines(&self) -> usize {
+        match self {
+            Self::Paragraph { runs, .. } => {
+                usize::from(runs.iter().any(|r| !r.text.trim().is_empty()))
+            }
+            Self::Table { rows } => rows
+                .iter()
+                .filter(|row| row.iter().flatten().any(|b| b.lines() > 0))
+                .count(),
+            Self::Graphic(_) => 0,
+            Self::Image { .. } | Self::Comment { .. } => 1,
+        }
+    }
+}
+
+#[allow(clippy::struct_excessive_bools)] // independent paragraph properties
 #[derive(Debug, Clone, Default)]
 struct ParaStyle {
     heading_level: u8, // 0 = normal, 1-9 = heading
+    /// `w:pStyle`, resolved against `word/styles.xml` after parsing.
+    style_id: Option<String>,
     list_level: Option<u8>,
+    /// `w:numId` of the list the paragraph is an item of.
+    num_id: Option<String>,
+    /// The item's number as Word shows it (`1.`, `a)`, `2.1`), resolved
+    /// from `word/numbering.xml`; `None` for bullets.
+    list_label: Option<String>,
+    /// Columns the item is indented by in markdown, to line up under
+    /// its parent's text; two per level if `None`.
+    list_indent: Option<usize>,
+    /// Bookmarks starting in the paragraph; after parsing, only those an
+    /// internal link points at, which become anchors in markdown.
+    bookmarks: Vec<String>,
+    /// `<w:pageBreakBefore/>`: the paragraph starts a new page.
+    page_break_before: bool,
+    /// A `<w:sectPr>` in the paragraph's properties: a section ends with it.
+    section_end: bool,
+    /// Set for a caption (`Caption` style), whose text describes the
+    /// image before it.
+    caption: bool,
+    /// Set for a paragraph in a code style (`Code`, `HTML Preformatted`, or
+    /// any in a monospace font), or, after `resolve_code_blocks`, for the
+    /// code block a stretch of code paragraphs is joined into.
+    code: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)] // independent formatting flags
 struct Run {
     text: String,
     bold: bool,
     italic: bool,
+    /// `<w:strike/>` or `<w:dstrike/>`.
+    strike: bool,
+    /// `<w:u>` of any style but `none`.
+    underline: bool,
+    /// `<w:vertAlign>`, unless on the baseline.
+    vert_align: Option<VertAlign>,
     /// If this run is inside a hyperlink, the resolved URL.
     link_url: Option<String>,
+    /// Set for a checkbox form field; the text is `[x]` or `[ ]`.
+    checkbox: bool,
+    /// Instruction of a document-property field whose cached result is
+    /// this run's text; resolved against metadata after parsing.
+    field: Option<String>,
+    /// Font color (`<w:color w:val="FF0000"/>`), unless automatic.
+    color: Option<String>,
+    /// Highlight color name (`<w:highlight w:val="yellow"/>`).
+    highlight: Option<String>,
+    /// Set for the empty runs marking where a comment's range starts
+    /// and ends.
+    comment: Option<CommentMark>,
+    /// Set for text inserted or deleted with track changes on.
+    revision: Option<Revision>,
+    /// Set for the runs naming a content control, kept with
+    /// `--control-labels`.
+    label: bool,
+    /// Set for text in a monospace font or a code character style.
+    code: bool,
+}
+
+/// Text raised or lowered from the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VertAlign {
+    Superscript,
+    Subscript,
+}
+
+/// A tracked change a run is part of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Revision {
+    /// In `<w:ins>` or `<w:moveTo>`.
+    Inserted,
+    /// In `<w:del>` or `<w:moveFrom>`, with its text in `<w:delText>`.
+    Deleted,
+}
+
+/// Where a comment is anchored, by its `w:id`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CommentMark {
+    /// `<w:commentRangeStart>`.
+    Start(String),
+    /// `<w:commentRangeEnd>` or `<w:commentReference>`, whichever comes
+    /// first.
+    End(String),
+}
+
+impl Run {
+    /// A run of unformatted text.
+    fn plain(text: &str) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    /// A copy of this run, formatting and all, with other text.
+    fn with_text(&self, text: &str) -> Self {
+        Self {
+            text: text.into(),
+            ..self.clone()
+        }
+    }
+
+    /// A checkbox form field rendered as `[x]` / `[ ]`.
+    fn checkbox(checked: bool) -> Self {
+        Self {
+            text: if checked { "[x]" } else { "[ ]" }.into(),
+            checkbox: true,
+            ..Self::default()
+        }
+    }
+
+    /// The runs naming a content control before its text: `Title:` in
+    /// bold, then a space.
+    fn control_label(label: String) -> [Self; 2] {
+        [
+            Self {
+                text: label + ":",
+                bold: true,
+                label: true,
+                ..Self::default()
+            },
+            Self {
+                label: true,
+                ..Self::plain(" ")
+            },
+        ]
+    }
+
+    /// An empty run marking the start or end of a comment's range.
+    fn comment_mark(mark: CommentMark) -> Self {
+        Self {
+            comment: Some(mark),
+            ..Self::default()
+        }
+    }
 }
 
 /// A single table cell containing blocks.
@@ -50,101 +280,440 @@ type Cell = Vec<Block>;
 type Row = Vec<Cell>;
 
 /// Extract plain text from a .docx file.
-pub(crate) fn extract_plain(data: &[u8]) -> crate::error::Result<String> {
-    let (blocks, _) = parse_docx(data, false)?;
-    Ok(render_plain(&blocks))
+///
+/// With `opts.table`, tables are drawn as box tables instead of
+/// tab-separated rows.
+pub(crate) fn extract_plain(data: &[u8], opts: &Options) -> crate::error::Result<String> {
+    let (mut blocks, _) = parse_docx(data, false, opts, opts.budget)?;
+    resolve_scripts(&mut blocks);
+    Ok(render_plain(&blocks, opts.table))
+}
+
+/// Extract blocks from a .docx file for the library API.
+pub(crate) fn extract_blocks(
+    data: &[u8],
+    opts: &Options,
+) -> crate::error::Result<Vec<document::Block>> {
+    let (mut blocks, _) = parse_docx(data, false, opts, Budget::default())?;
+    resolve_scripts(&mut blocks);
+    Ok(blocks.iter().filter_map(to_document_block).collect())
+}
+
+/// [`extract_blocks`], handing the blocks to `emit` a top-level element
+/// (paragraph, table) at a time as the body is read.
+pub(crate) fn stream_blocks(
+    data: &[u8],
+    opts: &Options,
+    emit: &mut dyn FnMut(Vec<document::Block>) -> crate::error::Result<()>,
+) -> crate::error::Result<()> {
+    stream_docx(data, false, opts, 1, &mut |mut blocks| {
+        resolve_scripts(&mut blocks);
+        let blocks: Vec<_> = blocks.iter().filter_map(to_document_block).collect();
+        if blocks.is_empty() {
+            return Ok(());
+        }
+        emit(blocks)
+    })?;
+    Ok(())
 }
 
 /// Extract markdown-formatted text from a .docx file.
 ///
-/// When `images` is true, embedded images are extracted and included as
+/// When `opts.images` is true, embedded images are extracted and included as
 /// reference-style base64 images: `![][imageN]` inline with definitions
 /// appended at the end of the document.
-pub(crate) fn extract_markdown(data: &[u8], images: bool) -> crate::error::Result<String> {
-    let (blocks, image_defs) = parse_docx(data, images)?;
+pub(crate) fn extract_markdown(data: &[u8], opts: &Options) -> crate::error::Result<String> {
+    let (mut blocks, image_defs) = parse_docx(data, opts.images, opts, opts.budget)?;
+    resolve_marks(&mut blocks, opts.marks);
     let mut md = render_markdown(&blocks);
-    if !image_defs.is_empty() {
-        for def in &image_defs {
-            md.push_str(def);
-            md.push('\n');
-        }
-    }
+    push_image_defs(&mut md, &image_defs);
     Ok(md)
 }
 
+/// Append the definitions of the images referenced in the markdown.
+fn push_image_defs(md: &mut String, image_defs: &[String]) {
+    for def in image_defs {
+        md.push_str(def);
+        md.push('\n');
+    }
+}
+
+/// Whether the main document part of a .docx is large enough that it is
+/// better written as it is read ([`stream_plain`], [`stream_markdown`])
+/// than extracted whole.
+pub(crate) fn is_large(data: &[u8]) -> bool {
+    let Ok(mut archive) = ZipArchive::new(Cursor::new(data)) else {
+        return false;
+    };
+    let main = MainPart::locate(&mut archive);
+    archive
+        .by_name(&main.path)
+        .is_ok_and(|part| part.size() >= STREAM_MIN_BYTES)
+}
+
+/// Write the plain text of a .docx to `out` as [`extract_plain`] would
+/// return it, a batch of blocks at a time, so that the whole document is
+/// never held at once.
+pub(crate) fn stream_plain(
+    data: &[u8],
+    opts: &Options,
+    out: &mut dyn Write,
+) -> crate::error::Result<()> {
+    write_plain(data, opts, STREAM_BATCH_BYTES, out)
+}
+
+/// Write the markdown of a .docx to `out` as [`extract_markdown`] would
+/// return it, a batch of blocks at a time.
+pub(crate) fn stream_markdown(
+    data: &[u8],
+    opts: &Options,
+    out: &mut dyn Write,
+) -> crate::error::Result<()> {
+    write_markdown(data, opts, STREAM_BATCH_BYTES, out)
+}
+
+/// [`stream_plain`], parsing `batch` bytes of body XML at a time.
+fn write_plain(
+    data: &[u8],
+    opts: &Options,
+    batch: usize,
+    out: &mut dyn Write,
+) -> crate::error::Result<()> {
+    let mut text = String::new();
+    let mut first = true;
+    stream_docx(data, false, opts, batch, &mut |mut blocks| {
+        resolve_scripts(&mut blocks);
+        render_plain_into(&blocks, opts.table, &mut text, &mut first);
+        out.write_all(text.as_bytes())?;
+        text.clear();
+        Ok(())
+    })?;
+    Ok(())
+}
+
+/// [`stream_markdown`], parsing `batch` bytes of body XML at a time.
+fn write_markdown(
+    data: &[u8],
+    opts: &Options,
+    batch: usize,
+    out: &mut dyn Write,
+) -> crate::error::Result<()> {
+    let mut md = String::new();
+    let image_defs = stream_docx(data, opts.images, opts, batch, &mut |mut blocks| {
+        resolve_marks(&mut blocks, opts.marks);
+        for block in &blocks {
+            render_block_markdown(block, &mut md);
+        }
+        // The last line break says how the next block starts (see `end_list`)
+        let keep = md.char_indices().rev().nth(1).map_or(0, |(i, _)| i);
+        out.write_all(&md.as_bytes()[..keep])?;
+        md.drain(..keep);
+        Ok(())
+    })?;
+    push_image_defs(&mut md, &image_defs);
+    out.write_all(md.as_bytes())?;
+    Ok(())
+}
+
 /// Parse the docx XML into structured blocks and image reference definitions.
 ///
 /// When `images` is true, image relationships are loaded and `<w:drawing>`
 /// elements are extracted as `Block::Image` entries with inline references.
 /// The second element of the tuple contains the reference definitions to
-/// append at the end of the document.
-fn parse_docx(data: &[u8], images: bool) -> crate::error::Result<(Vec<Block>, Vec<String>)> {
+/// append at the end of the document. Tracked changes and comments are
+/// handled as `opts.revisions` and `opts.comments` say. Parsing stops
+/// early once `budget` is spent.
+fn parse_docx(
+    data: &[u8],
+    images: bool,
+    opts: &Options,
+    mut budget: Budget,
+) -> crate::error::Result<(Vec<Block>, Vec<String>)> {
     let cursor = Cursor::new(data);
     let mut archive = ZipArchive::new(cursor)?;
-
-    // Load hyperlink relationships (rId → URL)
-    let rels = xml_util::load_rels(&mut archive, "word/_rels/document.xml.rels");
-
-    // Optionally load image relationships
-    let image_rels = if images {
-        xml_util::load_image_rels(&mut archive, "word/_rels/document.xml.rels")
-    } else {
-        xml_util::Rels::new()
-    };
+    let main = MainPart::locate(&mut archive);
+    let (rels, image_rels) = main.link_rels(&mut archive, images);
 
     let mut xml = String::new();
-    archive
-        .by_name("word/document.xml")?
-        .read_to_string(&mut xml)?;
+    archive.by_name(&main.path)?.read_to_string(&mut xml)?;
 
     let mut reader = Reader::from_str(&xml);
     let mut blocks = Vec::new();
     let mut in_body = false;
 
-    parse_body(&mut reader, &mut blocks, &mut in_body, &rels, &image_rels);
+    parse_body(
+        &mut reader,
+        &mut blocks,
+        &mut in_body,
+        &rels,
+        &image_rels,
+        &mut budget,
+        &mut BodyState::default(),
+    );
+    let mut resolver = Resolver::new(data, &main, opts, images)?;
+    let blocks = resolver.resolve(blocks);
+    let mut blocks = resolver.settle(blocks);
+    blocks.append(&mut resolver.finish());
+    Ok((blocks, resolver.image_defs))
+}
 
-    // If images enabled, resolve image blocks by reading from the archive
-    let image_defs = if images {
-        let cursor = Cursor::new(data);
-        let mut archive = ZipArchive::new(cursor)?;
-        resolve_images(&mut blocks, &mut archive)
-    } else {
-        Vec::new()
-    };
+/// The passes that resolve parsed blocks against the rest of the package
+/// (styles, numbering, comments, related parts), with what they carry
+/// from one batch of blocks to the next when a document is streamed.
+struct Resolver<'a> {
+    archive: ZipArchive<Cursor<&'a [u8]>>,
+    opts: &'a Options,
+    graphic_rels: Rels,
+    styles: Option<Styles>,
+    meta: Metadata,
+    numbering: Option<Numbering>,
+    /// Marker widths of the open list levels.
+    widths: Vec<usize>,
+    /// Whether the blocks so far end with a page separator, or there are
+    /// none yet.
+    after_separator: bool,
+    /// The bookmarks internal links point at, found in the first batch
+    /// unless known beforehand.
+    targets: Option<HashSet<String>>,
+    /// Bookmarks waiting for a paragraph with text.
+    bookmarks: Vec<String>,
+    comments: Option<CommentPlacer>,
+    /// Whether images are embedded, how many so far, and their reference
+    /// definitions.
+    images: bool,
+    image_count: usize,
+    image_defs: Vec<String>,
+}
+
+impl<'a> Resolver<'a> {
+    fn new(
+        data: &'a [u8],
+        main: &MainPart,
+        opts: &'a Options,
+        images: bool,
+    ) -> crate::error::Result<Self> {
+        let mut archive = ZipArchive::new(Cursor::new(data))?;
+        let mut graphic_rels = Rels::new();
+        for rel_type in [
+            "/diagramData",
+            "/chart",
+            "/aFChunk",
+            "/oleObject",
+            "/package",
+        ] {
+            graphic_rels.extend(main.typed_rels(&mut archive, rel_type));
+        }
+        let styles = main
+            .read_related(&mut archive, "/styles")?
+            .map(|xml| Styles::parse(&xml));
+        let meta = metadata::from_ooxml(&mut archive);
+        let numbering = main
+            .read_related(&mut archive, "/numbering")?
+            .map(|xml| Numbering::parse(&xml));
+        let comments = if opts.comments == Comments::None {
+            None
+        } else {
+            let xml = main
+                .read_related(&mut archive, "/comments")?
+                .unwrap_or_default();
+            Some(CommentPlacer::new(parse_comments(&xml), opts.comments))
+        };
+        Ok(Self {
+            archive,
+            opts,
+            graphic_rels,
+            styles,
+            meta,
+            numbering,
+            widths: Vec::new(),
+            after_separator: true,
+            targets: None,
+            bookmarks: Vec::new(),
+            comments,
+            images,
+            image_count: 0,
+            image_defs: Vec::new(),
+        })
+    }
+
+    /// Resolve the next batch of parsed blocks up to the numbering.
+    fn resolve(&mut self, mut blocks: Vec<Block>) -> Vec<Block> {
+        resolve_graphics(
+            &mut blocks,
+            &self.graphic_rels,
+            &mut self.archive,
+            self.opts,
+        );
+        let mut blocks = resolve_page_breaks(
+            blocks,
+            self.opts.page_breaks.as_deref(),
+            &mut self.after_separator,
+        );
+        resolve_revisions(&mut blocks, self.opts.revisions);
+        if !self.opts.control_labels {
+            drop_control_labels(&mut blocks);
+        }
+        let targets = self
+            .targets
+            .get_or_insert_with(|| bookmark_targets(&blocks));
+        link_bookmarks(&mut blocks, targets, &mut self.bookmarks);
+        if let Some(styles) = &self.styles {
+            resolve_styles(&mut blocks, styles);
+        }
+        resolve_fields(&mut blocks, &self.meta);
+        if let Some(numbering) = &mut self.numbering {
+            resolve_numbering(&mut blocks, numbering, &mut self.widths);
+        }
+        blocks
+    }
+
+    /// Finish resolved blocks that nothing after them can change (see
+    /// [`open_tail`]): captions, code blocks, comments, and images.
+    fn settle(&mut self, mut blocks: Vec<Block>) -> Vec<Block> {
+        resolve_captions(&mut blocks);
+        let mut blocks = resolve_code_blocks(blocks);
+        if let Some(placer) = &mut self.comments {
+            blocks = placer.place(blocks);
+        }
+        if self.images {
+            let defs = resolve_images(&mut blocks, &mut self.archive, &mut self.image_count);
+            self.image_defs.extend(defs);
+        }
+        blocks
+    }
+
+    /// The blocks that end the document: the `--comments end` section.
+    fn finish(&mut self) -> Vec<Block> {
+        self.comments
+            .as_mut()
+            .map(CommentPlacer::appendix)
+            .unwrap_or_default()
+    }
+}
+
+/// The main document part, `word/document.xml` unless the package's
+/// `officeDocument` relationship names another.
+struct MainPart {
+    path: String,
+    /// Its relationships part, `word/_rels/document.xml.rels`.
+    rels: String,
+    /// The directory relationship targets are relative to, `word`.
+    dir: String,
+}
+
+impl MainPart {
+    fn locate(archive: &mut ZipArchive<Cursor<&[u8]>>) -> Self {
+        let path = xml_util::main_part(archive)
+            .filter(|path| archive.index_for_name(path).is_some())
+            .unwrap_or_else(|| "word/document.xml".to_string());
+        let dir = path.rsplit_once('/').map_or("", |(dir, _)| dir).to_string();
+        Self {
+            rels: xml_util::rels_path(&path),
+            path,
+            dir,
+        }
+    }
+
+    /// The part's hyperlink relationships (rId → URL), and its image
+    /// relationships if `images` are wanted.
+    fn link_rels(&self, archive: &mut ZipArchive<Cursor<&[u8]>>, images: bool) -> (Rels, Rels) {
+        let rels = xml_util::load_rels(archive, &self.rels);
+        let image_rels = if images {
+            self.typed_rels(archive, "/image")
+        } else {
+            Rels::new()
+        };
+        (rels, image_rels)
+    }
+
+    /// The part's relationships of one type, with the targets resolved to
+    /// paths in the archive.
+    fn typed_rels(&self, archive: &mut ZipArchive<Cursor<&[u8]>>, rel_type: &str) -> Rels {
+        let mut rels = xml_util::load_typed_rels(archive, &self.rels, rel_type);
+        for target in rels.values_mut() {
+            *target = xml_util::part_path(&self.dir, target);
+        }
+        rels
+    }
+
+    /// Read the part related to this one by `rel_type` (`/styles`), or the
+    /// one where Word puts it when there is no such relationship. `None` if
+    /// there is no such part.
+    fn read_related(
+        &self,
+        archive: &mut ZipArchive<Cursor<&[u8]>>,
+        rel_type: &str,
+    ) -> crate::error::Result<Option<String>> {
+        let path = self
+            .typed_rels(archive, rel_type)
+            .into_values()
+            .next()
+            .unwrap_or_else(|| xml_util::part_path(&self.dir, &format!("{}.xml", &rel_type[1..])));
+        let Ok(mut part) = archive.by_name(&path) else {
+            return Ok(None);
+        };
+        let mut xml = String::new();
+        part.read_to_string(&mut xml)?;
+        Ok(Some(xml))
+    }
+}
 
-    Ok((blocks, image_defs))
+/// What [`parse_body`] carries from one stretch of the body to the next:
+/// the content controls and fields still open.
+#[derive(Default)]
+struct BodyState {
+    controls: BlockControls,
+    // Fields can span paragraphs, as a table of contents does
+    fields: FieldStack,
 }
 
-/// Walk the XML and collect blocks from the document body.
+/// Walk the XML and collect blocks from the document body, until the
+/// end or until `budget` is spent.
 fn parse_body(
     reader: &mut Reader<&[u8]>,
     blocks: &mut Vec<Block>,
     in_body: &mut bool,
     rels: &Rels,
     image_rels: &Rels,
+    budget: &mut Budget,
+    state: &mut BodyState,
 ) {
-    loop {
+    let BodyState { controls, fields } = state;
+    while !budget.spent() {
         match reader.read_event() {
             Ok(Event::Start(ref e)) => {
                 let name = e.local_name();
                 match name.as_ref() {
                     b"body" => *in_body = true,
+                    b"sdt" if *in_body => controls.depth += 1,
+                    b"sdtPr" if *in_body => controls.read_props(reader),
+                    b"p" if *in_body && controls.showing_placeholder() => {
+                        let _ = reader.read_to_end(e.name());
+                    }
                     b"p" if *in_body => {
-                        let mut para_blocks = parse_paragraph(reader, rels, image_rels);
+                        let mut para_blocks = parse_paragraph(reader, rels, image_rels, fields);
+                        controls.label_first(&mut para_blocks);
+                        budget.spend(
+                            para_blocks.iter().map(Block::text_len).sum(),
+                            para_blocks.iter().map(Block::lines).sum(),
+                        );
                         blocks.append(&mut para_blocks);
                     }
                     b"tbl" if *in_body => {
                         let table = parse_table(reader, rels);
+                        budget.spend(table.text_len(), table.lines());
                         blocks.push(table);
                     }
                     _ => {}
                 }
             }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == b"body" {
-                    *in_body = false;
-                }
+            Ok(Event::Empty(ref e)) if *in_body && e.local_name().as_ref() == b"altChunk" => {
+                blocks.extend(Graphic::from_element(e).map(Block::Graphic));
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"body" => {
+                *in_body = false;
             }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"sdt" => controls.end(),
             Ok(Event::Eof) | Err(_) => break,
             _ => {}
         }
@@ -157,89 +726,203 @@ fn parse_body(
 /// is active, any `<w:drawing>` elements (which live inside `<w:r>` runs)
 /// produce additional `Block::Image` entries. The paragraph is always first,
 /// followed by any images found.
-fn parse_paragraph(reader: &mut Reader<&[u8]>, rels: &Rels, image_rels: &Rels) -> Vec<Block> {
+///
+/// Fields, complex (`<w:fldChar>` begin/separate/end) or simple
+/// (`<w:fldSimple>`), are tracked in `fields` so that form fields can
+/// replace their cached display text, `HYPERLINK` fields become links, and
+/// a table of contents is left out. A paragraph left empty by that is
+/// dropped. Checkbox content controls (`<w14:checkbox>`) likewise replace
+/// their glyph with a checkbox run.
+fn parse_paragraph(
+    reader: &mut Reader<&[u8]>,
+    rels: &Rels,
+    image_rels: &Rels,
+    fields: &mut FieldStack,
+) -> Vec<Block> {
     let mut style = ParaStyle::default();
     let mut runs: Vec<Run> = Vec::new();
     let mut image_blocks: Vec<Block> = Vec::new();
+    let mut skip_sdt_content = false;
+    // Open <w:ins> and <w:del> elements, innermost last
+    let mut revisions: Vec<Revision> = Vec::new();
+    // Inside <m:oMathPara>, whose equations are set on their own line
+    let mut display_math = false;
 
     loop {
         match reader.read_event() {
             Ok(Event::Start(ref e)) => {
                 let name = e.local_name();
+                let first = runs.len();
                 match name.as_ref() {
+                    b"ins" | b"moveTo" => revisions.push(Revision::Inserted),
+                    b"del" | b"moveFrom" => revisions.push(Revision::Deleted),
                     b"pPr" => parse_para_props(reader, &mut style),
-                    b"r" => {
-                        let (run_opt, img_opt) = parse_run(reader, image_rels);
-                        if let Some(run) = run_opt {
-                            runs.push(run);
-                        }
-                        if let Some(blk) = img_opt {
-                            image_blocks.push(blk);
-                        }
-                    }
+                    b"r" => parse_run(reader, image_rels).add_to(
+                        &mut runs,
+                        &mut image_blocks,
+                        fields,
+                        None,
+                        skip_sdt_content,
+                    ),
                     b"hyperlink" => {
-                        // Resolve the hyperlink URL from r:id → rels map
-                        let url = get_attr(e, b"r:id").and_then(|rid| rels.get(&rid).cloned());
+                        let url = hyperlink_url(e, rels);
                         parse_hyperlink_runs(
                             reader,
                             &mut runs,
                             url.as_deref(),
                             image_rels,
                             &mut image_blocks,
+                            fields,
                         );
                     }
+                    b"fldSimple" => {
+                        for mark in simple_field(e) {
+                            fields.apply(mark, &mut runs);
+                        }
+                    }
+                    b"oMathPara" => display_math = true,
+                    b"oMath" => runs.extend(parse_math(reader, display_math)),
+                    b"sdtPr" => {
+                        // The checkbox glyph in <w:sdtContent> is replaced
+                        // by the control's checked state, and placeholder
+                        // text is left out.
+                        let props = parse_sdt_props(reader);
+                        runs.extend(props.label.map(Run::control_label).into_iter().flatten());
+                        if let Some(checked) = props.checkbox {
+                            runs.push(Run::checkbox(checked));
+                        }
+                        skip_sdt_content = props.checkbox.is_some() || props.placeholder;
+                    }
                     _ => {}
                 }
-            }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == b"p" {
-                    break;
+                if let Some(&revision) = revisions.last() {
+                    for run in runs.iter_mut().skip(first) {
+                        if run.comment.is_none() {
+                            run.revision = Some(revision);
+                        }
+                    }
                 }
             }
-            Ok(Event::Empty(ref e)) => {
-                let name = e.local_name();
-                if name.as_ref() == b"tab" {
-                    runs.push(Run {
-                        text: "\t".into(),
-                        bold: false,
-                        italic: false,
-                        link_url: None,
-                    });
-                } else if name.as_ref() == b"br" {
-                    runs.push(Run {
-                        text: "\n".into(),
-                        bold: false,
-                        italic: false,
-                        link_url: None,
-                    });
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"p" => {
+                break;
+            }
+            Ok(Event::End(ref e))
+                if matches!(
+                    e.local_name().as_ref(),
+                    b"ins" | b"del" | b"moveTo" | b"moveFrom"
+                ) =>
+            {
+                revisions.pop();
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"sdtContent" => {
+                skip_sdt_content = false;
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"oMathPara" => {
+                display_math = false;
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"fldSimple" => {
+                fields.apply(FieldMark::End, &mut runs);
+            }
+            Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"bookmarkStart" => {
+                style.bookmarks.extend(get_attr(e, b"w:name"));
+            }
+            Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"fldSimple" => {
+                let [begin, instr, _] = simple_field(e);
+                for mark in [begin, instr, FieldMark::End] {
+                    fields.apply(mark, &mut runs);
                 }
             }
+            Ok(Event::Empty(ref e)) => runs.extend(empty_run(e)),
             Ok(Event::Eof) | Err(_) => break,
             _ => {}
         }
     }
 
+    if fields.end_paragraph(&mut runs) && runs.iter().all(|r| r.text.trim().is_empty()) {
+        return image_blocks;
+    }
+    let runs = resolve_ballot_boxes(runs);
     let mut result = vec![Block::Paragraph { style, runs }];
     result.append(&mut image_blocks);
     result
 }
 
+/// Turn ballot-box characters typed as text (`☐ Yes ☒ No`), as in forms
+/// made without form fields, into checkbox runs, and keep a space between
+/// each checkbox and the text after it.
+fn resolve_ballot_boxes(runs: Vec<Run>) -> Vec<Run> {
+    let mut out: Vec<Run> = Vec::with_capacity(runs.len());
+    for run in runs {
+        if run.comment.is_some() || !run.text.contains(['☐', '☑', '☒']) {
+            out.push(run);
+            continue;
+        }
+        let mut rest = run.text.as_str();
+        while let Some(at) = rest.find(['☐', '☑', '☒']) {
+            if at > 0 {
+                out.push(run.with_text(&rest[..at]));
+            }
+            out.push(Run {
+                revision: run.revision,
+                ..Run::checkbox(!rest[at..].starts_with('☐'))
+            });
+            rest = &rest[at + '☐'.len_utf8()..];
+        }
+        if !rest.is_empty() {
+            out.push(run.with_text(rest));
+        }
+    }
+    let mut i = 0;
+    while i + 1 < out.len() {
+        if out[i].checkbox && !out[i + 1].text.starts_with(char::is_whitespace) {
+            out.insert(i + 1, Run::plain(" "));
+        }
+        i += 1;
+    }
+    out
+}
+
+/// The run for an empty element directly in a paragraph: a tab, a line
+/// break, or the start or end of a comment's range.
+fn empty_run(e: &quick_xml::events::BytesStart) -> Option<Run> {
+    match e.local_name().as_ref() {
+        b"tab" => Some(Run::plain("\t")),
+        b"br" => Some(Run::plain("\n")),
+        b"commentRangeStart" => {
+            get_attr(e, b"w:id").map(|id| Run::comment_mark(CommentMark::Start(id)))
+        }
+        b"commentRangeEnd" => {
+            get_attr(e, b"w:id").map(|id| Run::comment_mark(CommentMark::End(id)))
+        }
+        _ => None,
+    }
+}
+
 /// Parse `<w:pPr>` to extract heading level and list info.
 fn parse_para_props(reader: &mut Reader<&[u8]>, style: &mut ParaStyle) {
     let mut depth = 1u32;
     loop {
         match reader.read_event() {
-            Ok(Event::Start(_)) => {
+            Ok(Event::Start(ref e)) => {
                 depth += 1;
+                if e.local_name().as_ref() == b"sectPr" {
+                    style.section_end = true;
+                }
             }
             Ok(Event::Empty(ref e)) => {
                 let name = e.local_name();
                 match name.as_ref() {
+                    b"pageBreakBefore" => style.page_break_before = is_on(e),
+                    b"sectPr" => style.section_end = true,
                     b"pStyle" => {
                         if let Some(val) = get_val_attr(e) {
+                            // Until styles.xml says otherwise
                             if let Some(level) = parse_heading_level(&val) {
                                 style.heading_level = level;
                             }
+                            style.caption = val.eq_ignore_ascii_case("caption");
+                            style.code = is_code_style(&val);
+                            style.style_id = Some(val);
                         }
                     }
                     b"ilvl" => {
@@ -249,6 +932,7 @@ fn parse_para_props(reader: &mut Reader<&[u8]>, style: &mut ParaStyle) {
                             }
                         }
                     }
+                    b"numId" => style.num_id = get_val_attr(e),
                     _ => {}
                 }
             }
@@ -262,6 +946,213 @@ fn parse_para_props(reader: &mut Reader<&[u8]>, style: &mut ParaStyle) {
             _ => {}
         }
     }
+    // numId 0 takes the paragraph out of a list; a list without a level
+    // is at the first
+    match style.num_id.as_deref() {
+        Some("0") => {
+            style.num_id = None;
+            style.list_level = None;
+        }
+        Some(_) => {
+            style.list_level.get_or_insert(0);
+        }
+        None => {}
+    }
+}
+
+/// The paragraph styles of `word/styles.xml`, by `w:styleId`.
+///
+/// Style IDs are derived from the style's name in the language of the
+/// template (`berschrift1` for "Überschrift 1"), so a heading is known by
+/// its `w:name`, which Word keeps in English for built-in styles, by an
+/// outline level, or by the style it is based on.
+#[derive(Debug, Default)]
+struct Styles(HashMap<String, StyleDef>);
+
+#[derive(Debug, Default)]
+struct StyleDef {
+    name: Option<String>,
+    based_on: Option<String>,
+    /// `w:outlineLvl`: 0 for a top-level heading, 9 for body text.
+    outline_level: Option<u8>,
+    /// The style's `w:rFonts` is a monospace font.
+    monospace: bool,
+}
+
+/// Longest `w:basedOn` chain followed, as a guard against cycles.
+const MAX_STYLE_DEPTH: usize = 16;
+
+impl Styles {
+    fn parse(xml: &str) -> Self {
+        let mut styles = HashMap::new();
+        let mut reader = Reader::from_str(xml);
+        let mut current: Option<(String, StyleDef)> = None;
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"style" => {
+                    let paragraph = get_attr(e, b"w:type").is_none_or(|t| t == "paragraph");
+                    current = get_attr(e, b"w:styleId")
+                        .filter(|_| paragraph)
+                        .map(|id| (id, StyleDef::default()));
+                }
+                Ok(Event::Empty(ref e)) => {
+                    let Some((_, style)) = &mut current else {
+                        continue;
+                    };
+                    match e.local_name().as_ref() {
+                        b"name" => style.name = get_val_attr(e),
+                        b"basedOn" => style.based_on = get_val_attr(e),
+                        b"outlineLvl" => {
+                            style.outline_level = get_val_attr(e).and_then(|v| v.parse().ok());
+                        }
+                        b"rFonts" => {
+                            style.monospace = run_font(e).is_some_and(|f| is_monospace(&f));
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Event::End(ref e)) if e.local_name().as_ref() == b"style" => {
+                    styles.extend(current.take());
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => {}
+            }
+        }
+        Self(styles)
+    }
+
+    /// The heading level style `id` gives a paragraph, 0 for none, or
+    /// `None` if the style is not defined.
+    fn heading_level(&self, id: &str) -> Option<u8> {
+        let mut style = self.0.get(id)?;
+        for _ in 0..MAX_STYLE_DEPTH {
+            if let Some(level) = style.name.as_deref().and_then(parse_heading_level) {
+                return Some(level);
+            }
+            if let Some(level) = style.outline_level {
+                return Some(if level < 9 { level + 1 } else { 0 });
+            }
+            match style.based_on.as_deref().and_then(|id| self.0.get(id)) {
+                Some(base) => style = base,
+                None => break,
+            }
+        }
+        Some(0)
+    }
+
+    /// Whether style `id` is Word's Caption style or based on it, or
+    /// `None` if the style is not defined.
+    fn is_caption(&self, id: &str) -> Option<bool> {
+        let mut style = self.0.get(id)?;
+        for _ in 0..MAX_STYLE_DEPTH {
+            if style
+                .name
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case("caption"))
+            {
+                return Some(true);
+            }
+            match style.based_on.as_deref().and_then(|id| self.0.get(id)) {
+                Some(base) => style = base,
+                None => break,
+            }
+        }
+        Some(false)
+    }
+
+    /// Whether style `id` is a code style, by its name or font, or based on
+    /// one, or `None` if the style is not defined.
+    fn is_code(&self, id: &str) -> Option<bool> {
+        let mut style = self.0.get(id)?;
+        for _ in 0..MAX_STYLE_DEPTH {
+            if style.monospace || style.name.as_deref().is_some_and(is_code_style) {
+                return Some(true);
+            }
+            match style.based_on.as_deref().and_then(|id| self.0.get(id)) {
+                Some(base) => style = base,
+                None => break,
+            }
+        }
+        Some(false)
+    }
+}
+
+/// Whether a style ID or name is one of the styles Word, pandoc, and
+/// HTML import use for code, such as `HTMLPreformatted` or `Source Code`.
+fn is_code_style(id: &str) -> bool {
+    let key: String = id
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    matches!(
+        key.as_str(),
+        "code"
+            | "sourcecode"
+            | "htmlpreformatted"
+            | "htmlcode"
+            | "htmlkeyboard"
+            | "htmltypewriter"
+            | "htmlsample"
+            | "verbatimchar"
+    )
+}
+
+/// Whether `font` is a monospace family, by name: `Consolas`,
+/// `Courier New`, and any `… Mono`.
+fn is_monospace(font: &str) -> bool {
+    let font = font.to_ascii_lowercase();
+    font.contains("mono")
+        || font.starts_with("courier")
+        || [
+            "consolas",
+            "lucida console",
+            "lucida sans typewriter",
+            "menlo",
+            "monaco",
+            "inconsolata",
+            "source code pro",
+            "fira code",
+            "cascadia code",
+            "ocr a extended",
+        ]
+        .contains(&font.as_str())
+}
+
+/// The font of a `<w:rFonts>` element: the one for ASCII text, else the
+/// one for other Latin text.
+fn run_font(e: &quick_xml::events::BytesStart) -> Option<String> {
+    get_attr(e, b"w:ascii").or_else(|| get_attr(e, b"w:hAnsi"))
+}
+
+/// Set the heading level of paragraphs whose style is defined in
+/// `styles`, and whether they are captions, in place of the guesses from
+/// the style ID.
+fn resolve_styles(blocks: &mut [Block], styles: &Styles) {
+    for block in blocks {
+        match block {
+            Block::Paragraph { style, .. } => {
+                let Some(id) = style.style_id.as_deref() else {
+                    continue;
+                };
+                if let Some(level) = styles.heading_level(id) {
+                    style.heading_level = level;
+                }
+                if let Some(caption) = styles.is_caption(id) {
+                    style.caption = caption;
+                }
+                if let Some(code) = styles.is_code(id) {
+                    style.code = code;
+                }
+            }
+            Block::Table { rows } => {
+                for cell in rows.iter_mut().flatten() {
+                    resolve_styles(cell, styles);
+                }
+            }
+            Block::Image { .. } | Block::Comment { .. } | Block::Graphic(_) => {}
+        }
+    }
 }
 
 /// Parse a heading style value like "Heading1" -> Some(1), "Title" -> Some(1).
@@ -285,37 +1176,107 @@ fn parse_heading_level(val: &str) -> Option<u8> {
     None
 }
 
-/// Parse a `<w:r>` element into a text `Run` and/or an image `Block`.
+/// Everything a `<w:r>` element contributes to its paragraph.
+#[derive(Debug, Default)]
+struct RunContent {
+    run: Option<Run>,
+    image: Option<Block>,
+    /// Complex-field markers (`<w:fldChar>`, `<w:instrText>`) in document order.
+    fields: Vec<FieldMark>,
+    /// The `w:id` of a `<w:commentReference>`.
+    comment: Option<String>,
+    /// A diagram or chart in a drawing.
+    graphic: Option<Graphic>,
+}
+
+impl RunContent {
+    /// Add the run to its paragraph: field markers to `fields`, the text to
+    /// `runs` (linked to `link`, and left out when `hidden` or part of a
+    /// field instruction), and any drawing to `image_blocks`.
+    fn add_to(
+        self,
+        runs: &mut Vec<Run>,
+        image_blocks: &mut Vec<Block>,
+        fields: &mut FieldStack,
+        link: Option<&str>,
+        hidden: bool,
+    ) {
+        for mark in self.fields {
+            fields.apply(mark, runs);
+        }
+        if let Some(id) = self.comment {
+            runs.push(Run::comment_mark(CommentMark::End(id)));
+        }
+        if let Some(mut run) = self.run.filter(|_| !hidden && !fields.in_instruction()) {
+            run.link_url = link.map(String::from);
+            runs.push(run);
+        }
+        image_blocks.extend(self.image);
+        image_blocks.extend(self.graphic.map(Block::Graphic));
+    }
+}
+
+/// Parse an `<m:oMath>` equation into a run of LaTeX between `$` signs,
+/// or `$$` for a display equation (in `<m:oMathPara>`).
+fn parse_math(reader: &mut Reader<&[u8]>, display: bool) -> Option<Run> {
+    let latex = omml::to_latex(reader);
+    let delimiter = if display { "$$" } else { "$" };
+    (!latex.is_empty()).then(|| Run::plain(&format!("{delimiter}{latex}{delimiter}")))
+}
+
+/// Parse a `<w:r>` element into a text `Run`, an image `Block`, and any
+/// field markers it carries.
 ///
-/// A run may contain text, a drawing (image), or both. When `image_rels`
-/// is non-empty and a `<w:drawing>` is found inside the run, the image
-/// reference is extracted and returned as a `Block::Image`.
-fn parse_run(reader: &mut Reader<&[u8]>, image_rels: &Rels) -> (Option<Run>, Option<Block>) {
-    let mut bold = false;
-    let mut italic = false;
+/// A run may contain text, a picture (`<w:drawing>`, or a VML `<w:pict>`),
+/// or both. A picture is returned as a `Block::Image` with its alternative
+/// text, and with its ZIP path when `image_rels` has its relationship.
+fn parse_run(reader: &mut Reader<&[u8]>, image_rels: &Rels) -> RunContent {
+    let mut props = RunProps::default();
     let mut text = String::new();
-    let mut image_block: Option<Block> = None;
+    let mut content = RunContent::default();
+    let mut picture = Picture::default();
+    // Inside <w:object>, whose picture is only the object's icon
+    let mut in_object = false;
 
     loop {
         match reader.read_event() {
             Ok(Event::Start(ref e)) => {
                 let name = e.local_name();
                 match name.as_ref() {
-                    b"rPr" => parse_run_props(reader, &mut bold, &mut italic),
-                    b"t" => {
+                    b"rPr" => parse_run_props(reader, &mut props),
+                    b"t" | b"delText" => {
                         // Read text content
                         if let Ok(Event::Text(t)) = reader.read_event() {
                             if let Ok(s) = t.unescape() {
-                                text.push_str(&s);
+                                match props.symbol_font {
+                                    Some(ref font) => {
+                                        text.push_str(&symbols::map_symbol_text(font, &s));
+                                    }
+                                    None => text.push_str(&s),
+                                }
                             }
                         }
                         // Note: the </w:t> end tag will be consumed below
                     }
-                    b"drawing" if !image_rels.is_empty() => {
-                        if let Some(blk) = parse_drawing(reader, image_rels) {
-                            image_block = Some(blk);
+                    b"instrText" => {
+                        if let Ok(Event::Text(t)) = reader.read_event() {
+                            if let Ok(s) = t.unescape() {
+                                content.fields.push(FieldMark::Instr(s.into_owned()));
+                            }
+                        }
+                    }
+                    b"fldChar" => {
+                        // A begin <w:fldChar> with children carries <w:ffData>
+                        let form = parse_ffdata(reader);
+                        if let Some(mark) = field_mark(e, form) {
+                            content.fields.push(mark);
                         }
                     }
+                    b"docPr" | b"blip" => picture.read(e),
+                    b"shape" if !in_object => picture.read(e),
+                    b"object" => in_object = true,
+                    // A linked object has <o:LinkType> and such inside
+                    b"OLEObject" => content.graphic = Graphic::from_element(e),
                     _ => {}
                 }
             }
@@ -324,57 +1285,128 @@ fn parse_run(reader: &mut Reader<&[u8]>, image_rels: &Rels) -> (Option<Run>, Opt
                 if name.as_ref() == b"tab" {
                     text.push('\t');
                 } else if name.as_ref() == b"br" {
-                    text.push('\n');
+                    // A page break is marked with a form feed, as in .doc
+                    // text, until `resolve_page_breaks`
+                    let page = get_attr(e, b"w:type").as_deref() == Some("page");
+                    text.push(if page { PAGE_BREAK } else { '\n' });
+                } else if name.as_ref() == b"sym" {
+                    if let Some(c) = resolve_sym(e) {
+                        text.push(c);
+                    }
+                } else if name.as_ref() == b"fldChar" {
+                    if let Some(mark) = field_mark(e, None) {
+                        content.fields.push(mark);
+                    }
+                } else if name.as_ref() == b"commentReference" {
+                    content.comment = get_attr(e, b"w:id");
+                } else if let Some(graphic) = Graphic::from_element(e) {
+                    content.graphic = Some(graphic);
+                } else if matches!(name.as_ref(), b"docPr" | b"blip")
+                    || (name.as_ref() == b"imagedata" && !in_object)
+                {
+                    // Legacy VML picture: <w:pict><v:shape><v:imagedata r:id=".."/>,
+                    // common in documents converted from .doc. Neither it nor
+                    // a <w:drawing> is skipped, since their text boxes hold
+                    // document text.
+                    picture.read(e);
                 } else if name.as_ref() == b"b" || name.as_ref() == b"bCs" {
                     // Self-closing <w:b/> in rPr means bold on
-                    bold = true;
+                    props.bold = true;
                 } else if name.as_ref() == b"i" || name.as_ref() == b"iCs" {
-                    italic = true;
+                    props.italic = true;
                 }
             }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == b"r" {
-                    break;
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"r" => break,
+                b"object" => in_object = false,
+                b"drawing" | b"pict" => {
+                    let found = std::mem::take(&mut picture);
+                    if found.rid.is_some() {
+                        content.image = Some(image_block(found, image_rels));
+                    }
                 }
-            }
+                _ => {}
+            },
             Ok(Event::Eof) | Err(_) => break,
             _ => {}
         }
     }
 
-    let run = if text.is_empty() {
-        None
-    } else {
-        Some(Run {
-            text,
-            bold,
-            italic,
-            link_url: None,
-        })
-    };
+    if !text.is_empty() {
+        content.run = Some(props.into_run(text));
+    }
+    content
+}
 
-    (run, image_block)
+/// Formatting collected from a run's `<w:rPr>`.
+#[derive(Debug, Default)]
+#[allow(clippy::struct_excessive_bools)] // independent formatting flags
+struct RunProps {
+    bold: bool,
+    italic: bool,
+    strike: bool,
+    underline: bool,
+    vert_align: Option<VertAlign>,
+    /// Set when the run's font is a symbol font (Wingdings, Symbol) whose
+    /// code points need mapping to Unicode.
+    symbol_font: Option<String>,
+    color: Option<String>,
+    highlight: Option<String>,
+    code: bool,
 }
 
-/// Parse <w:rPr> to extract bold/italic.
-fn parse_run_props(reader: &mut Reader<&[u8]>, bold: &mut bool, italic: &mut bool) {
-    let mut depth = 1u32;
-    loop {
-        match reader.read_event() {
-            Ok(Event::Start(_)) => {
-                depth += 1;
+impl RunProps {
+    /// A run of `text` with this formatting.
+    fn into_run(self, text: String) -> Run {
+        Run {
+            text,
+            bold: self.bold,
+            italic: self.italic,
+            strike: self.strike,
+            underline: self.underline,
+            vert_align: self.vert_align,
+            color: self.color,
+            highlight: self.highlight,
+            code: self.code,
+            ..Run::default()
+        }
+    }
+}
+
+/// Parse <w:rPr> to extract bold/italic, strikethrough, underline,
+/// superscript/subscript, colors, and symbol fonts.
+fn parse_run_props(reader: &mut Reader<&[u8]>, props: &mut RunProps) {
+    let mut depth = 1u32;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(_)) => {
+                depth += 1;
             }
             Ok(Event::Empty(ref e)) => {
                 let name = e.local_name();
                 match name.as_ref() {
-                    b"b" | b"bCs" => {
-                        // Check for val="false" or val="0"
-                        let val = get_val_attr(e);
-                        *bold = !matches!(val.as_deref(), Some("false" | "0"));
+                    b"b" | b"bCs" => props.bold = is_on(e),
+                    b"i" | b"iCs" => props.italic = is_on(e),
+                    b"strike" | b"dstrike" => props.strike = is_on(e),
+                    b"u" => props.underline = get_attr(e, b"w:val").as_deref() != Some("none"),
+                    b"vertAlign" => {
+                        props.vert_align = match get_attr(e, b"w:val").as_deref() {
+                            Some("superscript") => Some(VertAlign::Superscript),
+                            Some("subscript") => Some(VertAlign::Subscript),
+                            _ => None,
+                        };
+                    }
+                    b"rFonts" => {
+                        let font = run_font(e);
+                        props.code |= font.as_deref().is_some_and(is_monospace);
+                        props.symbol_font = font.filter(|f| symbols::is_symbol_font(f));
                     }
-                    b"i" | b"iCs" => {
-                        let val = get_val_attr(e);
-                        *italic = !matches!(val.as_deref(), Some("false" | "0"));
+                    b"rStyle" => props.code |= get_val_attr(e).is_some_and(|id| is_code_style(&id)),
+                    b"color" => {
+                        props.color = get_attr(e, b"w:val").filter(|v| v != "auto");
+                    }
+                    b"highlight" => {
+                        props.highlight = get_attr(e, b"w:val").filter(|v| v != "none");
                     }
                     _ => {}
                 }
@@ -391,6 +1423,25 @@ fn parse_run_props(reader: &mut Reader<&[u8]>, bold: &mut bool, italic: &mut boo
     }
 }
 
+/// Resolve `<w:sym w:font="Wingdings" w:char="F0FC"/>` to a Unicode character.
+fn resolve_sym(e: &quick_xml::events::BytesStart) -> Option<char> {
+    let font = get_attr(e, b"w:font")?;
+    let code = symbols::parse_char_code(&get_attr(e, b"w:char")?)?;
+    symbols::symbol_to_unicode(&font, code)
+}
+
+/// The URL of a `<w:hyperlink>`: its `r:id` resolved through `rels`, with
+/// `w:anchor` as the fragment. A link within the document has only the
+/// anchor, the name of a bookmark, and becomes `#name`.
+fn hyperlink_url(e: &quick_xml::events::BytesStart, rels: &Rels) -> Option<String> {
+    let url = get_attr(e, b"r:id").and_then(|rid| rels.get(&rid).cloned());
+    match (url, get_attr(e, b"w:anchor")) {
+        (Some(url), Some(anchor)) => Some(format!("{url}#{anchor}")),
+        (url, None) => url,
+        (None, Some(anchor)) => Some(format!("#{anchor}")),
+    }
+}
+
 /// Parse runs inside a `<w:hyperlink>` element, tagging each run with the URL.
 fn parse_hyperlink_runs(
     reader: &mut Reader<&[u8]>,
@@ -398,25 +1449,15 @@ fn parse_hyperlink_runs(
     url: Option<&str>,
     image_rels: &Rels,
     image_blocks: &mut Vec<Block>,
+    fields: &mut FieldStack,
 ) {
     loop {
         match reader.read_event() {
-            Ok(Event::Start(ref e)) => {
-                if e.local_name().as_ref() == b"r" {
-                    let (run_opt, img_opt) = parse_run(reader, image_rels);
-                    if let Some(mut run) = run_opt {
-                        run.link_url = url.map(String::from);
-                        runs.push(run);
-                    }
-                    if let Some(blk) = img_opt {
-                        image_blocks.push(blk);
-                    }
-                }
+            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"r" => {
+                parse_run(reader, image_rels).add_to(runs, image_blocks, fields, url, false);
             }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == b"hyperlink" {
-                    break;
-                }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"hyperlink" => {
+                break;
             }
             Ok(Event::Eof) | Err(_) => break,
             _ => {}
@@ -424,377 +1465,2844 @@ fn parse_hyperlink_runs(
     }
 }
 
-/// Parse a `<w:drawing>` element to find an embedded image reference.
+// ── Fields ─────────────────────────────────────────────────────────
+
+/// A complex-field marker from inside a run.
+#[derive(Debug)]
+enum FieldMark {
+    /// `<w:fldChar w:fldCharType="begin">`, with the form field's value
+    /// when it is a checkbox or drop-down form field.
+    Begin { form: Option<FormField> },
+    /// `<w:instrText>` content.
+    Instr(String),
+    /// `<w:fldChar w:fldCharType="separate">`: the cached result follows.
+    Separate,
+    /// `<w:fldChar w:fldCharType="end">`.
+    End,
+}
+
+/// The marks a `<w:fldSimple>` stands for: a field with its instruction in
+/// `w:instr` and its cached result in the element's runs.
+fn simple_field(e: &quick_xml::events::BytesStart) -> [FieldMark; 3] {
+    [
+        FieldMark::Begin { form: None },
+        FieldMark::Instr(unescaped_attr(e, b"w:instr").unwrap_or_default()),
+        FieldMark::Separate,
+    ]
+}
+
+/// Build a `FieldMark` from a `<w:fldChar>` element.
+fn field_mark(e: &quick_xml::events::BytesStart, form: Option<FormField>) -> Option<FieldMark> {
+    match get_attr(e, b"w:fldCharType")?.as_str() {
+        "begin" => Some(FieldMark::Begin { form }),
+        "separate" => Some(FieldMark::Separate),
+        "end" => Some(FieldMark::End),
+        _ => None,
+    }
+}
+
+/// A field that has begun but not yet ended.
+#[derive(Debug, Default)]
+struct OpenField {
+    instr: String,
+    form: Option<FormField>,
+    /// Index into the paragraph's runs where the cached result starts;
+    /// `None` while still reading the instruction.
+    result_start: Option<usize>,
+}
+
+/// Tracks nested complex fields while paragraphs' runs are collected.
 ///
-/// Walks into `<wp:inline>` or `<wp:anchor>` → `<a:graphic>` →
-/// `<a:graphicData>` → `<pic:blipFill>` → `<a:blip r:embed="rIdN"/>`.
-/// Returns a `Block::Image` with the image's ZIP path (to be resolved later)
-/// stored in the `markdown` field as a placeholder.
-fn parse_drawing(reader: &mut Reader<&[u8]>, image_rels: &Rels) -> Option<Block> {
-    let mut embed_rid: Option<String> = None;
-    let mut depth = 1u32;
+/// Cached results are kept as ordinary runs; when a field ends, its result
+/// may be replaced (form fields), linked (`HYPERLINK`), or dropped (`TOC`)
+/// based on the instruction.
+#[derive(Debug, Default)]
+struct FieldStack {
+    open: Vec<OpenField>,
+    /// Set when text of the current paragraph was dropped as part of a
+    /// hidden field's result.
+    hidden: bool,
+}
 
-    loop {
-        match reader.read_event() {
-            Ok(Event::Start(ref e)) => {
-                depth += 1;
-                if e.local_name().as_ref() == b"blip" {
-                    if let Some(rid) = get_attr(e, b"r:embed") {
-                        embed_rid = Some(rid);
+impl FieldStack {
+    fn apply(&mut self, mark: FieldMark, runs: &mut Vec<Run>) {
+        match mark {
+            FieldMark::Begin { form } => self.open.push(OpenField {
+                form,
+                ..OpenField::default()
+            }),
+            FieldMark::Instr(s) => {
+                if let Some(field) = self.open.last_mut() {
+                    if field.result_start.is_none() {
+                        field.instr.push_str(&s);
                     }
                 }
             }
-            Ok(Event::Empty(ref e)) => {
-                if e.local_name().as_ref() == b"blip" {
-                    if let Some(rid) = get_attr(e, b"r:embed") {
-                        embed_rid = Some(rid);
-                    }
+            FieldMark::Separate => {
+                if let Some(field) = self.open.last_mut() {
+                    field.result_start = Some(runs.len());
                 }
             }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == b"drawing" {
-                    break;
-                }
-                depth -= 1;
-                if depth == 0 {
-                    break;
+            FieldMark::End => {
+                if let Some(field) = self.open.pop() {
+                    self.hidden |= hides_result(&field.instr);
+                    finish_field(&field, runs);
                 }
             }
-            Ok(Event::Eof) | Err(_) => break,
-            _ => {}
         }
     }
 
-    let rid = embed_rid?;
-    let target = image_rels.get(&rid)?;
+    /// Carry the fields still open at the end of a paragraph into the next:
+    /// a hidden field's result so far is dropped, and the others' results
+    /// continue from the next paragraph's first run. Returns whether any of
+    /// the paragraph's text was hidden.
+    fn end_paragraph(&mut self, runs: &mut Vec<Run>) -> bool {
+        for field in &mut self.open {
+            if let Some(start) = field.result_start {
+                if hides_result(&field.instr) {
+                    runs.truncate(start);
+                    self.hidden = true;
+                }
+                field.result_start = Some(0);
+            }
+        }
+        std::mem::take(&mut self.hidden)
+    }
 
-    // Store the resolved ZIP path as a placeholder — will be replaced with
-    // actual base64 content in resolve_images()
-    let zip_path = if target.starts_with('/') {
-        target.trim_start_matches('/').to_string()
-    } else {
-        format!("word/{target}")
-    };
+    /// True while any open field is still in its instruction part. Text
+    /// there (e.g. results of nested fields) is not display text.
+    fn in_instruction(&self) -> bool {
+        self.open.iter().any(|f| f.result_start.is_none())
+    }
+}
 
-    Some(Block::Image { markdown: zip_path })
+/// Whether a field's result is left out: a table of contents, which
+/// repeats the headings with page numbers.
+fn hides_result(instr: &str) -> bool {
+    fields::keyword(instr) == "TOC"
 }
 
-/// Resolve `Block::Image` placeholders by reading image data from the ZIP
-/// archive and converting to reference-style base64 images.
-///
-/// Each image gets a unique label (`image1`, `image2`, ...). The `markdown`
-/// field is replaced with the inline reference (`![][image1]`), and the
-/// corresponding definitions are collected for appending at document end.
-///
-/// Images with unsupported formats (EMF, WMF, etc.) are silently removed.
-fn resolve_images(blocks: &mut Vec<Block>, archive: &mut ZipArchive<Cursor<&[u8]>>) -> Vec<String> {
-    let mut definitions = Vec::new();
-    let mut counter = 0usize;
+/// Apply a completed field to the paragraph's runs.
+fn finish_field(field: &OpenField, runs: &mut Vec<Run>) {
+    let keyword = fields::keyword(&field.instr);
+    let start = field.result_start.unwrap_or(runs.len()).min(runs.len());
 
-    for block in blocks.iter_mut() {
-        if let Block::Image { markdown } = block {
-            let zip_path = markdown.clone();
-            if let Some(data) = xml_util::read_image_from_zip(archive, &zip_path, "") {
-                counter += 1;
-                let id = format!("image{counter}");
-                if let Some(img_ref) = crate::markup::image_to_base64_ref(&data, &id) {
-                    *markdown = img_ref.inline;
-                    definitions.push(img_ref.definition);
-                } else {
-                    // Unsupported format — mark for removal
-                    *markdown = String::new();
-                }
-            } else {
-                *markdown = String::new();
+    if hides_result(&field.instr) {
+        runs.truncate(start);
+    } else if keyword == "HYPERLINK" {
+        let url = fields::hyperlink_url(&field.instr);
+        for run in &mut runs[start..] {
+            if run.link_url.is_none() {
+                run.link_url.clone_from(&url);
             }
         }
+    } else if keyword == "FORMCHECKBOX" {
+        runs.truncate(start);
+        let checked = matches!(field.form, Some(FormField::Checkbox(true)));
+        runs.push(Run::checkbox(checked));
+    } else if let ("FORMDROPDOWN", Some(FormField::Dropdown(choice))) =
+        (keyword.as_str(), &field.form)
+    {
+        // Word doesn't cache a drop-down's result as text
+        runs.truncate(start);
+        runs.push(Run::plain(choice.as_str()));
+    } else if fields::is_metadata_field(&field.instr) {
+        // Collapse the cached result into one run, keeping the formatting
+        // of its first run, so it can be replaced by the property value.
+        let result: Vec<Run> = runs.drain(start..).collect();
+        let mut run = result.first().cloned().unwrap_or_default();
+        run.text = result.iter().map(|r| r.text.as_str()).collect();
+        run.field = Some(field.instr.clone());
+        runs.push(run);
     }
-    // Remove empty image blocks (unsupported or unreadable)
-    blocks.retain(|b| !matches!(b, Block::Image { markdown } if markdown.is_empty()));
+}
 
-    definitions
+/// Replace the cached text of document-property fields with values from
+/// the document's metadata.
+fn resolve_fields(blocks: &mut [Block], meta: &Metadata) {
+    for block in blocks {
+        match block {
+            Block::Paragraph { runs, .. } => {
+                for run in runs {
+                    if let Some(value) = run.field.as_deref().and_then(|f| fields::resolve(f, meta))
+                    {
+                        run.text = value;
+                    }
+                }
+            }
+            Block::Table { rows } => {
+                for cell in rows.iter_mut().flatten() {
+                    resolve_fields(cell, meta);
+                }
+            }
+            Block::Image { .. } | Block::Comment { .. } | Block::Graphic(_) => {}
+        }
+    }
 }
 
-/// Parse a `<w:tbl>` element into a `Block::Table`.
-fn parse_table(reader: &mut Reader<&[u8]>, rels: &Rels) -> Block {
-    let mut rows: Vec<Row> = Vec::new();
+// ── Page breaks ───────────────────────────────────────────────────
 
-    loop {
-        match reader.read_event() {
-            Ok(Event::Start(ref e)) => {
-                let name = e.local_name();
-                if name.as_ref() == b"tr" {
-                    let row = parse_table_row(reader, rels);
-                    rows.push(row);
-                }
+/// Stands for a `<w:br w:type="page"/>` in run text.
+const PAGE_BREAK: char = '\u{c}';
+
+/// Write `separator` as a paragraph of its own at page breaks, before a
+/// paragraph that starts a page, and after one that ends a section,
+/// splitting paragraphs with a page break inside. Without a separator a
+/// page break is a line break, as other breaks are. `after_separator`
+/// says whether the blocks before these end with a separator (or there
+/// are none), and is left saying the same of these.
+fn resolve_page_breaks(
+    blocks: Vec<Block>,
+    separator: Option<&str>,
+    after_separator: &mut bool,
+) -> Vec<Block> {
+    let mut out = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        match (block, separator) {
+            (Block::Paragraph { style, runs }, Some(separator)) => {
+                split_at_page_breaks(style, runs, separator, *after_separator, &mut out);
             }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == b"tbl" {
-                    break;
+            (mut block, _) => {
+                unmark_page_breaks(std::slice::from_mut(&mut block));
+                out.push(block);
+            }
+        }
+        *after_separator = ends_with_separator(&out, separator, *after_separator);
+    }
+    out
+}
+
+/// Add a paragraph to `out`, split at its page breaks, with `separator`
+/// wherever a page or section breaks. Its style goes with its first part
+/// with text; the other parts are plain. `after_separator` is as in
+/// [`resolve_page_breaks`], for when `out` is empty.
+fn split_at_page_breaks(
+    style: ParaStyle,
+    runs: Vec<Run>,
+    separator: &str,
+    after_separator: bool,
+    out: &mut Vec<Block>,
+) {
+    let (before, after) = (style.page_break_before, style.section_end);
+    if before {
+        push_separator(separator, after_separator, out);
+    }
+    if runs.iter().any(|r| r.text.contains(PAGE_BREAK)) {
+        let mut style = Some(style);
+        let mut part: Vec<Run> = Vec::new();
+        for run in runs {
+            for (i, text) in run.text.split(PAGE_BREAK).enumerate() {
+                if i > 0 {
+                    push_part(&mut part, &mut style, out);
+                    push_separator(separator, after_separator, out);
+                }
+                if !text.is_empty() || run.comment.is_some() {
+                    part.push(Run {
+                        text: text.to_string(),
+                        ..run.clone()
+                    });
                 }
             }
-            Ok(Event::Eof) | Err(_) => break,
-            _ => {}
         }
+        push_part(&mut part, &mut style, out);
+    } else {
+        out.push(Block::Paragraph { style, runs });
+    }
+    if after {
+        push_separator(separator, after_separator, out);
     }
+}
 
-    Block::Table { rows }
+/// Add the runs of `part` to `out` as a paragraph, unless they are only
+/// whitespace.
+fn push_part(part: &mut Vec<Run>, style: &mut Option<ParaStyle>, out: &mut Vec<Block>) {
+    let runs = std::mem::take(part);
+    if runs
+        .iter()
+        .any(|r| !r.text.trim().is_empty() || r.comment.is_some())
+    {
+        let style = style.take().unwrap_or_default();
+        out.push(Block::Paragraph { style, runs });
+    }
 }
 
-/// Parse a `<w:tr>` element into a row of cells.
-fn parse_table_row(reader: &mut Reader<&[u8]>, rels: &Rels) -> Row {
-    let mut cells: Row = Vec::new();
+/// Add a separator paragraph to `out`, unless it is at the start of the
+/// document or right after another.
+fn push_separator(separator: &str, after_separator: bool, out: &mut Vec<Block>) {
+    if !ends_with_separator(out, Some(separator), after_separator) {
+        out.push(Block::Paragraph {
+            style: ParaStyle::default(),
+            runs: vec![Run::plain(separator)],
+        });
+    }
+}
 
-    loop {
-        match reader.read_event() {
-            Ok(Event::Start(ref e)) => {
-                let name = e.local_name();
-                if name.as_ref() == b"tc" {
-                    let cell = parse_table_cell(reader, rels);
-                    cells.push(cell);
+/// Whether `blocks` end with a separator paragraph; `empty` if there are
+/// no blocks.
+fn ends_with_separator(blocks: &[Block], separator: Option<&str>, empty: bool) -> bool {
+    match blocks.last() {
+        None => empty,
+        Some(Block::Paragraph { runs, .. }) => {
+            matches!((runs.as_slice(), separator), ([r], Some(s)) if r.text == s)
+        }
+        Some(_) => false,
+    }
+}
+
+/// Turn the page breaks in `blocks` into line breaks.
+fn unmark_page_breaks(blocks: &mut [Block]) {
+    for block in blocks {
+        match block {
+            Block::Paragraph { runs, .. } => {
+                for run in runs.iter_mut().filter(|r| r.text.contains(PAGE_BREAK)) {
+                    run.text = run.text.replace(PAGE_BREAK, "\n");
                 }
             }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == b"tr" {
-                    break;
+            Block::Table { rows } => {
+                for cell in rows.iter_mut().flatten() {
+                    unmark_page_breaks(cell);
                 }
             }
-            Ok(Event::Eof) | Err(_) => break,
-            _ => {}
+            Block::Image { .. } | Block::Comment { .. } | Block::Graphic(_) => {}
         }
     }
+}
+
+// ── Bookmarks ─────────────────────────────────────────────────────
 
-    cells
+/// Keep the bookmarks that an internal link (`#name`) points at, moving
+/// any on a paragraph without text to the next one with text, and drop
+/// the links to bookmarks the document does not have. Word adds many
+/// bookmarks of its own (`_GoBack`, `_Toc…`), so anchors are only worth
+/// writing where a link lands. These are the lowercased names of those
+/// bookmarks in `blocks`.
+fn bookmark_targets(blocks: &[Block]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let mut targets = HashSet::new();
+    collect_bookmarks(blocks, &mut names, &mut targets);
+    targets.retain(|t| names.contains(t));
+    targets
 }
 
-/// Parse a `<w:tc>` element into a list of blocks.
-///
-/// Images inside table cells are not extracted (impractical in markdown
-/// tables), so an empty `image_rels` is used for paragraph parsing.
-fn parse_table_cell(reader: &mut Reader<&[u8]>, rels: &Rels) -> Cell {
-    let empty_image_rels = xml_util::Rels::new();
-    let mut blocks = Vec::new();
+/// Add the lowercased names of the bookmarks in `blocks` to `names`, and
+/// those of internal link targets to `targets`; bookmark names are not
+/// case-sensitive.
+fn collect_bookmarks(blocks: &[Block], names: &mut HashSet<String>, targets: &mut HashSet<String>) {
+    for block in blocks {
+        match block {
+            Block::Paragraph { style, runs } => {
+                names.extend(style.bookmarks.iter().map(|b| b.to_lowercase()));
+                targets.extend(
+                    runs.iter()
+                        .filter_map(|r| r.link_url.as_deref()?.strip_prefix('#'))
+                        .map(str::to_lowercase),
+                );
+            }
+            Block::Table { rows } => {
+                for cell in rows.iter().flatten() {
+                    collect_bookmarks(cell, names, targets);
+                }
+            }
+            Block::Image { .. } | Block::Comment { .. } | Block::Graphic(_) => {}
+        }
+    }
+}
 
-    loop {
-        match reader.read_event() {
-            Ok(Event::Start(ref e)) => {
-                let name = e.local_name();
-                match name.as_ref() {
-                    b"p" => {
-                        let mut para_blocks = parse_paragraph(reader, rels, &empty_image_rels);
-                        blocks.append(&mut para_blocks);
+/// Keep the bookmarks in `targets` and the links to them (see
+/// [`bookmark_targets`]). `pending` holds the bookmarks still waiting for
+/// a paragraph with text.
+fn link_bookmarks(blocks: &mut [Block], targets: &HashSet<String>, pending: &mut Vec<String>) {
+    for block in blocks {
+        match block {
+            Block::Paragraph { style, runs } => {
+                pending.extend(
+                    style
+                        .bookmarks
+                        .drain(..)
+                        .filter(|b| targets.contains(&b.to_lowercase())),
+                );
+                if runs.iter().any(|r| !r.text.trim().is_empty()) {
+                    style.bookmarks = std::mem::take(pending);
+                }
+                for run in runs {
+                    if run
+                        .link_url
+                        .as_deref()
+                        .and_then(|url| url.strip_prefix('#'))
+                        .is_some_and(|name| !targets.contains(&name.to_lowercase()))
+                    {
+                        run.link_url = None;
                     }
-                    b"tbl" => blocks.push(parse_table(reader, rels)), // nested table
-                    _ => {}
                 }
             }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == b"tc" {
-                    break;
+            Block::Table { rows } => {
+                for cell in rows.iter_mut().flatten() {
+                    link_bookmarks(cell, targets, &mut Vec::new());
                 }
             }
-            Ok(Event::Eof) | Err(_) => break,
-            _ => {}
+            Block::Image { .. } | Block::Comment { .. } | Block::Graphic(_) => {}
+        }
+    }
+}
+
+// ── Diagrams and charts ───────────────────────────────────────────
+
+/// Replace each `Block::Graphic` with the text of its diagram, chart,
+/// chunk, or embedded object, read from the part its relationship in
+/// `graphic_rels` points at.
+fn resolve_graphics(
+    blocks: &mut Vec<Block>,
+    graphic_rels: &Rels,
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    opts: &Options,
+) {
+    let mut i = 0;
+    while i < blocks.len() {
+        match &mut blocks[i] {
+            Block::Graphic(graphic) => {
+                let replacement = read_graphic(graphic, graphic_rels, archive, opts);
+                let count = replacement.len();
+                blocks.splice(i..=i, replacement);
+                i += count;
+            }
+            Block::Table { rows } => {
+                for cell in rows.iter_mut().flatten() {
+                    resolve_graphics(cell, graphic_rels, archive, opts);
+                }
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+}
+
+/// The blocks showing `graphic`: a diagram's nodes as list items, a
+/// chart's title and data table, a chunk's converted text, or an
+/// object's placeholder.
+fn read_graphic(
+    graphic: &Graphic,
+    graphic_rels: &Rels,
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    opts: &Options,
+) -> Vec<Block> {
+    let (Graphic::Diagram(rid)
+    | Graphic::Chart(rid)
+    | Graphic::Chunk(rid)
+    | Graphic::Object { rid, .. }) = graphic;
+    let Some(target) = graphic_rels.get(rid) else {
+        return Vec::new();
+    };
+    let path = target.clone();
+    let mut data = Vec::new();
+    let read = archive
+        .by_name(&path)
+        .is_ok_and(|mut part| part.read_to_end(&mut data).is_ok());
+    match graphic {
+        // A linked object's target is a file outside the document
+        Graphic::Object { prog_id, .. } => {
+            object_blocks(read.then_some(data), &path, prog_id.as_deref(), opts)
         }
+        _ if !read => Vec::new(),
+        Graphic::Diagram(_) => diagram_items(&String::from_utf8_lossy(&data)),
+        Graphic::Chart(_) => chart_blocks(&String::from_utf8_lossy(&data)),
+        Graphic::Chunk(_) => chunk_blocks(&data, &path),
     }
+}
+
+/// A diagram data part's nodes as bulleted list items.
+fn diagram_items(xml: &str) -> Vec<Block> {
+    smartart::parse(xml)
+        .into_iter()
+        .map(|node| Block::Paragraph {
+            style: ParaStyle {
+                list_level: Some(node.level),
+                ..ParaStyle::default()
+            },
+            runs: vec![Run::plain(&node.text)],
+        })
+        .collect()
+}
 
+/// A chart part's title, in bold, and its data as a table with a row
+/// per category and a column per series.
+fn chart_blocks(xml: &str) -> Vec<Block> {
+    let Some(chart) = Chart::parse(xml) else {
+        return Vec::new();
+    };
+    let mut blocks = Vec::new();
+    if let Some(title) = &chart.title {
+        blocks.push(Block::Paragraph {
+            style: ParaStyle::default(),
+            runs: vec![Run {
+                bold: true,
+                ..Run::plain(title)
+            }],
+        });
+    }
+    let cell = |text: String| {
+        vec![Block::Paragraph {
+            style: ParaStyle::default(),
+            runs: vec![Run::plain(&text)],
+        }]
+    };
+    blocks.push(Block::Table {
+        rows: chart
+            .rows()
+            .into_iter()
+            .map(|row| row.into_iter().map(cell).collect())
+            .collect(),
+    });
     blocks
 }
 
-/// Get the `w:val` (or `val`) attribute value from an XML element.
-fn get_val_attr(e: &quick_xml::events::BytesStart) -> Option<String> {
-    get_attr(e, b"w:val").or_else(|| get_attr(e, b"val"))
+/// A chunk part's content as blocks: a plain text part's lines as
+/// paragraphs, anything else read as HTML or MHT.
+fn chunk_blocks(data: &[u8], path: &str) -> Vec<Block> {
+    if path.to_ascii_lowercase().ends_with(".txt") {
+        return html::decode(data)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Block::Paragraph {
+                style: ParaStyle::default(),
+                runs: vec![Run::plain(line.trim_end())],
+            })
+            .collect();
+    }
+    html::parse(&html::decode(data))
+        .into_iter()
+        .map(html_block)
+        .collect()
 }
 
-/// Extract text content from a cell's blocks, joining paragraphs with spaces.
-fn cell_to_text(cell: &[Block], use_markdown: bool) -> String {
-    cell.iter()
-        .filter_map(|b| match b {
-            Block::Paragraph { runs, .. } => {
-                let t = if use_markdown {
-                    render_runs_markdown(runs)
-                } else {
-                    runs.iter().map(|r| r.text.as_str()).collect::<String>()
+/// An HTML block as the paragraph or table Word would import it as.
+fn html_block(block: html::Block) -> Block {
+    match block {
+        html::Block::Paragraph { kind, spans } => {
+            let style = match kind {
+                html::Kind::Normal => ParaStyle::default(),
+                html::Kind::Heading(level) => ParaStyle {
+                    heading_level: level,
+                    ..ParaStyle::default()
+                },
+                html::Kind::Item { level, number } => ParaStyle {
+                    list_level: Some(level),
+                    list_label: number.map(|n| format!("{n}.")),
+                    ..ParaStyle::default()
+                },
+            };
+            let runs = spans
+                .into_iter()
+                .map(|span| Run {
+                    bold: span.bold,
+                    italic: span.italic,
+                    link_url: span.link,
+                    ..Run::plain(&span.text)
+                })
+                .collect();
+            Block::Paragraph { style, runs }
+        }
+        html::Block::Table { rows } => Block::Table {
+            rows: rows
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|cell| cell.into_iter().map(html_block).collect())
+                        .collect()
+                })
+                .collect(),
+        },
+    }
+}
+
+/// An embedded object's placeholder, `[Object: name (ProgID)]`, followed
+/// with `--embedded` by the content of the file, if batdoc can read it.
+fn object_blocks(
+    data: Option<Vec<u8>>,
+    path: &str,
+    prog_id: Option<&str>,
+    opts: &Options,
+) -> Vec<Block> {
+    let embedded = data.map(embedded::unwrap);
+    let part_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let name = embedded
+        .as_ref()
+        .and_then(|e| e.name.as_deref())
+        .unwrap_or(part_name);
+    let placeholder = prog_id.map_or_else(
+        || format!("[Object: {name}]"),
+        |prog_id| format!("[Object: {name} ({prog_id})]"),
+    );
+    let mut blocks = vec![Block::Paragraph {
+        style: ParaStyle::default(),
+        runs: vec![Run::plain(&placeholder)],
+    }];
+    if let (true, Some(embedded)) = (opts.embedded, embedded) {
+        let nested = Options {
+            embedded: true,
+            cancel: opts.cancel.clone(),
+            ..Options::default()
+        };
+        let content = detect_format(&embedded.data)
+            .and_then(|format| document::extract_blocks(&embedded.data, format, &nested));
+        if let Ok(content) = content {
+            blocks.extend(document_blocks(content));
+        }
+    }
+    blocks
+}
+
+/// Blocks of another document as `.docx` blocks: headings, paragraphs,
+/// and list items with their text, and tables with their sheet name in
+/// bold above them.
+fn document_blocks(content: Vec<document::Block>) -> Vec<Block> {
+    let para = |style, text: &str| Block::Paragraph {
+        style,
+        runs: vec![Run::plain(text)],
+    };
+    let mut blocks = Vec::new();
+    // Last number used at each level of an ordered list
+    let mut numbers: Vec<u32> = Vec::new();
+    for block in content {
+        match block {
+            document::Block::ListItem {
+                level,
+                ordered,
+                text,
+                ..
+            } => {
+                let depth = usize::from(level);
+                numbers.resize(depth + 1, 0);
+                numbers[depth] += 1;
+                let style = ParaStyle {
+                    list_level: Some(level),
+                    list_label: ordered.then(|| format!("{}.", numbers[depth])),
+                    ..ParaStyle::default()
                 };
-                let t = t.trim().to_string();
-                if t.is_empty() {
-                    None
+                blocks.push(para(style, &text));
+                continue;
+            }
+            document::Block::Heading { level, text } => {
+                let style = ParaStyle {
+                    heading_level: level,
+                    ..ParaStyle::default()
+                };
+                blocks.push(para(style, &text));
+            }
+            document::Block::Paragraph { text, .. } => {
+                blocks.push(para(ParaStyle::default(), &text));
+            }
+            document::Block::Table { name, rows } => {
+                if let Some(name) = name {
+                    blocks.push(Block::Paragraph {
+                        style: ParaStyle::default(),
+                        runs: vec![Run {
+                            bold: true,
+                            ..Run::plain(&name)
+                        }],
+                    });
+                }
+                let rows = rows
+                    .into_iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|text| vec![para(ParaStyle::default(), text)])
+                            .collect()
+                    })
+                    .collect();
+                blocks.push(Block::Table { rows });
+            }
+            document::Block::Slide { .. } | document::Block::Page { .. } => {}
+        }
+        numbers.clear();
+    }
+    blocks
+}
+
+// ── Code ──────────────────────────────────────────────────────────
+
+/// Whether a paragraph is a line of code: not a heading or list item, and
+/// either in a code style or with all its text in code runs.
+fn is_code_line(style: &ParaStyle, runs: &[Run]) -> bool {
+    let has_text = runs.iter().any(|r| !r.text.trim().is_empty());
+    style.heading_level == 0
+        && style.list_level.is_none()
+        && (style.code || (has_text && runs.iter().all(|r| r.code || r.text.trim().is_empty())))
+}
+
+/// Join each stretch of code paragraphs into one code block paragraph,
+/// the lines separated by line breaks. Blank paragraphs between code
+/// lines stay in as blank lines.
+fn resolve_code_blocks(blocks: Vec<Block>) -> Vec<Block> {
+    let mut out: Vec<Block> = Vec::with_capacity(blocks.len());
+    // Whether the last block in `out` is a code block
+    let mut in_code = false;
+    // Blank paragraphs after a code block, kept in it if more code follows
+    let mut blanks: Vec<Block> = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Paragraph { mut style, runs } if is_code_line(&style, &runs) => {
+                if let (true, Some(Block::Paragraph { runs: code, .. })) = (in_code, out.last_mut())
+                {
+                    for _ in 0..=blanks.len() {
+                        code.push(Run::plain("\n"));
+                    }
+                    blanks.clear();
+                    code.extend(runs);
                 } else {
-                    Some(t)
+                    style.code = true;
+                    out.push(Block::Paragraph { style, runs });
+                    in_code = true;
                 }
             }
-            Block::Table { .. } | Block::Image { .. } => None,
-        })
-        .collect::<Vec<_>>()
-        .join(" ")
+            Block::Paragraph { ref runs, .. }
+                if in_code && runs.iter().all(|r| r.text.trim().is_empty()) =>
+            {
+                blanks.push(block);
+            }
+            other => {
+                out.append(&mut blanks);
+                out.push(other);
+                in_code = false;
+            }
+        }
+    }
+    out.append(&mut blanks);
+    out
 }
 
-// ── Plain text rendering ──────────────────────────────────────────
+/// Append `code` as a fenced code block, its fence longer than any run of
+/// backticks inside.
+fn push_code_block(code: &str, out: &mut String) {
+    end_list(out);
+    let fence = "`".repeat(markup::longest_backtick_run(code).max(2) + 1);
+    out.push_str(&fence);
+    out.push('\n');
+    out.push_str(code);
+    out.push('\n');
+    out.push_str(&fence);
+    out.push_str("\n\n");
+}
+
+// ── Tracked changes ───────────────────────────────────────────────
+
+/// Apply `mode` to the tracked changes in `blocks`: drop deleted text
+/// (`final`), drop inserted text (`original`), or keep both between
+/// `[-…-]` and `{+…+}` markers like `git diff --word-diff` (`marked`).
+fn resolve_revisions(blocks: &mut [Block], mode: Revisions) {
+    for block in blocks {
+        match block {
+            Block::Paragraph { runs, .. } => {
+                if runs.iter().all(|r| r.revision.is_none()) {
+                    continue;
+                }
+                let old = std::mem::take(runs);
+                let mut open: Option<Revision> = None;
+                for run in old {
+                    match mode {
+                        Revisions::Final if run.revision == Some(Revision::Deleted) => {}
+                        Revisions::Original if run.revision == Some(Revision::Inserted) => {}
+                        Revisions::Final | Revisions::Original => runs.push(run),
+                        Revisions::Marked => {
+                            if run.revision != open {
+                                if let Some(close) = open {
+                                    runs.push(Run::plain(revision_marker(close).1));
+                                }
+                                if let Some(start) = run.revision {
+                                    runs.push(Run::plain(revision_marker(start).0));
+                                }
+                                open = run.revision;
+                            }
+                            runs.push(run);
+                        }
+                    }
+                }
+                if let Some(close) = open {
+                    runs.push(Run::plain(revision_marker(close).1));
+                }
+            }
+            Block::Table { rows } => {
+                for cell in rows.iter_mut().flatten() {
+                    resolve_revisions(cell, mode);
+                }
+            }
+            Block::Image { .. } | Block::Comment { .. } | Block::Graphic(_) => {}
+        }
+    }
+}
+
+/// The opening and closing markers of a change in `--revisions marked`.
+const fn revision_marker(revision: Revision) -> (&'static str, &'static str) {
+    match revision {
+        Revision::Inserted => ("{+", "+}"),
+        Revision::Deleted => ("[-", "-]"),
+    }
+}
+
+/// Fit the strikethrough, underline, and highlight of runs to `mode`
+/// before they are rendered as markdown: with `Emphasis`, underlined runs
+/// become italic and highlighted ones bold; with `None`, all three are
+/// dropped.
+fn resolve_marks(blocks: &mut [Block], mode: Marks) {
+    if mode == Marks::Extended {
+        return;
+    }
+    for block in blocks {
+        match block {
+            Block::Paragraph { runs, .. } => {
+                for run in runs {
+                    if mode == Marks::Emphasis {
+                        run.italic |= run.underline && run.link_url.is_none();
+                        run.bold |= run.highlight.is_some();
+                    } else {
+                        run.strike = false;
+                    }
+                    run.underline = false;
+                    run.highlight = None;
+                }
+            }
+            Block::Table { rows } => {
+                for cell in rows.iter_mut().flatten() {
+                    resolve_marks(cell, mode);
+                }
+            }
+            Block::Image { .. } | Block::Comment { .. } | Block::Graphic(_) => {}
+        }
+    }
+}
+
+/// Write superscript and subscript runs in Unicode superscript and
+/// subscript characters (`x²`, `H₂O`) for plain text, where every
+/// character has one; others keep their text as is.
+fn resolve_scripts(blocks: &mut [Block]) {
+    for block in blocks {
+        match block {
+            Block::Paragraph { runs, .. } => {
+                for run in runs {
+                    let text = match run.vert_align.take() {
+                        Some(VertAlign::Superscript) => symbols::to_superscript(&run.text),
+                        Some(VertAlign::Subscript) => symbols::to_subscript(&run.text),
+                        None => None,
+                    };
+                    if let Some(text) = text {
+                        run.text = text;
+                    }
+                }
+            }
+            Block::Table { rows } => {
+                for cell in rows.iter_mut().flatten() {
+                    resolve_scripts(cell);
+                }
+            }
+            Block::Image { .. } | Block::Comment { .. } | Block::Graphic(_) => {}
+        }
+    }
+}
+
+// ── Comments ──────────────────────────────────────────────────────
+
+/// A comment from `word/comments.xml`.
+#[derive(Debug, PartialEq, Eq)]
+struct Comment {
+    author: String,
+    /// The text of its paragraphs, joined by spaces.
+    text: String,
+}
+
+/// Read the comments of `word/comments.xml`, by `w:id`.
+fn parse_comments(xml: &str) -> HashMap<String, Comment> {
+    let mut comments = HashMap::new();
+    let mut reader = Reader::from_str(xml);
+    let mut current: Option<(String, Comment)> = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"comment" => {
+                    current = get_attr(e, b"w:id").map(|id| {
+                        let author = unescaped_attr(e, b"w:author").unwrap_or_default();
+                        let text = String::new();
+                        (id, Comment { author, text })
+                    });
+                }
+                b"p" => {
+                    let mut fields = FieldStack::default();
+                    let blocks =
+                        parse_paragraph(&mut reader, &Rels::new(), &Rels::new(), &mut fields);
+                    if let (Some((_, comment)), Some(Block::Paragraph { runs, .. })) =
+                        (&mut current, blocks.first())
+                    {
+                        let text: String = runs
+                            .iter()
+                            .filter(|r| r.revision != Some(Revision::Deleted))
+                            .map(|r| r.text.as_str())
+                            .collect();
+                        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                        if !text.is_empty() {
+                            if !comment.text.is_empty() {
+                                comment.text.push(' ');
+                            }
+                            comment.text.push_str(&text);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"comment" => {
+                comments.extend(current.take());
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    comments
+}
+
+/// Longest quote of commented text in the `--comments end` section.
+const QUOTE_CHARS: usize = 60;
+
+/// Places comments at their anchors in document order: after the
+/// paragraph, or as a `[cN]` mark with the comment in an appendix.
+struct CommentPlacer {
+    comments: HashMap<String, Comment>,
+    placement: Comments,
+    /// Ranges started but not yet ended, with the text they cover so far.
+    open: Vec<(String, String)>,
+    placed: HashSet<String>,
+    /// The `--comments end` entries, in order.
+    entries: Vec<Block>,
+}
+
+impl CommentPlacer {
+    fn new(comments: HashMap<String, Comment>, placement: Comments) -> Self {
+        Self {
+            comments,
+            placement,
+            open: Vec::new(),
+            placed: HashSet::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// `blocks` with each comment placed at the end of its range.
+    fn place(&mut self, blocks: Vec<Block>) -> Vec<Block> {
+        let mut out = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            match block {
+                Block::Paragraph { style, mut runs } => {
+                    let mut inline = Vec::new();
+                    for run in &mut runs {
+                        match run.comment.clone() {
+                            Some(CommentMark::Start(id)) => self.open.push((id, String::new())),
+                            Some(CommentMark::End(id)) => match self.end(&id) {
+                                Some(block) if self.placement == Comments::Inline => {
+                                    inline.push(block);
+                                }
+                                Some(entry) => {
+                                    self.entries.push(entry);
+                                    run.text = format!("[c{}]", self.entries.len());
+                                }
+                                None => {}
+                            },
+                            None => {
+                                for (_, quote) in &mut self.open {
+                                    quote.push_str(&run.text);
+                                }
+                            }
+                        }
+                    }
+                    for (_, quote) in &mut self.open {
+                        quote.push(' ');
+                    }
+                    out.push(Block::Paragraph { style, runs });
+                    out.append(&mut inline);
+                }
+                Block::Table { rows } => {
+                    let rows = rows
+                        .into_iter()
+                        .map(|row| row.into_iter().map(|cell| self.place(cell)).collect())
+                        .collect();
+                    out.push(Block::Table { rows });
+                }
+                other => out.push(other),
+            }
+        }
+        out
+    }
+
+    /// Close the range of comment `id` the first time it ends, and return
+    /// the comment as an inline block or as its entry for the end section.
+    fn end(&mut self, id: &str) -> Option<Block> {
+        let comment = self.comments.get(id)?;
+        if !self.placed.insert(id.to_string()) {
+            return None;
+        }
+        let quote = self
+            .open
+            .iter()
+            .position(|(open, _)| open == id)
+            .map(|i| self.open.remove(i).1);
+        let author = if comment.author.is_empty() {
+            "Comment"
+        } else {
+            &comment.author
+        };
+        if self.placement == Comments::Inline {
+            return Some(Block::Comment {
+                author: author.to_string(),
+                text: comment.text.clone(),
+            });
+        }
+        let mut runs = vec![
+            Run::plain(&format!("[c{}] ", self.entries.len() + 1)),
+            Run {
+                text: author.to_string(),
+                bold: true,
+                ..Run::default()
+            },
+        ];
+        let quote = quote.map(|q| q.split_whitespace().collect::<Vec<_>>().join(" "));
+        runs.push(Run::plain(&match quote.filter(|q| !q.is_empty()) {
+            Some(q) if q.chars().count() > QUOTE_CHARS => {
+                let cut: String = q.chars().take(QUOTE_CHARS).collect();
+                format!(" on \u{201c}{}…\u{201d}: ", cut.trim_end())
+            }
+            Some(q) => format!(" on \u{201c}{q}\u{201d}: "),
+            None => ": ".to_string(),
+        }));
+        runs.push(Run::plain(&comment.text));
+        Some(Block::Paragraph {
+            style: ParaStyle::default(),
+            runs,
+        })
+    }
+
+    /// The `--comments end` section: a heading and one paragraph per
+    /// comment, or nothing if no comment was placed.
+    fn appendix(&mut self) -> Vec<Block> {
+        if self.entries.is_empty() {
+            return Vec::new();
+        }
+        let heading = Block::Paragraph {
+            style: ParaStyle {
+                heading_level: 1,
+                ..ParaStyle::default()
+            },
+            runs: vec![Run::plain("Comments")],
+        };
+        std::iter::once(heading)
+            .chain(self.entries.drain(..))
+            .collect()
+    }
+}
+
+/// Number the list items in document order, and work out how far each
+/// is indented to sit under its parent's text. `widths` holds the marker
+/// widths of the open list levels.
+fn resolve_numbering(blocks: &mut [Block], numbering: &mut Numbering, widths: &mut Vec<usize>) {
+    for block in blocks {
+        match block {
+            Block::Paragraph { style, runs } => match (style.list_level, &style.num_id) {
+                (Some(level), Some(num_id)) => {
+                    style.list_label = numbering.next(num_id, level);
+                    let level = usize::from(level);
+                    widths.resize(level, 2);
+                    style.list_indent = Some(widths.iter().sum());
+                    // The marker and the space after it
+                    widths.push(style.list_label.as_ref().map_or(1, String::len) + 1);
+                }
+                _ if runs.iter().any(|r| !r.text.trim().is_empty()) => widths.clear(),
+                _ => {}
+            },
+            Block::Table { rows } => {
+                for cell in rows.iter_mut().flatten() {
+                    resolve_numbering(cell, numbering, &mut Vec::new());
+                }
+            }
+            Block::Image { .. } | Block::Comment { .. } | Block::Graphic(_) => {}
+        }
+    }
+}
+
+/// The value of a legacy form field, from its `<w:ffData>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FormField {
+    /// A `FORMCHECKBOX`, checked or not.
+    Checkbox(bool),
+    /// A `FORMDROPDOWN`, with the entry chosen.
+    Dropdown(String),
+}
+
+/// Read the children of a `<w:fldChar>` start tag and return the form
+/// field's value if its `<w:ffData>` describes a checkbox or a drop-down.
+///
+/// A checkbox's `<w:checked>` overrides `<w:default>`; both use OOXML
+/// boolean semantics. A drop-down's `<w:result>` (or else `<w:default>`)
+/// is the index of the chosen `<w:listEntry>`.
+fn parse_ffdata(reader: &mut Reader<&[u8]>) -> Option<FormField> {
+    let mut is_checkbox = false;
+    let mut in_dropdown = false;
+    let mut default = false;
+    let mut checked: Option<bool> = None;
+    let mut choice: Option<usize> = None;
+    let mut default_choice = 0;
+    let mut entries: Vec<String> = Vec::new();
+    let mut depth = 1u32;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => {
+                depth += 1;
+                match e.local_name().as_ref() {
+                    b"checkBox" => is_checkbox = true,
+                    b"ddList" => in_dropdown = true,
+                    _ => {}
+                }
+            }
+            Ok(Event::Empty(ref e)) => match e.local_name().as_ref() {
+                b"checkBox" => is_checkbox = true,
+                b"default" if in_dropdown => {
+                    default_choice = get_val_attr(e).and_then(|v| v.parse().ok()).unwrap_or(0);
+                }
+                b"default" => default = is_on(e),
+                b"checked" => checked = Some(is_on(e)),
+                b"result" if in_dropdown => choice = get_val_attr(e).and_then(|v| v.parse().ok()),
+                b"listEntry" if in_dropdown => entries.extend(get_val_attr(e)),
+                _ => {}
+            },
+            Ok(Event::End(_)) => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    if is_checkbox {
+        return Some(FormField::Checkbox(checked.unwrap_or(default)));
+    }
+    let mut entries = entries.into_iter();
+    in_dropdown
+        .then(|| entries.nth(choice.unwrap_or(default_choice)))
+        .flatten()
+        .map(FormField::Dropdown)
+}
+
+/// What the `<w:sdtPr>` of a content control says about it.
+#[derive(Debug, Default)]
+struct SdtProps {
+    /// `<w:alias>` (the title shown in Word), or else `<w:tag>`.
+    label: Option<String>,
+    /// Set for a `<w14:checkbox>`, to its checked state.
+    checkbox: Option<bool>,
+    /// `<w:showingPlcHdr>`: the content is placeholder text such as
+    /// "Click or tap here to enter text."
+    placeholder: bool,
+}
+
+/// Parse `<w:sdtPr>` for the label, checkbox state, and placeholder flag
+/// of a content control.
+fn parse_sdt_props(reader: &mut Reader<&[u8]>) -> SdtProps {
+    let mut props = SdtProps::default();
+    let mut is_checkbox = false;
+    let mut checked = false;
+    let mut tag = None;
+    let mut depth = 1u32;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => {
+                depth += 1;
+                if e.local_name().as_ref() == b"checkbox" {
+                    is_checkbox = true;
+                }
+            }
+            Ok(Event::Empty(ref e)) => match e.local_name().as_ref() {
+                b"checked" => {
+                    let val = get_attr(e, b"w14:val").or_else(|| get_val_attr(e));
+                    checked = !matches!(val.as_deref(), Some("false" | "0"));
+                }
+                b"alias" => props.label = get_val_attr(e).filter(|v| !v.trim().is_empty()),
+                b"tag" => tag = get_val_attr(e).filter(|v| !v.trim().is_empty()),
+                b"showingPlcHdr" => props.placeholder = is_on(e),
+                _ => {}
+            },
+            Ok(Event::End(_)) => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    props.label = props.label.or(tag);
+    props.checkbox = is_checkbox.then_some(checked);
+    props
+}
+
+/// Content controls around paragraphs (`<w:sdt>` in the body or a table
+/// cell), as they are read.
+#[derive(Debug, Default)]
+struct BlockControls {
+    /// Open `<w:sdt>` elements.
+    depth: usize,
+    /// Depth of the outermost one showing placeholder text, whose
+    /// paragraphs are left out.
+    placeholder: Option<usize>,
+    /// Label of the control just opened, for its first paragraph.
+    label: Option<String>,
+}
+
+impl BlockControls {
+    /// Read the `<w:sdtPr>` of the innermost control.
+    fn read_props(&mut self, reader: &mut Reader<&[u8]>) {
+        let props = parse_sdt_props(reader);
+        if props.placeholder && self.placeholder.is_none() {
+            self.placeholder = Some(self.depth);
+        }
+        self.label = props.label;
+    }
+
+    /// Note the end of a `<w:sdt>`.
+    fn end(&mut self) {
+        if self.placeholder == Some(self.depth) {
+            self.placeholder = None;
+        }
+        self.depth = self.depth.saturating_sub(1);
+        self.label = None;
+    }
+
+    const fn showing_placeholder(&self) -> bool {
+        self.placeholder.is_some()
+    }
+
+    /// Put the label of the control a paragraph opens before its text.
+    fn label_first(&mut self, blocks: &mut [Block]) {
+        if let (Some(label), Some(Block::Paragraph { runs, .. })) =
+            (self.label.take(), blocks.first_mut())
+        {
+            runs.splice(0..0, Run::control_label(label));
+        }
+    }
+}
+
+/// Remove the runs naming content controls, unless `--control-labels`
+/// asks for them.
+fn drop_control_labels(blocks: &mut [Block]) {
+    for block in blocks {
+        match block {
+            Block::Paragraph { runs, .. } => runs.retain(|r| !r.label),
+            Block::Table { rows } => {
+                for cell in rows.iter_mut().flatten() {
+                    drop_control_labels(cell);
+                }
+            }
+            Block::Image { .. } | Block::Comment { .. } | Block::Graphic(_) => {}
+        }
+    }
+}
+
+/// What a run says about a picture in it, gathered from the elements of
+/// its `<w:drawing>` or `<w:pict>`.
+#[derive(Debug, Default)]
+struct Picture {
+    /// `r:embed` of `<a:blip>`, or `r:id` of `<v:imagedata>`.
+    rid: Option<String>,
+    /// `descr` or `title` of `<wp:docPr>`, or `alt` or `o:title` in VML.
+    description: Option<String>,
+    /// `name` of `<wp:docPr>`, such as `Picture 3`.
+    name: Option<String>,
+}
+
+impl Picture {
+    fn read(&mut self, e: &quick_xml::events::BytesStart) {
+        let non_empty = |attr: &[u8]| unescaped_attr(e, attr).filter(|v| !v.trim().is_empty());
+        match e.local_name().as_ref() {
+            b"docPr" => {
+                self.description = non_empty(b"descr").or_else(|| non_empty(b"title"));
+                self.name = non_empty(b"name");
+            }
+            b"blip" => self.rid = get_attr(e, b"r:embed").or_else(|| get_attr(e, b"r:link")),
+            b"shape" => self.description = non_empty(b"alt"),
+            b"imagedata" => {
+                self.rid = get_attr(e, b"r:id");
+                if self.description.is_none() {
+                    self.description = non_empty(b"o:title");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The `Block::Image` for `picture`: its ZIP path, if `image_rels` has
+/// its relationship, and its alternative text.
+fn image_block(picture: Picture, image_rels: &Rels) -> Block {
+    // Store the resolved ZIP path as a placeholder — will be replaced with
+    // actual base64 content in resolve_images()
+    let zip_path = picture
+        .rid
+        .and_then(|rid| image_rels.get(&rid))
+        .cloned()
+        .unwrap_or_default();
+    let described = picture.description.is_some();
+    let alt = picture.description.or(picture.name).unwrap_or_default();
+    Block::Image {
+        markdown: zip_path,
+        alt: alt.split_whitespace().collect::<Vec<_>>().join(" "),
+        described,
+    }
+}
+
+/// Give each image without a description the text of the caption below
+/// it, if there is one, as its alternative text.
+fn resolve_captions(blocks: &mut [Block]) {
+    for i in 0..blocks.len() {
+        if let Block::Table { rows } = &mut blocks[i] {
+            for cell in rows.iter_mut().flatten() {
+                resolve_captions(cell);
+            }
+            continue;
+        }
+        if !matches!(
+            blocks[i],
+            Block::Image {
+                described: false,
+                ..
+            }
+        ) {
+            continue;
+        }
+        let caption = blocks[i + 1..]
+            .iter()
+            .find(|b| !matches!(b, Block::Image { .. }))
+            .and_then(|b| match b {
+                Block::Paragraph { style, runs } if style.caption => {
+                    let text: String = runs.iter().map(|r| r.text.as_str()).collect();
+                    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    (!text.is_empty()).then_some(text)
+                }
+                _ => None,
+            });
+        if let (Some(caption), Block::Image { alt, .. }) = (caption, &mut blocks[i]) {
+            *alt = caption;
+        }
+    }
+}
+
+/// An image's alternative text with the brackets escaped, for use in
+/// `![alt]`.
+fn escape_alt(alt: &str) -> String {
+    let mut out = String::with_capacity(alt.len());
+    for c in alt.chars() {
+        if matches!(c, '\\' | '[' | ']') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// An image in markdown: its reference with `--images`, else `![alt]`.
+fn image_markdown(markdown: &str, alt: &str) -> String {
+    if markdown.is_empty() {
+        format!("![{}]", escape_alt(alt))
+    } else {
+        markdown.to_string()
+    }
+}
+
+/// An image in plain text, as `[Image: alt]`.
+fn image_note(alt: &str) -> String {
+    if alt.is_empty() {
+        "[Image]".to_string()
+    } else {
+        format!("[Image: {alt}]")
+    }
+}
+
+/// Resolve `Block::Image` placeholders by reading image data from the ZIP
+/// archive and converting to reference-style base64 images.
+///
+/// Each image gets a unique label (`image1`, `image2`, ...), numbered on
+/// from `counter`. The `markdown`
+/// field is replaced with the inline reference (`![alt][image1]`), and the
+/// corresponding definitions are collected for appending at document end.
+///
+/// Images with unsupported formats (EMF, WMF, etc.) or missing data are
+/// left to be shown by their alternative text.
+fn resolve_images(
+    blocks: &mut [Block],
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    counter: &mut usize,
+) -> Vec<String> {
+    let mut definitions = Vec::new();
+
+    for block in blocks.iter_mut() {
+        if let Block::Image { markdown, alt, .. } = block {
+            let zip_path = std::mem::take(markdown);
+            if zip_path.is_empty() {
+                continue;
+            }
+            if let Some(data) = xml_util::read_image_from_zip(archive, &zip_path, "") {
+                *counter += 1;
+                let id = format!("image{counter}");
+                if let Some(img_ref) = crate::markup::image_to_base64_ref(&data, &id) {
+                    *markdown = format!("![{}][{id}]", escape_alt(alt));
+                    definitions.push(img_ref.definition);
+                }
+            }
+        }
+    }
+
+    definitions
+}
+
+/// Parse a `<w:tbl>` element into a `Block::Table`.
+///
+/// Merged cells are spread over the grid so that every row has a cell per
+/// column: a cell spanning columns (`w:gridSpan`) is followed by empty
+/// cells, and one continuing a vertical merge (`w:vMerge`) repeats the
+/// text of the cell above it.
+fn parse_table(reader: &mut Reader<&[u8]>, rels: &Rels) -> Block {
+    let mut rows: Vec<Row> = Vec::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => {
+                let name = e.local_name();
+                if name.as_ref() == b"tr" {
+                    let (mut row, merged_up) = parse_table_row(reader, rels);
+                    if let Some(above) = rows.last() {
+                        for (col, cell) in row.iter_mut().enumerate() {
+                            if let (true, Some(above)) = (merged_up[col], above.get(col)) {
+                                cell.clone_from(above);
+                            }
+                        }
+                    }
+                    rows.push(row);
+                }
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"tbl" => {
+                break;
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    Block::Table { rows }
+}
+
+/// Parse a `<w:tr>` element into a row of cells, one per grid column,
+/// and whether each continues a vertical merge from the row above.
+fn parse_table_row(reader: &mut Reader<&[u8]>, rels: &Rels) -> (Row, Vec<bool>) {
+    let mut cells: Row = Vec::new();
+    let mut merged_up = Vec::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => {
+                let name = e.local_name();
+                if name.as_ref() == b"tc" {
+                    let (cell, span) = parse_table_cell(reader, rels);
+                    cells.push(cell);
+                    cells.resize_with(cells.len() + span.columns.max(1) - 1, Vec::new);
+                    merged_up.resize(cells.len(), span.merged_up);
+                }
+            }
+            // Grid columns left out before the first cell
+            Ok(Event::Empty(ref e))
+                if e.local_name().as_ref() == b"gridBefore" && cells.is_empty() =>
+            {
+                let before = get_val_attr(e)
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0)
+                    .min(MAX_COLUMNS);
+                cells.resize_with(before, Vec::new);
+                merged_up.resize(before, false);
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"tr" => {
+                break;
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    (cells, merged_up)
+}
+
+/// Columns a Word table can have, which limits how far a damaged
+/// `w:gridSpan` can pad a row.
+const MAX_COLUMNS: usize = 63;
+
+/// Where a `<w:tc>` sits in the table grid, from its `<w:tcPr>`.
+#[derive(Debug, Clone, Copy, Default)]
+struct CellSpan {
+    /// `w:gridSpan`: the grid columns the cell covers.
+    columns: usize,
+    /// `<w:vMerge/>` without `restart`: the cell continues the one above.
+    merged_up: bool,
+}
+
+/// Parse a `<w:tc>` element into a list of blocks.
+///
+/// Images inside table cells are not extracted (impractical in markdown
+/// tables), so an empty `image_rels` is used for paragraph parsing.
+fn parse_table_cell(reader: &mut Reader<&[u8]>, rels: &Rels) -> (Cell, CellSpan) {
+    let empty_image_rels = xml_util::Rels::new();
+    let mut blocks = Vec::new();
+    let mut controls = BlockControls::default();
+    let mut fields = FieldStack::default();
+    let mut span = CellSpan::default();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => {
+                let name = e.local_name();
+                match name.as_ref() {
+                    b"sdt" => controls.depth += 1,
+                    b"sdtPr" => controls.read_props(reader),
+                    b"p" if controls.showing_placeholder() => {
+                        let _ = reader.read_to_end(e.name());
+                    }
+                    b"p" => {
+                        let mut para_blocks =
+                            parse_paragraph(reader, rels, &empty_image_rels, &mut fields);
+                        controls.label_first(&mut para_blocks);
+                        blocks.append(&mut para_blocks);
+                    }
+                    b"tbl" => blocks.push(parse_table(reader, rels)), // nested table
+                    _ => {}
+                }
+            }
+            Ok(Event::Empty(ref e)) => match e.local_name().as_ref() {
+                b"gridSpan" => {
+                    span.columns = get_val_attr(e)
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(1)
+                        .min(MAX_COLUMNS);
+                }
+                b"vMerge" => span.merged_up = get_val_attr(e).as_deref() != Some("restart"),
+                b"altChunk" => blocks.extend(Graphic::from_element(e).map(Block::Graphic)),
+                _ => {}
+            },
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"sdt" => controls.end(),
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"tc" => {
+                break;
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    (blocks, span)
+}
+
+/// Get the `w:val` (or `val`) attribute value from an XML element.
+fn get_val_attr(e: &quick_xml::events::BytesStart) -> Option<String> {
+    get_attr(e, b"w:val").or_else(|| get_attr(e, b"val"))
+}
+
+/// OOXML on/off property: present means on unless `w:val` is false or 0.
+fn is_on(e: &quick_xml::events::BytesStart) -> bool {
+    !matches!(get_val_attr(e).as_deref(), Some("false" | "0"))
+}
+
+/// Extract text content from a cell's blocks, joining paragraphs with spaces.
+fn cell_to_text(cell: &[Block], use_markdown: bool) -> String {
+    cell.iter()
+        .filter_map(|b| match b {
+            Block::Paragraph { runs, .. } => {
+                let t = if use_markdown {
+                    render_runs_markdown(runs)
+                } else {
+                    runs.iter().map(|r| r.text.as_str()).collect::<String>()
+                };
+                let t = t.trim().to_string();
+                if t.is_empty() {
+                    None
+                } else {
+                    Some(t)
+                }
+            }
+            Block::Comment { author, text } => Some(comment_note(author, text)),
+            Block::Image { markdown, alt, .. } => Some(if use_markdown {
+                image_markdown(markdown, alt)
+            } else {
+                image_note(alt)
+            }),
+            Block::Table { .. } | Block::Graphic(_) => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Convert a parsed block to the public representation; empty paragraphs
+/// and images are dropped.
+fn to_document_block(block: &Block) -> Option<document::Block> {
+    match block {
+        Block::Paragraph { style, runs } => {
+            let text: String = runs.iter().map(|r| r.text.as_str()).collect();
+            let text = text.trim_end().to_string();
+            if text.is_empty() {
+                None
+            } else if style.heading_level > 0 {
+                Some(document::Block::Heading {
+                    level: style.heading_level,
+                    text,
+                })
+            } else if let Some(level) = style.list_level {
+                let spans = styled_spans(runs, text.len());
+                Some(document::Block::ListItem {
+                    level,
+                    ordered: style.list_label.is_some(),
+                    text,
+                    spans,
+                })
+            } else {
+                let spans = styled_spans(runs, text.len());
+                Some(document::Block::Paragraph { text, spans })
+            }
+        }
+        Block::Table { rows } => Some(document::Block::Table {
+            name: None,
+            rows: rows
+                .iter()
+                .map(|row| row.iter().map(|cell| cell_to_text(cell, false)).collect())
+                .collect(),
+        }),
+        Block::Comment { author, text } => Some(document::Block::Paragraph {
+            text: comment_note(author, text),
+            spans: Vec::new(),
+        }),
+        Block::Image { .. } | Block::Graphic(_) => None,
+    }
+}
+
+/// A comment as `[Author: text]`.
+fn comment_note(author: &str, text: &str) -> String {
+    format!("[{author}: {text}]")
+}
+
+/// Spans of colored or highlighted runs within the first `len` bytes of
+/// the runs' joined text, merging neighbours styled alike.
+fn styled_spans(runs: &[Run], len: usize) -> Vec<document::Span> {
+    let mut spans: Vec<document::Span> = Vec::new();
+    let mut pos = 0;
+    for run in runs {
+        let start = pos;
+        pos += run.text.len();
+        let end = pos.min(len);
+        if start >= end || (run.color.is_none() && run.highlight.is_none()) {
+            continue;
+        }
+        match spans.last_mut() {
+            Some(last)
+                if last.range.end == start
+                    && last.color == run.color
+                    && last.highlight == run.highlight =>
+            {
+                last.range.end = end;
+            }
+            _ => spans.push(document::Span {
+                range: start..end,
+                color: run.color.clone(),
+                highlight: run.highlight.clone(),
+            }),
+        }
+    }
+    spans
+}
+
+// ── Streaming ─────────────────────────────────────────────────────
+
+/// Size of the main document part from which the command line streams a
+/// .docx (see [`is_large`]).
+const STREAM_MIN_BYTES: u64 = 32 << 20;
+
+/// How much body XML a streamed document is parsed at a time.
+const STREAM_BATCH_BYTES: usize = 1 << 20;
+
+/// Parse a .docx and resolve its blocks a batch of `batch` bytes of body
+/// XML at a time, handing each batch to `emit` once nothing later in the
+/// document can change it. The main part is decompressed as it is read.
+/// Returns the image reference definitions that end the document.
+fn stream_docx(
+    data: &[u8],
+    images: bool,
+    opts: &Options,
+    batch: usize,
+    emit: &mut dyn FnMut(Vec<Block>) -> crate::error::Result<()>,
+) -> crate::error::Result<Vec<String>> {
+    let mut archive = ZipArchive::new(Cursor::new(data))?;
+    let main = MainPart::locate(&mut archive);
+    let (rels, image_rels) = main.link_rels(&mut archive, images);
+    let mut resolver = Resolver::new(data, &main, opts, images)?;
+    // Links may come before the bookmarks they point at
+    resolver.targets = Some(link_targets(BufReader::new(archive.by_name(&main.path)?))?);
+
+    let mut splitter = BodySplitter::new(BufReader::new(archive.by_name(&main.path)?), batch);
+    let mut state = BodyState::default();
+    let mut budget = opts.budget;
+    let mut held: Vec<Block> = Vec::new();
+    while !budget.spent() {
+        let Some(xml) = splitter.next_batch()? else {
+            break;
+        };
+        let mut reader = Reader::from_reader(xml.as_slice());
+        // Content controls are cut open, their end tags apart from their
+        // start, which may be in an earlier batch; `state` keeps track of them
+        let config = reader.config_mut();
+        config.check_end_names = false;
+        config.allow_unmatched_ends = true;
+        let mut blocks = Vec::new();
+        parse_body(
+            &mut reader,
+            &mut blocks,
+            &mut true,
+            &rels,
+            &image_rels,
+            &mut budget,
+            &mut state,
+        );
+        held.append(&mut resolver.resolve(blocks));
+        let ready: Vec<Block> = held.drain(..open_tail(&held)).collect();
+        emit(resolver.settle(ready))?;
+    }
+    let mut blocks = resolver.settle(held);
+    blocks.append(&mut resolver.finish());
+    emit(blocks)?;
+    Ok(resolver.image_defs)
+}
+
+/// Where the blocks that a later one may still change begin: the images
+/// at the end, whose caption may follow, and the code lines and blank
+/// paragraphs, which may join more code.
+fn open_tail(blocks: &[Block]) -> usize {
+    blocks
+        .iter()
+        .rposition(|block| match block {
+            Block::Image { .. } => false,
+            Block::Paragraph { style, runs } => {
+                !is_code_line(style, runs) && runs.iter().any(|r| !r.text.trim().is_empty())
+            }
+            _ => true,
+        })
+        .map_or(0, |i| i + 1)
+}
+
+/// The lowercased names of the bookmarks some internal link points at,
+/// read from the whole document XML ahead of streaming it (see
+/// [`bookmark_targets`]).
+///
+/// This is a second pass over the largest part of the file, so rather
+/// than parse it, it looks for the few elements that matter and parses
+/// only those: every other tag is passed over once its `>` is found.
+fn link_targets(mut source: impl BufRead) -> std::io::Result<HashSet<String>> {
+    let mut scan = LinkScan::default();
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        let chunk = source.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        buf.extend_from_slice(chunk);
+        let len = chunk.len();
+        source.consume(len);
+        let done = scan.feed(&buf);
+        buf.drain(..done);
+    }
+    scan.targets.retain(|t| scan.names.contains(t));
+    Ok(scan.targets)
+}
+
+/// What [`link_targets`] has found so far.
+#[derive(Default)]
+struct LinkScan {
+    names: HashSet<String>,
+    targets: HashSet<String>,
+    /// The instruction of the complex field being read, after its
+    /// `<w:instrText>` and before its next `<w:fldChar>`.
+    instr: Option<String>,
+}
+
+impl LinkScan {
+    /// Read the whole tags in `xml`, and the text before each, returning
+    /// how much of it was read; the rest waits for more.
+    fn feed(&mut self, xml: &[u8]) -> usize {
+        let mut pos = 0;
+        while let Some(lt) = xml[pos..].iter().position(|&b| b == b'<').map(|i| pos + i) {
+            let Some(gt) = tag_end(xml, lt) else {
+                break;
+            };
+            if let Some(instr) = &mut self.instr {
+                if let Ok(text) = std::str::from_utf8(&xml[pos..lt]) {
+                    instr.push_str(&quick_xml::escape::unescape(text).unwrap_or(text.into()));
+                }
+            }
+            self.tag(&xml[lt + 1..gt]);
+            pos = gt + 1;
+        }
+        pos
+    }
+
+    /// Note a tag, given what is between its `<` and `>`.
+    fn tag(&mut self, content: &[u8]) {
+        if matches!(content.first(), Some(b'/' | b'?' | b'!')) {
+            return;
+        }
+        let content = content.strip_suffix(b"/").unwrap_or(content);
+        let name_len = content
+            .iter()
+            .position(|b| b.is_ascii_whitespace())
+            .unwrap_or(content.len());
+        let name = &content[..name_len];
+        let local = name.rsplit(|&b| b == b':').next().unwrap_or(name);
+        if !matches!(
+            local,
+            b"bookmarkStart" | b"hyperlink" | b"fldSimple" | b"instrText" | b"fldChar"
+        ) {
+            return;
+        }
+        let Ok(content) = std::str::from_utf8(content) else {
+            return;
+        };
+        let e = quick_xml::events::BytesStart::from_content(content, name_len);
+        let field_target = |instr: &str| {
+            Some(
+                fields::hyperlink_url(instr)?
+                    .strip_prefix('#')?
+                    .to_lowercase(),
+            )
+        };
+        match local {
+            b"bookmarkStart" => {
+                self.names
+                    .extend(get_attr(&e, b"w:name").map(|n| n.to_lowercase()));
+            }
+            b"hyperlink" if get_attr(&e, b"r:id").is_none() => {
+                self.targets
+                    .extend(get_attr(&e, b"w:anchor").map(|a| a.to_lowercase()));
+            }
+            b"fldSimple" => {
+                if let [_, FieldMark::Instr(text), _] = simple_field(&e) {
+                    self.targets.extend(field_target(&text));
+                }
+            }
+            b"instrText" => {
+                self.instr.get_or_insert_with(String::new);
+            }
+            b"fldChar" => {
+                if let Some(text) = self.instr.take() {
+                    self.targets.extend(field_target(&text));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The `>` closing the tag that opens at `xml[lt]`, passing over any in
+/// quoted attribute values, or `None` if the tag runs past the end.
+fn tag_end(xml: &[u8], lt: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, &b) in xml.iter().enumerate().skip(lt + 1) {
+        match (quote, b) {
+            (None, b'>') => return Some(i),
+            (None, b'"' | b'\'') => quote = Some(b),
+            (Some(q), b) if b == q => quote = None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Cuts the XML of a document body into batches of whole top-level
+/// elements (paragraphs, tables), so that a long document can be parsed
+/// a batch at a time. Content controls around paragraphs are cut open,
+/// so that one around the whole body doesn't make a single batch of it:
+/// their properties come whole, their start and end tags on their own.
+struct BodySplitter<R: BufRead> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+    /// Least XML in a batch, unless the body ends first.
+    batch: usize,
+    in_body: bool,
+    done: bool,
+}
+
+impl<R: BufRead> BodySplitter<R> {
+    fn new(source: R, batch: usize) -> Self {
+        Self {
+            reader: Reader::from_reader(source),
+            buf: Vec::new(),
+            batch,
+            in_body: false,
+            done: false,
+        }
+    }
+
+    /// The XML of the next batch, or `None` after the end of the body.
+    fn next_batch(&mut self) -> crate::error::Result<Option<Vec<u8>>> {
+        let mut writer = quick_xml::Writer::new(Vec::new());
+        // Depth within the top-level element being copied
+        let mut depth = 0usize;
+        while !self.done && (depth > 0 || writer.get_ref().len() < self.batch) {
+            self.buf.clear();
+            let event = self
+                .reader
+                .read_event_into(&mut self.buf)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            let container = |name: &[u8]| matches!(name, b"sdt" | b"sdtContent");
+            let event = match event {
+                Event::Eof => {
+                    self.done = true;
+                    continue;
+                }
+                Event::Start(e) if !self.in_body => {
+                    self.in_body = e.local_name().as_ref() == b"body";
+                    continue;
+                }
+                _ if !self.in_body => continue,
+                Event::End(e) if depth == 0 && e.local_name().as_ref() == b"body" => {
+                    self.done = true;
+                    continue;
+                }
+                Event::Start(e) if depth == 0 && container(e.local_name().as_ref()) => {
+                    Event::Start(e)
+                }
+                Event::End(e) if depth == 0 => Event::End(e),
+                Event::Text(_) if depth == 0 => continue,
+                Event::Start(e) => {
+                    depth += 1;
+                    Event::Start(e)
+                }
+                Event::End(e) => {
+                    depth -= 1;
+                    Event::End(e)
+                }
+                other => other,
+            };
+            writer.write_event(event)?;
+        }
+        let xml = writer.into_inner();
+        Ok((!xml.is_empty() || !self.done).then_some(xml))
+    }
+}
+
+// ── Plain text rendering ──────────────────────────────────────────
+
+fn render_plain(blocks: &[Block], boxed_tables: bool) -> String {
+    let mut out = String::new();
+    render_plain_into(blocks, boxed_tables, &mut out, &mut true);
+    out
+}
+
+/// Append the plain text of `blocks` to `out`; `first` says whether
+/// nothing has been written yet.
+fn render_plain_into(blocks: &[Block], boxed_tables: bool, out: &mut String, first: &mut bool) {
+    for block in blocks {
+        match block {
+            Block::Table { rows } if boxed_tables => render_table_boxed(rows, out, first),
+            _ => render_block_plain(block, out, first),
+        }
+    }
+}
+
+fn render_block_plain(block: &Block, out: &mut String, first: &mut bool) {
+    match block {
+        Block::Paragraph { style, runs } => {
+            let text: String = runs.iter().map(|r| r.text.as_str()).collect();
+            let text = text.trim_end();
+            if !text.is_empty() {
+                if !*first {
+                    out.push('\n');
+                }
+                // Numbers are part of the text, as in "see step 3"
+                if let Some(label) = &style.list_label {
+                    out.push_str(label);
+                    out.push(' ');
+                }
+                out.push_str(text);
+                out.push('\n');
+                *first = false;
+            }
+        }
+        Block::Table { rows } => {
+            for row in rows {
+                let cells: Vec<String> = row.iter().map(|cell| cell_to_text(cell, false)).collect();
+
+                let line = cells.join("\t");
+                let line = line.trim_end();
+                if !line.is_empty() {
+                    if !*first {
+                        out.push('\n');
+                    }
+                    out.push_str(line);
+                    out.push('\n');
+                    *first = false;
+                }
+            }
+        }
+        Block::Comment { author, text } => {
+            if !*first {
+                out.push('\n');
+            }
+            out.push_str(&comment_note(author, text));
+            out.push('\n');
+            *first = false;
+        }
+        Block::Image { alt, .. } => {
+            if !*first {
+                out.push('\n');
+            }
+            out.push_str(&image_note(alt));
+            out.push('\n');
+            *first = false;
+        }
+        Block::Graphic(_) => {}
+    }
+}
+
+/// Draw a table with box-drawing characters (`--table`).
+fn render_table_boxed(rows: &[Row], out: &mut String, first: &mut bool) {
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|cell| cell_to_text(cell, false)).collect())
+        .collect();
+    if cells.iter().flatten().all(String::is_empty) {
+        return;
+    }
+    if !*first {
+        out.push('\n');
+    }
+    crate::boxtable::render(&cells, out);
+    *first = false;
+}
+
+// ── Markdown rendering ────────────────────────────────────────────
+
+/// After a list item, start the next block on a line of its own, so that
+/// it doesn't continue the item.
+fn end_list(out: &mut String) {
+    if out.ends_with('\n') && !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+}
+
+fn render_markdown(blocks: &[Block]) -> String {
+    let mut out = String::new();
+
+    for block in blocks {
+        render_block_markdown(block, &mut out);
+    }
+
+    out
+}
+
+fn render_block_markdown(block: &Block, out: &mut String) {
+    match block {
+        Block::Paragraph { style, runs } => {
+            let text = render_runs_markdown(runs);
+            let text = text.trim_end();
+            if text.is_empty() {
+                return;
+            }
+            if !style.bookmarks.is_empty() {
+                for name in &style.bookmarks {
+                    out.push_str("<a id=\"");
+                    out.push_str(name);
+                    out.push_str("\"></a>");
+                }
+                out.push_str("\n\n");
+            }
+
+            if style.code {
+                let code: String = runs.iter().map(|r| r.text.as_str()).collect();
+                push_code_block(code.trim_matches('\n').trim_end(), out);
+            } else if let (0, Some((checkbox, rest))) = (style.heading_level, split_task_item(runs))
+            {
+                // A paragraph led by a checkbox is a task-list item
+                out.push_str(&list_indent(style));
+                out.push_str("- ");
+                out.push_str(&checkbox.text);
+                let rest = render_runs_markdown(rest);
+                let rest = rest.trim();
+                if !rest.is_empty() {
+                    out.push(' ');
+                    out.push_str(rest);
+                }
+                out.push('\n');
+            } else if style.heading_level > 0 && style.heading_level <= 6 {
+                for _ in 0..style.heading_level {
+                    out.push('#');
+                }
+                out.push(' ');
+                out.push_str(text);
+                out.push_str("\n\n");
+            } else if style.list_level.is_some() {
+                out.push_str(&list_indent(style));
+                out.push_str(style.list_label.as_deref().unwrap_or("-"));
+                out.push(' ');
+                out.push_str(text);
+                out.push('\n');
+            } else {
+                end_list(out);
+                out.push_str(text);
+                out.push_str("\n\n");
+            }
+        }
+        Block::Image { markdown, alt, .. } => {
+            out.push_str(&image_markdown(markdown, alt));
+            out.push_str("\n\n");
+        }
+        Block::Graphic(_) => {}
+        Block::Comment { author, text } => {
+            end_list(out);
+            out.push_str("> ");
+            out.push_str(&comment_note(author, text));
+            out.push_str("\n\n");
+        }
+        Block::Table { rows } => {
+            if rows.is_empty() {
+                return;
+            }
+
+            let ncols = rows.iter().map(Vec::len).max().unwrap_or(0);
+            if ncols == 0 {
+                return;
+            }
+
+            let mut md_rows: Vec<Vec<String>> = Vec::new();
+            for row in rows {
+                let mut md_row = Vec::new();
+                for cell in row {
+                    let cell_text = cell_to_text(cell, true);
+                    md_row.push(cell_text.replace('|', "\\|"));
+                }
+                while md_row.len() < ncols {
+                    md_row.push(String::new());
+                }
+                md_rows.push(md_row);
+            }
+
+            if let Some(header) = md_rows.first() {
+                end_list(out);
+                out.push_str("| ");
+                out.push_str(&header.join(" | "));
+                out.push_str(" |\n");
+
+                out.push('|');
+                for _ in 0..ncols {
+                    out.push_str(" --- |");
+                }
+                out.push('\n');
+
+                for row in md_rows.iter().skip(1) {
+                    out.push_str("| ");
+                    out.push_str(&row.join(" | "));
+                    out.push_str(" |\n");
+                }
+                out.push('\n');
+            }
+        }
+    }
+}
+
+/// The indent of a list item in markdown.
+fn list_indent(style: &ParaStyle) -> String {
+    let columns = style
+        .list_indent
+        .unwrap_or_else(|| 2 * usize::from(style.list_level.unwrap_or(0)));
+    " ".repeat(columns)
+}
+
+/// Render runs with markdown inline formatting (bold/italic/hyperlinks).
+///
+/// Adjacent runs sharing the same `link_url` are grouped so the markdown
+/// link wraps the entire visible text: `[text](url)` instead of producing
+/// separate `[part1](url)[part2](url)` fragments.
+/// If the paragraph's first non-blank run is a checkbox, split it from the
+/// runs that follow.
+fn split_task_item(runs: &[Run]) -> Option<(&Run, &[Run])> {
+    let idx = runs.iter().position(|r| !r.text.trim().is_empty())?;
+    runs[idx].checkbox.then(|| (&runs[idx], &runs[idx + 1..]))
+}
+
+fn render_runs_markdown(runs: &[Run]) -> String {
+    markup::render_runs_markdown(runs)
+}
+
+/// Implement [`InlineRun`] for docx `Run` so the shared markup renderer
+/// can inspect formatting without knowing the concrete type.
+impl markup::InlineRun for Run {
+    fn text(&self) -> &str {
+        &self.text
+    }
+    fn bold(&self) -> bool {
+        self.bold
+    }
+    fn italic(&self) -> bool {
+        self.italic
+    }
+    fn link_url(&self) -> Option<&str> {
+        self.link_url.as_deref()
+    }
+    fn struck(&self) -> bool {
+        self.strike
+    }
+    // Links are underlined by their character style anyway
+    fn underlined(&self) -> bool {
+        self.underline && self.link_url.is_none()
+    }
+    fn highlighted(&self) -> bool {
+        self.highlight.is_some()
+    }
+    fn superscript(&self) -> bool {
+        self.vert_align == Some(VertAlign::Superscript)
+    }
+    fn subscript(&self) -> bool {
+        self.vert_align == Some(VertAlign::Subscript)
+    }
+    fn code(&self) -> bool {
+        self.code
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse a `<w:body>` fragment into blocks with no relationships.
+    fn parse_fragment(body: &str) -> Vec<Block> {
+        let xml = format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{body}</w:body></w:document>"#
+        );
+        let mut reader = Reader::from_str(&xml);
+        let mut blocks = Vec::new();
+        let mut in_body = false;
+        parse_body(
+            &mut reader,
+            &mut blocks,
+            &mut in_body,
+            &Rels::new(),
+            &Rels::new(),
+            &mut Budget::default(),
+            &mut BodyState::default(),
+        );
+        blocks
+    }
+
+    /// Concatenated run text of the first paragraph in `blocks`.
+    fn first_para_text(blocks: &[Block]) -> String {
+        match blocks.first() {
+            Some(Block::Paragraph { runs, .. }) => runs.iter().map(|r| r.text.as_str()).collect(),
+            other => panic!("expected paragraph, got {other:?}"),
+        }
+    }
+
+    // ── symbol characters ────────────────────────────────────────
+
+    #[test]
+    fn sym_element_mapped() {
+        let blocks = parse_fragment(
+            r#"<w:p><w:r><w:sym w:font="Wingdings" w:char="F0FC"/><w:t xml:space="preserve"> Done</w:t></w:r></w:p>"#,
+        );
+        assert_eq!(first_para_text(&blocks), "✓ Done");
+    }
+
+    #[test]
+    fn sym_unknown_font_dropped() {
+        let blocks = parse_fragment(
+            r#"<w:p><w:r><w:sym w:font="Webdings" w:char="F021"/><w:t>x</w:t></w:r></w:p>"#,
+        );
+        assert_eq!(first_para_text(&blocks), "x");
+    }
+
+    #[test]
+    fn symbol_font_run_mapped() {
+        let blocks = parse_fragment(
+            r#"<w:p><w:r><w:rPr><w:rFonts w:ascii="Symbol" w:hAnsi="Symbol"/></w:rPr><w:t>ab</w:t></w:r></w:p>"#,
+        );
+        assert_eq!(first_para_text(&blocks), "αβ");
+    }
+
+    // ── images ───────────────────────────────────────────────────
+
+    #[test]
+    fn drawing_and_vml_images_follow_their_paragraph() {
+        let xml = r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:v="urn:schemas-microsoft-com:vml"><w:body>
+            <w:p><w:r><w:drawing><wp:inline><a:graphic><a:graphicData><pic:pic><pic:blipFill><a:blip r:embed="rId7"/></pic:blipFill></pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>
+            <w:p><w:r><w:t>Logo</w:t></w:r><w:r><w:pict><v:shape><v:imagedata r:id="rId8" o:title=""/></v:shape></w:pict></w:r></w:p>
+        </w:body></w:document>"#;
+        let image_rels: Rels = [
+            ("rId7".to_string(), "word/media/image1.png".to_string()),
+            ("rId8".to_string(), "word/media/image2.wmf".to_string()),
+        ]
+        .into();
+        let mut reader = Reader::from_str(xml);
+        let mut blocks = Vec::new();
+        parse_body(
+            &mut reader,
+            &mut blocks,
+            &mut false,
+            &Rels::new(),
+            &image_rels,
+            &mut Budget::default(),
+            &mut BodyState::default(),
+        );
+        let images: Vec<&str> = blocks
+            .iter()
+            .filter_map(|b| match b {
+                Block::Image { markdown, .. } => Some(markdown.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(images, ["word/media/image1.png", "word/media/image2.wmf"]);
+        assert!(matches!(blocks[3], Block::Image { .. }));
+        assert_eq!(first_para_text(&blocks[2..]), "Logo");
+    }
+
+    const PICTURE: &str = r#"<w:p><w:r><w:drawing><wp:inline><wp:docPr id="1" name="Picture 1" descr="DESCR"/><a:graphic><a:graphicData><pic:pic><pic:blipFill><a:blip r:embed="rId7"/></pic:blipFill></pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>"#;
+
+    #[test]
+    fn image_described_without_images_flag() {
+        let blocks = parse_fragment(&PICTURE.replace("DESCR", "Sales by [region]"));
+        assert_eq!(render_markdown(&blocks), "![Sales by \\[region\\]]\n\n");
+        assert_eq!(render_plain(&blocks, false), "[Image: Sales by [region]]\n");
+    }
+
+    #[test]
+    fn caption_names_undescribed_image() {
+        let caption = r#"<w:p><w:pPr><w:pStyle w:val="Caption"/></w:pPr><w:r><w:t xml:space="preserve">Figure 1: Org  chart</w:t></w:r></w:p>"#;
+        let mut blocks = parse_fragment(&(PICTURE.replace("DESCR", "") + caption));
+        resolve_captions(&mut blocks);
+        assert_eq!(
+            render_markdown(&blocks),
+            "![Figure 1: Org chart]\n\nFigure 1: Org  chart\n\n"
+        );
+
+        // Without a caption, the picture's name
+        let mut blocks = parse_fragment(&PICTURE.replace("DESCR", ""));
+        resolve_captions(&mut blocks);
+        assert_eq!(render_plain(&blocks, false), "[Image: Picture 1]\n");
+    }
+
+    // ── code ─────────────────────────────────────────────────────
+
+    #[test]
+    fn monospace_runs_become_code_spans() {
+        let blocks = parse_fragment(
+            r#"<w:p>
+                <w:r><w:t xml:space="preserve">Run </w:t></w:r>
+                <w:r><w:rPr><w:rFonts w:ascii="Consolas" w:hAnsi="Consolas"/></w:rPr><w:t>cargo</w:t></w:r>
+                <w:r><w:rPr><w:rStyle w:val="HTMLCode"/></w:rPr><w:t xml:space="preserve"> test</w:t></w:r>
+                <w:r><w:t xml:space="preserve"> first.</w:t></w:r>
+            </w:p>"#,
+        );
+        assert_eq!(render_markdown(&blocks), "Run `cargo test` first.\n\n");
+        assert_eq!(render_plain(&blocks, false), "Run cargo test first.\n");
+    }
+
+    #[test]
+    fn code_paragraphs_join_into_fenced_block() {
+        let line = |text: &str| {
+            format!(
+                r#"<w:p><w:pPr><w:pStyle w:val="HTMLPreformatted"/></w:pPr><w:r><w:t xml:space="preserve">{text}</w:t></w:r></w:p>"#
+            )
+        };
+        let body = [
+            "<w:p><w:r><w:t>Example:</w:t></w:r></w:p>".to_string(),
+            line("fn main() {"),
+            line("    println!(\"```\");"),
+            "<w:p></w:p>".to_string(),
+            line("}"),
+            "<w:p></w:p>".to_string(),
+            "<w:p><w:r><w:t>Done.</w:t></w:r></w:p>".to_string(),
+        ]
+        .concat();
+        let blocks = resolve_code_blocks(parse_fragment(&body));
+        assert_eq!(
+            render_markdown(&blocks),
+            "Example:\n\n````\nfn main() {\n    println!(\"```\");\n\n}\n````\n\nDone.\n\n"
+        );
+    }
+
+    // ── run colors ───────────────────────────────────────────────
+
+    #[test]
+    fn colored_runs_become_spans() {
+        let blocks = parse_fragment(
+            r#"<w:p>
+                <w:r><w:t xml:space="preserve">Do </w:t></w:r>
+                <w:r><w:rPr><w:color w:val="FF0000"/></w:rPr><w:t xml:space="preserve">not </w:t></w:r>
+                <w:r><w:rPr><w:color w:val="FF0000"/></w:rPr><w:t>ship</w:t></w:r>
+                <w:r><w:rPr><w:color w:val="auto"/><w:highlight w:val="yellow"/></w:rPr><w:t xml:space="preserve"> yet </w:t></w:r>
+            </w:p>"#,
+        );
+        let Some(document::Block::Paragraph { text, spans }) = to_document_block(&blocks[0]) else {
+            panic!("expected a paragraph");
+        };
+        assert_eq!(text, "Do not ship yet");
+        assert_eq!(
+            spans,
+            vec![
+                document::Span {
+                    range: 3..11,
+                    color: Some("FF0000".into()),
+                    highlight: None,
+                },
+                document::Span {
+                    range: 11..15,
+                    color: None,
+                    highlight: Some("yellow".into()),
+                },
+            ]
+        );
+    }
+
+    // ── checkboxes ───────────────────────────────────────────────
+
+    fn render_markdown(blocks: &[Block]) -> String {
+        let mut out = String::new();
+        for block in blocks {
+            render_block_markdown(block, &mut out);
+        }
+        out
+    }
+
+    const FORM_CHECKBOX: &str = r#"<w:p><w:r><w:fldChar w:fldCharType="begin"><w:ffData><w:name w:val="Check1"/><w:checkBox><w:sizeAuto/><w:default w:val="0"/>{CHECKED}</w:checkBox></w:ffData></w:fldChar></w:r><w:r><w:instrText xml:space="preserve"> FORMCHECKBOX </w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:t>X</w:t></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r><w:r><w:t xml:space="preserve"> Ship it</w:t></w:r></w:p>"#;
+
+    #[test]
+    fn form_checkbox_checked() {
+        let body = FORM_CHECKBOX.replace("{CHECKED}", "<w:checked/>");
+        let blocks = parse_fragment(&body);
+        assert_eq!(first_para_text(&blocks), "[x] Ship it");
+        assert_eq!(render_markdown(&blocks), "- [x] Ship it\n");
+    }
+
+    #[test]
+    fn form_checkbox_default_unchecked() {
+        let body = FORM_CHECKBOX.replace("{CHECKED}", "");
+        let blocks = parse_fragment(&body);
+        assert_eq!(first_para_text(&blocks), "[ ] Ship it");
+    }
+
+    #[test]
+    fn form_checkbox_checked_val_false() {
+        let body = FORM_CHECKBOX.replace("{CHECKED}", r#"<w:checked w:val="0"/>"#);
+        let blocks = parse_fragment(&body);
+        assert_eq!(first_para_text(&blocks), "[ ] Ship it");
+    }
+
+    #[test]
+    fn sdt_checkbox_replaces_glyph() {
+        let blocks = parse_fragment(
+            r#"<w:p><w:sdt><w:sdtPr><w14:checkbox xmlns:w14="http://schemas.microsoft.com/office/word/2010/wordml"><w14:checked w14:val="1"/></w14:checkbox></w:sdtPr><w:sdtContent><w:r><w:t>☒</w:t></w:r></w:sdtContent></w:sdt><w:r><w:t xml:space="preserve"> Review</w:t></w:r></w:p>"#,
+        );
+        assert_eq!(first_para_text(&blocks), "[x] Review");
+    }
+
+    #[test]
+    fn checkbox_in_list_keeps_indent() {
+        let body = FORM_CHECKBOX.replace("{CHECKED}", "").replace(
+            "<w:p>",
+            r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="1"/><w:numId w:val="1"/></w:numPr></w:pPr>"#,
+        );
+        let blocks = parse_fragment(&body);
+        assert_eq!(render_markdown(&blocks), "  - [ ] Ship it\n");
+    }
+
+    #[test]
+    fn inline_checkbox_stays_inline() {
+        let body = FORM_CHECKBOX.replace("{CHECKED}", "<w:checked/>").replace(
+            "<w:p>",
+            "<w:p><w:r><w:t xml:space=\"preserve\">Agree: </w:t></w:r>",
+        );
+        let blocks = parse_fragment(&body);
+        assert_eq!(render_markdown(&blocks), "Agree: [x] Ship it\n\n");
+    }
+
+    #[test]
+    fn form_dropdown_shows_choice() {
+        let dropdown = |ff: &str| {
+            format!(
+                r#"<w:p><w:r><w:t xml:space="preserve">Colour: </w:t></w:r><w:r><w:fldChar w:fldCharType="begin"><w:ffData><w:name w:val="Dropdown1"/><w:ddList>{ff}<w:listEntry w:val="Red"/><w:listEntry w:val="Blue"/></w:ddList></w:ffData></w:fldChar></w:r><w:r><w:instrText xml:space="preserve"> FORMDROPDOWN </w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p>"#
+            )
+        };
+        let blocks = parse_fragment(&dropdown(r#"<w:result w:val="1"/>"#));
+        assert_eq!(first_para_text(&blocks), "Colour: Blue");
+        let blocks = parse_fragment(&dropdown(""));
+        assert_eq!(first_para_text(&blocks), "Colour: Red");
+    }
+
+    #[test]
+    fn ballot_box_characters_become_checkboxes() {
+        let blocks = parse_fragment(
+            r#"<w:p><w:r><w:t xml:space="preserve">☒Agree</w:t></w:r></w:p><w:p><w:r><w:t xml:space="preserve">Smoker: ☐ Yes ☑ No</w:t></w:r></w:p>"#,
+        );
+        assert_eq!(
+            render_markdown(&blocks),
+            "- [x] Agree\n\nSmoker: [ ] Yes [x] No\n\n"
+        );
+    }
+
+    #[test]
+    fn other_field_result_kept() {
+        let blocks = parse_fragment(
+            r#"<w:p><w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText> PAGE </w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:t>3</w:t></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p>"#,
+        );
+        assert_eq!(first_para_text(&blocks), "3");
+    }
+
+    #[test]
+    fn simple_fields() {
+        let blocks = parse_fragment(
+            r#"<w:p><w:r><w:t xml:space="preserve">Page </w:t></w:r><w:fldSimple w:instr=" PAGE "><w:r><w:t>4</w:t></w:r></w:fldSimple><w:r><w:t xml:space="preserve">, see </w:t></w:r><w:fldSimple w:instr="HYPERLINK &quot;https://example.com&quot;"><w:r><w:t>site</w:t></w:r></w:fldSimple><w:fldSimple w:instr="NUMPAGES"/></w:p>"#,
+        );
+        assert_eq!(
+            render_markdown(&blocks),
+            "Page 4, see [site](https://example.com)\n\n"
+        );
+    }
+
+    #[test]
+    fn hyperlink_field_becomes_link() {
+        let blocks = parse_fragment(
+            r#"<w:p><w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText xml:space="preserve"> HYPERLINK \l "Results" </w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:t>the results</w:t></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p>"#,
+        );
+        assert_eq!(render_markdown(&blocks), "[the results](#Results)\n\n");
+    }
+
+    #[test]
+    fn table_of_contents_left_out() {
+        let entry = |text: &str| {
+            format!(
+                r#"<w:hyperlink w:anchor="_Toc1"><w:r><w:t>{text}</w:t></w:r><w:r><w:tab/></w:r><w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText> PAGEREF _Toc1 \h </w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:t>2</w:t></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r></w:hyperlink>"#
+            )
+        };
+        let body = format!(
+            r#"<w:p><w:r><w:t>Contents</w:t></w:r></w:p><w:p><w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText xml:space="preserve"> TOC \o "1-3" \h </w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r>{}</w:p><w:p>{}</w:p><w:p><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p><w:p><w:r><w:t>Introduction</w:t></w:r></w:p>"#,
+            entry("Introduction"),
+            entry("Method"),
+        );
+        let blocks = parse_fragment(&body);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(render_plain(&blocks, false), "Contents\n\nIntroduction\n");
+    }
+
+    // ── document-property fields ─────────────────────────────────
+
+    #[test]
+    fn docproperty_field_resolved() {
+        let mut blocks = parse_fragment(
+            r#"<w:p><w:r><w:t xml:space="preserve">By </w:t></w:r><w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText xml:space="preserve"> DOCPROPERTY Company </w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:rPr><w:b/></w:rPr><w:t>Old</w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t>Co</w:t></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p>"#,
+        );
+        assert_eq!(first_para_text(&blocks), "By OldCo");
+
+        let meta = Metadata {
+            company: Some("Initech".into()),
+            ..Metadata::default()
+        };
+        resolve_fields(&mut blocks, &meta);
+        assert_eq!(first_para_text(&blocks), "By Initech");
+        assert_eq!(render_markdown(&blocks), "By **Initech**\n\n");
+    }
+
+    #[test]
+    fn createdate_field_without_result() {
+        let mut blocks = parse_fragment(
+            r#"<w:p><w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText>CREATEDATE \@ "d MMM yyyy"</w:instrText></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p>"#,
+        );
+        let meta = Metadata {
+            created: crate::metadata::DateTime::parse_w3cdtf("2023-07-04T10:00:00Z"),
+            ..Metadata::default()
+        };
+        resolve_fields(&mut blocks, &meta);
+        assert_eq!(first_para_text(&blocks), "4 Jul 2023");
+    }
+
+    // ── comments ─────────────────────────────────────────────────
+
+    const COMMENTS: &str = r#"<w:comments xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:comment w:id="0" w:author="Ann"><w:p><w:r><w:annotationRef/></w:r><w:r><w:t>Is this</w:t></w:r></w:p><w:p><w:r><w:t>right?</w:t></w:r></w:p></w:comment><w:comment w:id="1" w:author=""><w:p><w:r><w:t>Typo</w:t></w:r></w:p></w:comment></w:comments>"#;
+
+    const COMMENTED: &str = r#"<w:p><w:r><w:t xml:space="preserve">Revenue </w:t></w:r><w:commentRangeStart w:id="0"/><w:r><w:t>grew 12%</w:t></w:r><w:commentRangeEnd w:id="0"/><w:r><w:commentReference w:id="0"/></w:r><w:r><w:t>.</w:t></w:r></w:p><w:p><w:r><w:t>Teh end</w:t></w:r><w:r><w:commentReference w:id="1"/></w:r></w:p>"#;
+
+    #[test]
+    fn comments_parsed_by_id() {
+        let comments = parse_comments(COMMENTS);
+        assert_eq!(
+            comments["0"],
+            Comment {
+                author: "Ann".into(),
+                text: "Is this right?".into(),
+            }
+        );
+        assert_eq!(comments["1"].text, "Typo");
+    }
+
+    #[test]
+    fn comments_inline_after_paragraph() {
+        let mut placer = CommentPlacer::new(parse_comments(COMMENTS), Comments::Inline);
+        let blocks = placer.place(parse_fragment(COMMENTED));
+        assert!(placer.appendix().is_empty());
+        assert_eq!(
+            render_markdown(&blocks),
+            "Revenue grew 12%.\n\n> [Ann: Is this right?]\n\nTeh end\n\n> [Comment: Typo]\n\n"
+        );
+    }
+
+    #[test]
+    fn comments_at_end_with_marks() {
+        let mut placer = CommentPlacer::new(parse_comments(COMMENTS), Comments::End);
+        let mut blocks = placer.place(parse_fragment(COMMENTED));
+        blocks.append(&mut placer.appendix());
+        assert_eq!(
+            render_plain(&blocks, false),
+            "Revenue grew 12%[c1].\n\nTeh end[c2]\n\nComments\n\n\
+             [c1] Ann on \u{201c}grew 12%\u{201d}: Is this right?\n\n\
+             [c2] Comment: Typo\n"
+        );
+    }
 
-fn render_plain(blocks: &[Block]) -> String {
-    let mut out = String::new();
-    let mut first = true;
+    // ── equations ────────────────────────────────────────────────
 
-    for block in blocks {
-        render_block_plain(block, &mut out, &mut first);
+    #[test]
+    fn equations_inline_and_display() {
+        let m = r#"xmlns:m="http://schemas.openxmlformats.org/officeDocument/2006/math""#;
+        let body = format!(
+            r#"<w:p><w:r><w:t xml:space="preserve">Area is </w:t></w:r><m:oMath {m}><m:r><m:t>π</m:t></m:r><m:sSup><m:e><m:r><m:t>r</m:t></m:r></m:e><m:sup><m:r><m:t>2</m:t></m:r></m:sup></m:sSup></m:oMath><w:r><w:t>.</w:t></w:r></w:p><w:p><m:oMathPara {m}><m:oMath><m:f><m:num><m:r><m:t>a</m:t></m:r></m:num><m:den><m:r><m:t>b</m:t></m:r></m:den></m:f></m:oMath></m:oMathPara></w:p>"#
+        );
+        let blocks = parse_fragment(&body);
+        assert_eq!(
+            render_markdown(&blocks),
+            "Area is $\\pi r^{2}$.\n\n$$\\frac{a}{b}$$\n\n"
+        );
     }
 
-    out
-}
+    // ── SmartArt ─────────────────────────────────────────────────
 
-fn render_block_plain(block: &Block, out: &mut String, first: &mut bool) {
-    match block {
-        Block::Paragraph { runs, .. } => {
-            let text: String = runs.iter().map(|r| r.text.as_str()).collect();
-            let text = text.trim_end();
-            if !text.is_empty() {
-                if !*first {
-                    out.push('\n');
-                }
-                out.push_str(text);
-                out.push('\n');
-                *first = false;
-            }
-        }
-        Block::Table { rows } => {
-            for row in rows {
-                let cells: Vec<String> = row.iter().map(|cell| cell_to_text(cell, false)).collect();
+    #[test]
+    fn smartart_drawing_becomes_nested_list() {
+        let body = r#"<w:p><w:r><w:t>Team</w:t></w:r><w:r><w:drawing><wp:inline xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"><a:graphic xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/diagram"><dgm:relIds xmlns:dgm="http://schemas.openxmlformats.org/drawingml/2006/diagram" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" r:dm="rId5" r:lo="rId6" r:qs="rId7" r:cs="rId8"/></a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>"#;
+        let blocks = parse_fragment(body);
+        assert!(matches!(&blocks[1], Block::Graphic(Graphic::Diagram(rid)) if rid == "rId5"));
 
-                let line = cells.join("\t");
-                let line = line.trim_end();
-                if !line.is_empty() {
-                    if !*first {
-                        out.push('\n');
-                    }
-                    out.push_str(line);
-                    out.push('\n');
-                    *first = false;
-                }
-            }
-        }
-        Block::Image { .. } => {
-            // Images are not rendered in plain text mode
-        }
+        let data = r#"<dgm:dataModel xmlns:dgm="http://schemas.openxmlformats.org/drawingml/2006/diagram" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><dgm:ptLst><dgm:pt modelId="0" type="doc"/><dgm:pt modelId="1"><dgm:t><a:p><a:r><a:t>Lead</a:t></a:r></a:p></dgm:t></dgm:pt><dgm:pt modelId="2"><dgm:t><a:p><a:r><a:t>Dev</a:t></a:r></a:p></dgm:t></dgm:pt></dgm:ptLst><dgm:cxnLst><dgm:cxn modelId="3" srcId="0" destId="1" srcOrd="0"/><dgm:cxn modelId="4" srcId="1" destId="2" srcOrd="0"/></dgm:cxnLst></dgm:dataModel>"#;
+        let items = diagram_items(data);
+        assert_eq!(render_markdown(&items), "- Lead\n  - Dev\n");
     }
-}
 
-// ── Markdown rendering ────────────────────────────────────────────
-
-fn render_markdown(blocks: &[Block]) -> String {
-    let mut out = String::new();
+    #[test]
+    fn chart_as_title_and_table() {
+        let body = r#"<w:p><w:r><w:drawing><wp:inline xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing"><a:graphic xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/chart"><c:chart xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" r:id="rId9"/></a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>"#;
+        let blocks = parse_fragment(body);
+        assert!(matches!(&blocks[1], Block::Graphic(Graphic::Chart(rid)) if rid == "rId9"));
 
-    for block in blocks {
-        render_block_markdown(block, &mut out);
+        let chart = r#"<c:chartSpace xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><c:chart><c:title><c:tx><c:rich><a:p><a:r><a:t>Sales</a:t></a:r></a:p></c:rich></c:tx></c:title><c:plotArea><c:barChart><c:ser><c:tx><c:v>2024</c:v></c:tx><c:cat><c:strLit><c:pt idx="0"><c:v>Q1</c:v></c:pt><c:pt idx="1"><c:v>Q2</c:v></c:pt></c:strLit></c:cat><c:val><c:numLit><c:pt idx="0"><c:v>10</c:v></c:pt><c:pt idx="1"><c:v>12</c:v></c:pt></c:numLit></c:val></c:ser></c:barChart></c:plotArea></c:chart></c:chartSpace>"#;
+        assert_eq!(
+            render_markdown(&chart_blocks(chart)),
+            "**Sales**\n\n|  | 2024 |\n| --- | --- |\n| Q1 | 10 |\n| Q2 | 12 |\n\n"
+        );
     }
 
-    out
-}
+    #[test]
+    fn alt_chunk_imports_html() {
+        let body = r#"<w:p><w:r><w:t>Before</w:t></w:r></w:p><w:altChunk xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" r:id="rId4"/>"#;
+        let blocks = parse_fragment(body);
+        assert!(matches!(&blocks[1], Block::Graphic(Graphic::Chunk(rid)) if rid == "rId4"));
 
-fn render_block_markdown(block: &Block, out: &mut String) {
-    match block {
-        Block::Paragraph { style, runs } => {
-            let text = render_runs_markdown(runs);
-            let text = text.trim_end();
-            if text.is_empty() {
-                return;
-            }
+        let html = b"<html><body><h1>Offer</h1><p>Dear <b>Ann</b>,</p><ol><li>One</li><li>Two</li></ol><table><tr><td>A</td><td>1</td></tr></table></body></html>";
+        assert_eq!(
+            render_markdown(&chunk_blocks(html, "word/afchunk.htm")),
+            "# Offer\n\nDear **Ann**,\n\n1. One\n2. Two\n\n| A | 1 |\n| --- | --- |\n\n"
+        );
+    }
 
-            if style.heading_level > 0 && style.heading_level <= 6 {
-                for _ in 0..style.heading_level {
-                    out.push('#');
-                }
-                out.push(' ');
-                out.push_str(text);
-                out.push_str("\n\n");
-            } else if let Some(level) = style.list_level {
-                let indent = "  ".repeat(usize::from(level));
-                out.push_str(&indent);
-                out.push_str("- ");
-                out.push_str(text);
-                out.push('\n');
-            } else {
-                out.push_str(text);
-                out.push_str("\n\n");
-            }
-        }
-        Block::Image { markdown } => {
-            out.push_str(markdown);
-            out.push_str("\n\n");
-        }
-        Block::Table { rows } => {
-            if rows.is_empty() {
-                return;
-            }
+    #[test]
+    fn ole_object_placeholder() {
+        let body = r#"<w:p><w:r><w:object><v:shape xmlns:v="urn:schemas-microsoft-com:vml"/><o:OLEObject xmlns:o="urn:schemas-microsoft-com:office:office" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" Type="Embed" ProgID="Excel.Sheet.12" r:id="rId8"/></w:object></w:r></w:p>"#;
+        let blocks = parse_fragment(body);
+        assert!(matches!(
+            &blocks[1],
+            Block::Graphic(Graphic::Object { rid, prog_id })
+                if rid == "rId8" && prog_id.as_deref() == Some("Excel.Sheet.12")
+        ));
 
-            let ncols = rows.iter().map(Vec::len).max().unwrap_or(0);
-            if ncols == 0 {
-                return;
-            }
+        let path = "word/embeddings/Microsoft_Excel_Worksheet.xlsx";
+        let blocks = object_blocks(None, path, Some("Excel.Sheet.12"), &Options::default());
+        assert_eq!(
+            render_plain(&blocks, false),
+            "[Object: Microsoft_Excel_Worksheet.xlsx (Excel.Sheet.12)]\n"
+        );
+    }
 
-            let mut md_rows: Vec<Vec<String>> = Vec::new();
-            for row in rows {
-                let mut md_row = Vec::new();
-                for cell in row {
-                    let cell_text = cell_to_text(cell, true);
-                    md_row.push(cell_text.replace('|', "\\|"));
-                }
-                while md_row.len() < ncols {
-                    md_row.push(String::new());
-                }
-                md_rows.push(md_row);
-            }
+    #[test]
+    fn embedded_document_extracted_with_flag() {
+        use std::io::Write;
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file(
+            "word/document.xml",
+            zip::write::SimpleFileOptions::default(),
+        )
+        .unwrap();
+        zip.write_all(br#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:p><w:r><w:t>Inside</w:t></w:r></w:p></w:body></w:document>"#)
+            .unwrap();
+        let data = zip.finish().unwrap().into_inner();
+        let path = "word/embeddings/Document.docx";
 
-            if let Some(header) = md_rows.first() {
-                out.push_str("| ");
-                out.push_str(&header.join(" | "));
-                out.push_str(" |\n");
+        let placeholder = object_blocks(Some(data.clone()), path, None, &Options::default());
+        assert_eq!(
+            render_plain(&placeholder, false),
+            "[Object: Document.docx]\n"
+        );
 
-                out.push('|');
-                for _ in 0..ncols {
-                    out.push_str(" --- |");
-                }
-                out.push('\n');
+        let opts = Options {
+            embedded: true,
+            ..Options::default()
+        };
+        let blocks = object_blocks(Some(data), path, None, &opts);
+        assert_eq!(
+            render_plain(&blocks, false),
+            "[Object: Document.docx]\n\nInside\n"
+        );
+    }
 
-                for row in md_rows.iter().skip(1) {
-                    out.push_str("| ");
-                    out.push_str(&row.join(" | "));
-                    out.push_str(" |\n");
-                }
-                out.push('\n');
-            }
+    // ── main part ────────────────────────────────────────────────
+
+    #[test]
+    fn main_part_found_through_relationship() {
+        use std::io::Write;
+        let parts = [
+            (
+                "_rels/.rels",
+                r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document2.xml"/></Relationships>"#,
+            ),
+            (
+                "word/_rels/document2.xml.rels",
+                r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com" TargetMode="External"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles2.xml"/></Relationships>"#,
+            ),
+            (
+                "word/document2.xml",
+                r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><w:body><w:p><w:pPr><w:pStyle w:val="Title1"/></w:pPr><w:r><w:t>Report</w:t></w:r></w:p><w:p><w:hyperlink r:id="rId1"><w:r><w:t>site</w:t></w:r></w:hyperlink></w:p></w:body></w:document>"#,
+            ),
+            (
+                "word/styles2.xml",
+                r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:styleId="Title1"><w:name w:val="heading 1"/></w:style></w:styles>"#,
+            ),
+        ];
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, xml) in parts {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(xml.as_bytes()).unwrap();
         }
+        let data = zip.finish().unwrap().into_inner();
+        assert_eq!(
+            extract_markdown(&data, &Options::default()).unwrap(),
+            "# Report\n\n[site](https://example.com)\n\n"
+        );
     }
-}
 
-/// Render runs with markdown inline formatting (bold/italic/hyperlinks).
-///
-/// Adjacent runs sharing the same `link_url` are grouped so the markdown
-/// link wraps the entire visible text: `[text](url)` instead of producing
-/// separate `[part1](url)[part2](url)` fragments.
-fn render_runs_markdown(runs: &[Run]) -> String {
-    markup::render_runs_markdown(runs)
-}
+    // ── tracked changes ──────────────────────────────────────────
 
-/// Implement [`InlineRun`] for docx `Run` so the shared markup renderer
-/// can inspect formatting without knowing the concrete type.
-impl markup::InlineRun for Run {
-    fn text(&self) -> &str {
-        &self.text
+    const REVISED: &str = r#"<w:p><w:r><w:t xml:space="preserve">Revenue grew </w:t></w:r><w:del w:id="1" w:author="Ann"><w:r><w:delText>10%</w:delText></w:r></w:del><w:ins w:id="2" w:author="Ann"><w:r><w:t>12</w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t>%</w:t></w:r></w:ins><w:r><w:t xml:space="preserve"> over the year.</w:t></w:r></w:p>"#;
+
+    fn revised(mode: Revisions) -> String {
+        let mut blocks = parse_fragment(REVISED);
+        resolve_revisions(&mut blocks, mode);
+        render_plain(&blocks, false)
     }
-    fn bold(&self) -> bool {
-        self.bold
+
+    #[test]
+    fn revisions_final_original_and_marked() {
+        assert_eq!(
+            revised(Revisions::Final),
+            "Revenue grew 12% over the year.\n"
+        );
+        assert_eq!(
+            revised(Revisions::Original),
+            "Revenue grew 10% over the year.\n"
+        );
+        assert_eq!(
+            revised(Revisions::Marked),
+            "Revenue grew [-10%-]{+12%+} over the year.\n"
+        );
     }
-    fn italic(&self) -> bool {
-        self.italic
+
+    #[test]
+    fn deleted_text_left_out_of_comments() {
+        let xml = r#"<w:comments xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:comment w:id="0" w:author="Ann"><w:p><w:r><w:t xml:space="preserve">Fine </w:t></w:r><w:del w:id="3"><w:r><w:delText>not </w:delText></w:r></w:del><w:r><w:t>now</w:t></w:r></w:p></w:comment></w:comments>"#;
+        assert_eq!(parse_comments(xml)["0"].text, "Fine now");
     }
-    fn link_url(&self) -> Option<&str> {
-        self.link_url.as_deref()
+
+    // ── list numbering ───────────────────────────────────────────
+
+    #[test]
+    fn numbered_list_labels_and_nesting() {
+        let numbering = r#"<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:abstractNum w:abstractNumId="4"><w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%1."/></w:lvl><w:lvl w:ilvl="1"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="o"/></w:lvl></w:abstractNum><w:num w:numId="7"><w:abstractNumId w:val="4"/></w:num></w:numbering>"#;
+        let item = |level: u8, num_id: u8, text: &str| {
+            format!(
+                r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="{level}"/><w:numId w:val="{num_id}"/></w:numPr></w:pPr><w:r><w:t>{text}</w:t></w:r></w:p>"#
+            )
+        };
+        let body = [
+            item(0, 7, "Plan"),
+            item(1, 7, "Scope"),
+            item(0, 7, "Build"),
+            item(0, 0, "Not a list"),
+        ]
+        .concat();
+        let mut blocks = parse_fragment(&body);
+        resolve_numbering(
+            &mut blocks,
+            &mut Numbering::parse(numbering),
+            &mut Vec::new(),
+        );
+        assert_eq!(
+            render_markdown(&blocks),
+            "1. Plan\n   - Scope\n2. Build\n\nNot a list\n\n"
+        );
+        assert_eq!(
+            render_plain(&blocks, false),
+            "1. Plan\n\nScope\n\n2. Build\n\nNot a list\n"
+        );
+        assert!(matches!(
+            to_document_block(&blocks[0]),
+            Some(document::Block::ListItem { ordered: true, .. })
+        ));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // ── styles ───────────────────────────────────────────────────
+
+    #[test]
+    fn heading_styles_resolved_through_styles_xml() {
+        let styles = Styles::parse(
+            r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:styleId="berschrift1"><w:name w:val="heading 1"/><w:basedOn w:val="Standard"/></w:style><w:style w:type="paragraph" w:styleId="Standard"><w:name w:val="Normal"/></w:style><w:style w:type="paragraph" w:styleId="Kapitel"><w:name w:val="Kapitel"/><w:pPr><w:outlineLvl w:val="1"/></w:pPr></w:style><w:style w:type="paragraph" w:styleId="Abschnitt"><w:name w:val="Abschnitt"/><w:basedOn w:val="Kapitel"/></w:style><w:style w:type="paragraph" w:styleId="Heading3"><w:name w:val="Quote"/></w:style><w:style w:type="paragraph" w:styleId="A"><w:basedOn w:val="B"/></w:style><w:style w:type="paragraph" w:styleId="B"><w:basedOn w:val="A"/></w:style></w:styles>"#,
+        );
+        assert_eq!(styles.heading_level("berschrift1"), Some(1));
+        assert_eq!(styles.heading_level("Standard"), Some(0));
+        assert_eq!(styles.heading_level("Kapitel"), Some(2));
+        assert_eq!(styles.heading_level("Abschnitt"), Some(2));
+        assert_eq!(styles.heading_level("Heading3"), Some(0));
+        assert_eq!(styles.heading_level("A"), Some(0));
+        assert_eq!(styles.heading_level("Missing"), None);
+
+        let para = |id: &str| {
+            format!(
+                r#"<w:p><w:pPr><w:pStyle w:val="{id}"/></w:pPr><w:r><w:t>{id}</w:t></w:r></w:p>"#
+            )
+        };
+        let mut blocks = parse_fragment(&[para("berschrift1"), para("Heading2")].concat());
+        resolve_styles(&mut blocks, &styles);
+        assert_eq!(render_markdown(&blocks), "# berschrift1\n\n## Heading2\n\n");
+    }
 
     // ── parse_heading_level ──────────────────────────────────────
 
@@ -840,7 +4348,7 @@ mod tests {
             text: text.into(),
             bold,
             italic,
-            link_url: None,
+            ..Run::default()
         }
     }
 
@@ -900,6 +4408,7 @@ mod tests {
             bold: false,
             italic: false,
             link_url: Some("https://example.com".into()),
+            ..Run::default()
         }];
         assert_eq!(
             render_runs_markdown(&runs),
@@ -914,6 +4423,7 @@ mod tests {
             bold: true,
             italic: false,
             link_url: Some("https://example.com".into()),
+            ..Run::default()
         }];
         assert_eq!(
             render_runs_markdown(&runs),
@@ -930,12 +4440,14 @@ mod tests {
                 bold: false,
                 italic: false,
                 link_url: Some("https://example.com".into()),
+                ..Run::default()
             },
             Run {
                 text: "one".into(),
                 bold: true,
                 italic: false,
                 link_url: Some("https://example.com".into()),
+                ..Run::default()
             },
         ];
         assert_eq!(
@@ -953,6 +4465,7 @@ mod tests {
                 bold: false,
                 italic: false,
                 link_url: Some("https://example.com".into()),
+                ..Run::default()
             },
             run(" for details", false, false),
         ];
@@ -1020,6 +4533,7 @@ mod tests {
             style: ParaStyle {
                 heading_level: 2,
                 list_level: None,
+                ..ParaStyle::default()
             },
             runs: vec![run("My Heading", false, false)],
         };
@@ -1034,6 +4548,7 @@ mod tests {
             style: ParaStyle {
                 heading_level: 0,
                 list_level: Some(0),
+                ..ParaStyle::default()
             },
             runs: vec![run("Item one", false, false)],
         };
@@ -1048,6 +4563,7 @@ mod tests {
             style: ParaStyle {
                 heading_level: 0,
                 list_level: Some(2),
+                ..ParaStyle::default()
             },
             runs: vec![run("Nested", false, false)],
         };
@@ -1112,4 +4628,270 @@ mod tests {
         render_block_markdown(&table, &mut out);
         assert!(out.contains("A\\|B"));
     }
+
+    // ── bookmarks ────────────────────────────────────────────────
+
+    #[test]
+    fn internal_links_to_bookmark_anchors() {
+        let body = r#"<w:p><w:hyperlink w:anchor="Results"><w:r><w:t>see results</w:t></w:r></w:hyperlink><w:r><w:t xml:space="preserve"> or </w:t></w:r><w:hyperlink w:anchor="_Missing"><w:r><w:t>nowhere</w:t></w:r></w:hyperlink></w:p><w:p><w:bookmarkStart w:id="0" w:name="results"/><w:bookmarkEnd w:id="0"/></w:p><w:p><w:bookmarkStart w:id="1" w:name="_GoBack"/><w:r><w:t>Results</w:t></w:r></w:p>"#;
+        let mut blocks = parse_fragment(body);
+        let targets = bookmark_targets(&blocks);
+        link_bookmarks(&mut blocks, &targets, &mut Vec::new());
+        assert_eq!(
+            render_markdown(&blocks),
+            "[see results](#Results) or nowhere\n\n<a id=\"results\"></a>\n\nResults\n\n"
+        );
+    }
+
+    // ── strikethrough, underline, and highlight ──────────────────
+
+    const MARKED: &str = r#"<w:p><w:r><w:rPr><w:strike/></w:rPr><w:t>old</w:t></w:r><w:r><w:t xml:space="preserve"> </w:t></w:r><w:r><w:rPr><w:b/><w:u w:val="single"/></w:rPr><w:t>key</w:t></w:r><w:r><w:t xml:space="preserve"> </w:t></w:r><w:r><w:rPr><w:highlight w:val="yellow"/><w:dstrike w:val="true"/></w:rPr><w:t>note</w:t></w:r><w:r><w:rPr><w:strike w:val="0"/><w:u w:val="none"/></w:rPr><w:t xml:space="preserve"> plain</w:t></w:r></w:p>"#;
+
+    fn marked(mode: Marks) -> String {
+        let mut blocks = parse_fragment(MARKED);
+        resolve_marks(&mut blocks, mode);
+        render_markdown(&blocks)
+    }
+
+    #[test]
+    fn strike_underline_and_highlight_marks() {
+        assert_eq!(
+            marked(Marks::Extended),
+            "~~old~~ <u>**key**</u> ==~~note~~== plain\n\n"
+        );
+        assert_eq!(
+            marked(Marks::Emphasis),
+            "~~old~~ ***key*** ~~**note**~~ plain\n\n"
+        );
+        assert_eq!(marked(Marks::None), "old **key** note plain\n\n");
+    }
+
+    // ── superscript and subscript ────────────────────────────────
+
+    const SCRIPTS: &str = r#"<w:p><w:r><w:t>H</w:t></w:r><w:r><w:rPr><w:vertAlign w:val="subscript"/></w:rPr><w:t>2</w:t></w:r><w:r><w:t xml:space="preserve">O and x</w:t></w:r><w:r><w:rPr><w:vertAlign w:val="superscript"/></w:rPr><w:t>n + 1</w:t></w:r><w:r><w:t xml:space="preserve">, Acme</w:t></w:r><w:r><w:rPr><w:vertAlign w:val="superscript"/></w:rPr><w:t>TM</w:t></w:r><w:r><w:rPr><w:vertAlign w:val="baseline"/></w:rPr><w:t>.</w:t></w:r></w:p>"#;
+
+    #[test]
+    fn subscript_and_superscript() {
+        let mut blocks = parse_fragment(SCRIPTS);
+        assert_eq!(
+            render_markdown(&blocks),
+            "H~2~O and x^n\\ +\\ 1^, Acme^TM^.\n\n"
+        );
+        resolve_scripts(&mut blocks);
+        assert_eq!(render_plain(&blocks, false), "H₂O and xⁿ ⁺ ¹, AcmeTM.\n");
+    }
+
+    // ── content controls ─────────────────────────────────────────
+
+    const CONTROLS: &str = r#"<w:sdt><w:sdtPr><w:alias w:val="Applicant"/><w:tag w:val="name"/></w:sdtPr><w:sdtContent><w:p><w:r><w:t>Acme Ltd</w:t></w:r></w:p></w:sdtContent></w:sdt><w:sdt><w:sdtPr><w:tag w:val="notes"/><w:showingPlcHdr/></w:sdtPr><w:sdtContent><w:p><w:r><w:t>Click or tap here to enter text.</w:t></w:r></w:p></w:sdtContent></w:sdt><w:p><w:r><w:t xml:space="preserve">Born </w:t></w:r><w:sdt><w:sdtPr><w:alias w:val="Date of birth"/><w:showingPlcHdr/></w:sdtPr><w:sdtContent><w:r><w:t>Click or tap to enter a date.</w:t></w:r></w:sdtContent></w:sdt></w:p><w:tbl><w:tr><w:tc><w:sdt><w:sdtPr><w:alias w:val="City"/></w:sdtPr><w:sdtContent><w:p><w:r><w:t>Oslo</w:t></w:r></w:p></w:sdtContent></w:sdt></w:tc></w:tr></w:tbl>"#;
+
+    #[test]
+    fn content_controls_with_and_without_labels() {
+        let mut blocks = parse_fragment(CONTROLS);
+        assert_eq!(
+            render_markdown(&blocks),
+            "**Applicant:** Acme Ltd\n\nBorn **Date of birth:**\n\n| **City:** Oslo |\n| --- |\n\n"
+        );
+        drop_control_labels(&mut blocks);
+        assert_eq!(render_plain(&blocks, false), "Acme Ltd\n\nBorn\n\nOslo\n");
+    }
+
+    // ── merged cells ─────────────────────────────────────────────
+
+    #[test]
+    fn merged_cells_keep_columns_aligned() {
+        let cell = |props: &str, text: &str| {
+            format!("<w:tc><w:tcPr>{props}</w:tcPr><w:p><w:r><w:t>{text}</w:t></w:r></w:p></w:tc>")
+        };
+        let body = format!(
+            "<w:tbl><w:tr>{}{}</w:tr><w:tr>{}{}{}</w:tr><w:tr>{}{}{}</w:tr><w:tr><w:trPr><w:gridBefore w:val=\"1\"/></w:trPr>{}{}</w:tr></w:tbl>",
+            cell("", "Region"),
+            cell(r#"<w:gridSpan w:val="2"/>"#, "Sales"),
+            cell(r#"<w:vMerge w:val="restart"/>"#, "North"),
+            cell("", "Q1"),
+            cell("", "10"),
+            cell("<w:vMerge/>", ""),
+            cell("", "Q2"),
+            cell("", "12"),
+            cell("", "a"),
+            cell("", "b"),
+        );
+        let blocks = parse_fragment(&body);
+        assert_eq!(
+            render_markdown(&blocks),
+            "| Region | Sales |  |\n| --- | --- | --- |\n| North | Q1 | 10 |\n| North | Q2 | 12 |\n|  | a | b |\n\n"
+        );
+    }
+
+    // ── page breaks ──────────────────────────────────────────────
+
+    const PAGES: &str = r#"<w:p><w:r><w:br w:type="page"/></w:r><w:r><w:t>Cover</w:t></w:r></w:p><w:p><w:r><w:t>Before</w:t><w:br w:type="page"/><w:t>after</w:t><w:br/><w:t>line</w:t></w:r></w:p><w:p><w:pPr><w:sectPr><w:type w:val="nextPage"/></w:sectPr></w:pPr><w:r><w:t>End of part one</w:t></w:r></w:p><w:p><w:pPr><w:pStyle w:val="Heading1"/><w:pageBreakBefore/></w:pPr><w:r><w:t>Part two</w:t></w:r></w:p>"#;
+
+    #[test]
+    fn page_and_section_breaks() {
+        let blocks = resolve_page_breaks(parse_fragment(PAGES), Some("---"), &mut true);
+        assert_eq!(
+            render_markdown(&blocks),
+            "Cover\n\nBefore\n\n---\n\nafter\nline\n\nEnd of part one\n\n---\n\n# Part two\n\n"
+        );
+        let blocks = resolve_page_breaks(parse_fragment(PAGES), None, &mut true);
+        assert_eq!(
+            render_plain(&blocks, false),
+            "\nCover\n\nBefore\nafter\nline\n\nEnd of part one\n\nPart two\n"
+        );
+    }
+
+    // ── streaming ────────────────────────────────────────────────
+
+    #[test]
+    fn streamed_output_matches_whole() {
+        use std::io::Write;
+        let code = |text: &str| {
+            format!(
+                r#"<w:p><w:r><w:rPr><w:rFonts w:ascii="Courier New"/></w:rPr><w:t>{text}</w:t></w:r></w:p>"#
+            )
+        };
+        let item = |text: &str| {
+            format!(
+                r#"<w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>{text}</w:t></w:r></w:p>"#
+            )
+        };
+        let body = [
+            r#"<w:sdt><w:sdtPr><w:alias w:val="Intro"/></w:sdtPr><w:sdtContent><w:p><w:r><w:t>Intro</w:t></w:r></w:p><w:p><w:hyperlink w:anchor="Later"><w:r><w:t>see later</w:t></w:r></w:hyperlink></w:p></w:sdtContent></w:sdt>"#,
+            r#"<w:p><w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText>TOC \o</w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r><w:r><w:t>Intro 1</w:t></w:r></w:p><w:p><w:r><w:t>Later 2</w:t></w:r><w:r><w:fldChar w:fldCharType="end"/></w:r></w:p>"#,
+            &item("One"),
+            &item("Two"),
+            r#"<w:p><w:commentRangeStart w:id="0"/><w:r><w:t>After</w:t><w:br w:type="page"/><w:t>the list</w:t></w:r><w:commentRangeEnd w:id="0"/><w:r><w:commentReference w:id="0"/></w:r></w:p>"#,
+            &code("fn main() {"),
+            "<w:p></w:p>",
+            &code("}"),
+            "<w:tbl><w:tr><w:tc><w:p><w:r><w:t>A</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>1</w:t></w:r></w:p></w:tc></w:tr></w:tbl>",
+            r#"<w:p><w:bookmarkStart w:id="0" w:name="later"/><w:r><w:t>Later</w:t></w:r></w:p>"#,
+        ]
+        .concat();
+        let parts = [
+            (
+                "word/document.xml",
+                format!(
+                    r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{body}<w:sectPr/></w:body></w:document>"#
+                ),
+            ),
+            (
+                "word/numbering.xml",
+                r#"<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:abstractNum w:abstractNumId="0"><w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%1."/></w:lvl></w:abstractNum><w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num></w:numbering>"#.to_string(),
+            ),
+            (
+                "word/comments.xml",
+                r#"<w:comments xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:comment w:id="0" w:author="Ann"><w:p><w:r><w:t>Check this</w:t></w:r></w:p></w:comment></w:comments>"#.to_string(),
+            ),
+        ];
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, xml) in parts {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(xml.as_bytes()).unwrap();
+        }
+        let data = zip.finish().unwrap().into_inner();
+
+        let marked = Options {
+            comments: Comments::End,
+            page_breaks: Some("---".to_string()),
+            ..Options::default()
+        };
+        for opts in [Options::default(), marked] {
+            // One top-level element at a time, the worst case for what
+            // has to carry over
+            let mut plain = Vec::new();
+            write_plain(&data, &opts, 1, &mut plain).unwrap();
+            let plain = String::from_utf8(plain).unwrap();
+            assert_eq!(plain, extract_plain(&data, &opts).unwrap());
+            let mut md = Vec::new();
+            write_markdown(&data, &opts, 1, &mut md).unwrap();
+            let md = String::from_utf8(md).unwrap();
+            assert_eq!(md, extract_markdown(&data, &opts).unwrap());
+            assert!(md.contains("[see later](#Later)"), "{md}");
+            assert!(md.contains("1. One\n2. Two\n\n"), "{md}");
+            assert!(md.contains("```\nfn main() {\n\n}\n```"), "{md}");
+            assert!(!md.contains("Intro 1"), "{md}");
+        }
+        assert!(!is_large(&data));
+    }
+
+    #[test]
+    fn link_targets_across_chunks() {
+        let xml = r#"<w:body><w:p><w:bookmarkStart w:id="0" w:name="Intro"/><w:bookmarkStart w:id="1" w:name="_GoBack"/><w:bookmarkStart w:id="2" w:name="Simple"/><w:bookmarkStart w:id="3" w:name="Split"/></w:p>
+<w:p><w:hyperlink w:anchor="intro" w:tooltip="a > b"><w:r><w:t>up</w:t></w:r></w:hyperlink><w:hyperlink r:id="rId1" w:anchor="Simple"/><w:hyperlink w:anchor="Missing"/></w:p>
+<w:p><w:fldSimple w:instr="HYPERLINK \l &quot;Simple&quot;"/><w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText xml:space="preserve"> HYPERLINK \l </w:instrText></w:r><w:r><w:instrText>&quot;Split&quot;</w:instrText></w:r><w:r><w:fldChar w:fldCharType="separate"/></w:r></w:p></w:body>"#;
+        let want: HashSet<String> = ["intro", "simple", "split"].map(String::from).into();
+        for capacity in 1..64 {
+            let source = BufReader::with_capacity(capacity, xml.as_bytes());
+            assert_eq!(link_targets(source).unwrap(), want, "capacity {capacity}");
+        }
+    }
+
+    /// A .docx whose body has content controls between plain paragraphs,
+    /// one of them nested in another.
+    fn controls_docx() -> Vec<u8> {
+        use std::io::Write;
+        let para = |text: &str| format!("<w:p><w:r><w:t>{text}</w:t></w:r></w:p>");
+        let control = |alias: &str, content: &str| {
+            format!(
+                r#"<w:sdt><w:sdtPr><w:alias w:val="{alias}"/></w:sdtPr><w:sdtContent>{content}</w:sdtContent></w:sdt>"#
+            )
+        };
+        let mut body = String::new();
+        for i in 0..6 {
+            body.push_str(&para(&format!("para {i}")));
+            body.push_str(&control(
+                "Outer",
+                &[
+                    para(&format!("in sdt {i}")),
+                    control("Inner", &para(&format!("nested {i}"))),
+                    para(&format!("after nested {i}")),
+                ]
+                .concat(),
+            ));
+        }
+        body.push_str(&para("the end"));
+        let xml = format!(
+            r#"<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{body}<w:sectPr/></w:body></w:document>"#
+        );
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file(
+            "word/document.xml",
+            zip::write::SimpleFileOptions::default(),
+        )
+        .unwrap();
+        zip.write_all(xml.as_bytes()).unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn batches_cut_inside_content_controls() {
+        let data = controls_docx();
+        let labelled = Options {
+            control_labels: true,
+            ..Options::default()
+        };
+        for opts in [Options::default(), labelled] {
+            let (whole_plain, whole_md) = (
+                extract_plain(&data, &opts).unwrap(),
+                extract_markdown(&data, &opts).unwrap(),
+            );
+            assert!(whole_plain.contains("nested 5\n\nafter nested 5\n\nthe end"));
+            // Every batch size cuts some control open at a different place
+            for batch in 1..400 {
+                let mut plain = Vec::new();
+                write_plain(&data, &opts, batch, &mut plain).unwrap();
+                assert_eq!(
+                    String::from_utf8(plain).unwrap(),
+                    whole_plain,
+                    "batch {batch}"
+                );
+                let mut md = Vec::new();
+                write_markdown(&data, &opts, batch, &mut md).unwrap();
+                assert_eq!(String::from_utf8(md).unwrap(), whole_md, "batch {batch}");
+            }
+        }
+    }
 }
@@ -0,0 +1,149 @@
+//! Files embedded in a document as OLE objects (`embeddings/*` parts).
+//!
+//! A `.docx` keeps an embedded worksheet or document either as the file
+//! itself (`Microsoft_Excel_Worksheet.xlsx`) or wrapped in an OLE2
+//! compound file (`oleObject1.bin`). The wrapper holds the file in a
+//! stream named for the kind of object: `\x01Ole10Native` for a packaged
+//! file of any type (an "attachment"), `CONTENTS` for a PDF, `Package`
+//! for an OOXML document. A `.doc` or `.xls` object is the compound file
+//! itself.
+
+use cfb::CompoundFile;
+use std::io::{Cursor, Read};
+
+use crate::format::OLE2_MAGIC;
+
+/// An embedded file, unwrapped from its OLE container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Embedded {
+    /// The file name it was embedded from, if the container records one.
+    pub(crate) name: Option<String>,
+    pub(crate) data: Vec<u8>,
+}
+
+/// Unwrap the bytes of an `embeddings/*` part. Anything that isn't an
+/// OLE wrapper around a file is returned as it is.
+pub(crate) fn unwrap(data: Vec<u8>) -> Embedded {
+    if data.len() < 8 || data[..8] != OLE2_MAGIC {
+        return Embedded { name: None, data };
+    }
+    let Ok(mut cfb) = CompoundFile::open(Cursor::new(&data[..])) else {
+        return Embedded { name: None, data };
+    };
+    if let Some(native) = read_stream(&mut cfb, "/\u{1}Ole10Native") {
+        if let Some(embedded) = parse_native(&native) {
+            return embedded;
+        }
+    }
+    for stream in ["/CONTENTS", "/Package"] {
+        if let Some(inner) = read_stream(&mut cfb, stream) {
+            return Embedded {
+                name: None,
+                data: inner,
+            };
+        }
+    }
+    Embedded { name: None, data }
+}
+
+fn read_stream(cfb: &mut CompoundFile<Cursor<&[u8]>>, path: &str) -> Option<Vec<u8>> {
+    let mut stream = cfb.open_stream(path).ok()?;
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).ok()?;
+    Some(buf)
+}
+
+/// Parse an `\x01Ole10Native` stream: the stream size, a flags word, the
+/// label and source path as NUL-terminated strings, two reserved words,
+/// the temporary path with its length in front, and the file's data with
+/// its length in front.
+fn parse_native(stream: &[u8]) -> Option<Embedded> {
+    let mut pos = 6;
+    let label = cstring(stream, &mut pos)?;
+    let path = cstring(stream, &mut pos)?;
+    pos += 4;
+    let temp_len = usize::try_from(u32_at(stream, pos)?).ok()?;
+    pos = pos.checked_add(4 + temp_len)?;
+    let size = usize::try_from(u32_at(stream, pos)?).ok()?;
+    let data = stream.get(pos + 4..pos.checked_add(4 + size)?)?.to_vec();
+    let file_name = path.rsplit(['\\', '/']).next().unwrap_or_default();
+    let name = [label, file_name.to_string()]
+        .into_iter()
+        .find(|n| !n.is_empty());
+    Some(Embedded { name, data })
+}
+
+/// The NUL-terminated string at `*pos`, in Windows-1252, moving past it.
+fn cstring(bytes: &[u8], pos: &mut usize) -> Option<String> {
+    let rest = bytes.get(*pos..)?;
+    let len = rest.iter().position(|&b| b == 0)?;
+    *pos += len + 1;
+    let (text, _, _) = encoding_rs::WINDOWS_1252.decode(&rest[..len]);
+    Some(text.into_owned())
+}
+
+fn u32_at(bytes: &[u8], pos: usize) -> Option<u32> {
+    let b = bytes.get(pos..pos.checked_add(4)?)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn native_stream(label: &str, path: &str, data: &[u8]) -> Vec<u8> {
+        let mut body = vec![2, 0];
+        for s in [label, path] {
+            body.extend_from_slice(s.as_bytes());
+            body.push(0);
+        }
+        body.extend_from_slice(&[0, 0, 3, 0]);
+        let temp = b"C:\\Temp\\x\0";
+        body.extend_from_slice(&u32::try_from(temp.len()).unwrap().to_le_bytes());
+        body.extend_from_slice(temp);
+        body.extend_from_slice(&u32::try_from(data.len()).unwrap().to_le_bytes());
+        body.extend_from_slice(data);
+        let mut stream = u32::try_from(body.len()).unwrap().to_le_bytes().to_vec();
+        stream.extend(body);
+        stream
+    }
+
+    fn compound(stream: &str, content: &[u8]) -> Vec<u8> {
+        let mut cfb = CompoundFile::create(Cursor::new(Vec::new())).unwrap();
+        cfb.create_stream(stream)
+            .unwrap()
+            .write_all(content)
+            .unwrap();
+        cfb.into_inner().into_inner()
+    }
+
+    #[test]
+    fn packaged_file() {
+        let stream = native_stream("report.pdf", "C:\\Users\\ann\\report.pdf", b"%PDF-1.4");
+        let embedded = unwrap(compound("/\u{1}Ole10Native", &stream));
+        assert_eq!(embedded.name.as_deref(), Some("report.pdf"));
+        assert_eq!(embedded.data, b"%PDF-1.4");
+    }
+
+    #[test]
+    fn package_without_label_takes_file_name() {
+        let stream = native_stream("", "C:\\data\\notes.txt", b"hi");
+        let embedded = parse_native(&stream).unwrap();
+        assert_eq!(embedded.name.as_deref(), Some("notes.txt"));
+    }
+
+    #[test]
+    fn contents_stream_and_plain_files() {
+        let embedded = unwrap(compound("/CONTENTS", b"%PDF-1.7"));
+        assert_eq!((embedded.name, embedded.data), (None, b"%PDF-1.7".to_vec()));
+        let zip = b"PK\x03\x04rest".to_vec();
+        assert_eq!(unwrap(zip.clone()).data, zip);
+    }
+
+    #[test]
+    fn truncated_native_stream() {
+        let stream = native_stream("a.bin", "a.bin", b"data");
+        assert_eq!(parse_native(&stream[..stream.len() - 2]), None);
+    }
+}
@@ -1,11 +1,14 @@
 //! Error types for batdoc.
 //!
 //! Provides a single [`BatdocError`] enum that replaces the previous
-//! `Box<dyn std::error::Error>` usage throughout the codebase.
+//! `Box<dyn std::error::Error>` usage throughout the codebase. Each error
+//! has a stable [`code`](BatdocError::code) for machine-readable reporting
+//! (`--errors json`).
 
 /// All errors that can occur during document parsing and rendering.
 #[derive(Debug, thiserror::Error)]
-pub(crate) enum BatdocError {
+#[non_exhaustive]
+pub enum BatdocError {
     /// I/O error (file read, stream read, OLE2 compound file).
     #[error("{0}")]
     Io(#[from] std::io::Error),
@@ -14,14 +17,130 @@ pub(crate) enum BatdocError {
     #[error("{0}")]
     Zip(#[from] zip::result::ZipError),
 
-    /// Document-level error (unsupported format, encryption, corruption).
+    /// Document-level error (corrupt or malformed document).
     #[error("{0}")]
     Document(String),
 
+    /// The document is password-protected.
+    #[error("document is encrypted")]
+    Encrypted,
+
+    /// The password given for an encrypted document is not its password.
+    #[error("incorrect password")]
+    WrongPassword,
+
+    /// The input is not a supported format, or the requested output does
+    /// not apply to it.
+    #[error("{0}")]
+    Unsupported(String),
+
+    /// The document parsed but has no extractable text.
+    #[error("{0}")]
+    Empty(String),
+
+    /// The input exceeds the size limit. `size_mib` is `None` when the
+    /// full size is unknown, as for a download that was cut off.
+    #[error("too large ({}max {max_mib} MiB)", size_prefix(*.size_mib))]
+    TooLarge {
+        size_mib: Option<f64>,
+        max_mib: usize,
+    },
+
+    /// The options given cannot be applied to this input.
+    #[error("{0}")]
+    Usage(String),
+
+    /// Extraction was stopped through a [`CancelToken`](crate::CancelToken).
+    #[error("extraction cancelled")]
+    Cancelled,
+
     /// Pretty-printing error (bat rendering failure).
     #[error("pretty print: {0}")]
     Render(String),
 }
 
+impl BatdocError {
+    /// A [`TooLarge`](Self::TooLarge) error for an input of `size` bytes
+    /// (`None` if unknown) against a limit of `max` bytes.
+    #[allow(clippy::cast_precision_loss)] // only used in the message
+    pub(crate) fn too_large(size: Option<usize>, max: usize) -> Self {
+        Self::TooLarge {
+            size_mib: size.map(|n| n as f64 / (1024.0 * 1024.0)),
+            max_mib: max / (1024 * 1024),
+        }
+    }
+
+    /// Stable, machine-readable error code.
+    ///
+    /// These strings are part of the `--errors json` output and must not
+    /// change once released.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Io(e) => match e.kind() {
+                std::io::ErrorKind::NotFound => "not_found",
+                std::io::ErrorKind::PermissionDenied => "permission_denied",
+                _ => "io",
+            },
+            Self::Zip(_) | Self::Document(_) => "corrupt",
+            Self::Encrypted => "encrypted",
+            Self::WrongPassword => "wrong_password",
+            Self::Unsupported(_) => "unsupported",
+            Self::Empty(_) => "empty",
+            Self::TooLarge { .. } => "too_large",
+            Self::Usage(_) => "usage",
+            Self::Cancelled => "cancelled",
+            Self::Render(_) => "render",
+        }
+    }
+}
+
+/// `"300.0 MiB, "` for a known size, nothing otherwise.
+fn size_prefix(size_mib: Option<f64>) -> String {
+    size_mib.map_or_else(String::new, |mib| format!("{mib:.1} MiB, "))
+}
+
 /// Convenience alias used throughout the crate.
-pub(crate) type Result<T> = std::result::Result<T, BatdocError>;
+pub type Result<T> = std::result::Result<T, BatdocError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── code ─────────────────────────────────────────────────────
+
+    #[test]
+    fn io_codes_by_kind() {
+        let not_found = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert_eq!(BatdocError::from(not_found).code(), "not_found");
+        let other = std::io::Error::other("boom");
+        assert_eq!(BatdocError::from(other).code(), "io");
+    }
+
+    #[test]
+    fn document_codes() {
+        assert_eq!(BatdocError::Encrypted.code(), "encrypted");
+        assert_eq!(BatdocError::WrongPassword.code(), "wrong_password");
+        assert_eq!(BatdocError::Document("bad".into()).code(), "corrupt");
+        assert_eq!(
+            BatdocError::Unsupported("nope".into()).code(),
+            "unsupported"
+        );
+    }
+
+    #[test]
+    fn too_large_message() {
+        let e = BatdocError::TooLarge {
+            size_mib: Some(300.0),
+            max_mib: 256,
+        };
+        assert_eq!(e.to_string(), "too large (300.0 MiB, max 256 MiB)");
+        assert_eq!(e.code(), "too_large");
+
+        let e = BatdocError::TooLarge {
+            size_mib: None,
+            max_mib: 256,
+        };
+        assert_eq!(e.to_string(), "too large (max 256 MiB)");
+    }
+}
@@ -0,0 +1,507 @@
+//! Word field instructions.
+//!
+//! Fields such as `DOCPROPERTY Title` or `CREATEDATE \@ "MMMM d, yyyy"`
+//! carry a cached result that is only as fresh as the last time Word
+//! updated fields — often never, for generated documents. Fields that draw
+//! from document properties are re-evaluated here against the file's
+//! [`Metadata`], so the rendered value matches the properties. `HYPERLINK`
+//! instructions are parsed here too, for their target. Shared by the
+//! `.doc` and `.docx` parsers.
+//!
+//! `DATE` and `TIME` (the current date) are deliberately left alone: the
+//! cached result is the only stable answer for a text dump.
+
+use std::fmt::Write as _;
+
+use crate::metadata::{DateTime, Metadata};
+
+/// The field type keyword (first word of the instruction), upper-cased.
+pub(crate) fn keyword(instr: &str) -> String {
+    instr
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_uppercase()
+}
+
+/// True if the field's value comes from document properties and can be
+/// resolved with [`resolve`].
+pub(crate) fn is_metadata_field(instr: &str) -> bool {
+    matches!(
+        keyword(instr).as_str(),
+        "DOCPROPERTY"
+            | "TITLE"
+            | "SUBJECT"
+            | "AUTHOR"
+            | "KEYWORDS"
+            | "COMMENTS"
+            | "LASTSAVEDBY"
+            | "REVNUM"
+            | "TEMPLATE"
+            | "CREATEDATE"
+            | "SAVEDATE"
+            | "PRINTDATE"
+    )
+}
+
+/// Evaluate a document-property field against `meta`.
+///
+/// Returns `None` when the field isn't a property field or the property
+/// isn't set, in which case the caller keeps the cached result.
+pub(crate) fn resolve(instr: &str, meta: &Metadata) -> Option<String> {
+    if !is_metadata_field(instr) {
+        return None;
+    }
+    let field = Instruction::parse(instr);
+
+    let value = match field.keyword.as_str() {
+        "DOCPROPERTY" => property(meta, field.args.first()?)?,
+        "CREATEDATE" => Value::Date(meta.created?),
+        "SAVEDATE" => Value::Date(meta.modified?),
+        "PRINTDATE" => Value::Date(meta.printed?),
+        kw => {
+            // `TITLE "New title"` sets the property and shows the new value
+            if let Some(arg) = field.args.first() {
+                Value::Text(arg.clone())
+            } else {
+                let name = match kw {
+                    "REVNUM" => "RevisionNumber",
+                    "LASTSAVEDBY" => "LastSavedBy",
+                    _ => kw,
+                };
+                property(meta, name)?
+            }
+        }
+    };
+
+    let text = match value {
+        Value::Text(s) => s,
+        Value::Date(dt) => field
+            .date_picture
+            .as_deref()
+            .map_or_else(|| dt.to_iso(), |pic| format_date(dt, pic)),
+    };
+
+    Some(match field.text_format.as_deref() {
+        Some(fmt) => apply_text_format(&text, fmt),
+        None => text,
+    })
+}
+
+enum Value {
+    Text(String),
+    Date(DateTime),
+}
+
+/// Look up a property by its `DOCPROPERTY` name.
+fn property(meta: &Metadata, name: &str) -> Option<Value> {
+    let text = |v: &Option<String>| v.clone().map(Value::Text);
+    match name.to_ascii_lowercase().as_str() {
+        "title" => text(&meta.title),
+        "subject" => text(&meta.subject),
+        "author" => text(&meta.author),
+        "keywords" => text(&meta.keywords),
+        "comments" => text(&meta.comments),
+        "lastsavedby" => text(&meta.last_saved_by),
+        "revisionnumber" => text(&meta.revision),
+        "template" => text(&meta.template),
+        "category" => text(&meta.category),
+        "manager" => text(&meta.manager),
+        "company" => text(&meta.company),
+        "createtime" => meta.created.map(Value::Date),
+        "lastsavedtime" => meta.modified.map(Value::Date),
+        "lastprinted" => meta.printed.map(Value::Date),
+        _ => meta
+            .custom_property(name)
+            .map(|v| Value::Text(v.to_string())),
+    }
+}
+
+/// A field instruction split into keyword, arguments, and switches.
+#[derive(Debug, Default)]
+struct Instruction {
+    keyword: String,
+    args: Vec<String>,
+    /// `\@ "picture"` date-time format.
+    date_picture: Option<String>,
+    /// `\* Upper` etc. (`MERGEFORMAT` is ignored).
+    text_format: Option<String>,
+}
+
+impl Instruction {
+    fn parse(instr: &str) -> Self {
+        let mut tokens = tokenize(instr).into_iter();
+        let mut field = Self {
+            keyword: tokens.next().unwrap_or_default().to_ascii_uppercase(),
+            ..Self::default()
+        };
+
+        while let Some(tok) = tokens.next() {
+            match tok.as_str() {
+                "\\@" => field.date_picture = tokens.next(),
+                "\\*" => {
+                    if let Some(fmt) = tokens.next() {
+                        if !fmt.eq_ignore_ascii_case("MERGEFORMAT") {
+                            field.text_format = Some(fmt);
+                        }
+                    }
+                }
+                // Other switches (\h, \p, \! …) take no argument we need
+                s if s.starts_with('\\') => {}
+                _ => field.args.push(tok),
+            }
+        }
+
+        field
+    }
+}
+
+/// Split an instruction into words, treating `"quoted text"` as one word.
+fn tokenize(instr: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = instr.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut tok = String::new();
+            for c in chars.by_ref() {
+                if c == '"' {
+                    break;
+                }
+                tok.push(c);
+            }
+            tokens.push(tok);
+        } else {
+            let mut tok = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' {
+                    break;
+                }
+                tok.push(c);
+                chars.next();
+            }
+            tokens.push(tok);
+        }
+    }
+
+    tokens
+}
+
+/// Apply a `\*` text format switch.
+fn apply_text_format(text: &str, fmt: &str) -> String {
+    match fmt.to_ascii_lowercase().as_str() {
+        "upper" => text.to_uppercase(),
+        "lower" => text.to_lowercase(),
+        "caps" => text
+            .split(' ')
+            .map(capitalize)
+            .collect::<Vec<_>>()
+            .join(" "),
+        "firstcap" => capitalize(text),
+        _ => text.to_string(),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// Format a date with a Word `\@` picture such as `"MMMM d, yyyy"` or
+/// `"dd/MM/yyyy HH:mm"`. Text in single quotes is copied literally.
+fn format_date(dt: DateTime, picture: &str) -> String {
+    let chars: Vec<char> = picture.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\'' {
+            let close = chars[i + 1..]
+                .iter()
+                .position(|&ch| ch == '\'')
+                .map_or(chars.len(), |p| i + 1 + p);
+            out.extend(&chars[i + 1..close]);
+            i = close + 1;
+            continue;
+        }
+        if matches!(
+            &chars[i..],
+            ['A' | 'a', 'M' | 'm', '/', 'P' | 'p', 'M' | 'm', ..]
+        ) {
+            let pm = dt.hour >= 12;
+            let upper = c == 'A';
+            out.push_str(match (pm, upper) {
+                (false, true) => "AM",
+                (true, true) => "PM",
+                (false, false) => "am",
+                (true, false) => "pm",
+            });
+            i += 5;
+            continue;
+        }
+
+        let run = chars[i..].iter().take_while(|&&ch| ch == c).count();
+        let hour12 = match dt.hour % 12 {
+            0 => 12,
+            h => h,
+        };
+        let month_name = MONTHS[(dt.month as usize).saturating_sub(1) % 12]; // u32 → usize: lossless
+        let weekday_name = WEEKDAYS[dt.weekday() as usize];
+        let _ = match (c, run) {
+            ('y' | 'Y', 1 | 2) => write!(out, "{:02}", dt.year.rem_euclid(100)),
+            ('y' | 'Y', _) => write!(out, "{:04}", dt.year),
+            ('M', 1) => write!(out, "{}", dt.month),
+            ('M', 2) => write!(out, "{:02}", dt.month),
+            ('M', 3) => out.write_str(&month_name[..3]),
+            ('M', _) => out.write_str(month_name),
+            ('d' | 'D', 1) => write!(out, "{}", dt.day),
+            ('d' | 'D', 2) => write!(out, "{:02}", dt.day),
+            ('d' | 'D', 3) => out.write_str(&weekday_name[..3]),
+            ('d' | 'D', _) => out.write_str(weekday_name),
+            ('H', 1) => write!(out, "{}", dt.hour),
+            ('H', _) => write!(out, "{:02}", dt.hour),
+            ('h', 1) => write!(out, "{hour12}"),
+            ('h', _) => write!(out, "{hour12:02}"),
+            ('m', 1) => write!(out, "{}", dt.minute),
+            ('m', _) => write!(out, "{:02}", dt.minute),
+            ('s' | 'S', 1) => write!(out, "{}", dt.second),
+            ('s' | 'S', _) => write!(out, "{:02}", dt.second),
+            _ => out.write_str(&c.to_string().repeat(run)),
+        };
+        i += run;
+    }
+
+    out
+}
+
+/// The bookmark named by the `\l` switch of a HYPERLINK instruction.
+pub(crate) fn internal_link_target(instruction: &str) -> Option<String> {
+    if keyword(instruction) != "HYPERLINK" {
+        return None;
+    }
+    let (_, rest) = instruction.split_once("\\l")?;
+    let rest = rest.trim_start();
+    let name = rest.strip_prefix('"').map_or_else(
+        || rest.split_whitespace().next().unwrap_or_default(),
+        |inner| inner.split('"').next().unwrap_or_default(),
+    );
+    // Bookmark names are letters, digits, and underscores
+    (!name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_'))
+        .then(|| name.to_string())
+}
+
+/// Extract a URL from a HYPERLINK field instruction string. A link within
+/// the document (`\l "bookmark"`) becomes `#bookmark`.
+///
+/// Field instruction format: `HYPERLINK "http://example.com" \l "bookmark"`
+/// or `HYPERLINK http://example.com`. We extract the URL, handling both
+/// quoted and unquoted forms.
+pub(crate) fn hyperlink_url(instruction: &str) -> Option<String> {
+    let trimmed = instruction.trim();
+
+    // Must start with "HYPERLINK" (case-insensitive)
+    let rest = if let Some(r) = trimmed.strip_prefix("HYPERLINK") {
+        r
+    } else if let Some(r) = trimmed.strip_prefix("hyperlink") {
+        r
+    } else {
+        let lower = trimmed.to_lowercase();
+        if let Some(idx) = lower.find("hyperlink") {
+            &trimmed[idx + 9..]
+        } else {
+            return None;
+        }
+    };
+
+    let rest = rest.trim_start();
+    if rest.is_empty() {
+        return None;
+    }
+
+    // Internal link: `HYPERLINK \l "bookmark"` with no URL
+    if rest.starts_with('\\') {
+        return internal_link_target(instruction).map(|name| format!("#{name}"));
+    }
+
+    // Extract URL: may be quoted or unquoted
+    let url = rest.strip_prefix('"').map_or_else(
+        || {
+            // Unquoted: take until whitespace or backslash (switch start)
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == '\\')
+                .unwrap_or(rest.len());
+            &rest[..end]
+        },
+        |inner| {
+            // Quoted: find closing quote
+            let end = inner.find('"').unwrap_or(inner.len());
+            &inner[..end]
+        },
+    );
+
+    if url.is_empty() {
+        None
+    } else {
+        Some(url.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta() -> Metadata {
+        Metadata {
+            title: Some("Annual Plan".into()),
+            author: Some("Jo Park".into()),
+            company: Some("Initech".into()),
+            created: DateTime::parse_w3cdtf("2024-03-01T14:05:09Z"),
+            custom: vec![("Client".into(), "Globex".into())],
+            ..Metadata::default()
+        }
+    }
+
+    // ── resolve ──────────────────────────────────────────────────
+
+    #[test]
+    fn docproperty_builtin() {
+        assert_eq!(
+            resolve(r" DOCPROPERTY Title \* MERGEFORMAT ", &meta()).as_deref(),
+            Some("Annual Plan")
+        );
+        assert_eq!(
+            resolve(r#"DOCPROPERTY "Company""#, &meta()).as_deref(),
+            Some("Initech")
+        );
+    }
+
+    #[test]
+    fn docproperty_custom() {
+        assert_eq!(
+            resolve("DOCPROPERTY Client", &meta()).as_deref(),
+            Some("Globex")
+        );
+    }
+
+    #[test]
+    fn unset_property_unresolved() {
+        assert_eq!(resolve("DOCPROPERTY Manager", &meta()), None);
+        assert_eq!(resolve("SAVEDATE", &meta()), None);
+    }
+
+    #[test]
+    fn direct_property_fields() {
+        assert_eq!(resolve("AUTHOR", &meta()).as_deref(), Some("Jo Park"));
+        assert_eq!(
+            resolve(r#"TITLE "Replacement""#, &meta()).as_deref(),
+            Some("Replacement")
+        );
+    }
+
+    #[test]
+    fn createdate_default_iso() {
+        assert_eq!(
+            resolve("CREATEDATE", &meta()).as_deref(),
+            Some("2024-03-01 14:05:09")
+        );
+    }
+
+    #[test]
+    fn createdate_picture() {
+        assert_eq!(
+            resolve(r#"CREATEDATE \@ "MMMM d, yyyy""#, &meta()).as_deref(),
+            Some("March 1, 2024")
+        );
+        assert_eq!(
+            resolve(r#"CREATEDATE \@ "dddd dd/MM/yy h:mm am/pm""#, &meta()).as_deref(),
+            Some("Friday 01/03/24 2:05 pm")
+        );
+    }
+
+    #[test]
+    fn text_format_switch() {
+        assert_eq!(
+            resolve(r"AUTHOR \* Upper", &meta()).as_deref(),
+            Some("JO PARK")
+        );
+    }
+
+    #[test]
+    fn other_fields_unresolved() {
+        assert!(!is_metadata_field("PAGE"));
+        assert_eq!(resolve("DATE", &meta()), None);
+        assert_eq!(resolve(r#"HYPERLINK "http://x""#, &meta()), None);
+    }
+
+    // ── format_date ──────────────────────────────────────────────
+
+    #[test]
+    fn format_date_quoted_literal() {
+        let dt = DateTime::parse_w3cdtf("2024-03-01T09:00:00Z").unwrap();
+        assert_eq!(format_date(dt, "'Week of' MMM d"), "Week of Mar 1");
+        assert_eq!(format_date(dt, "HH:mm:ss"), "09:00:00");
+    }
+
+    // ── hyperlink_url ────────────────────────────────────────────
+
+    #[test]
+    fn hyperlink_url_quoted() {
+        assert_eq!(
+            hyperlink_url(r#" HYPERLINK "https://example.com" \l "top""#),
+            Some("https://example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn hyperlink_url_unquoted() {
+        assert_eq!(
+            hyperlink_url("HYPERLINK http://example.com"),
+            Some("http://example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn hyperlink_url_not_hyperlink() {
+        assert_eq!(hyperlink_url("TOC \\o \\h"), None);
+    }
+
+    #[test]
+    fn hyperlink_url_internal() {
+        assert_eq!(
+            hyperlink_url(r#" HYPERLINK \l "_Toc123" \h "#),
+            Some("#_Toc123".into())
+        );
+        assert_eq!(hyperlink_url(r"HYPERLINK \l"), None);
+    }
+}
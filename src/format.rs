@@ -0,0 +1,213 @@
+//! Document format detection.
+
+use crate::error::{BatdocError, Result};
+use crate::xml_util;
+
+// Magic signatures
+pub(crate) const OLE2_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+pub(crate) const ZIP_MAGIC: [u8; 4] = [0x50, 0x4B, 0x03, 0x04];
+const PDF_MAGIC: [u8; 5] = [0x25, 0x50, 0x44, 0x46, 0x2D]; // %PDF-
+
+/// Detected document format based on magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Format {
+    /// Legacy OLE2 Word 97+ binary format.
+    Doc,
+    /// Legacy OLE2 Excel 97+ binary format (BIFF8).
+    Xls,
+    /// Modern OOXML Word (ZIP-based) format.
+    Docx,
+    /// Modern OOXML Excel (ZIP-based) format.
+    Xlsx,
+    /// Modern OOXML `PowerPoint` (ZIP-based) format.
+    Pptx,
+    /// PDF document.
+    Pdf,
+}
+
+impl Format {
+    /// Short lowercase name, as used in `--errors json` output and config
+    /// file sections.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Doc => "doc",
+            Self::Xls => "xls",
+            Self::Docx => "docx",
+            Self::Xlsx => "xlsx",
+            Self::Pptx => "pptx",
+            Self::Pdf => "pdf",
+        }
+    }
+
+    /// The format usually stored under file extension `ext` (without the
+    /// dot, any case), including macro-enabled and template variants.
+    pub(crate) fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "doc" | "dot" => Some(Self::Doc),
+            "xls" | "xlt" => Some(Self::Xls),
+            "docx" | "docm" | "dotx" | "dotm" => Some(Self::Docx),
+            "xlsx" | "xlsm" | "xltx" | "xltm" => Some(Self::Xlsx),
+            "pptx" | "pptm" | "potx" | "ppsx" => Some(Self::Pptx),
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+
+    /// The format with the given [`name`](Self::name), if any.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Doc,
+            Self::Xls,
+            Self::Docx,
+            Self::Xlsx,
+            Self::Pptx,
+            Self::Pdf,
+        ]
+        .into_iter()
+        .find(|f| f.name() == name)
+    }
+}
+
+/// [`detect_format`], falling back to the format implied by a file
+/// extension when the signature is missing or unrecognized (a truncated
+/// header, say). Encrypted and corrupt containers are still reported.
+pub(crate) fn detect_format_or(data: &[u8], fallback: Option<Format>) -> Result<Format> {
+    match (detect_format(data), fallback) {
+        (Err(BatdocError::Unsupported(_)), Some(format)) => Ok(format),
+        (result, _) => result,
+    }
+}
+
+/// Detect the document format from magic bytes.
+///
+/// For OLE2 formats, peeks inside the compound file to distinguish
+/// `.doc` (has `WordDocument` stream) from `.xls` (has `Workbook` stream).
+/// For ZIP-based formats, peeks inside the archive to distinguish
+/// `.docx` (has `word/document.xml`) from `.xlsx` (has `xl/workbook.xml`).
+/// A Word document whose main part has another name (`word/document2.xml`)
+/// is recognized by the part's content type.
+pub(crate) fn detect_format(data: &[u8]) -> Result<Format> {
+    if data.len() >= 8 && data[..8] == OLE2_MAGIC {
+        let cursor = std::io::Cursor::new(data);
+        let cfb = cfb::CompoundFile::open(cursor)?;
+        if cfb.exists("/WordDocument") {
+            Ok(Format::Doc)
+        } else if cfb.exists("/Workbook") || cfb.exists("/Book") {
+            Ok(Format::Xls)
+        } else if cfb.exists("/EncryptedPackage") {
+            // Password-protected OOXML files are wrapped in an OLE2 container
+            Err(BatdocError::Encrypted)
+        } else {
+            Err(BatdocError::Unsupported(
+                "OLE2 file is not a .doc or .xls document".into(),
+            ))
+        }
+    } else if data.len() >= 5 && data[..5] == PDF_MAGIC {
+        Ok(Format::Pdf)
+    } else if data.len() >= 4 && data[..4] == ZIP_MAGIC {
+        let cursor = std::io::Cursor::new(data);
+        let mut archive = zip::ZipArchive::new(cursor)?;
+        if archive.index_for_name("word/document.xml").is_some() || is_word_package(&mut archive) {
+            Ok(Format::Docx)
+        } else if archive.index_for_name("xl/workbook.xml").is_some() {
+            Ok(Format::Xlsx)
+        } else if archive.index_for_name("ppt/presentation.xml").is_some() {
+            Ok(Format::Pptx)
+        } else {
+            Err(BatdocError::Unsupported(
+                "ZIP archive is not a .docx, .xlsx, or .pptx file".into(),
+            ))
+        }
+    } else {
+        Err(BatdocError::Unsupported(
+            "not a supported document (unrecognized format)".into(),
+        ))
+    }
+}
+
+/// Whether the package's main part, found through its `officeDocument`
+/// relationship, is a Word document.
+fn is_word_package(archive: &mut zip::ZipArchive<std::io::Cursor<&[u8]>>) -> bool {
+    let Some(main) = xml_util::main_part(archive) else {
+        return false;
+    };
+    xml_util::content_type(archive, &main).is_some_and(|ct| {
+        ct.contains("wordprocessingml") || ct.starts_with("application/vnd.ms-word")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── detect_format_or ─────────────────────────────────────────
+
+    #[test]
+    fn falls_back_on_unrecognized_signature() {
+        assert_eq!(
+            detect_format_or(b"junk", Some(Format::Pdf)).unwrap(),
+            Format::Pdf
+        );
+        assert!(matches!(
+            detect_format_or(b"junk", None),
+            Err(BatdocError::Unsupported(_))
+        ));
+        // A recognized signature wins over the hint
+        assert_eq!(
+            detect_format_or(b"%PDF-1.7", Some(Format::Doc)).unwrap(),
+            Format::Pdf
+        );
+    }
+
+    #[test]
+    fn corrupt_container_not_masked() {
+        let result = detect_format_or(&ZIP_MAGIC, Some(Format::Docx));
+        assert!(matches!(result, Err(BatdocError::Zip(_))));
+    }
+
+    // ── detect_format ────────────────────────────────────────────
+
+    #[test]
+    fn word_package_with_renamed_main_part() {
+        use std::io::Write;
+        let zip_of = |parts: &[(&str, &str)]| {
+            let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+            for (name, xml) in parts {
+                zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                    .unwrap();
+                zip.write_all(xml.as_bytes()).unwrap();
+            }
+            zip.finish().unwrap().into_inner()
+        };
+        let rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="/word/document2.xml"/></Relationships>"#;
+        let types = r#"<Types><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document2.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#;
+        let data = zip_of(&[
+            ("_rels/.rels", rels),
+            ("[Content_Types].xml", types),
+            ("word/document2.xml", "<w:document/>"),
+        ]);
+        assert_eq!(detect_format(&data).unwrap(), Format::Docx);
+
+        // Without a Word content type, still not a known package
+        let data = zip_of(&[
+            ("_rels/.rels", rels),
+            ("word/document2.xml", "<w:document/>"),
+        ]);
+        assert!(matches!(
+            detect_format(&data),
+            Err(BatdocError::Unsupported(_))
+        ));
+    }
+
+    // ── from_extension ───────────────────────────────────────────
+
+    #[test]
+    fn extensions() {
+        assert_eq!(Format::from_extension("DOCX"), Some(Format::Docx));
+        assert_eq!(Format::from_extension("xlsm"), Some(Format::Xlsx));
+        assert_eq!(Format::from_extension("dot"), Some(Format::Doc));
+        assert_eq!(Format::from_extension("txt"), None);
+    }
+}
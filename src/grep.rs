@@ -0,0 +1,139 @@
+//! Search across documents (`--grep`).
+//!
+//! Matches a literal pattern against each line of the document model
+//! ([`Block`]s) rather than the rendered output, so every format is
+//! searched the same way and each hit can name where it was found: the
+//! page, the slide, or the sheet and row. Table rows are searched as one
+//! tab-separated line.
+
+use std::fmt::Write as _;
+
+use crate::document::Block;
+
+/// grep's default colors: file names magenta, locations green, and
+/// matches bold red.
+const FILE_COLOR: &str = "\x1b[35m";
+const LOCATION_COLOR: &str = "\x1b[32m";
+const MATCH_COLOR: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+/// Lines of `blocks` containing `pattern`, one per output line as
+/// `source:location: text`, with matches highlighted when `color` is set.
+/// The location is left out for documents without pages, slides, or
+/// sheets.
+pub(crate) fn search(blocks: &[Block], source: &str, pattern: &str, color: bool) -> String {
+    let mut out = String::new();
+    let mut marker: Option<String> = None;
+    let mut hit = |location: Option<&str>, line: &str| {
+        if !line.contains(pattern) {
+            return;
+        }
+        if color {
+            let _ = write!(out, "{FILE_COLOR}{source}{RESET}:");
+            if let Some(location) = location {
+                let _ = write!(out, "{LOCATION_COLOR}{location}{RESET}:");
+            }
+            let _ = writeln!(
+                out,
+                " {}",
+                line.replace(pattern, &format!("{MATCH_COLOR}{pattern}{RESET}"))
+            );
+        } else {
+            out.push_str(source);
+            out.push(':');
+            if let Some(location) = location {
+                out.push_str(location);
+                out.push(':');
+            }
+            let _ = writeln!(out, " {line}");
+        }
+    };
+
+    for block in blocks {
+        match block {
+            Block::Page { number } => marker = Some(format!("page {number}")),
+            Block::Slide { number } => marker = Some(format!("slide {number}")),
+            Block::Heading { text, .. }
+            | Block::Paragraph { text, .. }
+            | Block::ListItem { text, .. } => {
+                for line in text.lines() {
+                    hit(marker.as_deref(), line);
+                }
+            }
+            Block::Table { name, rows } => {
+                for (i, row) in rows.iter().enumerate() {
+                    let line = row
+                        .iter()
+                        .map(|cell| cell.split_whitespace().collect::<Vec<_>>().join(" "))
+                        .collect::<Vec<_>>()
+                        .join("\t");
+                    match name {
+                        Some(sheet) => hit(Some(&format!("{sheet}, row {}", i + 1)), &line),
+                        None => hit(marker.as_deref(), &line),
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn para(text: &str) -> Block {
+        Block::Paragraph {
+            text: text.into(),
+            spans: Vec::new(),
+        }
+    }
+
+    // ── search ───────────────────────────────────────────────────
+
+    #[test]
+    fn matching_lines_with_locations() {
+        let blocks = vec![
+            para("Invoice 12\nunrelated"),
+            Block::Page { number: 2 },
+            para("Late invoice fee; Invoice 13"),
+            Block::Table {
+                name: None,
+                rows: vec![vec!["Invoice".into(), "due\ndate".into()]],
+            },
+        ];
+        assert_eq!(
+            search(&blocks, "a.pdf", "Invoice", false),
+            "a.pdf: Invoice 12\n\
+             a.pdf:page 2: Late invoice fee; Invoice 13\n\
+             a.pdf:page 2: Invoice\tdue date\n"
+        );
+    }
+
+    #[test]
+    fn sheet_rows_are_located() {
+        let blocks = vec![Block::Table {
+            name: Some("Q1".into()),
+            rows: vec![vec!["Item".into()], vec!["Rent".into(), "1200".into()]],
+        }];
+        assert_eq!(
+            search(&blocks, "b.xlsx", "Rent", false),
+            "b.xlsx:Q1, row 2: Rent\t1200\n"
+        );
+    }
+
+    #[test]
+    fn matches_highlighted_in_color() {
+        let blocks = vec![Block::Slide { number: 1 }, para("a tax and a tax")];
+        assert_eq!(
+            search(&blocks, "d.pptx", "tax", true),
+            "\x1b[35md.pptx\x1b[0m:\x1b[32mslide 1\x1b[0m: \
+             a \x1b[1;31mtax\x1b[0m and a \x1b[1;31mtax\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn no_match_is_empty() {
+        assert_eq!(search(&[para("nothing here")], "c.doc", "x", false), "");
+    }
+}
@@ -9,6 +9,158 @@
 //! - `"Appendix N:"` / `"Scenario N:"` → `## headings`
 //! - Short standalone lines (< 80 chars, no sentence punctuation) → `**bold**`
 //! - Tab-separated lines with consistent columns → markdown tables
+//! - Space-aligned columns (PDF layout) → markdown tables, via
+//!   [`render_aligned_tables`]
+//! - Lines starting with `>` (quoted replies) → markdown blockquotes
+//! - Runs of short capitalised lines after a closing salutation, or with
+//!   an address or contact line among them (signatures, addresses) → one
+//!   block with hard line breaks, so they don't become a stack of bold
+//!   headings
+//!
+//! [`reflow`] runs before inference to undo hard line breaks that split
+//! sentences across lines (common in PDFs and `.doc` files with manual
+//! line breaks).
+
+/// Characters that end a sentence or clause. A line ending in one of these
+/// is never joined with the next line.
+const TERMINAL_PUNCTUATION: [char; 6] = ['.', '!', '?', ':', ';', '…'];
+
+/// Closing quotes and brackets that may follow terminal punctuation
+/// (`He said "stop."`).
+const CLOSING_MARKS: [char; 6] = ['"', '\'', ')', ']', '”', '’'];
+
+/// Join hard-wrapped lines into paragraphs.
+///
+/// Two consecutive non-blank lines are joined when the first lacks terminal
+/// punctuation and either the second continues in lowercase or the first is
+/// long enough (≥ 60% of the wrap width, see [`wrap_width`]) to look like it
+/// hit the wrap margin. Across a single blank line only the lowercase-continuation rule
+/// applies, since `.doc` manual line breaks come out as paragraph breaks.
+///
+/// Headings, list items, tab-separated rows, and space-aligned columns are
+/// never joined.
+pub(crate) fn reflow(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    let wrap_width = wrap_width(&lines);
+
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+        if line.trim().is_empty() {
+            out.push(String::new());
+            continue;
+        }
+
+        let mut para = line.to_string();
+        let mut last = line;
+        loop {
+            let next = lines.get(i).copied().unwrap_or("");
+            if !next.trim().is_empty() {
+                if !is_wrapped(last, next, wrap_width, false) {
+                    break;
+                }
+                join_line(&mut para, next);
+                last = next;
+                i += 1;
+            } else if let Some(&after) = lines.get(i + 1) {
+                // `next` is blank here; only bridge a single blank line.
+                if after.trim().is_empty() || !is_wrapped(last, after, wrap_width, true) {
+                    break;
+                }
+                join_line(&mut para, after);
+                last = after;
+                i += 2;
+            } else {
+                break;
+            }
+        }
+        out.push(para);
+    }
+
+    let mut result = out.join("\n");
+    if text.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// The width the text looks wrapped at: the 90th percentile of the lengths
+/// of its prose lines, so one long line doesn't set it for the rest.
+/// Structural lines and lines without a space (a URL, say) aren't prose.
+fn wrap_width(lines: &[&str]) -> usize {
+    let mut widths: Vec<usize> = lines
+        .iter()
+        .filter(|l| l.trim().contains(char::is_whitespace) && !is_structural_line(l))
+        .map(|l| l.chars().count())
+        .collect();
+    widths.sort_unstable();
+    let i = (widths.len() * 9 / 10).min(widths.len().saturating_sub(1));
+    widths.get(i).copied().unwrap_or(0)
+}
+
+/// Append a continuation line to a paragraph. A trailing hyphen is kept and
+/// the words are joined without a space (`"self-" + "contained"`).
+fn join_line(para: &mut String, next: &str) {
+    let next = next.trim_start();
+    if !para.ends_with('-') {
+        para.push(' ');
+    }
+    para.push_str(next);
+}
+
+/// Decide whether `next` continues the sentence that `prev` started.
+fn is_wrapped(prev: &str, next: &str, wrap_width: usize, across_blank: bool) -> bool {
+    let prev_trimmed = prev.trim();
+    let next_trimmed = next.trim();
+
+    if is_structural_line(prev) || is_structural_line(next) {
+        return false;
+    }
+
+    let end = prev_trimmed.trim_end_matches(CLOSING_MARKS);
+    if end.ends_with(TERMINAL_PUNCTUATION) {
+        return false;
+    }
+
+    let Some(first) = next_trimmed.chars().next() else {
+        return false;
+    };
+    if first.is_lowercase() {
+        return true;
+    }
+    if across_blank {
+        return false;
+    }
+
+    // Long line without punctuation followed by a capitalised word: most
+    // likely wrapped before a proper noun or an "I".
+    prev.chars().count() * 5 >= wrap_width * 3
+}
+
+/// Lines that carry layout of their own and must stay on their own line:
+/// tab-separated, space-aligned, or markdown table rows, numbered headings,
+/// list items, and quoted reply lines.
+fn is_structural_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    if trimmed.contains('\t')
+        || trimmed.contains("   ")
+        || trimmed.starts_with('>')
+        || trimmed.starts_with('|')
+    {
+        return true;
+    }
+    if detect_numbered_heading(trimmed).is_some() {
+        return true;
+    }
+    let mut chars = trimmed.chars();
+    matches!(
+        (chars.next(), chars.next()),
+        (Some('-' | '*' | '•' | '–' | '◦' | '▪'), Some(' '))
+    )
+}
 
 /// Convert plain text into markdown using heuristics.
 pub(crate) fn plain_to_markdown(text: &str) -> String {
@@ -49,6 +201,14 @@ pub(crate) fn plain_to_markdown(text: &str) -> String {
             continue;
         }
 
+        // Quoted reply block: "> text", possibly nested (">> text")
+        if line.starts_with('>') {
+            let (run, next) = collect_run(&lines, i, |l| l.starts_with('>'));
+            render_blockquote(&run, &mut out);
+            i = next;
+            continue;
+        }
+
         // Detect numbered headings: "1. Foo", "2.3 Bar", "Appendix 1: Foo"
         if let Some(heading) = detect_numbered_heading(line) {
             out.push_str(&heading);
@@ -57,6 +217,13 @@ pub(crate) fn plain_to_markdown(text: &str) -> String {
             continue;
         }
 
+        // Signature / address block: keep the lines together
+        if let Some((run, next)) = detect_signature_block(&lines, i) {
+            render_line_group(&run, &mut out);
+            i = next;
+            continue;
+        }
+
         // Detect short standalone lines as subheadings (bold)
         // Must be: short, not ending in sentence punctuation, not a
         // single word, and surrounded by blank/different content
@@ -77,6 +244,184 @@ pub(crate) fn plain_to_markdown(text: &str) -> String {
     out
 }
 
+/// Collect a run of lines matching `pred` starting at `start`, allowing a
+/// single blank line between matches (`.doc` paragraphs are blank-separated).
+///
+/// Returns the trimmed matching lines and the index just past the last one.
+fn collect_run<'a>(
+    lines: &[&'a str],
+    start: usize,
+    pred: impl Fn(&str) -> bool,
+) -> (Vec<&'a str>, usize) {
+    let mut run = Vec::new();
+    let mut i = start;
+    while i < lines.len() && pred(lines[i].trim()) {
+        run.push(lines[i].trim());
+        i += 1;
+        let after_blank = i + 1;
+        if i < lines.len()
+            && lines[i].trim().is_empty()
+            && after_blank < lines.len()
+            && pred(lines[after_blank].trim())
+        {
+            i = after_blank;
+        }
+    }
+    (run, i)
+}
+
+/// Render quoted reply lines as a markdown blockquote, keeping the nesting
+/// depth (`>>` or `> >`) of each line.
+fn render_blockquote(lines: &[&str], out: &mut String) {
+    for line in lines {
+        let mut rest = *line;
+        let mut depth = 0;
+        while let Some(r) = rest.strip_prefix('>') {
+            depth += 1;
+            rest = r.trim_start();
+        }
+        out.push_str(&">".repeat(depth));
+        if !rest.is_empty() {
+            out.push(' ');
+            out.push_str(rest);
+        }
+        out.push('\n');
+    }
+    out.push('\n');
+}
+
+/// Maximum length of a line in a signature or address block.
+const SIGNATURE_LINE_MAX: usize = 60;
+
+/// Detect a signature or address block starting at `idx`: two or more
+/// short lines that start with a capital letter or digit and don't read as
+/// sentences after a closing salutation like `"Best regards,"`, or three or
+/// more such lines one of which gives an address or contact details. A run
+/// of short headings or contents entries has neither, and is left alone.
+fn detect_signature_block<'a>(lines: &[&'a str], idx: usize) -> Option<(Vec<&'a str>, usize)> {
+    let first = lines[idx].trim();
+    let (run, next) = if is_salutation(first) {
+        let (rest, next) = collect_run(lines, skip_blank(lines, idx + 1), is_signature_line);
+        if rest.len() < 2 {
+            return None;
+        }
+        let mut run = vec![first];
+        run.extend(rest);
+        (run, next)
+    } else {
+        let (run, next) = collect_run(lines, idx, is_signature_line);
+        if run.len() < 3 || !run.iter().any(|line| is_contact_line(line)) {
+            return None;
+        }
+        (run, next)
+    };
+    Some((run, next))
+}
+
+/// Index of the next line at or after `idx`, skipping one blank line.
+fn skip_blank(lines: &[&str], idx: usize) -> usize {
+    if lines.get(idx).is_some_and(|l| l.trim().is_empty()) {
+        idx + 1
+    } else {
+        idx
+    }
+}
+
+/// A closing salutation: `"Regards,"`, `"Best wishes,"`, `"Sincerely,"`.
+fn is_salutation(line: &str) -> bool {
+    line.len() <= 30
+        && line.ends_with(',')
+        && line.chars().next().is_some_and(char::is_uppercase)
+        && line.split_whitespace().count() <= 3
+}
+
+/// A line that could belong to a signature or address block.
+fn is_signature_line(line: &str) -> bool {
+    !line.is_empty()
+        && line.len() <= SIGNATURE_LINE_MAX
+        && !line.contains('\t')
+        && !line.ends_with(['.', '!', '?', ':', ';'])
+        && line
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_uppercase() || c.is_ascii_digit())
+        && detect_numbered_heading(line).is_none()
+}
+
+/// Words that name a street in an address line.
+const STREET_WORDS: [&str; 14] = [
+    "street",
+    "st",
+    "avenue",
+    "ave",
+    "road",
+    "rd",
+    "lane",
+    "ln",
+    "drive",
+    "dr",
+    "boulevard",
+    "blvd",
+    "way",
+    "suite",
+];
+
+/// A line of an address or contact details: an email address, web
+/// address, or phone number, a labelled contact line (`"Tel 555 0100"`),
+/// a PO box, a numbered street (`"123 Main Street"`), or a town with its
+/// postal code (`"Springfield, IL 62704"`).
+fn is_contact_line(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|w| !w.is_empty())
+        .collect();
+    let digits = line.chars().filter(char::is_ascii_digit).count();
+    let is_phone = digits >= 7
+        && line
+            .chars()
+            .all(|c| c.is_ascii_digit() || " +-().".contains(c));
+    let labelled = ["tel", "phone", "fax", "mobile", "email", "e-mail", "web"]
+        .iter()
+        .any(|label| {
+            words
+                .first()
+                .is_some_and(|w| w.trim_end_matches([':', '.']) == *label)
+        });
+    let street = words
+        .first()
+        .is_some_and(|w| w.starts_with(|c: char| c.is_ascii_digit()))
+        && words
+            .iter()
+            .skip(1)
+            .any(|w| STREET_WORDS.contains(&w.trim_end_matches('.')));
+    let postal = line.contains(',')
+        && words
+            .last()
+            .is_some_and(|w| w.len() >= 4 && w.chars().all(|c| c.is_ascii_digit() || c == '-'));
+    line.contains('@')
+        || lower.contains("www.")
+        || lower.contains("://")
+        || lower.starts_with("po box")
+        || lower.starts_with("p.o. box")
+        || is_phone
+        || labelled
+        || street
+        || postal
+}
+
+/// Render lines as a single block with markdown hard line breaks.
+fn render_line_group(lines: &[&str], out: &mut String) {
+    for (i, line) in lines.iter().enumerate() {
+        out.push_str(line);
+        if i + 1 < lines.len() {
+            out.push_str("  ");
+        }
+        out.push('\n');
+    }
+    out.push('\n');
+}
+
 /// Try to detect a numbered heading like "1. Introduction" or "Appendix 1: Server Analysis".
 /// Returns the markdown heading string if detected.
 pub(crate) fn detect_numbered_heading(line: &str) -> Option<String> {
@@ -225,27 +570,146 @@ fn render_table(lines: &[&str], out: &mut String) {
             continue;
         }
 
-        // Emit markdown table
-        for (ri, row) in rows.iter().enumerate() {
+        push_markdown_table(&rows, ncols, out);
+        out.push('\n');
+    }
+}
+
+/// Emit rows as a markdown table with the first row as the header. Rows
+/// shorter than `ncols` are padded with empty cells.
+pub(crate) fn push_markdown_table<R: AsRef<[C]>, C: AsRef<str>>(
+    rows: &[R],
+    ncols: usize,
+    out: &mut String,
+) {
+    for (ri, row) in rows.iter().enumerate() {
+        let row = row.as_ref();
+        out.push('|');
+        for ci in 0..ncols {
+            let cell = row.get(ci).map_or("", AsRef::as_ref);
+            out.push(' ');
+            out.push_str(&cell.replace('|', "\\|"));
+            out.push_str(" |");
+        }
+        out.push('\n');
+
+        if ri == 0 {
             out.push('|');
-            for ci in 0..ncols {
-                let cell = row.get(ci).copied().unwrap_or("");
-                out.push(' ');
-                out.push_str(&cell.replace('|', "\\|"));
-                out.push_str(" |");
+            for _ in 0..ncols {
+                out.push_str(" --- |");
             }
             out.push('\n');
+        }
+    }
+}
+
+// ── Space-aligned tables ──────────────────────────────────────────
+
+/// Minimum run of spaces that separates two columns in space-aligned text.
+const COLUMN_GAP: usize = 2;
+
+/// Render blocks of space-aligned columns as markdown tables, leaving all
+/// other lines untouched.
+///
+/// PDF text extraction lays tables out with runs of spaces rather than
+/// tabs. A block of two or more consecutive lines that each split into
+/// several cells on [`COLUMN_GAP`]+ spaces is a candidate; it becomes a
+/// table only if the gaps line up, i.e. some character columns are blank
+/// in every line of the block. The first row is taken as the header.
+pub(crate) fn render_aligned_tables(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < lines.len() {
+        let start = i;
+        while i < lines.len() && count_spaced_cells(lines[i]) >= 2 {
+            i += 1;
+        }
 
-            if ri == 0 {
-                out.push('|');
-                for _ in 0..ncols {
-                    out.push_str(" --- |");
+        if i - start >= 2 {
+            if let Some(rows) = split_aligned_columns(&lines[start..i]) {
+                if !out.is_empty() && !out.ends_with("\n\n") {
+                    out.push('\n');
                 }
-                out.push('\n');
+                let ncols = rows[0].len();
+                push_markdown_table(&rows, ncols, &mut out);
+                if lines.get(i).is_some_and(|l| !l.trim().is_empty()) {
+                    out.push('\n');
+                }
+                continue;
             }
         }
-        out.push('\n');
+
+        // Not a table: copy the lines through (at least one, to progress)
+        i = i.max(start + 1);
+        for line in &lines[start..i] {
+            out.push_str(line);
+            out.push('\n');
+        }
     }
+
+    if !text.ends_with('\n') && out.ends_with('\n') {
+        out.pop();
+    }
+    out
+}
+
+/// Number of cells in `line` when split on runs of [`COLUMN_GAP`]+ spaces.
+fn count_spaced_cells(line: &str) -> usize {
+    line.trim()
+        .split(&" ".repeat(COLUMN_GAP))
+        .filter(|c| !c.trim().is_empty())
+        .count()
+}
+
+/// Split a block of lines into columns at character positions that are
+/// blank in every line. Returns `None` if fewer than two columns line up.
+fn split_aligned_columns(lines: &[&str]) -> Option<Vec<Vec<String>>> {
+    let grid: Vec<Vec<char>> = lines.iter().map(|l| l.chars().collect()).collect();
+    let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+    let is_blank = |pos: usize| {
+        grid.iter()
+            .all(|row| row.get(pos).is_none_or(|c| *c == ' '))
+    };
+
+    // Column spans [start, end) between blank runs of at least COLUMN_GAP
+    let mut columns: Vec<(usize, usize)> = Vec::new();
+    let mut pos = (0..width).find(|&p| !is_blank(p))?;
+    while pos < width {
+        let start = pos;
+        loop {
+            while pos < width && !is_blank(pos) {
+                pos += 1;
+            }
+            let gap_start = pos;
+            while pos < width && is_blank(pos) {
+                pos += 1;
+            }
+            if pos >= width || pos - gap_start >= COLUMN_GAP {
+                columns.push((start, gap_start));
+                break;
+            }
+        }
+    }
+
+    if columns.len() < 2 {
+        return None;
+    }
+
+    let rows = grid
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|&(s, e)| {
+                    let cell: String = row.get(s..e.min(row.len())).unwrap_or(&[]).iter().collect();
+                    cell.trim().to_string()
+                })
+                .collect()
+        })
+        .collect();
+    Some(rows)
 }
 
 /// Detect the column count for a flat array of cells from a .doc table.
@@ -536,6 +1000,148 @@ mod tests {
         assert_eq!(detect_column_count(&cells), 1);
     }
 
+    // ── render_aligned_tables ────────────────────────────────────
+
+    #[test]
+    fn aligned_table_rendered() {
+        let text = "Quarterly results\n\nRegion    Sales   Growth\nNorth     1,200   4%\nSouth       950   -2%\n\nTotals are unaudited.\n";
+        assert_eq!(
+            render_aligned_tables(text),
+            "Quarterly results\n\n| Region | Sales | Growth |\n| --- | --- | --- |\n| North | 1,200 | 4% |\n| South | 950 | -2% |\n\nTotals are unaudited.\n"
+        );
+    }
+
+    #[test]
+    fn aligned_table_gets_blank_line_around() {
+        let text = "Intro line\nName  Qty\nPen   3\nAfter\n";
+        assert_eq!(
+            render_aligned_tables(text),
+            "Intro line\n\n| Name | Qty |\n| --- | --- |\n| Pen | 3 |\n\nAfter\n"
+        );
+    }
+
+    #[test]
+    fn misaligned_columns_left_alone() {
+        let text = "Alpha  beta gamma\ndelta epsilon  zeta\n";
+        assert_eq!(render_aligned_tables(text), text);
+    }
+
+    #[test]
+    fn single_spaced_prose_left_alone() {
+        let text = "One ordinary line.\nAnother ordinary line.\n";
+        assert_eq!(render_aligned_tables(text), text);
+    }
+
+    #[test]
+    fn single_aligned_line_not_table() {
+        let text = "Name   Value\nplain text\n";
+        assert_eq!(render_aligned_tables(text), text);
+    }
+
+    #[test]
+    fn aligned_table_empty_cell() {
+        let text = "Item   Note\nA      first\nB\n";
+        // "B" has one cell, so the block stops before it
+        assert_eq!(
+            render_aligned_tables(text),
+            "| Item | Note |\n| --- | --- |\n| A | first |\n\nB\n"
+        );
+    }
+
+    #[test]
+    fn reflow_keeps_markdown_table_rows() {
+        let text = "| A | B |\n| --- | --- |\n| x | y |\n";
+        assert_eq!(reflow(text), text);
+    }
+
+    // ── reflow ───────────────────────────────────────────────────
+
+    #[test]
+    fn reflow_joins_wrapped_lines() {
+        let input = "The quick brown fox jumps over the\nlazy dog and keeps running.\n";
+        assert_eq!(
+            reflow(input),
+            "The quick brown fox jumps over the lazy dog and keeps running.\n"
+        );
+    }
+
+    #[test]
+    fn reflow_keeps_sentence_boundaries() {
+        let input = "First sentence ends here.\nsecond line stays apart\n";
+        assert_eq!(reflow(input), input);
+    }
+
+    #[test]
+    fn reflow_long_line_before_capital() {
+        let input = "This line is long enough to have hit the wrap margin of\nParis and more text follows here.\n";
+        assert_eq!(
+            reflow(input),
+            "This line is long enough to have hit the wrap margin of Paris and more text follows here.\n"
+        );
+    }
+
+    #[test]
+    fn reflow_width_ignores_long_lines() {
+        let wrapped = "This line is long enough to have hit the wrap margin of\nParis and more text follows here.\n";
+        let joined = "This line is long enough to have hit the wrap margin of Paris and more text follows here.\n";
+        let url = format!("\nhttps://example.com/{}\n", "a".repeat(200));
+        assert_eq!(reflow(&format!("{wrapped}{url}")), format!("{joined}{url}"));
+
+        // One long sentence among many wrapped lines
+        let long = format!("{}.\n\n", "word ".repeat(40).trim_end());
+        let filler = "Lines of a paragraph wrapped at about this width.\n".repeat(10);
+        assert_eq!(
+            reflow(&format!("{long}{filler}\n{wrapped}")),
+            format!("{long}{filler}\n{joined}")
+        );
+    }
+
+    #[test]
+    fn reflow_short_title_not_joined() {
+        let input = "Executive Summary\nThis report covers the quarterly results in detail.\n";
+        assert_eq!(reflow(input), input);
+    }
+
+    #[test]
+    fn reflow_across_single_blank_line() {
+        let input = "The meeting was moved to\n\nthursday afternoon.\n";
+        assert_eq!(
+            reflow(input),
+            "The meeting was moved to thursday afternoon.\n"
+        );
+    }
+
+    #[test]
+    fn reflow_blank_line_needs_lowercase() {
+        let input = "Introduction\n\nThe project started in spring.\n";
+        assert_eq!(reflow(input), input);
+    }
+
+    #[test]
+    fn reflow_skips_tables_and_lists() {
+        let input = "Name\tAge\nAlice\t30\n- first item\n- second item\n";
+        assert_eq!(reflow(input), input);
+    }
+
+    #[test]
+    fn reflow_keeps_headings() {
+        let input = "1. Introduction\nthis paragraph starts lowercase\n";
+        assert_eq!(reflow(input), input);
+    }
+
+    #[test]
+    fn reflow_keeps_quoted_lines() {
+        let input =
+            "> the servers went down during the night and nobody\n> noticed until morning\n";
+        assert_eq!(reflow(input), input);
+    }
+
+    #[test]
+    fn reflow_hyphenated_break() {
+        let input = "a fully self-\ncontained unit\n";
+        assert_eq!(reflow(input), "a fully self-contained unit\n");
+    }
+
     // ── plain_to_markdown (integration) ──────────────────────────
 
     #[test]
@@ -561,6 +1167,83 @@ mod tests {
         assert!(result.contains("---"));
     }
 
+    #[test]
+    fn markdown_blockquote_grouped() {
+        let input = "Thanks for the update.\n\n> On Monday Bob wrote:\n\n> The servers are down.\n\n>> Are they?\n";
+        let result = plain_to_markdown(input);
+        assert!(
+            result.contains("> On Monday Bob wrote:\n> The servers are down.\n>> Are they?\n\n")
+        );
+    }
+
+    #[test]
+    fn markdown_signature_block() {
+        let input = "Please review.\n\nJohn Smith\n\nDirector of Operations\n\nAcme Corp\n\n123 Main Street\n";
+        let result = plain_to_markdown(input);
+        assert!(result
+            .contains("John Smith  \nDirector of Operations  \nAcme Corp  \n123 Main Street\n"));
+        assert!(!result.contains("**John Smith**"));
+    }
+
+    #[test]
+    fn markdown_salutation_block() {
+        let input = "See you soon.\n\nBest regards,\nJane Doe\nAcme Corp\n";
+        let result = plain_to_markdown(input);
+        assert!(result.contains("Best regards,  \nJane Doe  \nAcme Corp\n"));
+    }
+
+    #[test]
+    fn markdown_short_headings_not_signature() {
+        let input = "\nExecutive Summary\n\nBudget Overview\n\nRisk Register\n\nThe budget grew.\n";
+        let result = plain_to_markdown(input);
+        assert!(result.contains("**Executive Summary**"));
+        assert!(result.contains("**Budget Overview**"));
+        assert!(result.contains("**Risk Register**"));
+    }
+
+    #[test]
+    fn markdown_two_short_lines_not_signature() {
+        let input = "\nExecutive Summary\n\nBudget Overview\n\nThe budget grew.\n";
+        let result = plain_to_markdown(input);
+        assert!(result.contains("**Executive Summary**"));
+        assert!(result.contains("**Budget Overview**"));
+    }
+
+    // ── collect_run ──────────────────────────────────────────────
+
+    #[test]
+    fn collect_run_bridges_single_blank() {
+        let lines = vec!["> a", "", "> b", "", "", "> c"];
+        let (run, next) = collect_run(&lines, 0, |l| l.starts_with('>'));
+        assert_eq!(run, vec!["> a", "> b"]);
+        assert_eq!(next, 3);
+    }
+
+    // ── is_signature_line ────────────────────────────────────────
+
+    #[test]
+    fn signature_line_rules() {
+        assert!(is_signature_line("Acme Corp"));
+        assert!(is_signature_line("123 Main Street"));
+        assert!(is_signature_line("Springfield, IL 62704"));
+        assert!(!is_signature_line("This ends with a period."));
+        assert!(!is_signature_line("lowercase start"));
+        assert!(!is_signature_line("1. Introduction"));
+    }
+
+    #[test]
+    fn contact_line_rules() {
+        assert!(is_contact_line("123 Main Street"));
+        assert!(is_contact_line("Springfield, IL 62704"));
+        assert!(is_contact_line("jane@acme.com"));
+        assert!(is_contact_line("+1 (555) 010-0199"));
+        assert!(is_contact_line("Tel: 555 0100"));
+        assert!(is_contact_line("www.acme.com"));
+        assert!(!is_contact_line("2024 Budget Review"));
+        assert!(!is_contact_line("Executive Summary"));
+        assert!(!is_contact_line("Acme Corp"));
+    }
+
     #[test]
     fn markdown_toc_not_table() {
         let input = "Introduction\t3\n";
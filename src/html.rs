@@ -0,0 +1,733 @@
+//! Text of HTML and MHT (MIME HTML) documents, as embedded in a `.docx`
+//! by `<w:altChunk>`.
+//!
+//! This is not a browser: tags are scanned in order without building a
+//! tree, which is enough for the headings, paragraphs, lists, tables,
+//! emphasis, and links that mail-merge and report tools emit. Whatever
+//! isn't understood is read as running text; `<head>`, `<script>`, and
+//! `<style>` are skipped.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+
+/// A run of text with the same formatting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Span {
+    pub(crate) text: String,
+    pub(crate) bold: bool,
+    pub(crate) italic: bool,
+    /// `href` of the enclosing `<a>`.
+    pub(crate) link: Option<String>,
+}
+
+/// What a paragraph is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Kind {
+    #[default]
+    Normal,
+    /// `<h1>` to `<h6>`.
+    Heading(u8),
+    /// `<li>`, 0 for the outermost list, with its number in an `<ol>`.
+    Item { level: u8, number: Option<u32> },
+}
+
+/// A paragraph or a table of the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Block {
+    Paragraph {
+        kind: Kind,
+        spans: Vec<Span>,
+    },
+    /// Rows of cells, each cell holding its own blocks.
+    Table {
+        rows: Vec<Vec<Vec<Self>>>,
+    },
+}
+
+/// The HTML of an `.html` or `.mht` part's bytes: for MHT, the first
+/// `text/html` part of the message, decoded.
+pub(crate) fn decode(data: &[u8]) -> String {
+    if is_mime(data) {
+        let text = String::from_utf8_lossy(data);
+        if let Some(html) = mime_html(&text) {
+            return html;
+        }
+    }
+    decode_text(data, None)
+}
+
+/// Read HTML into blocks.
+pub(crate) fn parse(html: &str) -> Vec<Block> {
+    let mut builder = Builder::default();
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            builder.text(rest);
+            break;
+        };
+        builder.text(&rest[..lt]);
+        rest = &rest[lt..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some((tag, len)) = Tag::parse(rest) {
+            builder.tag(&tag);
+            rest = &rest[len..];
+        } else {
+            builder.text("<");
+            rest = &rest[1..];
+        }
+    }
+    builder.finish()
+}
+
+// ── Tags ──────────────────────────────────────────────────────────
+
+/// A start or end tag.
+#[derive(Debug, PartialEq, Eq)]
+struct Tag {
+    /// Lowercase.
+    name: String,
+    closing: bool,
+    /// `href`, the one attribute used.
+    href: Option<String>,
+}
+
+impl Tag {
+    /// The tag at the start of `s`, which starts with `<`, and its length
+    /// in bytes; `None` if the `<` doesn't start a tag.
+    fn parse(s: &str) -> Option<(Self, usize)> {
+        let body = &s[1..];
+        let closing = body.starts_with('/');
+        let body = if closing { &body[1..] } else { body };
+        if !body.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        let end = tag_end(body)?;
+        let inner = &body[..end];
+        let name_len = inner
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(inner.len());
+        let tag = Self {
+            name: inner[..name_len].to_ascii_lowercase(),
+            closing,
+            href: attr(&inner[name_len..], "href").map(|v| decode_entities(&v)),
+        };
+        Some((tag, s.len() - body.len() + end + 1))
+    }
+}
+
+/// Index of the `>` ending a tag, skipping quoted attribute values.
+fn tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The value of attribute `name` in the attributes of a tag.
+fn attr(attrs: &str, name: &str) -> Option<String> {
+    let mut rest = attrs;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            return None;
+        }
+        let key_len = rest
+            .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let key = &rest[..key_len];
+        rest = rest[key_len..].trim_start();
+        let (value, after) = rest
+            .strip_prefix('=')
+            .map_or(("", rest), |after| attr_value(after.trim_start()));
+        rest = after;
+        if key.eq_ignore_ascii_case(name) {
+            return Some(value.to_string());
+        }
+    }
+}
+
+/// An attribute value, quoted or not, at the start of `s`, and the text
+/// after it.
+fn attr_value(s: &str) -> (&str, &str) {
+    if let Some(q) = s.chars().next().filter(|c| matches!(c, '"' | '\'')) {
+        let quoted = &s[1..];
+        return quoted
+            .find(q)
+            .map_or((quoted, ""), |end| (&quoted[..end], &quoted[end + 1..]));
+    }
+    let end = s.find(char::is_whitespace).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+// ── Building blocks ───────────────────────────────────────────────
+
+/// A table being read, with the cell currently open.
+#[derive(Debug, Default)]
+struct OpenTable {
+    rows: Vec<Vec<Vec<Block>>>,
+    in_cell: bool,
+}
+
+/// Blocks read so far, and the state of the tags open at this point.
+#[derive(Debug)]
+struct Builder {
+    /// Where paragraphs go: the document, then each open table cell.
+    sinks: Vec<Vec<Block>>,
+    tables: Vec<OpenTable>,
+    kind: Kind,
+    spans: Vec<Span>,
+    bold: u32,
+    italic: u32,
+    link: Option<String>,
+    /// Open lists, innermost last: the last number used in an `<ol>`,
+    /// `None` for a `<ul>`.
+    lists: Vec<Option<u32>>,
+    /// Depth of the skipped elements open.
+    skip: u32,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            sinks: vec![Vec::new()],
+            tables: Vec::new(),
+            kind: Kind::Normal,
+            spans: Vec::new(),
+            bold: 0,
+            italic: 0,
+            link: None,
+            lists: Vec::new(),
+            skip: 0,
+        }
+    }
+}
+
+impl Builder {
+    /// Add text, with whitespace collapsed as a browser would.
+    fn text(&mut self, raw: &str) {
+        if self.skip > 0 || raw.is_empty() {
+            return;
+        }
+        let mut collapsed = String::with_capacity(raw.len());
+        let mut space = self
+            .spans
+            .last()
+            .is_none_or(|s| s.text.ends_with([' ', '\n']));
+        for c in raw.chars() {
+            if c.is_whitespace() {
+                if !space {
+                    collapsed.push(' ');
+                }
+                space = true;
+            } else {
+                collapsed.push(c);
+                space = false;
+            }
+        }
+        if !collapsed.is_empty() {
+            self.push(&decode_entities(&collapsed));
+        }
+    }
+
+    /// Add text as is, in the current formatting.
+    fn push(&mut self, text: &str) {
+        let bold = self.bold > 0;
+        let italic = self.italic > 0;
+        if let Some(last) = self.spans.last_mut() {
+            if last.bold == bold && last.italic == italic && last.link == self.link {
+                last.text.push_str(text);
+                return;
+            }
+        }
+        self.spans.push(Span {
+            text: text.to_string(),
+            bold,
+            italic,
+            link: self.link.clone(),
+        });
+    }
+
+    fn tag(&mut self, tag: &Tag) {
+        let name = tag.name.as_str();
+        if matches!(name, "head" | "script" | "style" | "title" | "template") {
+            if tag.closing {
+                self.skip = self.skip.saturating_sub(1);
+            } else {
+                self.skip += 1;
+            }
+            return;
+        }
+        if self.skip > 0 {
+            return;
+        }
+        match (name, tag.closing) {
+            ("b" | "strong", false) => self.bold += 1,
+            ("b" | "strong", true) => self.bold = self.bold.saturating_sub(1),
+            ("i" | "em", false) => self.italic += 1,
+            ("i" | "em", true) => self.italic = self.italic.saturating_sub(1),
+            ("a", false) => self.link = tag.href.clone().filter(|h| !h.is_empty()),
+            ("a", true) => self.link = None,
+            ("br", _) => self.push("\n"),
+            ("h1" | "h2" | "h3" | "h4" | "h5" | "h6", false) => {
+                self.flush();
+                self.kind = Kind::Heading(name.as_bytes()[1] - b'0');
+            }
+            ("ul" | "ol", false) => {
+                self.flush();
+                self.lists.push((name == "ol").then_some(0));
+            }
+            ("ul" | "ol", true) => {
+                self.flush();
+                self.lists.pop();
+            }
+            ("li", false) => {
+                self.flush();
+                let level = u8::try_from(self.lists.len().saturating_sub(1)).unwrap_or(u8::MAX);
+                let number = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        Some(*n)
+                    }
+                    _ => None,
+                };
+                self.kind = Kind::Item { level, number };
+            }
+            ("table", false) => {
+                self.flush();
+                self.tables.push(OpenTable::default());
+            }
+            ("table", true) => self.close_table(),
+            ("tr", _) => {
+                self.close_cell();
+                if !tag.closing {
+                    if let Some(table) = self.tables.last_mut() {
+                        table.rows.push(Vec::new());
+                    }
+                }
+            }
+            ("td" | "th", false) => {
+                self.close_cell();
+                if let Some(table) = self.tables.last_mut() {
+                    table.in_cell = true;
+                    self.sinks.push(Vec::new());
+                }
+            }
+            ("td" | "th", true) => self.close_cell(),
+            ("p" | "div" | "blockquote" | "pre" | "center" | "address" | "dt" | "dd", _)
+            | ("h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "li" | "hr", true)
+            | ("hr", false) => self.flush(),
+            _ => {}
+        }
+    }
+
+    /// End the paragraph being read, if it has any text.
+    fn flush(&mut self) {
+        if let Some(last) = self.spans.last_mut() {
+            let trimmed = last.text.trim_end().len();
+            last.text.truncate(trimmed);
+        }
+        if self.spans.iter().all(|s| s.text.trim().is_empty()) {
+            self.spans.clear();
+            return;
+        }
+        let block = Block::Paragraph {
+            kind: std::mem::take(&mut self.kind),
+            spans: std::mem::take(&mut self.spans),
+        };
+        if let Some(sink) = self.sinks.last_mut() {
+            sink.push(block);
+        }
+    }
+
+    /// End the open cell of the innermost table, if any.
+    fn close_cell(&mut self) {
+        self.flush();
+        let Some(table) = self.tables.last_mut() else {
+            return;
+        };
+        if !table.in_cell {
+            return;
+        }
+        table.in_cell = false;
+        let cell = self.sinks.pop().unwrap_or_default();
+        if table.rows.is_empty() {
+            table.rows.push(Vec::new());
+        }
+        if let Some(row) = table.rows.last_mut() {
+            row.push(cell);
+        }
+    }
+
+    fn close_table(&mut self) {
+        self.close_cell();
+        let Some(table) = self.tables.pop() else {
+            return;
+        };
+        let rows: Vec<_> = table.rows.into_iter().filter(|r| !r.is_empty()).collect();
+        if let (false, Some(sink)) = (rows.is_empty(), self.sinks.last_mut()) {
+            sink.push(Block::Table { rows });
+        }
+    }
+
+    fn finish(mut self) -> Vec<Block> {
+        while !self.tables.is_empty() {
+            self.close_table();
+        }
+        self.flush();
+        self.sinks.swap_remove(0)
+    }
+}
+
+// ── Entities ──────────────────────────────────────────────────────
+
+/// Replace character references (`&amp;`, `&#233;`, `&#xE9;`) with the
+/// characters they stand for. Unknown ones are kept as written.
+fn decode_entities(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| entity(&rest[1..end]).map(|c| (c, end + 1)));
+        if let Some((c, len)) = decoded {
+            out.push(c);
+            rest = &rest[len..];
+        } else {
+            out.push('&');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The character a reference's name (between `&` and `;`) stands for.
+fn entity(name: &str) -> Option<char> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "euro" => '€',
+        "pound" => '£',
+        "deg" => '°',
+        "middot" => '·',
+        "bull" => '•',
+        "hellip" => '…',
+        "ndash" => '–',
+        "mdash" => '—',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        _ => return None,
+    })
+}
+
+// ── MHT ───────────────────────────────────────────────────────────
+
+/// Whether `data` starts with MIME headers.
+fn is_mime(data: &[u8]) -> bool {
+    let start = data
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(0);
+    let head = &data[start..data.len().min(start + 13)];
+    head.eq_ignore_ascii_case(b"MIME-Version:") || head.eq_ignore_ascii_case(b"Content-Type:")
+}
+
+/// The decoded `text/html` body of a MIME message, looking into
+/// multipart bodies.
+fn mime_html(message: &str) -> Option<String> {
+    let (headers, body) = split_headers(message);
+    let content_type = header(&headers, "content-type").unwrap_or_default();
+    let media_type = content_type.split(';').next().unwrap_or("").trim();
+    if media_type.to_ascii_lowercase().starts_with("multipart/") {
+        let boundary = format!("--{}", param(&content_type, "boundary")?);
+        return body
+            .split(boundary.as_str())
+            .skip(1)
+            .find_map(|part| mime_html(part.trim_start_matches(['\r', '\n'])));
+    }
+    if !media_type.eq_ignore_ascii_case("text/html") {
+        return None;
+    }
+    let encoding = header(&headers, "content-transfer-encoding").unwrap_or_default();
+    let bytes = match encoding.trim().to_ascii_lowercase().as_str() {
+        "base64" => {
+            let compact: String = body.split_whitespace().collect();
+            BASE64.decode(compact).ok()?
+        }
+        "quoted-printable" => decode_quoted_printable(body),
+        _ => body.as_bytes().to_vec(),
+    };
+    Some(decode_text(
+        &bytes,
+        param(&content_type, "charset").as_deref(),
+    ))
+}
+
+/// A MIME entity's unfolded header lines and its body.
+fn split_headers(entity: &str) -> (Vec<String>, &str) {
+    let (head, body) = entity
+        .split_once("\r\n\r\n")
+        .or_else(|| entity.split_once("\n\n"))
+        .unwrap_or((entity, ""));
+    let mut headers: Vec<String> = Vec::new();
+    for line in head.lines() {
+        match headers.last_mut() {
+            Some(last) if line.starts_with([' ', '\t']) => {
+                last.push(' ');
+                last.push_str(line.trim());
+            }
+            _ => headers.push(line.to_string()),
+        }
+    }
+    (headers, body)
+}
+
+/// The value of header `name`, matched case-insensitively.
+fn header(headers: &[String], name: &str) -> Option<String> {
+    headers.iter().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().to_string())
+    })
+}
+
+/// A parameter of a header value (`boundary`, `charset`), unquoted.
+fn param(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|p| {
+        let (key, val) = p.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| val.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Decode a quoted-printable body: `=XX` escapes and `=` soft line breaks.
+fn decode_quoted_printable(body: &str) -> Vec<u8> {
+    let bytes = body.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'=' {
+            out.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+        let rest = &bytes[i + 1..];
+        if rest.starts_with(b"\r\n") {
+            i += 3;
+        } else if rest.starts_with(b"\n") {
+            i += 2;
+        } else if let Some(byte) = rest
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(b'=');
+            i += 1;
+        }
+    }
+    out
+}
+
+/// Text of an HTML document's bytes: in the encoding its byte order mark,
+/// `charset` (from MIME headers, or else a `<meta>` tag) names, else UTF-8
+/// if valid, else Windows-1252.
+fn decode_text(bytes: &[u8], charset: Option<&str>) -> String {
+    if let Some((encoding, bom)) = Encoding::for_bom(bytes) {
+        return encoding
+            .decode_without_bom_handling(&bytes[bom..])
+            .0
+            .into_owned();
+    }
+    let declared = charset
+        .map(str::to_string)
+        .or_else(|| meta_charset(bytes))
+        .and_then(|label| Encoding::for_label(label.trim().as_bytes()));
+    let encoding = match declared {
+        Some(encoding) => encoding,
+        None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+        None => WINDOWS_1252,
+    };
+    encoding.decode_without_bom_handling(bytes).0.into_owned()
+}
+
+/// The `charset=` in the first kilobyte of an HTML document, as in
+/// `<meta charset="..">` or a `Content-Type` `<meta>`.
+fn meta_charset(bytes: &[u8]) -> Option<String> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).to_ascii_lowercase();
+    let start = head.find("charset=")? + "charset=".len();
+    let value = head[start..].trim_start_matches(['"', '\'']);
+    let end = value
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+        .unwrap_or(value.len());
+    Some(value[..end].to_string()).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn para(kind: Kind, text: &str) -> Block {
+        Block::Paragraph {
+            kind,
+            spans: vec![Span {
+                text: text.into(),
+                ..Span::default()
+            }],
+        }
+    }
+
+    // ── parse ──
+
+    #[test]
+    fn headings_and_paragraphs() {
+        let html = "<html><head><title>T</title><style>p{}</style></head>\
+                    <body><h2>Title</h2>\n<p>One\n  two</p><p>&amp; three&nbsp;</p></body></html>";
+        assert_eq!(
+            parse(html),
+            vec![
+                para(Kind::Heading(2), "Title"),
+                para(Kind::Normal, "One two"),
+                para(Kind::Normal, "& three"),
+            ]
+        );
+    }
+
+    #[test]
+    fn emphasis_and_links() {
+        let blocks = parse("<p>A <b>bold</b> <a href=\"https://x.org/?a=1&amp;b=2\">link</a></p>");
+        let Block::Paragraph { spans, .. } = &blocks[0] else {
+            panic!("expected a paragraph");
+        };
+        assert_eq!(spans[1].text, "bold");
+        assert!(spans[1].bold);
+        assert_eq!(spans[3].text, "link");
+        assert_eq!(spans[3].link.as_deref(), Some("https://x.org/?a=1&b=2"));
+    }
+
+    #[test]
+    fn nested_lists() {
+        let html = "<ol><li>One<ul><li>Sub</li></ul></li><li>Two</ol>";
+        assert_eq!(
+            parse(html),
+            vec![
+                para(
+                    Kind::Item {
+                        level: 0,
+                        number: Some(1)
+                    },
+                    "One"
+                ),
+                para(
+                    Kind::Item {
+                        level: 1,
+                        number: None
+                    },
+                    "Sub"
+                ),
+                para(
+                    Kind::Item {
+                        level: 0,
+                        number: Some(2)
+                    },
+                    "Two"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn tables_with_omitted_end_tags() {
+        let html = "<table><tr><th>Name<th>Qty<tr><td>Apple<td>3</table><p>After";
+        assert_eq!(
+            parse(html),
+            vec![
+                Block::Table {
+                    rows: vec![
+                        vec![
+                            vec![para(Kind::Normal, "Name")],
+                            vec![para(Kind::Normal, "Qty")],
+                        ],
+                        vec![
+                            vec![para(Kind::Normal, "Apple")],
+                            vec![para(Kind::Normal, "3")],
+                        ],
+                    ],
+                },
+                para(Kind::Normal, "After"),
+            ]
+        );
+    }
+
+    #[test]
+    fn stray_angle_brackets_are_text() {
+        assert_eq!(
+            parse("<p>1 < 2 <!-- note --></p>"),
+            vec![para(Kind::Normal, "1 < 2")]
+        );
+    }
+
+    // ── decode ──
+
+    #[test]
+    fn mht_quoted_printable() {
+        let mht = "MIME-Version: 1.0\r\n\
+                   Content-Type: multipart/related;\r\n\tboundary=\"b1\"\r\n\r\n\
+                   --b1\r\n\
+                   Content-Type: text/html; charset=\"windows-1252\"\r\n\
+                   Content-Transfer-Encoding: quoted-printable\r\n\r\n\
+                   <p class=3D\"x\">Caf=E9 soft=\r\nbreak</p>\r\n\
+                   --b1--\r\n";
+        assert_eq!(
+            decode(mht.as_bytes()).trim(),
+            "<p class=\"x\">Café softbreak</p>"
+        );
+    }
+
+    #[test]
+    fn mht_base64() {
+        let mht = "Content-Type: text/html\n\
+                   Content-Transfer-Encoding: base64\n\n\
+                   PHA+SGk8L3A+\n";
+        assert_eq!(decode(mht.as_bytes()), "<p>Hi</p>");
+    }
+
+    #[test]
+    fn html_charsets() {
+        assert_eq!(decode(b"<p>caf\xe9</p>"), "<p>café</p>");
+        assert_eq!(decode(b"\xef\xbb\xbf<p>x</p>"), "<p>x</p>");
+        assert_eq!(decode(b"\xff\xfe<\0p\0>\0"), "<p>");
+    }
+}
@@ -0,0 +1,72 @@
+//! Minimal JSON writing helpers.
+//!
+//! batdoc only ever writes small, flat JSON objects, so these helpers
+//! produce them directly instead of pulling in a serialization crate.
+
+/// Append `s` as a quoted JSON string, escaping as required by RFC 8259.
+pub(crate) fn push_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => {
+                use std::fmt::Write as _;
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Build a single-line JSON object from `(key, value)` pairs, where a
+/// `None` value is written as `null`.
+pub(crate) fn object(fields: &[(&str, Option<&str>)]) -> String {
+    let mut out = String::from("{");
+    for (i, (key, value)) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        push_str(&mut out, key);
+        out.push(':');
+        match value {
+            Some(v) => push_str(&mut out, v),
+            None => out.push_str("null"),
+        }
+    }
+    out.push('}');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── push_str ─────────────────────────────────────────────────
+
+    #[test]
+    fn escapes_specials() {
+        let mut out = String::new();
+        push_str(&mut out, "a\"b\\c\nd\u{1}é");
+        assert_eq!(out, r#""a\"b\\c\nd\u0001é""#);
+    }
+
+    // ── object ───────────────────────────────────────────────────
+
+    #[test]
+    fn object_with_null() {
+        assert_eq!(
+            object(&[("file", Some("a.doc")), ("format", None)]),
+            r#"{"file":"a.doc","format":null}"#
+        );
+    }
+
+    #[test]
+    fn empty_object() {
+        assert_eq!(object(&[]), "{}");
+    }
+}
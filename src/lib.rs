@@ -0,0 +1,73 @@
+//! Text extraction from `.doc`, `.docx`, `.xls`, `.xlsx`, `.pptx`, and
+//! `.pdf` files.
+//!
+//! This is the library behind the `batdoc` command. [`DocumentReader`]
+//! detects a document's format from its magic bytes and yields its content
+//! as a sequence of format-independent [`Block`]s:
+//!
+//! ```no_run
+//! for block in batdoc::DocumentReader::open("budget.xlsx")? {
+//!     if let batdoc::Block::Table { name, rows } = block {
+//!         println!("{}: {} rows", name.unwrap_or_default(), rows.len());
+//!     }
+//! }
+//! # Ok::<(), batdoc::BatdocError>(())
+//! ```
+
+#![allow(clippy::redundant_pub_crate)]
+
+#[doc(hidden)]
+pub mod app;
+mod archive;
+mod boxtable;
+mod cancel;
+mod chart;
+mod chunk;
+mod cli;
+mod clipboard;
+mod codepage;
+mod config;
+mod crypto;
+mod dateconv;
+mod diff;
+mod doc;
+mod document;
+mod docx;
+mod embedded;
+mod error;
+mod fields;
+mod format;
+mod grep;
+mod heuristic;
+mod html;
+mod json;
+mod listing;
+mod markup;
+mod metadata;
+mod numbering;
+mod numfmt;
+mod omml;
+mod options;
+mod org;
+mod pdf;
+mod picker;
+mod pivot;
+mod platform;
+mod pptx;
+mod progress;
+mod remote;
+mod serve;
+mod sheet;
+mod smartart;
+mod stats;
+mod summary;
+mod symbols;
+mod xls;
+mod xlsx;
+mod xml;
+mod xml_util;
+
+pub use cancel::CancelToken;
+pub use document::{Block, DocumentReader, Span};
+pub use error::{BatdocError, Result};
+pub use format::Format;
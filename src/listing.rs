@@ -0,0 +1,269 @@
+//! Structure listing of a document (`--list`).
+//!
+//! Shows what a document is made of without its content, to decide what
+//! to extract: the sheets of a workbook with their sizes and defined
+//! names, the slides of a presentation with their titles, the page count
+//! and bookmarks of a PDF, or the heading outline of a word-processing
+//! document.
+
+use std::fmt::Write as _;
+
+use crate::document::{self, Block};
+use crate::error::Result;
+use crate::format::Format;
+use crate::metadata;
+use crate::options::Options;
+use crate::pdf::{self, Bookmark};
+use crate::sheet::Sheet;
+use crate::{xls, xlsx};
+
+/// List the structure of a document, one item per line.
+pub(crate) fn render(data: &[u8], format: Format, opts: &Options) -> Result<String> {
+    Ok(match format {
+        Format::Xls => sheets(&xls::extract_sheets(data, opts)?, &[]),
+        Format::Xlsx => {
+            let mut out = sheets(
+                &xlsx::extract_sheets(data, opts)?,
+                &xlsx::chart_sheets(data, opts),
+            );
+            let names = xlsx::defined_names(data);
+            if !names.is_empty() {
+                out.push_str("Defined names:\n");
+                out.push_str(&metadata::names_text(&names, "  "));
+            }
+            let lists = xlsx::validation_lists(data);
+            if !lists.is_empty() {
+                out.push_str("Validation lists:\n");
+                out.push_str(&metadata::names_text(&lists, "  "));
+            }
+            out
+        }
+        Format::Pptx => slides(&slide_titles(&document::extract_blocks(
+            data, format, opts,
+        )?)),
+        Format::Pdf => {
+            let (pages, bookmarks) = pdf::outline(data)?;
+            pdf_outline(pages, &bookmarks)
+        }
+        Format::Doc | Format::Docx => headings(&document::extract_blocks(data, format, opts)?),
+    })
+}
+
+/// Each slide's number and title: the text of its first heading or
+/// paragraph.
+pub(crate) fn slide_titles(blocks: &[Block]) -> Vec<(usize, String)> {
+    let mut slides: Vec<(usize, String)> = Vec::new();
+    for block in blocks {
+        match (block, slides.last_mut()) {
+            (Block::Slide { number }, _) => slides.push((*number, String::new())),
+            (
+                Block::Heading { text, .. }
+                | Block::Paragraph { text, .. }
+                | Block::ListItem { text, .. },
+                Some((_, title)),
+            ) if title.is_empty() => {
+                title.push_str(text.lines().next().unwrap_or_default());
+            }
+            _ => {}
+        }
+    }
+    slides
+}
+
+/// `Sheet N: name (R rows × C columns)` for each sheet, or
+/// `Sheet N: name (chart sheet)` for those named in `chart_sheets`.
+fn sheets(sheets: &[Sheet], chart_sheets: &[String]) -> String {
+    let mut out = String::new();
+    for (i, sheet) in sheets.iter().enumerate() {
+        if chart_sheets.contains(&sheet.name) {
+            let _ = writeln!(out, "Sheet {}: {} (chart sheet)", i + 1, sheet.name);
+            continue;
+        }
+        let cols = sheet.rows.iter().map(Vec::len).max().unwrap_or(0);
+        let _ = writeln!(
+            out,
+            "Sheet {}: {} ({} × {})",
+            i + 1,
+            sheet.name,
+            plural(sheet.rows.len(), "row"),
+            plural(cols, "column")
+        );
+    }
+    out
+}
+
+/// `Slide N: title` for each slide.
+fn slides(slides: &[(usize, String)]) -> String {
+    let mut out = String::new();
+    for (number, title) in slides {
+        let _ = writeln!(out, "Slide {number}: {title}");
+    }
+    out
+}
+
+/// The page count, then the bookmarks indented by level with their page.
+fn pdf_outline(pages: usize, bookmarks: &[Bookmark]) -> String {
+    let mut out = format!("{}\n", plural(pages, "page"));
+    for bookmark in bookmarks {
+        let indent = "  ".repeat(bookmark.level.saturating_sub(1));
+        let _ = writeln!(out, "{indent}{} (page {})", bookmark.title, bookmark.page);
+    }
+    out
+}
+
+/// The headings, indented by level below the highest one present.
+fn headings(blocks: &[Block]) -> String {
+    let outline: Vec<(u8, &str)> = blocks
+        .iter()
+        .filter_map(|block| match block {
+            Block::Heading { level, text } => Some((*level, text.as_str())),
+            _ => None,
+        })
+        .collect();
+    if outline.is_empty() {
+        return "(no headings)\n".into();
+    }
+    let top = outline.iter().map(|&(level, _)| level).min().unwrap_or(1);
+    let mut out = String::new();
+    for (level, text) in outline {
+        let indent = "  ".repeat(usize::from(level - top));
+        let _ = writeln!(out, "{indent}{text}");
+    }
+    out
+}
+
+/// `n` and `noun`, pluralized with an `s`.
+fn plural(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{n} {noun}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sheet::Cut;
+
+    fn para(text: &str) -> Block {
+        Block::Paragraph {
+            text: text.into(),
+            spans: Vec::new(),
+        }
+    }
+
+    // ── slide_titles ─────────────────────────────────────────────
+
+    #[test]
+    fn slide_titles_from_first_text() {
+        let blocks = [
+            Block::Slide { number: 1 },
+            Block::Heading {
+                level: 1,
+                text: "Welcome".into(),
+            },
+            Block::Paragraph {
+                text: "Body".into(),
+                spans: Vec::new(),
+            },
+            Block::Slide { number: 3 },
+            Block::Slide { number: 4 },
+            Block::Paragraph {
+                text: "Two\nlines".into(),
+                spans: Vec::new(),
+            },
+        ];
+        assert_eq!(
+            slide_titles(&blocks),
+            [
+                (1, "Welcome".to_string()),
+                (3, String::new()),
+                (4, "Two".to_string())
+            ]
+        );
+    }
+
+    // ── sheets / pdf_outline / headings ─────────────────────────
+
+    #[test]
+    fn sheet_sizes() {
+        let list = sheets(
+            &[
+                Sheet {
+                    name: "Summary".into(),
+                    rows: vec![vec!["a".into(), "b".into()], vec!["c".into()]],
+                    comments: Vec::new(),
+                    charts: Vec::new(),
+                    tables: Vec::new(),
+                    cut: Cut::default(),
+                    images: Vec::new(),
+                    pivots: Vec::new(),
+                },
+                Sheet {
+                    name: "Notes".into(),
+                    rows: vec![vec!["x".into()]],
+                    comments: Vec::new(),
+                    charts: Vec::new(),
+                    tables: Vec::new(),
+                    cut: Cut::default(),
+                    images: Vec::new(),
+                    pivots: Vec::new(),
+                },
+                Sheet {
+                    name: "Trend".into(),
+                    rows: Vec::new(),
+                    comments: Vec::new(),
+                    charts: Vec::new(),
+                    tables: Vec::new(),
+                    cut: Cut::default(),
+                    images: Vec::new(),
+                    pivots: Vec::new(),
+                },
+            ],
+            &["Trend".to_string()],
+        );
+        assert_eq!(
+            list,
+            "Sheet 1: Summary (2 rows × 2 columns)\nSheet 2: Notes (1 row × 1 column)\n\
+             Sheet 3: Trend (chart sheet)\n"
+        );
+    }
+
+    #[test]
+    fn bookmarks_nest_by_level() {
+        let bookmark = |level, title: &str, page| Bookmark {
+            level,
+            title: title.into(),
+            page,
+        };
+        let list = pdf_outline(
+            12,
+            &[
+                bookmark(1, "Intro", 1),
+                bookmark(2, "Scope", 2),
+                bookmark(1, "Design", 5),
+            ],
+        );
+        assert_eq!(
+            list,
+            "12 pages\nIntro (page 1)\n  Scope (page 2)\nDesign (page 5)\n"
+        );
+    }
+
+    #[test]
+    fn heading_outline_from_top_level() {
+        let heading = |level, text: &str| Block::Heading {
+            level,
+            text: text.into(),
+        };
+        let blocks = vec![
+            heading(2, "Intro"),
+            para("text"),
+            heading(3, "Scope"),
+            heading(2, "End"),
+        ];
+        assert_eq!(headings(&blocks), "Intro\n  Scope\nEnd\n");
+        assert_eq!(headings(&[para("text")]), "(no headings)\n");
+    }
+}
@@ -1,11 +1,14 @@
 //! Shared markdown inline formatting, hyperlink grouping, and image helpers.
 //!
 //! Both `docx.rs` and `pptx.rs` need to render text runs with bold/italic
-//! formatting and group consecutive runs sharing the same hyperlink URL.
+//! (and, for `.docx`, strikethrough, underline, and highlight) formatting and group consecutive runs sharing the same hyperlink URL.
 //! This module provides a single implementation via the [`InlineRun`] trait.
 //!
 //! The [`image_to_base64_md`] function encodes raw image bytes into a
 //! self-contained markdown `![](data:...)` image tag for `--images` support.
+//!
+//! [`escape_raw_html`] neutralizes document text that markdown renderers
+//! would pass through as HTML (`--escape-html`).
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 
@@ -22,6 +25,31 @@ pub(crate) trait InlineRun {
     fn italic(&self) -> bool;
     /// The resolved hyperlink URL, if any.
     fn link_url(&self) -> Option<&str>;
+    /// Whether the run is struck through, written `~~text~~`.
+    fn struck(&self) -> bool {
+        false
+    }
+    /// Whether the run is underlined, written `<u>text</u>`.
+    fn underlined(&self) -> bool {
+        false
+    }
+    /// Whether the run is highlighted, written `==text==`.
+    fn highlighted(&self) -> bool {
+        false
+    }
+    /// Whether the run is superscript, written `^text^`.
+    fn superscript(&self) -> bool {
+        false
+    }
+    /// Whether the run is subscript, written `~text~`.
+    fn subscript(&self) -> bool {
+        false
+    }
+    /// Whether the run is code, written `` `text` ``. Adjacent code runs
+    /// share one code span.
+    fn code(&self) -> bool {
+        false
+    }
 }
 
 /// Render a slice of runs as markdown with inline formatting and grouped
@@ -35,20 +63,16 @@ pub(crate) fn render_runs_markdown<R: InlineRun>(runs: &[R]) -> String {
     let mut i = 0;
 
     while i < runs.len() {
-        let run = &runs[i];
+        let url = runs[i].link_url();
+        let end = runs[i..]
+            .iter()
+            .position(|r| r.link_url() != url)
+            .map_or(runs.len(), |n| i + n);
 
         // Group consecutive runs that share the same hyperlink URL
-        if let Some(url) = run.link_url() {
+        if let Some(url) = url {
             let mut link_text = String::new();
-            while i < runs.len() && runs[i].link_url() == Some(url) {
-                let r = &runs[i];
-                if r.text().trim().is_empty() {
-                    link_text.push_str(r.text());
-                } else {
-                    format_run_inline(r, &mut link_text);
-                }
-                i += 1;
-            }
+            push_runs(&runs[i..end], &mut link_text);
             let link_text = link_text.trim();
             if !link_text.is_empty() {
                 out.push('[');
@@ -57,23 +81,67 @@ pub(crate) fn render_runs_markdown<R: InlineRun>(runs: &[R]) -> String {
                 out.push_str(url);
                 out.push(')');
             }
-            continue;
+        } else {
+            push_runs(&runs[i..end], &mut out);
         }
+        i = end;
+    }
 
-        if run.text().trim().is_empty() {
-            out.push_str(run.text());
+    out
+}
+
+/// Append runs with their formatting, joining each stretch of code runs
+/// into a single code span.
+fn push_runs<R: InlineRun>(runs: &[R], out: &mut String) {
+    let mut i = 0;
+    while i < runs.len() {
+        if !runs[i].code() {
+            format_run_inline(&runs[i], out);
             i += 1;
             continue;
         }
+        let end = runs[i..]
+            .iter()
+            .position(|r| !r.code())
+            .map_or(runs.len(), |n| i + n);
+        let text: String = runs[i..end].iter().map(InlineRun::text).collect();
+        push_code(&text, out);
+        i = end;
+    }
+}
 
-        format_run_inline(run, &mut out);
-        i += 1;
+/// Append `text` as a code span, with whitespace around it left outside.
+/// The span's backtick fence is longer than any run of backticks inside.
+fn push_code(text: &str, out: &mut String) {
+    let code = text.trim();
+    if code.is_empty() {
+        out.push_str(text);
+        return;
     }
+    let start = text.len() - text.trim_start().len();
+    out.push_str(&text[..start]);
+    let fence = "`".repeat(longest_backtick_run(code) + 1);
+    let pad = if code.starts_with('`') || code.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    out.push_str(&fence);
+    out.push_str(pad);
+    out.push_str(code);
+    out.push_str(pad);
+    out.push_str(&fence);
+    out.push_str(&text[start + code.len()..]);
+}
 
-    out
+/// Length of the longest run of backticks in `text`.
+pub(crate) fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
 }
 
-/// Apply bold/italic formatting to a single run and append to `out`.
+/// Apply a single run's formatting and append it to `out`: superscript or
+/// subscript innermost, then bold/italic, underline, strikethrough, and
+/// highlight.
 ///
 /// Whitespace-only runs are never wrapped in formatting markers.
 pub(crate) fn format_run_inline<R: InlineRun>(run: &R, out: &mut String) {
@@ -82,28 +150,40 @@ pub(crate) fn format_run_inline<R: InlineRun>(run: &R, out: &mut String) {
         return;
     }
 
-    match (run.bold(), run.italic()) {
-        (true, true) => {
-            out.push_str("***");
-            out.push_str(run.text());
-            out.push_str("***");
-        }
-        (true, false) => {
-            out.push_str("**");
-            out.push_str(run.text());
-            out.push_str("**");
-        }
-        (false, true) => {
-            out.push('*');
-            out.push_str(run.text());
-            out.push('*');
-        }
-        (false, false) => {
-            out.push_str(run.text());
-        }
+    let emphasis = match (run.bold(), run.italic()) {
+        (true, true) => "***",
+        (true, false) => "**",
+        (false, true) => "*",
+        (false, false) => "",
+    };
+    let marks = [
+        (run.highlighted(), "==", "=="),
+        (run.struck(), "~~", "~~"),
+        (run.underlined(), "<u>", "</u>"),
+    ];
+    for (_, open, _) in marks.iter().filter(|(on, ..)| *on) {
+        out.push_str(open);
+    }
+    out.push_str(emphasis);
+    match (run.superscript(), run.subscript()) {
+        (true, _) => push_script(run.text(), '^', out),
+        (false, true) => push_script(run.text(), '~', out),
+        (false, false) => out.push_str(run.text()),
+    }
+    out.push_str(emphasis);
+    for (_, _, close) in marks.iter().rev().filter(|(on, ..)| *on) {
+        out.push_str(close);
     }
 }
 
+/// Append `text` between `marker`s, as pandoc writes superscript (`^2^`)
+/// and subscript (`~2~`); spaces, which would end it, are escaped.
+fn push_script(text: &str, marker: char, out: &mut String) {
+    out.push(marker);
+    out.push_str(&text.replace(' ', "\\ "));
+    out.push(marker);
+}
+
 // ── Image helpers ──────────────────────────────────────────────────
 
 /// Detect the MIME type of an image from its magic bytes.
@@ -170,6 +250,103 @@ pub(crate) fn image_to_base64_ref(data: &[u8], id: &str) -> Option<ImageRef> {
     })
 }
 
+// ── HTML escaping ──────────────────────────────────────────────────
+
+/// Escape sequences in rendered markdown that a renderer would treat as
+/// raw HTML: `<` before a tag-like character becomes `&lt;`, and `&`
+/// starting an entity reference becomes `&amp;`, so `<script>` or
+/// `&lt;b&gt;` in a document shows up as written.
+///
+/// Code spans and fenced code blocks are left alone since renderers show
+/// them literally anyway, as is markup batdoc emits itself: `<a id>`
+/// anchor lines, `[id]: <data:...>` image definitions, and `<u>` tags.
+pub(crate) fn escape_raw_html(md: &str) -> String {
+    let mut out = String::with_capacity(md.len());
+    let mut in_fence = false;
+    for line in md.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+            out.push_str(line);
+        } else if in_fence || is_own_markup(trimmed) {
+            out.push_str(line);
+        } else {
+            escape_line(line, &mut out);
+        }
+    }
+    out
+}
+
+/// Lines made up only of markup batdoc generates.
+fn is_own_markup(line: &str) -> bool {
+    let line = line.trim_end();
+    if line.starts_with('[') && line.contains("]: <data:") {
+        return true;
+    }
+    let mut rest = line;
+    while let Some(tail) = rest.strip_prefix("<a id=\"") {
+        let Some((id, tail)) = tail.split_once("\"></a>") else {
+            return false;
+        };
+        if !id.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return false;
+        }
+        rest = tail;
+    }
+    rest.is_empty() && !line.is_empty()
+}
+
+fn escape_line(line: &str, out: &mut String) {
+    let mut rest = line;
+    while let Some(i) = rest.find(['<', '&', '`']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if tail.starts_with('`') {
+            // Copy a code span through its closing run of backticks
+            let ticks = tail.len() - tail.trim_start_matches('`').len();
+            let fence = &tail[..ticks];
+            let span = tail[ticks..]
+                .find(fence)
+                .map_or(ticks, |end| 2 * ticks + end);
+            out.push_str(&tail[..span]);
+            rest = &tail[span..];
+            continue;
+        }
+        if let Some(tag) = ["<u>", "</u>"].into_iter().find(|t| tail.starts_with(t)) {
+            out.push_str(tag);
+            rest = &tail[tag.len()..];
+            continue;
+        }
+        let next = tail[1..].chars().next();
+        if tail.starts_with('<')
+            && next.is_some_and(|c| c.is_ascii_alphabetic() || "/!?".contains(c))
+        {
+            out.push_str("&lt;");
+        } else if tail.starts_with('&') && is_entity(&tail[1..]) {
+            out.push_str("&amp;");
+        } else {
+            out.push_str(&tail[..1]);
+        }
+        rest = &tail[1..];
+    }
+    out.push_str(rest);
+}
+
+/// Whether `s` starts with the body of an entity reference: `name;`,
+/// `#123;`, or `#x1F;`.
+fn is_entity(s: &str) -> bool {
+    let Some(end) = s.find(';') else {
+        return false;
+    };
+    let body = &s[..end];
+    let digits = body.strip_prefix("#x").or_else(|| body.strip_prefix("#X"));
+    match (digits, body.strip_prefix('#')) {
+        (Some(hex), _) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        (None, Some(dec)) => !dec.is_empty() && dec.chars().all(|c| c.is_ascii_digit()),
+        (None, None) => !body.is_empty() && body.chars().all(|c| c.is_ascii_alphanumeric()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -180,6 +357,7 @@ mod tests {
         bold: bool,
         italic: bool,
         link_url: Option<String>,
+        code: bool,
     }
 
     impl InlineRun for TestRun {
@@ -195,6 +373,9 @@ mod tests {
         fn link_url(&self) -> Option<&str> {
             self.link_url.as_deref()
         }
+        fn code(&self) -> bool {
+            self.code
+        }
     }
 
     fn run(text: &str, bold: bool, italic: bool) -> TestRun {
@@ -203,6 +384,7 @@ mod tests {
             bold,
             italic,
             link_url: None,
+            code: false,
         }
     }
 
@@ -212,6 +394,14 @@ mod tests {
             bold,
             italic,
             link_url: Some(url.into()),
+            code: false,
+        }
+    }
+
+    fn code_run(text: &str) -> TestRun {
+        TestRun {
+            code: true,
+            ..run(text, false, false)
         }
     }
 
@@ -385,4 +575,55 @@ mod tests {
         let data = &[0x01, 0x00, 0x00, 0x00]; // not a recognized format
         assert!(image_to_base64_ref(data, "image1").is_none());
     }
+
+    // ── code spans ───────────────────────────────────────────────
+
+    #[test]
+    fn adjacent_code_runs_share_a_span() {
+        let runs = [
+            run("Run ", false, false),
+            code_run("cargo"),
+            code_run(" test "),
+            run("now, or ", false, false),
+            code_run("`x`"),
+        ];
+        assert_eq!(
+            render_runs_markdown(&runs),
+            "Run `cargo test` now, or `` `x` ``"
+        );
+    }
+
+    // ── escape_raw_html ──────────────────────────────────────────
+
+    #[test]
+    fn escapes_tags_and_entities() {
+        assert_eq!(
+            escape_raw_html("Run <script>alert(1)</script> & see &lt;b&gt;\n"),
+            "Run &lt;script>alert(1)&lt;/script> & see &amp;lt;b&amp;gt;\n"
+        );
+        assert_eq!(escape_raw_html("a < b && c <= d\n"), "a < b && c <= d\n");
+        assert_eq!(escape_raw_html("<!-- x -->"), "&lt;!-- x -->");
+        assert_eq!(escape_raw_html("<u>x</u> <ul>"), "<u>x</u> &lt;ul>");
+    }
+
+    #[test]
+    fn code_left_alone() {
+        let md = "Use `<div>` here\n\n```\n<b>&amp;</b>\n```\n<i>\n";
+        assert_eq!(
+            escape_raw_html(md),
+            "Use `<div>` here\n\n```\n<b>&amp;</b>\n```\n&lt;i>\n"
+        );
+        // An unclosed backtick is just a character
+        assert_eq!(escape_raw_html("`<b>"), "`&lt;b>");
+    }
+
+    #[test]
+    fn own_markup_kept() {
+        let md = "<a id=\"_Toc1\"></a>\n\n# Intro\n\n[image1]: <data:image/png;base64,AAAA>\n";
+        assert_eq!(escape_raw_html(md), md);
+        assert_eq!(
+            escape_raw_html("<a id=\"x\" onclick=\"y\"></a>\n"),
+            "&lt;a id=\"x\" onclick=\"y\">&lt;/a>\n"
+        );
+    }
 }
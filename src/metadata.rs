@@ -0,0 +1,1161 @@
+//! Document metadata: title, author, dates, and custom properties.
+//!
+//! OOXML files keep these in `docProps/core.xml`, `docProps/app.xml`, and
+//! `docProps/custom.xml`. Legacy OLE2 files store them in the
+//! `\x05SummaryInformation` and `\x05DocumentSummaryInformation` property
+//! set streams, and PDFs in the trailer's Info dictionary and the catalog's
+//! XMP packet. All are normalized into a single [`Metadata`] struct.
+
+use cfb::CompoundFile;
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use std::fmt::Write as _;
+use std::io::{Cursor, Read, Seek};
+use zip::ZipArchive;
+
+use crate::codepage;
+use crate::format::Format;
+use crate::xls::decode_utf16le;
+use crate::xlsx;
+use crate::xml_util::unescaped_attr;
+
+/// Document properties. Every field is optional; files often carry only a
+/// few of them.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Metadata {
+    pub(crate) title: Option<String>,
+    pub(crate) subject: Option<String>,
+    pub(crate) author: Option<String>,
+    pub(crate) keywords: Option<String>,
+    pub(crate) comments: Option<String>,
+    pub(crate) last_saved_by: Option<String>,
+    pub(crate) revision: Option<String>,
+    pub(crate) template: Option<String>,
+    pub(crate) category: Option<String>,
+    pub(crate) manager: Option<String>,
+    pub(crate) company: Option<String>,
+    /// Application that created the document.
+    pub(crate) application: Option<String>,
+    pub(crate) created: Option<DateTime>,
+    pub(crate) modified: Option<DateTime>,
+    pub(crate) printed: Option<DateTime>,
+    /// Statistics as last saved by the application; PDF page counts are
+    /// counted from the document itself.
+    pub(crate) pages: Option<u32>,
+    pub(crate) slides: Option<u32>,
+    pub(crate) words: Option<u32>,
+    /// User-defined properties as `(name, value)` pairs, in file order.
+    pub(crate) custom: Vec<(String, String)>,
+    /// A workbook's defined names as `(name, range)` pairs, in file order.
+    pub(crate) names: Vec<(String, String)>,
+    /// A workbook's drop-down lists as `(cells, values)` pairs, sheet by
+    /// sheet.
+    pub(crate) validations: Vec<(String, String)>,
+}
+
+impl Metadata {
+    /// The properties that are set, as `Label: value` lines with the
+    /// values aligned, followed by the user-defined properties and a
+    /// workbook's defined names and drop-down lists.
+    pub(crate) fn to_text(&self) -> String {
+        let date = |d: Option<DateTime>| d.map(DateTime::to_iso);
+        let count = |n: Option<u32>| n.map(|n| n.to_string());
+        let mut fields: Vec<(&str, Option<String>)> = vec![
+            ("Title", self.title.clone()),
+            ("Subject", self.subject.clone()),
+            ("Author", self.author.clone()),
+            ("Keywords", self.keywords.clone()),
+            ("Comments", self.comments.clone()),
+            ("Category", self.category.clone()),
+            ("Company", self.company.clone()),
+            ("Manager", self.manager.clone()),
+            ("Template", self.template.clone()),
+            ("Last saved by", self.last_saved_by.clone()),
+            ("Revision", self.revision.clone()),
+            ("Application", self.application.clone()),
+            ("Created", date(self.created)),
+            ("Modified", date(self.modified)),
+            ("Printed", date(self.printed)),
+            ("Pages", count(self.pages)),
+            ("Slides", count(self.slides)),
+            ("Words", count(self.words)),
+        ];
+        fields.extend(
+            self.custom
+                .iter()
+                .map(|(name, value)| (name.as_str(), Some(value.clone()))),
+        );
+        let fields: Vec<(&str, String)> = fields
+            .into_iter()
+            .filter_map(|(label, value)| Some((label, value?)))
+            .collect();
+
+        let width = fields
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut out = String::new();
+        for (label, value) in fields {
+            // Multi-line values (comments) stay on one line each
+            let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+            let pad = width - label.chars().count();
+            let _ = writeln!(out, "{label}:{:pad$} {value}", "");
+        }
+        if !self.names.is_empty() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str("Defined names:\n");
+            out.push_str(&names_text(&self.names, "  "));
+        }
+        if !self.validations.is_empty() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str("Validation lists:\n");
+            out.push_str(&names_text(&self.validations, "  "));
+        }
+        out
+    }
+
+    /// Look up a user-defined property by name (case-insensitive).
+    pub(crate) fn custom_property(&self, name: &str) -> Option<&str> {
+        self.custom
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// `name → range` lines with the ranges aligned, each line starting with
+/// `indent`.
+pub(crate) fn names_text(names: &[(String, String)], indent: &str) -> String {
+    let width = names
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for (name, range) in names {
+        let pad = width - name.chars().count();
+        let _ = writeln!(out, "{indent}{name}{:pad$} → {range}", "");
+    }
+    out
+}
+
+/// A calendar date and time in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DateTime {
+    pub(crate) year: i32,
+    pub(crate) month: u32,
+    pub(crate) day: u32,
+    pub(crate) hour: u32,
+    pub(crate) minute: u32,
+    pub(crate) second: u32,
+}
+
+/// Seconds between 1601-01-01 (the FILETIME epoch) and 1970-01-01.
+const FILETIME_UNIX_OFFSET: i64 = 11_644_473_600;
+
+impl DateTime {
+    /// Build from seconds since the Unix epoch.
+    pub(crate) fn from_unix(secs: i64) -> Self {
+        let days = secs.div_euclid(86_400);
+        let rem = secs.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // rem ∈ [0, 86400)
+        let rem = rem as u32;
+        Self {
+            year,
+            month,
+            day,
+            hour: rem / 3600,
+            minute: rem % 3600 / 60,
+            second: rem % 60,
+        }
+    }
+
+    /// Build from a Windows FILETIME (100 ns ticks since 1601-01-01).
+    /// Returns `None` for zero, which means "never".
+    pub(crate) fn from_filetime(ticks: u64) -> Option<Self> {
+        if ticks == 0 {
+            return None;
+        }
+        let secs = i64::try_from(ticks / 10_000_000).ok()?;
+        Some(Self::from_unix(secs - FILETIME_UNIX_OFFSET))
+    }
+
+    /// Parse a W3CDTF timestamp as used in `docProps/core.xml`
+    /// (`2024-03-01T09:30:00Z`, optionally with fractional seconds or a
+    /// `+hh:mm` offset). Offsets are normalized to UTC.
+    pub(crate) fn parse_w3cdtf(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (date, time) = s.split_once('T').unwrap_or((s, ""));
+
+        let mut parts = date.splitn(3, '-');
+        let year: i32 = parts.next()?.parse().ok()?;
+        let month: u32 = parts.next().map_or(Some(1), |m| m.parse().ok())?;
+        let day: u32 = parts.next().map_or(Some(1), |d| d.parse().ok())?;
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+
+        // Split the zone designator off the time
+        let (clock, offset_secs) = if let Some(t) = time.strip_suffix('Z') {
+            (t, 0)
+        } else if let Some(idx) = time.rfind(['+', '-']) {
+            let (t, zone) = time.split_at(idx);
+            let sign = if zone.starts_with('-') { -1 } else { 1 };
+            let zone = &zone[1..];
+            let (h, m) = zone.split_once(':').unwrap_or((zone, "0"));
+            let offset = h.parse::<i64>().ok()? * 3600 + m.parse::<i64>().ok()? * 60;
+            (t, sign * offset)
+        } else {
+            (time, 0)
+        };
+
+        let mut hms = clock.split(':');
+        let mut next = || -> Option<i64> {
+            match hms.next() {
+                Some(v) => v.split('.').next()?.parse().ok(),
+                None => Some(0),
+            }
+        };
+        let (hour, minute, second) = if clock.is_empty() {
+            (0, 0, 0)
+        } else {
+            (next()?, next()?, next()?)
+        };
+
+        let days = days_from_civil(year, month, day);
+        let secs = days * 86_400 + hour * 3600 + minute * 60 + second - offset_secs;
+        Some(Self::from_unix(secs))
+    }
+
+    /// Parse a PDF date string (`D:20240301093000+02'00'`). Everything
+    /// after the year is optional; offsets are normalized to UTC.
+    pub(crate) fn parse_pdf(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let s = s.strip_prefix("D:").unwrap_or(s);
+        let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (digits, zone) = s.split_at(end);
+        if digits.len() < 4 {
+            return None;
+        }
+        let field = |at: usize, default: &'static str| digits.get(at..at + 2).unwrap_or(default);
+        let zone = match zone.chars().next() {
+            Some('+' | '-') => zone.replace('\'', ":").trim_end_matches(':').to_string(),
+            _ => "Z".to_string(),
+        };
+        Self::parse_w3cdtf(&format!(
+            "{}-{}-{}T{}:{}:{}{zone}",
+            &digits[..4],
+            field(4, "01"),
+            field(6, "01"),
+            field(8, "00"),
+            field(10, "00"),
+            field(12, "00"),
+        ))
+    }
+
+    /// Day of the week, 0 = Sunday.
+    pub(crate) fn weekday(self) -> u32 {
+        // 1970-01-01 was a Thursday
+        let days = days_from_civil(self.year, self.month, self.day);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // ∈ [0, 7)
+        let wd = (days + 4).rem_euclid(7) as u32;
+        wd
+    }
+
+    /// ISO 8601 rendering: `YYYY-MM-DD`, plus ` HH:MM:SS` when the time
+    /// is not midnight.
+    pub(crate) fn to_iso(self) -> String {
+        let date = format!("{:04}-{:02}-{:02}", self.year, self.month, self.day);
+        if self.hour == 0 && self.minute == 0 && self.second == 0 {
+            date
+        } else {
+            format!(
+                "{date} {:02}:{:02}:{:02}",
+                self.hour, self.minute, self.second
+            )
+        }
+    }
+}
+
+/// Days since 1970-01-01 → (year, month, day), proleptic Gregorian.
+fn civil_from_days(days: i64) -> (i32, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // bounded ranges
+    (year as i32, month as u32, day as u32)
+}
+
+/// (year, month, day) → days since 1970-01-01, proleptic Gregorian.
+fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    let y = i64::from(year) - i64::from(month <= 2);
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let m = i64::from(month);
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Read the metadata of a document of `format`. Formats without a
+/// property store, and unreadable containers, give empty metadata.
+pub(crate) fn read(data: &[u8], format: Format, encoding: Option<u16>) -> Metadata {
+    match format {
+        Format::Doc | Format::Xls => CompoundFile::open(Cursor::new(data))
+            .map(|mut cfb| from_ole(&mut cfb, encoding))
+            .unwrap_or_default(),
+        Format::Docx | Format::Xlsx | Format::Pptx => ZipArchive::new(Cursor::new(data))
+            .map(|mut archive| from_ooxml(&mut archive))
+            .unwrap_or_default(),
+        Format::Pdf => from_pdf(data),
+    }
+}
+
+// ── OOXML ──────────────────────────────────────────────────────────
+
+/// Read metadata from the `docProps/*.xml` parts of an OOXML archive.
+/// Missing or malformed parts leave the corresponding fields empty.
+pub(crate) fn from_ooxml(archive: &mut ZipArchive<Cursor<&[u8]>>) -> Metadata {
+    let mut meta = Metadata::default();
+    if let Some(xml) = read_part(archive, "docProps/core.xml") {
+        parse_core_xml(&xml, &mut meta);
+    }
+    if let Some(xml) = read_part(archive, "docProps/app.xml") {
+        parse_app_xml(&xml, &mut meta);
+    }
+    if let Some(xml) = read_part(archive, "docProps/custom.xml") {
+        parse_custom_xml(&xml, &mut meta);
+    }
+    if let Some(xml) = read_part(archive, "xl/workbook.xml") {
+        meta.names = xlsx::parse_defined_names(&xml);
+        meta.validations = xlsx::read_validation_lists(archive);
+    }
+    meta
+}
+
+fn read_part(archive: &mut ZipArchive<Cursor<&[u8]>>, path: &str) -> Option<String> {
+    let mut xml = String::new();
+    archive.by_name(path).ok()?.read_to_string(&mut xml).ok()?;
+    Some(xml)
+}
+
+/// Collect `(element local name, text)` pairs for leaf elements directly
+/// under the root of a property part.
+fn leaf_elements(xml: &str) -> Vec<(String, String)> {
+    let mut reader = Reader::from_str(xml);
+    let mut out = Vec::new();
+    let mut current: Option<String> = None;
+    let mut depth = 0u32;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => {
+                depth += 1;
+                current = (depth == 2)
+                    .then(|| String::from_utf8_lossy(e.local_name().as_ref()).into_owned());
+            }
+            Ok(Event::Text(ref t)) => {
+                if let (Some(name), Ok(text)) = (current.as_ref(), t.unescape()) {
+                    let text = text.trim();
+                    if !text.is_empty() {
+                        out.push((name.clone(), text.to_string()));
+                    }
+                }
+            }
+            Ok(Event::End(_)) => {
+                depth = depth.saturating_sub(1);
+                current = None;
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    out
+}
+
+/// Parse `docProps/core.xml` (Dublin Core properties).
+fn parse_core_xml(xml: &str, meta: &mut Metadata) {
+    for (name, text) in leaf_elements(xml) {
+        match name.as_str() {
+            "title" => meta.title = Some(text),
+            "subject" => meta.subject = Some(text),
+            "creator" => meta.author = Some(text),
+            "keywords" => meta.keywords = Some(text),
+            "description" => meta.comments = Some(text),
+            "lastModifiedBy" => meta.last_saved_by = Some(text),
+            "revision" => meta.revision = Some(text),
+            "category" => meta.category = Some(text),
+            "created" => meta.created = DateTime::parse_w3cdtf(&text),
+            "modified" => meta.modified = DateTime::parse_w3cdtf(&text),
+            "lastPrinted" => meta.printed = DateTime::parse_w3cdtf(&text),
+            _ => {}
+        }
+    }
+}
+
+/// Parse `docProps/app.xml` (extended properties).
+fn parse_app_xml(xml: &str, meta: &mut Metadata) {
+    for (name, text) in leaf_elements(xml) {
+        match name.as_str() {
+            "Template" => meta.template = Some(text),
+            "Manager" => meta.manager = Some(text),
+            "Company" => meta.company = Some(text),
+            "Application" => meta.application = Some(text),
+            "Pages" => meta.pages = text.parse().ok(),
+            "Slides" => meta.slides = text.parse().ok(),
+            "Words" => meta.words = text.parse().ok(),
+            _ => {}
+        }
+    }
+}
+
+/// Parse `docProps/custom.xml`: `<property name="…"><vt:lpwstr>…</vt:lpwstr></property>`.
+fn parse_custom_xml(xml: &str, meta: &mut Metadata) {
+    let mut reader = Reader::from_str(xml);
+    let mut name: Option<String> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"property" => {
+                name = unescaped_attr(e, b"name");
+            }
+            Ok(Event::Text(ref t)) => {
+                if let (Some(n), Ok(text)) = (name.take(), t.unescape()) {
+                    meta.custom.push((n, text.trim().to_string()));
+                }
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"property" => {
+                name = None;
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+}
+
+// ── OLE2 property sets ─────────────────────────────────────────────
+
+const VT_I2: u32 = 2;
+const VT_I4: u32 = 3;
+const VT_R8: u32 = 5;
+const VT_BOOL: u32 = 11;
+const VT_LPSTR: u32 = 30;
+const VT_LPWSTR: u32 = 31;
+const VT_FILETIME: u32 = 64;
+
+/// Property set codepage value meaning UTF-16LE strings.
+const CP_WINUNICODE: u16 = 1200;
+
+/// A decoded property value.
+#[derive(Debug, Clone, PartialEq)]
+enum PropValue {
+    Text(String),
+    Int(i32),
+    Float(f64),
+    Bool(bool),
+    Date(Option<DateTime>),
+}
+
+impl PropValue {
+    fn into_text(self) -> Option<String> {
+        match self {
+            Self::Text(s) => Some(s),
+            Self::Int(n) => Some(n.to_string()),
+            Self::Float(f) => Some(f.to_string()),
+            Self::Bool(b) => Some(if b { "Yes" } else { "No" }.to_string()),
+            Self::Date(d) => d.map(DateTime::to_iso),
+        }
+    }
+}
+
+/// One section of a property set: `(property id, value)` pairs plus the
+/// name dictionary (only present for user-defined properties).
+#[derive(Debug, Default)]
+struct Section {
+    props: Vec<(u32, PropValue)>,
+    names: Vec<(u32, String)>,
+}
+
+/// Read metadata from the summary information streams of an OLE2 file.
+/// 8-bit strings are decoded with `encoding`, if given, instead of the
+/// codepage the stream declares.
+pub(crate) fn from_ole<F: Read + Seek>(
+    cfb: &mut CompoundFile<F>,
+    encoding: Option<u16>,
+) -> Metadata {
+    let mut meta = Metadata::default();
+
+    if let Some(sections) = read_property_set(cfb, "/\u{5}SummaryInformation", encoding) {
+        for (id, value) in sections.into_iter().flat_map(|s| s.props) {
+            match (id, value) {
+                (2, v) => meta.title = v.into_text(),
+                (3, v) => meta.subject = v.into_text(),
+                (4, v) => meta.author = v.into_text(),
+                (5, v) => meta.keywords = v.into_text(),
+                (6, v) => meta.comments = v.into_text(),
+                (7, v) => meta.template = v.into_text(),
+                (8, v) => meta.last_saved_by = v.into_text(),
+                (9, v) => meta.revision = v.into_text(),
+                (11, PropValue::Date(d)) => meta.printed = d,
+                (12, PropValue::Date(d)) => meta.created = d,
+                (13, PropValue::Date(d)) => meta.modified = d,
+                (14, PropValue::Int(n)) => meta.pages = u32::try_from(n).ok(),
+                (15, PropValue::Int(n)) => meta.words = u32::try_from(n).ok(),
+                (18, v) => meta.application = v.into_text(),
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(sections) = read_property_set(cfb, "/\u{5}DocumentSummaryInformation", encoding) {
+        let mut sections = sections.into_iter();
+        if let Some(first) = sections.next() {
+            for (id, value) in first.props {
+                match id {
+                    0x02 => meta.category = value.into_text(),
+                    0x07 => {
+                        if let PropValue::Int(n) = value {
+                            meta.slides = u32::try_from(n).ok();
+                        }
+                    }
+                    0x0E => meta.manager = value.into_text(),
+                    0x0F => meta.company = value.into_text(),
+                    _ => {}
+                }
+            }
+        }
+        // The second section holds user-defined properties
+        if let Some(custom) = sections.next() {
+            for (id, value) in custom.props {
+                let name = custom.names.iter().find(|(n, _)| *n == id);
+                if let (Some((_, name)), Some(value)) = (name, value.into_text()) {
+                    meta.custom.push((name.clone(), value));
+                }
+            }
+        }
+    }
+
+    // Empty strings are as good as absent
+    for field in [
+        &mut meta.title,
+        &mut meta.subject,
+        &mut meta.author,
+        &mut meta.keywords,
+        &mut meta.comments,
+        &mut meta.template,
+        &mut meta.last_saved_by,
+        &mut meta.revision,
+        &mut meta.category,
+        &mut meta.manager,
+        &mut meta.company,
+        &mut meta.application,
+    ] {
+        if field.as_deref().is_some_and(str::is_empty) {
+            *field = None;
+        }
+    }
+
+    meta
+}
+
+fn read_property_set<F: Read + Seek>(
+    cfb: &mut CompoundFile<F>,
+    path: &str,
+    encoding: Option<u16>,
+) -> Option<Vec<Section>> {
+    let mut stream = cfb.open_stream(path).ok()?;
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).ok()?;
+    Some(parse_property_set(&buf, encoding))
+}
+
+fn read_u16(data: &[u8], off: usize) -> Option<u16> {
+    data.get(off..off + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], off: usize) -> Option<u32> {
+    data.get(off..off + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Parse a property set stream (`[MS-OLEPS]`) into its sections.
+fn parse_property_set(data: &[u8], encoding: Option<u16>) -> Vec<Section> {
+    let count = read_u32(data, 24).unwrap_or(0).min(2) as usize; // u32 → usize: lossless on 32+ bit
+    (0..count)
+        .filter_map(|i| {
+            // Each entry: 16-byte FMTID, then a 4-byte section offset
+            let offset = read_u32(data, 28 + i * 20 + 16)? as usize;
+            data.get(offset..).map(|data| parse_section(data, encoding))
+        })
+        .collect()
+}
+
+fn parse_section(data: &[u8], encoding: Option<u16>) -> Section {
+    let mut section = Section::default();
+    let count = read_u32(data, 4).unwrap_or(0) as usize;
+
+    let entries: Vec<(u32, usize)> = (0..count.min(data.len() / 8))
+        .filter_map(|i| {
+            let id = read_u32(data, 8 + i * 8)?;
+            let off = read_u32(data, 12 + i * 8)? as usize;
+            Some((id, off))
+        })
+        .collect();
+
+    // The codepage (property 1) decides how 8-bit strings are decoded,
+    // unless overridden; UTF-16 strings are not 8-bit
+    let codepage = entries
+        .iter()
+        .find(|&&(id, _)| id == 1)
+        .and_then(|&(_, off)| read_u16(data, off + 4))
+        .unwrap_or(1252);
+    let codepage = match encoding {
+        Some(cp) if codepage != CP_WINUNICODE => cp,
+        _ => codepage,
+    };
+
+    for (id, off) in entries {
+        match id {
+            0 => section.names = parse_dictionary(data, off, codepage),
+            1 => {}
+            _ => {
+                if let Some(value) = parse_value(data, off, codepage) {
+                    section.props.push((id, value));
+                }
+            }
+        }
+    }
+
+    section
+}
+
+fn parse_value(data: &[u8], off: usize, codepage: u16) -> Option<PropValue> {
+    let vt = read_u32(data, off)? & 0xFFFF;
+    let body = off + 4;
+    match vt {
+        VT_I2 => read_u16(data, body).map(|v| PropValue::Int(i32::from(v.cast_signed()))),
+        VT_I4 => read_u32(data, body).map(|v| PropValue::Int(v.cast_signed())),
+        VT_R8 => data
+            .get(body..body + 8)
+            .and_then(|b| b.try_into().ok())
+            .map(|b| PropValue::Float(f64::from_le_bytes(b))),
+        VT_BOOL => read_u16(data, body).map(|v| PropValue::Bool(v != 0)),
+        VT_LPSTR => {
+            let len = read_u32(data, body)? as usize;
+            let bytes = data.get(body + 4..body + 4 + len)?;
+            Some(PropValue::Text(decode_string(bytes, codepage)))
+        }
+        VT_LPWSTR => {
+            let len = read_u32(data, body)? as usize;
+            let bytes = data.get(body + 4..body + 4 + len * 2)?;
+            Some(PropValue::Text(trim_nul(decode_utf16le(bytes))))
+        }
+        VT_FILETIME => {
+            let lo = u64::from(read_u32(data, body)?);
+            let hi = u64::from(read_u32(data, body + 4)?);
+            Some(PropValue::Date(DateTime::from_filetime(hi << 32 | lo)))
+        }
+        _ => None,
+    }
+}
+
+/// Parse the property-name dictionary of a user-defined section.
+fn parse_dictionary(data: &[u8], off: usize, codepage: u16) -> Vec<(u32, String)> {
+    let mut names = Vec::new();
+    let count = read_u32(data, off).unwrap_or(0);
+    let mut pos = off + 4;
+
+    for _ in 0..count {
+        let (Some(id), Some(len)) = (read_u32(data, pos), read_u32(data, pos + 4)) else {
+            break;
+        };
+        let len = len as usize;
+        pos += 8;
+        if codepage == CP_WINUNICODE {
+            let Some(bytes) = data.get(pos..pos + len * 2) else {
+                break;
+            };
+            names.push((id, trim_nul(decode_utf16le(bytes))));
+            // UTF-16 names are padded to a 4-byte boundary
+            pos += (len * 2).next_multiple_of(4);
+        } else {
+            let Some(bytes) = data.get(pos..pos + len) else {
+                break;
+            };
+            names.push((id, decode_string(bytes, codepage)));
+            pos += len;
+        }
+    }
+
+    names
+}
+
+fn decode_string(bytes: &[u8], codepage: u16) -> String {
+    if codepage == CP_WINUNICODE {
+        trim_nul(decode_utf16le(bytes))
+    } else {
+        trim_nul(codepage::decode_bytes(bytes, codepage))
+    }
+}
+
+fn trim_nul(s: String) -> String {
+    match s.find('\0') {
+        Some(idx) => s[..idx].to_string(),
+        None => s,
+    }
+}
+
+// ── PDF ────────────────────────────────────────────────────────────
+
+/// Read metadata from a PDF: the trailer's Info dictionary, with gaps
+/// filled from the catalog's XMP packet, plus the page count. Malformed
+/// files give empty metadata.
+fn from_pdf(data: &[u8]) -> Metadata {
+    // lopdf can panic on malformed input; see `pdf::extract_pages`
+    std::panic::catch_unwind(|| pdf_metadata(data))
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+fn pdf_metadata(data: &[u8]) -> Option<Metadata> {
+    let mut doc = pdf_extract::Document::load_mem(data).ok()?;
+    if doc.is_encrypted() {
+        doc.decrypt("").ok()?;
+    }
+    let mut meta = Metadata::default();
+
+    if let Ok(info) = doc
+        .trailer
+        .get_deref(b"Info", &doc)
+        .and_then(pdf_extract::Object::as_dict)
+    {
+        let text = |key: &[u8]| {
+            let value = info.get_deref(key, &doc).ok()?;
+            let value = pdf_extract::decode_text_string(value).ok()?;
+            let value = value.trim_start_matches('\u{FEFF}').trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+        meta.title = text(b"Title");
+        meta.subject = text(b"Subject");
+        meta.author = text(b"Author");
+        meta.keywords = text(b"Keywords");
+        meta.application = text(b"Creator").or_else(|| text(b"Producer"));
+        meta.created = text(b"CreationDate").and_then(|d| DateTime::parse_pdf(&d));
+        meta.modified = text(b"ModDate").and_then(|d| DateTime::parse_pdf(&d));
+    }
+
+    let xmp = doc
+        .catalog()
+        .and_then(|catalog| catalog.get_deref(b"Metadata", &doc))
+        .and_then(pdf_extract::Object::as_stream);
+    if let Ok(stream) = xmp {
+        // Unfiltered streams decompress to nothing
+        let bytes = stream
+            .decompressed_content()
+            .ok()
+            .filter(|b| !b.is_empty())
+            .unwrap_or_else(|| stream.content.clone());
+        parse_xmp(&String::from_utf8_lossy(&bytes), &mut meta);
+    }
+
+    meta.pages = u32::try_from(doc.get_pages().len()).ok();
+    Some(meta)
+}
+
+/// Fill the unset fields of `meta` from an XMP packet. Properties of the
+/// Dublin Core, XMP basic, and Adobe PDF schemas are read both as child
+/// elements of `rdf:Description` and as its attributes.
+fn parse_xmp(xml: &str, meta: &mut Metadata) {
+    // (property name, values); rdf:Seq and rdf:Bag give several values
+    let mut props: Vec<(String, Vec<String>)> = Vec::new();
+    let mut push = |name: &str, value: &str| {
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+        match props.iter_mut().find(|(n, _)| n == name) {
+            Some((_, values)) => values.push(value.to_string()),
+            None => props.push((name.to_string(), vec![value.to_string()])),
+        }
+    };
+
+    let mut reader = Reader::from_str(xml);
+    let mut stack: Vec<String> = Vec::new();
+    loop {
+        let event = reader.read_event();
+        match &event {
+            Ok(Event::Start(e) | Event::Empty(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                if name == "rdf:Description" {
+                    for attr in e.attributes().flatten() {
+                        if let Some(value) = unescaped_attr(e, attr.key.as_ref()) {
+                            push(&String::from_utf8_lossy(attr.key.as_ref()), &value);
+                        }
+                    }
+                }
+                if matches!(event, Ok(Event::Start(_))) {
+                    stack.push(name);
+                }
+            }
+            Ok(Event::Text(t)) => {
+                // The property is the element just inside rdf:Description
+                let prop = stack
+                    .iter()
+                    .rposition(|n| n == "rdf:Description")
+                    .and_then(|i| stack.get(i + 1));
+                if let (Some(prop), Ok(text)) = (prop, t.unescape()) {
+                    push(&prop.clone(), &text);
+                }
+            }
+            Ok(Event::End(_)) => {
+                stack.pop();
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    let first = |name: &str| {
+        props
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, values)| values[0].clone())
+    };
+    let all = |name: &str| {
+        props
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, values)| values.join(", "))
+    };
+    let fill = |field: &mut Option<String>, value: Option<String>| {
+        if field.is_none() {
+            *field = value;
+        }
+    };
+    fill(&mut meta.title, first("dc:title"));
+    fill(&mut meta.author, all("dc:creator"));
+    fill(&mut meta.comments, first("dc:description"));
+    fill(
+        &mut meta.keywords,
+        first("pdf:Keywords").or_else(|| all("dc:subject")),
+    );
+    fill(
+        &mut meta.application,
+        first("xmp:CreatorTool").or_else(|| first("pdf:Producer")),
+    );
+    meta.created = meta
+        .created
+        .or_else(|| first("xmp:CreateDate").and_then(|d| DateTime::parse_w3cdtf(&d)));
+    meta.modified = meta
+        .modified
+        .or_else(|| first("xmp:ModifyDate").and_then(|d| DateTime::parse_w3cdtf(&d)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── DateTime ─────────────────────────────────────────────────
+
+    #[test]
+    fn w3cdtf_utc() {
+        let dt = DateTime::parse_w3cdtf("2024-03-01T09:30:05Z").unwrap();
+        assert_eq!(dt.to_iso(), "2024-03-01 09:30:05");
+    }
+
+    #[test]
+    fn w3cdtf_offset_normalized() {
+        let dt = DateTime::parse_w3cdtf("2024-03-01T01:00:00+02:00").unwrap();
+        assert_eq!(dt.to_iso(), "2024-02-29 23:00:00");
+    }
+
+    #[test]
+    fn w3cdtf_date_only() {
+        let dt = DateTime::parse_w3cdtf("2023-12-25").unwrap();
+        assert_eq!(dt.to_iso(), "2023-12-25");
+    }
+
+    #[test]
+    fn w3cdtf_invalid() {
+        assert!(DateTime::parse_w3cdtf("yesterday").is_none());
+        assert!(DateTime::parse_w3cdtf("2023-13-01").is_none());
+    }
+
+    #[test]
+    fn filetime_conversion() {
+        // 2000-01-01 00:00:00 UTC
+        let dt = DateTime::from_filetime(125_911_584_000_000_000).unwrap();
+        assert_eq!(dt.to_iso(), "2000-01-01");
+        assert!(DateTime::from_filetime(0).is_none());
+    }
+
+    #[test]
+    fn pdf_dates() {
+        let dt = DateTime::parse_pdf("D:20240301093005+02'00'").unwrap();
+        assert_eq!(dt.to_iso(), "2024-03-01 07:30:05");
+        assert_eq!(
+            DateTime::parse_pdf("D:2023").unwrap().to_iso(),
+            "2023-01-01"
+        );
+        assert_eq!(
+            DateTime::parse_pdf("D:20231224120000Z00'00'")
+                .unwrap()
+                .to_iso(),
+            "2023-12-24 12:00:00"
+        );
+        assert!(DateTime::parse_pdf("D:99").is_none());
+    }
+
+    #[test]
+    fn weekday_known_dates() {
+        let dt = DateTime::parse_w3cdtf("2024-03-01").unwrap();
+        assert_eq!(dt.weekday(), 5); // Friday
+        let dt = DateTime::parse_w3cdtf("1970-01-04").unwrap();
+        assert_eq!(dt.weekday(), 0); // Sunday
+    }
+
+    // ── OOXML parts ──────────────────────────────────────────────
+
+    #[test]
+    fn core_xml_fields() {
+        let xml = r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/"><dc:title>Q3 Report</dc:title><dc:creator>Pat Lee</dc:creator><cp:lastModifiedBy>Sam</cp:lastModifiedBy><cp:revision>4</cp:revision><dcterms:created>2024-01-02T03:04:05Z</dcterms:created></cp:coreProperties>"#;
+        let mut meta = Metadata::default();
+        parse_core_xml(xml, &mut meta);
+        assert_eq!(meta.title.as_deref(), Some("Q3 Report"));
+        assert_eq!(meta.author.as_deref(), Some("Pat Lee"));
+        assert_eq!(meta.last_saved_by.as_deref(), Some("Sam"));
+        assert_eq!(meta.revision.as_deref(), Some("4"));
+        assert_eq!(meta.created.unwrap().to_iso(), "2024-01-02 03:04:05");
+    }
+
+    #[test]
+    fn app_xml_company() {
+        let xml = "<Properties><Template>Normal.dotm</Template><Company>Acme &amp; Co</Company><TitlesOfParts><vt:vector><vt:lpstr>x</vt:lpstr></vt:vector></TitlesOfParts></Properties>";
+        let mut meta = Metadata::default();
+        parse_app_xml(xml, &mut meta);
+        assert_eq!(meta.company.as_deref(), Some("Acme & Co"));
+        assert_eq!(meta.template.as_deref(), Some("Normal.dotm"));
+    }
+
+    #[test]
+    fn app_xml_statistics() {
+        let xml = "<Properties><Application>Microsoft Office Word</Application><Pages>12</Pages><Words>3456</Words></Properties>";
+        let mut meta = Metadata::default();
+        parse_app_xml(xml, &mut meta);
+        assert_eq!(meta.application.as_deref(), Some("Microsoft Office Word"));
+        assert_eq!(meta.pages, Some(12));
+        assert_eq!(meta.words, Some(3456));
+        assert_eq!(meta.slides, None);
+    }
+
+    #[test]
+    fn custom_xml_properties() {
+        let xml = r#"<Properties><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="2" name="Client"><vt:lpwstr>Globex</vt:lpwstr></property><property pid="3" name="Budget"><vt:i4>1200</vt:i4></property></Properties>"#;
+        let mut meta = Metadata::default();
+        parse_custom_xml(xml, &mut meta);
+        assert_eq!(meta.custom_property("client"), Some("Globex"));
+        assert_eq!(meta.custom_property("Budget"), Some("1200"));
+        assert_eq!(meta.custom_property("Missing"), None);
+    }
+
+    // ── to_text ──────────────────────────────────────────────────
+
+    #[test]
+    fn text_aligns_set_fields() {
+        let meta = Metadata {
+            title: Some("Q3\nReport".into()),
+            modified: DateTime::parse_w3cdtf("2024-01-02"),
+            words: Some(90),
+            custom: vec![("Client".into(), "Globex".into())],
+            ..Metadata::default()
+        };
+        assert_eq!(
+            meta.to_text(),
+            "Title:    Q3 Report\nModified: 2024-01-02\nWords:    90\nClient:   Globex\n"
+        );
+        assert_eq!(Metadata::default().to_text(), "");
+    }
+
+    #[test]
+    fn text_lists_defined_names() {
+        let meta = Metadata {
+            author: Some("Ann".into()),
+            names: vec![
+                ("Sales".into(), "Data!$A$1:$B$9".into()),
+                ("Rate (Q1)".into(), "0.2".into()),
+            ],
+            ..Metadata::default()
+        };
+        assert_eq!(
+            meta.to_text(),
+            "Author: Ann\n\nDefined names:\n  Sales     → Data!$A$1:$B$9\n  Rate (Q1) → 0.2\n"
+        );
+    }
+
+    #[test]
+    fn text_lists_validation_lists() {
+        let meta = Metadata {
+            names: vec![("Codes".into(), "Lists!$A$1:$A$3".into())],
+            validations: vec![
+                ("Form!C2:C100".into(), "Open, Closed".into()),
+                ("Form!D2".into(), "Codes".into()),
+            ],
+            ..Metadata::default()
+        };
+        assert_eq!(
+            meta.to_text(),
+            "Defined names:\n  Codes → Lists!$A$1:$A$3\n\n\
+             Validation lists:\n  Form!C2:C100 → Open, Closed\n  Form!D2      → Codes\n"
+        );
+    }
+
+    // ── PDF ──────────────────────────────────────────────────────
+
+    #[test]
+    fn xmp_fills_gaps() {
+        let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/" xmp:CreatorTool="Writer" xmp:CreateDate="2024-05-06T07:08:09Z"/>
+<rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/">
+<dc:title><rdf:Alt><rdf:li xml:lang="x-default">Budget</rdf:li><rdf:li xml:lang="fr">Budget FR</rdf:li></rdf:Alt></dc:title>
+<dc:creator><rdf:Seq><rdf:li>Ann</rdf:li><rdf:li>Bo</rdf:li></rdf:Seq></dc:creator>
+</rdf:Description></rdf:RDF></x:xmpmeta>"#;
+        let mut meta = Metadata {
+            title: Some("From Info".into()),
+            ..Metadata::default()
+        };
+        parse_xmp(xmp, &mut meta);
+        assert_eq!(meta.title.as_deref(), Some("From Info"));
+        assert_eq!(meta.author.as_deref(), Some("Ann, Bo"));
+        assert_eq!(meta.application.as_deref(), Some("Writer"));
+        assert_eq!(meta.created.unwrap().to_iso(), "2024-05-06 07:08:09");
+
+        let mut meta = Metadata::default();
+        parse_xmp(xmp, &mut meta);
+        assert_eq!(meta.title.as_deref(), Some("Budget"));
+    }
+
+    #[test]
+    fn malformed_pdf_is_empty() {
+        assert!(from_pdf(b"%PDF-1.4 garbage").to_text().is_empty());
+    }
+
+    // ── OLE2 property sets ───────────────────────────────────────
+
+    /// Build a one-section property set stream from `(id, type, body)`.
+    fn property_set(props: &[(u32, u32, Vec<u8>)]) -> Vec<u8> {
+        let mut section = Vec::new();
+        let header_len = 8 + props.len() * 8;
+        let mut values = Vec::new();
+        let mut index = Vec::new();
+        for (id, vt, body) in props {
+            index.extend_from_slice(&id.to_le_bytes());
+            index.extend_from_slice(
+                &u32::try_from(header_len + values.len())
+                    .unwrap()
+                    .to_le_bytes(),
+            );
+            values.extend_from_slice(&vt.to_le_bytes());
+            values.extend_from_slice(body);
+            while values.len() % 4 != 0 {
+                values.push(0);
+            }
+        }
+        section.extend_from_slice(
+            &u32::try_from(header_len + values.len())
+                .unwrap()
+                .to_le_bytes(),
+        );
+        section.extend_from_slice(&u32::try_from(props.len()).unwrap().to_le_bytes());
+        section.extend_from_slice(&index);
+        section.extend_from_slice(&values);
+
+        let mut stream = vec![0u8; 28];
+        stream[0..2].copy_from_slice(&0xFFFE_u16.to_le_bytes());
+        stream[24..28].copy_from_slice(&1u32.to_le_bytes());
+        stream.extend_from_slice(&[0u8; 16]); // FMTID
+        stream.extend_from_slice(&48u32.to_le_bytes());
+        stream.extend_from_slice(&section);
+        stream
+    }
+
+    fn lpstr(s: &str) -> Vec<u8> {
+        let mut body = u32::try_from(s.len() + 1).unwrap().to_le_bytes().to_vec();
+        body.extend_from_slice(s.as_bytes());
+        body.push(0);
+        body
+    }
+
+    #[test]
+    fn property_set_strings_and_dates() {
+        let stream = property_set(&[
+            (1, VT_I2, 1252u16.to_le_bytes().to_vec()),
+            (2, VT_LPSTR, lpstr("Minutes")),
+            (
+                12,
+                VT_FILETIME,
+                125_911_584_000_000_000_u64.to_le_bytes().to_vec(),
+            ),
+        ]);
+        let sections = parse_property_set(&stream, None);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(
+            sections[0].props,
+            vec![
+                (2, PropValue::Text("Minutes".into())),
+                (
+                    12,
+                    PropValue::Date(DateTime::parse_w3cdtf("2000-01-01T00:00:00Z"))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn property_set_encoding_override() {
+        let mut title = 3u32.to_le_bytes().to_vec();
+        title.extend_from_slice(b"\xC4\xE0\0");
+        let stream = property_set(&[
+            (1, VT_I2, 1251u16.to_le_bytes().to_vec()),
+            (2, VT_LPSTR, title),
+        ]);
+        let title = |encoding| parse_property_set(&stream, encoding)[0].props[0].1.clone();
+        assert_eq!(title(None), PropValue::Text("Да".into()));
+        assert_eq!(title(Some(1252)), PropValue::Text("Äà".into()));
+    }
+
+    #[test]
+    fn property_set_truncated_is_empty() {
+        assert!(parse_property_set(&[0u8; 10], None).is_empty());
+        let mut stream = property_set(&[(2, VT_LPSTR, lpstr("Title"))]);
+        stream.truncate(60);
+        let sections = parse_property_set(&stream, None);
+        assert!(sections.iter().all(|s| s.props.is_empty()));
+    }
+
+    #[test]
+    fn dictionary_ansi_names() {
+        let mut data = 1u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&7u32.to_le_bytes());
+        data.extend_from_slice(b"Client\0");
+        assert_eq!(
+            parse_dictionary(&data, 0, 1252),
+            vec![(2, "Client".to_string())]
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! List numbering of `.docx` documents (`word/numbering.xml`).
+//!
+//! A list paragraph names a numbering instance (`w:numId`) and a level
+//! (`w:ilvl`). The instance points at an abstract numbering definition,
+//! which gives each level a number format (`decimal`, `lowerLetter`,
+//! `bullet`, ...), a start value, and a label template such as `%1.` or
+//! `%1.%2.`. Instances of the same abstract definition continue each
+//! other's numbering unless they override a level's start, which is how
+//! Word restarts a list.
+
+use std::collections::{HashMap, HashSet};
+
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+
+use crate::xml_util::get_attr;
+
+/// Levels a list can have.
+const LEVELS: usize = 9;
+
+/// One level of an abstract numbering definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Level {
+    /// `w:numFmt`, such as `decimal` or `bullet`.
+    format: String,
+    /// `w:lvlText`, with `%N` standing for level N's number.
+    text: String,
+    start: u32,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self {
+            format: "decimal".into(),
+            text: String::new(),
+            start: 1,
+        }
+    }
+}
+
+/// A numbering instance: its abstract definition and restarted levels.
+#[derive(Debug, Default)]
+struct Instance {
+    abstract_id: String,
+    /// `w:startOverride` values, by level.
+    starts: HashMap<usize, u32>,
+}
+
+/// The numbering definitions of a document, and the counters of the
+/// lists numbered so far.
+#[derive(Debug, Default)]
+pub(crate) struct Numbering {
+    abstracts: HashMap<String, Vec<Level>>,
+    instances: HashMap<String, Instance>,
+    /// The current number at each level, by abstract definition.
+    counters: HashMap<String, [Option<u32>; LEVELS]>,
+    /// Instances already used, whose start overrides have been applied.
+    started: HashSet<String>,
+}
+
+impl Numbering {
+    /// Read the definitions in a `word/numbering.xml` part.
+    pub(crate) fn parse(xml: &str) -> Self {
+        let mut numbering = Self::default();
+        let mut reader = Reader::from_str(xml);
+        let mut abstract_id: Option<String> = None;
+        let mut num_id: Option<String> = None;
+        let mut level: Option<usize> = None;
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                    b"abstractNum" => abstract_id = get_attr(e, b"w:abstractNumId"),
+                    b"num" => {
+                        num_id = get_attr(e, b"w:numId");
+                        if let Some(id) = &num_id {
+                            numbering.instances.insert(id.clone(), Instance::default());
+                        }
+                    }
+                    b"lvl" | b"lvlOverride" => level = level_attr(e),
+                    _ => {}
+                },
+                Ok(Event::Empty(ref e)) => {
+                    let val = get_attr(e, b"w:val");
+                    match (e.local_name().as_ref(), val) {
+                        (b"numFmt", Some(val)) => {
+                            if let Some(level) = numbering.level(abstract_id.as_ref(), level) {
+                                level.format = val;
+                            }
+                        }
+                        (b"lvlText", Some(val)) => {
+                            if let Some(level) = numbering.level(abstract_id.as_ref(), level) {
+                                level.text = val;
+                            }
+                        }
+                        (b"start", Some(val)) => {
+                            if let Some(level) = numbering.level(abstract_id.as_ref(), level) {
+                                level.start = val.parse().unwrap_or(1);
+                            }
+                        }
+                        (b"abstractNumId", Some(val)) => {
+                            if let Some(instance) = num_id
+                                .as_ref()
+                                .and_then(|id| numbering.instances.get_mut(id))
+                            {
+                                instance.abstract_id = val;
+                            }
+                        }
+                        (b"startOverride", Some(val)) => {
+                            if let (Some(instance), Some(level)) = (
+                                num_id
+                                    .as_ref()
+                                    .and_then(|id| numbering.instances.get_mut(id)),
+                                level,
+                            ) {
+                                instance.starts.insert(level, val.parse().unwrap_or(1));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                    b"abstractNum" => abstract_id = None,
+                    b"num" => num_id = None,
+                    b"lvl" | b"lvlOverride" => level = None,
+                    _ => {}
+                },
+                Ok(Event::Eof) | Err(_) => break,
+                _ => {}
+            }
+        }
+        numbering
+    }
+
+    /// Level `level` of abstract definition `id`, created on first use.
+    fn level(&mut self, id: Option<&String>, level: Option<usize>) -> Option<&mut Level> {
+        let (id, level) = (id?, level?);
+        let levels = self.abstracts.entry(id.clone()).or_default();
+        if levels.len() <= level {
+            levels.resize_with(level + 1, Level::default);
+        }
+        levels.get_mut(level)
+    }
+
+    /// Count the next item of list `num_id` at `level`, returning its
+    /// label (`1.`, `a)`, `iv.`, `2.1.`), or `None` for a bullet or a list
+    /// that is not defined.
+    pub(crate) fn next(&mut self, num_id: &str, level: u8) -> Option<String> {
+        let level = usize::from(level).min(LEVELS - 1);
+        let instance = self.instances.get(num_id)?;
+        let levels = self.abstracts.get(&instance.abstract_id)?;
+        let counters = self
+            .counters
+            .entry(instance.abstract_id.clone())
+            .or_insert([None; LEVELS]);
+        if self.started.insert(num_id.to_string()) {
+            for &restarted in instance.starts.keys() {
+                if let Some(counter) = counters.get_mut(restarted) {
+                    *counter = None;
+                }
+            }
+        }
+        let start = |l: usize| {
+            instance
+                .starts
+                .get(&l)
+                .copied()
+                .unwrap_or_else(|| levels.get(l).map_or(1, |lvl| lvl.start))
+        };
+        counters[level] = Some(counters[level].map_or_else(|| start(level), |n| n + 1));
+        for deeper in &mut counters[level + 1..] {
+            *deeper = None;
+        }
+
+        let this = levels.get(level)?;
+        if matches!(this.format.as_str(), "bullet" | "none") || this.text.is_empty() {
+            return None;
+        }
+        let mut label = this.text.clone();
+        for l in (0..=level).rev() {
+            let placeholder = format!("%{}", l + 1);
+            if label.contains(&placeholder) {
+                let format = levels.get(l).map_or("decimal", |lvl| lvl.format.as_str());
+                let number = counters[l].unwrap_or_else(|| start(l));
+                label = label.replace(&placeholder, &format_number(number, format));
+            }
+        }
+        Some(label)
+    }
+}
+
+/// The `w:ilvl` attribute of a `<w:lvl>` or `<w:lvlOverride>`.
+fn level_attr(e: &quick_xml::events::BytesStart) -> Option<usize> {
+    get_attr(e, b"w:ilvl")
+        .and_then(|v| v.parse().ok())
+        .filter(|&l| l < LEVELS)
+}
+
+/// `n` written in number format `format`; unknown formats are decimal.
+fn format_number(n: u32, format: &str) -> String {
+    match format {
+        "lowerLetter" => letters(n).to_lowercase(),
+        "upperLetter" => letters(n),
+        "lowerRoman" => roman(n).to_lowercase(),
+        "upperRoman" => roman(n),
+        "decimalZero" => format!("{n:02}"),
+        _ => n.to_string(),
+    }
+}
+
+/// Word's letter numbering: A to Z, then AA, BB, ...
+fn letters(n: u32) -> String {
+    let n = n.max(1) - 1;
+    let letter = char::from(b'A' + u8::try_from(n % 26).unwrap_or(0));
+    letter.to_string().repeat((n / 26 + 1) as usize)
+}
+
+/// `n` in Roman numerals; 0 and numbers from 4000 on stay decimal.
+fn roman(n: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    if n == 0 || n >= 4000 {
+        return n.to_string();
+    }
+    let mut left = n;
+    let mut out = String::new();
+    for (value, numeral) in NUMERALS {
+        while left >= value {
+            out.push_str(numeral);
+            left -= value;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NUMBERING: &str = r#"<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+        <w:abstractNum w:abstractNumId="0">
+          <w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%1."/></w:lvl>
+          <w:lvl w:ilvl="1"><w:start w:val="1"/><w:numFmt w:val="lowerLetter"/><w:lvlText w:val="%2)"/></w:lvl>
+          <w:lvl w:ilvl="2"><w:start w:val="1"/><w:numFmt w:val="decimal"/><w:lvlText w:val="%1.%3"/></w:lvl>
+        </w:abstractNum>
+        <w:abstractNum w:abstractNumId="1">
+          <w:lvl w:ilvl="0"><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/></w:lvl>
+        </w:abstractNum>
+        <w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>
+        <w:num w:numId="2"><w:abstractNumId w:val="1"/></w:num>
+        <w:num w:numId="3"><w:abstractNumId w:val="0"/>
+          <w:lvlOverride w:ilvl="0"><w:startOverride w:val="1"/></w:lvlOverride>
+        </w:num>
+      </w:numbering>"#;
+
+    // ── Numbering ────────────────────────────────────────────────
+
+    #[test]
+    fn nested_levels_count_and_reset() {
+        let mut numbering = Numbering::parse(NUMBERING);
+        let labels: Vec<Option<String>> = [0, 1, 1, 0, 1, 2]
+            .iter()
+            .map(|&level| numbering.next("1", level))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                Some("1.".into()),
+                Some("a)".into()),
+                Some("b)".into()),
+                Some("2.".into()),
+                Some("a)".into()),
+                Some("2.1".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn bullets_and_unknown_lists_have_no_label() {
+        let mut numbering = Numbering::parse(NUMBERING);
+        assert_eq!(numbering.next("2", 0), None);
+        assert_eq!(numbering.next("9", 0), None);
+    }
+
+    #[test]
+    fn instances_continue_unless_restarted() {
+        let mut numbering = Numbering::parse(NUMBERING);
+        assert_eq!(numbering.next("1", 0).as_deref(), Some("1."));
+        assert_eq!(numbering.next("1", 0).as_deref(), Some("2."));
+        // Same abstract definition with a start override: restarts once
+        assert_eq!(numbering.next("3", 0).as_deref(), Some("1."));
+        assert_eq!(numbering.next("3", 0).as_deref(), Some("2."));
+        assert_eq!(numbering.next("1", 0).as_deref(), Some("3."));
+    }
+
+    // ── format_number ────────────────────────────────────────────
+
+    #[test]
+    fn number_formats() {
+        assert_eq!(format_number(3, "decimal"), "3");
+        assert_eq!(format_number(3, "decimalZero"), "03");
+        assert_eq!(format_number(28, "lowerLetter"), "bb");
+        assert_eq!(format_number(1, "upperLetter"), "A");
+        assert_eq!(format_number(14, "lowerRoman"), "xiv");
+        assert_eq!(format_number(1994, "upperRoman"), "MCMXCIV");
+        assert_eq!(format_number(7, "ordinalText"), "7");
+    }
+}
@@ -0,0 +1,446 @@
+//! Excel number formats: how a numeric cell is shown.
+//!
+//! A format code has up to four `;`-separated sections, for positive
+//! numbers, negative numbers, zero, and text. Within a section `0`, `#`,
+//! and `?` are digit placeholders, `.` is the decimal point, `,` between
+//! placeholders groups thousands and after them divides by 1000, `%`
+//! multiplies by 100, and `E+` switches to scientific notation. Quoted
+//! text, `\`-escaped characters, and currency tags such as `[$€-407]` are
+//! shown as they are; colors and the `_` and `*` spacing codes are not.
+//!
+//! Dates are the business of [`crate::dateconv`]. Formats this module
+//! does not cover — text, fractions, and conditional sections — leave
+//! the number as the file stores it, and so does `General`, short of the
+//! rounding in [`general`].
+
+use std::fmt::Write as _;
+
+/// The format code of a built-in `numFmtId` that is not a date, as Excel
+/// shows it in an English (US) locale. The currency and accounting
+/// formats (5–8, 41–44) follow the locale in Excel; the US ones are used.
+pub(crate) const fn builtin(id: u16) -> Option<&'static str> {
+    Some(match id {
+        1 => "0",
+        2 => "0.00",
+        3 => "#,##0",
+        4 => "#,##0.00",
+        5 => r##""$"#,##0_);\("$"#,##0\)"##,
+        6 => r##""$"#,##0_);[Red]\("$"#,##0\)"##,
+        7 => r##""$"#,##0.00_);\("$"#,##0.00\)"##,
+        8 => r##""$"#,##0.00_);[Red]\("$"#,##0.00\)"##,
+        9 => "0%",
+        10 => "0.00%",
+        11 => "0.00E+00",
+        37 => "#,##0_);(#,##0)",
+        38 => "#,##0_);[Red](#,##0)",
+        39 => "#,##0.00_);(#,##0.00)",
+        40 => "#,##0.00_);[Red](#,##0.00)",
+        41 => r#"_(* #,##0_);_(* \(#,##0\);_(* "-"_);_(@_)"#,
+        42 => r#"_("$"* #,##0_);_("$"* \(#,##0\);_("$"* "-"_);_(@_)"#,
+        43 => r#"_(* #,##0.00_);_(* \(#,##0.00\);_(* "-"??_);_(@_)"#,
+        44 => r#"_("$"* #,##0.00_);_("$"* \(#,##0.00\);_("$"* "-"??_);_(@_)"#,
+        48 => "##0.0E+0",
+        _ => return None,
+    })
+}
+
+/// Show `value` the way the format `code` does: `$#,##0.00` turns
+/// 1234.5 into `$1,234.50`. `None` when the format is one this module
+/// leaves alone.
+pub(crate) fn format(value: f64, code: &str) -> Option<String> {
+    if !value.is_finite() {
+        return None;
+    }
+    let sections = split_sections(code);
+    // A negative number takes the second section without its sign, and
+    // zero the third, when there are that many
+    let (section, signed) = match sections.len() {
+        n if value < 0.0 && n >= 2 => (sections[1], false),
+        n if value == 0.0 && n >= 3 => (sections[2], false),
+        _ => (sections[0], value < 0.0),
+    };
+    let section = parse_section(section)?;
+    let text = section.render(value.abs());
+    let text = text.trim();
+    Some(if signed {
+        format!("-{text}")
+    } else {
+        text.to_string()
+    })
+}
+
+/// A number in the General format, at the 15 significant digits Excel
+/// keeps: `0.30000000000000004` is `0.3`.
+pub(crate) fn general(value: f64) -> String {
+    let rounded: f64 = format!("{value:.14e}").parse().unwrap_or(value);
+    rounded.to_string()
+}
+
+/// Split a format code at the `;`s that are not quoted or escaped.
+fn split_sections(code: &str) -> Vec<&str> {
+    let mut sections = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (0, false, false);
+    for (i, c) in code.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if !quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                sections.push(&code[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    sections.push(&code[start..]);
+    sections
+}
+
+/// One piece of a format section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// Shown as it is.
+    Text(String),
+    /// A digit placeholder (`0`, `#`, or `?`) before the decimal point.
+    Int(char),
+    /// The decimal point.
+    Point,
+    /// A digit placeholder after the decimal point.
+    Frac(char),
+    /// `E+` or `E-`: the exponent's sign is shown always, or only when
+    /// negative.
+    Exp { plus: bool },
+    /// A digit placeholder of the exponent.
+    ExpDigit(char),
+}
+
+/// A parsed format section.
+#[derive(Debug, Default)]
+struct Section {
+    tokens: Vec<Token>,
+    /// Whether the integer digits are grouped in thousands.
+    thousands: bool,
+    /// Power of ten the value is scaled by: +2 for each `%`, -3 for each
+    /// trailing `,`.
+    scale: i32,
+}
+
+/// Parse a format section, or `None` for one this module does not
+/// render: dates and times, text, `General`, fractions, and conditions.
+fn parse_section(src: &str) -> Option<Section> {
+    if src.to_ascii_lowercase().contains("general") {
+        return None;
+    }
+    let chars: Vec<char> = src.chars().collect();
+    let is_placeholder = |c: &char| matches!(c, '0' | '#' | '?');
+    let mut section = Section::default();
+    let (mut point, mut exp) = (false, false);
+    let mut i = 0;
+    while let Some(&c) = chars.get(i) {
+        i += 1;
+        let token = match c {
+            '"' => {
+                let len = chars[i..].iter().position(|&c| c == '"');
+                let end = len.map_or(chars.len(), |len| i + len);
+                let text = chars[i..end].iter().collect();
+                i = end + 1;
+                Token::Text(text)
+            }
+            '\\' => {
+                i += 1;
+                Token::Text(chars.get(i - 1).map(char::to_string).unwrap_or_default())
+            }
+            // Padding the width of the next character, and fill
+            '_' | '*' => {
+                i += 1;
+                if c == '*' {
+                    continue;
+                }
+                Token::Text(" ".into())
+            }
+            '[' => {
+                let len = chars[i..].iter().position(|&c| c == ']')?;
+                let tag: String = chars[i..i + len].iter().collect();
+                i += len + 1;
+                match tag.strip_prefix('$') {
+                    // Currency and locale: `[$€-407]`
+                    Some(tag) => Token::Text(tag.split('-').next().unwrap_or_default().into()),
+                    // Conditions and elapsed time
+                    None if tag.starts_with(['<', '>', '=']) => return None,
+                    None if tag.chars().all(|c| "hHmMsS".contains(c)) => return None,
+                    // Colors
+                    None => continue,
+                }
+            }
+            '0' | '#' | '?' if exp => Token::ExpDigit(c),
+            '0' | '#' | '?' if point => Token::Frac(c),
+            '0' | '#' | '?' => Token::Int(c),
+            '.' if !point && !exp => {
+                point = true;
+                Token::Point
+            }
+            ',' => {
+                let after_digit =
+                    matches!(section.tokens.last(), Some(Token::Int(_) | Token::Frac(_)));
+                if after_digit && chars.get(i).is_some_and(is_placeholder) && !point {
+                    section.thousands = true;
+                    continue;
+                }
+                if after_digit && !chars[i..].iter().any(is_placeholder) {
+                    section.scale -= 3;
+                    continue;
+                }
+                Token::Text(",".into())
+            }
+            '%' => {
+                section.scale += 2;
+                Token::Text("%".into())
+            }
+            'E' | 'e' if matches!(chars.get(i), Some('+' | '-')) && !exp => {
+                exp = true;
+                i += 1;
+                Token::Exp {
+                    plus: chars[i - 1] == '+',
+                }
+            }
+            // Fractions, text, and date or time parts
+            '/' | '@' => return None,
+            c if "yYmMdDhHsS".contains(c) => return None,
+            c => Token::Text(c.to_string()),
+        };
+        section.tokens.push(token);
+    }
+    Some(section)
+}
+
+impl Section {
+    /// Show `value`, which is not negative.
+    fn render(&self, value: f64) -> String {
+        let count = |f: fn(&Token) -> bool| self.tokens.iter().filter(|t| f(t)).count();
+        let int_places = count(|t| matches!(t, Token::Int(_)));
+        let frac_places = count(|t| matches!(t, Token::Frac(_)));
+        let exp_places = count(|t| matches!(t, Token::ExpDigit(_)));
+        let has_exp = self.tokens.iter().any(|t| matches!(t, Token::Exp { .. }));
+
+        let value = value * 10f64.powi(self.scale);
+        let (mantissa, exponent) = if has_exp {
+            self.scientific(value, int_places, frac_places)
+        } else {
+            (value, 0)
+        };
+        let digits = format!("{:.frac_places$}", round(mantissa, frac_places));
+        let (int_digits, frac_digits) = digits.split_once('.').unwrap_or((&digits, ""));
+        let int_digits = int_digits.trim_start_matches('0');
+
+        let mut ints = self.fill_int(int_digits).into_iter();
+        let mut fracs = fill_frac(&self.tokens, frac_digits).into_iter();
+        let mut out = String::new();
+        let mut exp_shown = false;
+        for token in &self.tokens {
+            match token {
+                Token::Text(text) => out.push_str(text),
+                Token::Int(_) => out.push_str(&ints.next().unwrap_or_default()),
+                Token::Point => {
+                    // Integer digits with nowhere else to go: `.00`
+                    if int_places == 0 {
+                        out.push_str(int_digits);
+                    }
+                    out.push('.');
+                }
+                Token::Frac(_) => out.push_str(&fracs.next().unwrap_or_default()),
+                Token::Exp { plus } => {
+                    out.push('E');
+                    if exponent < 0 {
+                        out.push('-');
+                    } else if *plus {
+                        out.push('+');
+                    }
+                }
+                Token::ExpDigit(_) if !exp_shown => {
+                    exp_shown = true;
+                    let exponent = exponent.unsigned_abs();
+                    let _ = write!(out, "{exponent:0exp_places$}");
+                }
+                Token::ExpDigit(_) => {}
+            }
+        }
+        out
+    }
+
+    /// Split `value` into a mantissa and a power of ten for scientific
+    /// notation. The mantissa keeps one integer digit, or as many as the
+    /// format has `0`s before the point; a format starting with `#`, such
+    /// as `##0.0E+0`, keeps the exponent a multiple of its placeholders.
+    fn scientific(&self, value: f64, int_places: usize, frac_places: usize) -> (f64, i32) {
+        if value == 0.0 {
+            return (0.0, 0);
+        }
+        let engineering = matches!(
+            self.tokens.iter().find(|t| matches!(t, Token::Int(_))),
+            Some(Token::Int('#'))
+        );
+        let int_places = i32::try_from(int_places.max(1)).unwrap_or(1);
+        let (group, lead) = if engineering {
+            (int_places, 1)
+        } else {
+            (1, int_places)
+        };
+        #[allow(clippy::cast_possible_truncation)] // |log10| of a finite f64 is below 400
+        let magnitude = value.log10().floor() as i32;
+        let mut exponent = (magnitude - (lead - 1)).div_euclid(group) * group;
+        let mut mantissa = value / 10f64.powi(exponent);
+        // Rounding can carry into another digit: 9.999 as `0.00E+00`
+        if round(mantissa, frac_places) >= 10f64.powi(lead + group - 1) {
+            exponent += group;
+            mantissa /= 10f64.powi(group);
+        }
+        (mantissa, exponent)
+    }
+
+    /// What each integer placeholder shows, in order. Digits fill them
+    /// from the right, and the leftmost takes whatever digits are left.
+    fn fill_int(&self, digits: &str) -> Vec<String> {
+        let places: Vec<char> = self
+            .tokens
+            .iter()
+            .filter_map(|t| match t {
+                Token::Int(c) => Some(*c),
+                _ => None,
+            })
+            .collect();
+        let digits: Vec<char> = digits.chars().collect();
+        let mut shown = vec![String::new(); places.len()];
+        let (mut left, mut position) = (digits.len(), 0);
+        let mut push = |slot: &mut String, digit: char| {
+            if self.thousands && position > 0 && position % 3 == 0 {
+                slot.insert(0, ',');
+            }
+            slot.insert(0, digit);
+            position += 1;
+        };
+        for (k, &place) in places.iter().enumerate().rev() {
+            let slot = &mut shown[k];
+            let take = if k == 0 { left } else { left.min(1) };
+            for _ in 0..take {
+                left -= 1;
+                push(slot, digits[left]);
+            }
+            if take == 0 {
+                match place {
+                    '0' => push(slot, '0'),
+                    '?' => slot.push(' '),
+                    _ => {}
+                }
+            }
+        }
+        shown
+    }
+}
+
+/// `value` rounded to `places` decimals, halves away from zero as Excel
+/// does rather than to even as `format!` does.
+fn round(value: f64, places: usize) -> f64 {
+    let scale = 10f64.powi(i32::try_from(places).unwrap_or(i32::MAX));
+    let rounded = (value * scale).round() / scale;
+    if rounded.is_finite() {
+        rounded
+    } else {
+        value
+    }
+}
+
+/// What each fraction placeholder shows, in order: the digits from the
+/// left, with the trailing zeros under `#` and `?` dropped or blanked.
+fn fill_frac(tokens: &[Token], digits: &str) -> Vec<String> {
+    let places: Vec<char> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            Token::Frac(c) => Some(*c),
+            _ => None,
+        })
+        .collect();
+    let mut shown: Vec<String> = digits.chars().map(String::from).collect();
+    shown.resize(places.len(), String::new());
+    for (slot, place) in shown.iter_mut().zip(&places).rev() {
+        if *place == '0' || slot != "0" {
+            break;
+        }
+        *slot = if *place == '?' {
+            " ".into()
+        } else {
+            String::new()
+        };
+    }
+    shown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(value: f64, code: &str) -> String {
+        format(value, code).unwrap_or_else(|| panic!("{code} not rendered"))
+    }
+
+    // ── digits and decimals ──────────────────────────────────────
+
+    #[test]
+    fn fixed_decimals() {
+        assert_eq!(fmt(0.123_456_789_01, "0.00"), "0.12");
+        assert_eq!(fmt(2.5, "0"), "3");
+        assert_eq!(fmt(7.0, "000"), "007");
+        assert_eq!(fmt(0.5, "#.##"), ".5");
+        assert_eq!(fmt(3.0, "0.0#"), "3.0");
+        assert_eq!(fmt(-1.25, "0.0"), "-1.3");
+        assert_eq!(fmt(12.5, ".00"), "12.50");
+    }
+
+    #[test]
+    fn thousands_and_scaling() {
+        assert_eq!(fmt(1_234_567.891, "#,##0.00"), "1,234,567.89");
+        assert_eq!(fmt(999.0, "#,##0"), "999");
+        assert_eq!(fmt(0.0, "#,##0"), "0");
+        assert_eq!(fmt(12_345_678.0, "#,##0,"), "12,346");
+        assert_eq!(fmt(12_345_678.0, "0.0,,\" M\""), "12.3 M");
+    }
+
+    #[test]
+    fn percent_and_scientific() {
+        assert_eq!(fmt(0.1234, "0%"), "12%");
+        assert_eq!(fmt(0.1234, "0.00%"), "12.34%");
+        assert_eq!(fmt(12345.0, "0.00E+00"), "1.23E+04");
+        assert_eq!(fmt(0.000_12, "0.00E+00"), "1.20E-04");
+        assert_eq!(fmt(9.999, "0.00E+00"), "1.00E+01");
+        assert_eq!(fmt(12345.0, "##0.0E+0"), "12.3E+3");
+    }
+
+    // ── sections and literals ────────────────────────────────────
+
+    #[test]
+    fn currency_formats() {
+        assert_eq!(fmt(1234.5, "$#,##0.00"), "$1,234.50");
+        assert_eq!(fmt(-1234.5, builtin(7).unwrap()), "($1,234.50)");
+        assert_eq!(fmt(1234.5, builtin(44).unwrap()), "$1,234.50");
+        assert_eq!(fmt(0.0, builtin(44).unwrap()), "$-");
+        assert_eq!(fmt(5.0, "[$€-407] #,##0.00"), "€ 5.00");
+        assert_eq!(fmt(-5.0, "0;[Red]\"minus \"0"), "minus 5");
+        assert_eq!(fmt(3.0, r#"0" items;"\;"#), "3 items;;");
+    }
+
+    #[test]
+    fn general_drops_binary_noise() {
+        assert_eq!(general(0.1 + 0.2), "0.3");
+        assert_eq!(general(0.123_456_789_01), "0.12345678901");
+        assert_eq!(general(-42.0), "-42");
+    }
+
+    #[test]
+    fn unsupported_formats_left_alone() {
+        assert_eq!(format(1.5, "General"), None);
+        assert_eq!(format(1.5, "@"), None);
+        assert_eq!(format(1.5, "# ?/?"), None);
+        assert_eq!(format(1.5, "[>100]0;0.0"), None);
+        assert_eq!(format(1.5, "yyyy-mm-dd"), None);
+        assert_eq!(builtin(14), None);
+    }
+}
@@ -0,0 +1,560 @@
+//! Office Math (OMML) equations as LaTeX.
+//!
+//! Word stores equations as `<m:oMath>` trees of structures — fractions
+//! (`<m:f>`), scripts (`<m:sSup>`), radicals, n-ary operators such as sums
+//! and integrals, delimiters, matrices — whose arguments (`<m:num>`,
+//! `<m:e>`, `<m:sup>`, ...) hold runs of math text (`<m:r><m:t>`). Each
+//! structure maps onto a LaTeX construct, and the Unicode in the runs
+//! (Greek letters, operators, arrows) onto LaTeX commands, so an equation
+//! like `x = (−b ± √(b²−4ac)) / 2a` comes out as
+//! `x=\frac{-b\pm\sqrt{b^{2}-4ac}}{2a}`. Structures not listed here keep
+//! their arguments' text.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+
+use crate::xml_util::get_attr;
+
+/// Function names written as LaTeX commands (`\sin`) rather than as
+/// `\operatorname{...}`.
+const FUNCTIONS: &[&str] = &[
+    "arccos", "arcsin", "arctan", "arg", "cos", "cosh", "cot", "coth", "csc", "deg", "det", "dim",
+    "exp", "gcd", "hom", "inf", "ker", "lg", "lim", "liminf", "limsup", "ln", "log", "max", "min",
+    "Pr", "sec", "sin", "sinh", "sup", "tan", "tanh",
+];
+
+/// Convert the `<m:oMath>` element whose start tag was just read, up to
+/// and including its end tag.
+pub(crate) fn to_latex(reader: &mut Reader<&[u8]>) -> String {
+    convert(reader, b"oMath", false).trim().to_string()
+}
+
+/// The arguments and properties of a math structure.
+#[derive(Debug, Default)]
+struct Parts {
+    /// `m:val` of the elements in its `<m:...Pr>`, by local name; `"1"`
+    /// for a flag without a value, such as `<m:degHide/>`.
+    props: HashMap<String, String>,
+    /// Its arguments in order, converted, by local name.
+    args: Vec<(String, String)>,
+}
+
+impl Parts {
+    /// The first argument named `name`, or an empty one.
+    fn get(&self, name: &str) -> &str {
+        self.args
+            .iter()
+            .find(|(n, _)| n == name)
+            .map_or("", |(_, latex)| latex.as_str())
+    }
+
+    /// Every argument named `name`.
+    fn all(&self, name: &str) -> Vec<&str> {
+        self.args
+            .iter()
+            .filter(|(n, _)| n == name)
+            .map(|(_, latex)| latex.as_str())
+            .collect()
+    }
+
+    fn prop(&self, name: &str) -> Option<&str> {
+        self.props.get(name).map(String::as_str)
+    }
+
+    /// Whether flag `name` is on (`1`, `on`, or `true`).
+    fn flag(&self, name: &str) -> bool {
+        self.prop(name)
+            .is_some_and(|v| matches!(v, "1" | "on" | "true"))
+    }
+}
+
+/// Convert the content of the element `end` up to its end tag. Inside a
+/// function name (`fname`), known function names become commands.
+fn convert(reader: &mut Reader<&[u8]>, end: &[u8], fname: bool) -> String {
+    let mut out = String::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => {
+                let name = e.local_name().as_ref().to_vec();
+                let latex = match name.as_slice() {
+                    b"r" => run(reader, fname),
+                    b"f" | b"sSup" | b"sSub" | b"sSubSup" | b"sPre" | b"rad" | b"nary" | b"d"
+                    | b"func" | b"acc" | b"bar" | b"groupChr" | b"limLow" | b"limUpp"
+                    | b"borderBox" | b"eqArr" | b"m" => {
+                        let parts = read_parts(reader, &name, fname);
+                        structure(&name, &parts)
+                    }
+                    n if n.ends_with(b"Pr") => {
+                        let _ = reader.read_to_end(e.name());
+                        String::new()
+                    }
+                    _ => convert(reader, &name, fname),
+                };
+                push(&mut out, &latex);
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == end => break,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Read the properties and arguments of the structure `end`.
+fn read_parts(reader: &mut Reader<&[u8]>, end: &[u8], fname: bool) -> Parts {
+    let mut parts = Parts::default();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => {
+                let name = e.local_name().as_ref().to_vec();
+                if name.ends_with(b"Pr") {
+                    read_props(reader, &name, &mut parts.props);
+                    continue;
+                }
+                let latex = match name.as_slice() {
+                    // A matrix row: its cells, separated by &
+                    b"mr" => read_parts(reader, b"mr", fname).all("e").join(" & "),
+                    b"fName" => convert(reader, b"fName", true),
+                    _ => convert(reader, &name, fname),
+                };
+                parts
+                    .args
+                    .push((String::from_utf8_lossy(&name).into_owned(), latex));
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == end => break,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    parts
+}
+
+/// Collect the `m:val` of each element in the properties element `end`.
+fn read_props(reader: &mut Reader<&[u8]>, end: &[u8], props: &mut HashMap<String, String>) {
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(ref e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                let val = get_attr(e, b"m:val").unwrap_or_else(|| "1".into());
+                props.insert(name, val);
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == end => break,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+}
+
+/// Read an `<m:r>` run: its text, escaped for LaTeX, in `\text{...}` if
+/// it is marked as normal text.
+fn run(reader: &mut Reader<&[u8]>, fname: bool) -> String {
+    let mut text = String::new();
+    let mut normal = false;
+    let mut in_text = false;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"t" => in_text = true,
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"t" => in_text = false,
+            Ok(Event::Text(ref t)) if in_text => {
+                if let Ok(s) = t.unescape() {
+                    text.push_str(&s);
+                }
+            }
+            Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"nor" => {
+                normal = get_attr(e, b"m:val").is_none_or(|v| matches!(v.as_str(), "1" | "on"));
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"r" => break,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    if fname && FUNCTIONS.contains(&text.trim()) {
+        format!("\\{}", text.trim())
+    } else if fname && text.trim().chars().count() > 1 && text.chars().all(char::is_alphabetic) {
+        format!("\\operatorname{{{}}}", text.trim())
+    } else if normal {
+        format!("\\text{{{}}}", escape_text(&text))
+    } else {
+        escape(&text)
+    }
+}
+
+/// The LaTeX for structure `name` with arguments `parts`.
+fn structure(name: &[u8], parts: &Parts) -> String {
+    let e = parts.get("e");
+    match name {
+        b"f" => match parts.prop("type") {
+            Some("lin") => format!("{}/{}", parts.get("num"), parts.get("den")),
+            Some("noBar") => format!(
+                "\\genfrac{{}}{{}}{{0pt}}{{}}{{{}}}{{{}}}",
+                parts.get("num"),
+                parts.get("den")
+            ),
+            _ => format!("\\frac{{{}}}{{{}}}", parts.get("num"), parts.get("den")),
+        },
+        b"sSup" => format!("{}^{{{}}}", base(e), parts.get("sup")),
+        b"sSub" => format!("{}_{{{}}}", base(e), parts.get("sub")),
+        b"sSubSup" => format!(
+            "{}_{{{}}}^{{{}}}",
+            base(e),
+            parts.get("sub"),
+            parts.get("sup")
+        ),
+        b"sPre" => format!(
+            "{{}}_{{{}}}^{{{}}}{}",
+            parts.get("sub"),
+            parts.get("sup"),
+            base(e)
+        ),
+        b"rad" => {
+            let deg = parts.get("deg");
+            if parts.flag("degHide") || deg.is_empty() {
+                format!("\\sqrt{{{e}}}")
+            } else {
+                format!("\\sqrt[{deg}]{{{e}}}")
+            }
+        }
+        b"nary" => nary(parts),
+        b"d" => {
+            let open = parts.prop("begChr").unwrap_or("(");
+            let close = parts.prop("endChr").unwrap_or(")");
+            let sep = parts.prop("sepChr").unwrap_or("|");
+            let mut out = format!("\\left{}", delimiter(open));
+            push(&mut out, &parts.all("e").join(&escape(sep)));
+            out.push_str("\\right");
+            out.push_str(&delimiter(close));
+            out
+        }
+        b"func" => {
+            let mut out = parts.get("fName").to_string();
+            push(&mut out, e);
+            out
+        }
+        b"acc" => format!("\\{}{{{e}}}", accent(parts.prop("chr"))),
+        b"bar" => match parts.prop("pos") {
+            Some("top") => format!("\\overline{{{e}}}"),
+            _ => format!("\\underline{{{e}}}"),
+        },
+        b"groupChr" => {
+            let top = parts.prop("pos") == Some("top");
+            match parts.prop("chr").unwrap_or("\u{23DF}") {
+                "\u{23DE}" => format!("\\overbrace{{{e}}}"),
+                "\u{23DF}" => format!("\\underbrace{{{e}}}"),
+                chr if top => format!("\\overset{{{}}}{{{e}}}", escape(chr)),
+                chr => format!("\\underset{{{}}}{{{e}}}", escape(chr)),
+            }
+        }
+        b"limLow" => format!("{}_{{{}}}", base(e), parts.get("lim")),
+        b"limUpp" => format!("{}^{{{}}}", base(e), parts.get("lim")),
+        b"borderBox" => format!("\\boxed{{{e}}}"),
+        b"eqArr" => format!(
+            "\\begin{{aligned}}{}\\end{{aligned}}",
+            parts.all("e").join(" \\\\ ")
+        ),
+        b"m" => format!(
+            "\\begin{{matrix}}{}\\end{{matrix}}",
+            parts.all("mr").join(" \\\\ ")
+        ),
+        _ => e.to_string(),
+    }
+}
+
+/// An n-ary operator such as a sum or integral (the default), with its
+/// limits and operand.
+fn nary(parts: &Parts) -> String {
+    let mut out = escape(parts.prop("chr").unwrap_or("\u{222B}"));
+    let (sub, sup) = (parts.get("sub"), parts.get("sup"));
+    if !sub.is_empty() && !parts.flag("subHide") {
+        let _ = write!(out, "_{{{sub}}}");
+    }
+    if !sup.is_empty() && !parts.flag("supHide") {
+        let _ = write!(out, "^{{{sup}}}");
+    }
+    push(&mut out, parts.get("e"));
+    out
+}
+
+/// The accent command for combining character `chr`; a hat by default.
+fn accent(chr: Option<&str>) -> &'static str {
+    match chr.unwrap_or("\u{0302}") {
+        "\u{0303}" => "tilde",
+        "\u{0304}" | "\u{0305}" | "\u{00AF}" => "bar",
+        "\u{0307}" => "dot",
+        "\u{0308}" => "ddot",
+        "\u{20D7}" | "\u{2192}" => "vec",
+        "\u{0306}" => "breve",
+        "\u{030C}" => "check",
+        "\u{0301}" => "acute",
+        "\u{0300}" => "grave",
+        _ => "hat",
+    }
+}
+
+/// `latex` as the base of a script: in braces unless it is one symbol.
+fn base(latex: &str) -> String {
+    let single = latex.chars().count() == 1
+        || (latex.starts_with('\\')
+            && latex.len() > 1
+            && latex[1..].chars().all(|c| c.is_ascii_alphabetic()));
+    if single || latex.starts_with("\\left") && latex.ends_with(')') {
+        latex.to_string()
+    } else {
+        format!("{{{latex}}}")
+    }
+}
+
+/// A delimiter character for `\left` or `\right`; `.` for none.
+fn delimiter(chr: &str) -> String {
+    match chr {
+        "" => ".".into(),
+        "{" => "\\{".into(),
+        "}" => "\\}".into(),
+        "|" => "|".into(),
+        "\u{2016}" => "\\|".into(),
+        "\u{27E8}" | "\u{2329}" => "\\langle".into(),
+        "\u{27E9}" | "\u{232A}" => "\\rangle".into(),
+        "\u{230A}" => "\\lfloor".into(),
+        "\u{230B}" => "\\rfloor".into(),
+        "\u{2308}" => "\\lceil".into(),
+        "\u{2309}" => "\\rceil".into(),
+        other => other.to_string(),
+    }
+}
+
+/// Append `latex` to `out`, with a space between a command and a letter
+/// that would otherwise run into its name.
+fn push(out: &mut String, latex: &str) {
+    if latex.starts_with(|c: char| c.is_ascii_alphabetic()) && ends_with_command(out) {
+        out.push(' ');
+    }
+    out.push_str(latex);
+}
+
+/// Whether `s` ends with a command name such as `\alpha`.
+fn ends_with_command(s: &str) -> bool {
+    let name = s.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    name.len() < s.len() && name.ends_with('\\')
+}
+
+/// Math text with LaTeX's special characters escaped and symbols written
+/// as commands.
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match symbol(c) {
+            Some(command) => push(&mut out, command),
+            None if c.is_whitespace() => {}
+            None => {
+                let mut buf = [0; 4];
+                push(&mut out, c.encode_utf8(&mut buf));
+            }
+        }
+    }
+    out
+}
+
+/// Text for `\text{...}`, where only the special characters are escaped.
+fn escape_text(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '{' | '}' | '%' | '#' | '$' | '_' | '&' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\\' => out.push_str("\\textbackslash{}"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// The LaTeX for a character that is not written as itself in math.
+const fn symbol(c: char) -> Option<&'static str> {
+    Some(match c {
+        '{' => "\\{",
+        '}' => "\\}",
+        '%' => "\\%",
+        '#' => "\\#",
+        '$' => "\\$",
+        '_' => "\\_",
+        '\\' => "\\backslash",
+        '\u{2212}' => "-",
+        '\u{2217}' => "*",
+        'α' => "\\alpha",
+        'β' => "\\beta",
+        'γ' => "\\gamma",
+        'δ' => "\\delta",
+        'ε' | 'ϵ' => "\\epsilon",
+        'ζ' => "\\zeta",
+        'η' => "\\eta",
+        'θ' => "\\theta",
+        'ι' => "\\iota",
+        'κ' => "\\kappa",
+        'λ' => "\\lambda",
+        'μ' => "\\mu",
+        'ν' => "\\nu",
+        'ξ' => "\\xi",
+        'π' => "\\pi",
+        'ρ' => "\\rho",
+        'σ' => "\\sigma",
+        'τ' => "\\tau",
+        'υ' => "\\upsilon",
+        'φ' | 'ϕ' => "\\phi",
+        'χ' => "\\chi",
+        'ψ' => "\\psi",
+        'ω' => "\\omega",
+        'Γ' => "\\Gamma",
+        'Δ' => "\\Delta",
+        'Θ' => "\\Theta",
+        'Λ' => "\\Lambda",
+        'Ξ' => "\\Xi",
+        'Π' => "\\Pi",
+        'Σ' => "\\Sigma",
+        'Φ' => "\\Phi",
+        'Ψ' => "\\Psi",
+        'Ω' => "\\Omega",
+        '∑' => "\\sum",
+        '∏' => "\\prod",
+        '∐' => "\\coprod",
+        '∫' => "\\int",
+        '∬' => "\\iint",
+        '∭' => "\\iiint",
+        '∮' => "\\oint",
+        '⋃' => "\\bigcup",
+        '⋂' => "\\bigcap",
+        '±' => "\\pm",
+        '∓' => "\\mp",
+        '×' => "\\times",
+        '÷' => "\\div",
+        '⋅' | '·' => "\\cdot",
+        '≤' => "\\le",
+        '≥' => "\\ge",
+        '≠' => "\\ne",
+        '≈' => "\\approx",
+        '≡' => "\\equiv",
+        '∼' => "\\sim",
+        '∝' => "\\propto",
+        '∞' => "\\infty",
+        '∂' => "\\partial",
+        '∇' => "\\nabla",
+        '∈' => "\\in",
+        '∉' => "\\notin",
+        '⊂' => "\\subset",
+        '⊆' => "\\subseteq",
+        '∪' => "\\cup",
+        '∩' => "\\cap",
+        '∅' => "\\emptyset",
+        '∀' => "\\forall",
+        '∃' => "\\exists",
+        '¬' => "\\neg",
+        '∧' => "\\wedge",
+        '∨' => "\\vee",
+        '→' => "\\to",
+        '←' => "\\leftarrow",
+        '↔' => "\\leftrightarrow",
+        '⇒' => "\\Rightarrow",
+        '⇔' => "\\Leftrightarrow",
+        '…' => "\\ldots",
+        '⋯' => "\\cdots",
+        '√' => "\\surd",
+        '°' => "^{\\circ}",
+        '′' => "'",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Convert an `<m:oMath>` fragment.
+    fn latex(math: &str) -> String {
+        let xml = format!(
+            r#"<m:oMath xmlns:m="http://schemas.openxmlformats.org/officeDocument/2006/math">{math}</m:oMath>"#
+        );
+        let mut reader = Reader::from_str(&xml);
+        let _ = reader.read_event();
+        to_latex(&mut reader)
+    }
+
+    fn r(text: &str) -> String {
+        format!("<m:r><m:t>{text}</m:t></m:r>")
+    }
+
+    // ── structures ───────────────────────────────────────────────
+
+    #[test]
+    fn quadratic_formula() {
+        let math = format!(
+            "{}<m:f><m:num>{}<m:rad><m:radPr><m:degHide m:val=\"1\"/></m:radPr><m:deg/><m:e><m:sSup><m:e>{}</m:e><m:sup>{}</m:sup></m:sSup>{}</m:e></m:rad></m:num><m:den>{}</m:den></m:f>",
+            r("x="),
+            r("−b±"),
+            r("b"),
+            r("2"),
+            r("−4ac"),
+            r("2a"),
+        );
+        assert_eq!(latex(&math), "x=\\frac{-b\\pm\\sqrt{b^{2}-4ac}}{2a}");
+    }
+
+    #[test]
+    fn sums_functions_and_delimiters() {
+        let sum = format!(
+            "<m:nary><m:naryPr><m:chr m:val=\"∑\"/></m:naryPr><m:sub>{}</m:sub><m:sup>{}</m:sup><m:e><m:sSub><m:e>{}</m:e><m:sub>{}</m:sub></m:sSub></m:e></m:nary>",
+            r("i=1"),
+            r("n"),
+            r("x"),
+            r("i")
+        );
+        assert_eq!(latex(&sum), "\\sum_{i=1}^{n}x_{i}");
+
+        let func = format!(
+            "<m:func><m:fName>{}</m:fName><m:e><m:d><m:e>{}</m:e></m:d></m:e></m:func>",
+            r("sin"),
+            r("θ")
+        );
+        assert_eq!(latex(&func), "\\sin\\left(\\theta\\right)");
+
+        let set = format!(
+            "<m:d><m:dPr><m:begChr m:val=\"{{\"/><m:endChr m:val=\"}}\"/></m:dPr><m:e>{}</m:e><m:e>{}</m:e></m:d>",
+            r("a"),
+            r("b")
+        );
+        assert_eq!(latex(&set), "\\left\\{a|b\\right\\}");
+    }
+
+    #[test]
+    fn matrices_and_accents() {
+        let matrix = format!(
+            "<m:m><m:mr><m:e>{}</m:e><m:e>{}</m:e></m:mr><m:mr><m:e>{}</m:e><m:e>{}</m:e></m:mr></m:m>",
+            r("1"),
+            r("0"),
+            r("0"),
+            r("1")
+        );
+        assert_eq!(
+            latex(&matrix),
+            format!("\\begin{{{0}}}1 & 0 \\\\ 0 & 1\\end{{{0}}}", "matrix")
+        );
+        let accent = format!(
+            "<m:acc><m:accPr><m:chr m:val=\"\u{20D7}\"/></m:accPr><m:e>{}</m:e></m:acc>",
+            r("v")
+        );
+        assert_eq!(latex(&accent), "\\vec{v}");
+    }
+
+    // ── runs ─────────────────────────────────────────────────────
+
+    #[test]
+    fn symbols_and_normal_text() {
+        assert_eq!(latex(&r("αx ≤ 100%")), "\\alpha x\\le100\\%");
+        assert_eq!(
+            latex("<m:r><m:rPr><m:nor/></m:rPr><m:t>if x &gt; 0</m:t></m:r>"),
+            "\\text{if x > 0}"
+        );
+    }
+}
@@ -0,0 +1,591 @@
+//! Extraction options shared by the format parsers.
+//!
+//! `main.rs` builds an `Options` for each input from the defaults, the config
+//! file, and the command line, and passes it down to each format's
+//! `extract_*` function, so new knobs don't require touching every
+//! signature.
+
+use std::sync::{Arc, Mutex};
+
+use crate::cancel::CancelToken;
+use crate::progress::Progress;
+
+/// Knobs that influence how documents are extracted and rendered.
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)] // independent toggles, not a state machine
+pub(crate) struct Options {
+    /// Embed images as base64 data URIs in markdown output.
+    pub(crate) images: bool,
+    /// Join hard-wrapped lines into paragraphs before markdown inference
+    /// (`.doc` and PDF only).
+    pub(crate) reflow: bool,
+    /// Draw tables in plain output with box-drawing characters instead of
+    /// tab-separated rows.
+    pub(crate) table: bool,
+    /// Escape text that a markdown renderer would take for raw HTML.
+    pub(crate) escape_html: bool,
+    /// Crop `.xls` sheets to their print area, when one is defined.
+    pub(crate) print_area: bool,
+    /// Include hidden sheets, and the hidden rows and columns of `.xlsx`
+    /// sheets, instead of leaving them out.
+    pub(crate) hidden: bool,
+    /// Put the name of each `.docx` content control before its text.
+    pub(crate) control_labels: bool,
+    /// Extract the documents embedded in a `.docx` (`--embedded`) after
+    /// the placeholder that names them.
+    pub(crate) embedded: bool,
+    /// PDF pages to extract (`--pages`); all pages if `None`.
+    pub(crate) pages: Option<PageRanges>,
+    /// Spreadsheet sheet to extract (`--sheet`), by name or 1-based
+    /// index; all sheets if `None`.
+    pub(crate) sheet: Option<String>,
+    /// Sheets (by 1-based position) or slides (by number) chosen in the
+    /// `--pick` picker; all if `None`.
+    pub(crate) picked: Option<Vec<usize>>,
+    /// Password of an encrypted `.doc` or `.xls` file (`--password`).
+    /// Encrypted OOXML files are decrypted before they reach a parser.
+    pub(crate) password: Option<String>,
+    /// Windows codepage for 8-bit text in `.doc` and `.xls` files
+    /// (`--encoding`), replacing the one declared or guessed from the
+    /// document's language.
+    pub(crate) encoding: Option<u16>,
+    /// What line breaks inside a spreadsheet cell become in TSV output.
+    pub(crate) cell_newline: CellNewline,
+    /// Where `.docx` and `.xlsx` comments are shown, if at all
+    /// (`--comments`).
+    pub(crate) comments: Comments,
+    /// How `.docx` tracked changes are shown (`--revisions`).
+    pub(crate) revisions: Revisions,
+    /// How struck-through, underlined, and highlighted `.docx` text is
+    /// marked in markdown (`--marks`).
+    pub(crate) marks: Marks,
+    /// Line written at `.docx` page and section breaks (`--page-breaks`);
+    /// not marked if `None`.
+    pub(crate) page_breaks: Option<String>,
+    /// Rows and columns of each sheet shown at most (`--max-rows`,
+    /// `--max-cols`); all of them if `None`.
+    pub(crate) max_rows: Option<usize>,
+    pub(crate) max_cols: Option<usize>,
+    /// Text after which parsers may stop (`--preview`, `--head`); the CLI
+    /// cuts the output to it, so the rest would be thrown away.
+    pub(crate) budget: Budget,
+    /// Checked by long-running parse loops; never cancelled from the CLI.
+    pub(crate) cancel: CancelToken,
+    /// Told of each sheet or page as spreadsheet and PDF parsers reach it.
+    pub(crate) progress: Progress,
+    /// Problems the parser worked around, reported by the CLI.
+    pub(crate) warnings: Messages,
+    /// How the parser read the document, such as the codepage it chose
+    /// and the parts it skipped; shown by the CLI with `-vv`.
+    pub(crate) notes: Messages,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            images: false,
+            reflow: true,
+            table: false,
+            escape_html: false,
+            print_area: true,
+            hidden: false,
+            control_labels: false,
+            embedded: false,
+            pages: None,
+            sheet: None,
+            picked: None,
+            password: None,
+            encoding: None,
+            cell_newline: CellNewline::Escape,
+            comments: Comments::None,
+            revisions: Revisions::Final,
+            marks: Marks::Extended,
+            page_breaks: None,
+            max_rows: None,
+            max_cols: None,
+            budget: Budget::default(),
+            cancel: CancelToken::default(),
+            progress: Progress::default(),
+            warnings: Messages::default(),
+            notes: Messages::default(),
+        }
+    }
+}
+
+/// Option values set explicitly by one layer of configuration (a config
+/// file section or the command line). `None` keeps the value from the
+/// layer below.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Overrides {
+    pub(crate) images: Option<bool>,
+    pub(crate) reflow: Option<bool>,
+    pub(crate) table: Option<bool>,
+    pub(crate) escape_html: Option<bool>,
+    pub(crate) print_area: Option<bool>,
+    pub(crate) hidden: Option<bool>,
+    pub(crate) control_labels: Option<bool>,
+    pub(crate) embedded: Option<bool>,
+    pub(crate) cell_newline: Option<CellNewline>,
+    pub(crate) comments: Option<Comments>,
+    pub(crate) revisions: Option<Revisions>,
+    pub(crate) marks: Option<Marks>,
+    pub(crate) page_breaks: Option<String>,
+    pub(crate) max_rows: Option<usize>,
+    pub(crate) max_cols: Option<usize>,
+}
+
+impl Overrides {
+    /// Set an option by its config file key.
+    pub(crate) fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let slot = match key {
+            "images" => &mut self.images,
+            "reflow" => &mut self.reflow,
+            "table" => &mut self.table,
+            "escape_html" => &mut self.escape_html,
+            "print_area" => &mut self.print_area,
+            "hidden" => &mut self.hidden,
+            "control_labels" => &mut self.control_labels,
+            "embedded" => &mut self.embedded,
+            "cell_newline" => {
+                self.cell_newline = Some(
+                    CellNewline::parse(value)
+                        .ok_or_else(|| format!("{key} must be escape or space, not '{value}'"))?,
+                );
+                return Ok(());
+            }
+            "comments" => {
+                self.comments =
+                    Some(Comments::parse(value).ok_or_else(|| {
+                        format!("{key} must be none, inline, or end, not '{value}'")
+                    })?);
+                return Ok(());
+            }
+            "revisions" => {
+                self.revisions = Some(Revisions::parse(value).ok_or_else(|| {
+                    format!("{key} must be final, original, or marked, not '{value}'")
+                })?);
+                return Ok(());
+            }
+            "page_breaks" => {
+                self.page_breaks = Some(value.to_string());
+                return Ok(());
+            }
+            "marks" => {
+                self.marks = Some(Marks::parse(value).ok_or_else(|| {
+                    format!("{key} must be extended, emphasis, or none, not '{value}'")
+                })?);
+                return Ok(());
+            }
+            "max_rows" | "max_cols" => {
+                let limit = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("{key} must be a positive number, not '{value}'"))?;
+                if key == "max_rows" {
+                    self.max_rows = Some(limit);
+                } else {
+                    self.max_cols = Some(limit);
+                }
+                return Ok(());
+            }
+            _ => return Err(format!("unknown option '{key}'")),
+        };
+        *slot = Some(parse_bool(key, value)?);
+        Ok(())
+    }
+
+    /// Apply the values set in this layer on top of `opts`.
+    pub(crate) fn apply(&self, opts: &mut Options) {
+        if let Some(v) = self.images {
+            opts.images = v;
+        }
+        if let Some(v) = self.reflow {
+            opts.reflow = v;
+        }
+        if let Some(v) = self.table {
+            opts.table = v;
+        }
+        if let Some(v) = self.escape_html {
+            opts.escape_html = v;
+        }
+        if let Some(v) = self.print_area {
+            opts.print_area = v;
+        }
+        if let Some(v) = self.hidden {
+            opts.hidden = v;
+        }
+        if let Some(v) = self.control_labels {
+            opts.control_labels = v;
+        }
+        if let Some(v) = self.embedded {
+            opts.embedded = v;
+        }
+        if let Some(v) = self.cell_newline {
+            opts.cell_newline = v;
+        }
+        if let Some(v) = self.comments {
+            opts.comments = v;
+        }
+        if let Some(v) = self.revisions {
+            opts.revisions = v;
+        }
+        if let Some(v) = self.marks {
+            opts.marks = v;
+        }
+        if let Some(v) = &self.page_breaks {
+            opts.page_breaks = Some(v.clone());
+        }
+        if let Some(v) = self.max_rows {
+            opts.max_rows = Some(v);
+        }
+        if let Some(v) = self.max_cols {
+            opts.max_cols = Some(v);
+        }
+    }
+}
+
+/// Replacement for line breaks inside a spreadsheet cell in tab-separated
+/// output, so that each output line stays one row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CellNewline {
+    /// A literal backslash-n (`\n`), reversible by downstream tools.
+    Escape,
+    /// A single space.
+    Space,
+}
+
+impl CellNewline {
+    /// Parse a `--cell-newline` / `cell_newline` value.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "escape" => Some(Self::Escape),
+            "space" => Some(Self::Space),
+            _ => None,
+        }
+    }
+
+    /// The text that replaces each line break.
+    pub(crate) const fn replacement(self) -> &'static str {
+        match self {
+            Self::Escape => "\\n",
+            Self::Space => " ",
+        }
+    }
+}
+
+/// Where the comments of a `.docx` or `.xlsx` are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Comments {
+    /// Left out, as before comments were read.
+    None,
+    /// After the paragraph they are anchored to.
+    Inline,
+    /// Numbered in a section at the end, with a `[cN]` mark in the text.
+    End,
+}
+
+impl Comments {
+    /// Parse a `--comments` / `comments` value.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "none" => Some(Self::None),
+            "inline" => Some(Self::Inline),
+            "end" => Some(Self::End),
+            _ => None,
+        }
+    }
+}
+
+/// How the tracked changes of a `.docx` are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Revisions {
+    /// The text with all changes accepted.
+    Final,
+    /// The text with all changes rejected.
+    Original,
+    /// Both, with deletions as `[-…-]` and insertions as `{+…+}`.
+    Marked,
+}
+
+impl Revisions {
+    /// Parse a `--revisions` / `revisions` value.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "final" => Some(Self::Final),
+            "original" => Some(Self::Original),
+            "marked" => Some(Self::Marked),
+            _ => None,
+        }
+    }
+}
+
+/// How struck-through, underlined, and highlighted text is marked in
+/// markdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Marks {
+    /// `~~struck~~`, `<u>underlined</u>`, and `==highlighted==`.
+    Extended,
+    /// Only what plain markdown has: `~~struck~~`, with underlined text
+    /// as `*emphasis*` and highlighted text as `**strong**`.
+    Emphasis,
+    /// Not marked, as before these were read.
+    None,
+}
+
+impl Marks {
+    /// Parse a `--marks` / `marks` value.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "extended" => Some(Self::Extended),
+            "emphasis" => Some(Self::Emphasis),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+}
+
+/// Messages raised while extracting one document: warnings such as text
+/// that was recovered from a damaged file, or notes on how it was read.
+/// Clones share the list.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Messages(Arc<Mutex<Vec<String>>>);
+
+impl Messages {
+    /// Record a message.
+    pub(crate) fn push(&self, message: impl Into<String>) {
+        if let Ok(mut list) = self.0.lock() {
+            list.push(message.into());
+        }
+    }
+
+    /// The messages recorded so far, leaving the list empty.
+    pub(crate) fn take(&self) -> Vec<String> {
+        self.0
+            .lock()
+            .map(|mut list| std::mem::take(&mut *list))
+            .unwrap_or_default()
+    }
+}
+
+/// How much more text a parser must extract before it can stop, in bytes
+/// and in lines of output. Unlimited by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Budget {
+    bytes: Option<usize>,
+    lines: Option<usize>,
+}
+
+impl Budget {
+    pub(crate) const fn new(bytes: Option<usize>, lines: Option<usize>) -> Self {
+        Self { bytes, lines }
+    }
+
+    /// Count `bytes` of extracted text that make at least `lines` lines
+    /// of output.
+    pub(crate) const fn spend(&mut self, bytes: usize, lines: usize) {
+        if let Some(left) = &mut self.bytes {
+            *left = left.saturating_sub(bytes);
+        }
+        if let Some(left) = &mut self.lines {
+            *left = left.saturating_sub(lines);
+        }
+    }
+
+    /// Whether enough text has been extracted to stop parsing.
+    pub(crate) const fn spent(self) -> bool {
+        matches!(self.bytes, Some(0)) || matches!(self.lines, Some(0))
+    }
+}
+
+/// A set of 1-based page numbers such as `2-5,9,12-`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PageRanges(Vec<(usize, Option<usize>)>);
+
+impl PageRanges {
+    /// Parse a comma-separated list of `N`, `N-M`, and open-ended `N-`.
+    pub(crate) fn parse(spec: &str) -> Result<Self, String> {
+        let invalid = |part: &str| format!("invalid page range '{part}'");
+        let number = |s: &str, part: &str| match s.trim().parse::<usize>() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(invalid(part)),
+        };
+        let mut ranges = Vec::new();
+        for part in spec.split(',') {
+            let range = match part.split_once('-') {
+                None => (number(part, part)?, Some(number(part, part)?)),
+                Some((start, end)) if end.trim().is_empty() => (number(start, part)?, None),
+                Some((start, end)) => (number(start, part)?, Some(number(end, part)?)),
+            };
+            if range.1.is_some_and(|end| end < range.0) {
+                return Err(invalid(part));
+            }
+            ranges.push(range);
+        }
+        Ok(Self(ranges))
+    }
+
+    /// Whether page `n` (1-based) is selected.
+    pub(crate) fn contains(&self, n: usize) -> bool {
+        self.0
+            .iter()
+            .any(|&(start, end)| n >= start && end.is_none_or(|end| n <= end))
+    }
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("{key} must be true or false, not '{value}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── Overrides ────────────────────────────────────────────────
+
+    #[test]
+    fn set_and_apply() {
+        let mut o = Overrides::default();
+        o.set("table", "true").unwrap();
+        o.set("reflow", "false").unwrap();
+
+        let mut opts = Options::default();
+        o.apply(&mut opts);
+        assert!(opts.table);
+        assert!(!opts.reflow);
+        assert!(!opts.images);
+    }
+
+    #[test]
+    fn unset_keeps_lower_layer() {
+        let mut opts = Options {
+            images: true,
+            ..Options::default()
+        };
+        Overrides::default().apply(&mut opts);
+        assert!(opts.images);
+    }
+
+    #[test]
+    fn set_rejects_bad_input() {
+        let mut o = Overrides::default();
+        assert!(o.set("colour", "true").unwrap_err().contains("'colour'"));
+        assert!(o.set("table", "yes").unwrap_err().contains("true or false"));
+        assert!(o
+            .set("cell_newline", "tab")
+            .unwrap_err()
+            .contains("escape or space"));
+    }
+
+    #[test]
+    fn cell_newline_key() {
+        let mut o = Overrides::default();
+        o.set("cell_newline", "space").unwrap();
+        let mut opts = Options::default();
+        assert_eq!(opts.cell_newline, CellNewline::Escape);
+        o.apply(&mut opts);
+        assert_eq!(opts.cell_newline, CellNewline::Space);
+    }
+
+    #[test]
+    fn comments_key() {
+        let mut o = Overrides::default();
+        o.set("comments", "end").unwrap();
+        let mut opts = Options::default();
+        assert_eq!(opts.comments, Comments::None);
+        o.apply(&mut opts);
+        assert_eq!(opts.comments, Comments::End);
+        assert!(o
+            .set("comments", "margin")
+            .unwrap_err()
+            .contains("none, inline, or end"));
+    }
+
+    #[test]
+    fn revisions_key() {
+        let mut o = Overrides::default();
+        o.set("revisions", "marked").unwrap();
+        let mut opts = Options::default();
+        assert_eq!(opts.revisions, Revisions::Final);
+        o.apply(&mut opts);
+        assert_eq!(opts.revisions, Revisions::Marked);
+        assert!(o.set("revisions", "all").is_err());
+    }
+
+    #[test]
+    fn marks_key() {
+        let mut o = Overrides::default();
+        o.set("marks", "emphasis").unwrap();
+        let mut opts = Options::default();
+        assert_eq!(opts.marks, Marks::Extended);
+        o.apply(&mut opts);
+        assert_eq!(opts.marks, Marks::Emphasis);
+        assert!(o.set("marks", "html").is_err());
+    }
+
+    #[test]
+    fn page_breaks_key() {
+        let mut o = Overrides::default();
+        o.set("page_breaks", "* * *").unwrap();
+        let mut opts = Options::default();
+        assert_eq!(opts.page_breaks, None);
+        o.apply(&mut opts);
+        assert_eq!(opts.page_breaks.as_deref(), Some("* * *"));
+    }
+
+    #[test]
+    fn max_rows_and_cols_keys() {
+        let mut o = Overrides::default();
+        o.set("max_rows", "200").unwrap();
+        o.set("max_cols", "8").unwrap();
+        let mut opts = Options::default();
+        o.apply(&mut opts);
+        assert_eq!((opts.max_rows, opts.max_cols), (Some(200), Some(8)));
+        assert!(o
+            .set("max_rows", "0")
+            .unwrap_err()
+            .contains("positive number"));
+    }
+
+    // ── Budget ───────────────────────────────────────────────────
+
+    #[test]
+    fn budget() {
+        let mut unlimited = Budget::default();
+        unlimited.spend(usize::MAX, usize::MAX);
+        assert!(!unlimited.spent());
+
+        let mut bytes = Budget::new(Some(10), None);
+        bytes.spend(6, 1);
+        assert!(!bytes.spent());
+        bytes.spend(6, 1);
+        assert!(bytes.spent());
+
+        let mut lines = Budget::new(None, Some(2));
+        lines.spend(100, 1);
+        assert!(!lines.spent());
+        lines.spend(0, 1);
+        assert!(lines.spent());
+    }
+
+    // ── PageRanges ───────────────────────────────────────────────
+
+    #[test]
+    fn page_ranges() {
+        let pages = PageRanges::parse("2-4, 9,12-").unwrap();
+        let selected: Vec<usize> = (1..=14).filter(|&n| pages.contains(n)).collect();
+        assert_eq!(selected, vec![2, 3, 4, 9, 12, 13, 14]);
+    }
+
+    #[test]
+    fn page_ranges_rejects_bad_input() {
+        for spec in ["", "0", "3-1", "a", "1,,2", "-3"] {
+            let err = PageRanges::parse(spec).unwrap_err();
+            assert!(err.starts_with("invalid page range"), "{spec}: {err}");
+        }
+    }
+}
@@ -0,0 +1,659 @@
+//! Org-mode output.
+//!
+//! Every format already renders to markdown, so rather than teach each
+//! renderer a second markup language, this module converts that markdown
+//! into Emacs Org-mode: `*` headings, `|---+---|` table rules,
+//! `[[url][text]]` links, `*bold*` / `/italic/` emphasis, `+struck+` and
+//! `_underlined_` text, `H_{2}O` / `x^{2}` scripts, `<<name>>` bookmark
+//! targets and the `[[name][text]]` links to them, `~code~` (`=code=`
+//! when it holds a tilde), `#+BEGIN_SRC` code blocks, and `#+BEGIN_QUOTE`
+//! blocks. Highlighting and reference-style image definitions have no Org
+//! equivalent: the former is dropped and the latter are resolved inline.
+
+use std::collections::HashMap;
+
+/// Convert rendered markdown into Org-mode markup.
+pub(crate) fn markdown_to_org(md: &str) -> String {
+    let refs = collect_reference_defs(md);
+    let mut out = String::with_capacity(md.len());
+    let mut in_quote = false;
+    // Backticks of the fence of the code block being copied, if in one
+    let mut fence: Option<usize> = None;
+
+    for line in md.lines() {
+        if let Some(ticks) = fence {
+            if closes_fence(line, ticks) {
+                out.push_str("#+END_SRC\n");
+                fence = None;
+            } else {
+                push_src_line(line, &mut out);
+            }
+            continue;
+        }
+        if parse_reference_def(line).is_some() {
+            continue;
+        }
+
+        let quoted = line.strip_prefix('>').map(str::trim_start);
+        match (quoted, in_quote) {
+            (Some(_), false) => {
+                out.push_str("#+BEGIN_QUOTE\n");
+                in_quote = true;
+            }
+            (None, true) => {
+                out.push_str("#+END_QUOTE\n");
+                in_quote = false;
+            }
+            _ => {}
+        }
+        let line = quoted.unwrap_or(line);
+
+        if let Some((ticks, info)) = parse_fence(line).filter(|_| !in_quote) {
+            out.push_str("#+BEGIN_SRC");
+            if let Some(lang) = info.split_whitespace().next() {
+                out.push(' ');
+                out.push_str(lang);
+            }
+            out.push('\n');
+            fence = Some(ticks);
+            continue;
+        }
+
+        if let Some((level, text)) = parse_heading(line) {
+            out.push_str(&"*".repeat(level));
+            out.push(' ');
+            out.push_str(&convert_inline(text, &refs));
+        } else if line.trim_start().starts_with('|') {
+            out.push_str(&convert_table_line(line, &refs));
+        } else {
+            out.push_str(&convert_inline(line, &refs));
+        }
+        out.push('\n');
+    }
+
+    if in_quote {
+        out.push_str("#+END_QUOTE\n");
+    }
+    if fence.is_some() {
+        out.push_str("#+END_SRC\n");
+    }
+
+    out
+}
+
+/// Parse the opening fence of a fenced code block (```` ```lang ````) into
+/// its number of backticks and its info string.
+fn parse_fence(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim_start();
+    let ticks = line.bytes().take_while(|&b| b == b'`').count();
+    let info = &line[ticks..];
+    // A backtick after the fence makes the line a code span instead
+    (ticks >= 3 && !info.contains('`')).then_some((ticks, info.trim()))
+}
+
+/// Whether `line` closes a code block opened by `ticks` backticks: a run
+/// of at least as many, alone on the line.
+fn closes_fence(line: &str, ticks: usize) -> bool {
+    let line = line.trim();
+    line.len() >= ticks && line.bytes().all(|b| b == b'`')
+}
+
+/// Copy a line of code into a `#+BEGIN_SRC` block as it is, but for the
+/// comma Org puts before a `*` or `#+` that would otherwise start a
+/// heading or keyword.
+fn push_src_line(line: &str, out: &mut String) {
+    let code = line.trim_start_matches([' ', '\t']);
+    let indent = &line[..line.len() - code.len()];
+    let bare = code.trim_start_matches(',');
+    out.push_str(indent);
+    if bare.starts_with('*') || bare.starts_with("#+") {
+        out.push(',');
+    }
+    out.push_str(code);
+    out.push('\n');
+}
+
+/// Parse an ATX heading (`## Title`) into its level and text.
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.bytes().take_while(|&b| b == b'#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    line[level..].strip_prefix(' ').map(|text| (level, text))
+}
+
+/// Convert one markdown table line. Separator rows (`| --- | --- |`) become
+/// Org rules (`|---+---|`); content rows get inline conversion per cell.
+fn convert_table_line(line: &str, refs: &HashMap<String, String>) -> String {
+    let cells = split_table_cells(line);
+
+    let is_rule = !cells.is_empty()
+        && cells.iter().all(|c| {
+            let c = c.trim().trim_matches(':');
+            !c.is_empty() && c.chars().all(|ch| ch == '-')
+        });
+    if is_rule {
+        return format!("|{}|", vec!["---"; cells.len()].join("+"));
+    }
+
+    let mut out = String::from("|");
+    for cell in &cells {
+        // Org has no way to escape a pipe inside a cell; use the entity.
+        let text = cell.trim().replace("\\|", "\\vert{}");
+        out.push(' ');
+        out.push_str(&convert_inline(&text, refs));
+        out.push_str(" |");
+    }
+    out
+}
+
+/// Split a markdown table row into cells, honouring `\|` escapes.
+fn split_table_cells(line: &str) -> Vec<String> {
+    let inner = line.trim();
+    let inner = inner.strip_prefix('|').unwrap_or(inner);
+    let inner = inner.strip_suffix('|').unwrap_or(inner);
+
+    let mut cells = Vec::new();
+    let mut cur = String::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cur.push_str("\\|");
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut cur)),
+            _ => cur.push(c),
+        }
+    }
+    cells.push(cur);
+    cells
+}
+
+/// Collect `[id]: <url>` reference definitions (used for embedded images).
+fn collect_reference_defs(md: &str) -> HashMap<String, String> {
+    md.lines()
+        .filter_map(parse_reference_def)
+        .map(|(id, url)| (id.to_string(), url.to_string()))
+        .collect()
+}
+
+/// Parse a `[id]: <url>` or `[id]: url` line.
+fn parse_reference_def(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (id, rest) = rest.split_once("]: ")?;
+    if id.is_empty() || id.contains(']') {
+        return None;
+    }
+    let url = rest.trim();
+    let url = url
+        .strip_prefix('<')
+        .and_then(|u| u.strip_suffix('>'))
+        .unwrap_or(url);
+    Some((id, url))
+}
+
+/// Convert inline markdown (links, images, emphasis, code spans) to Org.
+fn convert_inline(text: &str, refs: &HashMap<String, String>) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '!' if chars.get(i + 1) == Some(&'[') => {
+                if let Some((label, target, end)) = parse_link(&chars, i + 1) {
+                    let url = match target {
+                        LinkTarget::Inline(url) => Some(url),
+                        LinkTarget::Reference(id) => refs.get(&id).cloned(),
+                    };
+                    if let Some(url) = url {
+                        out.push_str("[[");
+                        out.push_str(&url);
+                        out.push_str("]]");
+                    } else {
+                        out.push_str(&label);
+                    }
+                    i = end;
+                    continue;
+                }
+                out.push('!');
+                i += 1;
+            }
+            '[' => {
+                if let Some((label, LinkTarget::Inline(url), end)) = parse_link(&chars, i) {
+                    // `#name` points at an anchor, which is a `<<name>>` target
+                    out.push_str("[[");
+                    out.push_str(url.strip_prefix('#').unwrap_or(&url));
+                    out.push_str("][");
+                    out.push_str(&convert_inline(&label, refs));
+                    out.push_str("]]");
+                    i = end;
+                    continue;
+                }
+                out.push('[');
+                i += 1;
+            }
+            '`' => {
+                let run = chars[i..].iter().take_while(|&&c| c == '`').count();
+                if let Some(close) = find_code_close(&chars, i + run, run) {
+                    push_code(&chars[i + run..close], &mut out);
+                    i = close + run;
+                    continue;
+                }
+                out.extend(&chars[i..i + run]);
+                i += run;
+            }
+            '~' | '=' | '^' | '<' => {
+                if let Some(end) = convert_mark(&chars, i, refs, &mut out) {
+                    i = end;
+                    continue;
+                }
+                out.push(chars[i]);
+                i += 1;
+            }
+            '*' => {
+                let run = chars[i..].iter().take_while(|&&c| c == '*').count().min(3);
+                if let Some(close) = find_emphasis_close(&chars, i + run, '*', run) {
+                    let inner: String = chars[i + run..close].iter().collect();
+                    let inner = convert_inline(&inner, refs);
+                    match run {
+                        1 => {
+                            out.push('/');
+                            out.push_str(&inner);
+                            out.push('/');
+                        }
+                        2 => {
+                            out.push('*');
+                            out.push_str(&inner);
+                            out.push('*');
+                        }
+                        _ => {
+                            out.push_str("*/");
+                            out.push_str(&inner);
+                            out.push_str("/*");
+                        }
+                    }
+                    i = close + run;
+                    continue;
+                }
+                for _ in 0..run {
+                    out.push('*');
+                }
+                i += run;
+            }
+            '\\' if matches!(chars.get(i + 1), Some('*' | '_' | '[' | ']' | '`')) => {
+                out.push(chars[i + 1]);
+                i += 2;
+            }
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
+
+/// Convert the strikethrough, highlight, underline, or sub- or superscript
+/// mark opening at `i`, returning the index just past it. A doubled marker
+/// that opens nothing is copied as it is, so neither half is taken for a
+/// script.
+fn convert_mark(
+    chars: &[char],
+    i: usize,
+    refs: &HashMap<String, String>,
+    out: &mut String,
+) -> Option<usize> {
+    let marker = chars[i];
+    match marker {
+        // `~~struck~~` and `==highlighted==`
+        '~' | '=' if chars.get(i + 1) == Some(&marker) => {
+            let Some(close) = find_emphasis_close(chars, i + 2, marker, 2) else {
+                out.push(marker);
+                out.push(marker);
+                return Some(i + 2);
+            };
+            let inner: String = chars[i + 2..close].iter().collect();
+            let inner = convert_inline(&inner, refs);
+            if marker == '~' {
+                out.push('+');
+                out.push_str(&inner);
+                out.push('+');
+            } else {
+                out.push_str(&inner);
+            }
+            Some(close + 2)
+        }
+        // Pandoc's `~sub~` and `^super^`, spaces inside escaped
+        '~' | '^' => {
+            let close = find_script_close(chars, i + 1, marker)?;
+            let inner: String = chars[i + 1..close].iter().collect();
+            out.push(if marker == '~' { '_' } else { '^' });
+            out.push('{');
+            out.push_str(&inner.replace("\\ ", " "));
+            out.push('}');
+            Some(close + 1)
+        }
+        // Bookmark anchors, `<a id="name"></a>`
+        '<' if starts_with(&chars[i..], "<a id=\"") => {
+            let close = find_str(chars, i + 7, "\"></a>")?;
+            out.push_str("<<");
+            out.extend(&chars[i + 7..close]);
+            out.push_str(">>");
+            Some(close + 6)
+        }
+        '<' if starts_with(&chars[i..], "<u>") => {
+            let close = find_str(chars, i + 3, "</u>")?;
+            let inner: String = chars[i + 3..close].iter().collect();
+            out.push('_');
+            out.push_str(&convert_inline(&inner, refs));
+            out.push('_');
+            Some(close + 4)
+        }
+        _ => None,
+    }
+}
+
+enum LinkTarget {
+    /// `[text](url)`
+    Inline(String),
+    /// `[text][id]`
+    Reference(String),
+}
+
+/// Parse `[label](url)` or `[label][id]` starting at the `[` at `start`.
+/// Returns the label, target, and the index just past the link.
+fn parse_link(chars: &[char], start: usize) -> Option<(String, LinkTarget, usize)> {
+    let mut depth = 0;
+    let mut label_end = None;
+    for (j, &c) in chars.iter().enumerate().skip(start) {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    label_end = Some(j);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let label_end = label_end?;
+    let label: String = chars[start + 1..label_end].iter().collect();
+
+    let (open, close) = match chars.get(label_end + 1)? {
+        '(' => ('(', ')'),
+        '[' => ('[', ']'),
+        _ => return None,
+    };
+    let target_start = label_end + 2;
+    let target_len = chars[target_start..].iter().position(|&c| c == close)?;
+    let target: String = chars[target_start..target_start + target_len]
+        .iter()
+        .collect();
+    let end = target_start + target_len + 1;
+
+    let target = if open == '(' {
+        LinkTarget::Inline(target)
+    } else {
+        LinkTarget::Reference(target)
+    };
+    Some((label, target, end))
+}
+
+/// Find the closing emphasis run of exactly `run` `marker`s, starting the
+/// search at `from`. The opening run must be followed by a non-space and the
+/// closing run preceded by one, so `2 * 3 * 4` is left alone.
+fn find_emphasis_close(chars: &[char], from: usize, marker: char, run: usize) -> Option<usize> {
+    if chars.get(from).is_none_or(|c| c.is_whitespace()) {
+        return None;
+    }
+    let mut j = from + 1;
+    while j + run <= chars.len() {
+        if chars[j..j + run].iter().all(|&c| c == marker)
+            && chars.get(j + run) != Some(&marker)
+            && !chars[j - 1].is_whitespace()
+        {
+            return Some(j);
+        }
+        j += 1;
+    }
+    None
+}
+
+/// Find the run of exactly `run` backticks closing a code span whose
+/// content starts at `from`.
+fn find_code_close(chars: &[char], from: usize, run: usize) -> Option<usize> {
+    let mut j = from;
+    while j < chars.len() {
+        let ticks = chars[j..].iter().take_while(|&&c| c == '`').count();
+        if ticks == run {
+            return Some(j);
+        }
+        j += ticks.max(1);
+    }
+    None
+}
+
+/// Append a code span's content as Org code: `~code~`, or `=code=` when it
+/// holds a tilde. Markdown pads content that starts or ends with a
+/// backtick with a space on each side; the padding is dropped.
+fn push_code(code: &[char], out: &mut String) {
+    let padded = code.len() > 2
+        && code.first() == Some(&' ')
+        && code.last() == Some(&' ')
+        && code.iter().any(|&c| c != ' ');
+    let code = if padded {
+        &code[1..code.len() - 1]
+    } else {
+        code
+    };
+    let marker = if code.contains(&'~') { '=' } else { '~' };
+    out.push(marker);
+    out.extend(code);
+    out.push(marker);
+}
+
+/// Find the `marker` closing a sub- or superscript opened just before
+/// `from`. The script must not be empty, and any space in it must be
+/// escaped, so `~5 min ~10 min` is left alone.
+fn find_script_close(chars: &[char], from: usize, marker: char) -> Option<usize> {
+    let mut j = from;
+    while let Some(&c) = chars.get(j) {
+        match c {
+            '\\' if chars.get(j + 1) == Some(&' ') => j += 2,
+            c if c == marker => return (j > from).then_some(j),
+            c if c.is_whitespace() => return None,
+            _ => j += 1,
+        }
+    }
+    None
+}
+
+fn starts_with(chars: &[char], pat: &str) -> bool {
+    chars
+        .iter()
+        .copied()
+        .take(pat.chars().count())
+        .eq(pat.chars())
+}
+
+/// Index of the first `pat` at or after `from`.
+fn find_str(chars: &[char], from: usize, pat: &str) -> Option<usize> {
+    (from..chars.len()).find(|&j| starts_with(&chars[j..], pat))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inline(s: &str) -> String {
+        convert_inline(s, &HashMap::new())
+    }
+
+    // ── headings ─────────────────────────────────────────────────
+
+    #[test]
+    fn heading_levels() {
+        assert_eq!(markdown_to_org("# Title\n## Sub\n"), "* Title\n** Sub\n");
+    }
+
+    #[test]
+    fn hash_without_space_not_heading() {
+        assert_eq!(markdown_to_org("#hashtag\n"), "#hashtag\n");
+    }
+
+    // ── tables ───────────────────────────────────────────────────
+
+    #[test]
+    fn table_rule_converted() {
+        let md = "| A | B |\n| --- | --- |\n| 1 | 2 |\n";
+        assert_eq!(markdown_to_org(md), "| A | B |\n|---+---|\n| 1 | 2 |\n");
+    }
+
+    #[test]
+    fn table_escaped_pipe() {
+        let md = "| A\\|B | C |\n";
+        assert_eq!(markdown_to_org(md), "| A\\vert{}B | C |\n");
+    }
+
+    // ── inline ───────────────────────────────────────────────────
+
+    #[test]
+    fn link_converted() {
+        assert_eq!(
+            inline("see [docs](https://example.com) now"),
+            "see [[https://example.com][docs]] now"
+        );
+    }
+
+    #[test]
+    fn emphasis_converted() {
+        assert_eq!(inline("**bold**"), "*bold*");
+        assert_eq!(inline("*italic*"), "/italic/");
+        assert_eq!(inline("***both***"), "*/both/*");
+    }
+
+    #[test]
+    fn strikethrough_underline_highlight() {
+        assert_eq!(inline("~~struck~~ text"), "+struck+ text");
+        assert_eq!(inline("<u>under</u>"), "_under_");
+        assert_eq!(inline("==marked== text"), "marked text");
+        assert_eq!(inline("==~~**all**~~=="), "+*all*+");
+        assert_eq!(inline("a == b, c ~~ d"), "a == b, c ~~ d");
+        assert_eq!(inline("x <u> y"), "x <u> y");
+    }
+
+    #[test]
+    fn sub_and_superscript() {
+        assert_eq!(inline("H~2~O"), "H_{2}O");
+        assert_eq!(inline("x^2^ + y^n\\ 1^"), "x^{2} + y^{n 1}");
+        assert_eq!(inline("~~H~2~O~~"), "+H_{2}O+");
+        assert_eq!(inline("~5 min ~10 min, 2^3"), "~5 min ~10 min, 2^3");
+    }
+
+    #[test]
+    fn internal_link_targets_anchor() {
+        assert_eq!(
+            inline("[see results](#results) below"),
+            "[[results][see results]] below"
+        );
+    }
+
+    #[test]
+    fn bookmark_anchor_becomes_target() {
+        assert_eq!(inline(r#"<a id="results"></a>"#), "<<results>>");
+        assert_eq!(
+            inline(r#"<a id="_Toc1"></a><a id="intro"></a>"#),
+            "<<_Toc1>><<intro>>"
+        );
+        assert_eq!(inline(r#"<a id="open"#), r#"<a id="open"#);
+        let md = "[jump](#bm1)\n\n<a id=\"bm1\"></a>\n\n## Target\n";
+        assert_eq!(
+            markdown_to_org(md),
+            "[[bm1][jump]]\n\n<<bm1>>\n\n** Target\n"
+        );
+    }
+
+    #[test]
+    fn bold_link_text() {
+        assert_eq!(
+            inline("[**Click**](https://x.com)"),
+            "[[https://x.com][*Click*]]"
+        );
+    }
+
+    #[test]
+    fn arithmetic_asterisks_untouched() {
+        assert_eq!(inline("2 * 3 * 4"), "2 * 3 * 4");
+    }
+
+    #[test]
+    fn code_span() {
+        assert_eq!(inline("run `ls -l` here"), "run ~ls -l~ here");
+    }
+
+    #[test]
+    fn code_span_with_backticks() {
+        assert_eq!(inline("run `` a `b` `` here"), "run ~a `b`~ here");
+        assert_eq!(inline("``` x `` y ```"), "~x `` y~");
+        assert_eq!(inline("`~/bin`"), "=~/bin=");
+        assert_eq!(inline("`` unclosed `"), "`` unclosed `");
+    }
+
+    #[test]
+    fn unmatched_bracket_literal() {
+        assert_eq!(inline("[not a link"), "[not a link");
+    }
+
+    // ── images ───────────────────────────────────────────────────
+
+    #[test]
+    fn reference_image_resolved() {
+        let md = "![][image1]\n\n[image1]: <data:image/png;base64,AAAA>\n";
+        assert_eq!(markdown_to_org(md), "[[data:image/png;base64,AAAA]]\n\n");
+    }
+
+    // ── code blocks ──────────────────────────────────────────────
+
+    #[test]
+    fn fenced_block_becomes_src_block() {
+        let md = "Run:\n\n```sh\n# list **all** files\nls [a-z]*_old | wc -l\n```\n\nDone.\n";
+        assert_eq!(
+            markdown_to_org(md),
+            "Run:\n\n#+BEGIN_SRC sh\n# list **all** files\nls [a-z]*_old | wc -l\n#+END_SRC\n\nDone.\n"
+        );
+    }
+
+    #[test]
+    fn fenced_block_closes_on_matching_run() {
+        let md = "````\n```\n* not a heading\n  #+TITLE: x\n| not | table |\n````\n## After\n";
+        assert_eq!(
+            markdown_to_org(md),
+            "#+BEGIN_SRC\n```\n,* not a heading\n  ,#+TITLE: x\n| not | table |\n#+END_SRC\n** After\n"
+        );
+    }
+
+    #[test]
+    fn unclosed_fence_ends_with_document() {
+        assert_eq!(
+            markdown_to_org("```\n[x]: y\n"),
+            "#+BEGIN_SRC\n[x]: y\n#+END_SRC\n"
+        );
+        assert_eq!(markdown_to_org("``` x `` y ```\n"), "~x `` y~\n");
+    }
+
+    // ── blockquotes ──────────────────────────────────────────────
+
+    #[test]
+    fn blockquote_wrapped() {
+        let md = "> quoted\n> more\nafter\n";
+        assert_eq!(
+            markdown_to_org(md),
+            "#+BEGIN_QUOTE\nquoted\nmore\n#+END_QUOTE\nafter\n"
+        );
+    }
+}
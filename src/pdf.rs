@@ -5,21 +5,81 @@
 //! wrapped in [`std::panic::catch_unwind`] to convert panics into
 //! [`BatdocError::Document`] errors.
 
+use crate::document;
 use crate::error::{BatdocError, Result};
+use crate::heuristic;
+use crate::options::Options;
 use std::fmt::Write as _;
 use std::panic::{self, AssertUnwindSafe};
 
-/// Extract pages of text from a PDF byte slice, returning one `String` per
-/// page.
+/// Extract pages of text from a PDF byte slice, returning the page number
+/// and text of each page selected by `opts.pages` (all by default).
+fn extract_pages(data: &[u8], opts: &Options) -> Result<Vec<(usize, String)>> {
+    let mut pages = Vec::new();
+    each_page(data, opts, &mut |number, text| {
+        pages.push((number, text));
+        Ok(())
+    })?;
+    Ok(pages)
+}
+
+/// [`extract_pages`], handing the number and text of each page to `emit`
+/// as soon as it is read.
 ///
 /// Panics from the underlying library are caught and converted to errors.
-fn extract_pages(data: &[u8]) -> Result<Vec<String>> {
+/// `opts.cancel` is checked and `opts.progress` told before each page,
+/// and no more pages are read once `opts.budget` is spent.
+fn each_page(
+    data: &[u8],
+    opts: &Options,
+    emit: &mut dyn FnMut(usize, String) -> Result<()>,
+) -> Result<()> {
+    let cancel = &opts.cancel;
+    let mut page_count = 0;
+    let mut selected = 0;
     let data = data.to_vec(); // owned copy for the unwind boundary
     let result = panic::catch_unwind(AssertUnwindSafe(|| {
-        pdf_extract::extract_text_from_mem_by_pages(&data)
+        // Same as `pdf_extract::extract_text_from_mem_by_pages`, with a
+        // cancellation check between pages
+        let mut doc = pdf_extract::Document::load_mem(&data)?;
+        if doc.is_encrypted() {
+            doc.decrypt("")?;
+        }
+        let mut budget = opts.budget;
+        let page_nums = doc.get_pages().into_keys();
+        page_count = page_nums.len();
+        for page_num in page_nums {
+            let number = page_num as usize; // u32 → usize: lossless on 32+ bit
+            if opts.pages.as_ref().is_some_and(|p| !p.contains(number)) {
+                continue;
+            }
+            if cancel.is_cancelled() {
+                return Ok(Err(BatdocError::Cancelled));
+            }
+            if budget.spent() {
+                break;
+            }
+            opts.progress.step("page", number, page_count);
+            let mut text = String::new();
+            let mut output = pdf_extract::PlainTextOutput::new(&mut text);
+            if pdf_extract::output_doc_page(&doc, &mut output, page_num).is_err() {
+                break;
+            }
+            // Paragraphs, as reflowed markdown may join a paragraph's lines
+            let paragraphs = text.split("\n\n").filter(|p| !p.trim().is_empty());
+            budget.spend(text.len(), paragraphs.count());
+            selected += 1;
+            if let Err(e) = emit(number, text) {
+                return Ok(Err(e));
+            }
+        }
+        Ok::<_, pdf_extract::OutputError>(Ok(()))
     }));
     match result {
-        Ok(Ok(pages)) => Ok(pages),
+        Ok(Ok(Ok(()))) if selected == 0 && opts.pages.is_some() => Err(BatdocError::Usage(
+            format!("no pages selected by --pages (the document has {page_count})"),
+        )),
+        Ok(Ok(done)) => done,
         Ok(Err(e)) => Err(BatdocError::Document(format!("PDF extraction failed: {e}"))),
         Err(_) => Err(BatdocError::Document(
             "PDF extraction panicked (malformed document)".into(),
@@ -27,6 +87,49 @@ fn extract_pages(data: &[u8]) -> Result<Vec<String>> {
     }
 }
 
+/// A PDF bookmark: an entry of the document outline.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Bookmark {
+    /// Nesting depth, 1 for the top level.
+    pub(crate) level: usize,
+    pub(crate) title: String,
+    /// The page it points to, numbered from 1.
+    pub(crate) page: usize,
+}
+
+/// The page count and bookmarks of a PDF, without extracting any text.
+/// A document without an outline has no bookmarks.
+pub(crate) fn outline(data: &[u8]) -> Result<(usize, Vec<Bookmark>)> {
+    let data = data.to_vec(); // owned copy for the unwind boundary
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut doc = pdf_extract::Document::load_mem(&data)?;
+        if doc.is_encrypted() {
+            doc.decrypt("")?;
+        }
+        let bookmarks = doc.get_toc().map_or_else(
+            |_| Vec::new(),
+            |toc| {
+                toc.toc
+                    .into_iter()
+                    .map(|entry| Bookmark {
+                        level: entry.level,
+                        title: entry.title.trim().to_string(),
+                        page: entry.page,
+                    })
+                    .collect()
+            },
+        );
+        Ok::<_, pdf_extract::Error>((doc.get_pages().len(), bookmarks))
+    }));
+    match result {
+        Ok(Ok(outline)) => Ok(outline),
+        Ok(Err(e)) => Err(BatdocError::Document(format!("PDF parsing failed: {e}"))),
+        Err(_) => Err(BatdocError::Document(
+            "PDF parsing panicked (malformed document)".into(),
+        )),
+    }
+}
+
 /// Clean up a page of extracted text: trim trailing whitespace from each line,
 /// collapse runs of 3+ blank lines down to 2, and trim leading/trailing
 /// blank lines from the whole page.
@@ -60,9 +163,9 @@ fn clean_page(raw: &str) -> String {
 }
 
 /// Extract plain text from a PDF.
-pub(crate) fn extract_plain(data: &[u8]) -> Result<String> {
-    let pages = extract_pages(data)?;
-    let cleaned: Vec<String> = pages.iter().map(|p| clean_page(p)).collect();
+pub(crate) fn extract_plain(data: &[u8], opts: &Options) -> Result<String> {
+    let pages = extract_pages(data, opts)?;
+    let cleaned: Vec<String> = pages.iter().map(|(_, p)| clean_page(p)).collect();
 
     // Filter out completely empty pages
     let nonempty: Vec<&str> = cleaned
@@ -72,7 +175,7 @@ pub(crate) fn extract_plain(data: &[u8]) -> Result<String> {
         .collect();
 
     if nonempty.is_empty() {
-        return Err(BatdocError::Document(
+        return Err(BatdocError::Empty(
             "PDF contains no extractable text (may be scanned/image-only)".into(),
         ));
     }
@@ -80,28 +183,66 @@ pub(crate) fn extract_plain(data: &[u8]) -> Result<String> {
     Ok(nonempty.join("\n"))
 }
 
+/// Extract blocks from a PDF for the library API: a
+/// [`Page`](document::Block::Page) marker followed by its paragraphs.
+pub(crate) fn extract_blocks(data: &[u8], opts: &Options) -> Result<Vec<document::Block>> {
+    let mut blocks = Vec::new();
+    stream_blocks(data, opts, &mut |mut page| {
+        blocks.append(&mut page);
+        Ok(())
+    })?;
+    Ok(blocks)
+}
+
+/// [`extract_blocks`], handing the blocks of each page to `emit` as soon
+/// as the page is read.
+pub(crate) fn stream_blocks(
+    data: &[u8],
+    opts: &Options,
+    emit: &mut dyn FnMut(Vec<document::Block>) -> Result<()>,
+) -> Result<()> {
+    each_page(data, opts, &mut |number, page| {
+        let mut blocks = vec![document::Block::Page { number }];
+        document::text_blocks(&clean_page(&page), &mut blocks);
+        emit(blocks)
+    })
+}
+
 /// Extract markdown from a PDF.
 ///
-/// Each page gets a `## Page N` heading. Single-page documents omit the
-/// heading since it would be redundant.
-pub(crate) fn extract_markdown(data: &[u8]) -> Result<String> {
-    let pages = extract_pages(data)?;
-    let cleaned: Vec<String> = pages.iter().map(|p| clean_page(p)).collect();
+/// Each page gets a `## Page N` heading, numbered as in the document even
+/// when `--pages` selects a subset. Single-page output omits the heading
+/// since it would be redundant. Space-aligned columns are rendered
+/// as markdown tables. With `opts.reflow`, hard-wrapped lines within a page
+/// are joined into paragraphs.
+pub(crate) fn extract_markdown(data: &[u8], opts: &Options) -> Result<String> {
+    let pages = extract_pages(data, opts)?;
+    let cleaned: Vec<(usize, String)> = pages
+        .iter()
+        .map(|(number, p)| {
+            let page = heuristic::render_aligned_tables(&clean_page(p));
+            let page = if opts.reflow {
+                heuristic::reflow(&page)
+            } else {
+                page
+            };
+            (*number, page)
+        })
+        .collect();
 
     let nonempty: Vec<(usize, &str)> = cleaned
         .iter()
-        .enumerate()
-        .filter_map(|(i, s)| {
+        .filter_map(|(number, s)| {
             if s.is_empty() {
                 None
             } else {
-                Some((i + 1, s.as_str()))
+                Some((*number, s.as_str()))
             }
         })
         .collect();
 
     if nonempty.is_empty() {
-        return Err(BatdocError::Document(
+        return Err(BatdocError::Empty(
             "PDF contains no extractable text (may be scanned/image-only)".into(),
         ));
     }
@@ -158,7 +299,7 @@ mod tests {
     #[test]
     fn malformed_data_returns_error() {
         let garbage = b"not a pdf at all";
-        let result = extract_plain(garbage);
+        let result = extract_plain(garbage, &Options::default());
         assert!(result.is_err());
     }
 
@@ -166,7 +307,7 @@ mod tests {
     fn empty_pdf_header_returns_error() {
         // A minimal PDF header with no real content
         let data = b"%PDF-1.4\n%%EOF\n";
-        let result = extract_plain(data);
+        let result = extract_plain(data, &Options::default());
         assert!(result.is_err());
     }
 }
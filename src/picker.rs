@@ -0,0 +1,187 @@
+//! Interactive choice of the sheets or slides to show (`--pick`).
+//!
+//! The list is drawn on stderr and keys are read from the terminal, so
+//! stdout carries only the document. Typing filters the list (the typed
+//! letters must appear in order, ignoring case), the arrow keys move, Tab
+//! marks items, Enter shows the marked items or the one under the cursor,
+//! and Esc shows everything.
+
+use std::io;
+
+use console::{Key, Term};
+
+/// Ask the user to choose among `items`. Returns the 0-based indices of
+/// the chosen items in list order, or `None` to show them all.
+///
+/// Ctrl-C fails with [`io::ErrorKind::Interrupted`].
+pub(crate) fn pick(prompt: &str, items: &[String]) -> io::Result<Option<Vec<usize>>> {
+    let term = Term::stderr();
+    let mut picker = Picker::new(items);
+    let mut drawn = 0;
+    term.hide_cursor()?;
+    let result = loop {
+        term.clear_last_lines(drawn)?;
+        drawn = draw(&term, prompt, &picker)?;
+        if let Some(outcome) = picker.key(&term.read_key_raw()?) {
+            break outcome;
+        }
+    };
+    term.clear_last_lines(drawn)?;
+    term.show_cursor()?;
+    result
+}
+
+/// Write the prompt, the visible part of the list, and a key hint.
+/// Returns the number of lines written.
+fn draw(term: &Term, prompt: &str, picker: &Picker) -> io::Result<usize> {
+    let (rows, cols) = term.size();
+    let width = usize::from(cols).saturating_sub(1);
+    let height = usize::from(rows).saturating_sub(3).max(1);
+    let shown = picker.shown();
+    let top = picker.cursor.saturating_sub(height - 1);
+
+    term.write_line(&console::truncate_str(
+        &format!("{prompt}> {}", picker.filter),
+        width,
+        "…",
+    ))?;
+    for (row, &index) in shown.iter().enumerate().skip(top).take(height) {
+        let line = format!(
+            "{} [{}] {}",
+            if row == picker.cursor { '>' } else { ' ' },
+            if picker.marked[index] { 'x' } else { ' ' },
+            picker.items[index]
+        );
+        term.write_line(&console::truncate_str(&line, width, "…"))?;
+    }
+    term.write_line(&console::truncate_str(
+        "type to filter, tab to mark, enter to show, esc for all",
+        width,
+        "…",
+    ))?;
+    Ok(shown.len().min(height) + 2)
+}
+
+/// The state of the picker, apart from the terminal.
+struct Picker<'a> {
+    items: &'a [String],
+    /// Typed so far, narrowing the list
+    filter: String,
+    /// Position in the filtered list
+    cursor: usize,
+    /// Marked items, by index into `items`
+    marked: Vec<bool>,
+}
+
+impl<'a> Picker<'a> {
+    fn new(items: &'a [String]) -> Self {
+        Self {
+            items,
+            filter: String::new(),
+            cursor: 0,
+            marked: vec![false; items.len()],
+        }
+    }
+
+    /// Indices of the items that match the filter.
+    fn shown(&self) -> Vec<usize> {
+        (0..self.items.len())
+            .filter(|&i| matches(&self.items[i], &self.filter))
+            .collect()
+    }
+
+    /// Handle one key. Returns the outcome once the user is done.
+    fn key(&mut self, key: &Key) -> Option<io::Result<Option<Vec<usize>>>> {
+        let shown = self.shown();
+        match key {
+            Key::ArrowUp => self.cursor = self.cursor.saturating_sub(1),
+            Key::ArrowDown => self.cursor = (self.cursor + 1).min(shown.len().saturating_sub(1)),
+            Key::Home | Key::PageUp => self.cursor = 0,
+            Key::End | Key::PageDown => self.cursor = shown.len().saturating_sub(1),
+            Key::Tab => {
+                if let Some(&index) = shown.get(self.cursor) {
+                    self.marked[index] = !self.marked[index];
+                    self.cursor = (self.cursor + 1).min(shown.len() - 1);
+                }
+            }
+            Key::Enter => {
+                let marked: Vec<usize> =
+                    (0..self.items.len()).filter(|&i| self.marked[i]).collect();
+                if !marked.is_empty() {
+                    return Some(Ok(Some(marked)));
+                }
+                if let Some(&index) = shown.get(self.cursor) {
+                    return Some(Ok(Some(vec![index])));
+                }
+            }
+            Key::Escape => return Some(Ok(None)),
+            Key::CtrlC => return Some(Err(io::ErrorKind::Interrupted.into())),
+            Key::Backspace => {
+                self.filter.pop();
+                self.cursor = 0;
+            }
+            Key::Char(c) if !c.is_control() => {
+                self.filter.push(*c);
+                self.cursor = 0;
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Whether the characters of `filter` appear in `item` in order,
+/// ignoring case.
+fn matches(item: &str, filter: &str) -> bool {
+    let mut chars = item.chars().flat_map(char::to_lowercase);
+    filter
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|f| chars.any(|c| c == f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(names: &[&str]) -> Vec<String> {
+        names.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn filter_is_an_ordered_subsequence() {
+        assert!(matches("Quarterly Report", "qrep"));
+        assert!(matches("Summary", ""));
+        assert!(!matches("Summary", "ys"));
+    }
+
+    #[test]
+    fn enter_shows_the_item_under_the_cursor() {
+        let items = items(&["Summary", "Q1", "Q2"]);
+        let mut picker = Picker::new(&items);
+        assert!(picker.key(&Key::ArrowDown).is_none());
+        assert!(matches!(picker.key(&Key::Enter), Some(Ok(Some(ref v))) if v == &[1]));
+    }
+
+    #[test]
+    fn marked_items_in_list_order() {
+        let items = items(&["Summary", "Q1", "Q2", "Notes"]);
+        let mut picker = Picker::new(&items);
+        picker.key(&Key::Char('q'));
+        assert_eq!(picker.shown(), [1, 2]);
+        picker.key(&Key::ArrowDown);
+        picker.key(&Key::Tab);
+        picker.key(&Key::ArrowUp);
+        picker.key(&Key::Tab);
+        assert!(matches!(picker.key(&Key::Enter), Some(Ok(Some(ref v))) if v == &[1, 2]));
+    }
+
+    #[test]
+    fn escape_shows_all() {
+        let items = items(&["A", "B"]);
+        let mut picker = Picker::new(&items);
+        assert!(matches!(picker.key(&Key::Escape), Some(Ok(None))));
+        let err = picker.key(&Key::CtrlC).unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+    }
+}
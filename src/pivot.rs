@@ -0,0 +1,196 @@
+//! Pivot tables of a workbook (`pivotTables/pivotTable*.xml` parts).
+//!
+//! A pivot table definition names its fields by position in the pivot
+//! cache it summarizes (`pivotCacheDefinition*.xml`), which holds the
+//! field names and the range they were read from. The cells a pivot table
+//! fills are often not saved with the sheet, so its layout is shown
+//! instead: where it came from, and which fields make its rows, columns,
+//! values, and filters.
+
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+
+use crate::xml_util::{get_attr, unescaped_attr};
+
+/// The layout of a pivot table.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Pivot {
+    pub(crate) name: String,
+    /// The range the pivot table summarizes, as `Sheet!A1:D100`, or the
+    /// name of a table or named range.
+    source: Option<String>,
+    /// The cells the pivot table fills.
+    location: Option<String>,
+    rows: Vec<String>,
+    columns: Vec<String>,
+    values: Vec<String>,
+    filters: Vec<String>,
+}
+
+impl Pivot {
+    /// Read a pivot table definition, naming its fields from the pivot
+    /// cache definition `cache` it summarizes. Returns `None` if the part
+    /// is not a pivot table definition.
+    pub(crate) fn parse(xml: &str, cache: Option<&str>) -> Option<Self> {
+        let (fields, source) = cache.map(parse_cache).unwrap_or_default();
+        let field = |x: Option<String>| {
+            let x: usize = x?.parse().ok()?;
+            fields.get(x).cloned()
+        };
+        let mut reader = Reader::from_str(xml);
+        let mut pivot: Option<Self> = None;
+        // The area whose `<field>` elements are being read
+        let mut area: Option<&[u8]> = None;
+
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(ref e) | Event::Empty(ref e)) => {
+                    let name = e.local_name();
+                    match (name.as_ref(), pivot.as_mut()) {
+                        (b"pivotTableDefinition", _) => {
+                            pivot = Some(Self {
+                                name: unescaped_attr(e, b"name").unwrap_or_default(),
+                                source: source.clone(),
+                                ..Self::default()
+                            });
+                        }
+                        (b"location", Some(pivot)) => pivot.location = get_attr(e, b"ref"),
+                        (b"rowFields", _) => area = Some(b"rowFields"),
+                        (b"colFields", _) => area = Some(b"colFields"),
+                        // x="-2" is where the values go, not a field
+                        (b"field", Some(pivot)) => {
+                            if let Some(name) = field(get_attr(e, b"x")) {
+                                match area {
+                                    Some(b"rowFields") => pivot.rows.push(name),
+                                    Some(b"colFields") => pivot.columns.push(name),
+                                    _ => {}
+                                }
+                            }
+                        }
+                        (b"pageField", Some(pivot)) => {
+                            pivot.filters.extend(field(get_attr(e, b"fld")));
+                        }
+                        (b"dataField", Some(pivot)) => {
+                            let name =
+                                unescaped_attr(e, b"name").or_else(|| field(get_attr(e, b"fld")));
+                            pivot.values.extend(name);
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Event::End(ref e))
+                    if matches!(e.local_name().as_ref(), b"rowFields" | b"colFields") =>
+                {
+                    area = None;
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => {}
+            }
+        }
+        pivot
+    }
+
+    /// The layout as a table of two columns, headed by the pivot table's
+    /// name: its source and location, then the fields of each area that
+    /// has any.
+    pub(crate) fn rows(&self) -> Vec<Vec<String>> {
+        let mut rows = vec![vec!["Pivot table".to_string(), self.name.clone()]];
+        for (label, value) in [("Source", &self.source), ("Location", &self.location)] {
+            if let Some(value) = value {
+                rows.push(vec![label.to_string(), value.clone()]);
+            }
+        }
+        for (label, fields) in [
+            ("Rows", &self.rows),
+            ("Columns", &self.columns),
+            ("Values", &self.values),
+            ("Filters", &self.filters),
+        ] {
+            if !fields.is_empty() {
+                rows.push(vec![label.to_string(), fields.join(", ")]);
+            }
+        }
+        rows
+    }
+}
+
+/// The field names of a pivot cache definition, in order, and the range
+/// it was read from (`<worksheetSource>`): `Sheet!ref`, or the name it
+/// gives.
+fn parse_cache(xml: &str) -> (Vec<String>, Option<String>) {
+    let mut reader = Reader::from_str(xml);
+    let mut fields = Vec::new();
+    let mut source = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e) | Event::Empty(ref e)) => match e.local_name().as_ref() {
+                b"cacheField" => fields.push(unescaped_attr(e, b"name").unwrap_or_default()),
+                b"worksheetSource" => {
+                    source = match (unescaped_attr(e, b"sheet"), get_attr(e, b"ref")) {
+                        (Some(sheet), Some(range)) => Some(format!("{sheet}!{range}")),
+                        (_, range) => unescaped_attr(e, b"name").or(range),
+                    };
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    (fields, source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CACHE: &str = r#"<pivotCacheDefinition r:id="rId1" refreshOnLoad="1">
+<cacheSource type="worksheet"><worksheetSource ref="A1:D500" sheet="Sales"/></cacheSource>
+<cacheFields count="4">
+<cacheField name="Region" numFmtId="0"><sharedItems count="2"><s v="East"/><s v="West"/></sharedItems></cacheField>
+<cacheField name="Quarter" numFmtId="0"><sharedItems/></cacheField>
+<cacheField name="Year" numFmtId="0"><sharedItems/></cacheField>
+<cacheField name="Amount" numFmtId="0"><sharedItems containsNumber="1"/></cacheField>
+</cacheFields></pivotCacheDefinition>"#;
+
+    const DEFINITION: &str = r#"<pivotTableDefinition name="SalesPivot" cacheId="3" dataCaption="Values">
+<location ref="A3:D9" firstHeaderRow="1" firstDataRow="2" firstDataCol="1"/>
+<pivotFields count="4"><pivotField axis="axisRow" showAll="0"><items count="1"><item x="0"/></items></pivotField><pivotField axis="axisCol"/><pivotField axis="axisPage"/><pivotField dataField="1"/></pivotFields>
+<rowFields count="1"><field x="0"/></rowFields>
+<colFields count="2"><field x="1"/><field x="-2"/></colFields>
+<pageFields count="1"><pageField fld="2" hier="-1"/></pageFields>
+<dataFields count="2"><dataField name="Sum of Amount" fld="3" baseField="0" baseItem="0"/><dataField fld="3" subtotal="count"/></dataFields>
+</pivotTableDefinition>"#;
+
+    #[test]
+    fn layout_names_fields_from_cache() {
+        let pivot = Pivot::parse(DEFINITION, Some(CACHE)).unwrap();
+        assert_eq!(
+            pivot.rows(),
+            [
+                ["Pivot table", "SalesPivot"],
+                ["Source", "Sales!A1:D500"],
+                ["Location", "A3:D9"],
+                ["Rows", "Region"],
+                ["Columns", "Quarter"],
+                ["Values", "Sum of Amount, Amount"],
+                ["Filters", "Year"],
+            ]
+        );
+    }
+
+    #[test]
+    fn layout_without_cache() {
+        let pivot = Pivot::parse(DEFINITION, None).unwrap();
+        assert_eq!(
+            pivot.rows(),
+            [
+                ["Pivot table", "SalesPivot"],
+                ["Location", "A3:D9"],
+                ["Values", "Sum of Amount"],
+            ]
+        );
+        assert_eq!(Pivot::parse("<worksheet/>", None), None);
+    }
+}
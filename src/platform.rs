@@ -0,0 +1,111 @@
+//! Platform-specific console and path handling.
+//!
+//! On Windows, the console decodes output with the active code page, so
+//! UTF-8 text shows up garbled unless the code page is switched to UTF-8
+//! (65001) first. [`init_console`] does that for the lifetime of the
+//! returned guard and restores the previous code page afterwards, since the
+//! setting belongs to the console window rather than to this process.
+
+use std::ffi::OsString;
+use std::path::Path;
+
+/// Restores the console's previous output code page when dropped. Does
+/// nothing outside Windows.
+pub(crate) struct ConsoleGuard {
+    #[cfg(windows)]
+    previous: u32,
+}
+
+#[cfg(windows)]
+mod ffi {
+    // kernel32 is always linked on Windows targets
+    extern "system" {
+        pub(super) fn GetConsoleOutputCP() -> u32;
+        pub(super) fn SetConsoleOutputCP(code_page: u32) -> i32;
+    }
+}
+
+/// Switch the console to UTF-8 output for as long as the guard lives.
+#[cfg(windows)]
+pub(crate) fn init_console() -> ConsoleGuard {
+    const CP_UTF8: u32 = 65001;
+    // SAFETY: both calls take and return plain integers; they fail
+    // harmlessly (returning 0) when there is no console.
+    let previous = unsafe { ffi::GetConsoleOutputCP() };
+    if previous != 0 && previous != CP_UTF8 {
+        unsafe { ffi::SetConsoleOutputCP(CP_UTF8) };
+    }
+    ConsoleGuard { previous }
+}
+
+/// Switch the console to UTF-8 output for as long as the guard lives.
+#[cfg(not(windows))]
+pub(crate) const fn init_console() -> ConsoleGuard {
+    ConsoleGuard {}
+}
+
+#[cfg(windows)]
+impl Drop for ConsoleGuard {
+    fn drop(&mut self) {
+        if self.previous != 0 {
+            // SAFETY: see `init_console`.
+            unsafe { ffi::SetConsoleOutputCP(self.previous) };
+        }
+    }
+}
+
+/// A path as shown in messages: lossily converted to UTF-8, and without
+/// the `\\?\` prefix of Windows extended-length paths.
+pub(crate) fn display_path(path: &Path) -> String {
+    let s = path.to_string_lossy();
+    if let Some(rest) = s.strip_prefix(r"\\?\UNC\") {
+        return format!(r"\\{rest}");
+    }
+    s.strip_prefix(r"\\?\")
+        .map_or_else(|| s.to_string(), str::to_string)
+}
+
+/// A file name read as bytes, such as a line of a `--files-from` list.
+/// Outside Unix, where file names are not bytes, it must be UTF-8 and is
+/// converted lossily.
+#[cfg(unix)]
+pub(crate) fn path_from_bytes(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes)
+}
+
+/// A file name read as bytes, such as a line of a `--files-from` list.
+/// Outside Unix, where file names are not bytes, it must be UTF-8 and is
+/// converted lossily.
+#[cfg(not(unix))]
+pub(crate) fn path_from_bytes(bytes: Vec<u8>) -> OsString {
+    String::from_utf8_lossy(&bytes).into_owned().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── display_path ─────────────────────────────────────────────
+
+    #[test]
+    fn strips_verbatim_prefixes() {
+        assert_eq!(
+            display_path(Path::new(r"\\?\C:\docs\a.docx")),
+            r"C:\docs\a.docx"
+        );
+        assert_eq!(
+            display_path(Path::new(r"\\?\UNC\server\share\a.xls")),
+            r"\\server\share\a.xls"
+        );
+        assert_eq!(display_path(Path::new("dir/a.pdf")), "dir/a.pdf");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_is_lossy() {
+        use std::os::unix::ffi::OsStrExt;
+        let path = Path::new(std::ffi::OsStr::from_bytes(b"caf\xe9.doc"));
+        assert_eq!(display_path(path), "caf\u{fffd}.doc");
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! Unzips the `.pptx` archive, discovers slides from `ppt/presentation.xml`
 //! and its relationships, then parses each slide's XML to extract text from
-//! shapes. Hyperlinks on text runs are resolved from per-slide relationship
+//! shapes. Hyperlinks on text runs, and click actions on whole shapes and
+//! pictures (action buttons), are resolved from per-slide relationship
 //! files. Output is either plain text or markdown with per-slide headings.
 
 use quick_xml::events::Event;
@@ -12,8 +13,11 @@ use std::fmt::Write as _;
 use std::io::{Cursor, Read};
 use zip::ZipArchive;
 
+use crate::document;
 use crate::markup;
-use crate::xml_util::{self, get_attr, Rels};
+use crate::options::{Budget, Options};
+use crate::symbols;
+use crate::xml_util::{self, get_attr, unescaped_attr, Rels};
 
 /// A parsed slide: its number and extracted text runs.
 #[derive(Debug)]
@@ -29,6 +33,26 @@ struct Slide {
 #[derive(Debug)]
 struct ShapeText {
     paragraphs: Vec<Paragraph>,
+    /// Click action on the shape as a whole, resolved in [`parse_pptx`].
+    link: Option<ShapeLink>,
+}
+
+/// A click action on a whole shape or picture (`<a:hlinkClick>` in its
+/// non-visual properties), as opposed to one on a text run.
+#[derive(Debug, Default)]
+struct ShapeLink {
+    /// `"Button"` for shapes, `"Image"` for pictures.
+    kind: &'static str,
+    /// `name` of `<p:cNvPr>`, e.g. `"Picture 3"`.
+    name: String,
+    /// `descr` (alt text) of `<p:cNvPr>`.
+    descr: String,
+    /// Preset geometry, e.g. `actionButtonForwardNext`.
+    preset: String,
+    /// Relationship id of the target; empty for slide show jumps.
+    rid: String,
+    /// The `action` verb, e.g. `ppaction://hlinkshowjump?jump=nextslide`.
+    action: String,
 }
 
 /// Whether a paragraph is a bullet/numbered list item.
@@ -66,17 +90,68 @@ struct TextRun {
 }
 
 /// Extract plain text from a .pptx file.
-pub(crate) fn extract_plain(data: &[u8]) -> crate::error::Result<String> {
-    let (slides, _) = parse_pptx(data, false)?;
+pub(crate) fn extract_plain(data: &[u8], opts: &Options) -> crate::error::Result<String> {
+    let (slides, _) = parse_pptx(data, false, opts.budget, opts.picked.as_deref())?;
     Ok(render_plain(&slides))
 }
 
+/// Extract blocks from a .pptx file for the library API: a
+/// [`Slide`](document::Block::Slide) marker followed by its paragraphs.
+pub(crate) fn extract_blocks(data: &[u8]) -> crate::error::Result<Vec<document::Block>> {
+    let mut blocks = Vec::new();
+    stream_blocks(data, &mut |mut slide| {
+        blocks.append(&mut slide);
+        Ok(())
+    })?;
+    Ok(blocks)
+}
+
+/// [`extract_blocks`], handing the blocks of each slide to `emit` as soon
+/// as the slide is read.
+pub(crate) fn stream_blocks(
+    data: &[u8],
+    emit: &mut dyn FnMut(Vec<document::Block>) -> crate::error::Result<()>,
+) -> crate::error::Result<()> {
+    each_slide(data, false, Budget::default(), None, &mut |slide| {
+        let mut blocks = vec![document::Block::Slide {
+            number: slide.number,
+        }];
+        for para in slide.shapes.iter().flat_map(|s| &s.paragraphs) {
+            let text: String = para.runs.iter().map(|r| r.text.as_str()).collect();
+            let text = text.trim().to_string();
+            if text.is_empty() {
+                continue;
+            }
+            blocks.push(match para.bullet {
+                BulletKind::Bullet(level) | BulletKind::Numbered(level) => {
+                    document::Block::ListItem {
+                        level,
+                        ordered: matches!(para.bullet, BulletKind::Numbered(_)),
+                        text,
+                        spans: Vec::new(),
+                    }
+                }
+                BulletKind::None if para.heading_level > 0 => document::Block::Heading {
+                    level: para.heading_level,
+                    text,
+                },
+                BulletKind::None => document::Block::Paragraph {
+                    text,
+                    spans: Vec::new(),
+                },
+            });
+        }
+        emit(blocks)
+    })?;
+    Ok(())
+}
+
 /// Extract markdown-formatted text from a .pptx file.
 ///
-/// When `images` is true, embedded images are extracted and included as
+/// When `opts.images` is true, embedded images are extracted and included as
 /// reference-style base64 images with definitions appended at the end.
-pub(crate) fn extract_markdown(data: &[u8], images: bool) -> crate::error::Result<String> {
-    let (slides, image_defs) = parse_pptx(data, images)?;
+pub(crate) fn extract_markdown(data: &[u8], opts: &Options) -> crate::error::Result<String> {
+    let (slides, image_defs) = parse_pptx(data, opts.images, opts.budget, opts.picked.as_deref())?;
     let mut md = render_markdown(&slides);
     if !image_defs.is_empty() {
         for def in &image_defs {
@@ -92,22 +167,53 @@ pub(crate) fn extract_markdown(data: &[u8], images: bool) -> crate::error::Resul
 /// Parse the pptx archive into slides and image reference definitions.
 ///
 /// When `extract_images` is true, image relationships are loaded and
-/// `<p:pic>` elements are extracted as reference-style images.
+/// `<p:pic>` elements are extracted as reference-style images. Only the
+/// `picked` slide numbers are parsed, if given, and parsing stops once
+/// `budget` is spent.
 fn parse_pptx(
     data: &[u8],
     extract_images: bool,
+    budget: Budget,
+    picked: Option<&[usize]>,
 ) -> crate::error::Result<(Vec<Slide>, Vec<String>)> {
+    let mut slides = Vec::new();
+    let image_defs = each_slide(data, extract_images, budget, picked, &mut |slide| {
+        slides.push(slide);
+        Ok(())
+    })?;
+    Ok((slides, image_defs))
+}
+
+/// [`parse_pptx`], handing each slide to `emit` in turn and returning the
+/// image reference definitions.
+fn each_slide(
+    data: &[u8],
+    extract_images: bool,
+    mut budget: Budget,
+    picked: Option<&[usize]>,
+    emit: &mut dyn FnMut(Slide) -> crate::error::Result<()>,
+) -> crate::error::Result<Vec<String>> {
     let cursor = Cursor::new(data);
     let mut archive = ZipArchive::new(cursor)?;
 
     // Discover slides from presentation.xml + rels
     let slide_paths = discover_slides(&mut archive)?;
+    let slide_count = slide_paths.last().map_or(0, |(num, _)| *num);
+    let slide_numbers: HashMap<String, usize> = slide_paths
+        .iter()
+        .map(|(num, path)| (file_name(path).to_string(), *num))
+        .collect();
 
-    let mut slides = Vec::new();
     let mut all_image_defs = Vec::new();
     let mut image_counter = 0usize;
 
     for (num, path) in slide_paths {
+        if budget.spent() {
+            break;
+        }
+        if picked.is_some_and(|picked| !picked.contains(&num)) {
+            continue;
+        }
         let mut xml = String::new();
         match archive.by_name(&path) {
             Ok(mut entry) => {
@@ -127,7 +233,26 @@ fn parse_pptx(
             xml_util::Rels::new()
         };
 
-        let shapes = parse_slide_xml(&xml, &rels);
+        let mut shapes = parse_slide_xml(&xml, &rels);
+        if shapes.iter().any(|s| s.link.is_some()) {
+            let slide_rels = xml_util::load_typed_rels(&mut archive, &slide_rels_path, "/slide");
+            for shape in &mut shapes {
+                let Some(link) = shape.link.take() else {
+                    continue;
+                };
+                let jump = |target: &String| slide_numbers.get(file_name(target)).copied();
+                let target = rels.get(&link.rid).cloned().or_else(|| {
+                    slide_rels
+                        .get(&link.rid)
+                        .map_or_else(|| link.jump(num, slide_count), jump)
+                        .map(|n| format!("#slide-{n}"))
+                });
+                if let Some(target) = target {
+                    shape.apply_link(&link, &target);
+                }
+            }
+            shapes.retain(|s| !s.paragraphs.is_empty());
+        }
 
         // Extract images from <p:pic> elements
         let images = if extract_images && !image_rels.is_empty() {
@@ -153,14 +278,92 @@ fn parse_pptx(
             Vec::new()
         };
 
-        slides.push(Slide {
+        for para in shapes.iter().flat_map(|s| &s.paragraphs) {
+            let bytes = para.runs.iter().map(|r| r.text.len()).sum();
+            let text = para.runs.iter().any(|r| !r.text.trim().is_empty());
+            budget.spend(bytes, usize::from(text));
+        }
+        emit(Slide {
             number: num,
             shapes,
             images,
-        });
+        })?;
     }
 
-    Ok((slides, all_image_defs))
+    Ok(all_image_defs)
+}
+
+/// The last segment of a zip path or relationship target.
+fn file_name(path: &str) -> &str {
+    path.rsplit_once('/').map_or(path, |(_, name)| name)
+}
+
+impl ShapeLink {
+    /// The slide a slide show jump (`ppaction://hlinkshowjump`) lands on,
+    /// from slide `current` of `count`.
+    fn jump(&self, current: usize, count: usize) -> Option<usize> {
+        match self.action.strip_prefix("ppaction://hlinkshowjump?jump=")? {
+            "nextslide" => Some(current + 1).filter(|&n| n <= count),
+            "previousslide" => Some(current - 1).filter(|&n| n > 0),
+            "firstslide" => Some(1),
+            "lastslide" => Some(count),
+            _ => None,
+        }
+    }
+
+    /// What to call the link when the shape has no text: its alt text,
+    /// the kind of action button or jump, or else the shape's name.
+    fn label(&self) -> String {
+        let descr = self.descr.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !descr.is_empty() {
+            return descr;
+        }
+        let jump = self.action.strip_prefix("ppaction://hlinkshowjump?jump=");
+        let named = match self.preset.strip_prefix("actionButton") {
+            Some("ForwardNext") => Some("Next"),
+            Some("BackPrevious") => Some("Previous"),
+            Some("Beginning") => Some("First"),
+            Some("End") => Some("Last"),
+            Some(
+                kind
+                @ ("Home" | "Return" | "Help" | "Information" | "Document" | "Sound" | "Movie"),
+            ) => Some(kind),
+            _ => match jump {
+                Some("nextslide") => Some("Next"),
+                Some("previousslide") => Some("Previous"),
+                Some("firstslide") => Some("First"),
+                Some("lastslide") => Some("Last"),
+                _ => None,
+            },
+        };
+        named.map_or_else(|| self.name.trim().to_string(), str::to_string)
+    }
+}
+
+impl ShapeText {
+    /// Attach a shape's click `target`: its text becomes the link, or a
+    /// shape without text gets a `Button: label` / `Image: label` line.
+    fn apply_link(&mut self, link: &ShapeLink, target: &str) {
+        if self.paragraphs.is_empty() {
+            self.paragraphs.push(Paragraph {
+                runs: vec![TextRun {
+                    text: format!("{}: {}", link.kind, link.label()),
+                    bold: false,
+                    italic: false,
+                    link_url: Some(target.to_string()),
+                    font_size: None,
+                }],
+                heading_level: 0,
+                bullet: BulletKind::None,
+            });
+            return;
+        }
+        for run in self.paragraphs.iter_mut().flat_map(|p| &mut p.runs) {
+            if run.link_url.is_none() {
+                run.link_url = Some(target.to_string());
+            }
+        }
+    }
 }
 
 /// Parse a slide's XML to extract rId values from `<p:pic>` → `<a:blip>` elements.
@@ -187,22 +390,18 @@ fn parse_slide_pic_rids(xml: &str) -> Vec<String> {
                     }
                 }
             }
-            Ok(Event::Empty(ref e)) if in_pic => {
-                if e.local_name().as_ref() == b"blip" {
-                    if let Some(rid) = get_attr(e, b"r:embed") {
-                        rids.push(rid);
-                    }
+            Ok(Event::Empty(ref e)) if in_pic && e.local_name().as_ref() == b"blip" => {
+                if let Some(rid) = get_attr(e, b"r:embed") {
+                    rids.push(rid);
                 }
             }
-            Ok(Event::End(ref e)) => {
-                if in_pic {
-                    if e.local_name().as_ref() == b"pic" {
+            Ok(Event::End(ref e)) if in_pic => {
+                if e.local_name().as_ref() == b"pic" {
+                    in_pic = false;
+                } else {
+                    depth -= 1;
+                    if depth == 0 {
                         in_pic = false;
-                    } else {
-                        depth -= 1;
-                        if depth == 0 {
-                            in_pic = false;
-                        }
                     }
                 }
             }
@@ -297,12 +496,11 @@ fn parse_slide_xml(xml: &str, rels: &Rels) -> Vec<ShapeText> {
         match reader.read_event() {
             Ok(Event::Start(ref e)) => {
                 let name = e.local_name();
-                // <p:sp> = shape, <p:graphicFrame> = table/chart, <p:grpSp> = group
-                if name.as_ref() == b"sp" || name.as_ref() == b"graphicFrame" {
+                // <p:sp> = shape, <p:graphicFrame> = table/chart, <p:grpSp> = group,
+                // <p:pic> = picture (only kept when it is hyperlinked)
+                if matches!(name.as_ref(), b"sp" | b"graphicFrame" | b"pic") {
                     if let Some(shape) = parse_shape(&mut reader, rels, e.local_name().as_ref()) {
-                        if !shape.paragraphs.is_empty() {
-                            shapes.push(shape);
-                        }
+                        shapes.push(shape);
                     }
                 }
             }
@@ -314,31 +512,47 @@ fn parse_slide_xml(xml: &str, rels: &Rels) -> Vec<ShapeText> {
     shapes
 }
 
-/// Parse a shape element (`<p:sp>` or `<p:graphicFrame>`), extracting its text body.
+/// Parse a shape element (`<p:sp>`, `<p:graphicFrame>` or `<p:pic>`),
+/// extracting its text body and any click action on the shape itself.
 fn parse_shape(reader: &mut Reader<&[u8]>, rels: &Rels, end_tag: &[u8]) -> Option<ShapeText> {
     let mut paragraphs = Vec::new();
+    let mut props = ShapeLink {
+        kind: if end_tag == b"pic" { "Image" } else { "Button" },
+        ..ShapeLink::default()
+    };
+    let mut clickable = false;
 
     loop {
         match reader.read_event() {
-            Ok(Event::Start(ref e)) => {
-                if e.local_name().as_ref() == b"txBody" {
-                    parse_text_body(reader, rels, &mut paragraphs);
-                }
+            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"txBody" => {
+                parse_text_body(reader, rels, &mut paragraphs);
             }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == end_tag {
-                    break;
+            Ok(Event::Start(ref e) | Event::Empty(ref e)) => match e.local_name().as_ref() {
+                b"cNvPr" => {
+                    props.name = unescaped_attr(e, b"name").unwrap_or_default();
+                    props.descr = unescaped_attr(e, b"descr").unwrap_or_default();
                 }
+                b"hlinkClick" => {
+                    props.rid = get_attr(e, b"r:id").unwrap_or_default();
+                    props.action = get_attr(e, b"action").unwrap_or_default();
+                    clickable = true;
+                }
+                b"prstGeom" => props.preset = get_attr(e, b"prst").unwrap_or_default(),
+                _ => {}
+            },
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == end_tag => {
+                break;
             }
             Ok(Event::Eof) | Err(_) => break,
             _ => {}
         }
     }
 
-    if paragraphs.is_empty() {
+    let link = clickable.then_some(props);
+    if paragraphs.is_empty() && link.is_none() {
         None
     } else {
-        Some(ShapeText { paragraphs })
+        Some(ShapeText { paragraphs, link })
     }
 }
 
@@ -346,18 +560,14 @@ fn parse_shape(reader: &mut Reader<&[u8]>, rels: &Rels, end_tag: &[u8]) -> Optio
 fn parse_text_body(reader: &mut Reader<&[u8]>, rels: &Rels, paragraphs: &mut Vec<Paragraph>) {
     loop {
         match reader.read_event() {
-            Ok(Event::Start(ref e)) => {
-                if e.local_name().as_ref() == b"p" {
-                    let para = parse_para(reader, rels);
-                    if !para.runs.is_empty() {
-                        paragraphs.push(para);
-                    }
+            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"p" => {
+                let para = parse_para(reader, rels);
+                if !para.runs.is_empty() {
+                    paragraphs.push(para);
                 }
             }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == b"txBody" {
-                    break;
-                }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"txBody" => {
+                break;
             }
             Ok(Event::Eof) | Err(_) => break,
             _ => {}
@@ -427,10 +637,8 @@ fn parse_para(reader: &mut Reader<&[u8]>, rels: &Rels) -> Paragraph {
                     _ => {}
                 }
             }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == b"p" {
-                    break;
-                }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"p" => {
+                break;
             }
             Ok(Event::Eof) | Err(_) => break,
             _ => {}
@@ -536,6 +744,7 @@ fn parse_text_run(reader: &mut Reader<&[u8]>, rels: &Rels, end_tag: Option<&[u8]
     let mut italic = false;
     let mut link_url: Option<String> = None;
     let mut font_size: Option<u32> = None;
+    let mut symbol_font: Option<String> = None;
 
     loop {
         match reader.read_event() {
@@ -545,8 +754,8 @@ fn parse_text_run(reader: &mut Reader<&[u8]>, rels: &Rels, end_tag: Option<&[u8]
                     b"rPr" => {
                         // Read attributes from the <a:rPr> start tag
                         read_rpr_attrs(e, &mut bold, &mut italic, &mut font_size);
-                        // Parse children for hyperlinks
-                        parse_run_props_children(reader, &mut link_url, rels);
+                        // Parse children for hyperlinks and symbol fonts
+                        parse_run_props_children(reader, &mut link_url, &mut symbol_font, rels);
                     }
                     b"t" => {
                         if let Ok(Event::Text(t)) = reader.read_event() {
@@ -565,16 +774,18 @@ fn parse_text_run(reader: &mut Reader<&[u8]>, rels: &Rels, end_tag: Option<&[u8]
                     read_rpr_attrs(e, &mut bold, &mut italic, &mut font_size);
                 }
             }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == end_name {
-                    break;
-                }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == end_name => {
+                break;
             }
             Ok(Event::Eof) | Err(_) => break,
             _ => {}
         }
     }
 
+    if let Some(font) = symbol_font {
+        text = symbols::map_symbol_text(&font, &text);
+    }
+
     TextRun {
         text,
         bold,
@@ -605,10 +816,12 @@ fn read_rpr_attrs(
     }
 }
 
-/// Parse children of `<a:rPr>` to find hyperlink references.
+/// Parse children of `<a:rPr>` to find hyperlink references and symbol
+/// fonts (`<a:sym typeface="Wingdings"/>`, or a Symbol `<a:latin>` face).
 fn parse_run_props_children(
     reader: &mut Reader<&[u8]>,
     link_url: &mut Option<String>,
+    symbol_font: &mut Option<String>,
     rels: &Rels,
 ) {
     let mut depth = 1u32;
@@ -625,15 +838,23 @@ fn parse_run_props_children(
                 }
                 depth += 1;
             }
-            Ok(Event::Empty(ref e)) => {
-                if e.local_name().as_ref() == b"hlinkClick" {
+            Ok(Event::Empty(ref e)) => match e.local_name().as_ref() {
+                b"hlinkClick" => {
                     if let Some(rid) = get_attr(e, b"r:id") {
                         if let Some(url) = rels.get(&rid) {
                             *link_url = Some(url.clone());
                         }
                     }
                 }
-            }
+                b"sym" | b"latin" => {
+                    if let Some(face) = get_attr(e, b"typeface") {
+                        if symbols::is_symbol_font(&face) {
+                            *symbol_font = Some(face);
+                        }
+                    }
+                }
+                _ => {}
+            },
             Ok(Event::End(ref e)) => {
                 if e.local_name().as_ref() == b"rPr" {
                     break;
@@ -919,6 +1140,7 @@ mod tests {
                         bullet: BulletKind::None,
                     },
                 ],
+                link: None,
             }],
             images: Vec::new(),
         }];
@@ -946,6 +1168,7 @@ mod tests {
                     heading_level: 0,
                     bullet: BulletKind::None,
                 }],
+                link: None,
             }],
             images: Vec::new(),
         }];
@@ -971,6 +1194,7 @@ mod tests {
                         heading_level: 0,
                         bullet: BulletKind::None,
                     }],
+                    link: None,
                 }],
                 images: Vec::new(),
             },
@@ -988,6 +1212,7 @@ mod tests {
                         heading_level: 0,
                         bullet: BulletKind::None,
                     }],
+                    link: None,
                 }],
                 images: Vec::new(),
             },
@@ -1019,6 +1244,7 @@ mod tests {
                         heading_level: 1,
                         bullet: BulletKind::None,
                     }],
+                    link: None,
                 }],
                 images: Vec::new(),
             },
@@ -1036,6 +1262,7 @@ mod tests {
                         heading_level: 0,
                         bullet: BulletKind::None,
                     }],
+                    link: None,
                 }],
                 images: Vec::new(),
             },
@@ -1106,6 +1333,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_slide_symbol_font_run() {
+        let xml = r#"<p:sld xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main">
+            <p:cSld>
+                <p:spTree>
+                    <p:sp>
+                        <p:txBody>
+                            <a:p>
+                                <a:r>
+                                    <a:rPr lang="en-US">
+                                        <a:sym typeface="Wingdings" pitchFamily="2" charset="2"/>
+                                    </a:rPr>
+                                    <a:t>&#xF0FC;</a:t>
+                                </a:r>
+                            </a:p>
+                        </p:txBody>
+                    </p:sp>
+                </p:spTree>
+            </p:cSld>
+        </p:sld>"#;
+
+        let shapes = parse_slide_xml(xml, &HashMap::new());
+        assert_eq!(shapes[0].paragraphs[0].runs[0].text, "✓");
+    }
+
     #[test]
     fn parse_slide_empty() {
         let xml = r#"<p:sld xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main">
@@ -1115,6 +1367,101 @@ mod tests {
         assert!(shapes.is_empty());
     }
 
+    // ── shape links ──────────────────────────────────────────────
+
+    #[test]
+    fn parse_slide_button_and_picture_links() {
+        let xml = r#"<p:sld xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+            <p:cSld>
+                <p:spTree>
+                    <p:sp>
+                        <p:nvSpPr>
+                            <p:cNvPr id="4" name="Action Button: Forward or Next 3">
+                                <a:hlinkClick r:id="" action="ppaction://hlinkshowjump?jump=nextslide" highlightClick="1"/>
+                            </p:cNvPr>
+                        </p:nvSpPr>
+                        <p:spPr><a:prstGeom prst="actionButtonForwardNext"/></p:spPr>
+                    </p:sp>
+                    <p:pic>
+                        <p:nvPicPr>
+                            <p:cNvPr id="5" name="Picture 4" descr="Company logo">
+                                <a:hlinkClick r:id="rId3"/>
+                            </p:cNvPr>
+                        </p:nvPicPr>
+                        <p:blipFill><a:blip r:embed="rId2"/></p:blipFill>
+                    </p:pic>
+                    <p:pic>
+                        <p:nvPicPr><p:cNvPr id="6" name="Picture 5"/></p:nvPicPr>
+                    </p:pic>
+                </p:spTree>
+            </p:cSld>
+        </p:sld>"#;
+
+        let shapes = parse_slide_xml(xml, &HashMap::new());
+        assert_eq!(shapes.len(), 2); // the unlinked picture is dropped
+        let button = shapes[0].link.as_ref().unwrap();
+        assert_eq!(button.kind, "Button");
+        assert_eq!(button.label(), "Next");
+        assert_eq!(button.jump(2, 5), Some(3));
+        assert_eq!(button.jump(5, 5), None);
+        let picture = shapes[1].link.as_ref().unwrap();
+        assert_eq!(picture.kind, "Image");
+        assert_eq!(picture.rid, "rId3");
+        assert_eq!(picture.label(), "Company logo");
+    }
+
+    #[test]
+    fn shape_link_label_falls_back_to_name() {
+        let link = ShapeLink {
+            kind: "Button",
+            name: "Rounded Rectangle 7".into(),
+            preset: "roundRect".into(),
+            action: "ppaction://hlinksldjump".into(),
+            ..ShapeLink::default()
+        };
+        assert_eq!(link.label(), "Rounded Rectangle 7");
+        assert_eq!(link.jump(1, 3), None);
+    }
+
+    #[test]
+    fn apply_link_renders_button_line_or_links_text() {
+        let button = ShapeLink {
+            kind: "Button",
+            preset: "actionButtonHome".into(),
+            ..ShapeLink::default()
+        };
+        let mut empty = ShapeText {
+            paragraphs: Vec::new(),
+            link: None,
+        };
+        empty.apply_link(&button, "#slide-1");
+        let mut text = ShapeText {
+            paragraphs: vec![Paragraph {
+                runs: vec![TextRun {
+                    text: "Docs".into(),
+                    bold: false,
+                    italic: false,
+                    link_url: None,
+                    font_size: None,
+                }],
+                heading_level: 0,
+                bullet: BulletKind::None,
+            }],
+            link: None,
+        };
+        text.apply_link(&button, "https://example.com/docs");
+        let slides = vec![Slide {
+            number: 1,
+            shapes: vec![empty, text],
+            images: Vec::new(),
+        }];
+        assert_eq!(
+            render_markdown(&slides),
+            "[Button: Home](#slide-1)\n\n\n[Docs](https://example.com/docs)\n\n"
+        );
+        assert_eq!(render_plain(&slides), "Button: Home\nDocs\n");
+    }
+
     // ── bullet parsing ────────────────────────────────────────────
 
     #[test]
@@ -1209,6 +1556,7 @@ mod tests {
                         bullet: BulletKind::Bullet(1),
                     },
                 ],
+                link: None,
             }],
             images: Vec::new(),
         }];
@@ -1248,6 +1596,7 @@ mod tests {
                         bullet: BulletKind::Numbered(0),
                     },
                 ],
+                link: None,
             }],
             images: Vec::new(),
         }];
@@ -1286,6 +1635,7 @@ mod tests {
                         bullet: BulletKind::Bullet(1),
                     },
                 ],
+                link: None,
             }],
             images: Vec::new(),
         }];
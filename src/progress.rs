@@ -0,0 +1,109 @@
+//! Progress bar for long extractions.
+//!
+//! Parsing a workbook or PDF of a few hundred megabytes takes a while, and
+//! without output it looks as if batdoc has hung. When the CLI turns it
+//! on, the spreadsheet and PDF parsers report each sheet or page as they
+//! start on it, and a one-line bar on stderr shows how far they have got.
+//! The bar is cleared before the document is written.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use console::Term;
+
+/// Inputs at least this big get a progress bar.
+pub(crate) const MIN_BYTES: usize = 16 * 1024 * 1024;
+
+/// Cells in the bar itself.
+const BAR_WIDTH: usize = 20;
+
+/// Time between redraws, so many small pages don't flood the terminal.
+const REDRAW: Duration = Duration::from_millis(100);
+
+/// Where parsers report their progress. The default reports nowhere;
+/// clones share the bar.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Progress(Option<Arc<Mutex<Bar>>>);
+
+#[derive(Debug)]
+struct Bar {
+    label: String,
+    term: Term,
+    /// When the bar was last drawn, if it is on screen.
+    drawn: Option<Instant>,
+}
+
+impl Progress {
+    /// A bar on stderr, naming the document `label`.
+    pub(crate) fn stderr(label: &str) -> Self {
+        Self(Some(Arc::new(Mutex::new(Bar {
+            label: label.to_string(),
+            term: Term::stderr(),
+            drawn: None,
+        }))))
+    }
+
+    /// Report that the parser is starting on `unit` number `current` of
+    /// `total`, such as page 3 of 120.
+    pub(crate) fn step(&self, unit: &str, current: usize, total: usize) {
+        let Some(bar) = &self.0 else { return };
+        let Ok(mut bar) = bar.lock() else { return };
+        if bar.drawn.is_some_and(|at| at.elapsed() < REDRAW) {
+            return;
+        }
+        let width = usize::from(bar.term.size().1).saturating_sub(1);
+        let text = line(&bar.label, unit, current, total, width);
+        if bar
+            .term
+            .clear_line()
+            .and_then(|()| bar.term.write_str(&text))
+            .is_ok()
+        {
+            bar.drawn = Some(Instant::now());
+        }
+    }
+
+    /// Take the bar off the screen, if it was drawn.
+    pub(crate) fn finish(&self) {
+        let Some(bar) = &self.0 else { return };
+        let Ok(mut bar) = bar.lock() else { return };
+        if bar.drawn.take().is_some() {
+            let _ = bar.term.clear_line();
+        }
+    }
+}
+
+/// `label: unit current/total [####      ]`, with the bar filled for the
+/// units already done and the label shortened to fit in `width` columns.
+fn line(label: &str, unit: &str, current: usize, total: usize, width: usize) -> String {
+    let done = current.saturating_sub(1).min(total);
+    let filled = (done * BAR_WIDTH).checked_div(total).unwrap_or(0);
+    let status = format!(
+        "{unit} {current}/{total} [{}{}]",
+        "#".repeat(filled),
+        " ".repeat(BAR_WIDTH - filled)
+    );
+    let room = width.saturating_sub(status.len() + 2);
+    format!("{}: {status}", console::truncate_str(label, room, "…"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bar_fills_with_units_done() {
+        assert_eq!(
+            line("big.pdf", "page", 1, 4, 80),
+            "big.pdf: page 1/4 [                    ]"
+        );
+        assert_eq!(
+            line("big.pdf", "page", 3, 4, 80),
+            "big.pdf: page 3/4 [##########          ]"
+        );
+        assert_eq!(
+            line("workbook.xlsx", "sheet", 2, 2, 38),
+            "wor…: sheet 2/2 [##########          ]"
+        );
+    }
+}
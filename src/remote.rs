@@ -0,0 +1,162 @@
+//! URL arguments: `file://` URLs and, with `--allow-remote`, `http(s)://`.
+//!
+//! Remote documents are downloaded with `curl(1)` rather than an HTTP client
+//! library, which keeps TLS and proxy configuration in the hands of the
+//! system tool users already rely on for the `curl | batdoc` workflow.
+//! Downloads are limited to the same size cap as local files, and redirects
+//! may only lead to other HTTP(S) URLs.
+
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::error::{BatdocError, Result};
+
+/// Whether `arg` is an `http://` or `https://` URL.
+pub(crate) fn is_remote(arg: &str) -> bool {
+    let lower = arg.get(..8).unwrap_or(arg).to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// The local path named by a `file://` URL (`file:///tmp/a.doc` or
+/// `file://localhost/tmp/a.doc`), percent-decoded. `None` for anything
+/// else, including URLs naming another host.
+pub(crate) fn file_url_path(arg: &str) -> Option<PathBuf> {
+    let scheme = arg.get(..7)?;
+    if !scheme.eq_ignore_ascii_case("file://") {
+        return None;
+    }
+    let rest = &arg[7..];
+    let path = rest.strip_prefix("localhost").unwrap_or(rest);
+    if !path.starts_with('/') {
+        return None;
+    }
+    let bytes = percent_decode(path);
+    // Windows: file:///C:/dir/a.doc names C:/dir/a.doc
+    #[cfg(windows)]
+    let bytes = if bytes.get(2) == Some(&b':') {
+        bytes[1..].to_vec()
+    } else {
+        bytes
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Some(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+    }
+    #[cfg(not(unix))]
+    {
+        Some(PathBuf::from(String::from_utf8_lossy(&bytes).into_owned()))
+    }
+}
+
+/// Decode `%XX` escapes; malformed escapes are kept as-is.
+pub(crate) fn percent_decode(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Download `url` with curl, failing if the body exceeds `max_size` bytes.
+pub(crate) fn fetch(url: &str, max_size: usize) -> Result<Vec<u8>> {
+    let mut child = Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--proto", "=http,https", "--proto-redir", "=http,https"])
+        .args(["--connect-timeout", "30"])
+        .arg("--max-filesize")
+        .arg(max_size.to_string())
+        .arg("--")
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| std::io::Error::new(e.kind(), format!("fetching URLs requires curl: {e}")))?;
+
+    // The server may not announce a length, so cap the read as well
+    let mut data = Vec::new();
+    let stdout = child.stdout.take().expect("stdout is piped");
+    stdout.take(max_size as u64 + 1).read_to_end(&mut data)?;
+    if data.len() > max_size {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(BatdocError::too_large(None, max_size));
+    }
+
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_string(&mut stderr);
+    }
+    let status = child.wait()?;
+    match status.code() {
+        Some(0) => Ok(data),
+        // CURLE_FILESIZE_EXCEEDED: the announced length is over the cap
+        Some(63) => Err(BatdocError::too_large(None, max_size)),
+        _ => {
+            let msg = stderr.trim().trim_start_matches("curl: ");
+            Err(BatdocError::Io(std::io::Error::other(if msg.is_empty() {
+                format!("download failed ({status})")
+            } else {
+                msg.to_string()
+            })))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── is_remote ────────────────────────────────────────────────
+
+    #[test]
+    fn remote_schemes() {
+        assert!(is_remote("https://example.com/a.docx"));
+        assert!(is_remote("HTTP://example.com/a.docx"));
+        assert!(!is_remote("file:///tmp/a.docx"));
+        assert!(!is_remote("http.docx"));
+        assert!(!is_remote("ftp://example.com/a.doc"));
+    }
+
+    // ── file_url_path ────────────────────────────────────────────
+
+    #[cfg(unix)]
+    #[test]
+    fn file_urls() {
+        assert_eq!(
+            file_url_path("file:///tmp/my%20report.docx"),
+            Some(PathBuf::from("/tmp/my report.docx"))
+        );
+        assert_eq!(
+            file_url_path("FILE://localhost/a.pdf"),
+            Some(PathBuf::from("/a.pdf"))
+        );
+        assert_eq!(file_url_path("file://server/share/a.pdf"), None);
+        assert_eq!(file_url_path("/tmp/a.pdf"), None);
+    }
+
+    // ── percent_decode ───────────────────────────────────────────
+
+    #[test]
+    fn malformed_escapes_kept() {
+        assert_eq!(percent_decode("a%2Fb%zz%4"), b"a/b%zz%4");
+        assert_eq!(percent_decode("%C3%A9"), "é".as_bytes());
+    }
+}
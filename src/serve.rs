@@ -0,0 +1,418 @@
+//! HTTP server mode (`batdoc serve`).
+//!
+//! A small HTTP/1.1 server on `std::net`, so that a document-ingestion
+//! service can post documents to a long-running batdoc instead of
+//! starting a process per file. It is meant for a trusted network (it
+//! listens on localhost unless told otherwise) and serves one request per
+//! connection: a `POST /` carrying the document, either as the raw body or
+//! as the file of a `multipart/form-data` upload. `GET /` answers with a
+//! short description, which also serves as a health check.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use crate::error::BatdocError;
+use crate::remote;
+
+/// Where `batdoc serve` listens unless `--listen` says otherwise.
+pub(crate) const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+
+/// Longest request line and headers accepted.
+const MAX_HEAD: usize = 16 * 1024;
+
+/// How long a client may take to send its request or read the response.
+const TIMEOUT: Duration = Duration::from_mins(1);
+
+/// A document posted to the server.
+#[derive(Debug, Default)]
+pub(crate) struct Request {
+    /// Decoded query parameters, in order.
+    pub(crate) query: Vec<(String, String)>,
+    /// The file name of a multipart upload.
+    pub(crate) filename: Option<String>,
+    /// The document.
+    pub(crate) body: Vec<u8>,
+}
+
+impl Request {
+    /// The value of query parameter `name`, if given.
+    pub(crate) fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// What the server sends back.
+#[derive(Debug)]
+pub(crate) struct Response {
+    pub(crate) status: u16,
+    pub(crate) content_type: &'static str,
+    pub(crate) body: Vec<u8>,
+}
+
+impl Response {
+    pub(crate) fn new(status: u16, content_type: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            content_type,
+            body: body.into(),
+        }
+    }
+
+    /// A JSON object, on a line of its own.
+    pub(crate) fn json(status: u16, object: &str) -> Self {
+        Self::new(status, "application/json", format!("{object}\n"))
+    }
+
+    /// A plain-text response, such as an error in the request itself.
+    fn text(status: u16, message: &str) -> Self {
+        Self::new(status, "text/plain; charset=utf-8", format!("{message}\n"))
+    }
+}
+
+/// The HTTP status for a document that could not be converted.
+pub(crate) fn error_status(err: &BatdocError) -> u16 {
+    match err.code() {
+        "usage" => 400,
+        "too_large" => 413,
+        "unsupported" => 415,
+        "encrypted" | "wrong_password" | "corrupt" | "empty" => 422,
+        _ => 500,
+    }
+}
+
+/// Serve requests on `listener` forever, `workers` at a time, passing each
+/// posted document of up to `max_body` bytes to `convert`. With `log`,
+/// each request is reported on stderr.
+pub(crate) fn run(
+    listener: &TcpListener,
+    workers: usize,
+    max_body: usize,
+    log: bool,
+    convert: &(dyn Fn(&Request) -> Response + Sync),
+) {
+    std::thread::scope(|scope| {
+        for _ in 0..workers.max(1) {
+            scope.spawn(|| loop {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        if let Err(e) = connection(&stream, max_body, log, convert) {
+                            if log {
+                                eprintln!("batdoc: serve: {e}");
+                            }
+                        }
+                    }
+                    Err(e) => eprintln!("batdoc: serve: {e}"),
+                }
+            });
+        }
+    });
+}
+
+/// Read one request from `stream` and answer it.
+fn connection(
+    stream: &TcpStream,
+    max_body: usize,
+    log: bool,
+    convert: &(dyn Fn(&Request) -> Response + Sync),
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let head = read_head(&mut reader)?;
+    let response = match Head::parse(&head) {
+        Err(response) => response,
+        Ok(head) => match (head.method.as_str(), head.path.as_str()) {
+            ("POST", "/") => match read_body(&mut reader, stream, &head, max_body) {
+                Ok(request) => convert(&request),
+                Err(response) => response,
+            },
+            ("GET" | "HEAD", "/") => Response::text(
+                200,
+                &format!(
+                    "batdoc {}: POST a document to / (?to=markdown, plain, org, \
+                     csv, xml, or json)",
+                    env!("CARGO_PKG_VERSION")
+                ),
+            ),
+            (_, "/") => Response::text(405, "only GET and POST are supported"),
+            _ => Response::text(404, "not found; POST documents to /"),
+        },
+    };
+    if log {
+        let line = head.lines().next().unwrap_or_default();
+        eprintln!("batdoc: serve: {line} -> {}", response.status);
+    }
+    write_response(stream, &response)
+}
+
+/// The request line and headers, without the blank line that ends them.
+fn read_head(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut head = Vec::new();
+    loop {
+        let start = head.len();
+        let read = reader
+            .by_ref()
+            .take((MAX_HEAD - start) as u64)
+            .read_until(b'\n', &mut head)?;
+        if read == 0 || !head.ends_with(b"\n") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request headers are incomplete or too long",
+            ));
+        }
+        if head[start..].trim_ascii().is_empty() {
+            head.truncate(start);
+            return Ok(String::from_utf8_lossy(&head).into_owned());
+        }
+    }
+}
+
+/// The parts of a request head that matter here.
+#[derive(Debug, Default)]
+struct Head {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    content_length: Option<usize>,
+    content_type: Option<String>,
+    chunked: bool,
+    expect_continue: bool,
+}
+
+impl Head {
+    fn parse(head: &str) -> Result<Self, Response> {
+        let mut lines = head.lines();
+        let mut parts = lines.next().unwrap_or_default().split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+            return Err(Response::text(400, "malformed request line"));
+        };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let mut parsed = Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: parse_query(query),
+            ..Self::default()
+        };
+        for line in lines {
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "content-length" => {
+                    parsed.content_length = Some(
+                        value
+                            .parse()
+                            .map_err(|_| Response::text(400, "invalid Content-Length"))?,
+                    );
+                }
+                "content-type" => parsed.content_type = Some(value.to_string()),
+                "transfer-encoding" => parsed.chunked = true,
+                "expect" => parsed.expect_continue = value.eq_ignore_ascii_case("100-continue"),
+                _ => {}
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Read the body of a `POST`, and take the document out of it.
+fn read_body(
+    reader: &mut impl Read,
+    mut stream: &TcpStream,
+    head: &Head,
+    max_body: usize,
+) -> Result<Request, Response> {
+    let length = match head.content_length {
+        Some(length) if !head.chunked => length,
+        _ => {
+            return Err(Response::text(
+                411,
+                "send the document with a Content-Length",
+            ))
+        }
+    };
+    if length > max_body {
+        let err = BatdocError::too_large(Some(length), max_body);
+        return Err(Response::text(413, &err.to_string()));
+    }
+    // curl waits for this before sending a large body
+    if head.expect_continue {
+        let _ = stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n");
+    }
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| Response::text(400, "request body is shorter than its Content-Length"))?;
+
+    let boundary = head.content_type.as_deref().and_then(multipart_boundary);
+    let (filename, body) = match boundary {
+        Some(boundary) => multipart_file(&body, &boundary)
+            .map(|(filename, data)| (filename, data.to_vec()))
+            .ok_or_else(|| Response::text(400, "no file in the multipart upload"))?,
+        None => (None, body),
+    };
+    Ok(Request {
+        query: head.query.clone(),
+        filename,
+        body,
+    })
+}
+
+fn write_response(mut stream: &TcpStream, response: &Response) -> io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+const fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Content Too Large",
+        415 => "Unsupported Media Type",
+        422 => "Unprocessable Content",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Decode `a=1&b=two%20words` into pairs.
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    let decode = |s: &str| {
+        String::from_utf8_lossy(&remote::percent_decode(&s.replace('+', " "))).into_owned()
+    };
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+/// The boundary of a `multipart/form-data` content type.
+fn multipart_boundary(content_type: &str) -> Option<String> {
+    let (kind, params) = content_type.split_once(';')?;
+    if !kind.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params.split(';').find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("boundary")
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// The file name and content of the first file in a multipart body, or
+/// of its first part if none is a file.
+fn multipart_file<'a>(body: &'a [u8], boundary: &str) -> Option<(Option<String>, &'a [u8])> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = Vec::new();
+    let mut rest = &body[find(body, delimiter.as_bytes())? + delimiter.len()..];
+    // Each part is "\r\n" headers "\r\n\r\n" content "\r\n--boundary"
+    while let Some(part) = rest.strip_prefix(b"\r\n") {
+        let end = find(part, format!("\r\n{delimiter}").as_bytes())?;
+        let (headers, content) = part[..end].split_at(find(&part[..end], b"\r\n\r\n")?);
+        parts.push((content_disposition_filename(headers), &content[4..]));
+        rest = &part[end + 2 + delimiter.len()..];
+    }
+    let index = parts.iter().position(|(filename, _)| filename.is_some());
+    parts.into_iter().nth(index.unwrap_or(0))
+}
+
+/// The `filename` of a part's `Content-Disposition` header.
+fn content_disposition_filename(headers: &[u8]) -> Option<String> {
+    let headers = String::from_utf8_lossy(headers);
+    let disposition = headers.lines().find(|line| {
+        line.get(..20)
+            .is_some_and(|name| name.eq_ignore_ascii_case("content-disposition:"))
+    })?;
+    disposition.split(';').find_map(|param| {
+        let value = param.trim().strip_prefix("filename=")?;
+        Some(value.trim_matches('"').to_string())
+    })
+}
+
+/// Position of the first `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── request head ─────────────────────────────────────────────
+
+    #[test]
+    fn head_fields() {
+        let head = Head::parse(
+            "POST /?to=json&name=a%20b.docx HTTP/1.1\r\nHost: x\r\n\
+             content-length: 12\r\nExpect: 100-continue\r\n",
+        )
+        .unwrap();
+        assert_eq!((head.method.as_str(), head.path.as_str()), ("POST", "/"));
+        assert_eq!(
+            head.query,
+            [
+                ("to".into(), "json".into()),
+                ("name".into(), "a b.docx".into())
+            ]
+        );
+        assert_eq!(head.content_length, Some(12));
+        assert!(head.expect_continue && !head.chunked);
+        assert_eq!(Head::parse("nonsense").unwrap_err().status, 400);
+    }
+
+    #[test]
+    fn head_ends_at_blank_line() {
+        let mut input = &b"GET / HTTP/1.1\r\nHost: x\r\n\r\nbody"[..];
+        assert_eq!(
+            read_head(&mut input).unwrap(),
+            "GET / HTTP/1.1\r\nHost: x\r\n"
+        );
+        assert_eq!(input, b"body");
+        assert!(read_head(&mut &b"GET / HTTP/1.1\r\n"[..]).is_err());
+    }
+
+    // ── multipart ────────────────────────────────────────────────
+
+    #[test]
+    fn multipart_upload() {
+        let content_type = "multipart/form-data; boundary=\"XyZ\"";
+        let boundary = multipart_boundary(content_type).unwrap();
+        let body = b"--XyZ\r\nContent-Disposition: form-data; name=\"to\"\r\n\r\njson\r\n\
+                     --XyZ\r\nContent-Disposition: form-data; name=\"file\"; \
+                     filename=\"a.docx\"\r\nContent-Type: application/octet-stream\r\n\r\n\
+                     PK\x03\x04\r\ndata\r\n--XyZ--\r\n";
+        let (filename, data) = multipart_file(body, &boundary).unwrap();
+        assert_eq!(filename.as_deref(), Some("a.docx"));
+        assert_eq!(data, b"PK\x03\x04\r\ndata");
+        assert_eq!(multipart_boundary("application/octet-stream"), None);
+    }
+
+    #[test]
+    fn error_statuses() {
+        assert_eq!(error_status(&BatdocError::Encrypted), 422);
+        assert_eq!(error_status(&BatdocError::Unsupported("x".into())), 415);
+        assert_eq!(error_status(&BatdocError::too_large(Some(9), 1)), 413);
+    }
+}
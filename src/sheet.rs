@@ -1,18 +1,392 @@
 //! Shared spreadsheet rendering used by both `.xlsx` and `.xls` parsers.
 //!
 //! Provides the `Sheet` struct (a named 2D grid of cell values) and renderers
-//! that produce either tab-separated plain text or markdown tables.
+//! that produce tab-separated plain text, markdown tables, or CSV. Comments
+//! on cells (`--comments`) are marked `[c1]` in the cell and listed after
+//! the sheet or in a section at the end. In markdown, Excel tables are
+//! shown apart from the rest of their sheet, under their names.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+use crate::chart::Chart;
+use crate::error::{BatdocError, Result};
+use crate::options::{CellNewline, Comments, Options};
+use crate::pivot::Pivot;
 
 /// A parsed worksheet: a name and a 2D grid of cell values.
 #[derive(Debug)]
 pub(crate) struct Sheet {
     pub(crate) name: String,
     pub(crate) rows: Vec<Vec<String>>,
+    /// Comments on cells, read only when `--comments` asks for them.
+    pub(crate) comments: Vec<Comment>,
+    /// Charts drawn on the sheet, shown after its cells.
+    pub(crate) charts: Vec<Chart>,
+    /// Excel tables on the sheet, shown as tables of their own in
+    /// markdown.
+    pub(crate) tables: Vec<NamedTable>,
+    /// Rows and columns cut off by `--max-rows` and `--max-cols`.
+    pub(crate) cut: Cut,
+    /// Pictures placed on the sheet, as markdown image references in the
+    /// order of the cells they are anchored at; read only for `--images`.
+    pub(crate) images: Vec<String>,
+    /// Pivot tables on the sheet, shown by their layout after its charts.
+    pub(crate) pivots: Vec<Pivot>,
+}
+
+/// How many rows and columns holding cells were cut off the end of a
+/// sheet, noted after it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Cut {
+    pub(crate) rows: usize,
+    pub(crate) cols: usize,
+}
+
+impl Cut {
+    /// `… (N more rows, M more columns)`, or `None` if nothing was cut.
+    fn notice(self) -> Option<String> {
+        let count = |n: usize, what: &str| {
+            (n > 0).then(|| format!("{n} more {what}{}", if n == 1 { "" } else { "s" }))
+        };
+        let parts: Vec<String> = [count(self.rows, "row"), count(self.cols, "column")]
+            .into_iter()
+            .flatten()
+            .collect();
+        (!parts.is_empty()).then(|| format!("… ({})", parts.join(", ")))
+    }
+}
+
+/// An Excel table (`ListObject`): a named range of a sheet with a header
+/// row naming its columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct NamedTable {
+    pub(crate) name: String,
+    /// Grid rows and columns it covers, header row included.
+    pub(crate) rows: RangeInclusive<usize>,
+    pub(crate) cols: RangeInclusive<usize>,
+    /// The names of its columns.
+    pub(crate) columns: Vec<String>,
+    /// Whether its first row is the header row; tables can hide it.
+    pub(crate) header_row: bool,
+}
+
+impl NamedTable {
+    /// The table as rows of `grid`, its column names first.
+    fn grid_rows(&self, grid: &[Vec<String>]) -> Vec<Vec<String>> {
+        let ncols = self.cols.end() + 1 - self.cols.start();
+        let mut header = self.columns.clone();
+        header.resize(ncols, String::new());
+        let first = self.rows.start() + usize::from(self.header_row);
+        let body = grid
+            .get(first..=*self.rows.end())
+            .or_else(|| grid.get(first..))
+            .unwrap_or_default();
+        let mut rows = vec![header];
+        rows.extend(body.iter().map(|row| {
+            self.cols
+                .clone()
+                .map(|col| row.get(col).cloned().unwrap_or_default())
+                .collect()
+        }));
+        rows
+    }
+}
+
+/// A comment (note) on a cell, or a thread of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Comment {
+    /// 0-based row and column of the cell in the sheet's grid.
+    pub(crate) row: usize,
+    pub(crate) col: usize,
+    /// The cell's name in the workbook, such as `B3`. Hidden rows and
+    /// columns left out of the grid do not change it.
+    pub(crate) cell: String,
+    pub(crate) author: String,
+    pub(crate) text: String,
+    /// Its `[cN]` mark, once numbered by [`mark_comments`].
+    pub(crate) number: usize,
+    /// The sheet it is on, when listed with other sheets' comments.
+    pub(crate) sheet: Option<String>,
+}
+
+impl Comment {
+    /// The line listing the comment: `[c1] Ann on B3: text`, with the
+    /// author in bold for markdown.
+    fn note(&self, markdown: bool) -> String {
+        let author = if self.author.is_empty() {
+            "Comment"
+        } else {
+            &self.author
+        };
+        let author = if markdown {
+            format!("**{author}**")
+        } else {
+            author.to_string()
+        };
+        let cell = self.cell.clone();
+        let place = match &self.sheet {
+            Some(sheet) if sheet.contains(|c: char| !c.is_alphanumeric() && c != '_') => {
+                format!("'{}'!{cell}", sheet.replace('\'', "''"))
+            }
+            Some(sheet) => format!("{sheet}!{cell}"),
+            None => cell,
+        };
+        let text = self.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        format!("[c{}] {author} on {place}: {text}", self.number)
+    }
+}
+
+/// The A1-style name of a cell: row 2, column 27 is `AB3`.
+pub(crate) fn cell_name(row: usize, col: usize) -> String {
+    let mut letters = String::new();
+    let mut n = col + 1;
+    while n > 0 {
+        let rem = u8::try_from((n - 1) % 26).unwrap_or(0);
+        letters.insert(0, char::from(b'A' + rem));
+        n = (n - 1) / 26;
+    }
+    format!("{letters}{}", row + 1)
+}
+
+/// The anchor of the `## name` heading of a sheet in markdown, as GitHub
+/// makes it: lowercased, with spaces turned into hyphens and punctuation
+/// other than `-` and `_` dropped. Links within a workbook point at it.
+pub(crate) fn anchor(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Write a linked cell as `[value](url)`, unless it is empty.
+pub(crate) fn link_cell(cell: &mut String, url: &str) {
+    if !cell.is_empty() {
+        *cell = format!("[{cell}]({url})");
+    }
+}
+
+/// Where a hyperlink to `location` within a workbook points: the heading
+/// of the sheet before its `!` (`Sheet2!A1`), or of `sheet` itself for a
+/// bare cell or range such as `$B$2`. A defined name has no sheet to
+/// point at.
+pub(crate) fn location_link(location: &str, sheet: &str) -> Option<String> {
+    let target = if let Some((name, _)) = location.rsplit_once('!') {
+        name.strip_prefix('\'')
+            .and_then(|name| name.strip_suffix('\''))
+            .map_or_else(|| name.to_string(), |name| name.replace("''", "'"))
+    } else {
+        let is_cell = |cell: &str| {
+            let cell = cell.replace('$', "");
+            let row = cell.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+            row.len() < cell.len() && !row.is_empty() && row.bytes().all(|b| b.is_ascii_digit())
+        };
+        if !location.split(':').all(is_cell) {
+            return None;
+        }
+        sheet.to_string()
+    };
+    (!target.is_empty()).then(|| format!("#{}", anchor(&target)))
+}
+
+// ── Comments ──────────────────────────────────────────────────────
+
+/// Number the comments on the sheets and mark their cells, then take
+/// them off the sheets if they go in a section at the end
+/// (`--comments end`). Returns the ones taken, for
+/// [`render_comment_section`].
+pub(crate) fn place_comments(sheets: &mut [Sheet], placement: Comments) -> Vec<Comment> {
+    mark_comments(sheets);
+    if placement == Comments::End {
+        take_comments(sheets)
+    } else {
+        Vec::new()
+    }
+}
+
+/// Number the comments through the sheets in order, cell by cell, and
+/// mark each commented cell with `[cN]`.
+fn mark_comments(sheets: &mut [Sheet]) {
+    let mut number = 0;
+    for sheet in sheets {
+        sheet.comments.sort_by_key(|c| (c.row, c.col));
+        for comment in &mut sheet.comments {
+            number += 1;
+            comment.number = number;
+            if sheet.rows.len() <= comment.row {
+                sheet.rows.resize(comment.row + 1, Vec::new());
+            }
+            let row = &mut sheet.rows[comment.row];
+            if row.len() <= comment.col {
+                row.resize(comment.col + 1, String::new());
+            }
+            let _ = write!(row[comment.col], "[c{number}]");
+        }
+    }
+}
+
+/// Take the comments off every sheet, for a section at the end
+/// (`--comments end`). Their cells are named with their sheet when there
+/// are several.
+fn take_comments(sheets: &mut [Sheet]) -> Vec<Comment> {
+    let multiple = sheets.len() > 1;
+    let mut comments = Vec::new();
+    for sheet in sheets {
+        for mut comment in sheet.comments.drain(..) {
+            if multiple {
+                comment.sheet = Some(sheet.name.clone());
+            }
+            comments.push(comment);
+        }
+    }
+    comments
+}
+
+/// The section listing comments taken off the sheets, or nothing if
+/// there are none.
+pub(crate) fn render_comment_section(comments: &[Comment], markdown: bool) -> String {
+    let mut out = String::new();
+    if comments.is_empty() {
+        return out;
+    }
+    if markdown {
+        out.push_str("## Comments\n\n");
+    } else {
+        out.push_str("\n--- Comments ---\n");
+    }
+    push_comments(&mut out, comments, markdown);
+    out
+}
+
+/// Append a line per comment; a paragraph each in markdown.
+fn push_comments(out: &mut String, comments: &[Comment], markdown: bool) {
+    for comment in comments {
+        out.push_str(&comment.note(markdown));
+        out.push_str(if markdown { "\n\n" } else { "\n" });
+    }
+}
+
+/// A boolean cell as Excel shows it.
+pub(crate) const fn bool_text(value: bool) -> &'static str {
+    if value {
+        "TRUE"
+    } else {
+        "FALSE"
+    }
+}
+
+/// Keep the sheets chosen with `--pick` (`opts.picked`), and then the one
+/// chosen with `--sheet` (`opts.sheet`).
+pub(crate) fn choose(mut sheets: Vec<Sheet>, opts: &Options) -> Result<Vec<Sheet>> {
+    if let Some(picked) = &opts.picked {
+        let mut position = 0;
+        sheets.retain(|_| {
+            position += 1;
+            picked.contains(&position)
+        });
+    }
+    select(sheets, opts.sheet.as_deref())
+}
+
+/// Cut each sheet to `opts.max_rows` rows and `opts.max_cols` columns,
+/// counted from its first cells that are not blank, noting how many more
+/// hold any. The comments on the cells cut off go with them, and Excel
+/// tables shrink to what is left of them.
+pub(crate) fn truncate(sheets: &mut [Sheet], opts: &Options) {
+    if opts.max_rows.is_none() && opts.max_cols.is_none() {
+        return;
+    }
+    let filled = |cell: &String| !cell.trim().is_empty();
+    for sheet in sheets {
+        let rows = &sheet.rows;
+        let Some(first_row) = rows.iter().position(|row| row.iter().any(filled)) else {
+            continue;
+        };
+        let last_row = rows
+            .iter()
+            .rposition(|row| row.iter().any(filled))
+            .unwrap_or(first_row);
+        let first_col = rows
+            .iter()
+            .filter_map(|row| row.iter().position(filled))
+            .min()
+            .unwrap_or(0);
+        let last_col = rows
+            .iter()
+            .filter_map(|row| row.iter().rposition(filled))
+            .max()
+            .unwrap_or(0);
+        let end = |first: usize, max: Option<usize>| max.map_or(usize::MAX, |n| first + n);
+        let (row_end, col_end) = (end(first_row, opts.max_rows), end(first_col, opts.max_cols));
+        sheet.cut = Cut {
+            rows: (last_row + 1).saturating_sub(row_end),
+            cols: (last_col + 1).saturating_sub(col_end),
+        };
+
+        sheet.rows.truncate(row_end);
+        for row in &mut sheet.rows {
+            row.truncate(col_end);
+        }
+        sheet
+            .comments
+            .retain(|comment| comment.row < row_end && comment.col < col_end);
+        sheet.tables.retain_mut(|table| {
+            let (top, left) = (*table.rows.start(), *table.cols.start());
+            if top >= row_end || left >= col_end {
+                return false;
+            }
+            table.rows = top..=*table.rows.end().min(&(row_end - 1));
+            table.cols = left..=*table.cols.end().min(&(col_end - 1));
+            table.columns.truncate(table.cols.end() + 1 - left);
+            true
+        });
+    }
+}
+
+/// Keep only the sheet chosen with `--sheet`: the one with that name
+/// (exactly, then ignoring case), or else the one at that 1-based position.
+/// Without a selector, every sheet is kept.
+pub(crate) fn select(sheets: Vec<Sheet>, selector: Option<&str>) -> Result<Vec<Sheet>> {
+    let Some(selector) = selector else {
+        return Ok(sheets);
+    };
+    let index = sheets
+        .iter()
+        .position(|s| s.name == selector)
+        .or_else(|| {
+            sheets
+                .iter()
+                .position(|s| s.name.to_lowercase() == selector.to_lowercase())
+        })
+        .or_else(|| {
+            selector
+                .parse::<usize>()
+                .ok()
+                .filter(|&n| n >= 1 && n <= sheets.len())
+                .map(|n| n - 1)
+        });
+    if let Some(i) = index {
+        return Ok(sheets.into_iter().skip(i).take(1).collect());
+    }
+    let names: Vec<&str> = sheets.iter().map(|s| s.name.as_str()).collect();
+    Err(BatdocError::Usage(format!(
+        "no sheet '{selector}' in the workbook (sheets: {})",
+        names.join(", ")
+    )))
 }
 
 // ── Plain text rendering ──────────────────────────────────────────
 
-pub(crate) fn render_plain(sheets: &[Sheet]) -> String {
+/// Tab-separated rows, one output line per spreadsheet row: line breaks
+/// inside a cell are replaced according to `newline`.
+pub(crate) fn render_plain(sheets: &[Sheet], newline: CellNewline) -> String {
     let mut out = String::new();
     let multiple = sheets.len() > 1;
 
@@ -22,21 +396,148 @@ pub(crate) fn render_plain(sheets: &[Sheet]) -> String {
         }
 
         if multiple {
-            if i > 0 {
+            push_plain_heading(&mut out, &sheet.name, i);
+        }
+
+        let start = out.len();
+        push_tsv(&mut out, &sheet.rows, newline);
+        if let Some(notice) = sheet.cut.notice() {
+            out.push_str(&notice);
+            out.push('\n');
+        }
+        let after_cells = out.len() > start;
+        push_plain_figures(&mut out, &sheet.charts, &sheet.pivots, after_cells, newline);
+        if !sheet.comments.is_empty() {
+            out.push('\n');
+            push_comments(&mut out, &sheet.comments, false);
+        }
+    }
+
+    out
+}
+
+/// Append the `--- name ---` line that starts the sheet at `index` when
+/// several are shown, after a blank line unless it is the first.
+pub(crate) fn push_plain_heading(out: &mut String, name: &str, index: usize) {
+    if index > 0 {
+        out.push('\n');
+    }
+    out.push_str("--- ");
+    out.push_str(name);
+    out.push_str(" ---\n");
+}
+
+/// The charts and pivot tables of a sheet, each as its title and its
+/// table, in the order they follow the sheet's cells.
+fn figures<'a>(charts: &'a [Chart], pivots: &[Pivot]) -> Vec<(Option<&'a str>, Vec<Vec<String>>)> {
+    charts
+        .iter()
+        .map(|chart| (chart.title.as_deref(), chart.rows()))
+        .chain(pivots.iter().map(|pivot| (None, pivot.rows())))
+        .collect()
+}
+
+/// Append the data of each chart, after its title, and the layout of each
+/// pivot table, apart from what comes before it by a blank line;
+/// `after_cells` says whether the sheet's cells came before the first.
+pub(crate) fn push_plain_figures(
+    out: &mut String,
+    charts: &[Chart],
+    pivots: &[Pivot],
+    after_cells: bool,
+    newline: CellNewline,
+) {
+    let mut after = after_cells;
+    for (title, rows) in figures(charts, pivots) {
+        let start = out.len();
+        if after {
+            out.push('\n');
+        }
+        if let Some(title) = title {
+            out.push_str(title);
+            out.push('\n');
+        }
+        push_tsv(out, &rows, newline);
+        after = out.len() > start;
+    }
+}
+
+/// Append a tab-separated line per row, leaving out empty rows.
+fn push_tsv(out: &mut String, rows: &[Vec<String>], newline: CellNewline) {
+    for row in rows {
+        push_tsv_row(out, row, newline);
+    }
+}
+
+/// Append `row` as a tab-separated line, unless its cells are all blank.
+/// Returns whether it did.
+pub(crate) fn push_tsv_row(out: &mut String, row: &[String], newline: CellNewline) -> bool {
+    let line = row
+        .iter()
+        .map(|cell| flatten_cell(cell, newline))
+        .collect::<Vec<_>>()
+        .join("\t");
+    let line = line.trim_end();
+    if !line.is_empty() {
+        out.push_str(line);
+        out.push('\n');
+    }
+    !line.is_empty()
+}
+
+/// `cell` with each `\r\n`, `\n`, or `\r` line break replaced.
+fn flatten_cell(cell: &str, newline: CellNewline) -> std::borrow::Cow<'_, str> {
+    if !cell.contains(['\n', '\r']) {
+        return cell.into();
+    }
+    cell.replace("\r\n", "\n")
+        .replace(['\n', '\r'], newline.replacement())
+        .into()
+}
+
+/// Plain text with each sheet drawn as a box table (`--table`).
+pub(crate) fn render_boxed(sheets: &[Sheet]) -> String {
+    let mut out = String::new();
+    let multiple = sheets.len() > 1;
+
+    for sheet in sheets {
+        let rows = strip_trailing_empty_rows(&sheet.rows);
+        let (rows, ncols) = strip_empty_cols(&rows);
+        if ncols == 0 && sheet.charts.is_empty() && sheet.pivots.is_empty() {
+            continue;
+        }
+
+        if multiple {
+            if !out.is_empty() {
                 out.push('\n');
             }
             out.push_str("--- ");
             out.push_str(&sheet.name);
             out.push_str(" ---\n");
         }
-
-        for row in &sheet.rows {
-            let line: String = row.join("\t");
-            let line = line.trim_end();
-            if !line.is_empty() {
-                out.push_str(line);
+        let start = out.len();
+        if ncols > 0 {
+            let numeric = numeric_columns(&rows, ncols);
+            crate::boxtable::render_aligned(&rows, &numeric, &mut out);
+        }
+        if let Some(notice) = sheet.cut.notice() {
+            out.push_str(&notice);
+            out.push('\n');
+        }
+        for (title, rows) in figures(&sheet.charts, &sheet.pivots) {
+            if out.len() > start {
+                out.push('\n');
+            }
+            if let Some(title) = title {
+                out.push_str(title);
                 out.push('\n');
             }
+            let numeric = numeric_columns(&rows, rows[0].len());
+            crate::boxtable::render_aligned(&rows, &numeric, &mut out);
+        }
+        if !sheet.comments.is_empty() {
+            out.push('\n');
+            push_comments(&mut out, &sheet.comments, false);
         }
     }
 
@@ -54,17 +555,9 @@ pub(crate) fn render_markdown(sheets: &[Sheet]) -> String {
             continue;
         }
 
-        // Strip trailing empty rows
-        let rows = strip_trailing_empty_rows(&sheet.rows);
-        if rows.is_empty() {
-            continue;
-        }
-
-        // Strip leading empty columns and trailing empty columns
+        // Strip trailing empty rows, and leading and trailing empty columns
+        let rows = strip_trailing_empty_rows(&outside_tables(sheet));
         let (rows, ncols) = strip_empty_cols(&rows);
-        if ncols == 0 {
-            continue;
-        }
 
         if multiple {
             out.push_str("## ");
@@ -72,49 +565,196 @@ pub(crate) fn render_markdown(sheets: &[Sheet]) -> String {
             out.push_str("\n\n");
         }
 
-        // First row is the header
-        if let Some(header) = rows.first() {
-            out.push_str("| ");
-            out.push_str(
-                &header
-                    .iter()
-                    .map(|c| escape_pipe(c))
-                    .collect::<Vec<_>>()
-                    .join(" | "),
-            );
-            out.push_str(" |\n");
-
-            // Separator
-            out.push('|');
-            for _ in 0..ncols {
-                out.push_str(" --- |");
+        if ncols > 0 {
+            push_markdown_table(&mut out, &rows, ncols);
+        }
+        if let Some(notice) = sheet.cut.notice() {
+            out.push_str(&notice);
+            out.push_str("\n\n");
+        }
+        for table in &sheet.tables {
+            let level = if multiple { "###" } else { "##" };
+            let _ = write!(out, "{level} {}\n\n", table.name);
+            let rows = table.grid_rows(&sheet.rows);
+            push_markdown_table(&mut out, &rows, rows[0].len());
+        }
+        for (title, rows) in figures(&sheet.charts, &sheet.pivots) {
+            if let Some(title) = title {
+                out.push_str("**");
+                out.push_str(title);
+                out.push_str("**\n\n");
             }
-            out.push('\n');
+            push_markdown_table(&mut out, &rows, rows[0].len());
+        }
+        for image in &sheet.images {
+            out.push_str(image);
+            out.push_str("\n\n");
+        }
+        push_comments(&mut out, &sheet.comments, true);
+    }
 
-            // Data rows
-            for row in rows.iter().skip(1) {
-                out.push_str("| ");
-                out.push_str(
-                    &row.iter()
-                        .map(|c| escape_pipe(c))
-                        .collect::<Vec<_>>()
-                        .join(" | "),
-                );
-                out.push_str(" |\n");
-            }
-            out.push('\n');
+    out
+}
+
+/// The cells of a sheet outside its Excel tables, which markdown shows
+/// apart. Rows that only hold table cells are left out.
+fn outside_tables(sheet: &Sheet) -> Cow<'_, [Vec<String>]> {
+    if sheet.tables.is_empty() {
+        return Cow::Borrowed(&sheet.rows);
+    }
+    let mut rows = Vec::new();
+    for (r, row) in sheet.rows.iter().enumerate() {
+        let covering: Vec<&NamedTable> = sheet
+            .tables
+            .iter()
+            .filter(|table| table.rows.contains(&r))
+            .collect();
+        if covering.is_empty() {
+            rows.push(row.clone());
+            continue;
         }
+        let row: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(c, value)| {
+                if covering.iter().any(|table| table.cols.contains(&c)) {
+                    String::new()
+                } else {
+                    value.clone()
+                }
+            })
+            .collect();
+        if row.iter().any(|value| !value.trim().is_empty()) {
+            rows.push(row);
+        }
+    }
+    Cow::Owned(rows)
+}
+
+/// Append `rows` as a markdown table, the first row its header.
+fn push_markdown_table(out: &mut String, rows: &[Vec<String>], ncols: usize) {
+    let Some(header) = rows.first() else {
+        return;
+    };
+    out.push_str("| ");
+    out.push_str(
+        &header
+            .iter()
+            .map(|c| escape_pipe(c))
+            .collect::<Vec<_>>()
+            .join(" | "),
+    );
+    out.push_str(" |\n");
+
+    // Separator, right-aligning columns of numbers
+    out.push('|');
+    let numeric = numeric_columns(rows, ncols);
+    for &right in &numeric {
+        out.push_str(if right { " ---: |" } else { " --- |" });
     }
+    out.push('\n');
 
+    // Data rows
+    for row in rows.iter().skip(1) {
+        out.push_str("| ");
+        out.push_str(
+            &row.iter()
+                .map(|c| escape_pipe(c))
+                .collect::<Vec<_>>()
+                .join(" | "),
+        );
+        out.push_str(" |\n");
+    }
+    out.push('\n');
+}
+
+// ── CSV rendering ─────────────────────────────────────────────────
+
+/// Render one sheet as CSV with RFC 4180 quoting.
+///
+/// Trailing empty rows and columns are dropped; every row is padded to the
+/// same width so column positions are preserved. Lines end in `\n`.
+pub(crate) fn render_csv(sheet: &Sheet) -> String {
+    let rows = strip_trailing_empty_rows(&sheet.rows);
+    let ncols = rows
+        .iter()
+        .filter_map(|row| row.iter().rposition(|cell| !cell.trim().is_empty()))
+        .max()
+        .map_or(0, |last| last + 1);
+
+    let mut out = String::new();
+    for row in &rows {
+        push_csv_row(&mut out, row, ncols);
+    }
     out
 }
 
-/// Returns true if the sheet has no non-empty cells.
-pub(crate) fn skip_empty_sheet(sheet: &Sheet) -> bool {
-    sheet
-        .rows
+/// Append `row` as a CSV line of `ncols` fields, padded with empty ones.
+pub(crate) fn push_csv_row(out: &mut String, row: &[String], ncols: usize) {
+    for col in 0..ncols {
+        if col > 0 {
+            out.push(',');
+        }
+        push_csv_field(out, row.get(col).map_or("", String::as_str));
+    }
+    out.push('\n');
+}
+
+/// Append a CSV field, quoting it if it contains a comma, quote, or newline.
+fn push_csv_field(out: &mut String, field: &str) {
+    if field.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&field.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(field);
+    }
+}
+
+/// File names (`{stem}_{sheet}.csv`) for exporting the sheets named
+/// `names` as CSV.
+///
+/// Characters that are unsafe in file names are replaced with `_`, and
+/// names that collide after sanitizing get a numeric suffix.
+pub(crate) fn csv_file_names(stem: &str, names: &[&str]) -> Vec<String> {
+    let mut used = HashSet::new();
+    names
         .iter()
-        .all(|row| row.iter().all(|cell| cell.trim().is_empty()))
+        .map(|name| {
+            let base = sanitize_file_name(&format!("{stem}_{name}"));
+            let mut name = format!("{base}.csv");
+            let mut n = 2;
+            while !used.insert(name.to_lowercase()) {
+                name = format!("{base}_{n}.csv");
+                n += 1;
+            }
+            name
+        })
+        .collect()
+}
+
+fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Returns true if the sheet has no non-empty cells, no charts, no pivot
+/// tables, and no images.
+pub(crate) fn skip_empty_sheet(sheet: &Sheet) -> bool {
+    sheet.charts.is_empty()
+        && sheet.pivots.is_empty()
+        && sheet.images.is_empty()
+        && sheet
+            .rows
+            .iter()
+            .all(|row| row.iter().all(|cell| cell.trim().is_empty()))
 }
 
 /// Strip trailing rows that are entirely empty.
@@ -170,6 +810,48 @@ fn strip_empty_cols(rows: &[Vec<String>]) -> (Vec<Vec<String>>, usize) {
     (trimmed, new_ncols)
 }
 
+/// Whether each of the first `ncols` columns of `rows` holds numbers:
+/// every cell below the header row that is not blank, and at least one.
+fn numeric_columns(rows: &[Vec<String>], ncols: usize) -> Vec<bool> {
+    (0..ncols)
+        .map(|col| {
+            let mut cells = rows
+                .iter()
+                .skip(1)
+                .filter_map(|row| row.get(col))
+                .map(|cell| cell.trim())
+                .filter(|cell| !cell.is_empty())
+                .peekable();
+            cells.peek().is_some() && cells.all(is_number)
+        })
+        .collect()
+}
+
+/// Whether a cell reads as a number, as number formats write them:
+/// `-1,234.5`, `(75)`, `$1.50`, `12%`, `1.2E+03`, `5 €`.
+fn is_number(cell: &str) -> bool {
+    let currency = |c: char| matches!(c, '$' | '€' | '£' | '¥' | '₹') || c.is_whitespace();
+    let text = cell
+        .strip_prefix('(')
+        .and_then(|t| t.strip_suffix(')'))
+        .unwrap_or(cell);
+    let text = text.strip_prefix(['-', '+', '−']).unwrap_or(text);
+    let text = text.trim_start_matches(currency).trim_end_matches(currency);
+    let text = text.strip_suffix('%').unwrap_or(text);
+    let (mantissa, exponent) = text
+        .split_once(['E', 'e'])
+        .map_or((text, None), |(m, e)| (m, Some(e)));
+    let exponent_ok = exponent.is_none_or(|e| {
+        let digits = e.strip_prefix(['-', '+']).unwrap_or(e);
+        !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+    });
+    mantissa.bytes().any(|b| b.is_ascii_digit())
+        && mantissa
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b == b',' || b == b'.')
+        && exponent_ok
+}
+
 /// Escape pipe characters for markdown table cells.
 fn escape_pipe(s: &str) -> String {
     s.replace('|', "\\|")
@@ -190,11 +872,18 @@ mod tests {
                 vec!["Alice".into(), "30".into()],
                 vec!["Bob".into(), "25".into()],
             ],
+            comments: Vec::new(),
+            charts: Vec::new(),
+            tables: Vec::new(),
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
         }];
 
         let md = render_markdown(&sheets);
         assert!(md.contains("| Name | Age |"));
-        assert!(md.contains("| --- | --- |"));
+        // Ages are numbers, so right-aligned
+        assert!(md.contains("| --- | ---: |"));
         assert!(md.contains("| Alice | 30 |"));
         assert!(md.contains("| Bob | 25 |"));
         // Single sheet — no heading
@@ -207,10 +896,22 @@ mod tests {
             Sheet {
                 name: "People".into(),
                 rows: vec![vec!["Name".into()], vec!["Alice".into()]],
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
             },
             Sheet {
                 name: "Places".into(),
                 rows: vec![vec!["City".into()], vec!["NYC".into()]],
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
             },
         ];
 
@@ -227,10 +928,22 @@ mod tests {
             Sheet {
                 name: "Empty".into(),
                 rows: vec![vec![String::new(), String::new()]],
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
             },
             Sheet {
                 name: "Data".into(),
                 rows: vec![vec!["Hello".into()]],
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
             },
         ];
 
@@ -244,12 +957,75 @@ mod tests {
         let sheets = vec![Sheet {
             name: "Sheet1".into(),
             rows: vec![vec!["A|B".into()], vec!["C".into()]],
+            comments: Vec::new(),
+            charts: Vec::new(),
+            tables: Vec::new(),
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
         }];
 
         let md = render_markdown(&sheets);
         assert!(md.contains("A\\|B"));
     }
 
+    // ── select ───────────────────────────────────────────────────
+
+    fn named(names: &[&str]) -> Vec<Sheet> {
+        names
+            .iter()
+            .map(|&name| Sheet {
+                name: name.into(),
+                rows: vec![vec![name.into()]],
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn select_by_name_or_index() {
+        let pick = |sel| {
+            select(named(&["Q3 Data", "2", "Notes"]), sel).unwrap()[0]
+                .name
+                .clone()
+        };
+        assert_eq!(pick(Some("Q3 Data")), "Q3 Data");
+        assert_eq!(pick(Some("notes")), "Notes");
+        // A sheet named "2" wins over the second sheet
+        assert_eq!(pick(Some("2")), "2");
+        assert_eq!(pick(Some("3")), "Notes");
+        assert_eq!(select(named(&["A", "B"]), None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn choose_picked_sheets() {
+        let opts = Options {
+            picked: Some(vec![1, 3]),
+            ..Options::default()
+        };
+        let names: Vec<String> = choose(named(&["A", "B", "C"]), &opts)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["A", "C"]);
+    }
+
+    #[test]
+    fn select_missing_sheet() {
+        let err = select(named(&["A", "B"]), Some("3")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no sheet '3' in the workbook (sheets: A, B)"
+        );
+        assert_eq!(err.code(), "usage");
+    }
+
     // ── render_plain ─────────────────────────────────────────────
 
     #[test]
@@ -260,31 +1036,164 @@ mod tests {
                 vec!["Name".into(), "Age".into()],
                 vec!["Alice".into(), "30".into()],
             ],
+            comments: Vec::new(),
+            charts: Vec::new(),
+            tables: Vec::new(),
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
         }];
 
-        let text = render_plain(&sheets);
+        let text = render_plain(&sheets, CellNewline::Escape);
         assert!(text.contains("Name\tAge"));
         assert!(text.contains("Alice\t30"));
     }
 
+    #[test]
+    fn render_plain_cell_newlines() {
+        let sheets = vec![Sheet {
+            name: "Sheet1".into(),
+            rows: vec![vec!["a\nb".into(), "c\r\nd\re".into()]],
+            comments: Vec::new(),
+            charts: Vec::new(),
+            tables: Vec::new(),
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
+        }];
+
+        assert_eq!(
+            render_plain(&sheets, CellNewline::Escape),
+            "a\\nb\tc\\nd\\ne\n"
+        );
+        assert_eq!(render_plain(&sheets, CellNewline::Space), "a b\tc d e\n");
+    }
+
     #[test]
     fn render_plain_multi_sheet() {
         let sheets = vec![
             Sheet {
                 name: "People".into(),
                 rows: vec![vec!["Alice".into()]],
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
             },
             Sheet {
                 name: "Places".into(),
                 rows: vec![vec!["NYC".into()]],
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
             },
         ];
 
-        let text = render_plain(&sheets);
+        let text = render_plain(&sheets, CellNewline::Escape);
         assert!(text.contains("--- People ---"));
         assert!(text.contains("--- Places ---"));
     }
 
+    // ── render_csv ───────────────────────────────────────────────
+
+    #[test]
+    fn csv_quotes_special_fields() {
+        let sheet = Sheet {
+            name: "S".into(),
+            rows: vec![
+                vec!["a,b".into(), "say \"hi\"".into(), "plain".into()],
+                vec!["line1\nline2".into(), String::new(), "x".into()],
+            ],
+            comments: Vec::new(),
+            charts: Vec::new(),
+            tables: Vec::new(),
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
+        };
+        assert_eq!(
+            render_csv(&sheet),
+            "\"a,b\",\"say \"\"hi\"\"\",plain\n\"line1\nline2\",,x\n"
+        );
+    }
+
+    #[test]
+    fn csv_pads_rows_and_drops_trailing_empties() {
+        let sheet = Sheet {
+            name: "S".into(),
+            rows: vec![
+                vec![String::new(), "b".into(), String::new()],
+                vec!["c".into()],
+                vec![String::new(), String::new()],
+            ],
+            comments: Vec::new(),
+            charts: Vec::new(),
+            tables: Vec::new(),
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
+        };
+        assert_eq!(render_csv(&sheet), ",b\nc,\n");
+    }
+
+    #[test]
+    fn csv_file_names_sanitized_and_unique() {
+        assert_eq!(
+            csv_file_names("book", &["Q1/Q2", "Q1:Q2"]),
+            vec!["book_Q1_Q2.csv", "book_Q1_Q2_2.csv"]
+        );
+    }
+
+    // ── render_boxed ─────────────────────────────────────────────
+
+    #[test]
+    fn render_boxed_multi_sheet() {
+        let sheets = vec![
+            Sheet {
+                name: "A".into(),
+                rows: vec![
+                    vec![String::new(), "x".into()],
+                    vec![String::new(), "yy".into()],
+                ],
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
+            },
+            Sheet {
+                name: "Empty".into(),
+                rows: vec![vec![String::new()]],
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
+            },
+            Sheet {
+                name: "B".into(),
+                rows: vec![vec!["z".into()]],
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
+            },
+        ];
+        assert_eq!(
+            render_boxed(&sheets),
+            "--- A ---\n┌────┐\n│ x  │\n├────┤\n│ yy │\n└────┘\n\n--- B ---\n┌───┐\n│ z │\n└───┘\n"
+        );
+    }
+
     // ── skip_empty_sheet ─────────────────────────────────────────
 
     #[test]
@@ -295,6 +1204,12 @@ mod tests {
                 vec![String::new(), "  ".into()],
                 vec![String::new(), String::new()],
             ],
+            comments: Vec::new(),
+            charts: Vec::new(),
+            tables: Vec::new(),
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
         };
         assert!(skip_empty_sheet(&sheet));
     }
@@ -304,10 +1219,106 @@ mod tests {
         let sheet = Sheet {
             name: "Data".into(),
             rows: vec![vec![String::new(), "Hello".into()]],
+            comments: Vec::new(),
+            charts: Vec::new(),
+            tables: Vec::new(),
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
         };
         assert!(!skip_empty_sheet(&sheet));
     }
 
+    #[test]
+    fn picture_sheet_shows_its_images() {
+        let sheets = [
+            Sheet {
+                name: "Logo".into(),
+                rows: Vec::new(),
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: vec!["![][image1]".into()],
+                pivots: Vec::new(),
+            },
+            Sheet {
+                name: "Data".into(),
+                rows: vec![vec!["x".into()]],
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: vec!["![][image2]".into()],
+                pivots: Vec::new(),
+            },
+        ];
+        assert!(!skip_empty_sheet(&sheets[0]));
+        assert_eq!(
+            render_markdown(&sheets),
+            "## Logo\n\n![][image1]\n\n## Data\n\n| x |\n| --- |\n\n![][image2]\n\n"
+        );
+    }
+
+    // ── truncate ─────────────────────────────────────────────────
+
+    #[test]
+    fn truncated_sheet_notes_the_rest() {
+        let row = |cells: &[&str]| cells.iter().map(|&c| c.to_string()).collect();
+        let mut sheets = vec![Sheet {
+            name: "Orders".into(),
+            rows: vec![
+                row(&["", "Order", "Total", "Note"]),
+                row(&["", "1001", "250", ""]),
+                row(&["", "1002", "75", "rush"]),
+                row(&["", "1003", "", ""]),
+                row(&["", "", "", ""]),
+            ],
+            comments: vec![Comment {
+                row: 3,
+                col: 1,
+                cell: "B4".into(),
+                author: "Ann".into(),
+                text: "Late".into(),
+                number: 0,
+                sheet: None,
+            }],
+            charts: Vec::new(),
+            tables: vec![NamedTable {
+                name: "Orders".into(),
+                rows: 0..=3,
+                cols: 1..=3,
+                columns: vec!["Order".into(), "Total".into(), "Note".into()],
+                header_row: true,
+            }],
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
+        }];
+        let opts = Options {
+            max_rows: Some(2),
+            max_cols: Some(2),
+            ..Options::default()
+        };
+        truncate(&mut sheets, &opts);
+        assert_eq!(sheets[0].cut, Cut { rows: 2, cols: 1 });
+        assert!(sheets[0].comments.is_empty());
+        assert_eq!(
+            (&sheets[0].tables[0].rows, &sheets[0].tables[0].cols),
+            (&(0..=1), &(1..=2))
+        );
+        assert_eq!(sheets[0].tables[0].columns, ["Order", "Total"]);
+        assert_eq!(
+            render_plain(&sheets, CellNewline::Escape),
+            "\tOrder\tTotal\n\t1001\t250\n… (2 more rows, 1 more column)\n"
+        );
+        sheets[0].tables.clear();
+        assert_eq!(
+            render_markdown(&sheets),
+            "| Order | Total |\n| ---: | ---: |\n| 1001 | 250 |\n\n… (2 more rows, 1 more column)\n\n"
+        );
+    }
+
     // ── strip_trailing_empty_rows ────────────────────────────────
 
     #[test]
@@ -339,6 +1350,175 @@ mod tests {
         assert_eq!(result[1], vec!["C", "D"]);
     }
 
+    // ── tables ───────────────────────────────────────────────────
+
+    #[test]
+    fn named_tables_shown_apart() {
+        let sheet = Sheet {
+            name: "Q1".into(),
+            rows: vec![
+                vec!["Sales report".into(), String::new(), String::new()],
+                vec![String::new(), "Region".into(), "Total".into()],
+                vec![String::new(), "North".into(), "5".into()],
+                vec!["Notes".into(), String::new(), String::new()],
+            ],
+            comments: Vec::new(),
+            charts: Vec::new(),
+            tables: vec![NamedTable {
+                name: "Sales".into(),
+                rows: 1..=2,
+                cols: 1..=2,
+                columns: vec!["Region".into(), "Total".into()],
+                header_row: true,
+            }],
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
+        };
+        assert_eq!(
+            render_markdown(&[sheet]),
+            "| Sales report |\n| --- |\n| Notes |\n\n\
+             ## Sales\n\n| Region | Total |\n| --- | ---: |\n| North | 5 |\n\n"
+        );
+    }
+
+    // ── charts ───────────────────────────────────────────────────
+
+    #[test]
+    fn chart_only_sheet_shows_chart_data() {
+        let chart = Chart::parse(
+            r#"<c:chartSpace xmlns:c="c" xmlns:a="a"><c:chart><c:title><c:tx><c:rich><a:p><a:r><a:t>Sales</a:t></a:r></a:p></c:rich></c:tx></c:title><c:plotArea><c:barChart><c:ser><c:tx><c:v>North</c:v></c:tx><c:cat><c:strRef><c:strCache><c:pt idx="0"><c:v>Q1</c:v></c:pt><c:pt idx="1"><c:v>Q2</c:v></c:pt></c:strCache></c:strRef></c:cat><c:val><c:numRef><c:numCache><c:pt idx="0"><c:v>4</c:v></c:pt><c:pt idx="1"><c:v>2.5</c:v></c:pt></c:numCache></c:numRef></c:val></c:ser></c:barChart></c:plotArea></c:chart></c:chartSpace>"#,
+        )
+        .unwrap();
+        let sheets = vec![
+            Sheet {
+                name: "Data".into(),
+                rows: vec![vec!["Q1".into(), "4".into()]],
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
+            },
+            Sheet {
+                name: "Dashboard".into(),
+                rows: Vec::new(),
+                comments: Vec::new(),
+                charts: vec![chart],
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
+            },
+        ];
+        assert_eq!(
+            render_markdown(&sheets),
+            "## Data\n\n| Q1 | 4 |\n| --- | --- |\n\n## Dashboard\n\n**Sales**\n\n|  | North |\n| --- | ---: |\n| Q1 | 4 |\n| Q2 | 2.5 |\n\n"
+        );
+        assert_eq!(
+            render_plain(&sheets, CellNewline::Escape),
+            "--- Data ---\nQ1\t4\n\n--- Dashboard ---\nSales\n\tNorth\nQ1\t4\nQ2\t2.5\n"
+        );
+    }
+
+    // ── comments ─────────────────────────────────────────────────
+
+    fn commented(name: &str, row: usize, col: usize, text: &str) -> Sheet {
+        Sheet {
+            name: name.into(),
+            rows: vec![
+                vec!["Item".into(), "Cost".into()],
+                vec!["Tea".into(), "4".into()],
+            ],
+            comments: vec![Comment {
+                row,
+                col,
+                cell: cell_name(row, col),
+                author: "Ann".into(),
+                text: text.into(),
+                number: 0,
+                sheet: None,
+            }],
+            charts: Vec::new(),
+            tables: Vec::new(),
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn comments_listed_after_their_sheet() {
+        let mut sheets = vec![
+            commented("Q1", 1, 1, "Was 5"),
+            commented("Q2", 3, 0, "Add\ncoffee"),
+        ];
+        assert!(place_comments(&mut sheets, Comments::Inline).is_empty());
+        assert_eq!(
+            render_plain(&sheets, CellNewline::Escape),
+            "--- Q1 ---\nItem\tCost\nTea\t4[c1]\n\n[c1] Ann on B2: Was 5\n\n--- Q2 ---\nItem\tCost\nTea\t4\n[c2]\n\n[c2] Ann on A4: Add coffee\n"
+        );
+        assert!(render_markdown(&sheets)
+            .contains("| Tea | 4[c1] |\n\n[c1] **Ann** on B2: Was 5\n\n## Q2"));
+    }
+
+    #[test]
+    fn comments_gathered_at_end() {
+        let mut sheets = vec![commented("Q1", 0, 1, "Net"), commented("Q 2", 0, 0, "")];
+        let comments = place_comments(&mut sheets, Comments::End);
+        assert!(sheets.iter().all(|s| s.comments.is_empty()));
+        assert_eq!(sheets[1].rows[0][0], "Item[c2]");
+        assert_eq!(
+            render_comment_section(&comments, false),
+            "\n--- Comments ---\n[c1] Ann on Q1!B1: Net\n[c2] Ann on 'Q 2'!A1: \n"
+        );
+        assert_eq!(cell_name(9, 27), "AB10");
+    }
+
+    // ── numeric columns ──────────────────────────────────────────
+
+    #[test]
+    fn numbers_recognized() {
+        for cell in [
+            "42",
+            "-1,234.50",
+            "(75)",
+            "$1.50",
+            "12.5%",
+            "1.23E+04",
+            "1.234,50 €",
+        ] {
+            assert!(is_number(cell), "{cell}");
+        }
+        for cell in ["2024-01-01", "N/A", "4[c1]", "E5", "$", "1.2E"] {
+            assert!(!is_number(cell), "{cell}");
+        }
+    }
+
+    #[test]
+    fn boxed_numbers_right_aligned() {
+        let sheets = vec![Sheet {
+            name: "Costs".into(),
+            rows: vec![
+                vec!["Item".into(), "Cost".into()],
+                vec!["Tea".into(), "$4.50".into()],
+                vec!["Cake".into(), String::new()],
+                vec!["Lunch".into(), "$12.00".into()],
+            ],
+            comments: Vec::new(),
+            charts: Vec::new(),
+            tables: Vec::new(),
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
+        }];
+        assert_eq!(
+            render_boxed(&sheets),
+            "┌───────┬────────┐\n│ Item  │   Cost │\n├───────┼────────┤\n│ Tea   │  $4.50 │\n│ Cake  │        │\n│ Lunch │ $12.00 │\n└───────┴────────┘\n"
+        );
+    }
+
     // ── escape_pipe ──────────────────────────────────────────────
 
     #[test]
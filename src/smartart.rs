@@ -0,0 +1,203 @@
+//! Text of `SmartArt` diagrams (`diagrams/data*.xml` parts).
+//!
+//! A diagram's data model is a flat list of points (`<dgm:pt>`), each with
+//! optional text, and a list of connections (`<dgm:cxn>`) between them.
+//! The tree of the diagram, as shown in Word's text pane, is made by the
+//! parent-of connections from the `doc` point down, ordered by `srcOrd`.
+//! Presentation points and the other connection types only describe the
+//! layout.
+
+use std::collections::{HashMap, HashSet};
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::reader::Reader;
+
+use crate::xml_util::get_attr;
+
+/// A diagram node with text, and how deep in the tree it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Node {
+    /// 0 for the top level.
+    pub(crate) level: u8,
+    pub(crate) text: String,
+}
+
+/// The points and parent-of connections of a data model.
+#[derive(Debug, Default)]
+struct Model {
+    /// The `doc` point, root of the tree.
+    root: Option<String>,
+    /// Text by point ID.
+    texts: HashMap<String, String>,
+    /// Children by parent ID, with their order.
+    children: HashMap<String, Vec<(u32, String)>>,
+}
+
+/// Read a diagram data part into its nodes in outline order. Nodes without
+/// text are left out, and their children take their place.
+pub(crate) fn parse(xml: &str) -> Vec<Node> {
+    let mut reader = Reader::from_str(xml);
+    let mut model = Model::default();
+    // The content point being read
+    let mut point: Option<String> = None;
+    // Inside its <dgm:t>, and inside an <a:t> there
+    let mut in_text = false;
+    let mut in_run = false;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"pt" => point = model.add_point(e),
+                b"t" if in_text => in_run = true,
+                b"t" => in_text = point.is_some(),
+                b"p" if in_text => {
+                    // Paragraphs of a node's text are joined by a space
+                    if let Some(text) = point.as_ref().and_then(|id| model.texts.get_mut(id)) {
+                        if !text.is_empty() {
+                            text.push(' ');
+                        }
+                    }
+                }
+                b"cxn" => model.add_connection(e),
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) => match e.local_name().as_ref() {
+                b"pt" => {
+                    model.add_point(e);
+                }
+                b"cxn" => model.add_connection(e),
+                _ => {}
+            },
+            Ok(Event::Text(ref t)) if in_run => {
+                if let (Some(id), Ok(s)) = (&point, t.unescape()) {
+                    model.texts.entry(id.clone()).or_default().push_str(&s);
+                }
+            }
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"pt" => point = None,
+                b"t" if in_run => in_run = false,
+                b"t" => in_text = false,
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    let mut nodes = Vec::new();
+    if let Some(root) = model.root.clone() {
+        model.walk(&root, 0, &mut HashSet::new(), &mut nodes);
+    }
+    nodes
+}
+
+impl Model {
+    /// Note a `<dgm:pt>`, returning its ID if it is a node that can have
+    /// text (not the root or a presentation or transition point).
+    fn add_point(&mut self, e: &BytesStart) -> Option<String> {
+        let id = get_attr(e, b"modelId")?;
+        match get_attr(e, b"type").as_deref() {
+            None | Some("node" | "asst") => {
+                self.texts.entry(id.clone()).or_default();
+                Some(id)
+            }
+            Some("doc") => {
+                self.root = Some(id);
+                None
+            }
+            Some(_) => None,
+        }
+    }
+
+    /// Note a `<dgm:cxn>` if it is a parent-of connection.
+    fn add_connection(&mut self, e: &BytesStart) {
+        if get_attr(e, b"type").is_some_and(|t| t != "parOf") {
+            return;
+        }
+        if let (Some(parent), Some(child)) = (get_attr(e, b"srcId"), get_attr(e, b"destId")) {
+            let order = get_attr(e, b"srcOrd")
+                .and_then(|o| o.parse().ok())
+                .unwrap_or(0);
+            self.children
+                .entry(parent)
+                .or_default()
+                .push((order, child));
+        }
+    }
+
+    /// Add the descendants of `id` to `nodes`, the top ones at `level`.
+    fn walk(&self, id: &str, level: u8, seen: &mut HashSet<String>, nodes: &mut Vec<Node>) {
+        let Some(children) = self.children.get(id) else {
+            return;
+        };
+        let mut children: Vec<&(u32, String)> = children.iter().collect();
+        children.sort_by_key(|(order, _)| *order);
+        for (_, child) in children {
+            if !seen.insert(child.clone()) {
+                continue;
+            }
+            let text = self
+                .texts
+                .get(child)
+                .map(|t| t.split_whitespace().collect::<Vec<_>>().join(" "))
+                .unwrap_or_default();
+            if text.is_empty() {
+                self.walk(child, level, seen, nodes);
+            } else {
+                nodes.push(Node { level, text });
+                self.walk(child, level.saturating_add(1), seen, nodes);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &str = r#"<dgm:dataModel xmlns:dgm="http://schemas.openxmlformats.org/drawingml/2006/diagram" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main">
+        <dgm:ptLst>
+          <dgm:pt modelId="0" type="doc"><dgm:t><a:bodyPr/></dgm:t></dgm:pt>
+          <dgm:pt modelId="1"><dgm:t><a:p><a:r><a:t>CEO</a:t></a:r></a:p></dgm:t></dgm:pt>
+          <dgm:pt modelId="3"><dgm:t><a:p><a:r><a:t>CFO</a:t></a:r></a:p><a:p><a:r><a:t>Finance</a:t></a:r></a:p></dgm:t></dgm:pt>
+          <dgm:pt modelId="2"><dgm:t><a:p><a:r><a:t xml:space="preserve">CTO </a:t></a:r><a:r><a:t>&amp; IT</a:t></a:r></a:p></dgm:t></dgm:pt>
+          <dgm:pt modelId="4" type="asst"><dgm:t><a:p><a:r><a:t>Assistant</a:t></a:r></a:p></dgm:t></dgm:pt>
+          <dgm:pt modelId="5"><dgm:t><a:p/></dgm:t></dgm:pt>
+          <dgm:pt modelId="6"><dgm:t><a:p><a:r><a:t>Ops</a:t></a:r></a:p></dgm:t></dgm:pt>
+          <dgm:pt modelId="9" type="pres"><dgm:t><a:p><a:r><a:t>layout</a:t></a:r></a:p></dgm:t></dgm:pt>
+          <dgm:pt modelId="10" type="parTrans"/>
+        </dgm:ptLst>
+        <dgm:cxnLst>
+          <dgm:cxn modelId="20" srcId="0" destId="1" srcOrd="0" destOrd="0"/>
+          <dgm:cxn modelId="21" srcId="1" destId="3" srcOrd="1" destOrd="0"/>
+          <dgm:cxn modelId="22" srcId="1" destId="2" srcOrd="0" destOrd="0"/>
+          <dgm:cxn modelId="23" type="parOf" srcId="1" destId="4" srcOrd="2" destOrd="0"/>
+          <dgm:cxn modelId="24" srcId="0" destId="5" srcOrd="1" destOrd="0"/>
+          <dgm:cxn modelId="25" srcId="5" destId="6" srcOrd="0" destOrd="0"/>
+          <dgm:cxn modelId="26" type="presOf" srcId="1" destId="9" srcOrd="0" destOrd="0"/>
+        </dgm:cxnLst>
+      </dgm:dataModel>"#;
+
+    // ── parse ────────────────────────────────────────────────────
+
+    #[test]
+    fn nodes_in_outline_order() {
+        let nodes: Vec<(u8, String)> = parse(DATA).into_iter().map(|n| (n.level, n.text)).collect();
+        assert_eq!(
+            nodes,
+            [
+                (0, "CEO".to_string()),
+                (1, "CTO & IT".to_string()),
+                (1, "CFO Finance".to_string()),
+                (1, "Assistant".to_string()),
+                (0, "Ops".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_root_no_nodes() {
+        assert!(parse("<dgm:dataModel/>").is_empty());
+        assert!(parse("not xml <<").is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+//! Word, character, and structure counts (`--stats`).
+//!
+//! Counted from the document model ([`Block`]s) rather than the rendered
+//! text, so markup, list markers, and table borders are not included.
+//! Each document is one row of a table; a total row follows when there
+//! is more than one. `--summary` reports the same counts, so the two
+//! modes agree on what a word and a paragraph are.
+
+use crate::document::Block;
+use crate::format::Format;
+
+/// Column headings, aligned with [`Stats::row`].
+const HEADER: &str = "   words    chars  paragraphs  tables  slides  sheets  pages  file\n";
+
+/// Counts for one document, or the sum of several.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Stats {
+    /// Words in headings, paragraphs, list items, and table cells.
+    pub(crate) words: usize,
+    /// Characters including spaces, but not line breaks.
+    pub(crate) chars: usize,
+    pub(crate) headings: usize,
+    /// Body paragraphs, not counting headings and list items.
+    pub(crate) paragraphs: usize,
+    pub(crate) items: usize,
+    pub(crate) tables: usize,
+    pub(crate) slides: usize,
+    pub(crate) sheets: usize,
+    pub(crate) pages: usize,
+}
+
+impl Stats {
+    /// Count the contents of `blocks`. A spreadsheet's tables are its
+    /// sheets, so they count as sheets rather than tables.
+    pub(crate) fn new(blocks: &[Block], format: Format) -> Self {
+        let mut stats = Self::default();
+        for block in blocks {
+            match block {
+                Block::Heading { text, .. } => {
+                    stats.headings += 1;
+                    stats.count_text(text);
+                }
+                Block::Paragraph { text, .. } => {
+                    stats.paragraphs += 1;
+                    stats.count_text(text);
+                }
+                Block::ListItem { text, .. } => {
+                    stats.items += 1;
+                    stats.count_text(text);
+                }
+                Block::Table { rows, .. } => {
+                    if matches!(format, Format::Xls | Format::Xlsx) {
+                        stats.sheets += 1;
+                    } else {
+                        stats.tables += 1;
+                    }
+                    for cell in rows.iter().flatten() {
+                        stats.count_text(cell);
+                    }
+                }
+                Block::Slide { .. } => stats.slides += 1,
+                Block::Page { .. } => stats.pages += 1,
+            }
+        }
+        stats
+    }
+
+    fn count_text(&mut self, text: &str) {
+        self.words += text.split_whitespace().count();
+        self.chars += text.chars().filter(|&c| c != '\n' && c != '\r').count();
+    }
+
+    const fn add(&mut self, other: &Self) {
+        self.words += other.words;
+        self.chars += other.chars;
+        self.headings += other.headings;
+        self.paragraphs += other.paragraphs;
+        self.items += other.items;
+        self.tables += other.tables;
+        self.slides += other.slides;
+        self.sheets += other.sheets;
+        self.pages += other.pages;
+    }
+
+    /// One line of the table, labelled `name`. Its paragraphs column
+    /// counts headings and list items too.
+    fn row(&self, name: &str) -> String {
+        format!(
+            "{:>8} {:>8} {:>11} {:>7} {:>7} {:>7} {:>6}  {name}\n",
+            self.words,
+            self.chars,
+            self.headings + self.paragraphs + self.items,
+            self.tables,
+            self.slides,
+            self.sheets,
+            self.pages
+        )
+    }
+}
+
+/// Running total over the documents written to one output.
+#[derive(Debug, Default)]
+pub(crate) struct Tally {
+    total: Stats,
+    files: usize,
+}
+
+impl Tally {
+    /// Add a document's counts, returning its row, preceded by the column
+    /// headings for the first document.
+    pub(crate) fn record(&mut self, stats: &Stats, name: &str) -> String {
+        self.total.add(stats);
+        self.files += 1;
+        let row = stats.row(name);
+        if self.files == 1 {
+            format!("{HEADER}{row}")
+        } else {
+            row
+        }
+    }
+
+    /// The total row, once more than one document has been counted.
+    pub(crate) fn total(&self) -> Option<String> {
+        (self.files > 1).then(|| self.total.row("total"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn para(text: &str) -> Block {
+        Block::Paragraph {
+            text: text.into(),
+            spans: Vec::new(),
+        }
+    }
+
+    // ── Stats::new ───────────────────────────────────────────────
+
+    #[test]
+    fn counts_text_and_structure() {
+        let blocks = vec![
+            Block::Page { number: 1 },
+            Block::Heading {
+                level: 1,
+                text: "Intro".into(),
+            },
+            para("two words\nand more"),
+            Block::ListItem {
+                level: 0,
+                ordered: false,
+                text: "item".into(),
+                spans: Vec::new(),
+            },
+            Block::Table {
+                name: None,
+                rows: vec![vec!["a b".into(), "c".into()]],
+            },
+            Block::Page { number: 2 },
+        ];
+        let stats = Stats::new(&blocks, Format::Pdf);
+        assert_eq!(
+            stats,
+            Stats {
+                words: 9,
+                chars: 5 + 17 + 4 + 3 + 1,
+                headings: 1,
+                paragraphs: 1,
+                items: 1,
+                tables: 1,
+                slides: 0,
+                sheets: 0,
+                pages: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn spreadsheet_tables_are_sheets() {
+        let blocks = vec![
+            Block::Table {
+                name: Some("Data".into()),
+                rows: vec![vec!["1".into()]],
+            },
+            Block::Table {
+                name: Some("Notes".into()),
+                rows: vec![vec!["x".into()]],
+            },
+        ];
+        let stats = Stats::new(&blocks, Format::Xlsx);
+        assert_eq!((stats.sheets, stats.tables, stats.words), (2, 0, 2));
+    }
+
+    // ── Tally ────────────────────────────────────────────────────
+
+    #[test]
+    fn header_first_and_total_for_several() {
+        let mut tally = Tally::default();
+        let one = Stats::new(&[para("hello world")], Format::Docx);
+        let first = tally.record(&one, "a.docx");
+        assert_eq!(
+            first,
+            format!(
+                "{HEADER}       2       11           1       0       0       0      0  a.docx\n"
+            )
+        );
+        assert_eq!(tally.total(), None);
+        let second = tally.record(&one, "b.docx");
+        assert!(second.ends_with("  b.docx\n") && !second.contains("words"));
+        assert_eq!(
+            tally.total().unwrap(),
+            "       4       22           2       0       0       0      0  total\n"
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! Summary view of a document (`--summary`).
+//!
+//! A quick triage view for large documents, rendered as markdown from the
+//! document model ([`Block`]s) and the [`Metadata`]: the properties, an
+//! outline of the headings, an inventory of tables and figure captions,
+//! and the first few words of each section. Sections start at headings,
+//! and at slides and pages for formats that have them. The counts come
+//! from [`Stats`], so they match `--stats`.
+
+use std::fmt::Write as _;
+
+use crate::document::Block;
+use crate::format::Format;
+use crate::metadata::{DateTime, Metadata};
+use crate::stats::Stats;
+
+/// Words shown from each section unless `--summary-words` says otherwise.
+pub(crate) const DEFAULT_WORDS: usize = 50;
+
+/// A run of content under one heading, slide, or page.
+struct Section {
+    title: String,
+    /// Started by a slide or page marker and still waiting for its title.
+    untitled_marker: bool,
+    excerpt: Vec<String>,
+    truncated: bool,
+}
+
+/// A table: where it is and its size.
+struct TableInfo {
+    location: String,
+    rows: usize,
+    cols: usize,
+}
+
+/// What the summary reports, gathered in one pass over the blocks.
+#[derive(Default)]
+struct Scan<'a> {
+    outline: Vec<(u8, &'a str)>,
+    sections: Vec<Section>,
+    tables: Vec<TableInfo>,
+    figures: Vec<&'a str>,
+}
+
+impl<'a> Scan<'a> {
+    /// Walk `blocks`, keeping up to `words` words of each section.
+    fn new(blocks: &'a [Block], words: usize) -> Self {
+        let mut scan = Self::default();
+        for block in blocks {
+            match block {
+                Block::Heading { level, text } => {
+                    scan.outline.push((*level, text));
+                    match scan.sections.last_mut() {
+                        Some(s) if s.untitled_marker && s.excerpt.is_empty() => {
+                            s.title = format!("{}: {text}", s.title);
+                            s.untitled_marker = false;
+                        }
+                        _ => scan.start(text.clone(), false),
+                    }
+                }
+                Block::Slide { number } => scan.start(format!("Slide {number}"), true),
+                Block::Page { number } => scan.start(format!("Page {number}"), true),
+                Block::Paragraph { text, .. } | Block::ListItem { text, .. } => {
+                    if matches!(block, Block::Paragraph { .. }) && is_figure_caption(text) {
+                        scan.figures.push(text.lines().next().unwrap_or_default());
+                    }
+                    if scan.sections.is_empty() {
+                        scan.start("(untitled)".into(), false);
+                    }
+                    let section = scan.sections.last_mut().expect("a section was started");
+                    for word in text.split_whitespace() {
+                        if section.excerpt.len() < words {
+                            section.excerpt.push(word.to_string());
+                        } else {
+                            section.truncated = true;
+                        }
+                    }
+                }
+                Block::Table { name, rows } => {
+                    let location = name.clone().unwrap_or_else(|| {
+                        scan.sections
+                            .last()
+                            .map_or_else(|| "(untitled)".into(), |s| s.title.clone())
+                    });
+                    scan.tables.push(TableInfo {
+                        location,
+                        rows: rows.len(),
+                        cols: rows.iter().map(Vec::len).max().unwrap_or(0),
+                    });
+                }
+            }
+        }
+        scan
+    }
+
+    fn start(&mut self, title: String, untitled_marker: bool) {
+        self.sections.push(Section {
+            title,
+            untitled_marker,
+            excerpt: Vec::new(),
+            truncated: false,
+        });
+    }
+}
+
+/// Render the summary of `blocks`, showing up to `words` words of each
+/// section.
+pub(crate) fn render(
+    blocks: &[Block],
+    meta: &Metadata,
+    format: Format,
+    source: &str,
+    words: usize,
+) -> String {
+    let Scan {
+        outline,
+        sections,
+        tables,
+        figures,
+    } = Scan::new(blocks, words);
+    let stats = Stats::new(blocks, format);
+
+    let mut out = format!("# {source}\n\n");
+    let _ = writeln!(out, "- **Format:** {}", format.name());
+    let properties = [
+        ("Title", meta.title.clone()),
+        ("Subject", meta.subject.clone()),
+        ("Author", meta.author.clone()),
+        ("Keywords", meta.keywords.clone()),
+        ("Created", meta.created.map(DateTime::to_iso)),
+        ("Modified", meta.modified.map(DateTime::to_iso)),
+    ];
+    for (label, value) in properties {
+        if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+            let _ = writeln!(out, "- **{label}:** {}", value.trim());
+        }
+    }
+    let marker_noun = if format == Format::Pptx {
+        "slide"
+    } else {
+        "page"
+    };
+    let counts: Vec<String> = [
+        (stats.headings, "heading"),
+        (stats.paragraphs, "paragraph"),
+        (stats.items, "list item"),
+        (stats.tables + stats.sheets, "table"),
+        (stats.slides + stats.pages, marker_noun),
+    ]
+    .into_iter()
+    .filter(|&(n, _)| n > 0)
+    .map(|(n, noun)| plural(n, noun))
+    .chain(std::iter::once(plural(stats.words, "word")))
+    .collect();
+    let _ = writeln!(out, "- **Contents:** {}", counts.join(", "));
+
+    if !outline.is_empty() {
+        out.push_str("\n## Outline\n\n");
+        let top = outline.iter().map(|&(level, _)| level).min().unwrap_or(1);
+        for (level, text) in &outline {
+            let indent = "  ".repeat(usize::from(level - top));
+            let _ = writeln!(out, "{indent}- {text}");
+        }
+    }
+
+    if !tables.is_empty() {
+        out.push_str("\n## Tables\n\n| # | Location | Rows | Columns |\n|---|---|---|---|\n");
+        for (i, table) in tables.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                i + 1,
+                table.location.replace('|', "\\|"),
+                table.rows,
+                table.cols
+            );
+        }
+    }
+
+    if !figures.is_empty() {
+        out.push_str("\n## Figures\n\n");
+        for caption in &figures {
+            let _ = writeln!(out, "- {caption}");
+        }
+    }
+
+    let sections: Vec<&Section> = sections.iter().filter(|s| !s.excerpt.is_empty()).collect();
+    if !sections.is_empty() && words > 0 {
+        out.push_str("\n## Sections\n");
+        for section in sections {
+            let _ = write!(
+                out,
+                "\n### {}\n\n{}",
+                section.title,
+                section.excerpt.join(" ")
+            );
+            out.push_str(if section.truncated { " …\n" } else { "\n" });
+        }
+    }
+    out
+}
+
+/// Whether a paragraph is a figure caption such as `Figure 3: Layout`.
+fn is_figure_caption(text: &str) -> bool {
+    ["Figure ", "Fig. ", "Fig "].iter().any(|prefix| {
+        text.strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+    })
+}
+
+/// `n` and `noun`, pluralized with an `s`.
+fn plural(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{n} {noun}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heading(level: u8, text: &str) -> Block {
+        Block::Heading {
+            level,
+            text: text.into(),
+        }
+    }
+
+    fn para(text: &str) -> Block {
+        Block::Paragraph {
+            text: text.into(),
+            spans: Vec::new(),
+        }
+    }
+
+    // ── render ───────────────────────────────────────────────────
+
+    #[test]
+    fn metadata_outline_and_sections() {
+        let blocks = vec![
+            heading(1, "Intro"),
+            para("one two three four"),
+            heading(2, "Scope"),
+            para("five"),
+            Block::Table {
+                name: None,
+                rows: vec![vec!["a".into(), "b".into()], vec!["c".into()]],
+            },
+        ];
+        let meta = Metadata {
+            title: Some("Plan".into()),
+            ..Metadata::default()
+        };
+        assert_eq!(
+            render(&blocks, &meta, Format::Docx, "plan.docx", 3),
+            "# plan.docx\n\n\
+             - **Format:** docx\n\
+             - **Title:** Plan\n\
+             - **Contents:** 2 headings, 2 paragraphs, 1 table, 10 words\n\
+             \n## Outline\n\n- Intro\n  - Scope\n\
+             \n## Tables\n\n| # | Location | Rows | Columns |\n|---|---|---|---|\n\
+             | 1 | Scope | 2 | 2 |\n\
+             \n## Sections\n\
+             \n### Intro\n\none two three …\n\
+             \n### Scope\n\nfive\n"
+        );
+    }
+
+    #[test]
+    fn slides_take_their_title() {
+        let blocks = vec![
+            Block::Slide { number: 1 },
+            heading(1, "Welcome"),
+            para("hello"),
+            Block::Slide { number: 2 },
+            para("bye"),
+        ];
+        let text = render(&blocks, &Metadata::default(), Format::Pptx, "d.pptx", 10);
+        assert!(text.contains("1 heading, 2 paragraphs, 2 slides, 3 words"));
+        assert!(text.contains("### Slide 1: Welcome\n\nhello\n"));
+        assert!(text.contains("### Slide 2\n\nbye\n"));
+    }
+
+    #[test]
+    fn figure_captions_listed() {
+        let blocks = vec![para("Figure 2: Revenue by region"), para("Figures vary")];
+        let text = render(&blocks, &Metadata::default(), Format::Doc, "a.doc", 0);
+        assert!(text.contains("## Figures\n\n- Figure 2: Revenue by region\n"));
+        assert!(!text.contains("## Sections"));
+    }
+
+    #[test]
+    fn counts_agree_with_stats() {
+        let blocks = vec![
+            Block::Page { number: 1 },
+            heading(1, "Quarterly report"),
+            para("Revenue grew\nin every region."),
+            Block::ListItem {
+                level: 0,
+                ordered: false,
+                text: "north and south".into(),
+                spans: Vec::new(),
+            },
+            Block::Table {
+                name: None,
+                rows: vec![vec!["a b".into(), "c".into()]],
+            },
+            Block::Page { number: 2 },
+            para("Figure 1: Totals"),
+        ];
+        let stats = Stats::new(&blocks, Format::Pdf);
+        let text = render(&blocks, &Metadata::default(), Format::Pdf, "r.pdf", 5);
+        let contents = format!(
+            "- **Contents:** {} heading, {} paragraphs, {} list item, 1 table, {} pages, {} words\n",
+            stats.headings, stats.paragraphs, stats.items, stats.pages, stats.words
+        );
+        assert!(text.contains(&contents), "{text}");
+        assert!(text.contains("1 heading, 2 paragraphs, 1 list item, 1 table, 2 pages, 16 words"));
+    }
+}
@@ -0,0 +1,401 @@
+//! Symbol-font code point mapping.
+//!
+//! Word and `PowerPoint` store checkmarks, arrows, and Greek letters as code
+//! points in the Symbol and Wingdings fonts rather than as Unicode — either
+//! through `<w:sym w:font="Wingdings" w:char="F0FC"/>` or as ordinary text in
+//! a run whose font is a symbol font. Without mapping, these come out as
+//! `ü`, `þ`, or private-use characters. This module maps the commonly used
+//! code points to their Unicode equivalents.
+//!
+//! It also writes superscript and subscript text (`x²`, `H₂O`) in the
+//! Unicode characters made for them, for plain text output.
+
+/// Returns true if `font` is a symbol font we know how to map.
+pub(crate) fn is_symbol_font(font: &str) -> bool {
+    font_table(font).is_some()
+}
+
+/// Map a code point in a symbol font to its Unicode equivalent.
+///
+/// Code points in the `F000`–`F0FF` private-use range (how Word stores
+/// symbol characters) are folded to their low byte first. Returns `None` for
+/// unknown fonts and unmapped code points.
+pub(crate) fn symbol_to_unicode(font: &str, code: u32) -> Option<char> {
+    let table = font_table(font)?;
+    let code = if (0xF000..=0xF0FF).contains(&code) {
+        code & 0xFF
+    } else {
+        code
+    };
+    let byte = u8::try_from(code).ok()?;
+    table.iter().find(|&&(b, _)| b == byte).map(|&(_, c)| c)
+}
+
+/// Map every character of `text` (written in symbol font `font`), keeping
+/// characters that have no mapping.
+pub(crate) fn map_symbol_text(font: &str, text: &str) -> String {
+    text.chars()
+        .map(|c| symbol_to_unicode(font, u32::from(c)).unwrap_or(c))
+        .collect()
+}
+
+/// Parse a `w:char` attribute value (hex, e.g. `"F0FC"`).
+pub(crate) fn parse_char_code(hex: &str) -> Option<u32> {
+    u32::from_str_radix(hex.trim(), 16).ok()
+}
+
+/// `text` in Unicode superscript characters (`x²`, `nᵗʰ`), or `None` if
+/// one of its characters has no superscript form.
+pub(crate) fn to_superscript(text: &str) -> Option<String> {
+    text.chars().map(|c| script_char(SUPERSCRIPT, c)).collect()
+}
+
+/// `text` in Unicode subscript characters (`H₂O`, `xᵢ`), or `None` if one
+/// of its characters has no subscript form.
+pub(crate) fn to_subscript(text: &str) -> Option<String> {
+    text.chars().map(|c| script_char(SUBSCRIPT, c)).collect()
+}
+
+/// `c` in a superscript or subscript `table`; spaces stay spaces.
+fn script_char(table: &[(char, char)], c: char) -> Option<char> {
+    if c == ' ' {
+        return Some(c);
+    }
+    table
+        .iter()
+        .find(|&&(from, _)| from == c)
+        .map(|&(_, to)| to)
+}
+
+fn font_table(font: &str) -> Option<&'static [(u8, char)]> {
+    let font = font.trim();
+    if font.eq_ignore_ascii_case("Wingdings") {
+        Some(WINGDINGS)
+    } else if font.eq_ignore_ascii_case("Wingdings 2") {
+        Some(WINGDINGS_2)
+    } else if font.eq_ignore_ascii_case("Symbol") {
+        Some(SYMBOL)
+    } else {
+        None
+    }
+}
+
+const WINGDINGS: &[(u8, char)] = &[
+    (0x21, '✏'),
+    (0x22, '✂'),
+    (0x23, '✁'),
+    (0x28, '☎'),
+    (0x2A, '✉'),
+    (0x3F, '✍'),
+    (0x41, '✌'),
+    (0x43, '👍'),
+    (0x44, '👎'),
+    (0x45, '☜'),
+    (0x46, '☞'),
+    (0x4A, '☺'),
+    (0x4B, '😐'),
+    (0x4C, '☹'),
+    (0x4E, '☠'),
+    (0x52, '☼'),
+    (0x54, '❄'),
+    (0x58, '✠'),
+    (0x5B, '☯'),
+    (0x6C, '●'),
+    (0x6D, '❍'),
+    (0x6E, '■'),
+    (0x6F, '□'),
+    (0x71, '❑'),
+    (0x72, '❒'),
+    (0x74, '⧫'),
+    (0x75, '◆'),
+    (0x76, '❖'),
+    (0x78, '⌧'),
+    (0x9F, '•'),
+    (0xA1, '○'),
+    (0xA7, '▪'),
+    (0xA8, '◻'),
+    (0xAB, '★'),
+    (0xD8, '➢'),
+    (0xDF, '←'),
+    (0xE0, '→'),
+    (0xE1, '↑'),
+    (0xE2, '↓'),
+    (0xE8, '➔'),
+    (0xEF, '⇦'),
+    (0xF0, '⇨'),
+    (0xF1, '⇧'),
+    (0xF2, '⇩'),
+    (0xFB, '✗'),
+    (0xFC, '✓'),
+    (0xFD, '☒'),
+    (0xFE, '☑'),
+];
+
+const WINGDINGS_2: &[(u8, char)] = &[
+    (0x4F, '✗'),
+    (0x50, '✓'),
+    (0x52, '☑'),
+    (0x53, '☒'),
+    (0x54, '☒'),
+    (0x97, '•'),
+    (0xA3, '☐'),
+];
+
+const SYMBOL: &[(u8, char)] = &[
+    (0x22, '∀'),
+    (0x24, '∃'),
+    (0x27, '∋'),
+    (0x2A, '∗'),
+    (0x2D, '−'),
+    (0x40, '≅'),
+    (0x41, 'Α'),
+    (0x42, 'Β'),
+    (0x43, 'Χ'),
+    (0x44, 'Δ'),
+    (0x45, 'Ε'),
+    (0x46, 'Φ'),
+    (0x47, 'Γ'),
+    (0x48, 'Η'),
+    (0x49, 'Ι'),
+    (0x4A, 'ϑ'),
+    (0x4B, 'Κ'),
+    (0x4C, 'Λ'),
+    (0x4D, 'Μ'),
+    (0x4E, 'Ν'),
+    (0x4F, 'Ο'),
+    (0x50, 'Π'),
+    (0x51, 'Θ'),
+    (0x52, 'Ρ'),
+    (0x53, 'Σ'),
+    (0x54, 'Τ'),
+    (0x55, 'Υ'),
+    (0x56, 'ς'),
+    (0x57, 'Ω'),
+    (0x58, 'Ξ'),
+    (0x59, 'Ψ'),
+    (0x5A, 'Ζ'),
+    (0x5C, '∴'),
+    (0x5E, '⊥'),
+    (0x61, 'α'),
+    (0x62, 'β'),
+    (0x63, 'χ'),
+    (0x64, 'δ'),
+    (0x65, 'ε'),
+    (0x66, 'φ'),
+    (0x67, 'γ'),
+    (0x68, 'η'),
+    (0x69, 'ι'),
+    (0x6A, 'ϕ'),
+    (0x6B, 'κ'),
+    (0x6C, 'λ'),
+    (0x6D, 'μ'),
+    (0x6E, 'ν'),
+    (0x6F, 'ο'),
+    (0x70, 'π'),
+    (0x71, 'θ'),
+    (0x72, 'ρ'),
+    (0x73, 'σ'),
+    (0x74, 'τ'),
+    (0x75, 'υ'),
+    (0x76, 'ϖ'),
+    (0x77, 'ω'),
+    (0x78, 'ξ'),
+    (0x79, 'ψ'),
+    (0x7A, 'ζ'),
+    (0x7E, '∼'),
+    (0xA2, '′'),
+    (0xA3, '≤'),
+    (0xA5, '∞'),
+    (0xA7, '♣'),
+    (0xA8, '♦'),
+    (0xA9, '♥'),
+    (0xAA, '♠'),
+    (0xAB, '↔'),
+    (0xAC, '←'),
+    (0xAD, '↑'),
+    (0xAE, '→'),
+    (0xAF, '↓'),
+    (0xB0, '°'),
+    (0xB1, '±'),
+    (0xB2, '″'),
+    (0xB3, '≥'),
+    (0xB4, '×'),
+    (0xB5, '∝'),
+    (0xB6, '∂'),
+    (0xB7, '•'),
+    (0xB8, '÷'),
+    (0xB9, '≠'),
+    (0xBA, '≡'),
+    (0xBB, '≈'),
+    (0xBC, '…'),
+    (0xC4, '⊗'),
+    (0xC5, '⊕'),
+    (0xC6, '∅'),
+    (0xC7, '∩'),
+    (0xC8, '∪'),
+    (0xC9, '⊃'),
+    (0xCA, '⊇'),
+    (0xCB, '⊄'),
+    (0xCC, '⊂'),
+    (0xCD, '⊆'),
+    (0xCE, '∈'),
+    (0xCF, '∉'),
+    (0xD0, '∠'),
+    (0xD1, '∇'),
+    (0xD2, '®'),
+    (0xD3, '©'),
+    (0xD4, '™'),
+    (0xD5, '∏'),
+    (0xD6, '√'),
+    (0xD7, '⋅'),
+    (0xD8, '¬'),
+    (0xD9, '∧'),
+    (0xDA, '∨'),
+    (0xDB, '⇔'),
+    (0xDC, '⇐'),
+    (0xDD, '⇑'),
+    (0xDE, '⇒'),
+    (0xDF, '⇓'),
+    (0xE0, '◊'),
+    (0xE5, '∑'),
+    (0xF2, '∫'),
+];
+
+const SUPERSCRIPT: &[(char, char)] = &[
+    ('0', '⁰'),
+    ('1', '¹'),
+    ('2', '²'),
+    ('3', '³'),
+    ('4', '⁴'),
+    ('5', '⁵'),
+    ('6', '⁶'),
+    ('7', '⁷'),
+    ('8', '⁸'),
+    ('9', '⁹'),
+    ('+', '⁺'),
+    ('-', '⁻'),
+    ('−', '⁻'),
+    ('=', '⁼'),
+    ('(', '⁽'),
+    (')', '⁾'),
+    ('a', 'ᵃ'),
+    ('b', 'ᵇ'),
+    ('c', 'ᶜ'),
+    ('d', 'ᵈ'),
+    ('e', 'ᵉ'),
+    ('f', 'ᶠ'),
+    ('g', 'ᵍ'),
+    ('h', 'ʰ'),
+    ('i', 'ⁱ'),
+    ('j', 'ʲ'),
+    ('k', 'ᵏ'),
+    ('l', 'ˡ'),
+    ('m', 'ᵐ'),
+    ('n', 'ⁿ'),
+    ('o', 'ᵒ'),
+    ('p', 'ᵖ'),
+    ('r', 'ʳ'),
+    ('s', 'ˢ'),
+    ('t', 'ᵗ'),
+    ('u', 'ᵘ'),
+    ('v', 'ᵛ'),
+    ('w', 'ʷ'),
+    ('x', 'ˣ'),
+    ('y', 'ʸ'),
+    ('z', 'ᶻ'),
+];
+
+const SUBSCRIPT: &[(char, char)] = &[
+    ('0', '₀'),
+    ('1', '₁'),
+    ('2', '₂'),
+    ('3', '₃'),
+    ('4', '₄'),
+    ('5', '₅'),
+    ('6', '₆'),
+    ('7', '₇'),
+    ('8', '₈'),
+    ('9', '₉'),
+    ('+', '₊'),
+    ('-', '₋'),
+    ('−', '₋'),
+    ('=', '₌'),
+    ('(', '₍'),
+    (')', '₎'),
+    ('a', 'ₐ'),
+    ('e', 'ₑ'),
+    ('h', 'ₕ'),
+    ('i', 'ᵢ'),
+    ('j', 'ⱼ'),
+    ('k', 'ₖ'),
+    ('l', 'ₗ'),
+    ('m', 'ₘ'),
+    ('n', 'ₙ'),
+    ('o', 'ₒ'),
+    ('p', 'ₚ'),
+    ('r', 'ᵣ'),
+    ('s', 'ₛ'),
+    ('t', 'ₜ'),
+    ('u', 'ᵤ'),
+    ('v', 'ᵥ'),
+    ('x', 'ₓ'),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wingdings_checkmark_private_use() {
+        assert_eq!(symbol_to_unicode("Wingdings", 0xF0FC), Some('✓'));
+    }
+
+    #[test]
+    fn wingdings_low_byte() {
+        assert_eq!(symbol_to_unicode("Wingdings", 0xFE), Some('☑'));
+    }
+
+    #[test]
+    fn wingdings_2_ballot_box() {
+        assert_eq!(symbol_to_unicode("Wingdings 2", 0xF0A3), Some('☐'));
+    }
+
+    #[test]
+    fn symbol_greek() {
+        assert_eq!(symbol_to_unicode("Symbol", 0xF061), Some('α'));
+        assert_eq!(symbol_to_unicode("symbol", 0x57), Some('Ω'));
+    }
+
+    #[test]
+    fn unknown_font() {
+        assert_eq!(symbol_to_unicode("Arial", 0xF0FC), None);
+        assert!(!is_symbol_font("Calibri"));
+    }
+
+    #[test]
+    fn unmapped_code() {
+        assert_eq!(symbol_to_unicode("Wingdings", 0x20), None);
+        assert_eq!(symbol_to_unicode("Wingdings", 0x1_0000), None);
+    }
+
+    #[test]
+    fn map_text_keeps_unmapped() {
+        assert_eq!(map_symbol_text("Symbol", "a 1"), "α 1");
+    }
+
+    #[test]
+    fn superscripts_and_subscripts() {
+        assert_eq!(to_superscript("2").as_deref(), Some("²"));
+        assert_eq!(to_superscript("th").as_deref(), Some("ᵗʰ"));
+        assert_eq!(to_superscript("n + 1").as_deref(), Some("ⁿ ⁺ ¹"));
+        assert_eq!(to_subscript("2").as_deref(), Some("₂"));
+        assert_eq!(to_superscript("TM"), None);
+        assert_eq!(to_subscript("b"), None);
+    }
+
+    #[test]
+    fn parse_char_code_hex() {
+        assert_eq!(parse_char_code("F0FC"), Some(0xF0FC));
+        assert_eq!(parse_char_code("zz"), None);
+    }
+}
@@ -5,14 +5,26 @@
 //! sheet metadata (`BoundSheet8`), and cell records (LABELSST, NUMBER, RK,
 //! MULRK, FORMULA, LABEL, BOOLERR). Produces the same `Sheet` type used
 //! by the `.xlsx` parser for rendering.
+//!
+//! BIFF5 workbooks (Excel 5.0 and 95) share the cell records, but store
+//! every string as 8-bit text in the workbook codepage, without BIFF8's
+//! flags byte, and have no SST. Their notes and print areas are not read.
+//!
+//! Workbooks encrypted with RC4 or XOR-obfuscated (a FILEPASS record) are
+//! decrypted with `opts.password`, or with Excel's default password when
+//! none is given.
 
 use cfb::CompoundFile;
+use std::collections::HashMap;
 use std::io::{Cursor, Read};
 
+use crate::cancel::CancelToken;
 use crate::codepage;
-use crate::dateconv;
+use crate::crypto::{self, Rc4Stream, XorApp, XorObfuscation};
+use crate::dateconv::{self, Epoch};
 use crate::error::BatdocError;
-use crate::sheet::Sheet;
+use crate::options::{Budget, Comments, Options};
+use crate::sheet::{self, Comment, Cut, Sheet};
 
 // ── BIFF8 record types ────────────────────────────────────────────
 
@@ -34,21 +46,92 @@ const REC_FILEPASS: u16 = 0x002F;
 const REC_FORMAT: u16 = 0x041E;
 const REC_XF: u16 = 0x00E0;
 const REC_CODEPAGE: u16 = 0x0042;
+const REC_NAME: u16 = 0x0018;
+const REC_INTERFACEHDR: u16 = 0x00E1;
+const REC_RRDHEAD: u16 = 0x0138;
+const REC_USREXCL: u16 = 0x0194;
+const REC_FILELOCK: u16 = 0x0195;
+const REC_RRDINFO: u16 = 0x0196;
+const REC_HLINK: u16 = 0x01B8;
+const REC_NOTE: u16 = 0x001C;
+const REC_OBJ: u16 = 0x005D;
+const REC_TXO: u16 = 0x01B6;
+const REC_MERGEDCELLS: u16 = 0x00E5;
+const REC_DATEMODE: u16 = 0x0022;
+
+/// Built-in name index of `Print_Area` in a NAME record.
+const BUILTIN_PRINT_AREA: u8 = 0x06;
 
 /// Extract plain text (TSV) from a BIFF8 .xls file.
-pub(crate) fn extract_plain(data: &[u8]) -> crate::error::Result<String> {
-    let sheets = parse_xls(data)?;
-    Ok(crate::sheet::render_plain(&sheets))
+///
+/// With `opts.table`, each sheet is drawn as a box table instead.
+pub(crate) fn extract_plain(data: &[u8], opts: &Options) -> crate::error::Result<String> {
+    let mut sheets = crate::sheet::choose(parse_xls(data, true, opts)?, opts)?;
+    crate::sheet::truncate(&mut sheets, opts);
+    let comments = crate::sheet::place_comments(&mut sheets, opts.comments);
+    let mut text = if opts.table {
+        crate::sheet::render_boxed(&sheets)
+    } else {
+        crate::sheet::render_plain(&sheets, opts.cell_newline)
+    };
+    text.push_str(&crate::sheet::render_comment_section(&comments, false));
+    Ok(text)
 }
 
 /// Extract markdown-formatted text from a BIFF8 .xls file.
-pub(crate) fn extract_markdown(data: &[u8]) -> crate::error::Result<String> {
-    let sheets = parse_xls(data)?;
-    Ok(crate::sheet::render_markdown(&sheets))
+pub(crate) fn extract_markdown(data: &[u8], opts: &Options) -> crate::error::Result<String> {
+    let mut sheets = crate::sheet::choose(parse_xls(data, true, opts)?, opts)?;
+    crate::sheet::truncate(&mut sheets, opts);
+    let comments = crate::sheet::place_comments(&mut sheets, opts.comments);
+    let mut md = crate::sheet::render_markdown(&sheets);
+    md.push_str(&crate::sheet::render_comment_section(&comments, true));
+    Ok(md)
+}
+
+/// Parse a BIFF8 .xls file into one `Sheet` per worksheet.
+///
+/// Unlike the text renderers, hyperlinks are not folded into the cells, so
+/// the values are suitable for CSV export.
+pub(crate) fn extract_sheets(data: &[u8], opts: &Options) -> crate::error::Result<Vec<Sheet>> {
+    crate::sheet::choose(parse_xls(data, false, opts)?, opts)
+}
+
+/// Parse a BIFF8 .xls file as [`extract_sheets`] does, handing each sheet
+/// to `emit` as soon as it is read. `opts.sheet` and `opts.picked` are not
+/// applied.
+pub(crate) fn stream_sheets(
+    data: &[u8],
+    opts: &Options,
+    emit: &mut dyn FnMut(Sheet) -> crate::error::Result<()>,
+) -> crate::error::Result<()> {
+    each_sheet(data, false, opts, emit)
 }
 
 // ── Record-level types ─────────────────────────────────────────────
 
+/// The BIFF version of a workbook stream, from the `vers` field of its
+/// first BOF record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Biff {
+    /// Excel 5.0 and 95
+    V5,
+    /// Excel 97 and later
+    V8,
+}
+
+impl Biff {
+    /// BIFF5 if the stream starts with a BOF record of version 0x0500;
+    /// anything else is read as BIFF8.
+    fn of_stream(data: &[u8]) -> Self {
+        let bof = REC_BOF.to_le_bytes();
+        if data.get(..2) == Some(&bof[..]) && data.get(4..6) == Some(&[0x00, 0x05][..]) {
+            Self::V5
+        } else {
+            Self::V8
+        }
+    }
+}
+
 /// A raw BIFF8 record: type, offset in stream, and data bytes.
 #[derive(Debug)]
 struct Record<'a> {
@@ -63,6 +146,62 @@ struct SheetEntry {
     bof_offset: u32,
     visibility: u8,
     sheet_type: u8,
+    /// Bounds of the sheet's built-in `Print_Area` name, if it has one.
+    print_area: Option<Area>,
+}
+
+/// A rectangular cell range: 0-based, inclusive bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Area {
+    first_row: usize,
+    last_row: usize,
+    first_col: usize,
+    last_col: usize,
+}
+
+impl Area {
+    /// Read a `Ref8` cell range at `pos`: first and last row, then first
+    /// and last column, as 16-bit values.
+    fn from_ref8(data: &[u8], pos: usize) -> Option<Self> {
+        let u16_at = |pos: usize| {
+            let bytes = data.get(pos..pos + 2)?.try_into().ok()?;
+            Some(usize::from(u16::from_le_bytes(bytes)))
+        };
+        Some(Self {
+            first_row: u16_at(pos)?,
+            last_row: u16_at(pos + 2)?,
+            first_col: u16_at(pos + 4)?,
+            last_col: u16_at(pos + 6)?,
+        })
+    }
+
+    /// Smallest area covering both.
+    fn union(self, other: Self) -> Self {
+        Self {
+            first_row: self.first_row.min(other.first_row),
+            last_row: self.last_row.max(other.last_row),
+            first_col: self.first_col.min(other.first_col),
+            last_col: self.last_col.max(other.last_col),
+        }
+    }
+}
+
+/// A hyperlink from an `HLink` record: the cells it covers, and the URL
+/// or file it opens or the place in the workbook it jumps to.
+#[derive(Debug, PartialEq, Eq)]
+struct Hyperlink {
+    area: Area,
+    url: Option<String>,
+    location: Option<String>,
+}
+
+/// A sheet substream as read: its cells, merged regions, hyperlinks,
+/// and notes.
+struct ParsedSheet {
+    rows: Vec<Vec<String>>,
+    merges: Vec<Area>,
+    links: Vec<Hyperlink>,
+    comments: Vec<Comment>,
 }
 
 /// A cell being placed into the grid.
@@ -75,7 +214,28 @@ struct Cell {
 
 // ── Main parser ────────────────────────────────────────────────────
 
-fn parse_xls(data: &[u8]) -> crate::error::Result<Vec<Sheet>> {
+/// With `opts.print_area`, a sheet with a print area is cropped to it.
+/// Sheets after those that spend `opts.budget` are left out. When
+/// `display` is set (text output rather than CSV), linked cells are
+/// rewritten as `[value](url)`, and notes are read if `opts.comments`
+/// asks for them.
+fn parse_xls(data: &[u8], display: bool, opts: &Options) -> crate::error::Result<Vec<Sheet>> {
+    let mut sheets = Vec::new();
+    each_sheet(data, display, opts, &mut |sheet| {
+        sheets.push(sheet);
+        Ok(())
+    })?;
+    Ok(sheets)
+}
+
+/// [`parse_xls`], handing each sheet to `emit` in turn.
+fn each_sheet(
+    data: &[u8],
+    display: bool,
+    opts: &Options,
+    emit: &mut dyn FnMut(Sheet) -> crate::error::Result<()>,
+) -> crate::error::Result<()> {
+    let (print_area, cancel) = (opts.print_area, &opts.cancel);
     let cursor = Cursor::new(data);
     let mut cfb = CompoundFile::open(cursor)?;
 
@@ -93,33 +253,182 @@ fn parse_xls(data: &[u8]) -> crate::error::Result<Vec<Sheet>> {
     let mut stream = cfb.open_stream(stream_name)?;
     let mut buf = Vec::new();
     stream.read_to_end(&mut buf)?;
+    let biff = Biff::of_stream(&buf);
+    if biff == Biff::V5 {
+        opts.notes.push("read as an Excel 5.0/95 (BIFF5) workbook");
+    }
+    decrypt_workbook(&mut buf, biff, opts.password.as_deref())?;
 
     // Parse all records
     let records = parse_records(&buf);
 
     // Phase 1: Parse workbook globals (SST + sheet entries + XF styles + codepage)
-    // This also detects encryption (FILEPASS record) early.
-    let (sst, sheet_entries, xf_styles, cp) = parse_globals(&records)?;
+    let (sst, sheet_entries, xf_styles, cp) = parse_globals(&records, biff, opts.encoding);
+    opts.notes.push(match opts.encoding {
+        Some(_) => format!("8-bit text decoded as cp{cp} (--encoding)"),
+        None => format!("8-bit text decoded as cp{cp}"),
+    });
 
     // Phase 2: Parse each worksheet substream
-    let mut sheets = Vec::new();
-    for entry in &sheet_entries {
+    // --sheet and --pick choose among all sheets, so those before the
+    // chosen ones must not count
+    let mut budget = if opts.sheet.is_some() || opts.picked.is_some() {
+        Budget::default()
+    } else {
+        opts.budget
+    };
+    for (i, entry) in sheet_entries.iter().enumerate() {
+        if budget.spent() {
+            break;
+        }
+        opts.progress.step("sheet", i + 1, sheet_entries.len());
         // Skip non-worksheet types (charts, macros, VB modules)
         if entry.sheet_type != 0 {
+            opts.notes
+                .push(format!("skipped '{}': not a worksheet", entry.name));
             continue;
         }
         // Skip hidden sheets
-        if entry.visibility != 0 {
+        if entry.visibility != 0 && !opts.hidden {
+            opts.notes
+                .push(format!("skipped hidden sheet '{}'", entry.name));
             continue;
         }
-        let rows = parse_sheet_substream(&buf, entry.bof_offset, &sst, &xf_styles, cp);
-        sheets.push(Sheet {
+        let ParsedSheet {
+            mut rows,
+            merges,
+            links,
+            mut comments,
+        } = parse_sheet_substream(&buf, entry.bof_offset, &sst, &xf_styles, (biff, cp), cancel);
+        cancel.check()?;
+        fill_merged(&merges, &mut rows);
+        if display {
+            apply_hyperlinks(&links, &entry.name, &mut rows);
+        }
+        if !display || opts.comments == Comments::None {
+            comments.clear();
+        }
+        if let Some(area) = entry.print_area.filter(|_| print_area) {
+            rows = crop(rows, area);
+            crop_comments(&mut comments, area);
+        }
+        budget.spend(
+            rows.iter().flatten().map(|value| value.len() + 1).sum(),
+            rows.iter()
+                .filter(|row| row.iter().any(|v| !v.is_empty()))
+                .count(),
+        );
+        emit(Sheet {
             name: entry.name.clone(),
             rows,
-        });
+            comments,
+            charts: Vec::new(),
+            tables: Vec::new(),
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots: Vec::new(),
+        })?;
     }
 
-    Ok(sheets)
+    Ok(())
+}
+
+/// How a workbook stream is encrypted.
+enum Cipher {
+    Rc4(Rc4Stream),
+    Xor(XorObfuscation),
+}
+
+impl Cipher {
+    /// Derive the keys for `password` from the FILEPASS record data
+    /// `info`. BIFF5 workbooks are only ever XOR-obfuscated, and their
+    /// FILEPASS lacks BIFF8's leading encryption type.
+    fn new(info: &[u8], biff: Biff, password: &str) -> crate::error::Result<Self> {
+        let xor = |fields: Option<&[u8]>| {
+            let verifier = fields
+                .map(|f| u16::from_le_bytes([f[2], f[3]]))
+                .ok_or_else(|| BatdocError::Document("truncated FILEPASS record".into()))?;
+            XorObfuscation::new(verifier, password, XorApp::Excel).map(Self::Xor)
+        };
+        match (biff, info.get(..2)) {
+            (Biff::V5, _) => xor(info.get(..4)),
+            (_, Some([0, 0])) => xor(info.get(2..6)),
+            (_, Some([1, 0])) => Rc4Stream::new(&info[2..], password, 1024).map(Self::Rc4),
+            _ => Err(BatdocError::Unsupported(
+                "unsupported workbook encryption".into(),
+            )),
+        }
+    }
+}
+
+/// Decrypt a workbook stream protected by a FILEPASS record in place,
+/// trying `password` and then Excel's default password.
+///
+/// Everything after the FILEPASS record is encrypted, except record
+/// headers, a few records that must stay readable, and the stream offset
+/// at the start of each BOUNDSHEET. RC4 encrypts it as one stream in
+/// 1024-byte blocks; XOR obfuscation starts each record at the XOR array
+/// entry given by the record's offset plus its length.
+fn decrypt_workbook(
+    buf: &mut [u8],
+    biff: Biff,
+    password: Option<&str>,
+) -> crate::error::Result<()> {
+    let mut offset = 0;
+    let filepass = loop {
+        let Some(header) = buf.get(offset..offset + 4) else {
+            return Ok(());
+        };
+        let rec_type = u16::from_le_bytes([header[0], header[1]]);
+        let end =
+            (offset + 4 + usize::from(u16::from_le_bytes([header[2], header[3]]))).min(buf.len());
+        match rec_type {
+            REC_FILEPASS => break offset + 4..end,
+            // End of the globals without a FILEPASS: not encrypted
+            REC_EOF => return Ok(()),
+            _ => offset = end,
+        }
+    };
+
+    let info = &buf[filepass.clone()];
+    let cipher = match password.map(|p| Cipher::new(info, biff, p)) {
+        Some(Ok(cipher)) => cipher,
+        result => match Cipher::new(info, biff, crypto::DEFAULT_XLS_PASSWORD) {
+            Ok(cipher) => cipher,
+            Err(BatdocError::WrongPassword) if result.is_none() => {
+                return Err(BatdocError::Encrypted)
+            }
+            Err(e) => return Err(e),
+        },
+    };
+
+    let plain = match &cipher {
+        Cipher::Rc4(rc4) => {
+            let mut plain = buf.to_vec();
+            rc4.decrypt(&mut plain, 0);
+            plain
+        }
+        Cipher::Xor(_) => Vec::new(),
+    };
+    let mut offset = filepass.end;
+    while offset + 4 <= buf.len() {
+        let rec_type = u16::from_le_bytes([buf[offset], buf[offset + 1]]);
+        let len = usize::from(u16::from_le_bytes([buf[offset + 2], buf[offset + 3]]));
+        let start = offset + 4;
+        let end = (start + len).min(buf.len());
+        let start = match rec_type {
+            REC_BOF | REC_FILEPASS | REC_INTERFACEHDR | REC_RRDHEAD | REC_USREXCL
+            | REC_FILELOCK | REC_RRDINFO => end,
+            REC_BOUNDSHEET => (start + 4).min(end),
+            _ => start,
+        };
+        match &cipher {
+            Cipher::Rc4(_) => buf[start..end].copy_from_slice(&plain[start..end]),
+            Cipher::Xor(xor) => xor.decrypt(&mut buf[start..end], start + len),
+        }
+        offset = end;
+    }
+    Ok(())
 }
 
 /// Maximum number of BIFF8 records to parse (defense-in-depth against
@@ -167,6 +476,8 @@ fn parse_records(data: &[u8]) -> Vec<Record<'_>> {
 struct XfStyles {
     /// For each XF index, true if the numFmtId is a date format.
     is_date: Vec<bool>,
+    /// The workbook's date system, from its DATEMODE record.
+    epoch: Epoch,
 }
 
 impl XfStyles {
@@ -179,14 +490,14 @@ impl XfStyles {
     }
 }
 
-/// Parse workbook globals: extract SST, `BoundSheet8` entries, XF styles,
-/// and codepage.
-///
-/// Detects encrypted files early via the FILEPASS record, returning
-/// an error before doing any further parsing.
+/// Parse workbook globals: extract SST, `BoundSheet8` entries, XF styles
+/// with the date system, and codepage. The stream has already been decrypted, if it was
+/// encrypted. `encoding`, if given, replaces the CODEPAGE record.
 fn parse_globals(
     records: &[Record<'_>],
-) -> crate::error::Result<(Vec<String>, Vec<SheetEntry>, XfStyles, u16)> {
+    biff: Biff,
+    encoding: Option<u16>,
+) -> (Vec<String>, Vec<SheetEntry>, XfStyles, u16) {
     let mut sst = Vec::new();
     let mut sheet_entries = Vec::new();
     // Custom FORMAT records: numFmtId → format string
@@ -194,33 +505,31 @@ fn parse_globals(
     // XF records: each entry's numFmtId
     let mut xf_fmt_ids: Vec<u16> = Vec::new();
     // Codepage from CODEPAGE record (default: 1252 = Western European)
-    let mut cp: u16 = 1252;
+    let mut cp: u16 = encoding.unwrap_or(1252);
+    let mut epoch = Epoch::Y1900;
 
     let mut i = 0;
     while i < records.len() {
         let rec = &records[i];
 
         match rec.rec_type {
-            REC_FILEPASS => {
-                return Err(BatdocError::Document("document is encrypted".into()));
-            }
-            REC_CODEPAGE => {
-                if rec.data.len() >= 2 {
+            REC_CODEPAGE
+                if rec.data.len() >= 2 && encoding.is_none() => {
                     cp = u16::from_le_bytes([rec.data[0], rec.data[1]]);
                 }
-            }
+            // 1 for the 1904 date system of early Mac Excel
+            REC_DATEMODE if rec.data.first() == Some(&1) => epoch = Epoch::Y1904,
             REC_FORMAT => {
-                if let Some((id, code)) = parse_format_record(rec.data, cp) {
+                if let Some((id, code)) = parse_format_record(rec.data, biff, cp) {
                     custom_formats.push((id, code));
                 }
             }
-            REC_XF => {
+            REC_XF
                 // XF record: bytes 2-3 are numFmtId
-                if rec.data.len() >= 4 {
+                if rec.data.len() >= 4 => {
                     let fmt_id = u16::from_le_bytes([rec.data[2], rec.data[3]]);
                     xf_fmt_ids.push(fmt_id);
                 }
-            }
             REC_SST => {
                 // Collect SST + following CONTINUE records
                 let mut combined = rec.data.to_vec();
@@ -236,10 +545,20 @@ fn parse_globals(
                 continue;
             }
             REC_BOUNDSHEET => {
-                if let Some(entry) = parse_boundsheet(rec.data, cp) {
+                if let Some(entry) = parse_boundsheet(rec.data, biff, cp) {
                     sheet_entries.push(entry);
                 }
             }
+            // BIFF5 names hold their ranges in an older formula layout
+            REC_NAME if biff == Biff::V8 => {
+                // NAME records follow all BOUNDSHEET records; itab is the
+                // 1-based index of the sheet the name is local to
+                if let Some((itab, area)) = parse_print_area(rec.data) {
+                    if let Some(entry) = itab.checked_sub(1).and_then(|i| sheet_entries.get_mut(i)) {
+                        entry.print_area = Some(area);
+                    }
+                }
+            }
             REC_EOF => break, // End of workbook globals
             _ => {}
         }
@@ -247,25 +566,31 @@ fn parse_globals(
         i += 1;
     }
 
-    Ok((
+    (
         sst,
         sheet_entries,
         XfStyles {
             is_date: dateconv::resolve_date_styles(&xf_fmt_ids, &custom_formats),
+            epoch,
         },
         cp,
-    ))
+    )
 }
 
 /// Parse a FORMAT record (0x041E) into (`numFmtId`, `format_string`).
 ///
-/// Record format: 2 bytes numFmtId + BIFF8 unicode string (the format code).
-fn parse_format_record(data: &[u8], cp: u16) -> Option<(u16, String)> {
-    if data.len() < 5 {
+/// Record format: 2 bytes numFmtId + BIFF8 unicode string (the format code),
+/// or in BIFF5 a byte string with a 1-byte length.
+fn parse_format_record(data: &[u8], biff: Biff, cp: u16) -> Option<(u16, String)> {
+    if data.len() < 3 {
         return None;
     }
     let id = u16::from_le_bytes([data[0], data[1]]);
-    let (s, _) = read_biff8_string(data, 2, &[], cp);
+    let (s, _) = match biff {
+        Biff::V5 => read_biff5_string(data, 2, 1, cp),
+        Biff::V8 if data.len() >= 5 => read_biff8_string(data, 2, &[], cp),
+        Biff::V8 => return None,
+    };
     Some((id, s))
 }
 
@@ -425,10 +750,31 @@ fn read_biff8_string(
     (result, pos)
 }
 
+/// Read a BIFF5 string: a character count of `count_bytes` (1 or 2)
+/// bytes, then one byte per character in the workbook codepage. Returns
+/// the string and the position after it.
+fn read_biff5_string(data: &[u8], start: usize, count_bytes: usize, cp: u16) -> (String, usize) {
+    let Some(count) = data.get(start..start + count_bytes) else {
+        return (String::new(), data.len());
+    };
+    let char_count = match *count {
+        [n] => usize::from(n),
+        [lo, hi] => usize::from(u16::from_le_bytes([lo, hi])),
+        _ => 0,
+    };
+    let pos = start + count_bytes;
+    let end = (pos + char_count).min(data.len());
+    let text = data[pos..end]
+        .iter()
+        .map(|&b| codepage::decode_byte(b, cp))
+        .collect();
+    (text, end)
+}
+
 // ── BoundSheet8 parsing ────────────────────────────────────────────
 
-fn parse_boundsheet(data: &[u8], cp: u16) -> Option<SheetEntry> {
-    if data.len() < 8 {
+fn parse_boundsheet(data: &[u8], biff: Biff, cp: u16) -> Option<SheetEntry> {
+    if data.len() < 7 || (biff == Biff::V8 && data.len() < 8) {
         return None;
     }
 
@@ -436,9 +782,14 @@ fn parse_boundsheet(data: &[u8], cp: u16) -> Option<SheetEntry> {
     let visibility = data[4];
     let sheet_type = data[5];
     let str_len = usize::from(data[6]);
-    let options = data[7];
 
-    let name = if options & 0x01 != 0 {
+    let name = if biff == Biff::V5 {
+        // A byte string with a 1-byte length
+        if data.len() < 7 + str_len {
+            return None;
+        }
+        read_biff5_string(data, 6, 1, cp).0
+    } else if data[7] & 0x01 != 0 {
         // Unicode string
         let byte_len = str_len * 2;
         if data.len() < 8 + byte_len {
@@ -461,9 +812,113 @@ fn parse_boundsheet(data: &[u8], cp: u16) -> Option<SheetEntry> {
         bof_offset,
         visibility,
         sheet_type,
+        print_area: None,
     })
 }
 
+// ── Defined names ──────────────────────────────────────────────────
+
+/// Parse a NAME (Lbl) record, returning the sheet index (`itab`, 1-based)
+/// and range of a built-in `Print_Area` name. Other names are ignored.
+///
+/// Record format: 2 bytes flags (0x0020 = built-in), 1 byte shortcut key,
+/// 1 byte name length, 2 bytes formula length, 2 reserved bytes, 2 bytes
+/// itab, 4 reserved bytes, the name (flags byte + characters), and then
+/// the formula.
+fn parse_print_area(data: &[u8]) -> Option<(usize, Area)> {
+    if data.len() < 16 {
+        return None;
+    }
+    let flags = u16::from_le_bytes([data[0], data[1]]);
+    let name_len = data[3];
+    let formula_len = usize::from(u16::from_le_bytes([data[4], data[5]]));
+    let itab = usize::from(u16::from_le_bytes([data[8], data[9]]));
+    if flags & 0x0020 == 0 || name_len != 1 || data[15] != BUILTIN_PRINT_AREA {
+        return None;
+    }
+    let formula_start = if data[14] & 0x01 != 0 { 17 } else { 16 };
+    let formula = data.get(formula_start..formula_start + formula_len)?;
+    formula_area(formula).map(|area| (itab, area))
+}
+
+/// Bounding box of the ranges in a print-area formula: one `PtgArea3d`
+/// or `PtgRef3d`, or several joined by `PtgUnion` inside a `PtgMemFunc`.
+/// `None` for anything else.
+fn formula_area(formula: &[u8]) -> Option<Area> {
+    let u16_at = |pos: usize| -> Option<usize> {
+        let b = formula.get(pos..pos + 2)?;
+        Some(usize::from(u16::from_le_bytes([b[0], b[1]])))
+    };
+    // Column fields carry relative-reference flags in the top two bits
+    let col_at = |pos: usize| u16_at(pos).map(|c| c & 0x3FFF);
+
+    let mut bounds: Option<Area> = None;
+    let mut pos = 0;
+    while pos < formula.len() {
+        let (area, len) = match formula[pos] {
+            // PtgArea3d: ixti, rwFirst, rwLast, colFirst, colLast
+            0x3B | 0x5B | 0x7B => {
+                let (r1, r2) = (u16_at(pos + 3)?, u16_at(pos + 5)?);
+                let (c1, c2) = (col_at(pos + 7)?, col_at(pos + 9)?);
+                let area = Area {
+                    first_row: r1.min(r2),
+                    last_row: r1.max(r2),
+                    first_col: c1.min(c2),
+                    last_col: c1.max(c2),
+                };
+                (Some(area), 11)
+            }
+            // PtgRef3d: ixti, row, col
+            0x3A | 0x5A | 0x7A => {
+                let (row, col) = (u16_at(pos + 3)?, col_at(pos + 5)?);
+                let area = Area {
+                    first_row: row,
+                    last_row: row,
+                    first_col: col,
+                    last_col: col,
+                };
+                (Some(area), 7)
+            }
+            // PtgMemFunc (the ranges follow inline) and PtgUnion
+            0x29 | 0x49 | 0x69 => (None, 3),
+            0x10 => (None, 1),
+            _ => return None,
+        };
+        if let Some(area) = area {
+            bounds = Some(bounds.map_or(area, |b| b.union(area)));
+        }
+        pos += len;
+    }
+    bounds
+}
+
+/// Crop a sheet's grid to `area`.
+fn crop(rows: Vec<Vec<String>>, area: Area) -> Vec<Vec<String>> {
+    rows.into_iter()
+        .skip(area.first_row)
+        .take(area.last_row - area.first_row + 1)
+        .map(|row| {
+            row.into_iter()
+                .skip(area.first_col)
+                .take(area.last_col - area.first_col + 1)
+                .collect()
+        })
+        .collect()
+}
+
+/// Keep the comments on cells within `area`, placed as [`crop`] places
+/// their cells.
+fn crop_comments(comments: &mut Vec<Comment>, area: Area) {
+    comments.retain(|c| {
+        (area.first_row..=area.last_row).contains(&c.row)
+            && (area.first_col..=area.last_col).contains(&c.col)
+    });
+    for comment in comments {
+        comment.row -= area.first_row;
+        comment.col -= area.first_col;
+    }
+}
+
 // ── Sheet substream parsing ────────────────────────────────────────
 
 /// Accumulator for collecting cells while parsing a sheet substream.
@@ -471,6 +926,8 @@ struct GridBuilder {
     cells: Vec<Cell>,
     max_row: usize,
     max_col: usize,
+    merges: Vec<Area>,
+    links: Vec<Hyperlink>,
 }
 
 impl GridBuilder {
@@ -479,6 +936,8 @@ impl GridBuilder {
             cells: Vec::new(),
             max_row: 0,
             max_col: 0,
+            merges: Vec::new(),
+            links: Vec::new(),
         }
     }
 
@@ -500,49 +959,64 @@ impl GridBuilder {
 }
 
 /// Parse a sheet substream starting at `bof_offset` in the raw data,
-/// extracting cell values into a 2D grid.
+/// extracting cell values into a 2D grid, along with its merged regions,
+/// hyperlinks, and notes. Strings are read as `biff` stores them, 8-bit
+/// text in codepage `cp`.
 fn parse_sheet_substream(
     data: &[u8],
     bof_offset: u32,
     sst: &[String],
     xf_styles: &XfStyles,
-    cp: u16,
-) -> Vec<Vec<String>> {
+    (biff, cp): (Biff, u16),
+    cancel: &CancelToken,
+) -> ParsedSheet {
     let mut grid = GridBuilder::new();
+    let mut notes = NoteRecords::default();
     let mut offset = bof_offset as usize; // u32 → usize: lossless on 32+ bit
     let mut pending_string_cell: Option<(u16, u16)> = None;
 
     // Verify BOF
-    if offset + 4 > data.len() {
-        return Vec::new();
-    }
-    let rec_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
-    if rec_type != REC_BOF {
-        return Vec::new();
+    let bof = data.get(offset..offset + 2);
+    if bof != Some(&REC_BOF.to_le_bytes()[..]) || offset + 4 > data.len() {
+        return ParsedSheet {
+            rows: Vec::new(),
+            merges: Vec::new(),
+            links: Vec::new(),
+            comments: Vec::new(),
+        };
     }
 
     // Skip BOF record
     let rec_len = usize::from(u16::from_le_bytes([data[offset + 2], data[offset + 3]]));
     offset += 4 + rec_len;
 
-    while offset + 4 <= data.len() {
+    // On cancellation, stop early; the caller reports the error
+    while offset + 4 <= data.len() && !cancel.is_cancelled() {
         let rec_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
         let rec_len = usize::from(u16::from_le_bytes([data[offset + 2], data[offset + 3]]));
         let rec_end = (offset + 4 + rec_len).min(data.len());
         let rec_data = &data[offset + 4..rec_end];
+        if biff == Biff::V8 {
+            notes.record(rec_type, rec_data, cp);
+        }
 
         match rec_type {
             REC_EOF => break,
             REC_LABELSST => handle_labelsst(rec_data, sst, &mut grid),
-            REC_LABEL | REC_RSTRING => handle_label(rec_data, &mut grid, cp),
+            REC_LABEL | REC_RSTRING => handle_label(rec_data, &mut grid, biff, cp),
             REC_NUMBER => handle_number(rec_data, &mut grid, xf_styles),
             REC_RK => handle_rk(rec_data, &mut grid, xf_styles),
             REC_MULRK => handle_mulrk(rec_data, &mut grid, xf_styles),
             REC_FORMULA => {
                 handle_formula(rec_data, &mut grid, &mut pending_string_cell, xf_styles);
             }
-            REC_STRING => handle_string(rec_data, &mut grid, &mut pending_string_cell, cp),
+            REC_STRING => {
+                let cell = pending_string_cell.take();
+                handle_string(rec_data, &mut grid, cell, biff, cp);
+            }
             REC_BOOLERR => handle_boolerr(rec_data, &mut grid),
+            REC_MERGEDCELLS => grid.merges.extend(parse_merged_cells(rec_data)),
+            REC_HLINK => grid.links.extend(parse_hlink(rec_data, cp)),
             _ => {
                 // Clear pending string cell on any non-STRING record
                 // (STRING must immediately follow FORMULA)
@@ -555,7 +1029,116 @@ fn parse_sheet_substream(
         offset = rec_end;
     }
 
-    grid.into_grid()
+    let merges = std::mem::take(&mut grid.merges);
+    let links = std::mem::take(&mut grid.links);
+    ParsedSheet {
+        rows: grid.into_grid(),
+        merges,
+        links,
+        comments: notes.into_comments(),
+    }
+}
+
+// ── Notes ──────────────────────────────────────────────────────────
+
+/// The notes of a sheet, gathered from its records as they go by. Each
+/// note is an `Obj` record of the note kind, a `TxO` record whose text
+/// follows in `Continue` records, and, after the cells, a `Note` record
+/// naming the note's cell and author by the object's ID.
+#[derive(Default)]
+struct NoteRecords {
+    /// The ID of the note object just read, whose `TxO` comes next.
+    object: Option<u16>,
+    /// The text being read: its object's ID and the characters to go.
+    text: Option<(u16, usize, String)>,
+    texts: HashMap<u16, String>,
+    /// Row, column, object ID, and author of each `Note` record.
+    notes: Vec<(u16, u16, u16, String)>,
+}
+
+impl NoteRecords {
+    fn record(&mut self, rec_type: u16, data: &[u8], cp: u16) {
+        if rec_type != REC_CONTINUE {
+            if let Some((id, _, text)) = self.text.take() {
+                self.texts.insert(id, text);
+            }
+        }
+        let u16_at = |pos: usize| {
+            data.get(pos..pos + 2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        };
+        match rec_type {
+            // The common object data (ftCmo) comes first: ft, cb, ot, id
+            REC_OBJ => {
+                self.object = (u16_at(0) == Some(0x15) && u16_at(4) == Some(0x19))
+                    .then(|| u16_at(6))
+                    .flatten();
+            }
+            REC_TXO => {
+                if let (Some(id), Some(chars)) = (self.object.take(), u16_at(10)) {
+                    self.text = Some((id, usize::from(chars), String::new()));
+                }
+            }
+            // Each piece of the text starts with its own flags byte
+            REC_CONTINUE => {
+                if let Some((_, left, text)) = self.text.as_mut().filter(|(_, left, _)| *left > 0) {
+                    let Some((&flags, chars)) = data.split_first() else {
+                        return;
+                    };
+                    let piece = if flags & 0x01 != 0 {
+                        let len = (chars.len() / 2).min(*left);
+                        *left -= len;
+                        decode_utf16le(&chars[..len * 2])
+                    } else {
+                        let len = chars.len().min(*left);
+                        *left -= len;
+                        chars[..len]
+                            .iter()
+                            .map(|&b| codepage::decode_byte(b, cp))
+                            .collect()
+                    };
+                    text.push_str(&piece);
+                }
+            }
+            REC_NOTE => {
+                if let (Some(row), Some(col), Some(id)) = (u16_at(0), u16_at(2), u16_at(6)) {
+                    let (author, _) = read_biff8_string(data, 8, &[], cp);
+                    self.notes.push((row, col, id, author));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The notes whose text was found, in the order of their `Note`
+    /// records. Excel starts the text of a note with its author's name
+    /// and a colon, which is left out since the author is shown anyway.
+    fn into_comments(mut self) -> Vec<Comment> {
+        if let Some((id, _, text)) = self.text.take() {
+            self.texts.insert(id, text);
+        }
+        self.notes
+            .into_iter()
+            .filter_map(|(row, col, id, author)| {
+                let text = self.texts.get(&id)?.trim_start();
+                let text = if author.is_empty() {
+                    text
+                } else {
+                    text.strip_prefix(&format!("{author}:")).unwrap_or(text)
+                };
+                let (row, col) = (usize::from(row), usize::from(col));
+                Some(Comment {
+                    row,
+                    col,
+                    cell: sheet::cell_name(row, col),
+                    author,
+                    text: text.trim().to_string(),
+                    number: 0,
+                    sheet: None,
+                })
+            })
+            .collect()
+    }
 }
 
 // ── Cell record handlers ───────────────────────────────────────────
@@ -571,11 +1154,14 @@ fn handle_labelsst(rec_data: &[u8], sst: &[String], grid: &mut GridBuilder) {
     }
 }
 
-fn handle_label(rec_data: &[u8], grid: &mut GridBuilder, cp: u16) {
+fn handle_label(rec_data: &[u8], grid: &mut GridBuilder, biff: Biff, cp: u16) {
     if rec_data.len() >= 8 {
         let row = u16::from_le_bytes([rec_data[0], rec_data[1]]);
         let col = u16::from_le_bytes([rec_data[2], rec_data[3]]);
-        let (s, _) = read_biff8_string(rec_data, 6, &[], cp);
+        let (s, _) = match biff {
+            Biff::V5 => read_biff5_string(rec_data, 6, 2, cp),
+            Biff::V8 => read_biff8_string(rec_data, 6, &[], cp),
+        };
         grid.push(row, col, s);
     }
 }
@@ -655,15 +1241,12 @@ fn handle_formula(
                 // String result — follows in a STRING record
                 *pending_string_cell = Some((row, col));
             }
-            1 => {
-                let val = if result_bytes[2] != 0 {
-                    "TRUE"
-                } else {
-                    "FALSE"
-                };
-                grid.push(row, col, val.to_string());
+            1 => grid.push(row, col, sheet::bool_text(result_bytes[2] != 0).to_string()),
+            2 => {
+                if let Some(text) = error_text(result_bytes[2]) {
+                    grid.push(row, col, text.to_string());
+                }
             }
-            // 2 = error, skip. 3 = empty string.
             3 => grid.push(row, col, String::new()),
             _ => {}
         }
@@ -682,18 +1265,23 @@ fn handle_formula(
     }
 }
 
+/// Place the string result of the FORMULA cell just before, if any.
 fn handle_string(
     rec_data: &[u8],
     grid: &mut GridBuilder,
-    pending_string_cell: &mut Option<(u16, u16)>,
+    cell: Option<(u16, u16)>,
+    biff: Biff,
     cp: u16,
 ) {
-    if let Some((row, col)) = pending_string_cell.take() {
-        if rec_data.len() >= 3 {
-            let (s, _) = read_biff8_string(rec_data, 0, &[], cp);
-            grid.push(row, col, s);
-        }
-    }
+    let Some((row, col)) = cell else {
+        return;
+    };
+    let s = match biff {
+        Biff::V5 if rec_data.len() >= 2 => read_biff5_string(rec_data, 0, 2, cp).0,
+        Biff::V8 if rec_data.len() >= 3 => read_biff8_string(rec_data, 0, &[], cp).0,
+        _ => return,
+    };
+    grid.push(row, col, s);
 }
 
 fn handle_boolerr(rec_data: &[u8], grid: &mut GridBuilder) {
@@ -702,10 +1290,176 @@ fn handle_boolerr(rec_data: &[u8], grid: &mut GridBuilder) {
         let col = u16::from_le_bytes([rec_data[2], rec_data[3]]);
         let is_error = rec_data[7];
         if is_error == 0 {
-            let val = if rec_data[6] != 0 { "TRUE" } else { "FALSE" };
-            grid.push(row, col, val.to_string());
+            grid.push(row, col, sheet::bool_text(rec_data[6] != 0).to_string());
+        } else if let Some(text) = error_text(rec_data[6]) {
+            grid.push(row, col, text.to_string());
+        }
+    }
+}
+
+/// The literal Excel shows for a BIFF error code, as `.xlsx` files store it.
+const fn error_text(code: u8) -> Option<&'static str> {
+    Some(match code {
+        0x00 => "#NULL!",
+        0x07 => "#DIV/0!",
+        0x0F => "#VALUE!",
+        0x17 => "#REF!",
+        0x1D => "#NAME?",
+        0x24 => "#NUM!",
+        0x2A => "#N/A",
+        0x2B => "#GETTING_DATA",
+        _ => return None,
+    })
+}
+
+// ── Hyperlinks ─────────────────────────────────────────────────────
+
+/// CLSID of the URL moniker, as stored (little-endian fields).
+const URL_MONIKER: [u8; 16] = [
+    0xE0, 0xC9, 0xEA, 0x79, 0xF9, 0xBA, 0xCE, 0x11, 0x8C, 0x82, 0x00, 0xAA, 0x00, 0x4B, 0xA9, 0x0B,
+];
+
+/// CLSID of the file moniker, as stored (little-endian fields).
+const FILE_MONIKER: [u8; 16] = [
+    0x03, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+/// Parse a `MergeCells` record: a count, then a `Ref8` range for each
+/// merged region.
+fn parse_merged_cells(data: &[u8]) -> Vec<Area> {
+    let count = data
+        .get(..2)
+        .map_or(0, |b| u16::from_le_bytes([b[0], b[1]]));
+    (0..usize::from(count))
+        .map_while(|i| Area::from_ref8(data, 2 + i * 8))
+        .collect()
+}
+
+/// Copy the value of each merged region's top-left cell into the rest of
+/// the region, so a heading spanning several columns labels each of them
+/// rather than leaving the columns after the first blank. The grid is not
+/// widened for a region reaching past its last cell.
+fn fill_merged(merges: &[Area], rows: &mut [Vec<String>]) {
+    for area in merges {
+        let Some(value) = rows
+            .get(area.first_row)
+            .and_then(|row| row.get(area.first_col))
+            .filter(|value| !value.is_empty())
+            .cloned()
+        else {
+            continue;
+        };
+        for row in rows.iter_mut().take(area.last_row + 1).skip(area.first_row) {
+            for cell in row.iter_mut().take(area.last_col + 1).skip(area.first_col) {
+                cell.clone_from(&value);
+            }
+        }
+    }
+}
+
+/// Wrap each linked cell of the sheet `name` as `[value](url)`; a link to
+/// a place in the workbook points at the heading of its sheet, as in
+/// `.xlsx` files.
+fn apply_hyperlinks(links: &[Hyperlink], name: &str, rows: &mut [Vec<String>]) {
+    for link in links {
+        let url = link.url.clone().or_else(|| {
+            link.location
+                .as_deref()
+                .and_then(|location| sheet::location_link(location, name))
+        });
+        let Some(url) = url else {
+            continue;
+        };
+        let area = link.area;
+        for row in rows.iter_mut().take(area.last_row + 1).skip(area.first_row) {
+            for cell in row.iter_mut().take(area.last_col + 1).skip(area.first_col) {
+                sheet::link_cell(cell, &url);
+            }
+        }
+    }
+}
+
+/// Parse an `HLink` record: the `Ref8U` cell range, then, after the
+/// hyperlink's CLSID, a Hyperlink Object (MS-OSHARED 2.3.7.1) whose flags
+/// say which of its optional parts follow, in order: display name, target
+/// frame, moniker (or the moniker's string), and location. The moniker
+/// gives a URL or a file path; the location, a place in the workbook.
+fn parse_hlink(data: &[u8], cp: u16) -> Option<Hyperlink> {
+    let u16_at = |pos: usize| Some(u16::from_le_bytes(data.get(pos..pos + 2)?.try_into().ok()?));
+    let u32_at = |pos: usize| {
+        let bytes = data.get(pos..pos + 4)?.try_into().ok()?;
+        usize::try_from(u32::from_le_bytes(bytes)).ok()
+    };
+    // A HyperlinkString: a count of UTF-16 characters, the last a NUL
+    let string_at = |pos: &mut usize| {
+        let bytes = u32_at(*pos)?.checked_mul(2)?;
+        let text = data.get(*pos + 4..*pos + 4 + bytes)?;
+        *pos += 4 + bytes;
+        Some(decode_utf16le(text).trim_end_matches('\0').to_string())
+    };
+    let area = Area::from_ref8(data, 0)?;
+    // Ref8U, hlinkClsid, and streamVersion come before the flags
+    let flags = u32_at(28)?;
+    let mut pos = 32;
+    if flags & 0x10 != 0 {
+        string_at(&mut pos)?; // display name
+    }
+    if flags & 0x80 != 0 {
+        string_at(&mut pos)?; // target frame
+    }
+
+    let mut url = None;
+    if flags & 0x101 == 0x101 {
+        url = Some(string_at(&mut pos)?);
+    } else if flags & 0x1 != 0 {
+        let clsid = data.get(pos..pos + 16)?;
+        pos += 16;
+        if clsid == URL_MONIKER {
+            let len = u32_at(pos)?;
+            let text = data.get(pos + 4..pos + 4 + len)?;
+            pos += 4 + len;
+            url = Some(decode_utf16le(text).split('\0').next()?.to_string());
+        } else if clsid == FILE_MONIKER {
+            let up_dirs = usize::from(u16_at(pos)?);
+            let ansi_len = u32_at(pos + 2)?;
+            let ansi = data.get(pos + 6..pos + 6 + ansi_len)?;
+            // endServer, versionNumber, and 20 reserved bytes follow
+            pos += 6 + ansi_len + 24;
+            let unicode_size = u32_at(pos)?;
+            pos += 4;
+            let path = if unicode_size > 0 {
+                let bytes = u32_at(pos)?;
+                let text = data.get(pos + 6..pos + 6 + bytes)?;
+                pos += unicode_size;
+                decode_utf16le(text)
+            } else {
+                ansi.split(|&b| b == 0)
+                    .next()?
+                    .iter()
+                    .map(|&b| codepage::decode_byte(b, cp))
+                    .collect()
+            };
+            url = Some(format!(
+                "{}{}",
+                "../".repeat(up_dirs),
+                path.replace('\\', "/")
+            ));
+        } else {
+            // Another kind of moniker: its size can't be known
+            return None;
         }
     }
+    let location = if flags & 0x8 != 0 {
+        Some(string_at(&mut pos)?)
+    } else {
+        None
+    };
+
+    Some(Hyperlink {
+        area,
+        url: url.filter(|url| !url.is_empty()),
+        location: location.filter(|location| !location.is_empty()),
+    })
 }
 
 /// Maximum grid cells to allocate (defense-in-depth against crafted files
@@ -742,7 +1496,7 @@ fn cells_to_grid(cells: Vec<Cell>, max_row: usize, max_col: usize) -> Vec<Vec<St
 /// Format a numeric value, converting to ISO date if the XF style is a date format.
 fn format_maybe_date(val: f64, ixfe: u16, xf_styles: &XfStyles) -> String {
     if xf_styles.is_date_xf(ixfe) {
-        dateconv::serial_to_iso(val)
+        dateconv::serial_to_iso(val, xf_styles.epoch)
     } else {
         format_number(val)
     }
@@ -989,7 +1743,7 @@ mod tests {
             0x00, // compressed
         ];
         data.extend_from_slice(b"Sheet");
-        let entry = parse_boundsheet(&data, 1252).unwrap();
+        let entry = parse_boundsheet(&data, Biff::V8, 1252).unwrap();
         assert_eq!(entry.name, "Sheet");
         assert_eq!(entry.bof_offset, 0x1000);
         assert_eq!(entry.visibility, 0);
@@ -1007,7 +1761,7 @@ mod tests {
         ];
         // "Hi" in UTF-16LE
         data.extend_from_slice(&[0x48, 0x00, 0x69, 0x00]);
-        let entry = parse_boundsheet(&data, 1252).unwrap();
+        let entry = parse_boundsheet(&data, Biff::V8, 1252).unwrap();
         assert_eq!(entry.name, "Hi");
         assert_eq!(entry.visibility, 1);
     }
@@ -1015,7 +1769,301 @@ mod tests {
     #[test]
     fn boundsheet_too_short() {
         let data = vec![0x00, 0x00, 0x00];
-        assert!(parse_boundsheet(&data, 1252).is_none());
+        assert!(parse_boundsheet(&data, Biff::V8, 1252).is_none());
+    }
+
+    // ── parse_globals ────────────────────────────────────────────
+
+    #[test]
+    fn encoding_overrides_codepage_record() {
+        let boundsheet = [0, 0x10, 0, 0, 0, 0, 2, 0, 0xC4, 0xE0];
+        let records = [
+            Record {
+                rec_type: REC_CODEPAGE,
+                data: &1251u16.to_le_bytes(),
+            },
+            Record {
+                rec_type: REC_BOUNDSHEET,
+                data: &boundsheet,
+            },
+        ];
+        let (_, sheets, _, cp) = parse_globals(&records, Biff::V8, None);
+        assert_eq!((sheets[0].name.as_str(), cp), ("Да", 1251));
+        let (_, sheets, _, cp) = parse_globals(&records, Biff::V8, Some(1252));
+        assert_eq!((sheets[0].name.as_str(), cp), ("Äà", 1252));
+    }
+
+    #[test]
+    fn datemode_sets_the_epoch() {
+        // XF 0 uses built-in format 14, a date
+        let xf = [0, 0, 14, 0];
+        let globals = |datemode: u16| {
+            let mode = datemode.to_le_bytes();
+            let records = [
+                Record {
+                    rec_type: REC_DATEMODE,
+                    data: &mode,
+                },
+                Record {
+                    rec_type: REC_XF,
+                    data: &xf,
+                },
+            ];
+            parse_globals(&records, Biff::V8, None).2
+        };
+        assert_eq!(format_maybe_date(43830.0, 0, &globals(1)), "2024-01-01");
+        assert_eq!(format_maybe_date(45292.0, 0, &globals(0)), "2024-01-01");
+        assert_eq!(XfStyles::default().epoch, Epoch::Y1900);
+    }
+
+    // ── parse_print_area ──────────────────────────────────────────
+
+    /// A NAME record for a built-in name local to sheet `itab`.
+    fn name_record(builtin: u8, itab: u16, formula: &[u8]) -> Vec<u8> {
+        let mut data = vec![0x20, 0x00, 0x00, 0x01];
+        data.extend_from_slice(&u16::try_from(formula.len()).unwrap().to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(&itab.to_le_bytes());
+        data.extend_from_slice(&[0, 0, 0, 0, 0x00, builtin]);
+        data.extend_from_slice(formula);
+        data
+    }
+
+    /// A `PtgArea3d` for rows r1..=r2, columns c1..=c2 (absolute refs).
+    fn area3d(r1: u16, r2: u16, c1: u16, c2: u16) -> Vec<u8> {
+        let mut ptg = vec![0x3B, 0x00, 0x00];
+        for v in [r1, r2, c1 | 0xC000, c2 | 0xC000] {
+            ptg.extend_from_slice(&v.to_le_bytes());
+        }
+        ptg
+    }
+
+    #[test]
+    fn print_area_single_range() {
+        let data = name_record(BUILTIN_PRINT_AREA, 2, &area3d(1, 4, 0, 2));
+        let (itab, area) = parse_print_area(&data).unwrap();
+        assert_eq!(itab, 2);
+        assert_eq!(
+            area,
+            Area {
+                first_row: 1,
+                last_row: 4,
+                first_col: 0,
+                last_col: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn print_area_union_is_bounded() {
+        let mut formula = vec![0x29, 0x17, 0x00];
+        formula.extend(area3d(0, 2, 3, 3));
+        formula.extend(area3d(5, 9, 1, 1));
+        formula.push(0x10);
+        let (_, area) = parse_print_area(&name_record(BUILTIN_PRINT_AREA, 1, &formula)).unwrap();
+        assert_eq!((area.first_row, area.last_row), (0, 9));
+        assert_eq!((area.first_col, area.last_col), (1, 3));
+    }
+
+    #[test]
+    fn other_names_ignored() {
+        // Print_Titles (0x07), and a formula that is not a plain range
+        assert!(parse_print_area(&name_record(0x07, 1, &area3d(0, 0, 0, 0))).is_none());
+        assert!(
+            parse_print_area(&name_record(BUILTIN_PRINT_AREA, 1, &[0x1E, 0x01, 0x00])).is_none()
+        );
+    }
+
+    #[test]
+    fn crop_to_area() {
+        let rows: Vec<Vec<String>> = (0..4)
+            .map(|r| (0..3).map(|c| format!("{r}{c}")).collect())
+            .collect();
+        let area = Area {
+            first_row: 1,
+            last_row: 2,
+            first_col: 1,
+            last_col: 5,
+        };
+        assert_eq!(crop(rows, area), vec![vec!["11", "12"], vec!["21", "22"]]);
+    }
+
+    // ── merged cells ──────────────────────────────────────────────
+
+    #[test]
+    fn merged_regions_repeat_their_value() {
+        let mut data = 3u16.to_le_bytes().to_vec();
+        // A1:C1, A2:A3, and D1:F1 past the last column
+        for range in [[0u16, 0, 0, 2], [1, 2, 0, 0], [0, 0, 3, 5]] {
+            data.extend(range.iter().flat_map(|n| n.to_le_bytes()));
+        }
+        let merges = parse_merged_cells(&data);
+        assert_eq!(merges.len(), 3);
+        let mut rows = vec![
+            vec!["Q1".to_string(), String::new(), String::new(), "x".into()],
+            vec!["East".into(), "1".into(), "2".into(), "3".into()],
+            vec![String::new(), "4".into(), "5".into(), "6".into()],
+        ];
+        fill_merged(&merges, &mut rows);
+        assert_eq!(
+            rows,
+            [
+                ["Q1", "Q1", "Q1", "x"],
+                ["East", "1", "2", "3"],
+                ["East", "4", "5", "6"],
+            ]
+        );
+        // A truncated record gives the ranges it holds
+        assert_eq!(parse_merged_cells(&data[..12]).len(), 1);
+        assert!(parse_merged_cells(&[]).is_empty());
+    }
+
+    // ── handle_boolerr ────────────────────────────────────────────
+
+    #[test]
+    fn boolerr_cells_as_excel_shows_them() {
+        let mut grid = GridBuilder::new();
+        // row, col, ixfe, value, is_error
+        handle_boolerr(&[0, 0, 0, 0, 0, 0, 1, 0], &mut grid);
+        handle_boolerr(&[0, 0, 1, 0, 0, 0, 0x07, 1], &mut grid);
+        handle_boolerr(&[0, 0, 2, 0, 0, 0, 0x2A, 1], &mut grid);
+        let values: Vec<&str> = grid.cells.iter().map(|c| c.value.as_str()).collect();
+        assert_eq!(values, ["TRUE", "#DIV/0!", "#N/A"]);
+    }
+
+    // ── hyperlinks ────────────────────────────────────────────────
+
+    fn utf16(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    /// An `HLink` record over rows and columns `area`, with `flags` and
+    /// the optional parts after them.
+    fn hlink(area: [u16; 4], flags: u32, parts: &[u8]) -> Vec<u8> {
+        let mut data: Vec<u8> = area.iter().flat_map(|n| n.to_le_bytes()).collect();
+        data.extend([0xD0, 0xC9, 0xEA, 0x79, 0xF9, 0xBA, 0xCE, 0x11]);
+        data.extend([0x8C, 0x82, 0x00, 0xAA, 0x00, 0x4B, 0xA9, 0x0B]);
+        data.extend(2u32.to_le_bytes());
+        data.extend(flags.to_le_bytes());
+        data.extend(parts);
+        data
+    }
+
+    fn hlink_string(text: &str) -> Vec<u8> {
+        let chars = u32::try_from(text.encode_utf16().count() + 1).unwrap();
+        [&chars.to_le_bytes()[..], &utf16(text), &[0, 0]].concat()
+    }
+
+    #[test]
+    fn hlink_url_moniker() {
+        let url = [utf16("https://example.com/"), vec![0, 0]].concat();
+        let len = u32::try_from(url.len()).unwrap();
+        let parts = [
+            hlink_string("Site"),
+            URL_MONIKER.to_vec(),
+            len.to_le_bytes().to_vec(),
+            url,
+        ]
+        .concat();
+        let link = parse_hlink(&hlink([1, 2, 0, 0], 0x17, &parts), 1252).unwrap();
+        assert_eq!(link.url.as_deref(), Some("https://example.com/"));
+        assert_eq!(link.location, None);
+
+        let mut rows = vec![
+            vec!["Name".to_string()],
+            vec!["Site".to_string()],
+            vec![String::new()],
+        ];
+        apply_hyperlinks(&[link], "Links", &mut rows);
+        assert_eq!(rows[1][0], "[Site](https://example.com/)");
+        assert_eq!(rows[2][0], "");
+    }
+
+    #[test]
+    fn hlink_file_moniker_and_location() {
+        let ansi = b"docs\\notes.txt\0";
+        let parts = [
+            FILE_MONIKER.to_vec(),
+            1u16.to_le_bytes().to_vec(),
+            u32::try_from(ansi.len()).unwrap().to_le_bytes().to_vec(),
+            ansi.to_vec(),
+            vec![0xFF, 0xFF, 0xAD, 0xDE],
+            vec![0; 20],
+            0u32.to_le_bytes().to_vec(),
+        ]
+        .concat();
+        let link = parse_hlink(&hlink([0, 0, 0, 0], 0x01, &parts), 1252).unwrap();
+        assert_eq!(link.url.as_deref(), Some("../docs/notes.txt"));
+
+        let link = parse_hlink(
+            &hlink([0, 0, 1, 1], 0x08, &hlink_string("'Q1 Data'!B2")),
+            1252,
+        )
+        .unwrap();
+        assert_eq!(link.location.as_deref(), Some("'Q1 Data'!B2"));
+        let mut rows = vec![vec!["x".to_string(), "See Q1".to_string()]];
+        apply_hyperlinks(&[link], "Index", &mut rows);
+        assert_eq!(rows[0], ["x", "[See Q1](#q1-data)"]);
+    }
+
+    // ── notes ─────────────────────────────────────────────────────
+
+    #[test]
+    fn notes_from_obj_txo_and_note_records() {
+        let obj = |id: u16| {
+            let mut data = vec![0x15, 0, 0x12, 0, 0x19, 0];
+            data.extend(id.to_le_bytes());
+            data.resize(26, 0);
+            data
+        };
+        let txo = |chars: u16| {
+            let mut data = vec![0; 18];
+            data[10..12].copy_from_slice(&chars.to_le_bytes());
+            data
+        };
+        let note = |row: u16, col: u16, id: u16, author: &str| {
+            let mut data = [row, col, 0, id]
+                .iter()
+                .flat_map(|n| n.to_le_bytes())
+                .collect::<Vec<u8>>();
+            data.extend(u16::try_from(author.len()).unwrap().to_le_bytes());
+            data.push(0);
+            data.extend(author.bytes());
+            data
+        };
+        let first = "Ann:\nCheck the total";
+        let stream = [
+            record(REC_BOF, &[0, 6, 0x10, 0]),
+            record(REC_LABEL, &[1, 0, 2, 0, 0, 0, 1, 0, 0, b'x']),
+            record(REC_OBJ, &obj(1)),
+            record(REC_TXO, &txo(u16::try_from(first.len()).unwrap())),
+            // The text, split across two pieces, then its formatting runs
+            record(REC_CONTINUE, &[&[0][..], &first.as_bytes()[..10]].concat()),
+            record(REC_CONTINUE, &[&[0][..], &first.as_bytes()[10..]].concat()),
+            record(REC_CONTINUE, &[0; 16]),
+            record(REC_OBJ, &obj(2)),
+            record(REC_TXO, &txo(2)),
+            record(REC_CONTINUE, &[1, b'O', 0, b'K', 0]),
+            record(REC_NOTE, &note(1, 2, 1, "Ann")),
+            record(REC_NOTE, &note(0, 0, 2, "")),
+            record(REC_EOF, &[]),
+        ]
+        .concat();
+        let parsed = parse_sheet_substream(
+            &stream,
+            0,
+            &[],
+            &XfStyles::default(),
+            (Biff::V8, 1252),
+            &CancelToken::default(),
+        );
+        let notes: Vec<(&str, &str, &str)> = parsed
+            .comments
+            .iter()
+            .map(|c| (c.cell.as_str(), c.author.as_str(), c.text.as_str()))
+            .collect();
+        assert_eq!(notes, [("C2", "Ann", "Check the total"), ("A1", "", "OK")]);
+        assert_eq!((parsed.comments[0].row, parsed.comments[0].col), (1, 2));
     }
 
     // ── cells_to_grid ─────────────────────────────────────────────
@@ -1136,4 +2184,173 @@ mod tests {
         let strings = parse_sst(&data, &[boundary], 1252);
         assert_eq!(strings, vec!["ABC"]);
     }
+
+    // ── decrypt_workbook ──────────────────────────────────────────
+
+    fn record(rec_type: u16, data: &[u8]) -> Vec<u8> {
+        let len = u16::try_from(data.len()).unwrap();
+        [&rec_type.to_le_bytes()[..], &len.to_le_bytes(), data].concat()
+    }
+
+    fn encrypted_workbook(password: &str) -> (Vec<u8>, Vec<u8>) {
+        let filepass = [&[1, 0][..], &crypto::rc4_header(password)].concat();
+        let plain = [
+            record(REC_BOF, &[0, 6, 5, 0]),
+            record(REC_FILEPASS, &filepass),
+            record(REC_CODEPAGE, &[0xE4, 0x04]),
+            record(REC_BOUNDSHEET, &[0x40, 0, 0, 0, 0, 0, 2, 0, b'Q', b'1']),
+            record(REC_EOF, &[]),
+        ]
+        .concat();
+        // RC4 is symmetric, so decrypting the plain stream encrypts it
+        let mut encrypted = plain.clone();
+        decrypt_workbook(&mut encrypted, Biff::V8, Some(password)).unwrap();
+        (plain, encrypted)
+    }
+
+    #[test]
+    fn decrypts_with_default_password() {
+        let (plain, encrypted) = encrypted_workbook(crypto::DEFAULT_XLS_PASSWORD);
+        assert_ne!(encrypted, plain);
+        // Headers, BOF, and the BOUNDSHEET stream offset stay in the clear
+        assert_eq!(encrypted[..4], plain[..4]);
+        let boundsheet = plain.len() - 4 - 14;
+        assert_eq!(
+            encrypted[boundsheet..boundsheet + 8],
+            plain[boundsheet..boundsheet + 8]
+        );
+        for password in [None, Some("unrelated")] {
+            let mut buf = encrypted.clone();
+            decrypt_workbook(&mut buf, Biff::V8, password).unwrap();
+            assert_eq!(buf, plain);
+        }
+    }
+
+    #[test]
+    fn decrypts_with_password() {
+        let (plain, mut buf) = encrypted_workbook("secret");
+        assert!(matches!(
+            decrypt_workbook(&mut buf, Biff::V8, None),
+            Err(BatdocError::Encrypted)
+        ));
+        assert!(matches!(
+            decrypt_workbook(&mut buf, Biff::V8, Some("Secret")),
+            Err(BatdocError::WrongPassword)
+        ));
+        decrypt_workbook(&mut buf, Biff::V8, Some("secret")).unwrap();
+        assert_eq!(buf, plain);
+    }
+
+    #[test]
+    fn decrypts_xor_obfuscation() {
+        let header = crypto::xor_header("secret");
+        let verifier = u16::from_le_bytes([header[2], header[3]]);
+        let xor = XorObfuscation::new(verifier, "secret", XorApp::Excel).unwrap();
+        let records = [
+            (REC_BOF, vec![0, 6, 5, 0]),
+            (REC_FILEPASS, [&[0, 0][..], &header].concat()),
+            (REC_CODEPAGE, vec![0xE4, 0x04]),
+            (REC_BOUNDSHEET, vec![0x40, 0, 0, 0, 0, 0, 2, 0, b'Q', b'1']),
+            (REC_EOF, vec![]),
+        ];
+        let plain: Vec<u8> = records.iter().flat_map(|(t, d)| record(*t, d)).collect();
+        let mut encrypted = Vec::new();
+        for (rec_type, data) in &records {
+            let clear = match *rec_type {
+                REC_BOF | REC_FILEPASS => data.len(),
+                REC_BOUNDSHEET => 4,
+                _ => 0,
+            };
+            let mut data = data.clone();
+            let at = encrypted.len() + 4 + clear + data.len();
+            xor.encrypt(&mut data[clear..], at);
+            encrypted.extend(record(*rec_type, &data));
+        }
+        assert_ne!(encrypted, plain);
+
+        let mut buf = encrypted.clone();
+        assert!(matches!(
+            decrypt_workbook(&mut buf, Biff::V8, None),
+            Err(BatdocError::Encrypted)
+        ));
+        assert!(matches!(
+            decrypt_workbook(&mut buf, Biff::V8, Some("Secret")),
+            Err(BatdocError::WrongPassword)
+        ));
+        decrypt_workbook(&mut buf, Biff::V8, Some("secret")).unwrap();
+        assert_eq!(buf, plain);
+    }
+
+    // ── BIFF5 ─────────────────────────────────────────────────────
+
+    #[test]
+    fn biff5_workbook_strings() {
+        let bof = record(REC_BOF, &[0, 5, 5, 0]);
+        let label = |row: u8, col: u8, text: &[u8]| {
+            let len = u8::try_from(text.len()).unwrap();
+            record(
+                REC_LABEL,
+                &[&[row, 0, col, 0, 0, 0, len, 0][..], text].concat(),
+            )
+        };
+        let boundsheet = |offset: u8| {
+            record(
+                REC_BOUNDSHEET,
+                &[offset, 0, 0, 0, 0, 0, 3, b'D', 0xE4, b'n'],
+            )
+        };
+        let globals_len = bof.len() + 6 + boundsheet(0).len() + 4;
+        let stream = [
+            bof.clone(),
+            record(REC_CODEPAGE, &[0xE4, 0x04]),
+            boundsheet(u8::try_from(globals_len).unwrap()),
+            record(REC_EOF, &[]),
+            bof,
+            label(0, 0, b"Stra\xDFe"),
+            // A formula whose string result follows in a STRING record
+            record(
+                REC_FORMULA,
+                &[&[0, 0, 1, 0, 0, 0, 0, 0][..], &[0; 4], &[0xFF; 2], &[0; 6]].concat(),
+            ),
+            record(REC_STRING, &[2, 0, b'o', b'k']),
+            record(REC_EOF, &[]),
+        ]
+        .concat();
+        let biff = Biff::of_stream(&stream);
+        assert_eq!(biff, Biff::V5);
+        let records = parse_records(&stream);
+        let (_, sheets, styles, cp) = parse_globals(&records, biff, None);
+        assert_eq!(sheets[0].name, "Dän");
+        let parsed = parse_sheet_substream(
+            &stream,
+            sheets[0].bof_offset,
+            &[],
+            &styles,
+            (biff, cp),
+            &CancelToken::default(),
+        );
+        assert_eq!(parsed.rows, [["Straße", "ok"]]);
+
+        // BIFF5 encryption is always XOR obfuscation, with no type field
+        let mut buf = [
+            record(REC_BOF, &[0, 5, 5, 0]),
+            record(REC_FILEPASS, &crypto::xor_header("secret")),
+        ]
+        .concat();
+        assert!(matches!(
+            decrypt_workbook(&mut buf, Biff::V5, None),
+            Err(BatdocError::Encrypted)
+        ));
+        decrypt_workbook(&mut buf, Biff::V5, Some("secret")).unwrap();
+        assert_eq!(Biff::of_stream(&buf[..8]), Biff::V5);
+        assert_eq!(Biff::of_stream(&[]), Biff::V8);
+    }
+
+    #[test]
+    fn unencrypted_workbook_is_untouched() {
+        let plain = [record(REC_BOF, &[0, 6, 5, 0]), record(REC_EOF, &[])].concat();
+        let mut buf = plain.clone();
+        decrypt_workbook(&mut buf, Biff::V8, Some("secret")).unwrap();
+        assert_eq!(buf, plain);
+    }
 }
@@ -4,59 +4,153 @@
 //! worksheet's XML, then renders every sheet as either tab-separated
 //! plain text or a markdown table with a heading per sheet. Hyperlinks
 //! are resolved from sheet relationship files and rendered as markdown links.
+//! Notes and threaded comments on cells are shown with `--comments`, and
+//! the cached data of charts drawn on a sheet follows its cells. The
+//! workbook's defined names are listed by `--metadata` and `--list`.
+//! Large worksheets can be written as TSV or CSV a row at a time.
 
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
-use std::io::{Cursor, Read};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::ops::RangeInclusive;
 use zip::ZipArchive;
 
-use crate::dateconv;
-use crate::sheet::Sheet;
-use crate::xml_util::{self, get_attr, Rels};
+use crate::cancel::CancelToken;
+use crate::chart::Chart;
+use crate::dateconv::{self, Epoch};
+use crate::markup;
+use crate::numfmt;
+use crate::options::Messages;
+use crate::options::{Budget, Comments, Options};
+use crate::pivot::Pivot;
+use crate::sheet::{self, Comment, Cut, NamedTable, Sheet};
+use crate::xml_util::{self, get_attr, unescaped_attr, Rels};
 
 /// Extract plain text (TSV) from an .xlsx file.
-pub(crate) fn extract_plain(data: &[u8]) -> crate::error::Result<String> {
-    let sheets = parse_xlsx(data)?;
-    Ok(crate::sheet::render_plain(&sheets))
+///
+/// With `opts.table`, each sheet is drawn as a box table instead.
+pub(crate) fn extract_plain(data: &[u8], opts: &Options) -> crate::error::Result<String> {
+    let mut sheets = crate::sheet::choose(parse_xlsx(data, true, false, opts)?, opts)?;
+    crate::sheet::truncate(&mut sheets, opts);
+    let comments = crate::sheet::place_comments(&mut sheets, opts.comments);
+    let mut text = if opts.table {
+        crate::sheet::render_boxed(&sheets)
+    } else {
+        crate::sheet::render_plain(&sheets, opts.cell_newline)
+    };
+    text.push_str(&crate::sheet::render_comment_section(&comments, false));
+    Ok(text)
 }
 
 /// Extract markdown-formatted text from an .xlsx file.
 ///
-/// When `images` is true, embedded images from drawings are extracted
-/// and appended as reference-style base64 images with definitions at the end.
-pub(crate) fn extract_markdown(data: &[u8], images: bool) -> crate::error::Result<String> {
-    let sheets = parse_xlsx(data)?;
-    let mut md = crate::sheet::render_markdown(&sheets);
+/// When `opts.images` is true, embedded images from drawings are extracted
+/// as reference-style base64 images, shown after the sheet they are placed
+/// on, with definitions at the end.
+pub(crate) fn extract_markdown(data: &[u8], opts: &Options) -> crate::error::Result<String> {
+    let mut sheets = crate::sheet::choose(parse_xlsx(data, true, true, opts)?, opts)?;
+    crate::sheet::truncate(&mut sheets, opts);
 
-    if images {
+    let mut definitions = Vec::new();
+    if opts.images {
         let cursor = Cursor::new(data);
         let mut archive = ZipArchive::new(cursor)?;
-        let sheet_info = discover_sheets(&mut archive)?;
-        append_sheet_images(&mut md, &sheet_info, &mut archive);
+        let sheet_info = discover_sheets(&mut archive, opts.hidden, &Messages::default())?;
+        let mut count = 0;
+        for sheet in &mut sheets {
+            if let Some((_, path)) = sheet_info.iter().find(|(name, _)| *name == sheet.name) {
+                sheet.images = read_sheet_images(&mut archive, path, &mut count, &mut definitions);
+            }
+        }
+    }
+
+    let comments = crate::sheet::place_comments(&mut sheets, opts.comments);
+    let mut md = crate::sheet::render_markdown(&sheets);
+    md.push_str(&crate::sheet::render_comment_section(&comments, true));
+    for def in &definitions {
+        md.push_str(def);
+        md.push('\n');
     }
 
     Ok(md)
 }
 
+/// Parse an .xlsx file into raw cell values, one `Sheet` per worksheet.
+///
+/// Unlike the text renderers, hyperlinks are not folded into the cells, so
+/// the values are suitable for CSV export.
+pub(crate) fn extract_sheets(data: &[u8], opts: &Options) -> crate::error::Result<Vec<Sheet>> {
+    crate::sheet::choose(parse_xlsx(data, false, false, opts)?, opts)
+}
+
+/// Parse an .xlsx file as [`extract_sheets`] does, handing each sheet to
+/// `emit` as soon as it is read. `opts.sheet` and `opts.picked` are not
+/// applied.
+pub(crate) fn stream_sheets(
+    data: &[u8],
+    opts: &Options,
+    emit: &mut dyn FnMut(Sheet) -> crate::error::Result<()>,
+) -> crate::error::Result<()> {
+    each_sheet(data, false, false, opts, emit)
+}
+
 // ── Parsing ────────────────────────────────────────────────────────
 
-/// Parse the xlsx archive into a list of sheets.
-fn parse_xlsx(data: &[u8]) -> crate::error::Result<Vec<Sheet>> {
+/// Parse the xlsx archive into a list of sheets. When `display` is set
+/// (text output rather than CSV), linked cells are rewritten as
+/// `[value](url)`, charts and pivot tables are read, and so are comments
+/// if `opts.comments` asks for them. With `markdown` too, bold and italic runs of rich text
+/// keep their emphasis, and Excel tables are read to be shown apart.
+fn parse_xlsx(
+    data: &[u8],
+    display: bool,
+    markdown: bool,
+    opts: &Options,
+) -> crate::error::Result<Vec<Sheet>> {
+    let mut sheets = Vec::new();
+    each_sheet(data, display, markdown, opts, &mut |sheet| {
+        sheets.push(sheet);
+        Ok(())
+    })?;
+    Ok(sheets)
+}
+
+/// [`parse_xlsx`], handing each sheet to `emit` in turn.
+fn each_sheet(
+    data: &[u8],
+    display: bool,
+    markdown: bool,
+    opts: &Options,
+    emit: &mut dyn FnMut(Sheet) -> crate::error::Result<()>,
+) -> crate::error::Result<()> {
+    let cancel = &opts.cancel;
     let cursor = Cursor::new(data);
     let mut archive = ZipArchive::new(cursor)?;
 
     // 1. Load shared strings table (optional — some files use inline strings)
-    let shared_strings = parse_shared_strings(&mut archive);
+    let shared_strings = parse_shared_strings(&mut archive, markdown);
 
-    // 2. Load styles (for date format detection)
+    // 2. Load styles (for number formats) and the date system
     let styles = parse_styles(&mut archive);
 
     // 3. Discover sheets: name + file path
-    let sheet_info = discover_sheets(&mut archive)?;
+    let sheet_info = discover_sheets(&mut archive, opts.hidden, &opts.notes)?;
 
     // 4. Parse each sheet
-    let mut sheets = Vec::new();
-    for (name, path) in &sheet_info {
+    // --sheet and --pick choose among all sheets, so those before the
+    // chosen ones must not count
+    let mut budget = if opts.sheet.is_some() || opts.picked.is_some() {
+        Budget::default()
+    } else {
+        opts.budget
+    };
+    for (i, (name, path)) in sheet_info.iter().enumerate() {
+        if budget.spent() {
+            break;
+        }
+        opts.progress.step("sheet", i + 1, sheet_info.len());
         let mut xml = String::new();
         match archive.by_name(path) {
             Ok(mut entry) => {
@@ -65,33 +159,305 @@ fn parse_xlsx(data: &[u8]) -> crate::error::Result<Vec<Sheet>> {
             Err(_) => continue,
         }
 
-        // Load hyperlink relationships for this sheet
-        let sheet_rels_path = xml_util::rels_path(path);
-        let rels = xml_util::load_rels(&mut archive, &sheet_rels_path);
+        let mut rows = parse_sheet_xml(&xml, &shared_strings, &styles, cancel, &mut budget);
+        cancel.check()?;
 
-        let mut rows = parse_sheet_xml(&xml, &shared_strings, &styles);
+        if display {
+            // Apply hyperlinks: parse <hyperlinks> from sheet XML and
+            // resolve URLs from the rels map
+            let sheet_rels_path = xml_util::rels_path(path);
+            let rels = xml_util::load_rels(&mut archive, &sheet_rels_path);
+            apply_hyperlinks(&xml, name, &rels, &mut rows);
+        }
 
-        // Apply hyperlinks: parse <hyperlinks> from sheet XML and
-        // resolve URLs from the rels map
-        apply_hyperlinks(&xml, &rels, &mut rows);
+        let mut comments = if display && opts.comments != Comments::None {
+            read_comments(&mut archive, path)
+        } else {
+            Vec::new()
+        };
+
+        let mut tables = if markdown {
+            read_tables(&mut archive, path, &xml)
+        } else {
+            Vec::new()
+        };
 
-        sheets.push(Sheet {
+        if !opts.hidden {
+            drop_hidden(&xml, &mut rows, &mut comments, &mut tables);
+        }
+
+        let (charts, pivots) = if display {
+            (
+                read_charts(&mut archive, path),
+                read_pivots(&mut archive, path),
+            )
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        if is_chart_sheet(path) && charts.is_empty() {
+            note_chart_sheet(&opts.notes, name, display);
+        }
+
+        emit(Sheet {
             name: name.clone(),
             rows,
-        });
+            comments,
+            charts,
+            tables,
+            cut: Cut::default(),
+            images: Vec::new(),
+            pivots,
+        })?;
     }
 
-    Ok(sheets)
+    Ok(())
+}
+
+// ── Streaming ─────────────────────────────────────────────────────
+
+/// Size of a worksheet part from which the command line streams a .xlsx
+/// (see [`is_large`]).
+const STREAM_MIN_BYTES: u64 = 32 << 20;
+
+/// Whether some worksheet of a .xlsx is large enough that its rows are
+/// better written as they are read ([`stream_plain`], [`CsvSheets`]) than
+/// gathered into a grid first.
+pub(crate) fn is_large(data: &[u8]) -> bool {
+    let Ok(mut archive) = ZipArchive::new(Cursor::new(data)) else {
+        return false;
+    };
+    (0..archive.len()).any(|i| {
+        archive.by_index_raw(i).is_ok_and(|part| {
+            part.name().starts_with("xl/worksheets/") && part.size() >= STREAM_MIN_BYTES
+        })
+    })
+}
+
+/// Write the plain text of a .xlsx to `out` as [`extract_plain`] would
+/// return it without `--table` or `--comments`, a row at a time, so that
+/// no sheet is ever held whole.
+pub(crate) fn stream_plain(
+    data: &[u8],
+    opts: &Options,
+    out: &mut dyn std::io::Write,
+) -> crate::error::Result<()> {
+    let mut book = StreamedBook::open(data, opts)?;
+    let sheets = std::mem::take(&mut book.sheets);
+    for (i, (name, path)) in sheets.iter().enumerate() {
+        opts.progress.step("sheet", i + 1, sheets.len());
+        let charts = read_charts(&mut book.archive, path);
+        if is_chart_sheet(path) && charts.is_empty() {
+            note_chart_sheet(&opts.notes, name, true);
+        }
+        let rels = xml_util::load_rels(&mut book.archive, &xml_util::rels_path(path));
+        let links = sheet_links(BufReader::new(book.archive.by_name(path)?), name, &rels);
+
+        // A sheet without a cell that is not blank is left out, so its
+        // lines are held back until one shows up
+        let mut held = String::new();
+        if sheets.len() > 1 {
+            sheet::push_plain_heading(&mut held, name, i);
+        }
+        let pivots = read_pivots(&mut book.archive, path);
+        let mut shown = !charts.is_empty() || !pivots.is_empty();
+        let mut after_cells = false;
+        book.read_rows(path, &links, &mut |row| {
+            after_cells |= sheet::push_tsv_row(&mut held, &row, opts.cell_newline);
+            shown = shown || row.iter().any(|cell| !cell.trim().is_empty());
+            if shown {
+                out.write_all(held.as_bytes())?;
+                held.clear();
+            }
+            Ok(())
+        })?;
+        if shown {
+            sheet::push_plain_figures(&mut held, &charts, &pivots, after_cells, opts.cell_newline);
+            out.write_all(held.as_bytes())?;
+        }
+    }
+    Ok(())
 }
 
-// ── Style / date format detection ──────────────────────────────────
+/// The sheets of a .xlsx written as CSV a row at a time, each as
+/// [`sheet::render_csv`] renders it from [`extract_sheets`].
+pub(crate) struct CsvSheets<'a> {
+    book: StreamedBook<'a>,
+    /// For each sheet that is not empty, the columns its rows are padded to.
+    widths: Vec<usize>,
+}
+
+impl<'a> CsvSheets<'a> {
+    /// Open the sheets `opts` chooses, reading each through once to find
+    /// its width and leave it out if it is empty.
+    pub(crate) fn open(data: &'a [u8], opts: &'a Options) -> crate::error::Result<Self> {
+        let mut book = StreamedBook::open(data, opts)?;
+        let mut widths = Vec::new();
+        let sheets = std::mem::take(&mut book.sheets);
+        let count = sheets.len();
+        for (i, (name, path)) in sheets.into_iter().enumerate() {
+            opts.progress.step("sheet", i + 1, count);
+            let mut ncols = 0;
+            book.read_rows(&path, &HashMap::new(), &mut |row| {
+                if let Some(last) = row.iter().rposition(|cell| !cell.trim().is_empty()) {
+                    ncols = ncols.max(last + 1);
+                }
+                Ok(())
+            })?;
+            if ncols > 0 {
+                book.sheets.push((name, path));
+                widths.push(ncols);
+            } else if is_chart_sheet(&path) {
+                note_chart_sheet(&opts.notes, &name, false);
+            }
+        }
+        Ok(Self { book, widths })
+    }
+
+    /// The names of the sheets, in the order they are written.
+    pub(crate) fn names(&self) -> Vec<&str> {
+        self.book
+            .sheets
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Write the sheet at `index` (among [`Self::names`]) to `out`.
+    pub(crate) fn write(
+        &mut self,
+        index: usize,
+        out: &mut dyn std::io::Write,
+    ) -> crate::error::Result<()> {
+        let path = self.book.sheets[index].1.clone();
+        let ncols = self.widths[index];
+        // Blank rows are held back, as the trailing ones are left out
+        let mut held = String::new();
+        self.book.read_rows(&path, &HashMap::new(), &mut |row| {
+            sheet::push_csv_row(&mut held, &row, ncols);
+            if row.iter().any(|cell| !cell.trim().is_empty()) {
+                out.write_all(held.as_bytes())?;
+                held.clear();
+            }
+            Ok(())
+        })
+    }
+}
+
+/// A .xlsx opened to be read a row at a time: its shared strings and
+/// styles, and the sheets `opts` chooses.
+struct StreamedBook<'a> {
+    archive: ZipArchive<Cursor<&'a [u8]>>,
+    shared_strings: Vec<String>,
+    styles: Styles,
+    /// The chosen sheets: their names and worksheet parts.
+    sheets: Vec<(String, String)>,
+    opts: &'a Options,
+}
+
+impl<'a> StreamedBook<'a> {
+    fn open(data: &'a [u8], opts: &'a Options) -> crate::error::Result<Self> {
+        let mut archive = ZipArchive::new(Cursor::new(data))?;
+        let shared_strings = parse_shared_strings(&mut archive, false);
+        let styles = parse_styles(&mut archive);
+        let mut sheets = discover_sheets(&mut archive, opts.hidden, &opts.notes)?;
+
+        // --sheet and --pick choose as they do among parsed sheets
+        let named = sheets
+            .iter()
+            .map(|(name, _)| Sheet {
+                name: name.clone(),
+                rows: Vec::new(),
+                comments: Vec::new(),
+                charts: Vec::new(),
+                tables: Vec::new(),
+                cut: Cut::default(),
+                images: Vec::new(),
+                pivots: Vec::new(),
+            })
+            .collect();
+        let chosen = sheet::choose(named, opts)?;
+        sheets.retain(|(name, _)| chosen.iter().any(|s| &s.name == name));
+
+        Ok(Self {
+            archive,
+            shared_strings,
+            styles,
+            sheets,
+            opts,
+        })
+    }
+
+    /// Read the rows of the worksheet part at `path` as it is
+    /// decompressed, handing each to `emit` as [`parse_xlsx`] lays it out
+    /// in the grid: the cells of `links` written as `[value](url)`, and
+    /// hidden rows and columns left out unless `--hidden`. Rows are only
+    /// as wide as their last cell.
+    fn read_rows(
+        &mut self,
+        path: &str,
+        links: &HashMap<(usize, usize), String>,
+        emit: &mut dyn FnMut(Vec<String>) -> std::io::Result<()>,
+    ) -> crate::error::Result<()> {
+        let mut reader = Reader::from_reader(BufReader::new(self.archive.by_name(path)?));
+        let mut buf = Vec::new();
+        let mut hidden_cols: Vec<usize> = Vec::new();
+        let mut index = 0;
+
+        loop {
+            buf.clear();
+            match reader.read_event_into(&mut buf) {
+                // The columns come before the rows
+                Ok(Event::Empty(ref e) | Event::Start(ref e))
+                    if e.local_name().as_ref() == b"col" && !self.opts.hidden && is_hidden(e) =>
+                {
+                    hidden_cols.extend(col_span(e));
+                    hidden_cols.sort_unstable();
+                    hidden_cols.dedup();
+                }
+                Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"row" => {
+                    self.opts.cancel.check()?;
+                    let hidden = !self.opts.hidden && is_hidden(e);
+                    let cells = parse_row(&mut reader, &self.shared_strings, &self.styles);
+                    index += 1;
+                    if hidden {
+                        continue;
+                    }
+                    let width = cells.iter().map(|&(col, _)| col + 1).max().unwrap_or(0);
+                    let mut row = vec![String::new(); width];
+                    for (col, value) in cells {
+                        row[col] = value;
+                    }
+                    for (col, cell) in row.iter_mut().enumerate() {
+                        if let Some(url) = links.get(&(index - 1, col)) {
+                            sheet::link_cell(cell, url);
+                        }
+                    }
+                    let mut col = 0..;
+                    row.retain(|_| hidden_cols.binary_search(&col.next().unwrap_or(0)).is_err());
+                    emit(row)?;
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// ── Style / number format detection ────────────────────────────────
 
 /// Resolved style information: for each cell style index (`s` attribute),
-/// whether the number format is a date format.
+/// whether the number format is a date format, and its format code.
 #[derive(Debug, Default)]
 struct Styles {
     /// For each xf index, true if the numFmtId is a date format.
     is_date: Vec<bool>,
+    /// For each xf index, the number format code, if it has one other
+    /// than General.
+    codes: Vec<Option<String>>,
+    /// The workbook's date system.
+    epoch: Epoch,
 }
 
 impl Styles {
@@ -99,24 +465,47 @@ impl Styles {
     fn is_date_style(&self, style_idx: usize) -> bool {
         self.is_date.get(style_idx).copied().unwrap_or(false)
     }
+
+    /// The number format code of a cell style index.
+    fn code(&self, style_idx: usize) -> Option<&str> {
+        self.codes.get(style_idx)?.as_deref()
+    }
 }
 
-/// Parse `xl/styles.xml` to determine which cell styles are date formats.
-///
-/// Reads `<numFmt>` elements for custom format strings and `<xf>` elements
-/// in `<cellXfs>` for the numFmtId associated with each style index.
-fn parse_styles(archive: &mut ZipArchive<Cursor<&[u8]>>) -> Styles {
-    let mut xml = String::new();
-    match archive.by_name("xl/styles.xml") {
-        Ok(mut entry) => {
-            if entry.read_to_string(&mut xml).is_err() {
-                return Styles::default();
+/// The date system of `workbook.xml`: 1904 when its `<workbookPr>` says
+/// `date1904`.
+fn parse_date_system(xml: &str) -> Epoch {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(ref e) | Event::Start(ref e))
+                if e.local_name().as_ref() == b"workbookPr" =>
+            {
+                return match get_attr(e, b"date1904").as_deref() {
+                    Some("1" | "true") => Epoch::Y1904,
+                    _ => Epoch::Y1900,
+                };
             }
+            // The properties come before the sheets
+            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"sheets" => break,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
         }
-        Err(_) => return Styles::default(),
     }
+    Epoch::Y1900
+}
 
-    parse_styles_xml(&xml)
+/// Parse `xl/styles.xml` to determine the number format of each cell
+/// style, and `xl/workbook.xml` for the date system.
+///
+/// Reads `<numFmt>` elements for custom format strings and `<xf>` elements
+/// in `<cellXfs>` for the numFmtId associated with each style index.
+fn parse_styles(archive: &mut ZipArchive<Cursor<&[u8]>>) -> Styles {
+    let mut styles = read_part(archive, "xl/styles.xml")
+        .map_or_else(Styles::default, |xml| parse_styles_xml(&xml));
+    styles.epoch =
+        read_part(archive, "xl/workbook.xml").map_or(Epoch::Y1900, |xml| parse_date_system(&xml));
+    styles
 }
 
 /// Parse styles XML into resolved style info (separated for testability).
@@ -149,7 +538,7 @@ fn parse_styles_xml(xml: &str) -> Styles {
                 match name.as_ref() {
                     b"numFmt" => {
                         if let (Some(id_str), Some(code)) =
-                            (get_attr(e, b"numFmtId"), get_attr(e, b"formatCode"))
+                            (get_attr(e, b"numFmtId"), unescaped_attr(e, b"formatCode"))
                         {
                             if let Ok(id) = id_str.parse::<u16>() {
                                 custom_formats.push((id, code));
@@ -173,28 +562,52 @@ fn parse_styles_xml(xml: &str) -> Styles {
         }
     }
 
+    let codes = cell_xf_fmt_ids
+        .iter()
+        .map(|&fmt_id| {
+            custom_formats
+                .iter()
+                .find(|(id, _)| *id == fmt_id)
+                .map(|(_, code)| code.clone())
+                .or_else(|| numfmt::builtin(fmt_id).map(String::from))
+        })
+        .collect();
     Styles {
         is_date: dateconv::resolve_date_styles(&cell_xf_fmt_ids, &custom_formats),
+        codes,
+        epoch: Epoch::Y1900,
     }
 }
 
 // ── Hyperlink resolution ────────────────────────────────────────────
 
-/// Parse `<hyperlinks>` from a sheet XML and apply URLs to cell values.
-///
-/// Each `<hyperlink ref="A1" r:id="rId1"/>` maps a cell reference to
-/// a relationship ID. We resolve the rId to a URL and wrap the existing
-/// cell value as `[value](url)`.
-fn apply_hyperlinks(xml: &str, rels: &Rels, rows: &mut [Vec<String>]) {
-    if rels.is_empty() {
-        return;
+/// Parse `<hyperlinks>` from the XML of the sheet `name` and apply URLs
+/// to cell values, wrapping each linked cell that is not empty as
+/// `[value](url)`.
+fn apply_hyperlinks(xml: &str, name: &str, rels: &Rels, rows: &mut [Vec<String>]) {
+    for ((row, col), url) in sheet_links(xml.as_bytes(), name, rels) {
+        if let Some(cell) = rows.get_mut(row).and_then(|row| row.get_mut(col)) {
+            sheet::link_cell(cell, &url);
+        }
     }
+}
 
-    let mut reader = Reader::from_str(xml);
+/// The URL of each linked cell of a sheet, by 0-based row and column.
+///
+/// Each `<hyperlink ref="A1" r:id="rId1"/>` maps a cell reference to
+/// a relationship ID, which `rels` resolves to the URL. A link within the
+/// workbook has a `location` instead, such as `Sheet2!A1`, and points at
+/// the heading of the sheet it names, or of `sheet` itself when it names
+/// a cell alone.
+fn sheet_links(source: impl BufRead, sheet: &str, rels: &Rels) -> HashMap<(usize, usize), String> {
+    let mut links = HashMap::new();
+    let mut reader = Reader::from_reader(source);
+    let mut buf = Vec::new();
     let mut in_hyperlinks = false;
 
     loop {
-        match reader.read_event() {
+        buf.clear();
+        match reader.read_event_into(&mut buf) {
             Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"hyperlinks" => {
                 in_hyperlinks = true;
             }
@@ -206,120 +619,763 @@ fn apply_hyperlinks(xml: &str, rels: &Rels, rows: &mut [Vec<String>]) {
             {
                 let cell_ref = get_attr(e, b"ref").unwrap_or_default();
                 let rid = get_attr(e, b"r:id").unwrap_or_default();
+                let url = rels.get(&rid).cloned().or_else(|| {
+                    unescaped_attr(e, b"location")
+                        .and_then(|location| sheet::location_link(&location, sheet))
+                });
+
+                if let Some(url) = url {
+                    let cell = (cell_ref_to_row(&cell_ref), col_ref_to_index(&cell_ref));
+                    links.insert(cell, url);
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
 
-                if let Some(url) = rels.get(&rid) {
-                    let col = col_ref_to_index(&cell_ref);
-                    let row_num = cell_ref_to_row(&cell_ref);
-                    if let Some(row) = rows.get_mut(row_num) {
-                        if let Some(cell) = row.get_mut(col) {
-                            if !cell.is_empty() {
-                                *cell = format!("[{cell}]({url})");
-                            }
-                        }
-                    }
+    links
+}
+
+// ── Hidden rows and columns ──────────────────────────────────────
+
+/// Remove the rows and columns a sheet hides (`hidden="1"` on `<row>` and
+/// `<col>`) from its grid, with the comments on them; the other comments
+/// move with their cells, and tables shrink to the lines they keep.
+fn drop_hidden(
+    xml: &str,
+    rows: &mut Vec<Vec<String>>,
+    comments: &mut Vec<Comment>,
+    tables: &mut Vec<NamedTable>,
+) {
+    // Most sheets hide nothing; spare them a second pass
+    if !xml.contains("hidden=") {
+        return;
+    }
+    let (hidden_rows, hidden_cols) = hidden_lines(xml);
+    if hidden_rows.is_empty() && hidden_cols.is_empty() {
+        return;
+    }
+
+    // Where line `n` ends up once the hidden ones are gone, if it stays
+    let shift =
+        |hidden: &[usize], n: usize| hidden.binary_search(&n).err().map(|before| n - before);
+    comments.retain_mut(|comment| {
+        match (
+            shift(&hidden_rows, comment.row),
+            shift(&hidden_cols, comment.col),
+        ) {
+            (Some(row), Some(col)) => {
+                (comment.row, comment.col) = (row, col);
+                true
+            }
+            _ => false,
+        }
+    });
+    // The first and last lines of `range` that stay, where they end up
+    let kept = |hidden: &[usize], range: &RangeInclusive<usize>| {
+        let first = range.clone().find_map(|n| shift(hidden, n))?;
+        let last = range.clone().rev().find_map(|n| shift(hidden, n))?;
+        Some(first..=last)
+    };
+    tables.retain_mut(|table| {
+        let (Some(rows), Some(cols)) = (
+            kept(&hidden_rows, &table.rows),
+            kept(&hidden_cols, &table.cols),
+        ) else {
+            return false;
+        };
+        if hidden_rows.binary_search(table.rows.start()).is_ok() {
+            table.header_row = false;
+        }
+        let mut index = *table.cols.start()..;
+        table.columns.retain(|_| {
+            hidden_cols
+                .binary_search(&index.next().unwrap_or(0))
+                .is_err()
+        });
+        (table.rows, table.cols) = (rows, cols);
+        true
+    });
+
+    let mut index = 0..;
+    rows.retain(|_| {
+        hidden_rows
+            .binary_search(&index.next().unwrap_or(0))
+            .is_err()
+    });
+    if !hidden_cols.is_empty() {
+        for row in rows.iter_mut() {
+            let mut index = 0..;
+            row.retain(|_| {
+                hidden_cols
+                    .binary_search(&index.next().unwrap_or(0))
+                    .is_err()
+            });
+        }
+    }
+}
+
+/// The 0-based indexes, sorted, of the hidden rows and columns of a
+/// sheet. Rows are counted the way [`parse_sheet_xml`] lays them out,
+/// one grid row per `<row>` element.
+fn hidden_lines(xml: &str) -> (Vec<usize>, Vec<usize>) {
+    let mut reader = Reader::from_str(xml);
+    let (mut rows, mut cols) = (Vec::new(), Vec::new());
+    let mut row = 0;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"row" => {
+                if is_hidden(e) {
+                    rows.push(row);
                 }
+                row += 1;
+            }
+            Ok(Event::Empty(ref e) | Event::Start(ref e))
+                if e.local_name().as_ref() == b"col" && is_hidden(e) =>
+            {
+                cols.extend(col_span(e));
             }
             Ok(Event::Eof) | Err(_) => break,
             _ => {}
         }
     }
+
+    cols.sort_unstable();
+    cols.dedup();
+    (rows, cols)
 }
 
-// ── Image extraction ─────────────────────────────────────────────
+/// Whether a `<row>` or `<col>` is hidden.
+fn is_hidden(e: &quick_xml::events::BytesStart) -> bool {
+    matches!(get_attr(e, b"hidden").as_deref(), Some("1" | "true"))
+}
 
-/// Append embedded images from drawing overlays to the markdown output.
-///
-/// For each sheet that has a drawing relationship, parses the drawing XML
-/// to find `<a:blip>` references, reads the images from the ZIP, and
-/// appends them as reference-style markdown images. Inline refs go in the
-/// text flow; definitions are collected and appended at the document end.
-fn append_sheet_images(
-    md: &mut String,
-    sheet_info: &[(String, String)],
+/// The 0-based columns a `<col>` element sets the width of.
+fn col_span(e: &quick_xml::events::BytesStart) -> std::ops::Range<usize> {
+    /// Columns in a worksheet: `<col max>` can run to the last one.
+    const MAX_COLUMNS: usize = 16_384;
+
+    let bound = |name: &[u8]| get_attr(e, name).and_then(|n| n.parse::<usize>().ok());
+    match (bound(b"min"), bound(b"max")) {
+        (Some(min), Some(max)) => min.saturating_sub(1)..max.min(MAX_COLUMNS),
+        _ => 0..0,
+    }
+}
+
+// ── Tables ───────────────────────────────────────────────────────
+
+/// Read the Excel tables (`ListObject`s) on a sheet, in the order of its
+/// `<tableParts>`.
+fn read_tables(
     archive: &mut ZipArchive<Cursor<&[u8]>>,
-) {
-    let mut image_counter = 0usize;
-    let mut inline_refs = Vec::new();
-    let mut definitions = Vec::new();
+    sheet_path: &str,
+    xml: &str,
+) -> Vec<NamedTable> {
+    let rels = xml_util::load_typed_rels(archive, &xml_util::rels_path(sheet_path), "/table");
+    if rels.is_empty() {
+        return Vec::new();
+    }
+    let dir = sheet_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    table_part_rids(xml)
+        .iter()
+        .filter_map(|rid| rels.get(rid))
+        .filter_map(|target| read_part(archive, &xml_util::part_path(dir, target)))
+        .filter_map(|xml| parse_table_xml(&xml))
+        .collect()
+}
 
-    for (_name, path) in sheet_info {
-        // Load the sheet's relationships to find drawing references
-        let sheet_rels_path = xml_util::rels_path(path);
-        let mut rels_xml = String::new();
-        if let Ok(mut entry) = archive.by_name(&sheet_rels_path) {
-            let _ = entry.read_to_string(&mut rels_xml);
-        } else {
-            continue;
+/// The relationship IDs of a sheet's `<tablePart>`s.
+fn table_part_rids(xml: &str) -> Vec<String> {
+    let mut reader = Reader::from_str(xml);
+    let mut rids = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(ref e) | Event::Start(ref e))
+                if e.local_name().as_ref() == b"tablePart" =>
+            {
+                rids.extend(get_attr(e, b"r:id"));
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    rids
+}
+
+/// Parse a table part (`xl/tables/table1.xml`): its name as Excel shows
+/// it, its range, and its column names.
+fn parse_table_xml(xml: &str) -> Option<NamedTable> {
+    let mut reader = Reader::from_str(xml);
+    let mut table: Option<NamedTable> = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(ref e) | Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"table" => {
+                    let name =
+                        unescaped_attr(e, b"displayName").or_else(|| unescaped_attr(e, b"name"))?;
+                    let range = get_attr(e, b"ref")?;
+                    let (first, last) = range.split_once(':').unwrap_or((&range, &range));
+                    table = Some(NamedTable {
+                        name,
+                        rows: cell_ref_to_row(first)..=cell_ref_to_row(last),
+                        cols: col_ref_to_index(first)..=col_ref_to_index(last),
+                        columns: Vec::new(),
+                        header_row: get_attr(e, b"headerRowCount").is_none_or(|n| n != "0"),
+                    });
+                }
+                b"tableColumn" => {
+                    if let Some(table) = &mut table {
+                        table
+                            .columns
+                            .push(unescaped_attr(e, b"name").unwrap_or_default());
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
         }
+    }
+    table
+}
 
-        // Find drawing relationships (Type ends with /drawing)
-        let drawing_targets = parse_drawing_rels(&rels_xml);
-        if drawing_targets.is_empty() {
-            continue;
+// ── Comments ─────────────────────────────────────────────────────
+
+/// Read the comments on a sheet's cells: its threaded comments, replies
+/// and all, and its notes (legacy comments) on other cells. Excel writes
+/// a note alongside each thread for older versions, so a cell's thread
+/// wins over its note.
+fn read_comments(archive: &mut ZipArchive<Cursor<&[u8]>>, sheet_path: &str) -> Vec<Comment> {
+    let rels_path = xml_util::rels_path(sheet_path);
+    let dir = sheet_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let mut comments = Vec::new();
+
+    let threads = xml_util::load_typed_rels(archive, &rels_path, "/threadedComment");
+    if !threads.is_empty() {
+        let persons = xml_util::load_typed_rels(archive, "xl/_rels/workbook.xml.rels", "/person")
+            .into_values()
+            .filter_map(|target| read_part(archive, &xml_util::part_path("xl", &target)))
+            .flat_map(|xml| parse_persons(&xml))
+            .collect();
+        for target in threads.into_values() {
+            if let Some(xml) = read_part(archive, &xml_util::part_path(dir, &target)) {
+                comments.extend(parse_threaded_comments(&xml, &persons));
+            }
         }
+    }
+    for target in xml_util::load_typed_rels(archive, &rels_path, "/comments").into_values() {
+        if let Some(xml) = read_part(archive, &xml_util::part_path(dir, &target)) {
+            for note in parse_notes(&xml) {
+                if !comments
+                    .iter()
+                    .any(|c| (c.row, c.col) == (note.row, note.col))
+                {
+                    comments.push(note);
+                }
+            }
+        }
+    }
+    comments
+}
 
-        let base_dir = path.rsplit_once('/').map_or("xl", |(dir, _)| dir);
+/// A comment on the cell `cell_ref` (`B3`), not yet numbered.
+fn comment_at(cell_ref: &str, author: String, text: &str) -> Comment {
+    let (row, col) = (cell_ref_to_row(cell_ref), col_ref_to_index(cell_ref));
+    Comment {
+        row,
+        col,
+        cell: sheet::cell_name(row, col),
+        author,
+        text: text.trim().to_string(),
+        number: 0,
+        sheet: None,
+    }
+}
 
-        for drawing_target in &drawing_targets {
-            // Resolve drawing path relative to the sheet
-            let drawing_path = if drawing_target.starts_with('/') {
-                drawing_target.trim_start_matches('/').to_string()
-            } else {
-                let raw = format!("{base_dir}/{drawing_target}");
-                // Normalize ../
-                normalize_dotdot(&raw)
-            };
+/// Parse a comments part (`xl/comments1.xml`) into its notes. Excel
+/// starts the text of a note with its author's name and a colon, which
+/// is left out since the author is shown anyway.
+fn parse_notes(xml: &str) -> Vec<Comment> {
+    let mut reader = Reader::from_str(xml);
+    let mut authors: Vec<String> = Vec::new();
+    let mut notes = Vec::new();
+    // The note being read: cell, author, text
+    let mut note: Option<(String, String, String)> = None;
+    let mut in_author = false;
+    // Phonetic runs (`<rPh>`) repeat the text as it is read
+    let mut in_phonetic = false;
 
-            // Read drawing XML
-            let mut drawing_xml = String::new();
-            if let Ok(mut entry) = archive.by_name(&drawing_path) {
-                let _ = entry.read_to_string(&mut drawing_xml);
-            } else {
-                continue;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"author" => {
+                    in_author = true;
+                    authors.push(String::new());
+                }
+                b"comment" => {
+                    let author = get_attr(e, b"authorId")
+                        .and_then(|id| id.parse::<usize>().ok())
+                        .and_then(|id| authors.get(id).cloned())
+                        .unwrap_or_default();
+                    note = Some((
+                        get_attr(e, b"ref").unwrap_or_default(),
+                        author,
+                        String::new(),
+                    ));
+                }
+                b"rPh" => in_phonetic = true,
+                _ => {}
+            },
+            Ok(Event::Text(ref t)) => {
+                let Ok(text) = t.unescape() else { continue };
+                if in_author {
+                    if let Some(author) = authors.last_mut() {
+                        author.push_str(&text);
+                    }
+                } else if let (Some((_, _, body)), false) = (&mut note, in_phonetic) {
+                    body.push_str(&text);
+                }
             }
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"author" => in_author = false,
+                b"rPh" => in_phonetic = false,
+                b"comment" => {
+                    if let Some((cell, author, body)) = note.take() {
+                        let body = body.trim_start();
+                        let body = if author.is_empty() {
+                            body
+                        } else {
+                            body.strip_prefix(&format!("{author}:")).unwrap_or(body)
+                        };
+                        notes.push(comment_at(&cell, author, body));
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    notes
+}
+
+/// Parse a persons part (`xl/persons/person.xml`) into a map from person
+/// ID to display name, for the authors of threaded comments.
+fn parse_persons(xml: &str) -> HashMap<String, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut persons = HashMap::new();
 
-            // Load image rels for the drawing
-            let drawing_rels_path = xml_util::rels_path(&drawing_path);
-            let image_rels = xml_util::load_image_rels(archive, &drawing_rels_path);
-            if image_rels.is_empty() {
-                continue;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(ref e) | Event::Start(ref e))
+                if e.local_name().as_ref() == b"person" =>
+            {
+                if let (Some(id), Some(name)) =
+                    (get_attr(e, b"id"), unescaped_attr(e, b"displayName"))
+                {
+                    persons.insert(id, name);
+                }
             }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    persons
+}
 
-            // Extract blip rIds from drawing XML
-            let rids = parse_drawing_blip_rids(&drawing_xml);
-            let drawing_base = drawing_path.rsplit_once('/').map_or("xl", |(dir, _)| dir);
+/// Parse a threaded comments part into one comment per thread, each
+/// reply added to the text after a dash with its author:
+/// `Is this right? — Bob: Yes`.
+fn parse_threaded_comments(xml: &str, persons: &HashMap<String, String>) -> Vec<Comment> {
+    let mut reader = Reader::from_str(xml);
+    // Thread IDs in order, and each thread's comment
+    let mut threads: Vec<(String, Comment)> = Vec::new();
+    // The comment being read: its thread (its own ID or its parent's), author, and text
+    let mut current: Option<(String, bool, String, String)> = None;
+    let mut cell = String::new();
+    let mut in_text = false;
 
-            for rid in &rids {
-                if let Some(target) = image_rels.get(rid) {
-                    if let Some(data) = xml_util::read_image_from_zip(archive, target, drawing_base)
-                    {
-                        image_counter += 1;
-                        let id = format!("image{image_counter}");
-                        if let Some(img_ref) = crate::markup::image_to_base64_ref(&data, &id) {
-                            inline_refs.push(img_ref.inline);
-                            definitions.push(img_ref.definition);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"threadedComment" => {
+                    let parent = get_attr(e, b"parentId");
+                    let reply = parent.is_some();
+                    let thread = parent.or_else(|| get_attr(e, b"id")).unwrap_or_default();
+                    let author = get_attr(e, b"personId")
+                        .and_then(|id| persons.get(&id).cloned())
+                        .unwrap_or_default();
+                    cell = get_attr(e, b"ref").unwrap_or_default();
+                    current = Some((thread, reply, author, String::new()));
+                }
+                b"text" => in_text = true,
+                _ => {}
+            },
+            Ok(Event::Text(ref t)) if in_text => {
+                if let (Some((_, _, _, text)), Ok(t)) = (&mut current, t.unescape()) {
+                    text.push_str(&t);
+                }
+            }
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"text" => in_text = false,
+                b"threadedComment" => {
+                    let Some((thread, reply, author, text)) = current.take() else {
+                        continue;
+                    };
+                    let text = text.trim();
+                    match threads.iter_mut().find(|(id, _)| *id == thread) {
+                        Some((_, comment)) if reply => {
+                            let author = if author.is_empty() { "Reply" } else { &author };
+                            let _ = write!(comment.text, " — {author}: {text}");
                         }
+                        _ => threads.push((thread, comment_at(&cell, author, text))),
                     }
                 }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    threads.into_iter().map(|(_, comment)| comment).collect()
+}
+
+/// Read a part of the archive as text, if it is there.
+fn read_part(archive: &mut ZipArchive<Cursor<&[u8]>>, path: &str) -> Option<String> {
+    let mut xml = String::new();
+    archive.by_name(path).ok()?.read_to_string(&mut xml).ok()?;
+    Some(xml)
+}
+
+// ── Pivot tables ─────────────────────────────────────────────────
+
+/// Read the layout of the pivot tables on a sheet, each named from the
+/// pivot cache it summarizes.
+fn read_pivots(archive: &mut ZipArchive<Cursor<&[u8]>>, sheet_path: &str) -> Vec<Pivot> {
+    let Some(rels_xml) = read_part(archive, &xml_util::rels_path(sheet_path)) else {
+        return Vec::new();
+    };
+    let dir = sheet_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let mut pivots = Vec::new();
+    for target in rel_targets(&rels_xml, "/pivotTable") {
+        let pivot_path = xml_util::part_path(dir, &target);
+        let Some(xml) = read_part(archive, &pivot_path) else {
+            continue;
+        };
+        let pivot_dir = pivot_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let cache = xml_util::load_typed_rels(
+            archive,
+            &xml_util::rels_path(&pivot_path),
+            "/pivotCacheDefinition",
+        )
+        .into_values()
+        .next()
+        .and_then(|target| read_part(archive, &xml_util::part_path(pivot_dir, &target)));
+        pivots.extend(Pivot::parse(&xml, cache.as_deref()));
+    }
+    pivots
+}
+
+// ── Charts ───────────────────────────────────────────────────────
+
+/// Read the charts drawn on a sheet, in the order its drawings place them.
+fn read_charts(archive: &mut ZipArchive<Cursor<&[u8]>>, sheet_path: &str) -> Vec<Chart> {
+    let Some(rels_xml) = read_part(archive, &xml_util::rels_path(sheet_path)) else {
+        return Vec::new();
+    };
+    let dir = sheet_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let mut charts = Vec::new();
+    for target in rel_targets(&rels_xml, "/drawing") {
+        let drawing_path = xml_util::part_path(dir, &target);
+        let Some(drawing_xml) = read_part(archive, &drawing_path) else {
+            continue;
+        };
+        let chart_rels =
+            xml_util::load_typed_rels(archive, &xml_util::rels_path(&drawing_path), "/chart");
+        let drawing_dir = drawing_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+        for rid in parse_drawing_chart_rids(&drawing_xml) {
+            let chart = chart_rels
+                .get(&rid)
+                .and_then(|target| read_part(archive, &xml_util::part_path(drawing_dir, target)))
+                .and_then(|xml| Chart::parse(&xml));
+            charts.extend(chart);
+        }
+    }
+    charts
+}
+
+/// Extract chart rIds from drawing XML (`<c:chart r:id="rIdN"/>`).
+fn parse_drawing_chart_rids(xml: &str) -> Vec<String> {
+    let mut rids = Vec::new();
+    let mut reader = Reader::from_str(xml);
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e) | Event::Empty(ref e))
+                if e.local_name().as_ref() == b"chart" =>
+            {
+                if let Some(rid) = get_attr(e, b"r:id") {
+                    rids.push(rid);
+                }
             }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
         }
     }
 
-    // Append inline references in the text flow
-    for inline in &inline_refs {
-        md.push_str(inline);
-        md.push_str("\n\n");
+    rids
+}
+
+// ── Defined names ────────────────────────────────────────────────
+
+/// The defined names of a workbook as `(name, range)` pairs, for
+/// `--list`. Empty when the workbook has none or cannot be read.
+pub(crate) fn defined_names(data: &[u8]) -> Vec<(String, String)> {
+    ZipArchive::new(Cursor::new(data))
+        .ok()
+        .and_then(|mut archive| read_part(&mut archive, "xl/workbook.xml"))
+        .map(|xml| parse_defined_names(&xml))
+        .unwrap_or_default()
+}
+
+/// Parse the `<definedNames>` of `workbook.xml` into `(name, range)`
+/// pairs in file order. Names scoped to one sheet carry the sheet's name
+/// in parentheses, built-in names (`_xlnm.Print_Area`) lose their prefix,
+/// and hidden names, which Excel keeps for its own bookkeeping, are left
+/// out.
+pub(crate) fn parse_defined_names(xml: &str) -> Vec<(String, String)> {
+    let mut reader = Reader::from_str(xml);
+    // All sheets, hidden ones included: `localSheetId` indexes them all
+    let mut sheets: Vec<String> = Vec::new();
+    let mut names = Vec::new();
+    // The name being read, and its range as it is read
+    let mut name: Option<(String, String)> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(ref e) | Event::Start(ref e))
+                if e.local_name().as_ref() == b"sheet" =>
+            {
+                sheets.push(unescaped_attr(e, b"name").unwrap_or_default());
+            }
+            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"definedName" => {
+                let hidden = matches!(get_attr(e, b"hidden").as_deref(), Some("1" | "true"));
+                let Some(label) = unescaped_attr(e, b"name").filter(|_| !hidden) else {
+                    continue;
+                };
+                let label = label.strip_prefix("_xlnm.").unwrap_or(&label).to_string();
+                let scope = get_attr(e, b"localSheetId")
+                    .and_then(|id| id.parse::<usize>().ok())
+                    .and_then(|id| sheets.get(id));
+                let label = match scope {
+                    Some(sheet) => format!("{label} ({sheet})"),
+                    None => label,
+                };
+                name = Some((label, String::new()));
+            }
+            Ok(Event::Text(ref t)) => {
+                if let (Some((_, range)), Ok(text)) = (&mut name, t.unescape()) {
+                    range.push_str(&text);
+                }
+            }
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"definedName" => {
+                if let Some((label, range)) = name.take() {
+                    names.push((label, range.trim().to_string()));
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
     }
 
-    // Append definitions at the end
-    for def in &definitions {
-        md.push_str(def);
-        md.push('\n');
+    names
+}
+
+// ── Data validation ──────────────────────────────────────────────
+
+/// The drop-down lists the cells of a workbook are held to, as
+/// `(cells, values)` pairs, for `--list`. Empty when the workbook has
+/// none or cannot be read.
+pub(crate) fn validation_lists(data: &[u8]) -> Vec<(String, String)> {
+    ZipArchive::new(Cursor::new(data))
+        .map(|mut archive| read_validation_lists(&mut archive))
+        .unwrap_or_default()
+}
+
+/// Read the list data validations of every sheet, hidden ones included,
+/// as `(cells, values)` pairs: the cells as `Sheet!C2:C100`, and the
+/// values as `Open, Closed` when the list is written out in the rule, or
+/// the range or name they are taken from otherwise.
+pub(crate) fn read_validation_lists(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+) -> Vec<(String, String)> {
+    let Ok(sheets) = discover_sheets(archive, true, &Messages::default()) else {
+        return Vec::new();
+    };
+    let mut lists = Vec::new();
+    for (name, path) in sheets {
+        let Ok(entry) = archive.by_name(&path) else {
+            continue;
+        };
+        let sheet = if name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            name
+        } else {
+            format!("'{}'", name.replace('\'', "''"))
+        };
+        for (sqref, formula) in parse_validation_lists(BufReader::new(entry)) {
+            let cells: Vec<String> = sqref
+                .split_whitespace()
+                .map(|range| format!("{sheet}!{range}"))
+                .collect();
+            lists.push((cells.join(", "), list_values(&formula)));
+        }
+    }
+    lists
+}
+
+/// The `(sqref, formula1)` of each `type="list"` rule of a sheet, both
+/// `<dataValidation>` and the `<x14:dataValidation>` Excel 2010 writes
+/// for lists taken from another sheet, where the cells and formula are
+/// elements (`<xm:sqref>`, `<xm:f>`) rather than attributes.
+fn parse_validation_lists(source: impl BufRead) -> Vec<(String, String)> {
+    let mut reader = Reader::from_reader(source);
+    let mut buf = Vec::new();
+    let mut lists = Vec::new();
+    // The list rule being read: its cells and formula
+    let mut rule: Option<(String, String)> = None;
+    // Whether the text being read is the rule's formula or its cells
+    let mut field: Option<bool> = None;
+
+    loop {
+        buf.clear();
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"dataValidation" => {
+                    rule = (get_attr(e, b"type").as_deref() == Some("list"))
+                        .then(|| (get_attr(e, b"sqref").unwrap_or_default(), String::new()));
+                }
+                b"formula1" => field = Some(true),
+                b"sqref" => field = Some(false),
+                _ => {}
+            },
+            Ok(Event::Text(ref t)) => {
+                if let (Some(is_formula), Some((sqref, formula))) = (field, rule.as_mut()) {
+                    let text = t.unescape().unwrap_or_default();
+                    if is_formula { formula } else { sqref }.push_str(&text);
+                }
+            }
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"dataValidation" => {
+                    if let Some((sqref, formula)) = rule.take() {
+                        if !sqref.trim().is_empty() && !formula.trim().is_empty() {
+                            lists.push((sqref.trim().to_string(), formula.trim().to_string()));
+                        }
+                    }
+                }
+                b"formula1" | b"sqref" => field = None,
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    lists
+}
+
+/// The values of a list rule's formula: `"Open,Closed"` spelled out as
+/// `Open, Closed`, and a range or name (`Lists!$A$1:$A$5`) as it is.
+fn list_values(formula: &str) -> String {
+    formula
+        .strip_prefix('"')
+        .and_then(|list| list.strip_suffix('"'))
+        .map_or_else(
+            || formula.to_string(),
+            |list| {
+                let list = list.replace("\"\"", "\"");
+                list.split(',')
+                    .map(str::trim)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            },
+        )
+}
+
+// ── Image extraction ─────────────────────────────────────────────
+
+/// Read the embedded images of the sheet part at `path` from its
+/// drawing overlays, numbering them on from `count`.
+///
+/// For each drawing the sheet links to, finds the `<a:blip>` references of
+/// its pictures, reads the images from the ZIP, and returns them as
+/// reference-style markdown images in the order of the cells they are
+/// anchored at. Their definitions go to `definitions`, to be appended at
+/// the document end.
+fn read_sheet_images(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    path: &str,
+    count: &mut usize,
+    definitions: &mut Vec<String>,
+) -> Vec<String> {
+    // Load the sheet's relationships to find drawing references
+    let Some(rels_xml) = read_part(archive, &xml_util::rels_path(path)) else {
+        return Vec::new();
+    };
+    let base_dir = path.rsplit_once('/').map_or("xl", |(dir, _)| dir);
+
+    let mut pictures = Vec::new();
+    for drawing_target in rel_targets(&rels_xml, "/drawing") {
+        // Resolve drawing path relative to the sheet
+        let drawing_path = if drawing_target.starts_with('/') {
+            drawing_target.trim_start_matches('/').to_string()
+        } else {
+            let raw = format!("{base_dir}/{drawing_target}");
+            // Normalize ../
+            normalize_dotdot(&raw)
+        };
+        let Some(drawing_xml) = read_part(archive, &drawing_path) else {
+            continue;
+        };
+
+        // Load image rels for the drawing
+        let drawing_rels_path = xml_util::rels_path(&drawing_path);
+        let image_rels = xml_util::load_image_rels(archive, &drawing_rels_path);
+        if image_rels.is_empty() {
+            continue;
+        }
+
+        let drawing_base = drawing_path.rsplit_once('/').map_or("xl", |(dir, _)| dir);
+        for (anchor, rid) in parse_drawing_blips(&drawing_xml) {
+            if let Some(data) = image_rels
+                .get(&rid)
+                .and_then(|target| xml_util::read_image_from_zip(archive, target, drawing_base))
+            {
+                pictures.push((anchor, data));
+            }
+        }
     }
+
+    // Pictures anchored nowhere in particular go last
+    pictures.sort_by_key(|(anchor, _)| anchor.unwrap_or((usize::MAX, usize::MAX)));
+    let mut inline_refs = Vec::new();
+    for (_, data) in pictures {
+        *count += 1;
+        let id = format!("image{count}");
+        if let Some(img_ref) = crate::markup::image_to_base64_ref(&data, &id) {
+            inline_refs.push(img_ref.inline);
+            definitions.push(img_ref.definition);
+        }
+    }
+    inline_refs
 }
 
-/// Parse relationships XML to find drawing targets.
-fn parse_drawing_rels(xml: &str) -> Vec<String> {
+/// Parse relationships XML to find the targets of the relationships whose
+/// type ends with `type_suffix` (`/drawing`), in file order.
+fn rel_targets(xml: &str, type_suffix: &str) -> Vec<String> {
     let mut targets = Vec::new();
     let mut reader = Reader::from_str(xml);
 
@@ -330,7 +1386,7 @@ fn parse_drawing_rels(xml: &str) -> Vec<String> {
             {
                 let rel_type = get_attr(e, b"Type").unwrap_or_default();
                 let target = get_attr(e, b"Target").unwrap_or_default();
-                if rel_type.ends_with("/drawing") && !target.is_empty() {
+                if rel_type.ends_with(type_suffix) && !target.is_empty() {
                     targets.push(target);
                 }
             }
@@ -342,26 +1398,56 @@ fn parse_drawing_rels(xml: &str) -> Vec<String> {
     targets
 }
 
-/// Extract blip rIds from drawing XML (`<a:blip r:embed="rIdN"/>`).
-fn parse_drawing_blip_rids(xml: &str) -> Vec<String> {
-    let mut rids = Vec::new();
+/// Extract blip rIds from drawing XML (`<a:blip r:embed="rIdN"/>`), each
+/// with the 0-based row and column of the cell its picture is anchored at
+/// (`<xdr:from>`), if it has one.
+fn parse_drawing_blips(xml: &str) -> Vec<(Option<(usize, usize)>, String)> {
+    let mut blips = Vec::new();
     let mut reader = Reader::from_str(xml);
+    let mut anchor: Option<(usize, usize)> = None;
+    let mut in_from = false;
+    // The `<xdr:row>` or `<xdr:col>` of the anchor being read
+    let mut field: Option<bool> = None;
 
     loop {
         match reader.read_event() {
-            Ok(Event::Start(ref e) | Event::Empty(ref e)) => {
-                if e.local_name().as_ref() == b"blip" {
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"twoCellAnchor" | b"oneCellAnchor" | b"absoluteAnchor" => anchor = None,
+                b"from" => {
+                    in_from = true;
+                    anchor = Some((0, 0));
+                }
+                b"row" if in_from => field = Some(true),
+                b"col" if in_from => field = Some(false),
+                b"blip" => {
                     if let Some(rid) = get_attr(e, b"r:embed") {
-                        rids.push(rid);
+                        blips.push((anchor, rid));
                     }
                 }
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"blip" => {
+                if let Some(rid) = get_attr(e, b"r:embed") {
+                    blips.push((anchor, rid));
+                }
             }
+            Ok(Event::Text(ref t)) => {
+                if let (Some(is_row), Some((row, col))) = (field, anchor.as_mut()) {
+                    let value = t.unescape().ok().and_then(|v| v.trim().parse().ok());
+                    *(if is_row { row } else { col }) = value.unwrap_or(0);
+                }
+            }
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"from" => in_from = false,
+                b"row" | b"col" => field = None,
+                _ => {}
+            },
             Ok(Event::Eof) | Err(_) => break,
             _ => {}
         }
     }
 
-    rids
+    blips
 }
 
 /// Normalize a path by resolving `..` segments.
@@ -389,8 +1475,9 @@ fn cell_ref_to_row(cell_ref: &str) -> usize {
 /// Parse `xl/sharedStrings.xml` into a lookup table.
 ///
 /// Each `<si>` element contributes one string at its positional index.
-/// Strings may be plain `<t>` text or rich text with multiple `<r><t>` runs.
-fn parse_shared_strings(archive: &mut ZipArchive<Cursor<&[u8]>>) -> Vec<String> {
+/// Strings may be plain `<t>` text or rich text with multiple `<r><t>` runs;
+/// with `markdown`, bold and italic runs are written as `**`/`*`.
+fn parse_shared_strings(archive: &mut ZipArchive<Cursor<&[u8]>>, markdown: bool) -> Vec<String> {
     let mut xml = String::new();
     match archive.by_name("xl/sharedStrings.xml") {
         Ok(mut entry) => {
@@ -401,41 +1488,63 @@ fn parse_shared_strings(archive: &mut ZipArchive<Cursor<&[u8]>>) -> Vec<String>
         Err(_) => return Vec::new(),
     }
 
-    parse_shared_strings_xml(&xml)
+    parse_shared_strings_xml(&xml, markdown)
 }
 
 /// Parse shared string table XML into a list of strings.
 ///
 /// Separated from `parse_shared_strings` for testability (avoids needing
 /// a ZIP archive in tests).
-fn parse_shared_strings_xml(xml: &str) -> Vec<String> {
+fn parse_shared_strings_xml(xml: &str, markdown: bool) -> Vec<String> {
     let mut reader = Reader::from_str(xml);
     let mut strings = Vec::new();
-    let mut in_si = false;
-    let mut current = String::new();
+    // The runs of the string being read; plain `<t>` text is one run
+    let mut runs: Vec<TextRun> = Vec::new();
+    let (mut in_run, mut in_text) = (false, false);
+    // Phonetic runs (`<rPh>`) repeat the text as it is read
+    let mut in_phonetic = false;
 
     loop {
         match reader.read_event() {
-            Ok(Event::Start(ref e)) => {
-                let name = e.local_name();
-                if name.as_ref() == b"si" {
-                    in_si = true;
-                    current.clear();
+            Ok(Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"si" => runs.clear(),
+                b"r" => {
+                    in_run = true;
+                    runs.push(TextRun::default());
                 }
-            }
-            Ok(Event::End(ref e)) => {
-                if e.local_name().as_ref() == b"si" {
-                    strings.push(std::mem::take(&mut current));
-                    in_si = false;
+                b"t" if !in_phonetic => {
+                    in_text = true;
+                    if !in_run {
+                        runs.push(TextRun::default());
+                    }
+                }
+                b"rPh" => in_phonetic = true,
+                _ => {}
+            },
+            Ok(Event::Empty(ref e)) if in_run => {
+                let on = !matches!(get_attr(e, b"val").as_deref(), Some("0" | "false"));
+                match (e.local_name().as_ref(), runs.last_mut()) {
+                    (b"b", Some(run)) => run.bold = on,
+                    (b"i", Some(run)) => run.italic = on,
+                    _ => {}
                 }
             }
-            Ok(Event::Text(ref t)) => {
-                if in_si {
-                    if let Ok(s) = t.unescape() {
-                        current.push_str(&s);
-                    }
+            Ok(Event::Text(ref t)) if in_text => {
+                if let (Ok(text), Some(run)) = (t.unescape(), runs.last_mut()) {
+                    run.text.push_str(&text);
                 }
             }
+            Ok(Event::End(ref e)) => match e.local_name().as_ref() {
+                b"si" => strings.push(if markdown {
+                    markup::render_runs_markdown(&runs)
+                } else {
+                    runs.iter().map(|run| run.text.as_str()).collect()
+                }),
+                b"r" => in_run = false,
+                b"t" => in_text = false,
+                b"rPh" => in_phonetic = false,
+                _ => {}
+            },
             Ok(Event::Eof) | Err(_) => break,
             _ => {}
         }
@@ -444,11 +1553,68 @@ fn parse_shared_strings_xml(xml: &str) -> Vec<String> {
     strings
 }
 
+/// A run of a shared string, with the emphasis markdown can show.
+#[derive(Debug, Default)]
+struct TextRun {
+    text: String,
+    bold: bool,
+    italic: bool,
+}
+
+impl markup::InlineRun for TextRun {
+    fn text(&self) -> &str {
+        &self.text
+    }
+    fn bold(&self) -> bool {
+        self.bold
+    }
+    fn italic(&self) -> bool {
+        self.italic
+    }
+    fn link_url(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Whether the sheet part at `path` is a chart sheet: a sheet holding
+/// one chart and no cells, which Excel keeps under `xl/chartsheets/`.
+fn is_chart_sheet(path: &str) -> bool {
+    path.contains("chartsheets/")
+}
+
+/// The names of the chart sheets of a .xlsx, which `--list` marks as such
+/// rather than give them a size.
+pub(crate) fn chart_sheets(data: &[u8], opts: &Options) -> Vec<String> {
+    let Ok(mut archive) = ZipArchive::new(Cursor::new(data)) else {
+        return Vec::new();
+    };
+    discover_sheets(&mut archive, opts.hidden, &Messages::default())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, path)| is_chart_sheet(path))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Note that the chart sheet `name` has no cells, and, in text output
+/// (`display`), is left out for want of a chart that could be read.
+fn note_chart_sheet(notes: &Messages, name: &str, display: bool) {
+    notes.push(if display {
+        format!("skipped chart sheet '{name}': its chart has no data to show")
+    } else {
+        format!("chart sheet '{name}' has no cells to extract")
+    });
+}
+
 /// Discover sheet names and their file paths from workbook.xml and relationships.
 ///
-/// Returns `(sheet_name, zip_path)` pairs in workbook order.
+/// Returns `(sheet_name, zip_path)` pairs in workbook order, chart sheets
+/// among them. Hidden sheets are left out, with a note in `notes`, unless
+/// `include_hidden`, and so are dialog sheets.
 fn discover_sheets(
     archive: &mut ZipArchive<Cursor<&[u8]>>,
+    include_hidden: bool,
+    notes: &Messages,
 ) -> crate::error::Result<Vec<(String, String)>> {
     // Parse workbook.xml for sheet name → rId mapping
     let mut workbook_xml = String::new();
@@ -464,11 +1630,13 @@ fn discover_sheets(
             Ok(Event::Empty(ref e) | Event::Start(ref e))
                 if e.local_name().as_ref() == b"sheet" =>
             {
-                let name = get_attr(e, b"name").unwrap_or_default();
+                let name = unescaped_attr(e, b"name").unwrap_or_default();
                 let rid = get_attr(e, b"r:id").unwrap_or_default();
                 let state = get_attr(e, b"state").unwrap_or_default();
                 // Skip hidden sheets
-                if state != "hidden" && !name.is_empty() && !rid.is_empty() {
+                if !include_hidden && matches!(state.as_str(), "hidden" | "veryHidden") {
+                    notes.push(format!("skipped hidden sheet '{name}'"));
+                } else if !name.is_empty() && !rid.is_empty() {
                     sheet_entries.push((name, rid));
                 }
             }
@@ -512,7 +1680,12 @@ fn discover_sheets(
             } else {
                 format!("xl/{target}")
             };
-            result.push((name.clone(), path));
+            // Dialog sheets hold Excel 5 forms, with nothing to extract
+            if path.contains("dialogsheets/") {
+                notes.push(format!("skipped dialog sheet '{name}'"));
+            } else {
+                result.push((name.clone(), path));
+            }
         }
     }
 
@@ -526,16 +1699,26 @@ fn discover_sheets(
 /// - `t="inlineStr"`: inline string with `<is><t>` content
 /// - Otherwise: raw value from `<v>` (numbers, dates, formulas with cached values)
 ///
-/// Numeric cells whose style maps to a date format are converted to ISO dates.
-fn parse_sheet_xml(xml: &str, shared_strings: &[String], styles: &Styles) -> Vec<Vec<String>> {
+/// Numeric cells are shown in their style's number format, dates as ISO dates.
+/// Stops early if `cancel` is triggered; the caller reports the error.
+/// Also stops, without error, once `budget` is spent.
+fn parse_sheet_xml(
+    xml: &str,
+    shared_strings: &[String],
+    styles: &Styles,
+    cancel: &CancelToken,
+    budget: &mut Budget,
+) -> Vec<Vec<String>> {
     let mut reader = Reader::from_str(xml);
     let mut sparse_rows: Vec<Vec<(usize, String)>> = Vec::new();
     let mut max_col = 0usize;
 
-    loop {
+    while !cancel.is_cancelled() && !budget.spent() {
         match reader.read_event() {
             Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"row" => {
                 let row = parse_row(&mut reader, shared_strings, styles);
+                let bytes = row.iter().map(|(_, value)| value.len() + 1).sum();
+                budget.spend(bytes, usize::from(row.iter().any(|(_, v)| !v.is_empty())));
                 for &(col, _) in &row {
                     if col + 1 > max_col {
                         max_col = col + 1;
@@ -564,15 +1747,17 @@ fn parse_sheet_xml(xml: &str, shared_strings: &[String], styles: &Styles) -> Vec
 }
 
 /// Parse a `<row>` element, returning `(column_index, value)` pairs.
-fn parse_row(
-    reader: &mut Reader<&[u8]>,
+fn parse_row<R: BufRead>(
+    reader: &mut Reader<R>,
     shared_strings: &[String],
     styles: &Styles,
 ) -> Vec<(usize, String)> {
     let mut cells: Vec<(usize, String)> = Vec::new();
+    let mut buf = Vec::new();
 
     loop {
-        match reader.read_event() {
+        buf.clear();
+        match reader.read_event_into(&mut buf) {
             Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"c" => {
                 let col_idx = get_attr(e, b"r")
                     .as_deref()
@@ -600,11 +1785,11 @@ fn parse_row(
 
 /// Parse a single `<c>` cell element and return its text value.
 ///
-/// For numeric cells (no `t` attribute or `t="n"`), checks the style
-/// to see if the number format is a date — if so, converts the serial
-/// number to an ISO date string.
-fn parse_cell(
-    reader: &mut Reader<&[u8]>,
+/// Numeric cells (no `t` attribute or `t="n"`) are shown in their
+/// style's number format: dates as ISO dates, currency, percentages,
+/// and fixed decimals as Excel shows them.
+fn parse_cell<R: BufRead>(
+    reader: &mut Reader<R>,
     cell_type: &str,
     shared_strings: &[String],
     style_idx: usize,
@@ -612,15 +1797,17 @@ fn parse_cell(
 ) -> String {
     let mut value = String::new();
     let mut inline_text = String::new();
+    let (mut buf, mut text_buf) = (Vec::new(), Vec::new());
 
     loop {
-        match reader.read_event() {
+        buf.clear();
+        match reader.read_event_into(&mut buf) {
             Ok(Event::Start(ref e)) => {
                 let name = e.local_name();
                 match name.as_ref() {
                     b"v" => {
                         // Read the <v> text content
-                        if let Ok(Event::Text(t)) = reader.read_event() {
+                        if let Ok(Event::Text(t)) = reader.read_event_into(&mut text_buf) {
                             if let Ok(s) = t.unescape() {
                                 value = s.into_owned();
                             }
@@ -649,29 +1836,51 @@ fn parse_cell(
                 .unwrap_or_default()
         }
         "inlineStr" => inline_text,
-        // Numeric or untyped cells: check for date format
-        "" | "n" => maybe_convert_date(&value, style_idx, styles),
-        _ => value, // booleans ("b"), errors ("e"), formula strings ("str")
+        // Numeric or untyped cells: apply the number format
+        "" | "n" => format_number(&value, style_idx, styles),
+        "b" => match value.trim() {
+            "1" | "true" => sheet::bool_text(true).to_string(),
+            "0" | "false" => sheet::bool_text(false).to_string(),
+            _ => value,
+        },
+        // Errors are stored as the literal Excel shows, such as #DIV/0!
+        "e" => value.trim().to_string(),
+        _ => value, // formula strings ("str")
     }
 }
 
-/// If the cell's style is a date format and the value parses as a number,
-/// convert it to an ISO date string. Otherwise return the raw value.
-fn maybe_convert_date(value: &str, style_idx: usize, styles: &Styles) -> String {
+/// Show a number as the cell's style says: an ISO date for a date
+/// format, `$1,234.50` for `$#,##0.00`. Values that are not numbers give
+/// the raw value, and so do formats that [`numfmt`] leaves alone (General
+/// among them) unless the value carries binary noise past Excel's 15
+/// digits.
+fn format_number(value: &str, style_idx: usize, styles: &Styles) -> String {
+    let Ok(number) = value.parse::<f64>() else {
+        return value.to_string();
+    };
     if styles.is_date_style(style_idx) {
-        if let Ok(serial) = value.parse::<f64>() {
-            return dateconv::serial_to_iso(serial);
-        }
+        return dateconv::serial_to_iso(number, styles.epoch);
     }
-    value.to_string()
+    styles
+        .code(style_idx)
+        .and_then(|code| numfmt::format(number, code))
+        .unwrap_or_else(|| {
+            if value.len() > 15 {
+                numfmt::general(number)
+            } else {
+                value.to_string()
+            }
+        })
 }
 
 /// Parse an `<is>` inline string element, collecting all `<t>` text.
-fn parse_inline_string(reader: &mut Reader<&[u8]>) -> String {
+fn parse_inline_string<R: BufRead>(reader: &mut Reader<R>) -> String {
     let mut text = String::new();
+    let mut buf = Vec::new();
 
     loop {
-        match reader.read_event() {
+        buf.clear();
+        match reader.read_event_into(&mut buf) {
             Ok(Event::Text(ref t)) => {
                 if let Ok(s) = t.unescape() {
                     text.push_str(&s);
@@ -750,7 +1959,7 @@ mod tests {
             <si><t>Hello</t></si>
             <si><t>World</t></si>
         </sst>"#;
-        assert_eq!(parse_shared_strings_xml(xml), vec!["Hello", "World"]);
+        assert_eq!(parse_shared_strings_xml(xml, false), vec!["Hello", "World"]);
     }
 
     #[test]
@@ -759,7 +1968,17 @@ mod tests {
         let xml = r#"<sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
             <si><r><rPr><b/></rPr><t>Bold</t></r><r><t> Normal</t></r></si>
         </sst>"#;
-        assert_eq!(parse_shared_strings_xml(xml), vec!["Bold Normal"]);
+        assert_eq!(parse_shared_strings_xml(xml, false), vec!["Bold Normal"]);
+        assert_eq!(parse_shared_strings_xml(xml, true), vec!["**Bold** Normal"]);
+    }
+
+    #[test]
+    fn shared_strings_emphasis_and_phonetics() {
+        let xml = r#"<sst>
+            <si><r><rPr><b val="0"/><i/></rPr><t>Note</t></r></si>
+            <si><t>東京</t><rPh sb="0" eb="2"><t>トウキョウ</t></rPh></si>
+        </sst>"#;
+        assert_eq!(parse_shared_strings_xml(xml, true), vec!["*Note*", "東京"]);
     }
 
     // ── parse_sheet_xml ──────────────────────────────────────────
@@ -781,7 +2000,13 @@ mod tests {
         </worksheet>"#;
 
         let no_styles = Styles::default();
-        let rows = parse_sheet_xml(xml, &shared, &no_styles);
+        let rows = parse_sheet_xml(
+            xml,
+            &shared,
+            &no_styles,
+            &CancelToken::default(),
+            &mut Budget::default(),
+        );
         assert_eq!(rows.len(), 2);
         assert_eq!(rows[0], vec!["Name", "Age"]);
         assert_eq!(rows[1], vec!["Alice", "30"]);
@@ -799,11 +2024,30 @@ mod tests {
         </worksheet>"#;
 
         let no_styles = Styles::default();
-        let rows = parse_sheet_xml(xml, &[], &no_styles);
+        let rows = parse_sheet_xml(
+            xml,
+            &[],
+            &no_styles,
+            &CancelToken::default(),
+            &mut Budget::default(),
+        );
         assert_eq!(rows.len(), 1);
         assert_eq!(rows[0], vec!["Status", "Task"]);
     }
 
+    #[test]
+    fn parse_sheet_boolean_and_error_cells() {
+        let xml = r#"<worksheet><sheetData><row r="1"><c r="A1" t="b"><v>1</v></c><c r="B1" t="b"><v>0</v></c><c r="C1" t="e"><f>1/0</f><v>#DIV/0!</v></c><c r="D1" t="e"><v>#N/A</v></c></row></sheetData></worksheet>"#;
+        let rows = parse_sheet_xml(
+            xml,
+            &[],
+            &Styles::default(),
+            &CancelToken::default(),
+            &mut Budget::default(),
+        );
+        assert_eq!(rows[0], vec!["TRUE", "FALSE", "#DIV/0!", "#N/A"]);
+    }
+
     #[test]
     fn parse_sheet_sparse_columns() {
         // Row has A1 and C1 but no B1 — should produce 3 columns with gap
@@ -818,7 +2062,13 @@ mod tests {
         </worksheet>"#;
 
         let no_styles = Styles::default();
-        let rows = parse_sheet_xml(xml, &shared, &no_styles);
+        let rows = parse_sheet_xml(
+            xml,
+            &shared,
+            &no_styles,
+            &CancelToken::default(),
+            &mut Budget::default(),
+        );
         assert_eq!(rows.len(), 1);
         assert_eq!(rows[0].len(), 3);
         assert_eq!(rows[0][0], "First");
@@ -833,7 +2083,13 @@ mod tests {
         </worksheet>"#;
 
         let no_styles = Styles::default();
-        let rows = parse_sheet_xml(xml, &[], &no_styles);
+        let rows = parse_sheet_xml(
+            xml,
+            &[],
+            &no_styles,
+            &CancelToken::default(),
+            &mut Budget::default(),
+        );
         assert!(rows.is_empty());
     }
 
@@ -880,6 +2136,44 @@ mod tests {
         </styleSheet>"##;
         let styles = parse_styles_xml(xml);
         assert!(!styles.is_date_style(0));
+        assert_eq!(styles.code(0), Some("#,##0.00"));
+    }
+
+    #[test]
+    fn parse_sheet_number_formats_applied() {
+        let xml = r#"<styleSheet>
+            <numFmts count="1"><numFmt numFmtId="164" formatCode="&quot;$&quot;#,##0.00"/></numFmts>
+            <cellXfs count="4">
+                <xf numFmtId="0"/><xf numFmtId="164"/><xf numFmtId="10"/><xf numFmtId="14"/>
+            </cellXfs>
+        </styleSheet>"#;
+        let styles = parse_styles_xml(xml);
+        let xml = r#"<worksheet><sheetData><row r="1">
+            <c r="A1"><v>0.12345678901</v></c>
+            <c r="B1" s="1"><v>1234.5</v></c>
+            <c r="C1" s="2"><v>0.12345678901</v></c>
+            <c r="D1" s="3"><v>45292</v></c>
+            <c r="E1" s="1" t="str"><v>n/a</v></c>
+            <c r="F1"><v>0.30000000000000004</v></c>
+        </row></sheetData></worksheet>"#;
+        let rows = parse_sheet_xml(
+            xml,
+            &[],
+            &styles,
+            &CancelToken::default(),
+            &mut Budget::default(),
+        );
+        assert_eq!(
+            rows[0],
+            [
+                "0.12345678901",
+                "$1,234.50",
+                "12.35%",
+                "2024-01-01",
+                "n/a",
+                "0.3"
+            ]
+        );
     }
 
     #[test]
@@ -887,6 +2181,8 @@ mod tests {
         // Style index 1 maps to numFmtId 14 (builtin date)
         let styles = Styles {
             is_date: vec![false, true],
+            codes: Vec::new(),
+            epoch: Epoch::Y1900,
         };
         let xml = r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
             <sheetData>
@@ -897,11 +2193,27 @@ mod tests {
             </sheetData>
         </worksheet>"#;
 
-        let rows = parse_sheet_xml(xml, &[], &styles);
+        let rows = parse_sheet_xml(
+            xml,
+            &[],
+            &styles,
+            &CancelToken::default(),
+            &mut Budget::default(),
+        );
         assert_eq!(rows[0][0], "42");
         assert_eq!(rows[0][1], "2024-01-01");
     }
 
+    #[test]
+    fn date1904_flag_read() {
+        let xml = r#"<workbook><workbookPr date1904="1" defaultThemeVersion="124226"/><sheets/></workbook>"#;
+        assert_eq!(parse_date_system(xml), Epoch::Y1904);
+        assert_eq!(
+            parse_date_system("<workbook><workbookPr/></workbook>"),
+            Epoch::Y1900
+        );
+    }
+
     // ── hyperlink resolution ───────────────────────────────────────
 
     #[test]
@@ -923,7 +2235,7 @@ mod tests {
             </hyperlinks>
         </worksheet>"#;
         let mut rows = vec![vec!["Click here".to_string()]];
-        apply_hyperlinks(sheet_xml, &rels, &mut rows);
+        apply_hyperlinks(sheet_xml, "Sheet1", &rels, &mut rows);
         assert_eq!(rows[0][0], "[Click here](https://example.com)");
     }
 
@@ -931,7 +2243,387 @@ mod tests {
     fn apply_hyperlinks_empty_rels_noop() {
         let rels = Rels::new();
         let mut rows = vec![vec!["Hello".to_string()]];
-        apply_hyperlinks("<worksheet><hyperlinks/></worksheet>", &rels, &mut rows);
+        apply_hyperlinks(
+            "<worksheet><hyperlinks/></worksheet>",
+            "Sheet1",
+            &rels,
+            &mut rows,
+        );
         assert_eq!(rows[0][0], "Hello");
     }
+
+    #[test]
+    fn location_links_point_at_sheet_headings() {
+        let sheet_xml = r#"<worksheet>
+            <hyperlinks>
+                <hyperlink ref="A1" location="'Q1 &amp; Q2'!B3"/>
+                <hyperlink ref="A2" location="Totals!A1:C4"/>
+                <hyperlink ref="A3" location="$D$9"/>
+                <hyperlink ref="A4" location="TaxRate"/>
+            </hyperlinks>
+        </worksheet>"#;
+        let mut rows: Vec<Vec<String>> = ["Plan", "Sum", "Below", "Rate"]
+            .iter()
+            .map(|text| vec![text.to_string()])
+            .collect();
+        apply_hyperlinks(sheet_xml, "Main Sheet", &Rels::new(), &mut rows);
+        assert_eq!(rows[0][0], "[Plan](#q1--q2)");
+        assert_eq!(rows[1][0], "[Sum](#totals)");
+        assert_eq!(rows[2][0], "[Below](#main-sheet)");
+        assert_eq!(rows[3][0], "Rate");
+    }
+
+    /// A workbook with a `Main` sheet linking to a sheet named `name`.
+    fn linked_workbook(name: &str) -> Vec<u8> {
+        use std::io::Write;
+        let ns = r#"xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships""#;
+        let parts = [
+            (
+                "xl/workbook.xml",
+                format!(
+                    r#"<workbook {ns}><sheets><sheet name="Main" sheetId="1" r:id="rId1"/><sheet name="{name}" sheetId="2" r:id="rId2"/></sheets></workbook>"#
+                ),
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Target="worksheets/sheet2.xml"/></Relationships>"#.to_string(),
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                format!(
+                    r#"<worksheet {ns}><sheetData><row r="1"><c r="A1" t="inlineStr"><is><t>Go</t></is></c></row></sheetData><hyperlinks><hyperlink ref="A1" location="'{name}'!A1"/></hyperlinks></worksheet>"#
+                ),
+            ),
+            (
+                "xl/worksheets/sheet2.xml",
+                format!(
+                    r#"<worksheet {ns}><sheetData><row r="1"><c r="A1" t="inlineStr"><is><t>There</t></is></c></row></sheetData></worksheet>"#
+                ),
+            ),
+        ];
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, xml) in parts {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(xml.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn location_link_anchor_matches_escaped_sheet_heading() {
+        let data = linked_workbook("My Sheet &amp; Co &lt;1&gt;");
+        let md = extract_markdown(&data, &Options::default()).unwrap();
+        assert!(md.contains("## My Sheet & Co <1>\n"), "{md}");
+        let link = format!("[Go](#{})", sheet::anchor("My Sheet & Co <1>"));
+        assert!(md.contains(&link), "{md}");
+    }
+
+    #[test]
+    fn sheet_option_matches_decoded_name() {
+        let data = linked_workbook("My Sheet &amp; Co &lt;1&gt;");
+        let pick = |name: &str| Options {
+            sheet: Some(name.to_string()),
+            ..Options::default()
+        };
+        let plain = extract_plain(&data, &pick("My Sheet & Co <1>")).unwrap();
+        assert_eq!(plain, "There\n");
+        assert!(extract_plain(&data, &pick("My Sheet &amp; Co &lt;1&gt;")).is_err());
+    }
+
+    // ── hidden rows and columns ──────────────────────────────────
+
+    #[test]
+    fn hidden_rows_and_columns_dropped() {
+        let xml = r#"<worksheet><cols><col min="2" max="2" hidden="1"/></cols><sheetData>
+<row r="1"><c r="A1"><v>1</v></c><c r="B1"><v>2</v></c><c r="C1"><v>3</v></c></row>
+<row r="2" hidden="1"><c r="A2"><v>4</v></c></row>
+<row r="3"><c r="A3"><v>5</v></c><c r="C3"><v>6</v></c></row>
+</sheetData></worksheet>"#;
+        let mut rows = parse_sheet_xml(
+            xml,
+            &[],
+            &Styles::default(),
+            &CancelToken::default(),
+            &mut Budget::default(),
+        );
+        let mut tables = vec![NamedTable {
+            name: "T".into(),
+            rows: 0..=2,
+            cols: 0..=2,
+            columns: vec!["a".into(), "b".into(), "c".into()],
+            header_row: true,
+        }];
+        let mut comments = vec![
+            comment_at("C3", "Ann".into(), "kept"),
+            comment_at("A2", "Ann".into(), "hidden row"),
+            comment_at("B1", "Ann".into(), "hidden column"),
+        ];
+        drop_hidden(xml, &mut rows, &mut comments, &mut tables);
+        assert_eq!(rows, [["1", "3"], ["5", "6"]]);
+        assert_eq!(
+            (tables[0].rows.clone(), tables[0].cols.clone()),
+            (0..=1, 0..=1)
+        );
+        assert_eq!(tables[0].columns, ["a", "c"]);
+        assert_eq!(comments.len(), 1);
+        assert_eq!((comments[0].row, comments[0].col), (1, 1));
+        assert_eq!(comments[0].cell, "C3");
+    }
+
+    // ── tables ───────────────────────────────────────────────────
+
+    #[test]
+    fn table_part_parsed() {
+        let xml = r#"<table id="1" name="Table1" displayName="Sales" ref="B2:C5" totalsRowCount="1">
+<autoFilter ref="B2:C4"/><tableColumns count="2">
+<tableColumn id="1" name="Region"/><tableColumn id="2" name="Q1 &amp; Q2"/>
+</tableColumns></table>"#;
+        let table = parse_table_xml(xml).unwrap();
+        assert_eq!(table.name, "Sales");
+        assert_eq!((table.rows, table.cols), (1..=4, 1..=2));
+        assert_eq!(table.columns, ["Region", "Q1 & Q2"]);
+        assert!(table.header_row);
+        assert_eq!(
+            table_part_rids(
+                r#"<worksheet><tableParts count="1"><tablePart r:id="rId3"/></tableParts></worksheet>"#
+            ),
+            ["rId3"]
+        );
+    }
+
+    // ── comments ─────────────────────────────────────────────────
+
+    #[test]
+    fn notes_without_author_prefix() {
+        let xml = r#"<comments><authors><author>Ann Lee</author></authors><commentList><comment ref="C4" authorId="0"><text><r><rPr><b/></rPr><t>Ann Lee:</t></r><r><t xml:space="preserve">
+Check the total</t></r><rPh><t>x</t></rPh></text></comment><comment ref="A1" authorId="3"><text><t>Plain</t></text></comment></commentList></comments>"#;
+        let notes = parse_notes(xml);
+        assert_eq!(
+            notes
+                .iter()
+                .map(|n| (n.row, n.col, n.author.as_str(), n.text.as_str()))
+                .collect::<Vec<_>>(),
+            [(3, 2, "Ann Lee", "Check the total"), (0, 0, "", "Plain")]
+        );
+    }
+
+    #[test]
+    fn threaded_comments_with_replies() {
+        let persons = parse_persons(
+            r#"<personList><person displayName="Ann &amp; Co" id="{P1}"/><person displayName="Bob" id="{P2}"/></personList>"#,
+        );
+        let xml = r#"<ThreadedComments><threadedComment ref="B2" personId="{P1}" id="{T1}"><text>Is this right?</text></threadedComment><threadedComment ref="B2" personId="{P2}" id="{T2}" parentId="{T1}"><text>Yes</text></threadedComment></ThreadedComments>"#;
+        let comments = parse_threaded_comments(xml, &persons);
+        assert_eq!(comments.len(), 1);
+        assert_eq!((comments[0].row, comments[0].col), (1, 1));
+        assert_eq!(comments[0].author, "Ann & Co");
+        assert_eq!(comments[0].text, "Is this right? — Bob: Yes");
+    }
+
+    // ── defined names ────────────────────────────────────────────
+
+    #[test]
+    fn defined_names_with_scope() {
+        let xml = r#"<workbook><sheets>
+<sheet name="Data" sheetId="1" r:id="rId1"/><sheet name="Q&amp;A" sheetId="2" r:id="rId2"/>
+</sheets><definedNames>
+<definedName name="Sales">Data!$A$1:$B$9</definedName>
+<definedName name="_xlnm.Print_Area" localSheetId="1">'Q&amp;A'!$A$1:$C$4</definedName>
+<definedName name="_xlnm._FilterDatabase" localSheetId="0" hidden="1">Data!$A$1:$B$9</definedName>
+</definedNames></workbook>"#;
+        assert_eq!(
+            parse_defined_names(xml),
+            [
+                ("Sales".to_string(), "Data!$A$1:$B$9".to_string()),
+                (
+                    "Print_Area (Q&A)".to_string(),
+                    "'Q&A'!$A$1:$C$4".to_string()
+                ),
+            ]
+        );
+    }
+
+    // ── data validation ──────────────────────────────────────────
+
+    #[test]
+    fn validation_lists_both_forms() {
+        let xml = r#"<worksheet><sheetData/>
+<dataValidations count="3">
+<dataValidation type="list" allowBlank="1" sqref="C2:C100 E2"><formula1>"Open, Closed,Won't fix"</formula1></dataValidation>
+<dataValidation type="whole" sqref="F2"><formula1>1</formula1><formula2>9</formula2></dataValidation>
+<dataValidation type="list" sqref="G2"><formula1>Codes</formula1></dataValidation>
+</dataValidations>
+<extLst><ext><x14:dataValidations count="1"><x14:dataValidation type="list">
+<x14:formula1><xm:f>Lists!$A$1:$A$5</xm:f></x14:formula1><xm:sqref>D2:D50</xm:sqref>
+</x14:dataValidation></x14:dataValidations></ext></extLst></worksheet>"#;
+        let lists: Vec<(String, String)> = parse_validation_lists(xml.as_bytes())
+            .into_iter()
+            .map(|(sqref, formula)| (sqref, list_values(&formula)))
+            .collect();
+        assert_eq!(
+            lists,
+            [
+                (
+                    "C2:C100 E2".to_string(),
+                    "Open, Closed, Won't fix".to_string()
+                ),
+                ("G2".to_string(), "Codes".to_string()),
+                ("D2:D50".to_string(), "Lists!$A$1:$A$5".to_string()),
+            ]
+        );
+    }
+
+    // ── images ───────────────────────────────────────────────────
+
+    #[test]
+    fn drawing_blips_keep_their_anchor_cells() {
+        let xml = r#"<xdr:wsDr>
+            <xdr:twoCellAnchor>
+                <xdr:from><xdr:col>3</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>12</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:from>
+                <xdr:to><xdr:col>6</xdr:col><xdr:row>20</xdr:row></xdr:to>
+                <xdr:pic><xdr:blipFill><a:blip r:embed="rId1"/></xdr:blipFill></xdr:pic>
+            </xdr:twoCellAnchor>
+            <xdr:oneCellAnchor>
+                <xdr:from><xdr:col>0</xdr:col><xdr:row>2</xdr:row></xdr:from>
+                <xdr:pic><xdr:blipFill><a:blip r:embed="rId2"/></xdr:blipFill></xdr:pic>
+            </xdr:oneCellAnchor>
+            <xdr:absoluteAnchor>
+                <xdr:pos x="0" y="0"/>
+                <xdr:pic><xdr:blipFill><a:blip r:embed="rId3"/></xdr:blipFill></xdr:pic>
+            </xdr:absoluteAnchor>
+        </xdr:wsDr>"#;
+        assert_eq!(
+            parse_drawing_blips(xml),
+            [
+                (Some((12, 3)), "rId1".to_string()),
+                (Some((2, 0)), "rId2".to_string()),
+                (None, "rId3".to_string()),
+            ]
+        );
+    }
+
+    // ── streaming ────────────────────────────────────────────────
+
+    #[test]
+    fn streamed_rows_match_grid() {
+        use std::io::Write;
+        let ns = r#"xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships""#;
+        let data_sheet = format!(
+            r#"<worksheet {ns}><cols><col min="2" max="2" hidden="1"/></cols><sheetData><row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="inlineStr"><is><t>secret</t></is></c><c r="C1" t="s"><v>1</v></c></row><row r="2"><c r="A2" t="inlineStr"><is><t>Site</t></is></c><c r="C2" s="1"><v>0.25</v></c></row><row r="3" hidden="1"><c r="A3" t="inlineStr"><is><t>gone</t></is></c></row><row r="4"><c r="C4" t="inlineStr"><is><t>  </t></is></c></row><row r="5"><c r="A5" t="inlineStr"><is><t>two
+lines, "quoted"</t></is></c></row><row r="6"><c r="A6"/></row></sheetData><hyperlinks><hyperlink ref="A2" r:id="rId1"/></hyperlinks></worksheet>"#
+        );
+        let blank_sheet = format!(
+            r#"<worksheet {ns}><sheetData><row r="1"><c r="A1" t="inlineStr"><is><t> </t></is></c></row></sheetData></worksheet>"#
+        );
+        let parts = [
+            (
+                "xl/workbook.xml",
+                format!(
+                    r#"<workbook {ns}><sheets><sheet name="Data" sheetId="1" r:id="rId1"/><sheet name="Blank" sheetId="2" r:id="rId2"/></sheets></workbook>"#
+                ),
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Target="worksheets/sheet2.xml"/></Relationships>"#.to_string(),
+            ),
+            (
+                "xl/sharedStrings.xml",
+                format!(r"<sst {ns}><si><t>Name</t></si><si><t>Share</t></si></sst>"),
+            ),
+            (
+                "xl/styles.xml",
+                format!(r#"<styleSheet {ns}><cellXfs><xf numFmtId="0"/><xf numFmtId="9"/></cellXfs></styleSheet>"#),
+            ),
+            ("xl/worksheets/sheet1.xml", data_sheet),
+            (
+                "xl/worksheets/_rels/sheet1.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Target="https://example.com" TargetMode="External"/></Relationships>"#.to_string(),
+            ),
+            ("xl/worksheets/sheet2.xml", blank_sheet),
+        ];
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, xml) in parts {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(xml.as_bytes()).unwrap();
+        }
+        let data = zip.finish().unwrap().into_inner();
+        let shown = Options {
+            hidden: true,
+            ..Options::default()
+        };
+
+        for opts in [Options::default(), shown] {
+            let mut plain = Vec::new();
+            stream_plain(&data, &opts, &mut plain).unwrap();
+            let plain = String::from_utf8(plain).unwrap();
+            assert_eq!(plain, extract_plain(&data, &opts).unwrap());
+            assert!(plain.contains("[Site](https://example.com)\t"), "{plain}");
+            assert_eq!(plain.contains("gone"), opts.hidden, "{plain}");
+
+            let whole: Vec<Sheet> = extract_sheets(&data, &opts)
+                .unwrap()
+                .into_iter()
+                .filter(|s| !sheet::skip_empty_sheet(s))
+                .collect();
+            let mut csv = CsvSheets::open(&data, &opts).unwrap();
+            assert_eq!(csv.names(), ["Data"]);
+            let mut out = Vec::new();
+            csv.write(0, &mut out).unwrap();
+            assert_eq!(
+                String::from_utf8(out).unwrap(),
+                sheet::render_csv(&whole[0])
+            );
+        }
+        assert!(!is_large(&data));
+    }
+
+    #[test]
+    fn chart_and_dialog_sheets() {
+        use std::io::Write;
+        let ns = r#"xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships""#;
+        let parts = [
+            (
+                "xl/workbook.xml",
+                format!(
+                    r#"<workbook {ns}><sheets><sheet name="Data" sheetId="1" r:id="rId1"/><sheet name="Trend" sheetId="2" r:id="rId2"/><sheet name="Form" sheetId="3" r:id="rId3"/></sheets></workbook>"#
+                ),
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Target="chartsheets/sheet1.xml"/><Relationship Id="rId3" Target="dialogsheets/sheet1.xml"/></Relationships>"#.to_string(),
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                format!(r#"<worksheet {ns}><sheetData><row r="1"><c r="A1" t="inlineStr"><is><t>x</t></is></c></row></sheetData></worksheet>"#),
+            ),
+            ("xl/chartsheets/sheet1.xml", format!("<chartsheet {ns}/>")),
+            ("xl/dialogsheets/sheet1.xml", format!("<dialogsheet {ns}/>")),
+        ];
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, xml) in parts {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(xml.as_bytes()).unwrap();
+        }
+        let data = zip.finish().unwrap().into_inner();
+
+        let opts = Options::default();
+        assert_eq!(extract_plain(&data, &opts).unwrap(), "--- Data ---\nx\n");
+        assert_eq!(
+            opts.notes.take(),
+            [
+                "skipped dialog sheet 'Form'",
+                "skipped chart sheet 'Trend': its chart has no data to show"
+            ]
+        );
+        let names: Vec<String> = extract_sheets(&data, &opts)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["Data", "Trend"]);
+        assert_eq!(chart_sheets(&data, &opts), ["Trend"]);
+    }
 }
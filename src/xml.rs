@@ -0,0 +1,250 @@
+//! XML serialization of the document model (`--to xml`).
+//!
+//! Writes the format-independent [`Block`]s as a small, stable schema for
+//! archival workflows that need XML rather than markdown:
+//!
+//! ```xml
+//! <?xml version="1.0" encoding="UTF-8"?>
+//! <document format="pptx" source="deck.pptx">
+//!   <slide number="1">
+//!     <heading level="1">Title</heading>
+//!     <item level="0" ordered="false">Point</item>
+//!   </slide>
+//! </document>
+//! ```
+//!
+//! Other elements are `<paragraph>`, `<page number="N">` (PDF), and
+//! `<table name="...">` with `<row>` and `<cell>` children; `name` is only
+//! present for spreadsheet sheets. Slides and pages contain the blocks that
+//! follow them. Styled text in a paragraph or item is wrapped in
+//! `<span color="FF0000" highlight="yellow">`, with only the attributes
+//! that are set. Characters that XML 1.0 cannot represent are dropped.
+
+use std::fmt::Write as _;
+
+use crate::document::{Block, Span};
+use crate::format::Format;
+
+/// Serialize `blocks` of a document read from `source`.
+pub(crate) fn render(blocks: &[Block], format: Format, source: &str) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = write!(out, "<document format=\"{}\" source=\"", format.name());
+    push_escaped(&mut out, source, true);
+    out.push_str("\">\n");
+
+    // Closing tag of the open <slide> or <page>, if any
+    let mut container: Option<&str> = None;
+    for block in blocks {
+        let indent = if container.is_some() { "    " } else { "  " };
+        match block {
+            Block::Slide { number } | Block::Page { number } => {
+                if let Some(close) = container.take() {
+                    out.push_str(close);
+                }
+                let (open, close) = if matches!(block, Block::Slide { .. }) {
+                    ("slide", "  </slide>\n")
+                } else {
+                    ("page", "  </page>\n")
+                };
+                let _ = writeln!(out, "  <{open} number=\"{number}\">");
+                container = Some(close);
+            }
+            Block::Heading { level, text } => {
+                let _ = write!(out, "{indent}<heading level=\"{level}\">");
+                push_text_element_end(&mut out, text, "heading");
+            }
+            Block::Paragraph { text, spans } => {
+                let _ = write!(out, "{indent}<paragraph>");
+                push_spanned(&mut out, text, spans);
+                out.push_str("</paragraph>\n");
+            }
+            Block::ListItem {
+                level,
+                ordered,
+                text,
+                spans,
+            } => {
+                let _ = write!(
+                    out,
+                    "{indent}<item level=\"{level}\" ordered=\"{ordered}\">"
+                );
+                push_spanned(&mut out, text, spans);
+                out.push_str("</item>\n");
+            }
+            Block::Table { name, rows } => {
+                out.push_str(indent);
+                out.push_str("<table");
+                if let Some(name) = name {
+                    out.push_str(" name=\"");
+                    push_escaped(&mut out, name, true);
+                    out.push('"');
+                }
+                out.push_str(">\n");
+                for row in rows {
+                    let _ = writeln!(out, "{indent}  <row>");
+                    for cell in row {
+                        let _ = write!(out, "{indent}    <cell>");
+                        push_text_element_end(&mut out, cell, "cell");
+                    }
+                    let _ = writeln!(out, "{indent}  </row>");
+                }
+                let _ = writeln!(out, "{indent}</table>");
+            }
+        }
+    }
+    if let Some(close) = container {
+        out.push_str(close);
+    }
+
+    out.push_str("</document>\n");
+    out
+}
+
+/// Push escaped `text` followed by `</tag>` and a newline.
+fn push_text_element_end(out: &mut String, text: &str, tag: &str) {
+    push_escaped(out, text, false);
+    let _ = writeln!(out, "</{tag}>");
+}
+
+/// Push escaped `text`, wrapping each styled span in a `<span>`. Spans
+/// that overlap an earlier one or fall outside the text are ignored.
+fn push_spanned(out: &mut String, text: &str, spans: &[Span]) {
+    let mut pos = 0;
+    for span in spans {
+        let (Some(before), Some(inner)) = (
+            text.get(pos..span.range.start),
+            text.get(span.range.clone()),
+        ) else {
+            continue;
+        };
+        push_escaped(out, before, false);
+        out.push_str("<span");
+        for (name, value) in [("color", &span.color), ("highlight", &span.highlight)] {
+            if let Some(value) = value {
+                let _ = write!(out, " {name}=\"");
+                push_escaped(out, value, true);
+                out.push('"');
+            }
+        }
+        out.push('>');
+        push_escaped(out, inner, false);
+        out.push_str("</span>");
+        pos = span.range.end;
+    }
+    push_escaped(out, &text[pos..], false);
+}
+
+/// Push `text` with markup characters escaped (and quotes, inside
+/// attributes), dropping characters not allowed in XML 1.0.
+fn push_escaped(out: &mut String, text: &str, attr: bool) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attr => out.push_str("&quot;"),
+            '\n' if attr => out.push_str("&#10;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c < ' ' || c == '\u{FFFE}' || c == '\u{FFFF}' => {}
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ── render ───────────────────────────────────────────────────
+
+    #[test]
+    fn paragraphs_and_headings() {
+        let blocks = vec![
+            Block::Heading {
+                level: 1,
+                text: "A & B".into(),
+            },
+            Block::Paragraph {
+                text: "x < y".into(),
+                spans: Vec::new(),
+            },
+        ];
+        assert_eq!(
+            render(&blocks, Format::Docx, "a.docx"),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <document format=\"docx\" source=\"a.docx\">\n\
+             \x20 <heading level=\"1\">A &amp; B</heading>\n\
+             \x20 <paragraph>x &lt; y</paragraph>\n\
+             </document>\n"
+        );
+    }
+
+    #[test]
+    fn slides_contain_following_blocks() {
+        let blocks = vec![
+            Block::Slide { number: 1 },
+            Block::ListItem {
+                level: 0,
+                ordered: true,
+                text: "one".into(),
+                spans: Vec::new(),
+            },
+            Block::Slide { number: 2 },
+        ];
+        let xml = render(&blocks, Format::Pptx, "d.pptx");
+        assert!(xml.contains(
+            "  <slide number=\"1\">\n    <item level=\"0\" ordered=\"true\">one</item>\n  </slide>\n  <slide number=\"2\">\n  </slide>\n</document>"
+        ));
+    }
+
+    #[test]
+    fn styled_spans() {
+        let blocks = vec![Block::Paragraph {
+            text: "Do not ship <yet>".into(),
+            spans: vec![
+                Span {
+                    range: 3..6,
+                    color: Some("FF0000".into()),
+                    highlight: None,
+                },
+                Span {
+                    range: 12..17,
+                    color: Some("FF0000".into()),
+                    highlight: Some("yellow".into()),
+                },
+                // Overlaps the previous span
+                Span {
+                    range: 14..15,
+                    color: None,
+                    highlight: Some("green".into()),
+                },
+            ],
+        }];
+        let xml = render(&blocks, Format::Docx, "a.docx");
+        assert!(xml.contains(
+            "  <paragraph>Do <span color=\"FF0000\">not</span> ship \
+             <span color=\"FF0000\" highlight=\"yellow\">&lt;yet&gt;</span></paragraph>\n"
+        ));
+    }
+
+    #[test]
+    fn sheet_table() {
+        let blocks = vec![Block::Table {
+            name: Some("Q\"1\"".into()),
+            rows: vec![vec!["a".into(), String::new()]],
+        }];
+        let xml = render(&blocks, Format::Xlsx, "b.xlsx");
+        assert!(xml.contains(
+            "  <table name=\"Q&quot;1&quot;\">\n    <row>\n      <cell>a</cell>\n      <cell></cell>\n    </row>\n  </table>\n"
+        ));
+    }
+
+    // ── push_escaped ─────────────────────────────────────────────
+
+    #[test]
+    fn drops_invalid_characters() {
+        let mut out = String::new();
+        push_escaped(&mut out, "a\u{1}b\tc\u{FFFF}", false);
+        assert_eq!(out, "ab\tc");
+    }
+}
@@ -23,6 +23,16 @@ pub(crate) fn get_attr(e: &quick_xml::events::BytesStart, attr_name: &[u8]) -> O
     None
 }
 
+/// Get an attribute value with its entities (`&amp;`, `&lt;`) decoded.
+///
+/// `get_attr` returns the raw text, which is right for ids and paths;
+/// anything shown to the reader, such as a sheet name or alt text, goes
+/// through this instead.
+pub(crate) fn unescaped_attr(e: &quick_xml::events::BytesStart, name: &[u8]) -> Option<String> {
+    let attr = e.try_get_attribute(name).ok()??;
+    Some(attr.unescape_value().ok()?.into_owned())
+}
+
 /// Parse an OOXML relationships XML string into an rId → URL map.
 ///
 /// Only includes relationships with `TargetMode="External"` (hyperlinks)
@@ -56,10 +66,11 @@ pub(crate) fn parse_rels_xml(xml: &str) -> Rels {
 }
 
 /// Parse an OOXML relationships XML string into an rId → target path map
-/// for image relationships.
+/// for one type of relationship.
 ///
-/// Only includes relationships whose Type ends with `/image`.
-pub(crate) fn parse_image_rels_xml(xml: &str) -> Rels {
+/// Only includes relationships whose Type ends with `type_suffix`
+/// (e.g. `"/image"` or `"/slide"`).
+pub(crate) fn parse_typed_rels_xml(xml: &str, type_suffix: &str) -> Rels {
     let mut rels = Rels::new();
     let mut reader = Reader::from_str(xml);
 
@@ -72,7 +83,7 @@ pub(crate) fn parse_image_rels_xml(xml: &str) -> Rels {
                 let target = get_attr(e, b"Target").unwrap_or_default();
                 let rel_type = get_attr(e, b"Type").unwrap_or_default();
 
-                if !id.is_empty() && !target.is_empty() && rel_type.ends_with("/image") {
+                if !id.is_empty() && !target.is_empty() && rel_type.ends_with(type_suffix) {
                     rels.insert(id, target);
                 }
             }
@@ -88,6 +99,18 @@ pub(crate) fn parse_image_rels_xml(xml: &str) -> Rels {
 ///
 /// Returns an empty map if the file doesn't exist or can't be read.
 pub(crate) fn load_image_rels(archive: &mut ZipArchive<Cursor<&[u8]>>, path: &str) -> Rels {
+    load_typed_rels(archive, path, "/image")
+}
+
+/// Load the relationships of one type (see [`parse_typed_rels_xml`]) from a
+/// `.rels` file in a ZIP archive.
+///
+/// Returns an empty map if the file doesn't exist or can't be read.
+pub(crate) fn load_typed_rels(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    path: &str,
+    type_suffix: &str,
+) -> Rels {
     let mut xml = String::new();
     match archive.by_name(path) {
         Ok(mut entry) => {
@@ -97,7 +120,7 @@ pub(crate) fn load_image_rels(archive: &mut ZipArchive<Cursor<&[u8]>>, path: &st
         }
         Err(_) => return Rels::new(),
     }
-    parse_image_rels_xml(&xml)
+    parse_typed_rels_xml(&xml, type_suffix)
 }
 
 /// Load a relationships file from a ZIP archive and parse it into a `Rels` map.
@@ -128,18 +151,7 @@ pub(crate) fn read_image_from_zip(
     target: &str,
     base_dir: &str,
 ) -> Option<Vec<u8>> {
-    // Resolve relative path: join base_dir + target, then normalize "../"
-    let full_path = if target.starts_with('/') {
-        target.trim_start_matches('/').to_string()
-    } else {
-        let raw = if base_dir.is_empty() {
-            target.to_string()
-        } else {
-            format!("{base_dir}/{target}")
-        };
-        normalize_zip_path(&raw)
-    };
-
+    let full_path = part_path(base_dir, target);
     let mut data = Vec::new();
     archive
         .by_name(&full_path)
@@ -149,6 +161,64 @@ pub(crate) fn read_image_from_zip(
     Some(data)
 }
 
+/// The ZIP path of a relationship target in a part in `base_dir`:
+/// `"media/image1.png"` from `"word"` is `"word/media/image1.png"`, and an
+/// absolute `"/word/media/image1.png"` is taken from the package root.
+pub(crate) fn part_path(base_dir: &str, target: &str) -> String {
+    // Join base_dir + target, then normalize "../"
+    if target.starts_with('/') {
+        target.trim_start_matches('/').to_string()
+    } else if base_dir.is_empty() {
+        normalize_zip_path(target)
+    } else {
+        normalize_zip_path(&format!("{base_dir}/{target}"))
+    }
+}
+
+/// The path of the package's main part (`word/document.xml` in a typical
+/// `.docx`), from the `officeDocument` relationship in `_rels/.rels`.
+pub(crate) fn main_part(archive: &mut ZipArchive<Cursor<&[u8]>>) -> Option<String> {
+    let rels = load_typed_rels(archive, "_rels/.rels", "/officeDocument");
+    let target = rels.into_values().next()?;
+    Some(part_path("", &target))
+}
+
+/// The content type of the part at `path`, from `[Content_Types].xml`: an
+/// `Override` for the part itself, or else the `Default` for its extension.
+pub(crate) fn content_type(archive: &mut ZipArchive<Cursor<&[u8]>>, path: &str) -> Option<String> {
+    let mut xml = String::new();
+    archive
+        .by_name("[Content_Types].xml")
+        .ok()?
+        .read_to_string(&mut xml)
+        .ok()?;
+    let extension = path.rsplit_once('.').map(|(_, ext)| ext);
+    let mut default = None;
+    let mut reader = Reader::from_str(&xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(ref e) | Event::Start(ref e)) => match e.local_name().as_ref() {
+                b"Override" => {
+                    let name = get_attr(e, b"PartName").unwrap_or_default();
+                    if name.trim_start_matches('/').eq_ignore_ascii_case(path) {
+                        return get_attr(e, b"ContentType");
+                    }
+                }
+                b"Default" => {
+                    let ext = get_attr(e, b"Extension");
+                    if ext.is_some_and(|ext| Some(ext.to_ascii_lowercase().as_str()) == extension) {
+                        default = get_attr(e, b"ContentType");
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    default
+}
+
 /// Normalize a ZIP path by resolving `..` segments.
 ///
 /// `"ppt/slides/../media/image1.png"` → `"ppt/media/image1.png"`
@@ -180,6 +250,20 @@ pub(crate) fn rels_path(part_path: &str) -> String {
 mod tests {
     use super::*;
 
+    // ── get_attr / unescaped_attr ────────────────────────────────
+
+    #[test]
+    fn unescaped_attr_decodes_entities() {
+        let mut reader = Reader::from_str(r#"<sheet name="Q&amp;A &lt;draft&gt;" r:id="rId1"/>"#);
+        let Ok(Event::Empty(e)) = reader.read_event() else {
+            panic!("expected an empty element");
+        };
+        assert_eq!(get_attr(&e, b"name").unwrap(), "Q&amp;A &lt;draft&gt;");
+        assert_eq!(unescaped_attr(&e, b"name").unwrap(), "Q&A <draft>");
+        assert_eq!(unescaped_attr(&e, b"r:id").unwrap(), "rId1");
+        assert!(unescaped_attr(&e, b"state").is_none());
+    }
+
     // ── parse_rels_xml ───────────────────────────────────────────
 
     #[test]
@@ -216,7 +300,7 @@ mod tests {
         assert_eq!(rels.get("rId1").unwrap(), "https://example.com");
     }
 
-    // ── parse_image_rels_xml ─────────────────────────────────────
+    // ── parse_typed_rels_xml ─────────────────────────────────────
 
     #[test]
     fn parse_image_rels_basic() {
@@ -227,7 +311,7 @@ mod tests {
   <Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/image2.jpeg"/>
   <Relationship Id="rId4" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com" TargetMode="External"/>
 </Relationships>"#;
-        let rels = parse_image_rels_xml(xml);
+        let rels = parse_typed_rels_xml(xml, "/image");
         assert_eq!(rels.len(), 2);
         assert_eq!(rels.get("rId2").unwrap(), "media/image1.png");
         assert_eq!(rels.get("rId3").unwrap(), "media/image2.jpeg");
@@ -241,7 +325,7 @@ mod tests {
 <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
   <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com" TargetMode="External"/>
 </Relationships>"#;
-        let rels = parse_image_rels_xml(xml);
+        let rels = parse_typed_rels_xml(xml, "/image");
         assert!(rels.is_empty());
     }
 
@@ -268,6 +352,21 @@ mod tests {
         assert_eq!(rels_path("sheet1.xml"), "_rels/sheet1.xml.rels");
     }
 
+    // ── part_path ────────────────────────────────────────────────
+
+    #[test]
+    fn part_path_relative_and_absolute() {
+        assert_eq!(
+            part_path("word", "media/image1.png"),
+            "word/media/image1.png"
+        );
+        assert_eq!(
+            part_path("word", "/customXml/item1.xml"),
+            "customXml/item1.xml"
+        );
+        assert_eq!(part_path("", "word/document2.xml"), "word/document2.xml");
+    }
+
     // ── normalize_zip_path ────────────────────────────────────────
 
     #[test]
@@ -0,0 +1,202 @@
+//! Differential comparison against reference extractors.
+//!
+//! Runs `batdoc -p` and a reference tool over every file in a fixture
+//! corpus and reports how much of the reference text batdoc recovers:
+//!
+//! | Extension | Reference                              |
+//! |-----------|----------------------------------------|
+//! | `.doc`    | `catdoc`                               |
+//! | `.xls`    | `xls2csv` (from catdoc)                |
+//! | `.pdf`    | `pdftotext` (poppler)                  |
+//! | `.docx`   | `python3` with the `python-docx` package |
+//!
+//! Coverage is the share of the reference's words (as a multiset) that
+//! also appear in batdoc's output; word order and layout are ignored, so
+//! the number tracks extraction completeness rather than formatting.
+//!
+//! The harness needs a corpus and the reference tools, so it is ignored by
+//! default:
+//!
+//! ```text
+//! BATDOC_FIXTURES=~/corpus cargo test --test differential -- --ignored --nocapture
+//! ```
+//!
+//! Files without an available reference tool are skipped. Set
+//! `BATDOC_MIN_COVERAGE` (a percentage) to fail when any file falls below
+//! it.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Python snippet that dumps paragraphs and table cells of a .docx file.
+const PYTHON_DOCX_DUMP: &str = "\
+import sys, docx
+d = docx.Document(sys.argv[1])
+for p in d.paragraphs:
+    print(p.text)
+for t in d.tables:
+    for r in t.rows:
+        print('\\t'.join(c.text for c in r.cells))
+";
+
+/// Reference extractor command for a file, by extension.
+fn reference_command(path: &Path) -> Option<Command> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    let mut cmd = match ext.as_str() {
+        "doc" => Command::new("catdoc"),
+        "xls" => Command::new("xls2csv"),
+        "pdf" => {
+            let mut cmd = Command::new("pdftotext");
+            cmd.args(["-enc", "UTF-8", "-q"]);
+            cmd.arg(path).arg("-");
+            return Some(cmd);
+        }
+        "docx" => {
+            let mut cmd = Command::new("python3");
+            cmd.args(["-c", PYTHON_DOCX_DUMP]);
+            cmd
+        }
+        _ => return None,
+    };
+    cmd.arg(path);
+    Some(cmd)
+}
+
+/// Run a command, returning its stdout if it succeeded.
+fn run(mut cmd: Command) -> Option<String> {
+    let out = cmd.output().ok()?;
+    out.status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+/// Lowercased alphanumeric words, counted.
+fn word_counts(text: &str) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        *counts.entry(word.to_lowercase()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Fraction of `reference` words (with multiplicity) found in `actual`,
+/// and up to `limit` of the most frequent missing words.
+fn coverage(reference: &str, actual: &str, limit: usize) -> (f64, Vec<String>) {
+    let want = word_counts(reference);
+    let have = word_counts(actual);
+
+    let total: usize = want.values().sum();
+    if total == 0 {
+        return (1.0, Vec::new());
+    }
+
+    let mut found = 0;
+    let mut missing: Vec<(&String, usize)> = Vec::new();
+    for (word, &n) in &want {
+        let got = have.get(word).copied().unwrap_or(0);
+        found += n.min(got);
+        if got < n {
+            missing.push((word, n - got));
+        }
+    }
+    missing.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    #[allow(clippy::cast_precision_loss)] // word counts, reporting only
+    let ratio = found as f64 / total as f64;
+    let missing = missing
+        .into_iter()
+        .take(limit)
+        .map(|(w, _)| w.clone())
+        .collect();
+    (ratio, missing)
+}
+
+/// All regular files under `dir`, sorted for a stable report.
+fn corpus_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+#[test]
+#[ignore = "needs a fixture corpus (BATDOC_FIXTURES) and reference tools"]
+fn compare_with_reference_tools() {
+    let dir = std::env::var_os("BATDOC_FIXTURES")
+        .map_or_else(|| PathBuf::from("tests/fixtures"), PathBuf::from);
+    let min_coverage: Option<f64> = std::env::var("BATDOC_MIN_COVERAGE")
+        .ok()
+        .map(|v| v.parse().expect("BATDOC_MIN_COVERAGE must be a number"));
+
+    let mut below = Vec::new();
+    let mut compared = 0;
+    for path in corpus_files(&dir) {
+        let Some(reference) = reference_command(&path).and_then(run) else {
+            continue;
+        };
+        let mut batdoc = Command::new(env!("CARGO_BIN_EXE_batdoc"));
+        batdoc.arg("-p").arg("--no-config").arg(&path);
+        let actual = run(batdoc).unwrap_or_default();
+
+        let (ratio, missing) = coverage(&reference, &actual, 8);
+        let percent = ratio * 100.0;
+        println!(
+            "{percent:6.1}%  {}{}",
+            path.display(),
+            if missing.is_empty() {
+                String::new()
+            } else {
+                format!("  missing: {}", missing.join(" "))
+            }
+        );
+        compared += 1;
+        if min_coverage.is_some_and(|min| percent < min) {
+            below.push(path.display().to_string());
+        }
+    }
+
+    println!("{compared} file(s) compared");
+    assert!(
+        below.is_empty(),
+        "below BATDOC_MIN_COVERAGE: {}",
+        below.join(", ")
+    );
+}
+
+// ── coverage ─────────────────────────────────────────────────────
+
+#[test]
+fn coverage_counts_multiplicity() {
+    let (ratio, missing) = coverage("a a b c", "A b", 10);
+    assert!((ratio - 0.5).abs() < f64::EPSILON);
+    assert_eq!(missing, vec!["a", "c"]);
+}
+
+#[test]
+fn coverage_ignores_layout() {
+    let (ratio, missing) = coverage("Name\tAge\nAlice 30", "| Name | Age |\n| Alice | 30 |", 10);
+    assert!((ratio - 1.0).abs() < f64::EPSILON);
+    assert!(missing.is_empty());
+}
+
+#[test]
+fn coverage_of_empty_reference() {
+    assert!((coverage("", "anything", 10).0 - 1.0).abs() < f64::EPSILON);
+}